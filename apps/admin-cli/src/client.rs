@@ -0,0 +1,61 @@
+use std::{error::Error, sync::Arc, time::Duration};
+
+use backend::{
+    frame::{decode_legacy_reply, Request, RequestData},
+    model::state::FIRST_REQUEST_ID,
+};
+use prost::Message;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct AdminClient {
+    stream: TcpStream,
+    next_request_id: u32,
+}
+
+impl AdminClient {
+    pub async fn connect(host: &str, port: u16) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self {
+            stream: TcpStream::connect((host, port)).await?,
+            next_request_id: FIRST_REQUEST_ID,
+        })
+    }
+
+    /// Send one request and wait for the response carrying the same request
+    /// id. Push events that arrive in between are skipped.
+    pub async fn call<T: Message + Default>(
+        &mut self,
+        data: RequestData,
+    ) -> Result<T, Box<dyn Error + Send + Sync>> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let bytes = Request::new(request_id, Arc::new(data)).to_bytes()?;
+        self.stream.write_all(&bytes).await?;
+        match timeout(RESPONSE_TIMEOUT, self.read_response(request_id)).await {
+            // Admin requests never connect, so replies stay in the legacy
+            // frames that carry no kind
+            Ok(payload) => Ok(decode_legacy_reply(&payload?)??),
+            Err(_) => Err("no response from server, is the admin interface enabled?".into()),
+        }
+    }
+
+    async fn read_response(
+        &mut self,
+        request_id: u32,
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        loop {
+            let state = self.stream.read_u32_le().await?;
+            let len = self.stream.read_u32_le().await?;
+            let mut payload = vec![0; len as usize];
+            self.stream.read_exact(&mut payload).await?;
+            if state == request_id {
+                return Ok(payload);
+            }
+        }
+    }
+}
@@ -0,0 +1,306 @@
+mod client;
+mod table;
+
+use std::error::Error;
+
+use backend::{
+    frame::RequestData,
+    model::{
+        admin::{
+            announce::{AnnounceRequest, AnnounceResponse},
+            ban::{BanRequest, BanResponse},
+            close_game::{CloseGameRequest, CloseGameResponse},
+            handoff::{HandoffRequest, HandoffResponse},
+            inspect::{inspect_request, InspectRequest, InspectResponse},
+            issue_token::{IssueTokenRequest, IssueTokenResponse},
+            kick::{KickRequest, KickResponse},
+            list_players::{ListPlayersRequest, ListPlayersResponse},
+            maintenance::{MaintenanceRequest, MaintenanceResponse},
+            reload::{ReloadRequest, ReloadResponse},
+            revoke_token::{RevokeTokenRequest, RevokeTokenResponse},
+        },
+        control::connect::Scope,
+    },
+};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use client::AdminClient;
+
+#[derive(Debug, Parser)]
+#[command(name = "admin-cli", about = "Operate a running Letter Legend server")]
+struct Cli {
+    #[arg(long, env = "HOST", default_value = "127.0.0.1")]
+    host: String,
+    #[arg(long, env = "PORT", default_value_t = 45678)]
+    port: u16,
+    #[arg(long, env = "ADMIN_TOKEN", hide_env_values = true)]
+    token: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List online players
+    List,
+    /// Force-disconnect a player
+    Kick { player_id: u32 },
+    /// Refuse future connections using this name
+    Ban { name: String },
+    /// Push a message to every connected client
+    #[command(alias = "broadcast")]
+    Announce {
+        message: String,
+        /// Also greet every client that connects from now on with it
+        #[arg(long)]
+        motd: bool,
+    },
+    /// Refuse new connections while the server is under maintenance
+    Maintenance {
+        #[arg(value_enum)]
+        state: Toggle,
+        #[arg(long, default_value = "")]
+        message: String,
+    },
+    /// Show the state of a lobby or a game
+    Inspect {
+        #[command(subcommand)]
+        target: Target,
+    },
+    /// End a stuck game and send its players back
+    CloseGame { game_id: u32 },
+    /// Load the config and dictionaries again, like sending the server SIGHUP
+    Reload,
+    /// Hand out an API token a bot or service connects with as `name`
+    IssueToken {
+        name: String,
+        #[arg(value_enum, required = true)]
+        scopes: Vec<TokenScope>,
+    },
+    /// Stop an API token from opening new sessions
+    RevokeToken { api_token: String },
+    /// Move the lobbies that aren't in a game to the instance `node`, so
+    /// this one can be drained
+    Handoff { node: u16 },
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+enum Toggle {
+    On,
+    Off,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum TokenScope {
+    Play,
+    Spectate,
+    Admin,
+    Observe,
+}
+
+#[derive(Debug, Subcommand)]
+enum Target {
+    Lobby { id: u32 },
+    Game { id: u32 },
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+    let mut client = AdminClient::connect(&cli.host, cli.port).await?;
+    let token = cli.token;
+    match cli.command {
+        Command::List => {
+            let res: ListPlayersResponse = client
+                .call(RequestData::AdminListPlayers(ListPlayersRequest { token }))
+                .await?;
+            let rows: Vec<Vec<String>> = res
+                .players
+                .iter()
+                .map(|player| {
+                    vec![
+                        player.id.to_string(),
+                        player.name.clone(),
+                        optional_id(player.lobby_id),
+                        optional_id(player.game_id),
+                    ]
+                })
+                .collect();
+            print!("{}", table::render(&["ID", "NAME", "LOBBY", "GAME"], &rows));
+        }
+        Command::Kick { player_id } => {
+            let res: KickResponse = client
+                .call(RequestData::AdminKick(KickRequest { token, player_id }))
+                .await?;
+            check(res.success, "kick")?;
+            println!("kicked player {}", player_id);
+        }
+        Command::Ban { name } => {
+            let res: BanResponse = client
+                .call(RequestData::AdminBan(BanRequest {
+                    token,
+                    name: name.clone(),
+                }))
+                .await?;
+            check(res.success, "ban")?;
+            println!("banned {}", name);
+        }
+        Command::Announce { message, motd } => {
+            let res: AnnounceResponse = client
+                .call(RequestData::AdminAnnounce(AnnounceRequest {
+                    token,
+                    message,
+                    set_motd: motd,
+                }))
+                .await?;
+            check(res.success, "announce")?;
+            println!("announcement sent to {} players", res.recipients);
+        }
+        Command::Maintenance { state, message } => {
+            let res: MaintenanceResponse = client
+                .call(RequestData::AdminMaintenance(MaintenanceRequest {
+                    token,
+                    enabled: matches!(state, Toggle::On),
+                    message,
+                }))
+                .await?;
+            check(res.success, "maintenance")?;
+            println!(
+                "maintenance mode {}",
+                match res.enabled {
+                    true => "on",
+                    false => "off",
+                }
+            );
+        }
+        Command::Inspect { target } => {
+            let target = match target {
+                Target::Lobby { id } => inspect_request::Target::LobbyId(id),
+                Target::Game { id } => inspect_request::Target::GameId(id),
+            };
+            let res: InspectResponse = client
+                .call(RequestData::AdminInspect(InspectRequest {
+                    token,
+                    target: Some(target),
+                }))
+                .await?;
+            check(res.success, "inspect")?;
+            print_inspect(res);
+        }
+        Command::CloseGame { game_id } => {
+            let res: CloseGameResponse = client
+                .call(RequestData::AdminCloseGame(CloseGameRequest {
+                    token,
+                    game_id,
+                }))
+                .await?;
+            check(res.success, "close-game")?;
+            println!("closed game {}", game_id);
+        }
+        Command::Reload => {
+            let res: ReloadResponse = client
+                .call(RequestData::AdminReload(ReloadRequest { token }))
+                .await?;
+            check(res.success, "reload")?;
+            println!("reload requested, see the server log for the outcome");
+        }
+        Command::IssueToken { name, scopes } => {
+            let scopes = scopes.iter().fold(0, |bits, scope| {
+                bits | match scope {
+                    TokenScope::Play => Scope::Play as u64,
+                    TokenScope::Spectate => Scope::Spectate as u64,
+                    TokenScope::Admin => Scope::Admin as u64,
+                    TokenScope::Observe => Scope::Observe as u64,
+                }
+            });
+            let res: IssueTokenResponse = client
+                .call(RequestData::AdminIssueToken(IssueTokenRequest {
+                    token,
+                    name,
+                    scopes,
+                }))
+                .await?;
+            check(res.success, "issue-token")?;
+            println!("{}", res.api_token);
+        }
+        Command::RevokeToken { api_token } => {
+            let res: RevokeTokenResponse = client
+                .call(RequestData::AdminRevokeToken(RevokeTokenRequest {
+                    token,
+                    api_token,
+                }))
+                .await?;
+            check(res.success, "revoke-token")?;
+            match res.revoked {
+                true => println!("revoked"),
+                false => println!("no such token"),
+            }
+        }
+        Command::Handoff { node } => {
+            let res: HandoffResponse = client
+                .call(RequestData::AdminHandoff(HandoffRequest {
+                    token,
+                    node: node as u32,
+                }))
+                .await?;
+            check(res.success, "handoff")?;
+            println!(
+                "handed off {} lobbies, redirected {} players",
+                res.lobbies, res.players
+            );
+        }
+    }
+    Ok(())
+}
+
+fn check(success: bool, command: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    match success {
+        true => Ok(()),
+        false => Err(format!("{} failed", command).into()),
+    }
+}
+
+fn optional_id(id: Option<u32>) -> String {
+    match id {
+        Some(id) => id.to_string(),
+        None => String::from("-"),
+    }
+}
+
+fn print_inspect(res: InspectResponse) {
+    if let Some(lobby) = res.lobby {
+        println!("lobby {}", lobby.id);
+        println!("max players: {}", lobby.max_players);
+        if let Some(leader) = lobby.leader {
+            println!("leader: {} ({})", leader.name, leader.id);
+        }
+        let rows: Vec<Vec<String>> = lobby
+            .members
+            .iter()
+            .filter_map(|member| {
+                let player = member.player.as_ref()?;
+                Some(vec![
+                    player.id.to_string(),
+                    player.name.clone(),
+                    member.ready.to_string(),
+                ])
+            })
+            .collect();
+        print!("{}", table::render(&["ID", "NAME", "READY"], &rows));
+    }
+    if let Some(game) = res.game {
+        println!("game {}", game.id);
+        println!("turn: {}", game.turn);
+        println!("tiles on board: {}", game.tiles);
+        if let Some(current) = game.current_player {
+            println!("current player: {} ({})", current.name, current.id);
+        }
+        let rows: Vec<Vec<String>> = game
+            .players
+            .iter()
+            .map(|player| vec![player.id.to_string(), player.name.clone()])
+            .collect();
+        print!("{}", table::render(&["ID", "NAME"], &rows));
+    }
+}
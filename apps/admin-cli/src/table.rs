@@ -0,0 +1,58 @@
+/// Render rows as a plain text table with a header and aligned columns.
+pub fn render(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    out.push_str(&render_row(
+        &headers.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+        &widths,
+    ));
+    out.push_str(&render_row(
+        &widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>(),
+        &widths,
+    ));
+    for row in rows {
+        out.push_str(&render_row(row, &widths));
+    }
+    out
+}
+
+fn render_row(cells: &[String], widths: &[usize]) -> String {
+    let line = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+        .collect::<Vec<_>>()
+        .join("  ");
+    format!("{}\n", line.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_with_rows_should_align_columns() {
+        let table = render(
+            &["ID", "NAME"],
+            &[
+                vec![String::from("0"), String::from("alice")],
+                vec![String::from("12"), String::from("bob")],
+            ],
+        );
+        assert_eq!(table, "ID  NAME\n--  -----\n0   alice\n12  bob\n");
+    }
+
+    #[test]
+    fn render_without_rows_should_only_print_header() {
+        let table = render(&["ID", "NAME"], &[]);
+        assert_eq!(table, "ID  NAME\n--  ----\n");
+    }
+}
@@ -0,0 +1,174 @@
+//! Compares the sharded player/lobby maps against the single `Mutex<HashMap>`
+//! they replaced, with every thread working on its own players.
+//!
+//! Run with `cargo bench --bench concurrency`.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use backend::{
+    config::{GameConfig, SeasonConfig},
+    lobby::lobby::Lobby,
+    player::Player,
+    service::{
+        broadcast_service::BroadcastService, dictionary_service::DictionaryService,
+        game_service::GameService, leaderboard_service::LeaderboardService,
+        lobby_service::LobbyService, player_service::PlayerService, season_service::SeasonService,
+        stats_service::StatsService,
+    },
+    storage::memory::MemoryStorage,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tokio::sync::mpsc;
+
+const THREADS: [u32; 3] = [1, 4, 8];
+const LOOKUPS: u32 = 10_000;
+
+fn new_player(id: u32) -> Arc<Player> {
+    Arc::new(Player::new(id, format!("player{}", id)))
+}
+
+fn new_player_service() -> PlayerService {
+    let dictionary = env::temp_dir().join("letterlegend-bench-wordlist.txt");
+    fs::write(&dictionary, "the\n").unwrap();
+    let config = GameConfig {
+        dictionary_path: dictionary.to_string_lossy().into_owned(),
+        ..GameConfig::default()
+    };
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let dictionary_service = runtime.block_on(DictionaryService::new(&config)).unwrap();
+    let storage = Arc::new(MemoryStorage::new());
+    let game_service = GameService::new(
+        &config,
+        Arc::new(dictionary_service),
+        Arc::new(StatsService::new(storage.clone())),
+        Arc::new(LeaderboardService::new(storage.clone())),
+        Arc::new(SeasonService::new(
+            &SeasonConfig::default(),
+            storage.clone(),
+        )),
+        Arc::new(BroadcastService::new()),
+        storage,
+    );
+    PlayerService::new(Arc::new(LobbyService::new()), Arc::new(game_service))
+}
+
+fn online_players(c: &mut Criterion) {
+    let mut group = c.benchmark_group("online_players");
+    for threads in THREADS {
+        let map = Mutex::new(HashMap::<u32, Arc<Player>>::new());
+        group.bench_with_input(
+            BenchmarkId::new("mutex", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    thread::scope(|s| {
+                        for id in 0..threads {
+                            let map = &map;
+                            s.spawn(move || {
+                                map.lock().unwrap().insert(id, new_player(id));
+                                for _ in 0..LOOKUPS {
+                                    assert!(map.lock().unwrap().get(&id).cloned().is_some());
+                                }
+                                map.lock().unwrap().remove(&id);
+                            });
+                        }
+                    })
+                })
+            },
+        );
+
+        let service = new_player_service();
+        group.bench_with_input(
+            BenchmarkId::new("sharded", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    thread::scope(|s| {
+                        for id in 0..threads {
+                            let service = &service;
+                            s.spawn(move || {
+                                let (sender, _) = mpsc::channel(1);
+                                let player =
+                                    service.add_player(id, format!("player{}", id), sender);
+                                for _ in 0..LOOKUPS {
+                                    assert!(service.get_player(id).is_some());
+                                }
+                                service.remove_player(player).unwrap();
+                            });
+                        }
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn lobbies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lobbies");
+    for threads in THREADS {
+        let next_id = Mutex::new(0);
+        let map = Mutex::new(HashMap::<u32, Arc<Lobby>>::new());
+        group.bench_with_input(
+            BenchmarkId::new("mutex", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    thread::scope(|s| {
+                        for id in 0..threads {
+                            let (next_id, map) = (&next_id, &map);
+                            s.spawn(move || {
+                                let lobby_id = {
+                                    let mut next_id = next_id.lock().unwrap();
+                                    let lobby = Arc::new(Lobby::new(*next_id, 4, new_player(id)));
+                                    map.lock().unwrap().insert(*next_id, lobby);
+                                    *next_id += 1;
+                                    *next_id - 1
+                                };
+                                for _ in 0..LOOKUPS {
+                                    assert!(map.lock().unwrap().get(&lobby_id).cloned().is_some());
+                                }
+                                map.lock().unwrap().remove(&lobby_id);
+                            });
+                        }
+                    })
+                })
+            },
+        );
+
+        let service = LobbyService::new();
+        group.bench_with_input(
+            BenchmarkId::new("sharded", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    thread::scope(|s| {
+                        for id in 0..threads {
+                            let service = &service;
+                            s.spawn(move || {
+                                let leader = new_player(id);
+                                let lobby = service.create_lobby(leader.clone(), 4).unwrap();
+                                for _ in 0..LOOKUPS {
+                                    assert!(service.get_lobby(lobby.get_id()).is_some());
+                                }
+                                service.remove_player_from_lobby(leader).unwrap();
+                            });
+                        }
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, online_players, lobbies);
+criterion_main!(benches);
@@ -0,0 +1,215 @@
+//! Request handling from the router down through the services: dispatching a
+//! request to its controller, placing tiles, and players joining and leaving
+//! a lobby from several threads at once.
+//!
+//! Run with `cargo bench --bench hot_path`.
+
+use std::{env, fs, sync::Arc, thread};
+
+use backend::{
+    config::{GameConfig, SeasonConfig},
+    controller::{
+        control::heartbeat::HeartbeatController, controller::Controller,
+        game::set_tile::SetTileController,
+    },
+    frame::{Frame, Request, RequestData, ResponseData},
+    model::{
+        control::{connect::Scope, heartbeat::HeartbeatRequest},
+        game::set_tile::SetTileRequest,
+    },
+    operation::Operation,
+    player::Player,
+    router::{RequestContext, Router},
+    service::{
+        broadcast_service::BroadcastService, dictionary_service::DictionaryService,
+        game_service::GameService, leaderboard_service::LeaderboardService,
+        lobby_service::LobbyService, player_service::PlayerService, season_service::SeasonService,
+        stats_service::StatsService,
+    },
+    storage::memory::MemoryStorage,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tokio::{
+    runtime::Runtime,
+    sync::mpsc::{self, Sender},
+};
+
+/// Players joining the lobby at once, its leader takes the last of the 8
+/// seats.
+const JOINERS: [u32; 3] = [1, 3, 7];
+const ROUNDS: u32 = 1_000;
+
+struct Services {
+    runtime: Runtime,
+    lobby_service: Arc<LobbyService>,
+    game_service: Arc<GameService>,
+    player_service: Arc<PlayerService>,
+}
+
+fn new_services() -> Services {
+    let dictionary = env::temp_dir().join("letterlegend-bench-wordlist.txt");
+    fs::write(&dictionary, "the\n").unwrap();
+    let config = GameConfig {
+        dictionary_path: dictionary.to_string_lossy().into_owned(),
+        ..GameConfig::default()
+    };
+    // Broadcasts are spawned onto the runtime, so it has to keep running
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let dictionary_service = runtime.block_on(DictionaryService::new(&config)).unwrap();
+    let storage = Arc::new(MemoryStorage::new());
+    let game_service = Arc::new(GameService::new(
+        &config,
+        Arc::new(dictionary_service),
+        Arc::new(StatsService::new(storage.clone())),
+        Arc::new(LeaderboardService::new(storage.clone())),
+        Arc::new(SeasonService::new(
+            &SeasonConfig::default(),
+            storage.clone(),
+        )),
+        Arc::new(BroadcastService::new()),
+        storage,
+    ));
+    let lobby_service = Arc::new(LobbyService::new());
+    let player_service = Arc::new(PlayerService::new(
+        lobby_service.clone(),
+        game_service.clone(),
+    ));
+    Services {
+        runtime,
+        lobby_service,
+        game_service,
+        player_service,
+    }
+}
+
+/// A sender whose receiver is gone, so anything pushed to the player is
+/// dropped straight away.
+fn closed_sender() -> Sender<Frame> {
+    mpsc::channel(1).0
+}
+
+fn router_dispatch(c: &mut Criterion) {
+    let services = new_services();
+    let _runtime = services.runtime.enter();
+    services
+        .player_service
+        .add_player(0, String::from("player0"), closed_sender());
+    let mut router = Router::new();
+    router
+        .register(
+            Operation::Heartbeat,
+            Box::new(HeartbeatController::new(services.player_service.clone())),
+        )
+        .unwrap();
+    let sender = closed_sender();
+
+    let mut group = c.benchmark_group("router_dispatch");
+    for (name, data) in [
+        (
+            "heartbeat",
+            RequestData::Heartbeat(HeartbeatRequest::default()),
+        ),
+        ("list_supported_opcodes", RequestData::ListSupportedOpcodes),
+    ] {
+        let data = Arc::new(data);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                router
+                    .route(
+                        Request::new(0, data.clone()),
+                        RequestContext::new(0, sender.clone()),
+                        Some(Scope::INTERACTIVE),
+                    )
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn set_tile(c: &mut Criterion) {
+    let services = new_services();
+    let _runtime = services.runtime.enter();
+    let player = services
+        .player_service
+        .add_player(0, String::from("player0"), closed_sender());
+    let lobby = services
+        .lobby_service
+        .create_lobby(player.clone(), 4)
+        .unwrap();
+    lobby.get_player(player.id).unwrap().set_ready(true);
+    let game =
+        GameService::start_game(services.game_service.clone(), player.clone(), lobby).unwrap();
+    let center = game.get_rules().board_size / 2;
+    let controller = SetTileController::new(
+        services.player_service.clone(),
+        services.game_service.clone(),
+    );
+    let set_tile = |card_index| {
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::SetTile(SetTileRequest {
+                    x: center as u32,
+                    y: center as u32,
+                    card_index,
+                })),
+            ),
+            RequestContext::new(0, closed_sender()),
+        )
+    };
+
+    let mut group = c.benchmark_group("set_tile");
+    group.bench_function("valid", |b| {
+        b.iter(|| {
+            assert!(matches!(set_tile(0), Ok(ResponseData::SetTile(_))));
+            services
+                .game_service
+                .remove_tile(game.clone(), &player, center, center)
+                .unwrap();
+        })
+    });
+    // The tile stays down, so each attempt to place another card on it is
+    // turned away by validation
+    set_tile(0).unwrap();
+    group.bench_function("rejected", |b| b.iter(|| assert!(set_tile(1).is_err())));
+    group.finish();
+}
+
+fn lobby_join_leave(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lobby_join_leave");
+    for joiners in JOINERS {
+        let service = LobbyService::new();
+        let leader = Arc::new(Player::new(u32::MAX, String::from("leader")));
+        let lobby = service.create_lobby(leader, 8).unwrap();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(joiners),
+            &joiners,
+            |b, &joiners| {
+                b.iter(|| {
+                    thread::scope(|s| {
+                        for id in 0..joiners {
+                            let (service, lobby) = (&service, &lobby);
+                            s.spawn(move || {
+                                let player = Arc::new(Player::new(id, format!("player{}", id)));
+                                for _ in 0..ROUNDS {
+                                    service
+                                        .join_lobby(player.clone(), lobby.clone(), false)
+                                        .unwrap();
+                                    service.remove_player_from_lobby(player.clone()).unwrap();
+                                }
+                            });
+                        }
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, router_dispatch, set_tile, lobby_join_leave);
+criterion_main!(benches);
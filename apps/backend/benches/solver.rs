@@ -0,0 +1,93 @@
+//! Move generation: compiling a lexicon or loading a compiled one, and
+//! finding every placement for a hand on an empty board and on one a few
+//! words into a game.
+//!
+//! Run with `cargo bench --bench solver`.
+
+use std::sync::Arc;
+
+use backend::{
+    game::{
+        board::{Board, BOARD_SIZE},
+        card::Card,
+        rules::GameRules,
+        tile::Tile,
+    },
+    player::Player,
+    solver::{self, Lexicon},
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+const WORDS: usize = 50_000;
+/// Most common first, so words come out with a plausible mix of letters.
+const LETTERS: &[u8] = b"etaoinshrdlucmfwypvbgkjqxz";
+
+/// Made-up words, seeded so every run searches the same list.
+fn words() -> Vec<String> {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    (0..WORDS)
+        .map(|_| {
+            let len = rng.gen_range(2..=8);
+            (0..len)
+                .map(|_| {
+                    // The smaller of two picks leans towards common letters
+                    let i = rng
+                        .gen_range(0..LETTERS.len())
+                        .min(rng.gen_range(0..LETTERS.len()));
+                    LETTERS[i] as char
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn hand() -> Vec<Card> {
+    "etaoinsr".chars().map(Card::new).collect()
+}
+
+/// A board with a few crossing words through the centre.
+fn midgame_board(rules: &GameRules) -> Board {
+    let owner = Arc::new(Player::new(0, String::from("bench")));
+    let mut board = Board::new();
+    let centre = rules.board_size / 2;
+    let mut place = |word: &str, x: usize, y: usize, across: bool| {
+        for (i, char) in word.chars().enumerate() {
+            let (x, y) = match across {
+                true => (x + i, y),
+                false => (x, y - i),
+            };
+            board.tiles[BOARD_SIZE - 1 - y][x] = Some(Tile::new(char, owner.clone(), 1));
+        }
+    };
+    place("stone", centre - 2, centre, true);
+    place("train", centre, centre + 2, false);
+    place("near", centre - 3, centre - 2, true);
+    board
+}
+
+fn lexicon(c: &mut Criterion) {
+    let words = words();
+    let mut group = c.benchmark_group("lexicon");
+    group.bench_function("compile", |b| b.iter(|| Lexicon::new(&words)));
+    let bytes = Lexicon::new(&words).to_bytes();
+    group.bench_function("load", |b| b.iter(|| Lexicon::from_bytes(&bytes).unwrap()));
+    group.finish();
+}
+
+fn find_placements(c: &mut Criterion) {
+    let lexicon = Lexicon::new(words());
+    let rules = GameRules::default();
+    let boards = [("empty", Board::new()), ("midgame", midgame_board(&rules))];
+    let mut group = c.benchmark_group("find_placements");
+    for (name, board) in boards {
+        group.bench_function(name, |b| {
+            b.iter(|| solver::find_placements(&board, &rules, &hand(), &lexicon))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lexicon, find_placements);
+criterion_main!(benches);
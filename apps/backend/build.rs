@@ -4,6 +4,13 @@ fn main() -> Result<()> {
         &[
             "src/proto/player/player.proto",
             "src/proto/player/players.proto",
+            "src/proto/player/stats.proto",
+            "src/proto/player/report.proto",
+            "src/proto/player/block.proto",
+            "src/proto/player/mute.proto",
+            "src/proto/player/profile.proto",
+            "src/proto/leaderboard/daily.proto",
+            "src/proto/leaderboard/season.proto",
         ],
         &["src/proto/"],
     )?;
@@ -12,6 +19,11 @@ fn main() -> Result<()> {
             "src/proto/control/connect.proto",
             "src/proto/control/disconnect.proto",
             "src/proto/control/heartbeat.proto",
+            "src/proto/control/time_sync.proto",
+            "src/proto/control/preferences.proto",
+            "src/proto/control/notice.proto",
+            "src/proto/control/opcodes.proto",
+            "src/proto/control/server_info.proto",
         ],
         &["src/proto/"],
     )?;
@@ -20,15 +32,20 @@ fn main() -> Result<()> {
             "src/proto/lobby/broadcast.proto",
             "src/proto/lobby/create.proto",
             "src/proto/lobby/join.proto",
+            "src/proto/lobby/kick.proto",
             "src/proto/lobby/list.proto",
             "src/proto/lobby/lobby.proto",
             "src/proto/lobby/quit.proto",
             "src/proto/lobby/ready.proto",
+            "src/proto/lobby/rules.proto",
+            "src/proto/lobby/update_settings.proto",
+            "src/proto/lobby/upload_dictionary.proto",
         ],
         &["src/proto/"],
     )?;
     prost_build::compile_protos(
         &[
+            "src/proto/game/analyze.proto",
             "src/proto/game/board.proto",
             "src/proto/game/start.proto",
             "src/proto/game/tile.proto",
@@ -38,13 +55,56 @@ fn main() -> Result<()> {
             "src/proto/game/broadcast.proto",
             "src/proto/game/finish_turn.proto",
             "src/proto/game/get_new_card.proto",
+            "src/proto/game/hint.proto",
             "src/proto/game/set_tile.proto",
+            "src/proto/game/spectate.proto",
+            "src/proto/game/subscribe.proto",
+            "src/proto/game/snapshot.proto",
+            "src/proto/game/resync.proto",
+            "src/proto/game/bag_info.proto",
+            "src/proto/game/exchange.proto",
+            "src/proto/game/vote_kick.proto",
             "src/proto/game/cancel.proto",
+            "src/proto/game/coop.proto",
+            "src/proto/game/draw.proto",
             "src/proto/game/exit.proto",
+            "src/proto/game/export.proto",
+            "src/proto/game/pause.proto",
+            "src/proto/game/resign.proto",
+            "src/proto/game/resume.proto",
             "src/proto/game/words.proto",
         ],
         &["src/proto/"],
     )?;
+    prost_build::compile_protos(
+        &[
+            "src/proto/chat/send.proto",
+            "src/proto/chat/quick_chat.proto",
+            "src/proto/chat/broadcast.proto",
+        ],
+        &["src/proto/"],
+    )?;
+    prost_build::compile_protos(
+        &[
+            "src/proto/admin/announce.proto",
+            "src/proto/admin/ban.proto",
+            "src/proto/admin/close_game.proto",
+            "src/proto/admin/handoff.proto",
+            "src/proto/admin/inspect.proto",
+            "src/proto/admin/issue_token.proto",
+            "src/proto/admin/kick.proto",
+            "src/proto/admin/list_players.proto",
+            "src/proto/admin/maintenance.proto",
+            "src/proto/admin/reload.proto",
+            "src/proto/admin/revoke_token.proto",
+        ],
+        &["src/proto/"],
+    )?;
     prost_build::compile_protos(&["src/proto/error/error.proto"], &["src/proto/"])?;
+    prost_build::compile_protos(&["src/proto/frame/envelope.proto"], &["src/proto/"])?;
+    // Service code for the gRPC gateway, the messages it shares are the ones
+    // generated above
+    #[cfg(feature = "grpc")]
+    tonic_build::configure().compile(&["src/proto/gateway/gateway.proto"], &["src/proto/"])?;
     Ok(())
 }
@@ -0,0 +1,20 @@
+//! Arbitrary bytes from a client, in both protocol versions. Decoding must
+//! never panic, and a stream cut short of a frame it accepts must read as
+//! incomplete rather than malformed.
+
+#![no_main]
+
+use backend::frame::{Error, Frame, LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for version in [LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION] {
+        if let Ok((_, len)) = Frame::decode(data, version) {
+            assert!(len <= data.len());
+            assert!(matches!(
+                Frame::decode(&data[..len - 1], version),
+                Err(Error::Incomplete)
+            ));
+        }
+    }
+});
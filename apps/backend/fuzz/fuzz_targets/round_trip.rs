@@ -0,0 +1,28 @@
+//! Any request the server accepts encodes back to bytes that decode to the
+//! same request.
+
+#![no_main]
+
+use backend::frame::{Frame, LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for version in [LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION] {
+        let req = match Frame::decode(data, version) {
+            Ok((Frame::Request(req), _)) => req,
+            _ => continue,
+        };
+        let bytes = match version {
+            PROTOCOL_VERSION => req.to_envelope_bytes(),
+            _ => req.to_bytes(),
+        }
+        .expect("decoded request should encode");
+        match Frame::decode(&bytes, version) {
+            Ok((Frame::Request(decoded), len)) => {
+                assert_eq!(decoded, req);
+                assert_eq!(len, bytes.len());
+            }
+            other => panic!("re-encoded request failed to decode: {:?}", other),
+        }
+    }
+});
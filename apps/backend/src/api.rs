@@ -0,0 +1,530 @@
+//! JSON over HTTP for the web frontend: accounts, stats, match history and
+//! leaderboards. Requests are independent, so a page can be built without
+//! holding a game connection open.
+
+use std::{error::Error, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    headers::{authorization::Bearer, Authorization},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router, TypedHeader,
+};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tracing::info;
+
+use crate::{
+    game::game::current_day,
+    model::leaderboard::daily::LeaderboardEntry,
+    service::{
+        account_service::{AccountService, SESSION_TTL},
+        game_service::GameService,
+        stats_service::{HistoryFilter, MatchCursor, MatchRecord, PlayerStats},
+    },
+};
+
+const DEFAULT_MATCH_LIMIT: usize = 20;
+const MAX_MATCH_LIMIT: usize = 100;
+/// Set on a page of matches when there are older ones, to pass as `before`
+/// for the next page.
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+#[derive(Debug)]
+pub struct Api {
+    account_service: Arc<AccountService>,
+    game_service: Arc<GameService>,
+}
+
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorBody { error: self.1 })).into_response()
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for ApiError {
+    fn from(e: Box<dyn Error + Send + Sync>) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Credentials {
+    pub name: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountBody {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionBody {
+    pub token: String,
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WordScore {
+    pub word: String,
+    pub score: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatsBody {
+    pub name: String,
+    pub games_played: u32,
+    pub games_won: u32,
+    pub games_resigned: u32,
+    pub win_rate: f32,
+    pub total_score: u64,
+    pub average_score: f32,
+    pub tiles_placed: u64,
+    pub best_word: Option<WordScore>,
+    pub longest_word: Option<String>,
+}
+
+impl StatsBody {
+    fn new(name: String, stats: &PlayerStats) -> Self {
+        Self {
+            name,
+            games_played: stats.games_played,
+            games_won: stats.games_won,
+            games_resigned: stats.games_resigned,
+            win_rate: stats.win_rate(),
+            total_score: stats.total_score,
+            average_score: stats.average_score(),
+            tiles_placed: stats.tiles_placed,
+            best_word: stats.best_word.as_ref().map(|(word, score)| WordScore {
+                word: word.clone(),
+                score: *score,
+            }),
+            longest_word: stats.longest_word.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScoreBody {
+    pub name: String,
+    pub score: u32,
+}
+
+impl ScoreBody {
+    fn list(scores: &[(String, u32)]) -> Vec<Self> {
+        scores
+            .iter()
+            .map(|(name, score)| Self {
+                name: name.clone(),
+                score: *score,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MatchBody {
+    pub game_id: u32,
+    /// Seconds since the Unix epoch.
+    pub finished_at: u64,
+    /// Best first.
+    pub scores: Vec<ScoreBody>,
+    /// Players who forfeited, with their score when they did.
+    pub forfeited: Vec<ScoreBody>,
+    /// Players who resigned, with their score when they did.
+    pub resigned: Vec<ScoreBody>,
+    pub drawn: bool,
+    pub won: bool,
+}
+
+impl MatchBody {
+    fn new(account: &str, record: &MatchRecord) -> Self {
+        Self {
+            game_id: record.game_id,
+            finished_at: record.finished_at,
+            scores: ScoreBody::list(&record.scores),
+            forfeited: ScoreBody::list(&record.forfeited),
+            resigned: ScoreBody::list(&record.resigned),
+            drawn: record.drawn,
+            won: record.winners().any(|winner| winner == account),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RankBody {
+    pub rank: u32,
+    pub name: String,
+    pub score: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderboardBody {
+    pub day: u32,
+    pub entries: Vec<RankBody>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MatchesQuery {
+    limit: Option<usize>,
+    /// The cursor the previous page came with, unset for the newest games.
+    before: Option<String>,
+    /// Seconds since the Unix epoch, only games finished at or after it.
+    since: Option<u64>,
+    /// Seconds since the Unix epoch, only games finished before it.
+    until: Option<u64>,
+    /// Only games this player played in too.
+    opponent: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DailyQuery {
+    /// Days since 1970-01-01 UTC, unset for today.
+    day: Option<u32>,
+}
+
+impl Api {
+    pub fn new(account_service: Arc<AccountService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            account_service,
+            game_service,
+        }
+    }
+
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/accounts", post(register))
+            .route("/sessions", post(login))
+            .route("/me", get(me))
+            .route("/players/:name/stats", get(player_stats))
+            .route("/players/:name/matches", get(player_matches))
+            .route("/leaderboards/daily", get(daily_leaderboard))
+            .with_state(self)
+    }
+
+    /// Answer HTTP requests on `listener` until it fails.
+    pub async fn serve(
+        self: Arc<Self>,
+        listener: TcpListener,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        info!(addr = %listener.local_addr()?, "http api listening");
+        axum::Server::from_tcp(listener.into_std()?)?
+            .serve(self.router().into_make_service())
+            .await?;
+        Ok(())
+    }
+
+    fn stats(&self, name: String) -> Result<StatsBody, ApiError> {
+        let stats = self.game_service.get_stats_service().get_stats(&name)?;
+        Ok(StatsBody::new(name, &stats))
+    }
+}
+
+async fn register(
+    State(api): State<Arc<Api>>,
+    Json(credentials): Json<Credentials>,
+) -> Result<(StatusCode, Json<AccountBody>), ApiError> {
+    let account_service = api.account_service.clone();
+    let name = credentials.name.clone();
+    // Hashing the password takes long enough to stall other requests
+    tokio::task::spawn_blocking(move || {
+        account_service.register(&credentials.name, &credentials.password)
+    })
+    .await
+    .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok((StatusCode::CREATED, Json(AccountBody { name })))
+}
+
+async fn login(
+    State(api): State<Arc<Api>>,
+    Json(credentials): Json<Credentials>,
+) -> Result<Json<SessionBody>, ApiError> {
+    let account_service = api.account_service.clone();
+    let token = tokio::task::spawn_blocking(move || {
+        account_service.login(&credentials.name, &credentials.password)
+    })
+    .await
+    .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| ApiError(StatusCode::UNAUTHORIZED, e.to_string()))?;
+    Ok(Json(SessionBody {
+        token,
+        expires_in_secs: SESSION_TTL.as_secs(),
+    }))
+}
+
+async fn me(
+    State(api): State<Arc<Api>>,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<StatsBody>, ApiError> {
+    match api.account_service.authenticate(bearer.token()) {
+        Some(name) => Ok(Json(api.stats(name)?)),
+        None => Err(ApiError(
+            StatusCode::UNAUTHORIZED,
+            String::from("Invalid or expired token"),
+        )),
+    }
+}
+
+async fn player_stats(
+    State(api): State<Arc<Api>>,
+    Path(name): Path<String>,
+) -> Result<Json<StatsBody>, ApiError> {
+    Ok(Json(api.stats(name)?))
+}
+
+async fn player_matches(
+    State(api): State<Arc<Api>>,
+    Path(name): Path<String>,
+    Query(query): Query<MatchesQuery>,
+) -> Result<(HeaderMap, Json<Vec<MatchBody>>), ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_MATCH_LIMIT)
+        .min(MAX_MATCH_LIMIT);
+    let before = match query.before {
+        Some(cursor) => Some(
+            cursor
+                .parse::<MatchCursor>()
+                .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?,
+        ),
+        None => None,
+    };
+    let filter = HistoryFilter {
+        before,
+        since: query.since,
+        until: query.until,
+        opponent: query.opponent,
+    };
+    // One past the page tells whether there is another
+    let mut history =
+        api.game_service
+            .get_stats_service()
+            .get_history(&name, &filter, limit + 1)?;
+    let mut headers = HeaderMap::new();
+    if history.len() > limit {
+        history.truncate(limit);
+        if let Some(last) = history.last() {
+            let cursor = MatchCursor::of(last).to_string();
+            if let Ok(value) = HeaderValue::from_str(&cursor) {
+                headers.insert(NEXT_CURSOR_HEADER, value);
+            }
+        }
+    }
+    Ok((
+        headers,
+        Json(
+            history
+                .iter()
+                .map(|record| MatchBody::new(&name, record))
+                .collect(),
+        ),
+    ))
+}
+
+async fn daily_leaderboard(
+    State(api): State<Arc<Api>>,
+    Query(query): Query<DailyQuery>,
+) -> Result<Json<LeaderboardBody>, ApiError> {
+    let day = query.day.unwrap_or_else(current_day);
+    let scores = api.game_service.get_leaderboard_service().get_daily(day)?;
+    Ok(Json(LeaderboardBody {
+        day,
+        entries: LeaderboardEntry::ranked(&scores)
+            .into_iter()
+            .map(|entry| RankBody {
+                rank: entry.rank,
+                name: entry.name,
+                score: entry.score,
+            })
+            .collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use axum::{body::Body, http::Request};
+    use serde::de::DeserializeOwned;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{game::game::Game, player::Player, storage::memory::MemoryStorage};
+
+    fn new_api() -> Arc<Api> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        Arc::new(Api::new(
+            Arc::new(AccountService::new(Arc::new(MemoryStorage::new()))),
+            game_service,
+        ))
+    }
+
+    async fn call<T: DeserializeOwned>(
+        api: &Arc<Api>,
+        req: Request<Body>,
+    ) -> Result<(StatusCode, T), Box<dyn Error + Send + Sync>> {
+        let res = api.clone().router().oneshot(req).await?;
+        let status = res.status();
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        Ok((status, serde_json::from_slice(&body)?))
+    }
+
+    fn post_json(uri: &str, credentials: &Credentials) -> Request<Body> {
+        Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(credentials).unwrap()))
+            .unwrap()
+    }
+
+    fn credentials(password: &str) -> Credentials {
+        Credentials {
+            name: String::from("test"),
+            password: String::from(password),
+        }
+    }
+
+    #[tokio::test]
+    async fn login_after_register_should_authorize_me() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let api = new_api();
+        let (status, account) =
+            call::<AccountBody>(&api, post_json("/accounts", &credentials("password"))).await?;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(account.name, "test");
+        let (status, session) =
+            call::<SessionBody>(&api, post_json("/sessions", &credentials("password"))).await?;
+        assert_eq!(status, StatusCode::OK);
+        let (status, stats) = call::<StatsBody>(
+            &api,
+            Request::get("/me")
+                .header("authorization", format!("Bearer {}", session.token))
+                .body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(stats.name, "test");
+        assert_eq!(stats.games_played, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn login_with_wrong_password_should_return_unauthorized(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let api = new_api();
+        call::<AccountBody>(&api, post_json("/accounts", &credentials("password"))).await?;
+        let (status, body) =
+            call::<ErrorBody>(&api, post_json("/sessions", &credentials("passwort"))).await?;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body.error, "Invalid name or password");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn register_with_taken_name_should_return_bad_request(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let api = new_api();
+        call::<AccountBody>(&api, post_json("/accounts", &credentials("password"))).await?;
+        let (status, body) =
+            call::<ErrorBody>(&api, post_json("/accounts", &credentials("password1"))).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.error, "Name already taken");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn daily_leaderboard_should_rank_scores() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let api = new_api();
+        let leaderboard_service = api.game_service.get_leaderboard_service();
+        leaderboard_service.submit_daily(3, "test", 10)?;
+        leaderboard_service.submit_daily(3, "test1", 12)?;
+        let (status, board) = call::<LeaderboardBody>(
+            &api,
+            Request::get("/leaderboards/daily?day=3").body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(board.day, 3);
+        assert_eq!(
+            board
+                .entries
+                .iter()
+                .map(|entry| (entry.rank, entry.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(1, "test1"), (2, "test")]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn player_matches_with_unknown_player_should_return_empty(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (status, matches) = call::<Vec<MatchBody>>(
+            &new_api(),
+            Request::get("/players/nobody/matches?limit=5").body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(status, StatusCode::OK);
+        assert!(matches.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn player_matches_with_cursor_should_return_next_page(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let api = new_api();
+        for id in 0..3 {
+            let game = Game::new(
+                id,
+                vec![
+                    Arc::new(Player::new(0, String::from("test"))),
+                    Arc::new(Player::new(1, String::from("test1"))),
+                ],
+            );
+            api.game_service.get_stats_service().record_game(&game)?;
+        }
+        let res = api
+            .clone()
+            .router()
+            .oneshot(Request::get("/players/test/matches?limit=2").body(Body::empty())?)
+            .await?;
+        let cursor = res.headers()[NEXT_CURSOR_HEADER].to_str()?.to_string();
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        let matches: Vec<MatchBody> = serde_json::from_slice(&body)?;
+        assert_eq!(
+            matches.iter().map(|m| m.game_id).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        let uri = format!("/players/test/matches?limit=2&before={}", cursor);
+        let res = api
+            .clone()
+            .router()
+            .oneshot(Request::get(uri).body(Body::empty())?)
+            .await?;
+        assert!(res.headers().get(NEXT_CURSOR_HEADER).is_none());
+        let body = hyper::body::to_bytes(res.into_body()).await?;
+        let matches: Vec<MatchBody> = serde_json::from_slice(&body)?;
+        assert_eq!(
+            matches.iter().map(|m| m.game_id).collect::<Vec<_>>(),
+            vec![0]
+        );
+        let (status, _) = call::<ErrorBody>(
+            &api,
+            Request::get("/players/test/matches?before=soon").body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+}
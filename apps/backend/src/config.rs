@@ -0,0 +1,962 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    error::Error,
+    fs,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use crate::{operation::Operation, service::cluster_service::MAX_NODES};
+
+/// Everything the server can be tuned with. Values come from the defaults
+/// below, then `config.toml` (or the file at `CONFIG_PATH`), then env vars.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Shown to clients listing servers, along with `region` and `motd`.
+    pub server_name: String,
+    /// Free-form tag such as `eu-west`.
+    pub region: String,
+    /// Message of the day, sent to every client right after it connects
+    /// when not empty. Operators can replace it at runtime.
+    pub motd: String,
+    pub host: String,
+    pub port: u16,
+    /// More addresses clients can connect on besides `host:port`, such as
+    /// `[::]:45678` or an internal interface. IPv6 addresses only take IPv6
+    /// clients, so they can share a port with an IPv4 one.
+    pub listen: Vec<String>,
+    /// Expect a PROXY protocol header on every client connection and take the
+    /// client's address from it. Only for servers every client reaches
+    /// through a load balancer that sends one, anyone else could claim any
+    /// address.
+    pub proxy_protocol: bool,
+    pub health_port: u16,
+    /// Port of the read-only gRPC gateway, which only runs when this is set
+    /// and the server is built with the `grpc` feature.
+    pub grpc_port: Option<u16>,
+    /// Port of the HTTP API for accounts and stats, which only runs when this
+    /// is set and the server is built with the `http` feature.
+    pub http_port: Option<u16>,
+    /// Further clients are turned away once this many are connected.
+    pub max_connections: usize,
+    /// Cap on clients sharing one address, so a single host can't take every
+    /// slot.
+    pub max_connections_per_ip: usize,
+    /// What happens when a name that is already online connects again.
+    pub duplicate_sessions: SessionPolicy,
+    /// A client that sends nothing for this long is disconnected.
+    pub heartbeat_timeout_secs: u64,
+    /// A client that hasn't connected this long after opening its socket is
+    /// disconnected.
+    pub handshake_timeout_secs: u64,
+    /// A player whose average heartbeat round trip goes over this many
+    /// milliseconds is reported to their lobby or game, 0 to never report it.
+    pub lag_warning_ms: u32,
+    /// Requests whose controller takes longer than this many milliseconds
+    /// are logged with the lock they waited on longest, 0 to never log them.
+    pub slow_request_ms: u64,
+    /// Budgets for single operations in place of `slow_request_ms`, by
+    /// operation name such as `FinishTurn`.
+    pub slow_request_budgets: BTreeMap<String, u64>,
+    /// A client whose socket accepts nothing for this long is disconnected.
+    pub write_timeout_secs: u64,
+    /// Frames queued per connection before senders have to wait.
+    pub outbound_queue_size: usize,
+    /// Send small frames as soon as they're written instead of letting the OS
+    /// batch them, turns go out without waiting on Nagle.
+    pub tcp_nodelay: bool,
+    /// Seconds a client socket is idle before the OS starts probing it, and
+    /// between probes, 0 to leave keepalive off.
+    pub tcp_keepalive_secs: u64,
+    /// Socket send buffer size, 0 to keep the OS default.
+    pub tcp_send_buffer_bytes: usize,
+    /// Socket receive buffer size, 0 to keep the OS default.
+    pub tcp_recv_buffer_bytes: usize,
+    /// Where accounts, stats and unfinished games are kept, `sqlite:` or
+    /// `postgres:`. Unset keeps them in memory, lost on restart.
+    pub database_url: Option<String>,
+    /// Redis other instances behind the same load balancer use, to share
+    /// who is online and broadcasts with them. Unset runs alone.
+    pub redis_url: Option<String>,
+    /// This instance among those sharing `redis_url`, each needs its own.
+    /// Lobby ids carry it so any instance knows where a lobby lives.
+    pub node_id: u16,
+    /// Where clients reach this instance, when sent here by another one.
+    /// Unset uses `host:port`.
+    pub advertise_address: Option<String>,
+    /// File every state-changing request is recorded in, unset to record
+    /// nothing.
+    pub audit_log_path: Option<String>,
+    /// Size the audit log is rotated at.
+    pub audit_log_max_bytes: u64,
+    pub admin_token: Option<String>,
+    /// Tokens bots and services connect with instead of a name.
+    pub api_tokens: Vec<ApiTokenConfig>,
+    pub lobby: LobbyConfig,
+    pub game: GameConfig,
+    pub chat: ChatConfig,
+    pub season: SeasonConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LobbyConfig {
+    /// Range leaders can pick a lobby's `max_players` from.
+    pub min_players: u32,
+    pub max_players: u32,
+    /// Players a lobby needs before it can start, unless its leader asks for
+    /// more.
+    pub min_players_to_start: u32,
+    /// How long a lobby that never started a game may go without anyone
+    /// joining or leaving before it is closed, 0 to keep it open.
+    pub max_idle_secs: u64,
+    /// Chat messages a lobby keeps to show players who join, 0 to keep none.
+    pub chat_history_len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// Word list for the default language, `en`.
+    pub dictionary_path: String,
+    /// Word lists for further languages lobbies can pick, by language key.
+    pub dictionaries: BTreeMap<String, String>,
+    /// Where word lists compiled for the solver are kept, so later starts
+    /// load them instead of compiling them again. Empty to compile them on
+    /// every start.
+    pub dictionary_cache_dir: String,
+    /// Short definitions of words in the default language's list, sent with
+    /// the words a turn makes. One `word<TAB>definition` per line. Empty for
+    /// none.
+    pub definitions_path: String,
+    /// Definitions for further languages, by language key.
+    pub definitions: BTreeMap<String, String>,
+    pub turn_duration_secs: u64,
+    /// Hints each player gets per game, unless the lobby leader picks another
+    /// budget.
+    pub hint_budget: u32,
+    /// Turn timers a player can let run out in a row before they forfeit, 0
+    /// to never forfeit idle players.
+    pub forfeit_after_missed_turns: u32,
+    /// How long a player who dropped mid-game keeps their seat for, before
+    /// they forfeit.
+    pub disconnect_grace_secs: u64,
+    /// How long a vote to kick a player out of a game stays open for.
+    pub vote_kick_secs: u64,
+    /// Invalid moves a player can send, such as playing a card they don't
+    /// hold, before they are kicked, 0 to never kick. Moves out of turn or
+    /// after the timer don't count, as honest clients racing the timer send
+    /// them too.
+    pub kick_after_violations: u32,
+    /// How often unfinished games are saved so they survive a restart, 0 to
+    /// never save them.
+    pub snapshot_interval_secs: u64,
+    /// How far behind live spectators see a game, 0 to show it live.
+    pub spectator_delay_secs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChatConfig {
+    pub filter: ChatFilter,
+    /// Words the filter acts on, matched whole and ignoring case.
+    pub blocked_words: Vec<String>,
+}
+
+/// Ranked seasons run back to back, each `length_days` long, from `start` on.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SeasonConfig {
+    /// UTC date the first season starts on, as `YYYY-MM-DD`.
+    pub start: String,
+    pub length_days: u32,
+    /// How much of the distance between a rating and the starting rating
+    /// carries over into the next season, in percent. 0 puts everyone back
+    /// at the start.
+    pub carry_over_percent: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiTokenConfig {
+    /// Name the token's sessions play under.
+    pub name: String,
+    pub token: String,
+    pub scopes: Vec<TokenScope>,
+}
+
+/// What a session opened with an API token may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+    /// Lobbies, games and chat.
+    Play,
+    /// Watching other players' games.
+    Spectate,
+    /// Admin requests, with the API token as their token.
+    Admin,
+    /// Following every event of a game live, for broadcasting tools.
+    Observe,
+}
+
+/// What happens to a chat message with a blocked word in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatFilter {
+    /// Deliver it with the blocked words starred out.
+    Mask,
+    /// Refuse to deliver it.
+    Reject,
+}
+
+/// What to do with a second session under a name that is online already.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionPolicy {
+    /// Refuse the new session.
+    Reject,
+    /// Close the old session, telling it why, and let the new one take over
+    /// its seat if it was in a game.
+    Replace,
+    /// Let both stay online.
+    Allow,
+}
+
+impl FromStr for SessionPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(SessionPolicy::Reject),
+            "replace" => Ok(SessionPolicy::Replace),
+            "allow" => Ok(SessionPolicy::Allow),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FromStr for ChatFilter {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mask" => Ok(ChatFilter::Mask),
+            "reject" => Ok(ChatFilter::Reject),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            server_name: String::from("LetterLegend"),
+            region: String::new(),
+            motd: String::new(),
+            host: String::from("0.0.0.0"),
+            listen: Vec::new(),
+            proxy_protocol: false,
+            port: 45678,
+            health_port: 45679,
+            grpc_port: None,
+            http_port: None,
+            max_connections: 1024,
+            max_connections_per_ip: 16,
+            duplicate_sessions: SessionPolicy::Allow,
+            heartbeat_timeout_secs: 30,
+            handshake_timeout_secs: 10,
+            lag_warning_ms: 500,
+            slow_request_ms: 50,
+            slow_request_budgets: BTreeMap::new(),
+            write_timeout_secs: 10,
+            outbound_queue_size: 128,
+            tcp_nodelay: true,
+            tcp_keepalive_secs: 60,
+            tcp_send_buffer_bytes: 0,
+            tcp_recv_buffer_bytes: 0,
+            database_url: None,
+            redis_url: None,
+            node_id: 0,
+            advertise_address: None,
+            audit_log_path: None,
+            audit_log_max_bytes: 10 * 1024 * 1024,
+            admin_token: None,
+            api_tokens: Vec::new(),
+            lobby: LobbyConfig::default(),
+            game: GameConfig::default(),
+            chat: ChatConfig::default(),
+            season: SeasonConfig::default(),
+        }
+    }
+}
+
+impl Default for LobbyConfig {
+    fn default() -> Self {
+        Self {
+            min_players: 4,
+            max_players: 8,
+            min_players_to_start: 1,
+            max_idle_secs: 600,
+            chat_history_len: 20,
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            dictionary_path: String::from("assets/wordlist.txt"),
+            dictionaries: BTreeMap::new(),
+            dictionary_cache_dir: String::new(),
+            definitions_path: String::new(),
+            definitions: BTreeMap::new(),
+            turn_duration_secs: 30,
+            hint_budget: 3,
+            forfeit_after_missed_turns: 3,
+            disconnect_grace_secs: 120,
+            vote_kick_secs: 30,
+            kick_after_violations: 5,
+            snapshot_interval_secs: 30,
+            spectator_delay_secs: 60,
+        }
+    }
+}
+
+impl Default for SeasonConfig {
+    fn default() -> Self {
+        Self {
+            start: String::from("2024-01-01"),
+            length_days: 91,
+            carry_over_percent: 50,
+        }
+    }
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        Self {
+            filter: ChatFilter::Mask,
+            blocked_words: Vec::new(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Load the config file named by `CONFIG_PATH`, falling back to an
+    /// optional `config.toml`, and apply env overrides on top.
+    pub fn load() -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut config = match env::var("CONFIG_PATH") {
+            Ok(path) => Self::from_file(Path::new(&path))?,
+            Err(_) if Path::new("config.toml").exists() => {
+                Self::from_file(Path::new("config.toml"))?
+            }
+            Err(_) => Self::default(),
+        };
+        config.apply_env(|key| env::var(key).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Self::from_toml(&content)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Override fields with the env vars `var` knows about.
+    pub fn apply_env(
+        &mut self,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        fn parse<T: std::str::FromStr>(
+            key: &str,
+            value: String,
+        ) -> Result<T, Box<dyn Error + Send + Sync>> {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value for {}: {}", key, value).into())
+        }
+
+        if let Some(value) = var("SERVER_NAME") {
+            self.server_name = value;
+        }
+        if let Some(value) = var("SERVER_REGION") {
+            self.region = value;
+        }
+        if let Some(value) = var("MOTD") {
+            self.motd = value;
+        }
+        if let Some(value) = var("HOST") {
+            self.host = value;
+        }
+        if let Some(value) = var("PORT") {
+            self.port = parse("PORT", value)?;
+        }
+        if let Some(value) = var("LISTEN") {
+            self.listen = value
+                .split(',')
+                .map(|address| address.trim().to_string())
+                .filter(|address| !address.is_empty())
+                .collect();
+        }
+        if let Some(value) = var("PROXY_PROTOCOL") {
+            self.proxy_protocol = parse("PROXY_PROTOCOL", value)?;
+        }
+        if let Some(value) = var("HEALTH_PORT") {
+            self.health_port = parse("HEALTH_PORT", value)?;
+        }
+        if let Some(value) = var("GRPC_PORT") {
+            self.grpc_port = match value.is_empty() {
+                true => None,
+                false => Some(parse("GRPC_PORT", value)?),
+            };
+        }
+        if let Some(value) = var("HTTP_PORT") {
+            self.http_port = match value.is_empty() {
+                true => None,
+                false => Some(parse("HTTP_PORT", value)?),
+            };
+        }
+        if let Some(value) = var("MAX_CONNECTIONS") {
+            self.max_connections = parse("MAX_CONNECTIONS", value)?;
+        }
+        if let Some(value) = var("MAX_CONNECTIONS_PER_IP") {
+            self.max_connections_per_ip = parse("MAX_CONNECTIONS_PER_IP", value)?;
+        }
+        if let Some(value) = var("DUPLICATE_SESSIONS") {
+            self.duplicate_sessions = parse("DUPLICATE_SESSIONS", value)?;
+        }
+        if let Some(value) = var("HEARTBEAT_TIMEOUT_SECS") {
+            self.heartbeat_timeout_secs = parse("HEARTBEAT_TIMEOUT_SECS", value)?;
+        }
+        if let Some(value) = var("HANDSHAKE_TIMEOUT_SECS") {
+            self.handshake_timeout_secs = parse("HANDSHAKE_TIMEOUT_SECS", value)?;
+        }
+        if let Some(value) = var("LAG_WARNING_MS") {
+            self.lag_warning_ms = parse("LAG_WARNING_MS", value)?;
+        }
+        if let Some(value) = var("SLOW_REQUEST_MS") {
+            self.slow_request_ms = parse("SLOW_REQUEST_MS", value)?;
+        }
+        if let Some(value) = var("WRITE_TIMEOUT_SECS") {
+            self.write_timeout_secs = parse("WRITE_TIMEOUT_SECS", value)?;
+        }
+        if let Some(value) = var("OUTBOUND_QUEUE_SIZE") {
+            self.outbound_queue_size = parse("OUTBOUND_QUEUE_SIZE", value)?;
+        }
+        if let Some(value) = var("TCP_NODELAY") {
+            self.tcp_nodelay = parse("TCP_NODELAY", value)?;
+        }
+        if let Some(value) = var("TCP_KEEPALIVE_SECS") {
+            self.tcp_keepalive_secs = parse("TCP_KEEPALIVE_SECS", value)?;
+        }
+        if let Some(value) = var("TCP_SEND_BUFFER_BYTES") {
+            self.tcp_send_buffer_bytes = parse("TCP_SEND_BUFFER_BYTES", value)?;
+        }
+        if let Some(value) = var("TCP_RECV_BUFFER_BYTES") {
+            self.tcp_recv_buffer_bytes = parse("TCP_RECV_BUFFER_BYTES", value)?;
+        }
+        if let Some(value) = var("DATABASE_URL") {
+            self.database_url = Some(value).filter(|value| !value.is_empty());
+        }
+        if let Some(value) = var("REDIS_URL") {
+            self.redis_url = Some(value).filter(|value| !value.is_empty());
+        }
+        if let Some(value) = var("NODE_ID") {
+            self.node_id = parse("NODE_ID", value)?;
+        }
+        if let Some(value) = var("ADVERTISE_ADDRESS") {
+            self.advertise_address = Some(value).filter(|value| !value.is_empty());
+        }
+        if let Some(value) = var("AUDIT_LOG_PATH") {
+            self.audit_log_path = Some(value).filter(|value| !value.is_empty());
+        }
+        if let Some(value) = var("AUDIT_LOG_MAX_BYTES") {
+            self.audit_log_max_bytes = parse("AUDIT_LOG_MAX_BYTES", value)?;
+        }
+        if let Some(value) = var("ADMIN_TOKEN") {
+            self.admin_token = Some(value).filter(|value| !value.is_empty());
+        }
+        if let Some(value) = var("LOBBY_MIN_PLAYERS") {
+            self.lobby.min_players = parse("LOBBY_MIN_PLAYERS", value)?;
+        }
+        if let Some(value) = var("LOBBY_MAX_PLAYERS") {
+            self.lobby.max_players = parse("LOBBY_MAX_PLAYERS", value)?;
+        }
+        if let Some(value) = var("LOBBY_MIN_PLAYERS_TO_START") {
+            self.lobby.min_players_to_start = parse("LOBBY_MIN_PLAYERS_TO_START", value)?;
+        }
+        if let Some(value) = var("LOBBY_MAX_IDLE_SECS") {
+            self.lobby.max_idle_secs = parse("LOBBY_MAX_IDLE_SECS", value)?;
+        }
+        if let Some(value) = var("LOBBY_CHAT_HISTORY_LEN") {
+            self.lobby.chat_history_len = parse("LOBBY_CHAT_HISTORY_LEN", value)?;
+        }
+        if let Some(value) = var("DICTIONARY_PATH") {
+            self.game.dictionary_path = value;
+        }
+        if let Some(value) = var("DICTIONARY_CACHE_DIR") {
+            self.game.dictionary_cache_dir = value;
+        }
+        if let Some(value) = var("DEFINITIONS_PATH") {
+            self.game.definitions_path = value;
+        }
+        if let Some(value) = var("TURN_DURATION_SECS") {
+            self.game.turn_duration_secs = parse("TURN_DURATION_SECS", value)?;
+        }
+        if let Some(value) = var("HINT_BUDGET") {
+            self.game.hint_budget = parse("HINT_BUDGET", value)?;
+        }
+        if let Some(value) = var("FORFEIT_AFTER_MISSED_TURNS") {
+            self.game.forfeit_after_missed_turns = parse("FORFEIT_AFTER_MISSED_TURNS", value)?;
+        }
+        if let Some(value) = var("DISCONNECT_GRACE_SECS") {
+            self.game.disconnect_grace_secs = parse("DISCONNECT_GRACE_SECS", value)?;
+        }
+        if let Some(value) = var("VOTE_KICK_SECS") {
+            self.game.vote_kick_secs = parse("VOTE_KICK_SECS", value)?;
+        }
+        if let Some(value) = var("KICK_AFTER_VIOLATIONS") {
+            self.game.kick_after_violations = parse("KICK_AFTER_VIOLATIONS", value)?;
+        }
+        if let Some(value) = var("SNAPSHOT_INTERVAL_SECS") {
+            self.game.snapshot_interval_secs = parse("SNAPSHOT_INTERVAL_SECS", value)?;
+        }
+        if let Some(value) = var("SPECTATOR_DELAY_SECS") {
+            self.game.spectator_delay_secs = parse("SPECTATOR_DELAY_SECS", value)?;
+        }
+        if let Some(value) = var("SEASON_START") {
+            self.season.start = value;
+        }
+        if let Some(value) = var("SEASON_LENGTH_DAYS") {
+            self.season.length_days = parse("SEASON_LENGTH_DAYS", value)?;
+        }
+        if let Some(value) = var("SEASON_CARRY_OVER_PERCENT") {
+            self.season.carry_over_percent = parse("SEASON_CARRY_OVER_PERCENT", value)?;
+        }
+        if let Some(value) = var("CHAT_FILTER") {
+            self.chat.filter = parse("CHAT_FILTER", value)?;
+        }
+        if let Some(value) = var("CHAT_BLOCKED_WORDS") {
+            self.chat.blocked_words = value
+                .split(',')
+                .map(|word| word.trim().to_string())
+                .filter(|word| !word.is_empty())
+                .collect();
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.lobby.min_players == 0 || self.lobby.min_players > self.lobby.max_players {
+            return Err("lobby.min_players must be between 1 and lobby.max_players".into());
+        }
+        if self.lobby.min_players_to_start == 0
+            || self.lobby.min_players_to_start > self.lobby.min_players
+        {
+            return Err(
+                "lobby.min_players_to_start must be between 1 and lobby.min_players".into(),
+            );
+        }
+        if self.max_connections == 0 || self.max_connections_per_ip == 0 {
+            return Err("connection limits must be greater than 0".into());
+        }
+        if self.outbound_queue_size == 0 {
+            return Err("outbound_queue_size must be greater than 0".into());
+        }
+        if let Some(name) = self
+            .slow_request_budgets
+            .keys()
+            .find(|name| Operation::from_name(name).is_none())
+        {
+            return Err(format!("slow_request_budgets has unknown operation {}", name).into());
+        }
+        if self.node_id >= MAX_NODES {
+            return Err(format!("node_id must be below {}", MAX_NODES).into());
+        }
+        if self.audit_log_max_bytes == 0 {
+            return Err("audit_log_max_bytes must be greater than 0".into());
+        }
+        if self.heartbeat_timeout_secs == 0
+            || self.handshake_timeout_secs == 0
+            || self.write_timeout_secs == 0
+            || self.game.turn_duration_secs == 0
+        {
+            return Err("timeouts must be greater than 0".into());
+        }
+        if self
+            .api_tokens
+            .iter()
+            .any(|api_token| api_token.name.is_empty() || api_token.token.is_empty())
+        {
+            return Err("api_tokens need a name and a token".into());
+        }
+        if self.season.start_day().is_none() {
+            return Err("season.start must be a date as YYYY-MM-DD".into());
+        }
+        if self.season.length_days == 0 {
+            return Err("season.length_days must be greater than 0".into());
+        }
+        if self.season.carry_over_percent > 100 {
+            return Err("season.carry_over_percent must be at most 100".into());
+        }
+        Ok(())
+    }
+
+    /// Every address the game listens on, `host:port` first.
+    pub fn listen_addresses(&self) -> Vec<String> {
+        let mut addresses = vec![format!("{}:{}", self.host, self.port)];
+        addresses.extend(self.listen.iter().cloned());
+        addresses
+    }
+
+    /// Where clients reach this instance from another one.
+    pub fn advertise_address(&self) -> String {
+        self.advertise_address
+            .clone()
+            .unwrap_or_else(|| format!("{}:{}", self.host, self.port))
+    }
+
+    pub fn heartbeat_timeout(&self) -> Duration {
+        Duration::from_secs(self.heartbeat_timeout_secs)
+    }
+
+    pub fn handshake_timeout(&self) -> Duration {
+        Duration::from_secs(self.handshake_timeout_secs)
+    }
+
+    /// How long `operation` may take to handle before it's logged as slow,
+    /// `None` to never log it.
+    pub fn slow_request_budget(&self, operation: &Operation) -> Option<Duration> {
+        let ms = self
+            .slow_request_budgets
+            .get(&format!("{:?}", operation))
+            .copied()
+            .unwrap_or(self.slow_request_ms);
+        Some(Duration::from_millis(ms)).filter(|budget| !budget.is_zero())
+    }
+
+    pub fn write_timeout(&self) -> Duration {
+        Duration::from_secs(self.write_timeout_secs)
+    }
+
+    pub fn tcp_keepalive(&self) -> Option<Duration> {
+        Some(Duration::from_secs(self.tcp_keepalive_secs)).filter(|time| !time.is_zero())
+    }
+
+    /// This config with `next`'s values for everything that can change while
+    /// the server runs. The rest is bound to listeners, storage or services
+    /// built at start, so it's kept, and the names of those `next` changes
+    /// are returned to report they wait for a restart.
+    pub fn reloaded(&self, next: ServerConfig) -> (ServerConfig, Vec<&'static str>) {
+        let restart_only = [
+            ("host", self.host != next.host),
+            ("port", self.port != next.port),
+            ("listen", self.listen != next.listen),
+            ("health_port", self.health_port != next.health_port),
+            ("grpc_port", self.grpc_port != next.grpc_port),
+            ("http_port", self.http_port != next.http_port),
+            ("database_url", self.database_url != next.database_url),
+            ("redis_url", self.redis_url != next.redis_url),
+            ("node_id", self.node_id != next.node_id),
+            (
+                "advertise_address",
+                self.advertise_address != next.advertise_address,
+            ),
+            ("audit_log_path", self.audit_log_path != next.audit_log_path),
+            (
+                "audit_log_max_bytes",
+                self.audit_log_max_bytes != next.audit_log_max_bytes,
+            ),
+            ("admin_token", self.admin_token != next.admin_token),
+            ("api_tokens", self.api_tokens != next.api_tokens),
+            ("lobby", self.lobby != next.lobby),
+            (
+                "game.snapshot_interval_secs",
+                self.game.snapshot_interval_secs != next.game.snapshot_interval_secs,
+            ),
+            (
+                "game.spectator_delay_secs",
+                self.game.spectator_delay_secs != next.game.spectator_delay_secs,
+            ),
+            ("chat", self.chat != next.chat),
+            ("season", self.season != next.season),
+        ];
+        let pending = restart_only
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(name, _)| name)
+            .collect();
+        let config = ServerConfig {
+            host: self.host.clone(),
+            port: self.port,
+            listen: self.listen.clone(),
+            health_port: self.health_port,
+            grpc_port: self.grpc_port,
+            http_port: self.http_port,
+            database_url: self.database_url.clone(),
+            redis_url: self.redis_url.clone(),
+            node_id: self.node_id,
+            advertise_address: self.advertise_address.clone(),
+            audit_log_path: self.audit_log_path.clone(),
+            audit_log_max_bytes: self.audit_log_max_bytes,
+            admin_token: self.admin_token.clone(),
+            api_tokens: self.api_tokens.clone(),
+            lobby: self.lobby.clone(),
+            game: GameConfig {
+                snapshot_interval_secs: self.game.snapshot_interval_secs,
+                spectator_delay_secs: self.game.spectator_delay_secs,
+                ..next.game
+            },
+            chat: self.chat.clone(),
+            season: self.season.clone(),
+            ..next
+        };
+        (config, pending)
+    }
+}
+
+/// The config the server is running on. A reload swaps it whole, so readers
+/// never see half of an old config and half of a new one.
+#[derive(Debug)]
+pub struct LiveConfig(RwLock<Arc<ServerConfig>>);
+
+impl LiveConfig {
+    pub fn new(config: ServerConfig) -> Self {
+        Self(RwLock::new(Arc::new(config)))
+    }
+
+    pub fn get(&self) -> Arc<ServerConfig> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub fn set(&self, config: ServerConfig) {
+        *self.0.write().unwrap() = Arc::new(config);
+    }
+}
+
+impl LobbyConfig {
+    pub fn max_idle(&self) -> Option<Duration> {
+        Some(Duration::from_secs(self.max_idle_secs)).filter(|max_idle| !max_idle.is_zero())
+    }
+}
+
+impl GameConfig {
+    pub fn turn_duration(&self) -> Duration {
+        Duration::from_secs(self.turn_duration_secs)
+    }
+
+    pub fn disconnect_grace(&self) -> Duration {
+        Duration::from_secs(self.disconnect_grace_secs)
+    }
+
+    pub fn vote_kick_timeout(&self) -> Duration {
+        Duration::from_secs(self.vote_kick_secs)
+    }
+
+    pub fn snapshot_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(self.snapshot_interval_secs))
+            .filter(|interval| !interval.is_zero())
+    }
+
+    pub fn spectator_delay(&self) -> Duration {
+        Duration::from_secs(self.spectator_delay_secs)
+    }
+}
+
+impl SeasonConfig {
+    /// Days since 1970-01-01 the first season starts on, `None` if `start`
+    /// isn't a date.
+    pub fn start_day(&self) -> Option<u32> {
+        parse_date(&self.start)
+    }
+}
+
+/// Days since 1970-01-01 of a `YYYY-MM-DD` date.
+fn parse_date(date: &str) -> Option<u32> {
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=month_days).contains(&day) {
+        return None;
+    }
+    // Count from March so the leap day falls at the end of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    u32::try_from(era * 146_097 + day_of_era - 719_468).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn from_toml_with_partial_file_should_keep_defaults() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let config = ServerConfig::from_toml(
+            r#"
+            port = 5000
+
+            [lobby]
+            max_players = 6
+            "#,
+        )?;
+        assert_eq!(config.port, 5000);
+        assert_eq!(config.lobby.max_players, 6);
+        assert_eq!(config.lobby.min_players, 4);
+        assert_eq!(config.game, GameConfig::default());
+        Ok(())
+    }
+
+    #[test]
+    fn from_toml_with_unknown_key_should_return_error() {
+        assert!(ServerConfig::from_toml("prot = 5000").is_err());
+    }
+
+    #[test]
+    fn apply_env_should_override_file_values() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut config = ServerConfig::from_toml("port = 5000")?;
+        let vars = HashMap::from([("PORT", "6000"), ("TURN_DURATION_SECS", "45")]);
+        config.apply_env(|key| vars.get(key).map(|value| value.to_string()))?;
+        assert_eq!(config.port, 6000);
+        assert_eq!(config.game.turn_duration_secs, 45);
+        Ok(())
+    }
+
+    #[test]
+    fn reloaded_should_keep_restart_only_settings() {
+        let current = ServerConfig::default();
+        let mut next = ServerConfig {
+            motd: String::from("test"),
+            port: current.port + 1,
+            max_connections: current.max_connections + 1,
+            ..ServerConfig::default()
+        };
+        next.game.turn_duration_secs += 1;
+        next.game.spectator_delay_secs += 1;
+        let (config, pending) = current.reloaded(next.clone());
+        assert_eq!(config.motd, next.motd);
+        assert_eq!(config.max_connections, next.max_connections);
+        assert_eq!(config.game.turn_duration_secs, next.game.turn_duration_secs);
+        assert_eq!(config.port, current.port);
+        assert_eq!(
+            config.game.spectator_delay_secs,
+            current.game.spectator_delay_secs
+        );
+        assert_eq!(pending, vec!["port", "game.spectator_delay_secs"]);
+        assert!(current.reloaded(current.clone()).1.is_empty());
+    }
+
+    #[test]
+    fn apply_env_with_blocked_words_should_split_on_commas(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut config = ServerConfig::default();
+        let vars = HashMap::from([
+            ("CHAT_FILTER", "reject"),
+            ("CHAT_BLOCKED_WORDS", "foo, bar,"),
+        ]);
+        config.apply_env(|key| vars.get(key).map(|value| value.to_string()))?;
+        assert_eq!(config.chat.filter, ChatFilter::Reject);
+        assert_eq!(config.chat.blocked_words, vec!["foo", "bar"]);
+        Ok(())
+    }
+
+    #[test]
+    fn apply_env_with_listen_should_add_addresses_after_host_and_port(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut config = ServerConfig::default();
+        let vars = HashMap::from([("LISTEN", "[::]:45678, 10.0.0.2:45690,")]);
+        config.apply_env(|key| vars.get(key).map(|value| value.to_string()))?;
+        assert_eq!(
+            config.listen_addresses(),
+            vec!["0.0.0.0:45678", "[::]:45678", "10.0.0.2:45690"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn apply_env_with_empty_grpc_port_should_disable_gateway(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut config = ServerConfig::from_toml("grpc_port = 45680")?;
+        assert_eq!(config.grpc_port, Some(45680));
+        let vars = HashMap::from([("GRPC_PORT", "")]);
+        config.apply_env(|key| vars.get(key).map(|value| value.to_string()))?;
+        assert_eq!(config.grpc_port, None);
+        Ok(())
+    }
+
+    #[test]
+    fn apply_env_with_invalid_number_should_return_error() {
+        let mut config = ServerConfig::default();
+        assert!(config
+            .apply_env(|key| match key {
+                "PORT" => Some(String::from("port")),
+                _ => None,
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn slow_request_budget_should_prefer_operation_budget(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let config = ServerConfig::from_toml(
+            "slow_request_ms = 50\n[slow_request_budgets]\nFinishTurn = 200\nHeartbeat = 0",
+        )?;
+        config.validate()?;
+        assert_eq!(
+            config.slow_request_budget(&Operation::SetTile),
+            Some(Duration::from_millis(50))
+        );
+        assert_eq!(
+            config.slow_request_budget(&Operation::FinishTurn),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(config.slow_request_budget(&Operation::Heartbeat), None);
+        let config = ServerConfig::from_toml("[slow_request_budgets]\nFinishTurns = 200")?;
+        assert!(config.validate().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn validate_with_min_players_over_max_should_return_error() {
+        let mut config = ServerConfig::default();
+        config.lobby.min_players = 9;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn start_day_should_count_days_since_epoch() {
+        let mut season = SeasonConfig::default();
+        assert_eq!(season.start_day(), Some(19_723));
+        season.start = String::from("2024-03-01");
+        assert_eq!(season.start_day(), Some(19_783));
+        season.start = String::from("2023-02-29");
+        assert_eq!(season.start_day(), None);
+        season.start = String::from("1969-12-31");
+        assert_eq!(season.start_day(), None);
+    }
+}
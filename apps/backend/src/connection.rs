@@ -1,67 +1,121 @@
-use std::{io::Cursor, time::Duration};
+use std::{fmt::Debug, time::Duration};
 
-use crate::frame::Frame;
+use crate::{
+    frame::{self, Frame, ResponseData, LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION},
+    model::control::connect::Compression,
+};
 use bytes::{Buf, BytesMut};
-use prost::Message;
+#[cfg(feature = "simulation")]
+use tokio::io::{DuplexStream, ReadHalf, WriteHalf};
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
     net::{
         tcp::{OwnedReadHalf, OwnedWriteHalf},
         TcpStream,
     },
-    sync::Mutex,
+    sync::watch,
     time::timeout,
 };
 
-#[derive(Debug)]
-pub struct Connection {
-    reader: Mutex<OwnedReadHalf>,
-    writer: Mutex<OwnedWriteHalf>,
-    buffer: Mutex<BytesMut>,
+/// Envelopes smaller than this are not worth compressing.
+const COMPRESSION_THRESHOLD: usize = 1024;
+/// Most frames the write task takes off the outbound queue for one write.
+pub const MAX_BATCH_FRAMES: usize = 64;
+
+/// A byte stream a client talks to the server over, which the server splits
+/// into halves it can read and write from separate tasks.
+pub trait Transport: Send + 'static {
+    type Reader: AsyncRead + Unpin + Send + Sync + Debug + 'static;
+    type Writer: AsyncWrite + Unpin + Send + Sync + Debug + 'static;
+
+    fn into_halves(self) -> (Self::Reader, Self::Writer);
 }
 
-impl Connection {
-    pub fn new(stream: TcpStream) -> Self {
-        let (reader, writer) = stream.into_split();
-        Self {
-            reader: Mutex::new(reader),
-            writer: Mutex::new(writer),
-            buffer: Mutex::new(BytesMut::with_capacity(4096)),
-        }
+impl Transport for TcpStream {
+    type Reader = OwnedReadHalf;
+    type Writer = OwnedWriteHalf;
+
+    fn into_halves(self) -> (Self::Reader, Self::Writer) {
+        self.into_split()
+    }
+}
+
+/// The server's end of an in-memory pipe, see `Server::connect_in_memory`.
+#[cfg(feature = "simulation")]
+impl Transport for DuplexStream {
+    type Reader = ReadHalf<DuplexStream>;
+    type Writer = WriteHalf<DuplexStream>;
+
+    fn into_halves(self) -> (Self::Reader, Self::Writer) {
+        tokio::io::split(self)
     }
+}
+
+/// Split a client connection into a reader owned by the read task and a
+/// writer owned by the write task. The only state they share is the
+/// negotiated protocol version, which the writer publishes once the connect
+/// response is on the wire.
+pub fn split<T: Transport>(
+    stream: T,
+    heartbeat_timeout: Duration,
+    write_timeout: Duration,
+) -> (ConnectionReader<T::Reader>, ConnectionWriter<T::Writer>) {
+    let (reader, writer) = stream.into_halves();
+    let (protocol_tx, protocol_rx) = watch::channel(LEGACY_PROTOCOL_VERSION);
+    (
+        ConnectionReader {
+            reader,
+            buffer: BytesMut::with_capacity(4096),
+            protocol_version: protocol_rx,
+            heartbeat_timeout,
+        },
+        ConnectionWriter {
+            writer,
+            protocol_version: protocol_tx,
+            compression: false,
+            write_timeout,
+        },
+    )
+}
+
+#[derive(Debug)]
+pub struct ConnectionReader<R = OwnedReadHalf> {
+    reader: R,
+    buffer: BytesMut,
+    protocol_version: watch::Receiver<u32>,
+    heartbeat_timeout: Duration,
+}
 
+impl<R: AsyncRead + Unpin> ConnectionReader<R> {
     pub async fn read_frame(
-        &self,
+        &mut self,
     ) -> Result<Option<Frame>, Box<dyn std::error::Error + Send + Sync>> {
         loop {
             // Attempt to parse a frame from the buffered data. If
             // enough data has been buffered, the frame is
             // returned.
-            if let Some(frame) = self.parse_frame().await? {
+            if let Some(frame) = self.parse_frame()? {
                 return Ok(Some(frame));
             }
 
+            // There is not enough buffered data to read a frame.
+            // Attempt to read more data from the socket.
+            //
+            // On success, the number of bytes is returned. `0`
+            // indicates "end of stream".
             match timeout(
-                Duration::from_secs(30),
-                self.reader
-                    .lock()
-                    .await
-                    .read_buf(&mut *self.buffer.lock().await),
+                self.heartbeat_timeout,
+                self.reader.read_buf(&mut self.buffer),
             )
             .await
             {
-                // There is not enough buffered data to read a frame.
-                // Attempt to read more data from the socket.
-                //
-                // On success, the number of bytes is returned. `0`
-                // indicates "end of stream".
                 Ok(result) => {
                     if 0 == result? {
                         // The remote closed the connection. For this to be
                         // a clean shutdown, there should be no data in the
                         // read buffer. If there is, this means that the
                         // peer closed the socket while sending a frame.
-                        if self.buffer.lock().await.is_empty() {
+                        if self.buffer.is_empty() {
                             return Ok(None);
                         } else {
                             return Err("connection reset by peer".into());
@@ -73,144 +127,254 @@ impl Connection {
         }
     }
 
-    async fn parse_frame(&self) -> Result<Option<Frame>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut buf_mutex_guard = self.buffer.lock().await;
-        // Create the `T: Buf` type.
-        let mut buf = Cursor::new(&buf_mutex_guard[..]);
+    /// Take the next frame off the buffer. A frame that can't be decoded
+    /// leaves no way to find where the next one starts, so it ends the
+    /// connection.
+    fn parse_frame(&mut self) -> Result<Option<Frame>, Box<dyn std::error::Error + Send + Sync>> {
+        match Frame::decode(&self.buffer, self.get_protocol_version()) {
+            Ok((frame, len)) => {
+                // Discard the frame from the buffer
+                self.buffer.advance(len);
+                Ok(Some(frame))
+            }
+            // Not enough data has been buffered
+            Err(frame::Error::Incomplete) => Ok(None),
+            // Client sent invalid data
+            Err(e) => Err(e.into()),
+        }
+    }
 
-        // Check whether a full frame is available
-        match Frame::check(&mut buf) {
-            Ok(_) => {
-                // Get the byte length of the frame
-                let len = buf.position() as usize;
+    pub fn get_protocol_version(&self) -> u32 {
+        *self.protocol_version.borrow()
+    }
+}
 
-                // Reset the internal cursor for the
-                // call to `parse`.
-                buf.set_position(0);
+#[derive(Debug)]
+pub struct ConnectionWriter<W = OwnedWriteHalf> {
+    writer: W,
+    protocol_version: watch::Sender<u32>,
+    compression: bool,
+    write_timeout: Duration,
+}
 
-                // Parse the frame
-                let frame = match Frame::parse(&mut buf) {
-                    Ok(frame) => frame,
-                    Err(e) => panic!("parse error, this should not happen: {:?}", e),
-                };
+impl<W: AsyncWrite + Unpin> ConnectionWriter<W> {
+    /// Write a frame to the connection.
+    pub async fn write_frame(
+        &mut self,
+        frame: &Frame,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.write_frames(std::slice::from_ref(frame)).await
+    }
 
-                // Discard the frame from the buffer
-                buf_mutex_guard.advance(len);
+    /// Write frames to the connection in order, encoded into one buffer so
+    /// a burst of broadcasts costs a single write.
+    pub async fn write_frames(
+        &mut self,
+        frames: &[Frame],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut bytes = Vec::new();
+        for frame in frames {
+            self.encode_frame(frame, &mut bytes)?;
+        }
 
-                // Return the frame to the caller.
-                Ok(Some(frame))
+        // A client that stops reading fills the socket buffer and would
+        // block this write, and then the outbound queue, forever
+        match timeout(self.write_timeout, self.writer.write_all(&bytes)).await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err("write timeout, client stopped reading".into()),
+        }
+    }
+
+    /// Append a frame to `dst` in the encoding negotiated so far.
+    fn encode_frame(
+        &mut self,
+        frame: &Frame,
+        dst: &mut Vec<u8>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let res = match frame {
+            Frame::Response(res) => res,
+            _ => return Err("not implemented".into()),
+        };
+        let mut bytes = match self.get_protocol_version() {
+            PROTOCOL_VERSION => res.to_envelope_bytes(),
+            _ => res.to_bytes(),
+        }
+        .map_err(|e| e.to_string())?;
+        if self.compression && bytes.len() > COMPRESSION_THRESHOLD {
+            bytes = Frame::compress_envelope(&bytes).map_err(|e| e.to_string())?;
+        }
+
+        // The connect response is the last frame in the old encoding, the
+        // client switches as soon as it has read it, so any frames batched
+        // after it already use the new one.
+        if let ResponseData::Connect(connect) = res.get_data().as_ref() {
+            if connect.success && connect.protocol_version == PROTOCOL_VERSION {
+                self.protocol_version.send_replace(PROTOCOL_VERSION);
+                self.compression = connect.compression() == Compression::Lz4;
             }
-            // Not enough data has been buffered
-            Err(crate::frame::Error::Incomplete) => Ok(None),
-            // client sent invalid data
-            Err(crate::frame::Error::ProtobufDecodeFailed(_)) => Ok(None),
-            // An error was encountered
-            Err(crate::frame::Error::Other(e)) => Err(e),
         }
+        dst.extend_from_slice(&bytes);
+        Ok(())
     }
 
-    /// Write a frame to the connection.
-    pub async fn write_frame(&self, frame: &Frame) -> Result<(), Box<dyn std::error::Error>> {
-        match frame {
-            Frame::Response(res) => {
-                let mut buf = match res.get_data().as_ref() {
-                    crate::frame::ResponseData::Connect(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::Disconnect(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::Heartbeat(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::CreateLobby(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::JoinLobby(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::QuitLobby(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::ListLobby(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::Ready(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::StartGame(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::Error(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::LobbyBroadcast(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::SetTile(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::FinishTurn(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::GetNewCard(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::GameBroadcast(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::Cancel(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                    crate::frame::ResponseData::Exit(res) => {
-                        let mut buf = BytesMut::with_capacity(res.encoded_len());
-                        res.encode(&mut buf)?;
-                        buf
-                    }
-                };
+    pub async fn shutdown(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.writer.shutdown().await?;
+        Ok(())
+    }
 
-                {
-                    let mut writer = self.writer.lock().await;
-                    writer.write_u32_le(res.get_state()).await?;
-                    writer.write_u32_le(buf.len().try_into().unwrap()).await?;
-                    writer.write_buf(&mut buf).await?;
-                }
+    pub fn get_protocol_version(&self) -> u32 {
+        *self.protocol_version.borrow()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, sync::Arc};
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::{
+        frame::{Request, RequestData, Response},
+        model::{
+            control::{
+                connect::{ConnectRequest, ConnectResponse},
+                heartbeat::HeartbeatRequest,
+                notice::Notice,
+            },
+            state::State,
+        },
+    };
+
+    async fn connect(
+    ) -> Result<(TcpStream, ConnectionReader, ConnectionWriter), Box<dyn Error + Send + Sync>> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let client = TcpStream::connect(listener.local_addr()?).await?;
+        let (socket, _) = listener.accept().await?;
+        let (reader, writer) = split(socket, Duration::from_secs(5), Duration::from_millis(100));
+        Ok((client, reader, writer))
+    }
+
+    #[tokio::test]
+    async fn read_frame_with_legacy_request_should_return_request(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (mut client, mut reader, _writer) = connect().await?;
+        let req = Request::new(
+            1,
+            Arc::new(RequestData::Connect(ConnectRequest {
+                name: String::from("test"),
+                protocol_version: 0,
+                compression: 0,
+                capabilities: 0,
+                token: String::new(),
+            })),
+        );
+        client.write_all(&req.to_bytes()?).await?;
+        match reader.read_frame().await? {
+            Some(Frame::Request(parsed)) => assert_eq!(parsed, req),
+            _ => panic!("invalid frame"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_frame_after_writer_negotiates_envelope_should_parse_envelope(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (mut client, mut reader, mut writer) = connect().await?;
+        writer
+            .write_frame(&Frame::Response(Response::new(
+                1,
+                Arc::new(ResponseData::Connect(ConnectResponse {
+                    success: true,
+                    player: None,
+                    protocol_version: PROTOCOL_VERSION,
+                    compression: Compression::None as i32,
+                    game: None,
+                    capabilities: 0,
+                    scopes: 0,
+                    lobby: None,
+                })),
+            )))
+            .await?;
+        assert_eq!(reader.get_protocol_version(), PROTOCOL_VERSION);
+        let req = Request::new(
+            2,
+            Arc::new(RequestData::Heartbeat(HeartbeatRequest::default())),
+        );
+        client.write_all(&req.to_envelope_bytes()?).await?;
+        match reader.read_frame().await? {
+            Some(Frame::Request(parsed)) => assert_eq!(parsed, req),
+            _ => panic!("invalid frame"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_frame_with_malformed_frame_should_fail(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (mut client, mut reader, _writer) = connect().await?;
+        client.write_all(&[0xff, 0, 0, 0]).await?;
+        assert!(reader.read_frame().await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_frame_with_closed_peer_should_return_none(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (client, mut reader, _writer) = connect().await?;
+        drop(client);
+        assert!(reader.read_frame().await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_frames_should_send_frames_in_order() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let (mut client, _reader, mut writer) = connect().await?;
+        let frames = ["a", "b", "c"]
+            .iter()
+            .map(|message| {
+                Response::event(
+                    State::Notice,
+                    Arc::new(ResponseData::Notice(Notice {
+                        message: String::from(*message),
+                        maintenance: false,
+                        redirect: String::new(),
+                    })),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut expected = Vec::new();
+        for frame in &frames {
+            expected.extend(frame.to_bytes()?);
+        }
+        writer
+            .write_frames(&frames.into_iter().map(Frame::Response).collect::<Vec<_>>())
+            .await?;
+        let mut received = vec![0; expected.len()];
+        client.read_exact(&mut received).await?;
+        assert_eq!(received, expected);
+        Ok(())
+    }
 
-                Ok(())
+    #[tokio::test]
+    async fn write_frame_to_client_not_reading_should_time_out(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (_client, _reader, mut writer) = connect().await?;
+        let frame = Frame::Response(Response::event(
+            State::Notice,
+            Arc::new(ResponseData::Notice(Notice {
+                message: "a".repeat(64 * 1024),
+                maintenance: false,
+                redirect: String::new(),
+            })),
+        ));
+        // Keep writing until the socket buffers are full and a write stalls
+        for _ in 0..1024 {
+            if writer.write_frame(&frame).await.is_err() {
+                return Ok(());
             }
-            _ => Err("not implemented".into()),
         }
+        panic!("write never timed out");
     }
 }
@@ -1,4 +1,8 @@
+pub mod admin;
+pub mod chat;
 pub mod control;
 pub mod controller;
 pub mod game;
+pub mod leaderboard;
 pub mod lobby;
+pub mod player;
@@ -0,0 +1,103 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::announce::AnnounceResponse,
+    router::RequestContext,
+    service::admin_service::AdminService,
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct AnnounceController {
+    admin_service: Arc<AdminService>,
+}
+
+impl AnnounceController {
+    pub fn new(admin_service: Arc<AdminService>) -> Self {
+        Self { admin_service }
+    }
+}
+
+impl PrintableController for AnnounceController {}
+
+impl Controller for AnnounceController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminAnnounce(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        if req.set_motd {
+            self.admin_service.set_motd(req.message.clone());
+            if req.message.is_empty() {
+                return Ok(ResponseData::AdminAnnounce(AnnounceResponse {
+                    success: true,
+                    recipients: 0,
+                }));
+            }
+        }
+        let recipients = self
+            .admin_service
+            .announce(&req.message, self.admin_service.get_maintenance().is_some());
+        Ok(ResponseData::AdminAnnounce(AnnounceResponse {
+            success: true,
+            recipients,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::{
+        model::admin::announce::AnnounceRequest,
+        service::{
+            game_service::GameService, lobby_service::LobbyService, player_service::PlayerService,
+        },
+    };
+
+    #[test]
+    fn handle_request_with_online_players_should_count_recipients(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        player_service.add_player(0, String::from("test"));
+        player_service.add_player(1, String::from("test1"));
+        let controller = AnnounceController::new(Arc::new(AdminService::new(
+            Some(String::from("secret")),
+            player_service,
+        )));
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::AdminAnnounce(AnnounceRequest {
+                    token: String::from("secret"),
+                    message: String::from("hello"),
+                    set_motd: false,
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        assert_eq!(
+            res,
+            ResponseData::AdminAnnounce(AnnounceResponse {
+                success: true,
+                recipients: 2,
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::ban::BanResponse,
+    router::RequestContext,
+    service::admin_service::AdminService,
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct BanController {
+    admin_service: Arc<AdminService>,
+}
+
+impl BanController {
+    pub fn new(admin_service: Arc<AdminService>) -> Self {
+        Self { admin_service }
+    }
+}
+
+impl PrintableController for BanController {}
+
+impl Controller for BanController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminBan(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        if req.name.is_empty() {
+            return Err("Name is empty".into());
+        }
+        self.admin_service.ban(req.name.clone());
+        Ok(ResponseData::AdminBan(BanResponse { success: true }))
+    }
+}
@@ -0,0 +1,94 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::close_game::CloseGameResponse,
+    router::RequestContext,
+    service::{admin_service::AdminService, game_service::GameService},
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct CloseGameController {
+    game_service: Arc<GameService>,
+    admin_service: Arc<AdminService>,
+}
+
+impl CloseGameController {
+    pub fn new(game_service: Arc<GameService>, admin_service: Arc<AdminService>) -> Self {
+        Self {
+            game_service,
+            admin_service,
+        }
+    }
+}
+
+impl PrintableController for CloseGameController {}
+
+impl Controller for CloseGameController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminCloseGame(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        self.game_service.close_game(req.game_id)?;
+        Ok(ResponseData::AdminCloseGame(CloseGameResponse {
+            success: true,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::{
+        lobby::lobby::Lobby,
+        model::admin::close_game::CloseGameRequest,
+        player::Player,
+        service::{lobby_service::LobbyService, player_service::PlayerService},
+    };
+
+    #[tokio::test]
+    async fn handle_request_with_running_game_should_remove_game(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player = Arc::new(Player::new(0, String::from("test")));
+        let lobby = Arc::new(Lobby::new(0, 4, player.clone()));
+        lobby.get_player(0).unwrap().set_ready(true);
+        let game = GameService::start_game(game_service.clone(), player.clone(), lobby)?;
+        let controller = CloseGameController::new(
+            game_service.clone(),
+            Arc::new(AdminService::new(
+                Some(String::from("secret")),
+                Arc::new(PlayerService::new(
+                    Arc::new(LobbyService::new()),
+                    game_service.clone(),
+                )),
+            )),
+        );
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::AdminCloseGame(CloseGameRequest {
+                    token: String::from("secret"),
+                    game_id: game.id,
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        assert!(game_service.get_game(game.id).is_none());
+        assert!(player.get_game().is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::handoff::HandoffResponse,
+    router::RequestContext,
+    service::{
+        admin_service::AdminService, cluster_service::ClusterEvent, lobby_service::LobbyService,
+        player_service::PlayerService,
+    },
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct HandoffController {
+    player_service: Arc<PlayerService>,
+    lobby_service: Arc<LobbyService>,
+    admin_service: Arc<AdminService>,
+}
+
+impl HandoffController {
+    pub fn new(
+        player_service: Arc<PlayerService>,
+        lobby_service: Arc<LobbyService>,
+        admin_service: Arc<AdminService>,
+    ) -> Self {
+        Self {
+            player_service,
+            lobby_service,
+            admin_service,
+        }
+    }
+}
+
+impl PrintableController for HandoffController {}
+
+impl Controller for HandoffController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminHandoff(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        let cluster_service = self.player_service.get_cluster_service();
+        let node = u16::try_from(req.node).map_err(|_| "Unknown node")?;
+        if node == cluster_service.get_node_id() {
+            return Err("Can't hand lobbies off to this node".into());
+        }
+        let address = match cluster_service.get_node_address(node) {
+            Some(address) => address,
+            None => return Err("Unknown node".into()),
+        };
+        let (mut lobbies, mut players) = (0, 0);
+        for lobby in self.lobby_service.get_lobbies() {
+            if lobby.is_in_game() {
+                continue;
+            }
+            let lobby_players = lobby.get_players();
+            let handoff = match self.lobby_service.hand_off(lobby) {
+                Ok(handoff) => handoff,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to hand off lobby");
+                    continue;
+                }
+            };
+            cluster_service.record_move(handoff.lobby_id, node);
+            cluster_service.publish(ClusterEvent::Handoff {
+                node,
+                lobby: handoff,
+            });
+            lobbies += 1;
+            for lobby_player in lobby_players {
+                self.player_service
+                    .redirect(lobby_player.player.clone(), address.clone())?;
+                players += 1;
+            }
+        }
+        tracing::info!(node, lobbies, players, "hand off lobbies");
+        Ok(ResponseData::AdminHandoff(HandoffResponse {
+            success: true,
+            lobbies,
+            players,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::{
+        model::admin::handoff::HandoffRequest,
+        service::{cluster_service::ClusterService, game_service::GameService},
+    };
+
+    #[test]
+    fn handle_request_with_own_or_unknown_node_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(
+            PlayerService::new(
+                lobby_service.clone(),
+                Arc::new(GameService::new(HashSet::new())),
+            )
+            .with_cluster_service(Arc::new(ClusterService::local(1))),
+        );
+        let controller = HandoffController::new(
+            player_service.clone(),
+            lobby_service.clone(),
+            Arc::new(AdminService::new(
+                Some(String::from("secret")),
+                player_service.clone(),
+            )),
+        );
+        let player = player_service.add_player(0, String::from("test"));
+        lobby_service.create_lobby(player, 4)?;
+        for node in [1, 2, 1 << 16] {
+            let res = controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::AdminHandoff(HandoffRequest {
+                        token: String::from("secret"),
+                        node,
+                    })),
+                ),
+                RequestContext::new(0),
+            );
+            assert!(res.is_err());
+        }
+        assert_eq!(lobby_service.get_lobbies().len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::inspect::{inspect_request::Target, GameDetail, InspectResponse, LobbyDetail},
+    router::RequestContext,
+    service::{
+        admin_service::AdminService, game_service::GameService, lobby_service::LobbyService,
+    },
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct InspectController {
+    lobby_service: Arc<LobbyService>,
+    game_service: Arc<GameService>,
+    admin_service: Arc<AdminService>,
+}
+
+impl InspectController {
+    pub fn new(
+        lobby_service: Arc<LobbyService>,
+        game_service: Arc<GameService>,
+        admin_service: Arc<AdminService>,
+    ) -> Self {
+        Self {
+            lobby_service,
+            game_service,
+            admin_service,
+        }
+    }
+}
+
+impl PrintableController for InspectController {}
+
+impl Controller for InspectController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminInspect(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        let res = match req.target {
+            Some(Target::LobbyId(id)) => match self.lobby_service.get_lobby(id) {
+                Some(lobby) => InspectResponse {
+                    success: true,
+                    lobby: Some(LobbyDetail::from(lobby.as_ref())),
+                    game: None,
+                },
+                None => return Err(ServerError::LobbyNotFound),
+            },
+            Some(Target::GameId(id)) => match self.game_service.get_game(id) {
+                Some(game) => InspectResponse {
+                    success: true,
+                    lobby: None,
+                    game: Some(GameDetail::from(game.as_ref())),
+                },
+                None => return Err(ServerError::GameNotFound),
+            },
+            None => return Err("No target to inspect".into()),
+        };
+        Ok(ResponseData::AdminInspect(res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::{
+        model::admin::inspect::InspectRequest, player::Player,
+        service::player_service::PlayerService,
+    };
+
+    #[test]
+    fn handle_request_with_lobby_id_should_return_lobby_detail(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby =
+            lobby_service.create_lobby(Arc::new(Player::new(0, String::from("test"))), 4)?;
+        let controller = InspectController::new(
+            lobby_service.clone(),
+            game_service.clone(),
+            Arc::new(AdminService::new(
+                Some(String::from("secret")),
+                Arc::new(PlayerService::new(lobby_service, game_service)),
+            )),
+        );
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::AdminInspect(InspectRequest {
+                    token: String::from("secret"),
+                    target: Some(Target::LobbyId(lobby.get_id())),
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        match res {
+            ResponseData::AdminInspect(res) => {
+                let detail = res.lobby.unwrap();
+                assert_eq!(detail.max_players, 4);
+                assert_eq!(detail.members.len(), 1);
+            }
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_unknown_game_should_return_error() {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let controller = InspectController::new(
+            lobby_service.clone(),
+            game_service.clone(),
+            Arc::new(AdminService::new(
+                Some(String::from("secret")),
+                Arc::new(PlayerService::new(lobby_service, game_service)),
+            )),
+        );
+        assert!(controller
+            .handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::AdminInspect(InspectRequest {
+                        token: String::from("secret"),
+                        target: Some(Target::GameId(3)),
+                    })),
+                ),
+                RequestContext::new(0),
+            )
+            .is_err());
+    }
+}
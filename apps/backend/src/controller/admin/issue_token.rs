@@ -0,0 +1,115 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::issue_token::IssueTokenResponse,
+    router::RequestContext,
+    service::admin_service::AdminService,
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct IssueTokenController {
+    admin_service: Arc<AdminService>,
+}
+
+impl IssueTokenController {
+    pub fn new(admin_service: Arc<AdminService>) -> Self {
+        Self { admin_service }
+    }
+}
+
+impl PrintableController for IssueTokenController {}
+
+impl Controller for IssueTokenController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminIssueToken(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        if req.name.is_empty() {
+            return Err("Name is empty".into());
+        }
+        let api_token = self.admin_service.issue_token(req.name.clone(), req.scopes);
+        Ok(ResponseData::AdminIssueToken(IssueTokenResponse {
+            success: true,
+            api_token,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::{
+        model::{admin::issue_token::IssueTokenRequest, control::connect::Scope},
+        service::{
+            game_service::GameService, lobby_service::LobbyService, player_service::PlayerService,
+        },
+    };
+
+    fn new_controller() -> IssueTokenController {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        IssueTokenController::new(Arc::new(AdminService::new(
+            Some(String::from("secret")),
+            player_service,
+        )))
+    }
+
+    #[test]
+    fn handle_request_with_correct_token_should_issue_api_token(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let controller = new_controller();
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::AdminIssueToken(IssueTokenRequest {
+                    token: String::from("secret"),
+                    name: String::from("bot"),
+                    scopes: Scope::Spectate as u64 | 1 << 40,
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        let api_token = match res {
+            ResponseData::AdminIssueToken(res) => res.api_token,
+            _ => panic!("invalid response"),
+        };
+        let api_token = controller.admin_service.get_api_token(&api_token).unwrap();
+        assert_eq!(api_token.name, "bot");
+        assert_eq!(api_token.scopes, Scope::Spectate as u64);
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_wrong_token_should_return_error() {
+        let controller = new_controller();
+        assert!(controller
+            .handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::AdminIssueToken(IssueTokenRequest {
+                        token: String::from("guess"),
+                        name: String::from("bot"),
+                        scopes: Scope::ALL,
+                    })),
+                ),
+                RequestContext::new(0),
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::kick::KickResponse,
+    router::RequestContext,
+    service::admin_service::AdminService,
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct KickController {
+    admin_service: Arc<AdminService>,
+}
+
+impl KickController {
+    pub fn new(admin_service: Arc<AdminService>) -> Self {
+        Self { admin_service }
+    }
+}
+
+impl PrintableController for KickController {}
+
+impl Controller for KickController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminKick(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        self.admin_service
+            .kick(req.player_id, "Kicked by an operator")?;
+        Ok(ResponseData::AdminKick(KickResponse { success: true }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::{
+        model::admin::kick::KickRequest,
+        service::{
+            game_service::GameService, lobby_service::LobbyService, player_service::PlayerService,
+        },
+    };
+
+    #[test]
+    fn handle_request_with_online_player_should_remove_player(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        player_service.add_player(1, String::from("test"));
+        let controller = KickController::new(Arc::new(AdminService::new(
+            Some(String::from("secret")),
+            player_service.clone(),
+        )));
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::AdminKick(KickRequest {
+                    token: String::from("secret"),
+                    player_id: 1,
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        assert!(player_service.get_player(1).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_wrong_token_should_return_error() {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        player_service.add_player(1, String::from("test"));
+        let controller = KickController::new(Arc::new(AdminService::new(
+            Some(String::from("secret")),
+            player_service.clone(),
+        )));
+        assert!(controller
+            .handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::AdminKick(KickRequest {
+                        token: String::from("guess"),
+                        player_id: 1,
+                    })),
+                ),
+                RequestContext::new(0),
+            )
+            .is_err());
+        assert!(player_service.get_player(1).is_some());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::list_players::{ListPlayersResponse, OnlinePlayer},
+    router::RequestContext,
+    service::{admin_service::AdminService, player_service::PlayerService},
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct ListPlayersController {
+    player_service: Arc<PlayerService>,
+    admin_service: Arc<AdminService>,
+}
+
+impl ListPlayersController {
+    pub fn new(player_service: Arc<PlayerService>, admin_service: Arc<AdminService>) -> Self {
+        Self {
+            player_service,
+            admin_service,
+        }
+    }
+}
+
+impl PrintableController for ListPlayersController {}
+
+impl Controller for ListPlayersController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminListPlayers(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        let mut players = self
+            .player_service
+            .get_players()
+            .iter()
+            .map(|player| OnlinePlayer {
+                id: player.id,
+                name: player.name.clone(),
+                lobby_id: player.get_lobby().map(|lobby| lobby.get_id()),
+                game_id: player.get_game().map(|game| game.id),
+            })
+            .collect::<Vec<_>>();
+        players.sort_by_key(|player| player.id);
+        Ok(ResponseData::AdminListPlayers(ListPlayersResponse {
+            success: true,
+            players,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::{
+        model::admin::list_players::ListPlayersRequest,
+        service::{game_service::GameService, lobby_service::LobbyService},
+    };
+
+    #[test]
+    fn handle_request_with_player_in_lobby_should_return_lobby_id(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        let player = player_service.add_player(0, String::from("test"));
+        player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(player, 4)?;
+        let controller = ListPlayersController::new(
+            player_service.clone(),
+            Arc::new(AdminService::new(
+                Some(String::from("secret")),
+                player_service,
+            )),
+        );
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::AdminListPlayers(ListPlayersRequest {
+                    token: String::from("secret"),
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        match res {
+            ResponseData::AdminListPlayers(res) => {
+                assert_eq!(res.players.len(), 2);
+                assert_eq!(res.players[0].lobby_id, Some(lobby.get_id()));
+                assert_eq!(res.players[1].lobby_id, None);
+            }
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::maintenance::MaintenanceResponse,
+    router::RequestContext,
+    service::admin_service::AdminService,
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct MaintenanceController {
+    admin_service: Arc<AdminService>,
+}
+
+impl MaintenanceController {
+    pub fn new(admin_service: Arc<AdminService>) -> Self {
+        Self { admin_service }
+    }
+}
+
+impl PrintableController for MaintenanceController {}
+
+impl Controller for MaintenanceController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminMaintenance(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        self.admin_service
+            .set_maintenance(req.enabled, req.message.clone());
+        Ok(ResponseData::AdminMaintenance(MaintenanceResponse {
+            success: true,
+            enabled: req.enabled,
+        }))
+    }
+}
@@ -0,0 +1,45 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::reload::ReloadResponse,
+    router::RequestContext,
+    service::admin_service::AdminService,
+};
+
+use crate::controller::controller::Controller;
+
+/// Has the server load its config and dictionaries again, the same as
+/// sending it SIGHUP. The reload runs after the response, its outcome goes to
+/// the server log.
+#[derive(Debug, Clone)]
+pub struct ReloadController {
+    admin_service: Arc<AdminService>,
+}
+
+impl ReloadController {
+    pub fn new(admin_service: Arc<AdminService>) -> Self {
+        Self { admin_service }
+    }
+}
+
+impl PrintableController for ReloadController {}
+
+impl Controller for ReloadController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminReload(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        self.admin_service.request_reload();
+        Ok(ResponseData::AdminReload(ReloadResponse { success: true }))
+    }
+}
@@ -0,0 +1,101 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::admin::revoke_token::RevokeTokenResponse,
+    router::RequestContext,
+    service::admin_service::AdminService,
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct RevokeTokenController {
+    admin_service: Arc<AdminService>,
+}
+
+impl RevokeTokenController {
+    pub fn new(admin_service: Arc<AdminService>) -> Self {
+        Self { admin_service }
+    }
+}
+
+impl PrintableController for RevokeTokenController {}
+
+impl Controller for RevokeTokenController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AdminRevokeToken(req) => req,
+            _ => panic!("invalid request"),
+        };
+        self.admin_service.authorize(&req.token)?;
+        Ok(ResponseData::AdminRevokeToken(RevokeTokenResponse {
+            success: true,
+            revoked: self.admin_service.revoke_token(&req.api_token),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::{
+        model::{admin::revoke_token::RevokeTokenRequest, control::connect::Scope},
+        service::{
+            game_service::GameService, lobby_service::LobbyService, player_service::PlayerService,
+        },
+    };
+
+    #[test]
+    fn handle_request_with_issued_token_should_revoke_it(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        let controller = RevokeTokenController::new(Arc::new(AdminService::new(
+            Some(String::from("secret")),
+            player_service,
+        )));
+        let api_token = controller
+            .admin_service
+            .issue_token(String::from("bot"), Scope::Play as u64);
+        let revoke = || {
+            controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::AdminRevokeToken(RevokeTokenRequest {
+                        token: String::from("secret"),
+                        api_token: api_token.clone(),
+                    })),
+                ),
+                RequestContext::new(0),
+            )
+        };
+        assert_eq!(
+            revoke()?,
+            ResponseData::AdminRevokeToken(RevokeTokenResponse {
+                success: true,
+                revoked: true,
+            })
+        );
+        assert!(controller.admin_service.get_api_token(&api_token).is_none());
+        assert_eq!(
+            revoke()?,
+            ResponseData::AdminRevokeToken(RevokeTokenResponse {
+                success: true,
+                revoked: false,
+            })
+        );
+        Ok(())
+    }
+}
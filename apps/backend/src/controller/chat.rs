@@ -0,0 +1,2 @@
+pub mod quick_chat;
+pub mod send;
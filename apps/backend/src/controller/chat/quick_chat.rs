@@ -0,0 +1,112 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::chat::quick_chat::{QuickChat, SendQuickChatResponse};
+use crate::service::chat_service::ChatService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct QuickChatController {
+    player_service: Arc<PlayerService>,
+    chat_service: Arc<ChatService>,
+}
+
+impl QuickChatController {
+    pub fn new(player_service: Arc<PlayerService>, chat_service: Arc<ChatService>) -> Self {
+        Self {
+            player_service,
+            chat_service,
+        }
+    }
+}
+
+impl PrintableController for QuickChatController {}
+
+impl Controller for QuickChatController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::SendQuickChat(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let phrase = match QuickChat::from_i32(req.phrase) {
+            Some(phrase) => phrase,
+            None => return Err("Unknown quick chat phrase".into()),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let message = self.chat_service.send_quick(player, phrase)?;
+        Ok(ResponseData::SendQuickChat(SendQuickChatResponse {
+            success: true,
+            message,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        config::ChatConfig,
+        model::chat::quick_chat::SendQuickChatRequest,
+        service::broadcast_service::BroadcastService,
+        service::{game_service::GameService, lobby_service::LobbyService},
+        storage::memory::MemoryStorage,
+    };
+
+    use super::*;
+
+    fn send(controller: &QuickChatController, phrase: i32) -> Result<ResponseData, ServerError> {
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::SendQuickChat(SendQuickChatRequest { phrase })),
+            ),
+            RequestContext::new(0),
+        )
+    }
+
+    #[test]
+    fn handle_request_with_phrase_should_return_its_text_and_reject_unknown_ids(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let controller = QuickChatController::new(
+            Arc::new(PlayerService::new(
+                lobby_service.clone(),
+                Arc::new(GameService::new(HashSet::new())),
+            )),
+            Arc::new(ChatService::new(
+                &ChatConfig::default(),
+                Arc::new(MemoryStorage::new()),
+                Arc::new(BroadcastService::new()),
+            )),
+        );
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        lobby_service.create_lobby(player, 4)?;
+        assert_eq!(
+            send(&controller, QuickChat::WellPlayed as i32)?,
+            ResponseData::SendQuickChat(SendQuickChatResponse {
+                success: true,
+                message: String::from("Well played!"),
+            })
+        );
+        assert!(send(&controller, 99).is_err());
+        Ok(())
+    }
+}
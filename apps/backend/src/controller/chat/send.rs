@@ -0,0 +1,138 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::chat::send::SendChatResponse;
+use crate::service::chat_service::ChatService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct SendController {
+    player_service: Arc<PlayerService>,
+    chat_service: Arc<ChatService>,
+}
+
+impl SendController {
+    pub fn new(player_service: Arc<PlayerService>, chat_service: Arc<ChatService>) -> Self {
+        Self {
+            player_service,
+            chat_service,
+        }
+    }
+}
+
+impl PrintableController for SendController {}
+
+impl Controller for SendController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::SendChat(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let message = self.chat_service.send(player, &req.message)?;
+        Ok(ResponseData::SendChat(SendChatResponse {
+            success: true,
+            message,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        config::{ChatConfig, ChatFilter},
+        model::chat::send::SendChatRequest,
+        service::broadcast_service::BroadcastService,
+        service::{game_service::GameService, lobby_service::LobbyService},
+        storage::memory::MemoryStorage,
+    };
+
+    use super::*;
+
+    fn controller(filter: ChatFilter) -> (SendController, Arc<LobbyService>) {
+        let lobby_service = Arc::new(LobbyService::new());
+        let controller = SendController::new(
+            Arc::new(PlayerService::new(
+                lobby_service.clone(),
+                Arc::new(GameService::new(HashSet::new())),
+            )),
+            Arc::new(ChatService::new(
+                &ChatConfig {
+                    filter,
+                    blocked_words: vec![String::from("darn")],
+                },
+                Arc::new(MemoryStorage::new()),
+                Arc::new(BroadcastService::new()),
+            )),
+        );
+        (controller, lobby_service)
+    }
+
+    fn send(controller: &SendController, message: &str) -> Result<ResponseData, ServerError> {
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::SendChat(SendChatRequest {
+                    message: String::from(message),
+                })),
+            ),
+            RequestContext::new(0),
+        )
+    }
+
+    #[test]
+    fn handle_request_with_blocked_word_should_return_masked_message(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let (controller, lobby_service) = controller(ChatFilter::Mask);
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        lobby_service.create_lobby(player, 4)?;
+        assert_eq!(
+            send(&controller, "oh darn ")?,
+            ResponseData::SendChat(SendChatResponse {
+                success: true,
+                message: String::from("oh ****"),
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_reject_filter_should_return_error(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let (controller, lobby_service) = controller(ChatFilter::Reject);
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        lobby_service.create_lobby(player, 4)?;
+        assert!(send(&controller, "oh darn").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_without_lobby_should_return_error() {
+        let (controller, _) = controller(ChatFilter::Mask);
+        controller
+            .player_service
+            .add_player(0, String::from("test"));
+        assert!(send(&controller, "hello").is_err());
+    }
+}
@@ -1,3 +1,6 @@
 pub mod connect;
 pub mod disconnect;
 pub mod heartbeat;
+pub mod preferences;
+pub mod server_info;
+pub mod time_sync;
@@ -1,11 +1,15 @@
+use crate::error::ServerError;
 use std::sync::Arc;
 
 use crate::{
     controller::controller::PrintableController,
-    frame::{Request, RequestData, ResponseData},
-    model::control::connect::ConnectResponse,
+    frame::{self, Request, RequestData, ResponseData, LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION},
+    model::{
+        control::connect::{Capability, Compression, ConnectResponse, Scope},
+        game::start::StartResponse,
+    },
     router::RequestContext,
-    service::player_service::PlayerService,
+    service::{admin_service::AdminService, player_service::PlayerService},
 };
 
 use crate::controller::controller::Controller;
@@ -13,11 +17,15 @@ use crate::controller::controller::Controller;
 #[derive(Debug, Clone)]
 pub struct ConnectController {
     player_service: Arc<PlayerService>,
+    admin_service: Arc<AdminService>,
 }
 
 impl ConnectController {
-    pub fn new(player_service: Arc<PlayerService>) -> Self {
-        Self { player_service }
+    pub fn new(player_service: Arc<PlayerService>, admin_service: Arc<AdminService>) -> Self {
+        Self {
+            player_service,
+            admin_service,
+        }
     }
 }
 
@@ -28,25 +36,70 @@ impl Controller for ConnectController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         let data = req.get_data();
         let req = match data.as_ref() {
             RequestData::Connect(req) => req,
             _ => panic!("invalid request"),
         };
+        let protocol_version = req
+            .protocol_version
+            .clamp(LEGACY_PROTOCOL_VERSION, PROTOCOL_VERSION);
+        if protocol_version < PROTOCOL_VERSION && cfg!(not(feature = "legacy-frame")) {
+            return Err(ServerError::Frame(frame::Error::UnsupportedProtocol(
+                req.protocol_version,
+            )));
+        }
+        // Compressed frames are flagged in the envelope length prefix, the
+        // legacy header has no room for it
+        let compression = match protocol_version {
+            PROTOCOL_VERSION => req.compression(),
+            _ => Compression::None,
+        };
+        if let Some(message) = self.admin_service.get_maintenance() {
+            return Err(format!("Server is under maintenance: {}", message).into());
+        }
+        // Bots and services skip the name and play under their token's
+        let (name, scopes) = match req.token.is_empty() {
+            true => (req.name.clone(), Scope::INTERACTIVE),
+            false => match self.admin_service.get_api_token(&req.token) {
+                Some(api_token) => (api_token.name, api_token.scopes),
+                None => return Err(ServerError::Unauthenticated),
+            },
+        };
+        if self.admin_service.is_banned(&name) {
+            return Err("Player is banned".into());
+        }
         let player = match self.player_service.get_player(context.client_id) {
             Some(_) => return Err("client already connected".into()),
-            None => self.player_service.add_player(
+            None => self.player_service.open_session(
                 context.client_id,
-                req.name.clone(),
+                name,
                 #[cfg(not(test))]
                 context.sender,
-            ),
+            )?,
         };
+        let capabilities = req.capabilities & Capability::ALL;
+        self.player_service
+            .get_broadcast_service()
+            .set_capabilities(player.id, capabilities);
+        let game = player.get_game().and_then(|game| {
+            let game_player = game.get_player(player.id)?;
+            Some(StartResponse::new(&game, game_player))
+        });
+        let lobby = player
+            .get_lobby()
+            .map(crate::model::lobby::lobby::Lobby::from);
 
         Ok(ResponseData::Connect(ConnectResponse {
             success: true,
             player: Some(crate::model::player::player::Player::from(player)),
+            protocol_version,
+            compression: compression as i32,
+            game,
+            capabilities,
+            scopes,
+            lobby,
         }))
     }
 }
@@ -60,21 +113,33 @@ mod tests {
     };
     use std::{collections::HashSet, error::Error};
 
+    fn new_controller() -> ConnectController {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        ConnectController::new(
+            player_service.clone(),
+            Arc::new(AdminService::new(None, player_service)),
+        )
+    }
+
     #[test]
     fn handle_request_with_test_user_should_create_test_user(
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let controller = ConnectController::new(Arc::new(PlayerService::new(
-            Arc::new(LobbyService::new()),
-            Arc::new(GameService::new(HashSet::new())),
-        )));
+        let controller = new_controller();
         controller.handle_request(
             Request::new(
                 0,
                 Arc::new(RequestData::Connect(ConnectRequest {
                     name: String::from("test"),
+                    protocol_version: 0,
+                    compression: 0,
+                    capabilities: 0,
+                    token: String::new(),
                 })),
             ),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )?;
         let player = controller.player_service.get_player(0).unwrap();
         assert_eq!(player.id, 0);
@@ -85,18 +150,19 @@ mod tests {
     #[test]
     fn handle_request_with_test_user_who_already_connected_should_return_error(
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let controller = ConnectController::new(Arc::new(PlayerService::new(
-            Arc::new(LobbyService::new()),
-            Arc::new(GameService::new(HashSet::new())),
-        )));
+        let controller = new_controller();
         controller.handle_request(
             Request::new(
                 0,
                 Arc::new(RequestData::Connect(ConnectRequest {
                     name: String::from("test"),
+                    protocol_version: 0,
+                    compression: 0,
+                    capabilities: 0,
+                    token: String::new(),
                 })),
             ),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )?;
         assert!(controller
             .handle_request(
@@ -104,11 +170,215 @@ mod tests {
                     0,
                     Arc::new(RequestData::Connect(ConnectRequest {
                         name: String::from("test"),
+                        protocol_version: 0,
+                        compression: 0,
+                        capabilities: 0,
+                        token: String::new(),
                     }))
                 ),
-                RequestContext { client_id: 0 },
+                RequestContext::new(0),
             )
             .is_err());
         Ok(())
     }
+
+    #[test]
+    fn handle_request_with_newer_protocol_version_should_negotiate_current_version(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let controller = new_controller();
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::Connect(ConnectRequest {
+                    name: String::from("test"),
+                    protocol_version: PROTOCOL_VERSION + 1,
+                    compression: 0,
+                    capabilities: 0,
+                    token: String::new(),
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        match res {
+            ResponseData::Connect(res) => assert_eq!(res.protocol_version, PROTOCOL_VERSION),
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_without_protocol_version_should_negotiate_legacy_version(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let controller = new_controller();
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::Connect(ConnectRequest {
+                    name: String::from("test"),
+                    protocol_version: 0,
+                    compression: 0,
+                    capabilities: 0,
+                    token: String::new(),
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        match res {
+            ResponseData::Connect(res) => {
+                assert_eq!(res.protocol_version, LEGACY_PROTOCOL_VERSION)
+            }
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_lz4_on_legacy_protocol_should_not_compress(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let controller = new_controller();
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::Connect(ConnectRequest {
+                    name: String::from("test"),
+                    protocol_version: LEGACY_PROTOCOL_VERSION,
+                    compression: Compression::Lz4 as i32,
+                    capabilities: 0,
+                    token: String::new(),
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        match res {
+            ResponseData::Connect(res) => assert_eq!(res.compression(), Compression::None),
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_lz4_on_current_protocol_should_compress(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let controller = new_controller();
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::Connect(ConnectRequest {
+                    name: String::from("test"),
+                    protocol_version: PROTOCOL_VERSION,
+                    compression: Compression::Lz4 as i32,
+                    capabilities: 0,
+                    token: String::new(),
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        match res {
+            ResponseData::Connect(res) => assert_eq!(res.compression(), Compression::Lz4),
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_unknown_capabilities_should_only_return_known_ones(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let controller = new_controller();
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::Connect(ConnectRequest {
+                    name: String::from("test"),
+                    protocol_version: PROTOCOL_VERSION,
+                    compression: 0,
+                    capabilities: u64::MAX,
+                    token: String::new(),
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        match res {
+            ResponseData::Connect(res) => assert_eq!(res.capabilities, Capability::ALL),
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_banned_name_should_return_error() {
+        let controller = new_controller();
+        controller.admin_service.ban(String::from("test"));
+        assert!(controller
+            .handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::Connect(ConnectRequest {
+                        name: String::from("test"),
+                        protocol_version: 0,
+                        compression: 0,
+                        capabilities: 0,
+                        token: String::new(),
+                    })),
+                ),
+                RequestContext::new(0),
+            )
+            .is_err());
+        assert!(controller.player_service.get_player(0).is_none());
+    }
+
+    #[test]
+    fn handle_request_during_maintenance_should_return_error() {
+        let controller = new_controller();
+        controller
+            .admin_service
+            .set_maintenance(true, String::from("restarting"));
+        assert!(controller
+            .handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::Connect(ConnectRequest {
+                        name: String::from("test"),
+                        protocol_version: 0,
+                        compression: 0,
+                        capabilities: 0,
+                        token: String::new(),
+                    })),
+                ),
+                RequestContext::new(0),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn handle_request_with_api_token_should_connect_under_token_name(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let controller = new_controller();
+        let token = controller
+            .admin_service
+            .issue_token(String::from("bot"), Scope::Play as u64);
+        let connect = |client_id, token: &str| {
+            controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::Connect(ConnectRequest {
+                        name: String::from("test"),
+                        token: token.to_string(),
+                        ..Default::default()
+                    })),
+                ),
+                RequestContext::new(client_id),
+            )
+        };
+        match connect(0, &token)? {
+            ResponseData::Connect(res) => assert_eq!(res.scopes, Scope::Play as u64),
+            _ => panic!("invalid response"),
+        }
+        assert_eq!(controller.player_service.get_player(0).unwrap().name, "bot");
+        match connect(1, "guess") {
+            Err(ServerError::Unauthenticated) => {}
+            _ => panic!("expected an unauthenticated error"),
+        }
+        assert!(controller.player_service.get_player(1).is_none());
+        Ok(())
+    }
 }
@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use std::sync::Arc;
 
 use crate::{
@@ -28,14 +29,14 @@ impl Controller for DisconnectController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         assert!(match *req.get_data() {
             RequestData::Disconnect => true,
             _ => false,
         });
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
         self.player_service.remove_player(player)?;
         Ok(ResponseData::Disconnect(DisconnectResponse {
@@ -64,7 +65,7 @@ mod tests {
             .add_player(0, String::from("test"));
         controller.handle_request(
             Request::new(0, Arc::new(RequestData::Disconnect)),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )?;
         assert!(controller.player_service.get_player(0).is_none());
         Ok(())
@@ -79,7 +80,7 @@ mod tests {
         assert!(controller
             .handle_request(
                 Request::new(0, Arc::new(RequestData::Disconnect)),
-                RequestContext { client_id: 0 }
+                RequestContext::new(0)
             )
             .is_err());
         Ok(())
@@ -1,17 +1,26 @@
+use crate::error::ServerError;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
 use crate::{
     controller::controller::PrintableController,
     frame::{Request, RequestData, ResponseData},
     router::RequestContext,
+    service::player_service::PlayerService,
 };
 
 use crate::controller::controller::Controller;
 use crate::model::control::heartbeat::HeartbeatResponse;
 #[derive(Debug, Clone)]
-pub struct HeartbeatController {}
+pub struct HeartbeatController {
+    player_service: Arc<PlayerService>,
+}
 
 impl HeartbeatController {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(player_service: Arc<PlayerService>) -> Self {
+        Self { player_service }
     }
 }
 
@@ -21,12 +30,76 @@ impl Controller for HeartbeatController {
     fn handle_request(
         &self,
         req: Request,
-        _: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
-        assert!(match *req.get_data() {
-            RequestData::Heartbeat => true,
-            _ => false,
-        });
-        Ok(ResponseData::Heartbeat(HeartbeatResponse { success: true }))
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::Heartbeat(req) => req,
+            _ => panic!("invalid request"),
+        };
+        // Heartbeats can come before connect, there is nobody to time then
+        let player = self.player_service.get_player(context.client_id);
+        if let Some(player) = &player {
+            if req.rtt_ms > 0 {
+                self.player_service.record_rtt(player, req.rtt_ms);
+            }
+        }
+        Ok(ResponseData::Heartbeat(HeartbeatResponse {
+            success: true,
+            sent_at: req.sent_at,
+            server_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            average_rtt_ms: player.and_then(|player| player.get_average_rtt()),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::{
+        model::control::heartbeat::HeartbeatRequest,
+        service::{game_service::GameService, lobby_service::LobbyService},
+    };
+
+    #[test]
+    fn handle_request_with_rtt_should_track_average_latency(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        let player = player_service.add_player(0, String::from("test"));
+        let controller = HeartbeatController::new(player_service);
+        let heartbeat = |sent_at, rtt_ms| {
+            controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::Heartbeat(HeartbeatRequest { sent_at, rtt_ms })),
+                ),
+                RequestContext::new(0),
+            )
+        };
+        match heartbeat(1, 0)? {
+            ResponseData::Heartbeat(res) => {
+                assert_eq!(res.sent_at, 1);
+                assert_eq!(res.average_rtt_ms, None);
+            }
+            _ => panic!("invalid response"),
+        }
+        heartbeat(2, 100)?;
+        match heartbeat(3, 50)? {
+            ResponseData::Heartbeat(res) => assert_eq!(res.average_rtt_ms, Some(75)),
+            _ => panic!("invalid response"),
+        }
+        assert_eq!(
+            crate::model::player::player::Player::from(player).ping_ms,
+            Some(75)
+        );
+        Ok(())
     }
 }
@@ -0,0 +1,127 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::control::preferences::SetPreferencesResponse,
+    router::RequestContext,
+    service::{broadcast_service::BroadcastService, player_service::PlayerService},
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct PreferencesController {
+    player_service: Arc<PlayerService>,
+    broadcast_service: Arc<BroadcastService>,
+}
+
+impl PreferencesController {
+    pub fn new(
+        player_service: Arc<PlayerService>,
+        broadcast_service: Arc<BroadcastService>,
+    ) -> Self {
+        Self {
+            player_service,
+            broadcast_service,
+        }
+    }
+}
+
+impl PrintableController for PreferencesController {}
+
+impl Controller for PreferencesController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::SetPreferences(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        self.broadcast_service
+            .set_preferences(player.id, req.clone());
+        Ok(ResponseData::SetPreferences(SetPreferencesResponse {
+            success: true,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        frame::Response,
+        model::{
+            chat::broadcast::ChatBroadcast, control::preferences::SetPreferencesRequest,
+            state::State,
+        },
+        service::{game_service::GameService, lobby_service::LobbyService},
+    };
+    use std::{collections::HashSet, error::Error};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn handle_request_with_mute_chat_should_drop_chat_for_player(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        let broadcast_service = Arc::new(BroadcastService::new());
+        player_service.add_player(0, String::from("test"));
+        let (tx, mut rx) = mpsc::channel(4);
+        broadcast_service.register(0, tx);
+        let controller = PreferencesController::new(player_service, broadcast_service.clone());
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::SetPreferences(SetPreferencesRequest {
+                    mute_chat: true,
+                    ..Default::default()
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        broadcast_service.send_to_player(
+            0,
+            Response::event(
+                State::Chat,
+                Arc::new(ResponseData::ChatBroadcast(ChatBroadcast {
+                    message: String::from("hello"),
+                    ..Default::default()
+                })),
+            ),
+        );
+        tokio::task::yield_now().await;
+        assert!(rx.try_recv().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_without_player_should_return_error() {
+        let controller = PreferencesController::new(
+            Arc::new(PlayerService::new(
+                Arc::new(LobbyService::new()),
+                Arc::new(GameService::new(HashSet::new())),
+            )),
+            Arc::new(BroadcastService::new()),
+        );
+        assert!(controller
+            .handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::SetPreferences(SetPreferencesRequest::default())),
+                ),
+                RequestContext::new(0),
+            )
+            .is_err());
+    }
+}
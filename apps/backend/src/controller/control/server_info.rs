@@ -0,0 +1,128 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::{
+    config::LiveConfig,
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::control::server_info::ServerInfoResponse,
+    router::RequestContext,
+    service::{
+        admin_service::AdminService, game_service::GameService, lobby_service::LobbyService,
+        player_service::PlayerService,
+    },
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct ServerInfoController {
+    config: Arc<LiveConfig>,
+    admin_service: Arc<AdminService>,
+    player_service: Arc<PlayerService>,
+    lobby_service: Arc<LobbyService>,
+    game_service: Arc<GameService>,
+}
+
+impl ServerInfoController {
+    pub fn new(
+        config: Arc<LiveConfig>,
+        admin_service: Arc<AdminService>,
+        player_service: Arc<PlayerService>,
+        lobby_service: Arc<LobbyService>,
+        game_service: Arc<GameService>,
+    ) -> Self {
+        Self {
+            config,
+            admin_service,
+            player_service,
+            lobby_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for ServerInfoController {}
+
+impl Controller for ServerInfoController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match *req.get_data() {
+            RequestData::ServerInfo => req,
+            _ => panic!("invalid request"),
+        };
+        let config = self.config.get();
+        let players = self.player_service.get_players().len() as u32;
+        Ok(ResponseData::ServerInfo(ServerInfoResponse {
+            success: true,
+            name: config.server_name.clone(),
+            region: config.region.clone(),
+            version: String::from(env!("CARGO_PKG_VERSION")),
+            players,
+            max_players: config.max_connections as u32,
+            lobbies: self.lobby_service.get_lobbies().len() as u32,
+            games: self.game_service.get_gamees().len() as u32,
+            motd: self.admin_service.get_motd().unwrap_or_default(),
+            cluster_players: players
+                + self
+                    .player_service
+                    .get_cluster_service()
+                    .get_remote_players(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use super::*;
+    use crate::config::ServerConfig;
+
+    #[test]
+    fn handle_request_before_connect_should_return_config_and_load(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = ServerInfoController::new(
+            Arc::new(LiveConfig::new(ServerConfig {
+                server_name: String::from("test"),
+                region: String::from("eu-west"),
+                ..ServerConfig::default()
+            })),
+            Arc::new(
+                AdminService::new(None, player_service.clone()).with_motd(String::from("welcome")),
+            ),
+            player_service.clone(),
+            lobby_service.clone(),
+            game_service,
+        );
+        let leader = player_service.add_player(0, String::from("test"));
+        lobby_service.create_lobby(leader, 4)?;
+        player_service.add_player(1, String::from("test1"));
+        let res = match controller.handle_request(
+            Request::new(0, Arc::new(RequestData::ServerInfo)),
+            RequestContext::new(2),
+        )? {
+            ResponseData::ServerInfo(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert_eq!(res.name, "test");
+        assert_eq!(res.region, "eu-west");
+        assert_eq!(res.motd, "welcome");
+        assert_eq!(res.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(res.players, 2);
+        assert_eq!(res.cluster_players, 2);
+        assert_eq!(res.max_players, 1024);
+        assert_eq!(res.lobbies, 1);
+        assert_eq!(res.games, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use crate::error::ServerError;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    controller::controller::PrintableController,
+    frame::{Request, RequestData, ResponseData},
+    model::control::time_sync::TimeSyncResponse,
+    router::RequestContext,
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone, Default)]
+pub struct TimeSyncController {}
+
+impl TimeSyncController {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PrintableController for TimeSyncController {}
+
+impl Controller for TimeSyncController {
+    fn handle_request(
+        &self,
+        req: Request,
+        _context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let server_received_at = unix_millis();
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::TimeSync(req) => req,
+            _ => panic!("invalid request"),
+        };
+        Ok(ResponseData::TimeSync(TimeSyncResponse {
+            success: true,
+            client_sent_at: req.client_sent_at,
+            server_received_at,
+            server_sent_at: unix_millis(),
+        }))
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, sync::Arc};
+
+    use super::*;
+    use crate::model::control::time_sync::TimeSyncRequest;
+
+    #[test]
+    fn handle_request_should_echo_client_time_with_server_times(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let before = unix_millis();
+        let res = match TimeSyncController::new().handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::TimeSync(TimeSyncRequest { client_sent_at: 1 })),
+            ),
+            RequestContext::new(0),
+        )? {
+            ResponseData::TimeSync(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert_eq!(res.client_sent_at, 1);
+        assert!(before <= res.server_received_at);
+        assert!(res.server_received_at <= res.server_sent_at);
+        assert!(res.server_sent_at <= unix_millis());
+        Ok(())
+    }
+}
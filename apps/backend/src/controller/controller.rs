@@ -1,8 +1,8 @@
 use crate::{
+    error::ServerError,
     frame::{Request, ResponseData},
     router::RequestContext,
 };
-use std::error::Error;
 use std::fmt::Debug;
 
 pub trait Controller {
@@ -10,7 +10,7 @@ pub trait Controller {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn Error + Send + Sync>>;
+    ) -> Result<ResponseData, ServerError>;
 }
 
 pub trait PrintableController: Controller + Debug + Send + Sync {}
@@ -1,6 +1,24 @@
+pub mod analyze;
+pub mod answer_draw;
+pub mod answer_vote_kick;
+pub mod bag_info;
 pub mod cancel;
+pub mod daily;
+pub mod exchange;
 pub mod exit;
+pub mod export;
 pub mod finish_turn;
 pub mod get_new_card;
+pub mod hint;
+pub mod offer_draw;
+pub mod pause;
+pub mod resign;
+pub mod resume;
+pub mod resync;
 pub mod set_tile;
+pub mod snapshot;
+pub mod solo;
+pub mod spectate;
 pub mod start;
+pub mod start_vote_kick;
+pub mod subscribe;
@@ -0,0 +1,110 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::analyze::{AnalyzeGameResponse, TurnAnalysis};
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct AnalyzeGameController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl AnalyzeGameController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for AnalyzeGameController {}
+
+impl Controller for AnalyzeGameController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AnalyzeGame(req) => req,
+            _ => panic!("invalid request"),
+        };
+        if self.player_service.get_player(context.client_id).is_none() {
+            return Err(ServerError::PlayerNotFound);
+        }
+        let turns = self
+            .game_service
+            .analyze_game(req.game_id)?
+            .iter()
+            .map(TurnAnalysis::from)
+            .collect();
+        Ok(ResponseData::AnalyzeGame(AnalyzeGameResponse {
+            success: true,
+            turns,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{model::game::analyze::AnalyzeGameRequest, service::lobby_service::LobbyService};
+
+    use super::*;
+
+    fn analyze(game_id: u32) -> Request {
+        Request::new(
+            0,
+            Arc::new(RequestData::AnalyzeGame(AnalyzeGameRequest { game_id })),
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_finished_game_should_return_turns(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = AnalyzeGameController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        let player = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader.clone(), lobby)?;
+        assert!(matches!(
+            controller.handle_request(analyze(game.id), RequestContext::new(0)),
+            Err(ServerError::GameNotFound)
+        ));
+        let turn_player = game.get_player_in_this_turn().player.clone();
+        GameService::timeout_finish_turn(game_service.clone(), game.clone())?;
+        GameService::resign(game_service.clone(), turn_player)?;
+        match controller.handle_request(analyze(game.id), RequestContext::new(0))? {
+            ResponseData::AnalyzeGame(res) => {
+                assert!(res.success);
+                assert_eq!(res.turns.len(), 1);
+                assert_eq!(res.turns[0].turn, 1);
+                assert_eq!(res.turns[0].points, 0);
+            }
+            _ => panic!("wrong response type"),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::draw::{AnswerDrawResponse, DrawState};
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct AnswerDrawController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl AnswerDrawController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for AnswerDrawController {}
+
+impl Controller for AnswerDrawController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AnswerDraw(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        let status = GameService::answer_draw(self.game_service.clone(), game, player, req.accept)?;
+        Ok(ResponseData::AnswerDraw(AnswerDrawResponse {
+            success: true,
+            draw: Some(DrawState::from(status)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::game::draw::AnswerDrawRequest,
+        service::{lobby_service::LobbyService, stats_service::HistoryFilter},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_request_with_everyone_accepting_should_end_game_as_draw(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = AnswerDrawController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        let player = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader.clone(), lobby)?;
+        let answer = |client_id, accept| {
+            controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::AnswerDraw(AnswerDrawRequest { accept })),
+                ),
+                RequestContext::new(client_id),
+            )
+        };
+        assert!(answer(player.id, true).is_err());
+        GameService::offer_draw(game_service.clone(), game.clone(), leader.clone())?;
+        answer(player.id, false)?;
+        assert_eq!(game.get_draw_status().votes, 0);
+
+        GameService::offer_draw(game_service.clone(), game.clone(), leader.clone())?;
+        match answer(player.id, true)? {
+            ResponseData::AnswerDraw(res) => assert_eq!(res.draw.unwrap().votes, 2),
+            _ => panic!("wrong response type"),
+        }
+        assert!(game_service.get_game(game.id).is_none());
+        let history =
+            game_service
+                .get_stats_service()
+                .get_history("test", &HistoryFilter::default(), 1)?;
+        assert!(history[0].drawn);
+        assert_eq!(history[0].winners().count(), 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::vote_kick::{AnswerVoteKickResponse, KickVote};
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct AnswerVoteKickController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl AnswerVoteKickController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for AnswerVoteKickController {}
+
+impl Controller for AnswerVoteKickController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::AnswerVoteKick(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        let status =
+            GameService::answer_vote_kick(self.game_service.clone(), game, player, req.kick)?;
+        Ok(ResponseData::AnswerVoteKick(AnswerVoteKickResponse {
+            success: true,
+            vote: Some(KickVote::from(status)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        game::game::Withdrawal, model::game::vote_kick::AnswerVoteKickRequest,
+        service::lobby_service::LobbyService,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_request_with_majority_should_forfeit_target(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller =
+            AnswerVoteKickController::new(player_service.clone(), game_service.clone());
+        let players = (0..4)
+            .map(|id| player_service.add_player(id, format!("test{}", id)))
+            .collect::<Vec<_>>();
+        let lobby = lobby_service.create_lobby(players[0].clone(), 4)?;
+        for player in &players[1..] {
+            lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        }
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), players[0].clone(), lobby)?;
+        let answer = |client_id, kick| {
+            controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::AnswerVoteKick(AnswerVoteKickRequest { kick })),
+                ),
+                RequestContext::new(client_id),
+            )
+        };
+        assert!(answer(1, true).is_err());
+        // Two of the three others against and it can't pass
+        GameService::start_vote_kick(game_service.clone(), game.clone(), players[0].clone(), 3)?;
+        assert!(answer(3, false).is_err());
+        answer(1, false)?;
+        answer(2, false)?;
+        assert!(game.get_kick_status().is_none());
+        assert!(game.get_player(3).is_some());
+
+        GameService::start_vote_kick(game_service.clone(), game.clone(), players[0].clone(), 3)?;
+        answer(1, false)?;
+        // Changed their mind
+        match answer(1, true)? {
+            ResponseData::AnswerVoteKick(res) => {
+                let vote = res.vote.unwrap();
+                assert_eq!(vote.votes, 2);
+                assert_eq!(vote.votes_against, 0);
+            }
+            _ => panic!("wrong response type"),
+        }
+        assert!(game.get_kick_status().is_none());
+        assert!(game.get_player(3).is_none());
+        assert!(players[3].get_game().is_none());
+        let withdrawn = game.get_withdrawn();
+        assert_eq!(withdrawn[0].0.player.id, 3);
+        assert_eq!(withdrawn[0].1, Withdrawal::Forfeited);
+        Ok(())
+    }
+}
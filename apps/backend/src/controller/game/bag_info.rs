@@ -0,0 +1,152 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::game::game_player::GamePlayer;
+use crate::model::game::bag_info::{GetBagInfoResponse, LetterOdds};
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct GetBagInfoController {
+    player_service: Arc<PlayerService>,
+}
+
+impl GetBagInfoController {
+    pub fn new(player_service: Arc<PlayerService>) -> Self {
+        Self { player_service }
+    }
+}
+
+impl PrintableController for GetBagInfoController {}
+
+impl Controller for GetBagInfoController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match req.get_data().as_ref() {
+            RequestData::GetBagInfo => (),
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        // Spectators can follow along too
+        let game = match player.get_game().or_else(|| player.get_spectating()) {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        let rules = game.get_rules();
+        let letters = match rules.letter_odds {
+            true => GamePlayer::deal_odds(rules.hand_size)
+                .into_iter()
+                .map(|(letter, per_mille)| LetterOdds {
+                    letter: letter.to_string(),
+                    per_mille,
+                })
+                .collect(),
+            false => Vec::new(),
+        };
+        Ok(ResponseData::GetBagInfo(GetBagInfoResponse {
+            success: true,
+            turns_left: (game.get_last_turn() + 1).saturating_sub(game.get_turns()),
+            language: rules.language.clone(),
+            letters,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        game::rules::GameRules,
+        service::{game_service::GameService, lobby_service::LobbyService},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_request_with_letter_odds_should_return_turns_left_and_odds(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = GetBagInfoController::new(player_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        assert!(controller
+            .handle_request(
+                Request::new(0, Arc::new(RequestData::GetBagInfo)),
+                RequestContext::new(0),
+            )
+            .is_err());
+        let rules = GameRules {
+            letter_odds: true,
+            ..GameRules::default()
+        };
+        let lobby = lobby_service.create_lobby_with_settings(leader.clone(), 4, None, rules)?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader.clone(), lobby)?;
+        let res = controller.handle_request(
+            Request::new(0, Arc::new(RequestData::GetBagInfo)),
+            RequestContext::new(0),
+        )?;
+        match res {
+            ResponseData::GetBagInfo(res) => {
+                assert_eq!(res.turns_left, game.get_last_turn() + 1 - game.get_turns());
+                assert_eq!(res.language, game.get_rules().language);
+                assert_eq!(res.letters.len(), 26);
+                assert_eq!(res.letters[0].letter, "a");
+                assert!(res.letters[0].per_mille > res.letters[1].per_mille);
+                let total = res.letters.iter().map(|odds| odds.per_mille).sum::<u32>();
+                assert!((990..=1010).contains(&total));
+            }
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_letter_odds_off_should_return_turns_left_only(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = GetBagInfoController::new(player_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader.clone(), lobby)?;
+        let res = controller.handle_request(
+            Request::new(0, Arc::new(RequestData::GetBagInfo)),
+            RequestContext::new(0),
+        )?;
+        match res {
+            ResponseData::GetBagInfo(res) => {
+                assert_eq!(res.turns_left, game.get_last_turn() + 1 - game.get_turns());
+                assert!(res.letters.is_empty());
+            }
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+}
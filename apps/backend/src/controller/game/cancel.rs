@@ -1,5 +1,5 @@
+use crate::error::ServerError;
 use crate::frame::Request;
-use crate::game::board::BOARD_SIZE;
 use crate::model::game::cancel::CancelResponse;
 use crate::service::game_service::GameService;
 use crate::{
@@ -34,7 +34,7 @@ impl Controller for CancelController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         let data = req.get_data();
         let req = match data.as_ref() {
             RequestData::Cancel(req) => req,
@@ -42,30 +42,22 @@ impl Controller for CancelController {
         };
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
         let game = match player.get_game() {
             Some(game) => game,
-            None => return Err("Player not in a game".into()),
+            None => return Err(ServerError::NotInGame),
         };
+        if game.is_paused() {
+            return Err(ServerError::GamePaused);
+        }
 
         let game_player = match game.get_player(player.id) {
             Some(game_player) => game_player,
-            None => return Err("Player not found".into()),
-        };
-
-        let card = match game.get_board().lock().unwrap().tiles[BOARD_SIZE - req.y as usize - 1]
-            [req.x as usize]
-            .clone()
-        {
-            Some(card) => card,
-            None => return Err("card not in the board".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
-        if card.turn != game.get_turns() {
-            return Err("card not place in this turn".into());
-        }
-        self.game_service.remove_selected_tile(req.x, req.y, game);
-        game_player.return_cancel_card(card.char);
+        self.game_service
+            .remove_tile(game, &player, req.x as usize, req.y as usize)?;
         Ok(ResponseData::Cancel(CancelResponse {
             success: true,
             cards: Some(crate::model::game::cards::Cards::from(
@@ -80,7 +72,8 @@ mod tests {
     use std::{collections::HashSet, error::Error};
 
     use crate::{
-        game::tile::Tile, model::game::cancel::CancelRequest, service::lobby_service::LobbyService,
+        game::event::Event, model::game::cancel::CancelRequest,
+        service::lobby_service::LobbyService,
     };
 
     use super::*;
@@ -105,18 +98,17 @@ mod tests {
         let lobby_player = lobby.clone().get_player(player.clone().id).unwrap();
         lobby_player.set_ready(true);
         let game = GameService::start_game(game_service, player.clone(), lobby.clone())?;
-        let tile = Tile::new('z', player, 1);
         controller
             .game_service
-            .place_tile_on_board(game.clone(), tile, 1, 1);
-        game.next_turn();
+            .place_tile(game.clone(), &player, 0, 13, 13)?;
+        game.apply(Event::EndTurn { keep_tiles: true })?;
         assert!(controller
             .handle_request(
                 Request::new(
                     0,
                     Arc::new(RequestData::Cancel(CancelRequest { x: 24, y: 1 }))
                 ),
-                RequestContext { client_id: 0 },
+                RequestContext::new(0),
             )
             .is_err());
         Ok(())
@@ -142,22 +134,17 @@ mod tests {
         let lobby_player = lobby.clone().get_player(player.clone().id).unwrap();
         lobby_player.set_ready(true);
         let game = GameService::start_game(game_service, player.clone(), lobby.clone())?;
-        let tile = Tile::new(
-            game.get_player_in_this_turn().get_cards()[0].char,
-            player,
-            game.get_turns(),
-        );
         controller
             .game_service
-            .place_tile_on_board(game.clone(), tile, 1, 1);
-        game.next_turn();
+            .place_tile(game.clone(), &player, 0, 13, 13)?;
+        game.apply(Event::EndTurn { keep_tiles: true })?;
         assert!(controller
             .handle_request(
                 Request::new(
                     0,
-                    Arc::new(RequestData::Cancel(CancelRequest { x: 1, y: 1 })),
+                    Arc::new(RequestData::Cancel(CancelRequest { x: 13, y: 13 })),
                 ),
-                RequestContext { client_id: 0 },
+                RequestContext::new(0),
             )
             .is_err());
         Ok(())
@@ -183,21 +170,15 @@ mod tests {
         let lobby_player = lobby.clone().get_player(player.clone().id).unwrap();
         lobby_player.set_ready(true);
         let game = GameService::start_game(game_service, player.clone(), lobby.clone())?;
-        let tile = Tile::new(
-            game.get_player_in_this_turn().get_cards()[0].char,
-            player,
-            1,
-        );
-        game.get_player_in_this_turn().get_cards()[0].used = true;
         controller
             .game_service
-            .place_tile_on_board(game.clone(), tile, 1, 1);
+            .place_tile(game.clone(), &player, 0, 13, 13)?;
         controller.handle_request(
             Request::new(
                 0,
-                Arc::new(RequestData::Cancel(CancelRequest { x: 24, y: 1 })),
+                Arc::new(RequestData::Cancel(CancelRequest { x: 13, y: 13 })),
             ),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )?;
         assert!(game.get_player_in_this_turn().get_cards()[0].used == false);
         Ok(())
@@ -0,0 +1,117 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::frame::Request;
+use crate::model::game::start::StartResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct DailyController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl DailyController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for DailyController {}
+
+impl Controller for DailyController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match *req.get_data() {
+            RequestData::StartDailyChallenge => req,
+            _ => panic!("invalid request"),
+        };
+
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let game = GameService::start_daily_challenge(self.game_service.clone(), player.clone())?;
+        let game_player = match game.get_player(player.id) {
+            Some(game_player) => game_player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        Ok(ResponseData::StartDailyChallenge(StartResponse::new(
+            &game,
+            game_player,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{game::game::current_day, service::lobby_service::LobbyService};
+
+    use super::*;
+
+    fn controller() -> DailyController {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        DailyController::new(
+            Arc::new(PlayerService::new(
+                Arc::new(LobbyService::new()),
+                game_service.clone(),
+            )),
+            game_service,
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_idle_player_should_start_daily_game(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let controller = controller();
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        let res = match controller.handle_request(
+            Request::new(0, Arc::new(RequestData::StartDailyChallenge)),
+            RequestContext::new(0),
+        )? {
+            ResponseData::StartDailyChallenge(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert!(res.success);
+        assert_eq!(res.day, Some(current_day()));
+        assert!(res.current_player.is_some());
+        assert!(player.get_game().is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_player_in_lobby_should_return_error(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let controller = controller();
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        LobbyService::new().create_lobby(player.clone(), 4)?;
+        assert!(controller
+            .handle_request(
+                Request::new(0, Arc::new(RequestData::StartDailyChallenge)),
+                RequestContext::new(0),
+            )
+            .is_err());
+        assert!(player.get_game().is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::exchange::ExchangeCardsResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct ExchangeCardsController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl ExchangeCardsController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for ExchangeCardsController {}
+
+impl Controller for ExchangeCardsController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::ExchangeCards(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        if game.is_paused() {
+            return Err(ServerError::GamePaused);
+        }
+        let game_player = match game.get_player(player.id) {
+            Some(game_player) => game_player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let card_indices = req
+            .card_indices
+            .iter()
+            .map(|index| *index as usize)
+            .collect();
+        let cards = self
+            .game_service
+            .exchange(game, game_player, card_indices)?;
+        Ok(ResponseData::ExchangeCards(ExchangeCardsResponse {
+            success: true,
+            cards: Some(crate::model::game::cards::Cards::from(&cards)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        game::rules::GameRules, model::game::exchange::ExchangeCardsRequest,
+        service::lobby_service::LobbyService,
+    };
+
+    use super::*;
+
+    fn exchange(card_indices: Vec<u32>) -> Request {
+        Request::new(
+            0,
+            Arc::new(RequestData::ExchangeCards(ExchangeCardsRequest {
+                card_indices,
+            })),
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_some_cards_should_keep_others_and_refill_hand(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = ExchangeCardsController::new(player_service.clone(), game_service.clone());
+        let player = player_service.add_player(0, String::from("test"));
+        let rules = GameRules {
+            hand_size: 6,
+            ..GameRules::default()
+        };
+        let lobby = lobby_service.create_lobby_with_settings(player.clone(), 4, None, rules)?;
+        lobby.get_player(player.id).unwrap().set_ready(true);
+        let game = GameService::start_game(game_service, player.clone(), lobby)?;
+        let before = game.get_player(player.id).unwrap().get_cards();
+        let res = controller.handle_request(exchange(vec![1, 4]), RequestContext::new(0))?;
+        match res {
+            ResponseData::ExchangeCards(res) => {
+                let cards = res.cards.unwrap().cards;
+                assert_eq!(cards.len(), 6);
+                let kept = [0, 2, 3, 5]
+                    .iter()
+                    .map(|i| before[*i].char.to_string())
+                    .collect::<Vec<_>>();
+                let held = cards[..4]
+                    .iter()
+                    .map(|card| card.card.clone().unwrap().symbol)
+                    .collect::<Vec<_>>();
+                assert_eq!(held, kept);
+            }
+            _ => panic!("invalid response"),
+        }
+        // The exchange took the turn's shuffle
+        assert!(controller
+            .handle_request(exchange(vec![0]), RequestContext::new(0))
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_invalid_cards_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = ExchangeCardsController::new(player_service.clone(), game_service.clone());
+        let player = player_service.add_player(0, String::from("test"));
+        let rules = GameRules {
+            hand_size: 5,
+            ..GameRules::default()
+        };
+        let lobby = lobby_service.create_lobby_with_settings(player.clone(), 4, None, rules)?;
+        lobby.get_player(player.id).unwrap().set_ready(true);
+        let game = GameService::start_game(game_service, player.clone(), lobby)?;
+        let before = game.get_player(player.id).unwrap().get_cards();
+        for card_indices in [vec![], vec![5], vec![1, 1], vec![0, 1, 2, 3, 4, 5]] {
+            assert!(controller
+                .handle_request(exchange(card_indices), RequestContext::new(0))
+                .is_err());
+        }
+        assert_eq!(game.get_player(player.id).unwrap().get_cards(), before);
+        // The whole hand is still fine
+        controller.handle_request(exchange(vec![4, 3, 2, 1, 0]), RequestContext::new(0))?;
+        Ok(())
+    }
+}
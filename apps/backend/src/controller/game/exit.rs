@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use crate::frame::Request;
 use crate::model::game::exit::ExitResponse;
 use crate::service::game_service::GameService;
@@ -33,7 +34,7 @@ impl Controller for ExitController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         let data = req.get_data();
         match data.as_ref() {
             RequestData::Exit => true,
@@ -42,16 +43,16 @@ impl Controller for ExitController {
 
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
 
         let game = match player.get_game() {
             Some(game) => game,
-            None => return Err("Player not in a game".into()),
+            None => return Err(ServerError::NotInGame),
         };
         match game.get_player(player.id) {
             Some(game_player) => game_player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
         match self.game_service.remove_player_from_game(player.clone()) {
             Ok(_) => Ok(ResponseData::Exit(ExitResponse { success: true })),
@@ -89,9 +90,7 @@ mod tests {
         GameService::start_game(game_service, player.clone(), lobby)?;
         let res = controller.handle_request(
             Request::new(0, Arc::new(RequestData::Exit)),
-            RequestContext {
-                client_id: player.id,
-            },
+            RequestContext::new(player.id),
         )?;
         match res {
             ResponseData::Exit(data) => {
@@ -124,9 +123,7 @@ mod tests {
         assert!(controller
             .handle_request(
                 Request::new(0, Arc::new(RequestData::Exit)),
-                RequestContext {
-                    client_id: player.id,
-                },
+                RequestContext::new(player.id),
             )
             .is_err());
         Ok(())
@@ -0,0 +1,112 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::game::notation::Notation;
+use crate::model::game::export::ExportGameResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct ExportGameController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl ExportGameController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for ExportGameController {}
+
+impl Controller for ExportGameController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::ExportGame(req) => req,
+            _ => panic!("invalid request"),
+        };
+        if self.player_service.get_player(context.client_id).is_none() {
+            return Err(ServerError::PlayerNotFound);
+        }
+        let notation = self
+            .game_service
+            .export_game(req.game_id, Notation::from(req.format()))?;
+        Ok(ResponseData::ExportGame(ExportGameResponse {
+            success: true,
+            notation,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::game::export::{ExportFormat, ExportGameRequest},
+        service::lobby_service::LobbyService,
+    };
+
+    use super::*;
+
+    fn export(game_id: u32, format: ExportFormat) -> Request {
+        Request::new(
+            0,
+            Arc::new(RequestData::ExportGame(ExportGameRequest {
+                game_id,
+                format: format as i32,
+            })),
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_finished_game_should_return_notation(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = ExportGameController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        let player = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader.clone(), lobby)?;
+        assert!(matches!(
+            controller.handle_request(export(game.id, ExportFormat::Text), RequestContext::new(0)),
+            Err(ServerError::GameNotFound)
+        ));
+        GameService::resign(game_service.clone(), player)?;
+        match controller
+            .handle_request(export(game.id, ExportFormat::Gcg), RequestContext::new(0))?
+        {
+            ResponseData::ExportGame(res) => {
+                assert!(res.success);
+                assert!(res.notation.contains("#note test1 resigned\n"));
+            }
+            _ => panic!("wrong response type"),
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use crate::frame::Request;
 use crate::model::game::finish_turn::FinishTurnResponse;
 use crate::service::game_service::GameService;
@@ -33,7 +34,7 @@ impl Controller for FinishTurnController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         let data = req.get_data();
         match data.as_ref() {
             RequestData::FinishTurn => true,
@@ -42,19 +43,22 @@ impl Controller for FinishTurnController {
 
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
 
         let game = match player.get_game() {
             Some(game) => game,
-            None => return Err("Player not in a game".into()),
+            None => return Err(ServerError::NotInGame),
         };
+        if game.is_paused() {
+            return Err(ServerError::GamePaused);
+        }
         let request_game_player = match game.get_player(player.id) {
             Some(game_player) => game_player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
         if request_game_player != game.get_player_in_this_turn() {
-            return Err("Player not in his turn".into());
+            return Err(ServerError::NotYourTurn);
         }
         match GameService::validate_board_and_finish_turn(self.game_service.clone(), game.clone()) {
             Ok(words) => Ok(ResponseData::FinishTurn(FinishTurnResponse {
@@ -69,7 +73,11 @@ impl Controller for FinishTurnController {
                 cards: Some(crate::model::game::cards::Cards::from(
                     &request_game_player.get_cards(),
                 )),
-                words: Some(crate::model::game::words::Words::from(&words)),
+                words: Some(self.game_service.get_words(&game, &words)),
+                score: game
+                    .get_annotations()
+                    .last()
+                    .map(crate::model::game::words::TurnScore::from),
             })),
             Err(_) => Ok(ResponseData::FinishTurn(FinishTurnResponse {
                 success: false,
@@ -77,6 +85,7 @@ impl Controller for FinishTurnController {
                 next_player: None,
                 cards: None,
                 words: None,
+                score: None,
             })),
         }
     }
@@ -114,9 +123,7 @@ mod tests {
         assert!(controller
             .handle_request(
                 Request::new(0, Arc::new(RequestData::FinishTurn)),
-                RequestContext {
-                    client_id: player_now.player.id
-                },
+                RequestContext::new(player_now.player.id),
             )
             .is_ok());
         Ok(())
@@ -149,13 +156,11 @@ mod tests {
         assert!(controller
             .handle_request(
                 Request::new(0, Arc::new(RequestData::FinishTurn)),
-                RequestContext {
-                    client_id: match player_now.player.id {
-                        0 => 1,
-                        1 => 0,
-                        _ => panic!("invalid test case"),
-                    }
-                },
+                RequestContext::new(match player_now.player.id {
+                    0 => 1,
+                    1 => 0,
+                    _ => panic!("invalid test case"),
+                }),
             )
             .is_err());
         Ok(())
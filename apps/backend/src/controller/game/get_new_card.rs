@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use crate::frame::Request;
 use crate::model::game::get_new_card::GetNewCardResponse;
 use crate::service::game_service::GameService;
@@ -35,35 +36,27 @@ impl Controller for GetNewCardController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         match *req.get_data() {
             RequestData::GetNewCard => req,
             _ => panic!("invalid request"),
         };
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
         let game = match player.get_game() {
             Some(game) => game,
-            None => return Err("Player not in a game".into()),
+            None => return Err(ServerError::NotInGame),
         };
+        if game.is_paused() {
+            return Err(ServerError::GamePaused);
+        }
         let game_player = match game.get_player(player.id) {
             Some(game_player) => game_player,
-            None => return Err("Player not found".into()),
-        };
-        let turn_player = game.get_player_in_this_turn();
-        if turn_player != game_player {
-            return Err("Player get new card when not his turn".into());
+            None => return Err(ServerError::PlayerNotFound),
         };
-        if turn_player.get_has_shuffled() {
-            return Err("Player has shuffled in this turn".into());
-        }
-        let cards = self.game_service.shuffle(
-            #[cfg(not(test))]
-            game,
-            turn_player,
-        )?;
+        let cards = self.game_service.shuffle(game, game_player)?;
         Ok(ResponseData::GetNewCard(GetNewCardResponse {
             success: true,
             cards: Some(crate::model::game::cards::Cards::from(&cards)),
@@ -101,7 +94,7 @@ mod tests {
         GameService::start_game(game_service, player, lobby)?;
         let res = controller.handle_request(
             Request::new(0, Arc::new(RequestData::GetNewCard)),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )?;
         match res {
             ResponseData::GetNewCard(data) => {
@@ -142,13 +135,11 @@ mod tests {
         assert!(controller
             .handle_request(
                 Request::new(0, Arc::new(RequestData::GetNewCard)),
-                RequestContext {
-                    client_id: match player_now.player.id {
-                        0 => 1,
-                        1 => 0,
-                        _ => panic!("invalid test case"),
-                    }
-                },
+                RequestContext::new(match player_now.player.id {
+                    0 => 1,
+                    1 => 0,
+                    _ => panic!("invalid test case"),
+                }),
             )
             .is_err());
         Ok(())
@@ -175,12 +166,12 @@ mod tests {
         GameService::start_game(game_service, player, lobby)?;
         controller.handle_request(
             Request::new(0, Arc::new(RequestData::GetNewCard)),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )?;
         assert!(controller
             .handle_request(
                 Request::new(0, Arc::new(RequestData::GetNewCard)),
-                RequestContext { client_id: 0 }
+                RequestContext::new(0)
             )
             .is_err());
         Ok(())
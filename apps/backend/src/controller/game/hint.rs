@@ -0,0 +1,166 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::game::event::Event;
+use crate::model::game::hint::HintResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct HintController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl HintController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for HintController {}
+
+impl Controller for HintController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::GetHint(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        if game.is_paused() {
+            return Err(ServerError::GamePaused);
+        }
+        let game_player = match game.get_player(player.id) {
+            Some(game_player) => game_player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        if game.get_player_in_this_turn() != game_player {
+            return Err(ServerError::NotYourTurn);
+        }
+        if game_player.get_hints_left() == 0 {
+            return Err("No hints left".into());
+        }
+        // Only spend a hint once there is something to show
+        let placement =
+            self.game_service
+                .find_hint(game.clone(), &game_player, req.mode().into())?;
+        game.apply(Event::UseHint {
+            player_id: player.id,
+        })?;
+        let hints_left = game_player.get_hints_left();
+        Ok(ResponseData::GetHint(HintResponse {
+            success: true,
+            word: placement.word.clone(),
+            tiles: Vec::from(&placement),
+            score: placement.score,
+            hints_left,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::game::hint::{HintMode, HintRequest},
+        service::lobby_service::LobbyService,
+    };
+
+    use super::*;
+
+    fn new_controller(wordlist: HashSet<String>) -> (HintController, Arc<LobbyService>) {
+        let game_service = Arc::new(GameService::new(wordlist));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        (
+            HintController::new(player_service, game_service),
+            lobby_service,
+        )
+    }
+
+    fn hint(controller: &HintController) -> Result<ResponseData, ServerError> {
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::GetHint(HintRequest {
+                    mode: HintMode::Best as i32,
+                })),
+            ),
+            RequestContext::new(0),
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_budget_spent_should_return_error(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        // Every single letter is a word, so any hand has a hint
+        let wordlist = ('a'..='z').map(String::from).collect();
+        let (controller, lobby_service) = new_controller(wordlist);
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        let lobby = lobby_service.create_lobby(player.clone(), 4)?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(controller.game_service.clone(), player, lobby)?;
+        let budget = game.get_rules().hint_budget;
+        for hints_left in (0..budget).rev() {
+            match hint(&controller)? {
+                ResponseData::GetHint(res) => {
+                    assert_eq!(res.hints_left, hints_left);
+                    assert_eq!(res.tiles.len(), 1);
+                }
+                _ => panic!("wrong response type"),
+            }
+        }
+        assert!(hint(&controller).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn handle_request_without_placement_should_not_spend_hint(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let (controller, lobby_service) = new_controller(HashSet::new());
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        let lobby = lobby_service.create_lobby(player.clone(), 4)?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(controller.game_service.clone(), player, lobby)?;
+        assert!(hint(&controller).is_err());
+        assert_eq!(
+            game.get_player(0).unwrap().get_hints_left(),
+            game.get_rules().hint_budget
+        );
+        Ok(())
+    }
+}
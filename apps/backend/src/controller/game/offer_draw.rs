@@ -0,0 +1,102 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::draw::{DrawState, OfferDrawResponse};
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct OfferDrawController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl OfferDrawController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for OfferDrawController {}
+
+impl Controller for OfferDrawController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match *req.get_data() {
+            RequestData::OfferDraw => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        let status = GameService::offer_draw(self.game_service.clone(), game, player)?;
+        Ok(ResponseData::OfferDraw(OfferDrawResponse {
+            success: true,
+            draw: Some(DrawState::from(status)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::service::lobby_service::LobbyService;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_request_with_draw_on_offer_should_return_error(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = OfferDrawController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        let player = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service, leader.clone(), lobby)?;
+        let offer = |client_id| {
+            controller.handle_request(
+                Request::new(0, Arc::new(RequestData::OfferDraw)),
+                RequestContext::new(client_id),
+            )
+        };
+        match offer(leader.id)? {
+            ResponseData::OfferDraw(res) => {
+                let draw = res.draw.unwrap();
+                assert_eq!(draw.votes, 1);
+                assert_eq!(draw.votes_needed, 2);
+            }
+            _ => panic!("wrong response type"),
+        }
+        assert!(offer(player.id).is_err());
+        game.cancel_timeout_task();
+        Ok(())
+    }
+}
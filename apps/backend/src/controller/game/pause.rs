@@ -0,0 +1,105 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::pause::{PauseResponse, PauseState};
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct PauseController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl PauseController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for PauseController {}
+
+impl Controller for PauseController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match *req.get_data() {
+            RequestData::PauseGame => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        let status = GameService::vote_pause(self.game_service.clone(), game, player, true)?;
+        Ok(ResponseData::PauseGame(PauseResponse {
+            success: true,
+            pause: Some(PauseState::from(status)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::service::lobby_service::LobbyService;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_request_with_one_of_two_players_should_not_pause(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = PauseController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        let player = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service, leader.clone(), lobby)?;
+        let pause = |client_id| {
+            controller.handle_request(
+                Request::new(0, Arc::new(RequestData::PauseGame)),
+                RequestContext::new(client_id),
+            )
+        };
+        match pause(leader.id)? {
+            ResponseData::PauseGame(res) => {
+                let pause = res.pause.unwrap();
+                assert!(!pause.paused);
+                assert_eq!(pause.votes, 1);
+                assert_eq!(pause.votes_needed, 2);
+            }
+            _ => panic!("wrong response type"),
+        }
+        assert!(!game.is_paused());
+        pause(player.id)?;
+        assert!(game.is_paused());
+        assert!(pause(player.id).is_err());
+        Ok(())
+    }
+}
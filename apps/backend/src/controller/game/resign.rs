@@ -0,0 +1,101 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::resign::ResignResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct ResignController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl ResignController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for ResignController {}
+
+impl Controller for ResignController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match *req.get_data() {
+            RequestData::Resign => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        GameService::resign(self.game_service.clone(), player)?;
+        Ok(ResponseData::Resign(ResignResponse { success: true }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::service::{lobby_service::LobbyService, stats_service::HistoryFilter};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_request_with_one_of_two_players_should_end_game(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = ResignController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        let player = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader.clone(), lobby)?;
+        let resign = |client_id| {
+            controller.handle_request(
+                Request::new(0, Arc::new(RequestData::Resign)),
+                RequestContext::new(client_id),
+            )
+        };
+        match resign(player.id)? {
+            ResponseData::Resign(res) => assert!(res.success),
+            _ => panic!("wrong response type"),
+        }
+        assert!(game_service.get_game(game.id).is_none());
+        assert!(leader.get_game().is_none());
+        assert!(resign(leader.id).is_err());
+
+        let stats = game_service.get_stats_service();
+        assert_eq!(stats.get_stats("test")?.games_won, 1);
+        let resigned = stats.get_stats("test1")?;
+        assert_eq!(resigned.games_played, 1);
+        assert_eq!(resigned.games_won, 0);
+        assert_eq!(resigned.games_resigned, 1);
+        let history = stats.get_history("test1", &HistoryFilter::default(), 1)?;
+        assert_eq!(history[0].resigned, vec![(String::from("test1"), 0)]);
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::{pause::PauseState, resume::ResumeResponse};
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct ResumeController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl ResumeController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for ResumeController {}
+
+impl Controller for ResumeController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match *req.get_data() {
+            RequestData::ResumeGame => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        let status = GameService::vote_pause(self.game_service.clone(), game, player, false)?;
+        Ok(ResponseData::ResumeGame(ResumeResponse {
+            success: true,
+            pause: Some(PauseState::from(status)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::service::lobby_service::LobbyService;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_request_with_paused_game_should_resume(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = ResumeController::new(player_service.clone(), game_service.clone());
+        let player = player_service.add_player(0, String::from("test"));
+        let lobby = lobby_service.create_lobby(player.clone(), 4)?;
+        lobby.get_player(player.id).unwrap().set_ready(true);
+        let game = GameService::start_game(game_service.clone(), player.clone(), lobby)?;
+        let resume = || {
+            controller.handle_request(
+                Request::new(0, Arc::new(RequestData::ResumeGame)),
+                RequestContext::new(player.id),
+            )
+        };
+        assert!(resume().is_err());
+        GameService::vote_pause(game_service, game.clone(), player.clone(), true)?;
+        assert!(game.is_paused());
+        match resume()? {
+            ResponseData::ResumeGame(res) => assert!(!res.pause.unwrap().paused),
+            _ => panic!("wrong response type"),
+        }
+        assert!(!game.is_paused());
+        Ok(())
+    }
+}
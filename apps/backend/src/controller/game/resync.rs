@@ -0,0 +1,136 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::resync::ResyncFromResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct ResyncController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl ResyncController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for ResyncController {}
+
+impl Controller for ResyncController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::ResyncFrom(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let replayed = self.game_service.resync(&player, req.version)?;
+        Ok(ResponseData::ResyncFrom(ResyncFromResponse {
+            success: true,
+            replayed,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use tokio::sync::mpsc;
+
+    use crate::{
+        frame::{Frame, Response},
+        model::{
+            game::{
+                broadcast::{GameBroadcast, GameEvent},
+                resync::ResyncFromRequest,
+            },
+            state::State,
+        },
+        service::lobby_service::LobbyService,
+    };
+
+    use super::*;
+
+    fn resync_from(version: u64) -> Request {
+        Request::new(
+            0,
+            Arc::new(RequestData::ResyncFrom(ResyncFromRequest { version })),
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_after_events_were_dropped_should_send_resync(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = ResyncController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader, lobby)?;
+        let broadcast_service = game_service.get_broadcast_service();
+        let shuffle = Response::event(
+            State::GameBroadcast,
+            Arc::new(ResponseData::GameBroadcast(GameBroadcast {
+                event: GameEvent::Shuffle as i32,
+                ..Default::default()
+            })),
+        );
+        for _ in 0..100 {
+            broadcast_service.send_to_game(&game, shuffle.clone(), None);
+        }
+        let version = broadcast_service.get_state_version(game.id);
+        let (tx, mut rx) = mpsc::channel(4);
+        broadcast_service.register(0, tx);
+        let res = match controller.handle_request(resync_from(0), RequestContext::new(0))? {
+            ResponseData::ResyncFrom(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert!(!res.replayed);
+        match rx.recv().await {
+            Some(Frame::Response(res)) => match res.get_data().as_ref() {
+                ResponseData::GameBroadcast(broadcast) => {
+                    assert_eq!(broadcast.event(), GameEvent::Resync);
+                    assert_eq!(broadcast.state_version, version);
+                    assert!(broadcast.board.is_some());
+                    assert!(broadcast.cards.is_some());
+                }
+                _ => panic!("invalid response"),
+            },
+            _ => panic!("invalid frame"),
+        }
+        let res = match controller.handle_request(resync_from(version), RequestContext::new(0))? {
+            ResponseData::ResyncFrom(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert!(res.replayed);
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
+use crate::error::ServerError;
 use crate::frame::Request;
-use crate::game::tile::Tile;
 use crate::model::game::set_tile::SetTileResponse;
 use crate::service::game_service::GameService;
 use crate::{
@@ -34,7 +34,7 @@ impl Controller for SetTileController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         let data = req.get_data();
         let req = match data.as_ref() {
             RequestData::SetTile(req) => req,
@@ -42,41 +42,29 @@ impl Controller for SetTileController {
         };
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
         let game = match player.get_game() {
             Some(game) => game,
-            None => return Err("Player not in a game".into()),
+            None => return Err(ServerError::NotInGame),
         };
-        let game_player = match game.get_player(player.id) {
-            Some(game_player) => game_player,
-            None => return Err("Player not found".into()),
-        };
-        let card = game_player.get_card(req.card_index as usize);
-        if card.used {
-            return Err("Card has used".into());
-        }
-        let turn_player = game.get_player_in_this_turn();
-        if turn_player != game_player {
-            return Err("Player can't place tile when not his turn".into());
-        }
-        if req.x >= 26 {
-            return Err("Tile out of board".into());
+        if game.is_paused() {
+            return Err(ServerError::GamePaused);
         }
-        if req.y >= 26 {
-            return Err("Tile out of board".into());
-        }
-        self.game_service.place_tile_on_board(
-            game.clone(),
-            Tile {
-                char: card.char,
-                owner: player,
-                turn: game.get_turns(),
-            },
+        if let Err(e) = self.game_service.place_tile(
+            game,
+            &player,
+            req.card_index as usize,
             req.x as usize,
             req.y as usize,
-        );
-        game_player.take_card(req.card_index as usize);
+        ) {
+            if let ServerError::Move(violation) = e {
+                if violation.is_tampered() {
+                    self.player_service.record_violation(player, violation);
+                }
+            }
+            return Err(e);
+        }
         Ok(ResponseData::SetTile(SetTileResponse { success: true }))
     }
 }
@@ -87,7 +75,7 @@ mod tests {
     use std::{collections::HashSet, error::Error};
 
     use crate::{
-        model::game::set_tile::SetTileRequest,
+        model::{error::error::ErrorCode, game::set_tile::SetTileRequest},
         service::lobby_service::{self, LobbyService},
     };
 
@@ -115,14 +103,14 @@ mod tests {
             Request::new(
                 0,
                 Arc::new(RequestData::SetTile(SetTileRequest {
-                    x: 1,
-                    y: 2,
+                    x: 13,
+                    y: 13,
                     card_index: 1,
                 })),
             ),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )?;
-        assert!(game.get_board().lock().unwrap().tiles[23][1].is_some());
+        assert!(game.get_board().lock().unwrap().tiles[12][13].is_some());
         Ok(())
     }
 
@@ -162,13 +150,11 @@ mod tests {
                         card_index: 1,
                     }))
                 ),
-                RequestContext {
-                    client_id: match player_now.player.id {
-                        0 => 1,
-                        1 => 0,
-                        _ => panic!("invalid test case"),
-                    }
-                },
+                RequestContext::new(match player_now.player.id {
+                    0 => 1,
+                    1 => 0,
+                    _ => panic!("invalid test case"),
+                }),
             )
             .is_err());
         Ok(())
@@ -202,9 +188,127 @@ mod tests {
                         card_index: 1,
                     }))
                 ),
-                RequestContext { client_id: 0 },
+                RequestContext::new(0),
             )
             .is_err());
         Ok(())
     }
+
+    #[tokio::test]
+    async fn handle_request_breaking_placement_rules_should_return_distinct_codes(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let controller = SetTileController::new(
+            Arc::new(PlayerService::new(
+                Arc::new(LobbyService::new()),
+                game_service.clone(),
+            )),
+            game_service.clone(),
+        );
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        let lobby_service = Arc::new(lobby_service::LobbyService::new());
+        let lobby = lobby_service.create_lobby(player.clone(), 4)?;
+        lobby.get_player(player.id).unwrap().set_ready(true);
+        GameService::start_game(game_service, player, lobby)?;
+        let set_tile = |x, y, card_index| {
+            controller
+                .handle_request(
+                    Request::new(
+                        0,
+                        Arc::new(RequestData::SetTile(SetTileRequest { x, y, card_index })),
+                    ),
+                    RequestContext::new(0),
+                )
+                .map_err(|e| e.code())
+        };
+        assert_eq!(set_tile(0, 0, 0).err(), Some(ErrorCode::TileNotOnCenter));
+        set_tile(13, 13, 0).unwrap();
+        assert_eq!(set_tile(13, 13, 1).err(), Some(ErrorCode::TileOccupied));
+        assert_eq!(set_tile(15, 13, 1).err(), Some(ErrorCode::TileNotConnected));
+        assert_eq!(set_tile(27, 13, 1).err(), Some(ErrorCode::TileOutOfBoard));
+        set_tile(14, 13, 1).unwrap();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_repeated_invalid_moves_should_kick_player(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let controller = SetTileController::new(
+            Arc::new(
+                PlayerService::new(Arc::new(LobbyService::new()), game_service.clone())
+                    .with_kick_after_violations(2),
+            ),
+            game_service.clone(),
+        );
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        let lobby_service = Arc::new(lobby_service::LobbyService::new());
+        let lobby = lobby_service.create_lobby(player.clone(), 4)?;
+        lobby.get_player(player.id).unwrap().set_ready(true);
+        GameService::start_game(game_service, player, lobby)?;
+        let set_tile = |x, y, card_index| {
+            controller
+                .handle_request(
+                    Request::new(
+                        0,
+                        Arc::new(RequestData::SetTile(SetTileRequest { x, y, card_index })),
+                    ),
+                    RequestContext::new(0),
+                )
+                .map_err(|e| e.code())
+        };
+        set_tile(13, 13, 0).unwrap();
+        assert_eq!(set_tile(14, 13, 0).err(), Some(ErrorCode::CardReused));
+        assert!(controller.player_service.get_player(0).is_some());
+        assert_eq!(set_tile(14, 13, 99).err(), Some(ErrorCode::CardNotInHand));
+        assert!(controller.player_service.get_player(0).is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_repeated_moves_out_of_turn_should_not_kick_player(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let controller = SetTileController::new(
+            Arc::new(
+                PlayerService::new(Arc::new(LobbyService::new()), game_service.clone())
+                    .with_kick_after_violations(1),
+            ),
+            game_service.clone(),
+        );
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        let other = controller
+            .player_service
+            .add_player(1, String::from("test1"));
+        let lobby_service = Arc::new(lobby_service::LobbyService::new());
+        let lobby = lobby_service.create_lobby(player.clone(), 4)?;
+        lobby_service.add_player_to_lobby(other, lobby.clone())?;
+        for id in [0, 1] {
+            lobby.get_player(id).unwrap().set_ready(true);
+        }
+        let game = GameService::start_game(game_service, player, lobby)?;
+        let waiting = 1 - game.get_player_in_this_turn().player.id;
+        for _ in 0..2 {
+            let res = controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::SetTile(SetTileRequest {
+                        x: 13,
+                        y: 13,
+                        card_index: 0,
+                    })),
+                ),
+                RequestContext::new(waiting),
+            );
+            assert_eq!(res.err().map(|e| e.code()), Some(ErrorCode::NotYourTurn));
+        }
+        assert!(controller.player_service.get_player(waiting).is_some());
+        Ok(())
+    }
 }
@@ -0,0 +1,110 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::snapshot::BoardSnapshotResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct BoardSnapshotController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl BoardSnapshotController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for BoardSnapshotController {}
+
+impl Controller for BoardSnapshotController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match req.get_data().as_ref() {
+            RequestData::BoardSnapshot => (),
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        self.game_service.send_board_snapshot(&player)?;
+        Ok(ResponseData::BoardSnapshot(BoardSnapshotResponse {
+            success: true,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use tokio::sync::mpsc;
+
+    use crate::{
+        frame::Frame, model::game::broadcast::GameEvent, service::lobby_service::LobbyService,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_request_with_spectator_should_push_board_at_sent_version(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = BoardSnapshotController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader.clone(), lobby)?;
+        let spectator = player_service.add_player(0, String::from("test"));
+        assert!(controller
+            .handle_request(
+                Request::new(0, Arc::new(RequestData::BoardSnapshot)),
+                RequestContext::new(0),
+            )
+            .is_err());
+        game_service.spectate(&spectator, Some(game.id))?;
+        let center = game.get_rules().board_size / 2;
+        game_service.place_tile(game.clone(), &leader, 0, center, center)?;
+        let (tx, mut rx) = mpsc::channel(4);
+        game_service.get_broadcast_service().register(0, tx);
+        controller.handle_request(
+            Request::new(0, Arc::new(RequestData::BoardSnapshot)),
+            RequestContext::new(0),
+        )?;
+        match rx.recv().await {
+            Some(Frame::Response(res)) => match res.get_data().as_ref() {
+                ResponseData::GameBroadcast(broadcast) => {
+                    assert_eq!(broadcast.event(), GameEvent::BoardSnapshot);
+                    assert_eq!(broadcast.board_version, Some(1));
+                    assert!(broadcast.board.is_some());
+                }
+                _ => panic!("invalid response"),
+            },
+            _ => panic!("invalid frame"),
+        }
+        Ok(())
+    }
+}
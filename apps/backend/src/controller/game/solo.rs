@@ -0,0 +1,130 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::frame::Request;
+use crate::model::game::start::StartResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct SoloController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl SoloController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for SoloController {}
+
+impl Controller for SoloController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match *req.get_data() {
+            RequestData::StartSolo => req,
+            _ => panic!("invalid request"),
+        };
+
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let lobby = match player.get_lobby() {
+            Some(lobby) => lobby,
+            None => return Err(ServerError::NotInLobby),
+        };
+        let game = GameService::start_solo(self.game_service.clone(), player.clone(), lobby)?;
+        let game_player = match game.get_player(player.id) {
+            Some(game_player) => game_player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        Ok(ResponseData::StartSolo(StartResponse::new(
+            &game,
+            game_player,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{game::game::GameMode, service::lobby_service::LobbyService};
+
+    use super::*;
+
+    fn controller() -> (SoloController, Arc<LobbyService>) {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        (
+            SoloController::new(
+                Arc::new(PlayerService::new(
+                    lobby_service.clone(),
+                    game_service.clone(),
+                )),
+                game_service,
+            ),
+            lobby_service,
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_lobby_of_one_should_start_practice_game(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let (controller, lobby_service) = controller();
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        lobby_service.create_lobby(player.clone(), 4)?;
+        let res = match controller.handle_request(
+            Request::new(0, Arc::new(RequestData::StartSolo)),
+            RequestContext::new(0),
+        )? {
+            ResponseData::StartSolo(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert!(res.success);
+        assert_eq!(res.day, None);
+        assert!(res.current_player.is_some());
+        assert_eq!(player.get_game().unwrap().get_mode(), GameMode::Practice);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_other_players_in_lobby_should_return_error(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let (controller, lobby_service) = controller();
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        let other = controller
+            .player_service
+            .add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(player.clone(), 4)?;
+        lobby_service.add_player_to_lobby(other, lobby)?;
+        assert!(controller
+            .handle_request(
+                Request::new(0, Arc::new(RequestData::StartSolo)),
+                RequestContext::new(0),
+            )
+            .is_err());
+        assert!(player.get_game().is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::spectate::SpectateResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct SpectateController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl SpectateController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for SpectateController {}
+
+impl Controller for SpectateController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::Spectate(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        self.game_service.spectate(&player, req.game_id)?;
+        Ok(ResponseData::Spectate(SpectateResponse {
+            success: true,
+            delay_secs: self
+                .game_service
+                .get_broadcast_service()
+                .get_spectator_delay()
+                .as_secs() as u32,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{model::game::spectate::SpectateRequest, service::lobby_service::LobbyService};
+
+    use super::*;
+
+    fn spectate(game_id: Option<u32>) -> Request {
+        Request::new(
+            0,
+            Arc::new(RequestData::Spectate(SpectateRequest { game_id })),
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_running_game_should_add_spectator(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = SpectateController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader, lobby)?;
+        let spectator = player_service.add_player(0, String::from("test"));
+        controller.handle_request(spectate(Some(game.id)), RequestContext::new(0))?;
+        assert_eq!(game.get_spectators(), vec![0]);
+        assert!(spectator.get_spectating().is_some());
+        controller.handle_request(spectate(None), RequestContext::new(0))?;
+        assert!(game.get_spectators().is_empty());
+        assert!(controller
+            .handle_request(spectate(Some(game.id + 1)), RequestContext::new(0))
+            .is_err());
+        Ok(())
+    }
+}
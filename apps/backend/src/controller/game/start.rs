@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use std::sync::Arc;
 
 use crate::frame::Request;
@@ -36,7 +37,7 @@ impl Controller for StartController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         match *req.get_data() {
             RequestData::StartGame => req,
             _ => panic!("invalid request"),
@@ -44,37 +45,25 @@ impl Controller for StartController {
 
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
         let lobby = match player.get_lobby() {
             Some(lobby) => lobby,
-            None => return Err("Player not in lobby".into()),
+            None => return Err(ServerError::NotInLobby),
         };
         match player.get_game() {
-            Some(_) => return Err("player already in game".into()),
+            Some(_) => return Err(ServerError::AlreadyInGame),
             None => (),
         };
         let game = GameService::start_game(self.game_service.clone(), player.clone(), lobby)?;
         let game_player = match game.get_player(player.id) {
             Some(game_player) => game_player,
-            None => return Err("find no player".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
-        Ok(ResponseData::StartGame(StartResponse {
-            success: true,
-            board: Some(crate::model::game::board::Board::from(
-                &*game.get_board().lock().unwrap(),
-            )),
-            cards: Some(crate::model::game::cards::Cards::from(
-                &game_player.get_cards(),
-            )),
-            current_player: Some(crate::model::player::player::Player::from(
-                game.get_player_in_this_turn(),
-            )),
-            next_player: match game.get_next_turn_player() {
-                Some(game_player) => Some(crate::model::player::player::Player::from(game_player)),
-                None => None,
-            },
-        }))
+        Ok(ResponseData::StartGame(StartResponse::new(
+            &game,
+            game_player,
+        )))
     }
 }
 
@@ -105,7 +94,7 @@ mod tests {
         lobby_player.set_ready(true);
         let res = match controller.handle_request(
             Request::new(0, Arc::new(RequestData::StartGame)),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )? {
             ResponseData::StartGame(res) => res,
             _ => panic!("invalid response"),
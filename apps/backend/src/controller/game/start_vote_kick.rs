@@ -0,0 +1,119 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::vote_kick::{KickVote, StartVoteKickResponse};
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct StartVoteKickController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl StartVoteKickController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for StartVoteKickController {}
+
+impl Controller for StartVoteKickController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::StartVoteKick(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        let status =
+            GameService::start_vote_kick(self.game_service.clone(), game, player, req.target_id)?;
+        Ok(ResponseData::StartVoteKick(StartVoteKickResponse {
+            success: true,
+            vote: Some(KickVote::from(status)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::game::vote_kick::StartVoteKickRequest, service::lobby_service::LobbyService,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_request_with_two_players_or_vote_open_should_return_error(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = StartVoteKickController::new(player_service.clone(), game_service.clone());
+        let players = (0..5)
+            .map(|id| player_service.add_player(id, format!("test{}", id)))
+            .collect::<Vec<_>>();
+        let start = |client_id, target_id| {
+            controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::StartVoteKick(StartVoteKickRequest {
+                        target_id,
+                    })),
+                ),
+                RequestContext::new(client_id),
+            )
+        };
+        for seats in [&players[..3], &players[3..]] {
+            let lobby = lobby_service.create_lobby(seats[0].clone(), 4)?;
+            for player in &seats[1..] {
+                lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+            }
+            for lobby_player in lobby.get_players() {
+                lobby_player.set_ready(true);
+            }
+            GameService::start_game(game_service.clone(), seats[0].clone(), lobby)?;
+        }
+        assert!(start(3, 4).is_err());
+        assert!(start(0, 0).is_err());
+        assert!(start(0, 3).is_err());
+        match start(0, 1)? {
+            ResponseData::StartVoteKick(res) => {
+                let vote = res.vote.unwrap();
+                assert_eq!(vote.target_id, 1);
+                assert_eq!(vote.votes, 1);
+                assert_eq!(vote.votes_needed, 2);
+            }
+            _ => panic!("wrong response type"),
+        }
+        assert!(start(2, 0).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::game::subscribe::SubscribeGameResponse;
+use crate::service::game_service::GameService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct SubscribeGameController {
+    player_service: Arc<PlayerService>,
+    game_service: Arc<GameService>,
+}
+
+impl SubscribeGameController {
+    pub fn new(player_service: Arc<PlayerService>, game_service: Arc<GameService>) -> Self {
+        Self {
+            player_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for SubscribeGameController {}
+
+impl Controller for SubscribeGameController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::SubscribeGame(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let state_version = self.game_service.subscribe(&player, req.game_id)?;
+        Ok(ResponseData::SubscribeGame(SubscribeGameResponse {
+            success: true,
+            state_version,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::game::subscribe::SubscribeGameRequest, service::lobby_service::LobbyService,
+    };
+
+    use super::*;
+
+    fn subscribe(game_id: Option<u32>) -> Request {
+        Request::new(
+            0,
+            Arc::new(RequestData::SubscribeGame(SubscribeGameRequest { game_id })),
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_running_game_should_add_subscriber(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = SubscribeGameController::new(player_service.clone(), game_service.clone());
+        let leader = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), leader.clone(), lobby)?;
+        let observer = player_service.add_player(0, String::from("overlay"));
+        match controller.handle_request(subscribe(Some(game.id)), RequestContext::new(0))? {
+            ResponseData::SubscribeGame(res) => assert_eq!(
+                res.state_version,
+                game_service
+                    .get_broadcast_service()
+                    .get_state_version(game.id)
+            ),
+            _ => panic!("wrong response type"),
+        }
+        assert_eq!(game.get_subscribers(), vec![0]);
+        assert!(game.get_spectators().is_empty());
+        assert!(observer.get_subscribed().is_some());
+        // Playing in a game is no way to follow one from outside
+        assert!(controller
+            .handle_request(subscribe(Some(game.id)), RequestContext::new(1))
+            .is_err());
+        player_service.remove_player(observer)?;
+        assert!(game.get_subscribers().is_empty());
+        let observer = player_service.add_player(0, String::from("overlay"));
+        controller.handle_request(subscribe(Some(game.id)), RequestContext::new(0))?;
+        controller.handle_request(subscribe(None), RequestContext::new(0))?;
+        assert!(game.get_subscribers().is_empty());
+        assert!(observer.get_subscribed().is_none());
+        assert!(controller
+            .handle_request(subscribe(Some(game.id + 1)), RequestContext::new(0))
+            .is_err());
+        Ok(())
+    }
+}
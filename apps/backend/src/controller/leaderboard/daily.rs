@@ -0,0 +1,109 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::game::game::current_day;
+use crate::model::leaderboard::daily::{DailyLeaderboardResponse, LeaderboardEntry};
+use crate::service::leaderboard_service::LeaderboardService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct DailyLeaderboardController {
+    player_service: Arc<PlayerService>,
+    leaderboard_service: Arc<LeaderboardService>,
+}
+
+impl DailyLeaderboardController {
+    pub fn new(
+        player_service: Arc<PlayerService>,
+        leaderboard_service: Arc<LeaderboardService>,
+    ) -> Self {
+        Self {
+            player_service,
+            leaderboard_service,
+        }
+    }
+}
+
+impl PrintableController for DailyLeaderboardController {}
+
+impl Controller for DailyLeaderboardController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::GetDailyLeaderboard(req) => req,
+            _ => panic!("invalid request"),
+        };
+        if self.player_service.get_player(context.client_id).is_none() {
+            return Err(ServerError::PlayerNotFound);
+        }
+        let day = req.day.unwrap_or_else(current_day);
+        let scores = self.leaderboard_service.get_daily(day)?;
+        Ok(ResponseData::GetDailyLeaderboard(
+            DailyLeaderboardResponse {
+                success: true,
+                day,
+                entries: LeaderboardEntry::ranked(&scores),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::leaderboard::daily::DailyLeaderboardRequest,
+        service::{game_service::GameService, lobby_service::LobbyService},
+    };
+
+    use super::*;
+
+    #[test]
+    fn handle_request_with_day_should_return_ranked_entries(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            game_service.clone(),
+        ));
+        let controller =
+            DailyLeaderboardController::new(player_service, game_service.get_leaderboard_service());
+        controller
+            .player_service
+            .add_player(0, String::from("test"));
+        controller.leaderboard_service.submit_daily(5, "a", 10)?;
+        controller.leaderboard_service.submit_daily(5, "b", 30)?;
+        controller.leaderboard_service.submit_daily(6, "c", 50)?;
+        match controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::GetDailyLeaderboard(DailyLeaderboardRequest {
+                    day: Some(5),
+                })),
+            ),
+            RequestContext::new(0),
+        )? {
+            ResponseData::GetDailyLeaderboard(res) => {
+                assert_eq!(res.day, 5);
+                assert_eq!(res.entries.len(), 2);
+                assert_eq!(res.entries[0].rank, 1);
+                assert_eq!(res.entries[0].name, "b");
+                assert_eq!(res.entries[1].score, 10);
+            }
+            _ => panic!("wrong response type"),
+        }
+        Ok(())
+    }
+}
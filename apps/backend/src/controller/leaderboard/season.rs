@@ -0,0 +1,133 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::leaderboard::{
+    daily::LeaderboardEntry,
+    season::{SeasonInfoResponse, SeasonReward},
+};
+use crate::service::season_service::{Reward, SeasonService};
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+/// Ratings listed in a season's standings.
+const STANDINGS_SHOWN: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct SeasonInfoController {
+    player_service: Arc<PlayerService>,
+    season_service: Arc<SeasonService>,
+}
+
+impl SeasonInfoController {
+    pub fn new(player_service: Arc<PlayerService>, season_service: Arc<SeasonService>) -> Self {
+        Self {
+            player_service,
+            season_service,
+        }
+    }
+}
+
+impl PrintableController for SeasonInfoController {}
+
+impl Controller for SeasonInfoController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::GetSeasonInfo(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let season = match req.season {
+            Some(number) => self.season_service.get_season(number),
+            None => match self.season_service.current_season() {
+                Some(season) => season,
+                None => return Err("No season has started yet".into()),
+            },
+        };
+        let standings = self.season_service.get_standings(season.number)?;
+        let rank = standings
+            .iter()
+            .position(|(name, _)| *name == player.name)
+            .map_or(0, |i| i as u32 + 1);
+        let previous_reward = match season.number {
+            1 => Reward::None,
+            number => self.season_service.get_reward(number - 1, &player.name)?,
+        };
+        Ok(ResponseData::GetSeasonInfo(SeasonInfoResponse {
+            success: true,
+            season: season.number,
+            starts_at: season.starts_at(),
+            ends_at: season.ends_at(),
+            rating: self
+                .season_service
+                .get_rating(season.number, &player.name)?,
+            rank,
+            standings: LeaderboardEntry::ranked(&standings[..standings.len().min(STANDINGS_SHOWN)]),
+            previous_reward: SeasonReward::from(previous_reward) as i32,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::leaderboard::season::SeasonInfoRequest,
+        service::{
+            game_service::GameService, lobby_service::LobbyService, season_service::BASE_RATING,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn handle_request_without_rated_games_should_return_base_rating(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            game_service.clone(),
+        ));
+        let controller =
+            SeasonInfoController::new(player_service, game_service.get_season_service());
+        controller
+            .player_service
+            .add_player(0, String::from("test"));
+        match controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::GetSeasonInfo(SeasonInfoRequest {
+                    season: Some(2),
+                })),
+            ),
+            RequestContext::new(0),
+        )? {
+            ResponseData::GetSeasonInfo(res) => {
+                let season = controller.season_service.get_season(2);
+                assert_eq!(res.season, 2);
+                assert_eq!(res.starts_at, season.starts_at());
+                assert_eq!(res.ends_at, season.ends_at());
+                assert_eq!(res.rating, BASE_RATING);
+                assert_eq!(res.rank, 0);
+                assert!(res.standings.is_empty());
+                assert_eq!(res.previous_reward(), SeasonReward::None);
+            }
+            _ => panic!("wrong response type"),
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,10 @@
 pub mod create;
 pub mod join;
+pub mod join_by_code;
+pub mod kick;
 pub mod list;
+pub mod quick_join;
 pub mod quit;
 pub mod ready;
+pub mod update_settings;
+pub mod upload_dictionary;
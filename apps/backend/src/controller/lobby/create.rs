@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use std::sync::Arc;
 
 use crate::frame::Request;
@@ -6,7 +7,9 @@ use crate::{
     controller::controller::PrintableController,
     frame::{RequestData, ResponseData},
     router::RequestContext,
-    service::{lobby_service::LobbyService, player_service::PlayerService},
+    service::{
+        game_service::GameService, lobby_service::LobbyService, player_service::PlayerService,
+    },
 };
 
 use crate::controller::controller::Controller;
@@ -15,13 +18,19 @@ use crate::controller::controller::Controller;
 pub struct CreateController {
     player_service: Arc<PlayerService>,
     lobby_service: Arc<LobbyService>,
+    game_service: Arc<GameService>,
 }
 
 impl CreateController {
-    pub fn new(player_service: Arc<PlayerService>, lobby_service: Arc<LobbyService>) -> Self {
+    pub fn new(
+        player_service: Arc<PlayerService>,
+        lobby_service: Arc<LobbyService>,
+        game_service: Arc<GameService>,
+    ) -> Self {
         Self {
             player_service,
             lobby_service,
+            game_service,
         }
     }
 }
@@ -33,7 +42,7 @@ impl Controller for CreateController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         let data = req.get_data();
         let req = match data.as_ref() {
             RequestData::CreateLobby(req) => req,
@@ -41,9 +50,19 @@ impl Controller for CreateController {
         };
         let leader = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
-        let lobby = self.lobby_service.create_lobby(leader, req.max_players)?;
+        let rules = self.game_service.resolve_rules(req.rules.as_ref())?;
+        let min_players_to_start = match req.min_players_to_start {
+            0 => None,
+            min_players_to_start => Some(min_players_to_start),
+        };
+        let lobby = self.lobby_service.create_lobby_with_settings(
+            leader,
+            req.max_players,
+            min_players_to_start,
+            rules,
+        )?;
 
         Ok(ResponseData::CreateLobby(CreateResponse {
             success: true,
@@ -55,7 +74,7 @@ impl Controller for CreateController {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{model::lobby::create::CreateRequest, service::game_service::GameService};
+    use crate::model::lobby::{create::CreateRequest, rules::Rules};
     use std::{collections::HashSet, error::Error};
 
     #[test]
@@ -66,13 +85,21 @@ mod tests {
             Arc::new(GameService::new(HashSet::new())),
         ));
         player_service.add_player(0, String::from("test"));
-        let controller = CreateController::new(player_service, Arc::new(LobbyService::new()));
+        let controller = CreateController::new(
+            player_service,
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        );
         let res = match controller.handle_request(
             Request::new(
                 0,
-                Arc::new(RequestData::CreateLobby(CreateRequest { max_players: 4 })),
+                Arc::new(RequestData::CreateLobby(CreateRequest {
+                    max_players: 4,
+                    rules: None,
+                    min_players_to_start: 0,
+                })),
             ),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )? {
             ResponseData::CreateLobby(res) => res,
             _ => panic!("invalid response"),
@@ -90,23 +117,35 @@ mod tests {
             Arc::new(GameService::new(HashSet::new())),
         ));
         player_service.add_player(0, String::from("test"));
-        let controller = CreateController::new(player_service, Arc::new(LobbyService::new()));
+        let controller = CreateController::new(
+            player_service,
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        );
         assert!(controller
             .handle_request(
                 Request::new(
                     0,
-                    Arc::new(RequestData::CreateLobby(CreateRequest { max_players: 3 }))
+                    Arc::new(RequestData::CreateLobby(CreateRequest {
+                        max_players: 3,
+                        rules: None,
+                        min_players_to_start: 0,
+                    }))
                 ),
-                RequestContext { client_id: 0 },
+                RequestContext::new(0),
             )
             .is_err());
         assert!(controller
             .handle_request(
                 Request::new(
                     0,
-                    Arc::new(RequestData::CreateLobby(CreateRequest { max_players: 9 }))
+                    Arc::new(RequestData::CreateLobby(CreateRequest {
+                        max_players: 9,
+                        rules: None,
+                        min_players_to_start: 0,
+                    }))
                 ),
-                RequestContext { client_id: 0 },
+                RequestContext::new(0),
             )
             .is_err());
         Ok(())
@@ -119,14 +158,22 @@ mod tests {
             Arc::new(LobbyService::new()),
             Arc::new(GameService::new(HashSet::new())),
         ));
-        let controller = CreateController::new(player_service, Arc::new(LobbyService::new()));
+        let controller = CreateController::new(
+            player_service,
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        );
         assert!(controller
             .handle_request(
                 Request::new(
                     0,
-                    Arc::new(RequestData::CreateLobby(CreateRequest { max_players: 4 }))
+                    Arc::new(RequestData::CreateLobby(CreateRequest {
+                        max_players: 4,
+                        rules: None,
+                        min_players_to_start: 0,
+                    }))
                 ),
-                RequestContext { client_id: 0 },
+                RequestContext::new(0),
             )
             .is_err());
         Ok(())
@@ -140,13 +187,21 @@ mod tests {
             Arc::new(GameService::new(HashSet::new())),
         ));
         let player = player_service.add_player(0, String::from("test"));
-        let controller = CreateController::new(player_service, Arc::new(LobbyService::new()));
+        let controller = CreateController::new(
+            player_service,
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        );
         let res = match controller.handle_request(
             Request::new(
                 0,
-                Arc::new(RequestData::CreateLobby(CreateRequest { max_players: 4 })),
+                Arc::new(RequestData::CreateLobby(CreateRequest {
+                    max_players: 4,
+                    rules: None,
+                    min_players_to_start: 0,
+                })),
             ),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )? {
             ResponseData::CreateLobby(res) => res,
             _ => panic!("invalid response"),
@@ -157,4 +212,75 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn handle_request_with_rules_should_echo_rules_in_lobby(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        player_service.add_player(0, String::from("test"));
+        let controller = CreateController::new(
+            player_service,
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        );
+        let res = match controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::CreateLobby(CreateRequest {
+                    max_players: 4,
+                    rules: Some(Rules {
+                        board_size: 15,
+                        hand_size: 7,
+                        ..Rules::default()
+                    }),
+                    min_players_to_start: 0,
+                })),
+            ),
+            RequestContext::new(0),
+        )? {
+            ResponseData::CreateLobby(res) => res,
+            _ => panic!("invalid response"),
+        };
+        let rules = res.lobby.unwrap().rules.unwrap();
+        assert_eq!(rules.board_size, 15);
+        assert_eq!(rules.hand_size, 7);
+        assert_eq!(rules.turn_duration, 30);
+        assert_eq!(rules.language, "en");
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_unknown_language_should_return_error(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        player_service.add_player(0, String::from("test"));
+        let controller = CreateController::new(
+            player_service,
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        );
+        assert!(controller
+            .handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::CreateLobby(CreateRequest {
+                        max_players: 4,
+                        rules: Some(Rules {
+                            language: String::from("xx"),
+                            ..Rules::default()
+                        }),
+                        min_players_to_start: 0,
+                    }))
+                ),
+                RequestContext::new(0),
+            )
+            .is_err());
+        Ok(())
+    }
 }
@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use std::sync::Arc;
 
 use crate::frame::Request;
@@ -33,7 +34,7 @@ impl Controller for JoinController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         let data = req.get_data();
         let req = match data.as_ref() {
             RequestData::JoinLobby(req) => req,
@@ -41,17 +42,35 @@ impl Controller for JoinController {
         };
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
         let lobby = match self.lobby_service.get_lobby(req.lobby_id) {
             Some(lobby) => lobby,
-            None => return Err("Lobby not found".into()),
+            None => {
+                return Err(
+                    match self
+                        .player_service
+                        .get_cluster_service()
+                        .route(req.lobby_id)
+                    {
+                        Some(address) => ServerError::Moved(address),
+                        None => ServerError::LobbyNotFound,
+                    },
+                )
+            }
+        };
+        let waitlist_position =
+            self.lobby_service
+                .join_lobby(player.clone(), lobby.clone(), req.waitlist)?;
+        let chat_history = match waitlist_position {
+            Some(_) => Vec::new(),
+            None => lobby.get_chat_history(&player),
         };
-        self.lobby_service
-            .add_player_to_lobby(player, lobby.clone())?;
         Ok(ResponseData::JoinLobby(JoinResponse {
             success: true,
             lobby: Some(crate::model::lobby::lobby::Lobby::from(lobby)),
+            waitlist_position: waitlist_position.map(|position| position as u32),
+            chat_history,
         }))
     }
 }
@@ -59,6 +78,7 @@ impl Controller for JoinController {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::chat::broadcast::ChatBroadcast;
     use crate::model::lobby::join::JoinRequest;
     use crate::player::Player;
     use crate::service::game_service::GameService;
@@ -80,9 +100,12 @@ mod tests {
         let res = match controller.handle_request(
             Request::new(
                 0,
-                Arc::new(RequestData::JoinLobby(JoinRequest { lobby_id: 0 })),
+                Arc::new(RequestData::JoinLobby(JoinRequest {
+                    lobby_id: 0,
+                    waitlist: false,
+                })),
             ),
-            RequestContext { client_id: 1 },
+            RequestContext::new(1),
         )? {
             ResponseData::JoinLobby(res) => res,
             _ => panic!("invalid response"),
@@ -93,6 +116,41 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn handle_request_should_return_lobby_chat_history() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        let leader = player_service.add_player(0, String::from("test1"));
+        player_service.add_player(1, String::from("test2"));
+        let lobby_service = Arc::new(LobbyService::new());
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        lobby.record_chat(ChatBroadcast {
+            player: Some(leader.into()),
+            message: String::from("hello"),
+            quick_chat: None,
+        });
+        let controller = JoinController::new(player_service, lobby_service);
+        let res = match controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::JoinLobby(JoinRequest {
+                    lobby_id: 0,
+                    waitlist: false,
+                })),
+            ),
+            RequestContext::new(1),
+        )? {
+            ResponseData::JoinLobby(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert_eq!(res.chat_history.len(), 1);
+        assert_eq!(res.chat_history[0].message, "hello");
+        Ok(())
+    }
+
     #[test]
     fn handle_request_with_not_exist_user_and_test_lobby_should_return_error(
     ) -> Result<(), Box<dyn Error + Sync + Send>> {
@@ -108,9 +166,12 @@ mod tests {
             .handle_request(
                 Request::new(
                     0,
-                    Arc::new(RequestData::JoinLobby(JoinRequest { lobby_id: 0 }))
+                    Arc::new(RequestData::JoinLobby(JoinRequest {
+                        lobby_id: 0,
+                        waitlist: false,
+                    }))
                 ),
-                RequestContext { client_id: 1 },
+                RequestContext::new(1),
             )
             .is_err());
         Ok(())
@@ -130,9 +191,12 @@ mod tests {
             .handle_request(
                 Request::new(
                     0,
-                    Arc::new(RequestData::JoinLobby(JoinRequest { lobby_id: 0 }))
+                    Arc::new(RequestData::JoinLobby(JoinRequest {
+                        lobby_id: 0,
+                        waitlist: false,
+                    }))
                 ),
-                RequestContext { client_id: 0 },
+                RequestContext::new(0),
             )
             .is_err());
         Ok(())
@@ -150,11 +214,55 @@ mod tests {
             .handle_request(
                 Request::new(
                     0,
-                    Arc::new(RequestData::JoinLobby(JoinRequest { lobby_id: 0 }))
+                    Arc::new(RequestData::JoinLobby(JoinRequest {
+                        lobby_id: 0,
+                        waitlist: false,
+                    }))
                 ),
-                RequestContext { client_id: 0 },
+                RequestContext::new(0),
             )
             .is_err());
         Ok(())
     }
+
+    #[test]
+    fn handle_request_with_full_lobby_and_waitlist_should_queue_player(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        let lobby_service = Arc::new(LobbyService::new());
+        let lobby =
+            lobby_service.create_lobby(player_service.add_player(0, String::from("test")), 4)?;
+        for id in 1..4 {
+            lobby_service.add_player_to_lobby(
+                player_service.add_player(id, format!("test{}", id)),
+                lobby.clone(),
+            )?;
+        }
+        let player = player_service.add_player(4, String::from("test4"));
+        let controller = JoinController::new(player_service, lobby_service);
+        let join = |waitlist| {
+            controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::JoinLobby(JoinRequest {
+                        lobby_id: 0,
+                        waitlist,
+                    })),
+                ),
+                RequestContext::new(4),
+            )
+        };
+        assert!(join(false).is_err());
+        let res = match join(true)? {
+            ResponseData::JoinLobby(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert_eq!(res.waitlist_position, Some(1));
+        assert!(player.get_lobby().is_none());
+        assert_eq!(lobby.get_waitlist(), vec![player]);
+        Ok(())
+    }
 }
@@ -0,0 +1,109 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::frame::Request;
+use crate::model::lobby::join::JoinResponse;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::{lobby_service::LobbyService, player_service::PlayerService},
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct JoinByCodeController {
+    player_service: Arc<PlayerService>,
+    lobby_service: Arc<LobbyService>,
+}
+
+impl JoinByCodeController {
+    pub fn new(player_service: Arc<PlayerService>, lobby_service: Arc<LobbyService>) -> Self {
+        Self {
+            player_service,
+            lobby_service,
+        }
+    }
+}
+
+impl PrintableController for JoinByCodeController {}
+
+impl Controller for JoinByCodeController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::JoinByCode(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let lobby = match self.lobby_service.get_lobby_by_code(&req.code) {
+            Some(lobby) => lobby,
+            None => return Err(ServerError::LobbyNotFound),
+        };
+        let waitlist_position =
+            self.lobby_service
+                .join_lobby(player.clone(), lobby.clone(), req.waitlist)?;
+        let chat_history = match waitlist_position {
+            Some(_) => Vec::new(),
+            None => lobby.get_chat_history(&player),
+        };
+        Ok(ResponseData::JoinByCode(JoinResponse {
+            success: true,
+            lobby: Some(crate::model::lobby::lobby::Lobby::from(lobby)),
+            waitlist_position: waitlist_position.map(|position| position as u32),
+            chat_history,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lobby::join::JoinByCodeRequest;
+    use crate::service::game_service::GameService;
+    use std::collections::HashSet;
+    use std::error::Error;
+
+    #[test]
+    fn handle_request_with_lobby_code_should_join_lobby() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        let leader = player_service.add_player(0, String::from("test"));
+        let player = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader, 4)?;
+        let controller = JoinByCodeController::new(player_service, lobby_service);
+        let join = |code: &str| {
+            controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::JoinByCode(JoinByCodeRequest {
+                        code: code.to_string(),
+                        waitlist: false,
+                    })),
+                ),
+                RequestContext::new(1),
+            )
+        };
+        assert!(join("??????").is_err());
+        let res = match join(lobby.get_code().unwrap())? {
+            ResponseData::JoinByCode(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert!(res.success);
+        assert_eq!(res.lobby.unwrap().code.as_deref(), lobby.get_code());
+        assert_eq!(player.get_lobby(), Some(lobby));
+        Ok(())
+    }
+}
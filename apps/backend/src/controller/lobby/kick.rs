@@ -0,0 +1,91 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::frame::Request;
+use crate::model::lobby::kick::KickFromLobbyResponse;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::{lobby_service::LobbyService, player_service::PlayerService},
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct KickFromLobbyController {
+    player_service: Arc<PlayerService>,
+    lobby_service: Arc<LobbyService>,
+}
+
+impl KickFromLobbyController {
+    pub fn new(player_service: Arc<PlayerService>, lobby_service: Arc<LobbyService>) -> Self {
+        Self {
+            player_service,
+            lobby_service,
+        }
+    }
+}
+
+impl PrintableController for KickFromLobbyController {}
+
+impl Controller for KickFromLobbyController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::KickFromLobby(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        self.lobby_service
+            .kick_player(player, req.player_id, req.ban)?;
+        Ok(ResponseData::KickFromLobby(KickFromLobbyResponse {
+            success: true,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lobby::kick::KickFromLobbyRequest;
+    use crate::service::game_service::GameService;
+    use std::collections::HashSet;
+    use std::error::Error;
+
+    #[tokio::test]
+    async fn handle_request_with_ban_should_keep_player_out_of_lobby(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        let controller =
+            KickFromLobbyController::new(player_service.clone(), lobby_service.clone());
+        let leader = player_service.add_player(0, String::from("test"));
+        let player = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader, 4)?;
+        lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::KickFromLobby(KickFromLobbyRequest {
+                    player_id: 1,
+                    ban: true,
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        assert!(player.get_lobby().is_none());
+        assert!(lobby_service.add_player_to_lobby(player, lobby).is_err());
+        Ok(())
+    }
+}
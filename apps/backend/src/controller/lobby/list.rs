@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use std::sync::Arc;
 
 use crate::frame::Request;
@@ -25,11 +26,7 @@ impl ListController {
 impl PrintableController for ListController {}
 
 impl Controller for ListController {
-    fn handle_request(
-        &self,
-        req: Request,
-        _: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    fn handle_request(&self, req: Request, _: RequestContext) -> Result<ResponseData, ServerError> {
         match *req.get_data() {
             RequestData::ListLobby => req,
             _ => panic!("invalid request"),
@@ -0,0 +1,91 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::frame::Request;
+use crate::model::lobby::join::JoinResponse;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::{lobby_service::LobbyService, player_service::PlayerService},
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct QuickJoinController {
+    player_service: Arc<PlayerService>,
+    lobby_service: Arc<LobbyService>,
+}
+
+impl QuickJoinController {
+    pub fn new(player_service: Arc<PlayerService>, lobby_service: Arc<LobbyService>) -> Self {
+        Self {
+            player_service,
+            lobby_service,
+        }
+    }
+}
+
+impl PrintableController for QuickJoinController {}
+
+impl Controller for QuickJoinController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        match *req.get_data() {
+            RequestData::QuickJoin => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let lobby = self.lobby_service.quick_join(player.clone())?;
+        Ok(ResponseData::QuickJoin(JoinResponse {
+            success: true,
+            chat_history: lobby.get_chat_history(&player),
+            lobby: Some(crate::model::lobby::lobby::Lobby::from(lobby)),
+            waitlist_position: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::game_service::GameService;
+    use std::collections::HashSet;
+    use std::error::Error;
+
+    #[tokio::test]
+    async fn handle_request_with_started_lobby_should_create_new_lobby(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let leader = player_service.add_player(0, String::from("test"));
+        player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader.clone(), 4)?;
+        lobby.get_player(0).unwrap().set_ready(true);
+        GameService::start_game(game_service, leader, lobby.clone())?;
+        let controller = QuickJoinController::new(player_service, lobby_service);
+        let res = match controller.handle_request(
+            Request::new(0, Arc::new(RequestData::QuickJoin)),
+            RequestContext::new(1),
+        )? {
+            ResponseData::QuickJoin(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert!(res.success);
+        let joined = res.lobby.unwrap();
+        assert_ne!(joined.id, lobby.get_id());
+        assert_eq!(joined.players[0].name, "test1");
+        Ok(())
+    }
+}
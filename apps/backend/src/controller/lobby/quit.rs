@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use std::sync::Arc;
 
 use crate::frame::Request;
@@ -33,15 +34,19 @@ impl Controller for QuitController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         match *req.get_data() {
             RequestData::QuitLobby => req,
             _ => panic!("invalid request"),
         };
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
+        // Quitting also takes a waitlisted player out of the queue
+        if player.get_lobby().is_none() && self.lobby_service.leave_waitlist(&player) {
+            return Ok(ResponseData::QuitLobby(QuitResponse { success: true }));
+        }
         self.lobby_service.remove_player_from_lobby(player)?;
         Ok(ResponseData::QuitLobby(QuitResponse { success: true }))
     }
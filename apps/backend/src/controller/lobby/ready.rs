@@ -1,3 +1,4 @@
+use crate::error::ServerError;
 use std::sync::Arc;
 
 use crate::frame::Request;
@@ -29,7 +30,7 @@ impl Controller for ReadyController {
         &self,
         req: Request,
         context: RequestContext,
-    ) -> Result<ResponseData, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<ResponseData, ServerError> {
         match *req.get_data() {
             RequestData::Ready => req,
             _ => panic!("invalid request"),
@@ -37,7 +38,7 @@ impl Controller for ReadyController {
 
         let player = match self.player_service.get_player(context.client_id) {
             Some(player) => player,
-            None => return Err("Player not found".into()),
+            None => return Err(ServerError::PlayerNotFound),
         };
 
         match player.get_lobby() {
@@ -50,7 +51,7 @@ impl Controller for ReadyController {
                 }
                 None => panic!("Player in lobby but LobbyPlayer not found"),
             },
-            None => Err("Player not in lobby".into()),
+            None => Err(ServerError::NotInLobby),
         }
     }
 }
@@ -77,7 +78,7 @@ mod tests {
         let lobby = lobby_service.create_lobby(leader, 4)?;
         controller.handle_request(
             Request::new(0, Arc::new(RequestData::Ready)),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )?;
         assert!(lobby.get_player(0).unwrap().get_ready());
         Ok(())
@@ -98,7 +99,7 @@ mod tests {
         lobby.get_player(0).unwrap().set_ready(true);
         controller.handle_request(
             Request::new(0, Arc::new(RequestData::Ready)),
-            RequestContext { client_id: 0 },
+            RequestContext::new(0),
         )?;
         assert!(!lobby.get_player(0).unwrap().get_ready());
         Ok(())
@@ -0,0 +1,171 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::frame::Request;
+use crate::model::lobby::update_settings::UpdateLobbySettingsResponse;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::{
+        game_service::GameService, lobby_service::LobbyService, player_service::PlayerService,
+    },
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct UpdateLobbySettingsController {
+    player_service: Arc<PlayerService>,
+    lobby_service: Arc<LobbyService>,
+    game_service: Arc<GameService>,
+}
+
+impl UpdateLobbySettingsController {
+    pub fn new(
+        player_service: Arc<PlayerService>,
+        lobby_service: Arc<LobbyService>,
+        game_service: Arc<GameService>,
+    ) -> Self {
+        Self {
+            player_service,
+            lobby_service,
+            game_service,
+        }
+    }
+}
+
+impl PrintableController for UpdateLobbySettingsController {}
+
+impl Controller for UpdateLobbySettingsController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::UpdateLobbySettings(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let leader = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let lobby = match leader.get_lobby() {
+            Some(lobby) => lobby,
+            None => return Err(ServerError::NotInLobby),
+        };
+        let rules = match &req.rules {
+            Some(rules) => self.game_service.resolve_rules(Some(rules))?,
+            None => lobby.get_rules(),
+        };
+        let max_players = match req.max_players {
+            0 => lobby.get_max_players(),
+            max_players => max_players,
+        };
+        let min_players_to_start = match req.min_players_to_start {
+            0 => lobby.get_min_players_to_start(),
+            min_players_to_start => min_players_to_start,
+        };
+        let lobby =
+            self.lobby_service
+                .update_settings(leader, max_players, min_players_to_start, rules)?;
+
+        Ok(ResponseData::UpdateLobbySettings(
+            UpdateLobbySettingsResponse {
+                success: true,
+                lobby: Some(crate::model::lobby::lobby::Lobby::from(lobby)),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lobby::{rules::Rules, update_settings::UpdateLobbySettingsRequest};
+    use std::{collections::HashSet, error::Error};
+
+    fn update(max_players: u32, rules: Option<Rules>) -> Request {
+        Request::new(
+            0,
+            Arc::new(RequestData::UpdateLobbySettings(
+                UpdateLobbySettingsRequest {
+                    max_players,
+                    rules,
+                    min_players_to_start: 0,
+                },
+            )),
+        )
+    }
+
+    #[test]
+    fn handle_request_with_new_settings_should_update_lobby_and_reset_ready(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = UpdateLobbySettingsController::new(
+            player_service.clone(),
+            lobby_service.clone(),
+            game_service,
+        );
+        let leader = player_service.add_player(0, String::from("test"));
+        let player = player_service.add_player(1, String::from("test1"));
+        let lobby = lobby_service.create_lobby(leader, 4)?;
+        lobby_service.add_player_to_lobby(player, lobby.clone())?;
+        lobby.get_player(1).unwrap().set_ready(true);
+        let res = match controller.handle_request(
+            update(
+                5,
+                Some(Rules {
+                    turn_duration: 30,
+                    ..Rules::default()
+                }),
+            ),
+            RequestContext::new(0),
+        )? {
+            ResponseData::UpdateLobbySettings(res) => res,
+            _ => panic!("invalid response"),
+        };
+        assert_eq!(res.lobby.unwrap().max_players, 5);
+        assert_eq!(lobby.get_max_players(), 5);
+        assert_eq!(lobby.get_rules().turn_duration.as_secs(), 30);
+        assert!(!lobby.get_player(1).unwrap().get_ready());
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_from_non_leader_or_below_player_count_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = UpdateLobbySettingsController::new(
+            player_service.clone(),
+            lobby_service.clone(),
+            game_service,
+        );
+        let leader = player_service.add_player(0, String::from("test"));
+        let lobby = lobby_service.create_lobby(leader, 6)?;
+        for id in 1..5 {
+            let player = player_service.add_player(id, format!("test{}", id));
+            lobby_service.add_player_to_lobby(player, lobby.clone())?;
+        }
+        assert!(controller
+            .handle_request(update(8, None), RequestContext::new(1))
+            .is_err());
+        assert!(controller
+            .handle_request(update(4, None), RequestContext::new(0))
+            .is_err());
+        assert_eq!(lobby.get_max_players(), 6);
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+use crate::error::ServerError;
+use std::sync::Arc;
+
+use crate::frame::Request;
+use crate::model::lobby::upload_dictionary::UploadDictionaryResponse;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::{
+        dictionary_service::DictionaryService, lobby_service::LobbyService,
+        player_service::PlayerService,
+    },
+};
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct UploadDictionaryController {
+    player_service: Arc<PlayerService>,
+    lobby_service: Arc<LobbyService>,
+    dictionary_service: Arc<DictionaryService>,
+}
+
+impl UploadDictionaryController {
+    pub fn new(
+        player_service: Arc<PlayerService>,
+        lobby_service: Arc<LobbyService>,
+        dictionary_service: Arc<DictionaryService>,
+    ) -> Self {
+        Self {
+            player_service,
+            lobby_service,
+            dictionary_service,
+        }
+    }
+}
+
+impl PrintableController for UploadDictionaryController {}
+
+impl Controller for UploadDictionaryController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::UploadDictionary(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let lobby = match player.get_lobby() {
+            Some(lobby) => lobby,
+            None => return Err(ServerError::NotInLobby),
+        };
+        if player != lobby.leader {
+            return Err("Only leader can upload a dictionary".into());
+        }
+        if req.chunk_index == 0 {
+            self.dictionary_service
+                .retain_lobbies(|lobby_id| self.lobby_service.get_lobby(lobby_id).is_some());
+        }
+        let word_count = self.dictionary_service.upload_chunk(
+            lobby.get_id(),
+            &lobby.get_rules().language,
+            req,
+        )?;
+        Ok(ResponseData::UploadDictionary(UploadDictionaryResponse {
+            success: true,
+            complete: word_count.is_some(),
+            word_count: word_count.unwrap_or_default() as u32,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::lobby::upload_dictionary::UploadDictionaryRequest,
+        service::game_service::GameService,
+    };
+
+    use super::*;
+
+    fn upload(words: &[&str], chunk_index: u32, chunk_count: u32) -> Request {
+        Request::new(
+            0,
+            Arc::new(RequestData::UploadDictionary(UploadDictionaryRequest {
+                chunk_index,
+                chunk_count,
+                words: words.iter().map(|word| word.to_string()).collect(),
+                replace: true,
+            })),
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_request_with_chunks_in_order_should_use_list_in_next_game(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::from([String::from("the")])));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = UploadDictionaryController::new(
+            player_service.clone(),
+            lobby_service.clone(),
+            game_service.get_dictionary_service(),
+        );
+        let player = player_service.add_player(0, String::from("test"));
+        let lobby = lobby_service.create_lobby(player.clone(), 4)?;
+        match controller.handle_request(upload(&["Cat"], 0, 2), RequestContext::new(0))? {
+            ResponseData::UploadDictionary(res) => assert!(!res.complete),
+            _ => panic!("invalid response"),
+        }
+        match controller.handle_request(upload(&["dog"], 1, 2), RequestContext::new(0))? {
+            ResponseData::UploadDictionary(res) => {
+                assert!(res.complete);
+                assert_eq!(res.word_count, 2);
+            }
+            _ => panic!("invalid response"),
+        }
+        let other = player_service.add_player(1, String::from("test1"));
+        lobby_service.add_player_to_lobby(other, lobby.clone())?;
+        for lobby_player in lobby.get_players() {
+            lobby_player.set_ready(true);
+        }
+        let game = GameService::start_game(game_service.clone(), player, lobby)?;
+        let wordlist = game_service.get_wordlist(&game)?;
+        assert!(wordlist.contains("cat") && wordlist.contains("dog"));
+        assert!(!wordlist.contains("the"));
+        game.cancel_timeout_task();
+        Ok(())
+    }
+
+    #[test]
+    fn handle_request_with_chunk_out_of_order_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let lobby_service = Arc::new(LobbyService::new());
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        let controller = UploadDictionaryController::new(
+            player_service.clone(),
+            lobby_service.clone(),
+            game_service.get_dictionary_service(),
+        );
+        let player = player_service.add_player(0, String::from("test"));
+        lobby_service.create_lobby(player, 4)?;
+        assert!(controller
+            .handle_request(upload(&["cat"], 1, 2), RequestContext::new(0))
+            .is_err());
+        assert!(controller
+            .handle_request(upload(&["c4t"], 0, 1), RequestContext::new(0))
+            .is_err());
+        Ok(())
+    }
+}
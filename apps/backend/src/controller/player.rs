@@ -0,0 +1,5 @@
+pub mod block;
+pub mod mute;
+pub mod profile;
+pub mod report;
+pub mod stats;
@@ -0,0 +1,96 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::player::block::BlockPlayerResponse;
+use crate::service::block_service::BlockService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct BlockController {
+    player_service: Arc<PlayerService>,
+    block_service: Arc<BlockService>,
+}
+
+impl BlockController {
+    pub fn new(player_service: Arc<PlayerService>, block_service: Arc<BlockService>) -> Self {
+        Self {
+            player_service,
+            block_service,
+        }
+    }
+}
+
+impl PrintableController for BlockController {}
+
+impl Controller for BlockController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::BlockPlayer(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        self.block_service
+            .set_blocked(&player, req.name.trim(), req.blocked)?;
+        Ok(ResponseData::BlockPlayer(BlockPlayerResponse {
+            success: true,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::player::block::BlockPlayerRequest,
+        service::{game_service::GameService, lobby_service::LobbyService},
+        storage::memory::MemoryStorage,
+    };
+
+    use super::*;
+
+    #[test]
+    fn handle_request_with_blocked_name_should_block_on_next_connection(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let block_service = Arc::new(BlockService::new(Arc::new(MemoryStorage::new())));
+        let player_service = Arc::new(
+            PlayerService::new(
+                Arc::new(LobbyService::new()),
+                Arc::new(GameService::new(HashSet::new())),
+            )
+            .with_block_service(block_service.clone()),
+        );
+        let controller = BlockController::new(player_service.clone(), block_service);
+        let player = player_service.add_player(0, String::from("test"));
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::BlockPlayer(BlockPlayerRequest {
+                    name: String::from("test1"),
+                    blocked: true,
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        assert!(player.has_blocked("test1"));
+        player_service.remove_player(player)?;
+        let player = player_service.add_player(1, String::from("test"));
+        assert!(player.has_blocked("test1"));
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::player::mute::MutePlayerResponse;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct MuteController {
+    player_service: Arc<PlayerService>,
+}
+
+impl MuteController {
+    pub fn new(player_service: Arc<PlayerService>) -> Self {
+        Self { player_service }
+    }
+}
+
+impl PrintableController for MuteController {}
+
+impl Controller for MuteController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::MutePlayer(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        if req.player_id == player.id {
+            return Err("Cannot mute yourself".into());
+        }
+        // Unmuting someone who has since left is still allowed
+        if req.muted && self.player_service.get_player(req.player_id).is_none() {
+            return Err(ServerError::PlayerNotFound);
+        }
+        player.set_muted(req.player_id, req.muted);
+        Ok(ResponseData::MutePlayer(MutePlayerResponse {
+            success: true,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::player::mute::MutePlayerRequest,
+        service::{game_service::GameService, lobby_service::LobbyService},
+    };
+
+    use super::*;
+
+    #[test]
+    fn handle_request_with_muted_and_unmuted_should_toggle_mute(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let controller = MuteController::new(Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        )));
+        let player = controller
+            .player_service
+            .add_player(0, String::from("test"));
+        controller
+            .player_service
+            .add_player(1, String::from("test1"));
+        for muted in [true, false] {
+            controller.handle_request(
+                Request::new(
+                    0,
+                    Arc::new(RequestData::MutePlayer(MutePlayerRequest {
+                        player_id: 1,
+                        muted,
+                    })),
+                ),
+                RequestContext::new(0),
+            )?;
+            assert_eq!(player.has_muted(1), muted);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::player::profile::{self, UpdateProfileResponse};
+use crate::service::profile_service::{Profile, ProfileService};
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct UpdateProfileController {
+    player_service: Arc<PlayerService>,
+    profile_service: Arc<ProfileService>,
+}
+
+impl UpdateProfileController {
+    pub fn new(player_service: Arc<PlayerService>, profile_service: Arc<ProfileService>) -> Self {
+        Self {
+            player_service,
+            profile_service,
+        }
+    }
+}
+
+impl PrintableController for UpdateProfileController {}
+
+impl Controller for UpdateProfileController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::UpdateProfile(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let player = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let profile = match &req.profile {
+            Some(profile) => Profile::try_from(profile)?,
+            None => return Err("Missing profile".into()),
+        };
+        let profile = self.profile_service.update_profile(&player.name, profile)?;
+        // Shows up in the lobby and game broadcasts from now on
+        player.set_profile(profile);
+        Ok(ResponseData::UpdateProfile(UpdateProfileResponse {
+            profile: Some(profile::Profile::from(&profile)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        model::player::profile::{TileColor, Title, UpdateProfileRequest},
+        service::{
+            game_service::GameService, lobby_service::LobbyService, stats_service::StatsService,
+        },
+        storage::memory::MemoryStorage,
+    };
+
+    use super::*;
+
+    #[test]
+    fn handle_request_with_valid_profile_should_show_it_in_player_model(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let storage = Arc::new(MemoryStorage::new());
+        let profile_service = Arc::new(ProfileService::new(
+            storage.clone(),
+            Arc::new(StatsService::new(storage)),
+        ));
+        let player_service = Arc::new(
+            PlayerService::new(
+                Arc::new(LobbyService::new()),
+                Arc::new(GameService::new(HashSet::new())),
+            )
+            .with_profile_service(profile_service.clone()),
+        );
+        let controller = UpdateProfileController::new(player_service.clone(), profile_service);
+        let player = player_service.add_player(0, String::from("test"));
+        let profile = profile::Profile {
+            avatar_id: 7,
+            tile_color: TileColor::Sunset as i32,
+            title: Title::NoTitle as i32,
+        };
+        controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::UpdateProfile(UpdateProfileRequest {
+                    profile: Some(profile.clone()),
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        assert_eq!(
+            crate::model::player::player::Player::from(player).profile,
+            Some(profile.clone())
+        );
+        // Loaded again on the next connection
+        player_service.remove_player(player_service.get_player(0).unwrap())?;
+        let player = player_service.add_player(1, String::from("test"));
+        assert_eq!(player.get_profile().avatar_id, 7);
+        let invalid = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::UpdateProfile(UpdateProfileRequest {
+                    profile: Some(profile::Profile {
+                        tile_color: 99,
+                        ..profile
+                    }),
+                })),
+            ),
+            RequestContext::new(1),
+        );
+        assert!(invalid.is_err());
+        Ok(())
+    }
+}
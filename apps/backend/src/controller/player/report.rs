@@ -0,0 +1,112 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::player::report::ReportPlayerResponse;
+use crate::service::chat_service::ChatService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct ReportController {
+    player_service: Arc<PlayerService>,
+    chat_service: Arc<ChatService>,
+}
+
+impl ReportController {
+    pub fn new(player_service: Arc<PlayerService>, chat_service: Arc<ChatService>) -> Self {
+        Self {
+            player_service,
+            chat_service,
+        }
+    }
+}
+
+impl PrintableController for ReportController {}
+
+impl Controller for ReportController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::ReportPlayer(req) => req,
+            _ => panic!("invalid request"),
+        };
+        let reporter = match self.player_service.get_player(context.client_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let reported = match self.player_service.get_player(req.player_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        self.chat_service
+            .report(&reporter, &reported, &req.reason)?;
+        Ok(ResponseData::ReportPlayer(ReportPlayerResponse {
+            success: true,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        config::ChatConfig,
+        model::player::report::ReportPlayerRequest,
+        service::broadcast_service::BroadcastService,
+        service::{game_service::GameService, lobby_service::LobbyService},
+        storage::memory::MemoryStorage,
+    };
+
+    use super::*;
+
+    #[test]
+    fn handle_request_with_other_player_should_record_report(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let controller = ReportController::new(
+            Arc::new(PlayerService::new(
+                Arc::new(LobbyService::new()),
+                Arc::new(GameService::new(HashSet::new())),
+            )),
+            Arc::new(ChatService::new(
+                &ChatConfig::default(),
+                Arc::new(MemoryStorage::new()),
+                Arc::new(BroadcastService::new()),
+            )),
+        );
+        controller
+            .player_service
+            .add_player(0, String::from("test"));
+        controller
+            .player_service
+            .add_player(1, String::from("test1"));
+        let res = controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::ReportPlayer(ReportPlayerRequest {
+                    player_id: 1,
+                    reason: String::from("spam"),
+                })),
+            ),
+            RequestContext::new(0),
+        )?;
+        assert_eq!(
+            res,
+            ResponseData::ReportPlayer(ReportPlayerResponse { success: true })
+        );
+        let reports = controller.chat_service.get_reports("test1")?;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].reason, "spam");
+        Ok(())
+    }
+}
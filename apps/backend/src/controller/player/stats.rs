@@ -0,0 +1,112 @@
+use crate::error::ServerError;
+use crate::frame::Request;
+use crate::model::player::stats::{GetPlayerStatsResponse, PlayerStats};
+use crate::service::stats_service::StatsService;
+use crate::{
+    controller::controller::PrintableController,
+    frame::{RequestData, ResponseData},
+    router::RequestContext,
+    service::player_service::PlayerService,
+};
+use std::sync::Arc;
+
+use crate::controller::controller::Controller;
+
+#[derive(Debug, Clone)]
+pub struct StatsController {
+    player_service: Arc<PlayerService>,
+    stats_service: Arc<StatsService>,
+}
+
+impl StatsController {
+    pub fn new(player_service: Arc<PlayerService>, stats_service: Arc<StatsService>) -> Self {
+        Self {
+            player_service,
+            stats_service,
+        }
+    }
+}
+
+impl PrintableController for StatsController {}
+
+impl Controller for StatsController {
+    fn handle_request(
+        &self,
+        req: Request,
+        context: RequestContext,
+    ) -> Result<ResponseData, ServerError> {
+        let data = req.get_data();
+        let req = match data.as_ref() {
+            RequestData::GetPlayerStats(req) => req,
+            _ => panic!("invalid request"),
+        };
+        if self.player_service.get_player(context.client_id).is_none() {
+            return Err(ServerError::PlayerNotFound);
+        }
+        // Stats belong to the account, which is the name until players can
+        // sign in
+        let player = match self.player_service.get_player(req.player_id) {
+            Some(player) => player,
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        let stats = self.stats_service.get_stats(&player.name)?;
+        Ok(ResponseData::GetPlayerStats(GetPlayerStatsResponse {
+            success: true,
+            stats: Some(PlayerStats::new(player.name.clone(), &stats)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use crate::{
+        game::game::Game,
+        model::player::stats::GetPlayerStatsRequest,
+        service::{game_service::GameService, lobby_service::LobbyService},
+    };
+
+    use super::*;
+
+    #[test]
+    fn handle_request_with_other_player_should_return_their_stats(
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            game_service.clone(),
+        ));
+        let controller = StatsController::new(player_service, game_service.get_stats_service());
+        controller
+            .player_service
+            .add_player(0, String::from("test"));
+        let player = controller
+            .player_service
+            .add_player(1, String::from("test1"));
+        let game = Game::new(0, vec![player]);
+        game.get_player(1)
+            .unwrap()
+            .record_turn(&[(String::from("the"), 6)], 3);
+        controller.stats_service.record_game(&game)?;
+        match controller.handle_request(
+            Request::new(
+                0,
+                Arc::new(RequestData::GetPlayerStats(GetPlayerStatsRequest {
+                    player_id: 1,
+                })),
+            ),
+            RequestContext::new(0),
+        )? {
+            ResponseData::GetPlayerStats(res) => {
+                let stats = res.stats.unwrap();
+                assert_eq!(stats.name, "test1");
+                assert_eq!(stats.games_played, 1);
+                assert_eq!(stats.best_word, "the");
+                assert_eq!(stats.tiles_placed, 3);
+            }
+            _ => panic!("wrong response type"),
+        }
+        Ok(())
+    }
+}
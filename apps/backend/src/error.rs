@@ -0,0 +1,146 @@
+use std::error::Error;
+
+use crate::{
+    frame,
+    game::{board::PlacementError, game::MoveViolation},
+    model::error::error::ErrorCode,
+    validation::InvalidRequest,
+};
+
+/// What went wrong handling a request, as services and controllers report
+/// it. The router turns it into the error code clients see, so the variants
+/// are the failures a client can act on; everything else is `Other`.
+#[derive(Debug, thiserror::Error)]
+pub enum ServerError {
+    #[error("Player not found")]
+    PlayerNotFound,
+    #[error("Player not in a lobby")]
+    NotInLobby,
+    #[error("Player not in a game")]
+    NotInGame,
+    #[error("Player already in a lobby")]
+    AlreadyInLobby,
+    #[error("Player already in a game")]
+    AlreadyInGame,
+    #[error("Already connected from another session")]
+    AlreadyConnected,
+    /// Asked for something that needs a session before connecting.
+    #[error("Not connected")]
+    Unauthenticated,
+    /// Asked for something outside the session's scopes.
+    #[error("Not allowed for this session")]
+    Forbidden,
+    /// Asked for an operation this server has no controller for, such as
+    /// one a client built against a newer server knows.
+    #[error("Operation not supported")]
+    UnsupportedOperation,
+    #[error("Lobby not found")]
+    LobbyNotFound,
+    #[error("Lobby is full")]
+    LobbyFull,
+    /// The lobby lives on another instance, which clients reach at the
+    /// address.
+    #[error("{0}")]
+    Moved(String),
+    /// Banned from the lobby or blocked by its leader, which the player
+    /// isn't told apart.
+    #[error("Not allowed to join this lobby")]
+    BannedFromLobby,
+    #[error("Game not found")]
+    GameNotFound,
+    #[error("Game is paused")]
+    GamePaused,
+    /// Asked for something only the player on turn may do, other than a
+    /// move, which is a `MoveViolation`.
+    #[error("Player not in his turn")]
+    NotYourTurn,
+    #[error(transparent)]
+    InvalidPlacement(#[from] PlacementError),
+    #[error(transparent)]
+    Move(#[from] MoveViolation),
+    #[error(transparent)]
+    InvalidRequest(#[from] InvalidRequest),
+    #[error(transparent)]
+    Frame(#[from] frame::Error),
+    #[error(transparent)]
+    Other(Box<dyn Error + Send + Sync>),
+}
+
+impl ServerError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ServerError::PlayerNotFound => ErrorCode::PlayerNotFound,
+            ServerError::NotInLobby => ErrorCode::NotInLobby,
+            ServerError::NotInGame => ErrorCode::NotInGame,
+            ServerError::AlreadyInLobby => ErrorCode::AlreadyInLobby,
+            ServerError::AlreadyInGame => ErrorCode::AlreadyInGame,
+            ServerError::AlreadyConnected => ErrorCode::AlreadyConnected,
+            ServerError::Unauthenticated => ErrorCode::Unauthenticated,
+            ServerError::Forbidden => ErrorCode::Forbidden,
+            ServerError::UnsupportedOperation => ErrorCode::UnsupportedOperation,
+            ServerError::LobbyNotFound => ErrorCode::LobbyNotFound,
+            ServerError::LobbyFull => ErrorCode::LobbyFull,
+            ServerError::Moved(_) => ErrorCode::Moved,
+            ServerError::BannedFromLobby => ErrorCode::BannedFromLobby,
+            ServerError::GameNotFound => ErrorCode::GameNotFound,
+            ServerError::GamePaused => ErrorCode::GamePaused,
+            ServerError::NotYourTurn => ErrorCode::NotYourTurn,
+            ServerError::InvalidPlacement(error) => ErrorCode::from(*error),
+            ServerError::Move(violation) => ErrorCode::from(*violation),
+            ServerError::InvalidRequest(_) => ErrorCode::InvalidRequest,
+            ServerError::Frame(error) => ErrorCode::from(error),
+            ServerError::Other(_) => ErrorCode::Unknown,
+        }
+    }
+}
+
+/// Errors from code that still returns boxed errors, such as `Game::apply`
+/// and storage. Typed errors are taken back out of the box, so they keep
+/// their code.
+impl From<Box<dyn Error + Send + Sync>> for ServerError {
+    fn from(error: Box<dyn Error + Send + Sync>) -> Self {
+        let error = match error.downcast::<ServerError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        if let Some(error) = error.downcast_ref::<PlacementError>() {
+            return ServerError::InvalidPlacement(*error);
+        }
+        if let Some(violation) = error.downcast_ref::<MoveViolation>() {
+            return ServerError::Move(*violation);
+        }
+        match error.downcast::<frame::Error>() {
+            Ok(error) => ServerError::Frame(*error),
+            Err(error) => ServerError::Other(error),
+        }
+    }
+}
+
+impl From<&str> for ServerError {
+    fn from(message: &str) -> Self {
+        ServerError::Other(message.into())
+    }
+}
+
+impl From<String> for ServerError {
+    fn from(message: String) -> Self {
+        ServerError::Other(message.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_boxed_typed_error_should_keep_code() {
+        let boxed: Box<dyn Error + Send + Sync> = Box::new(PlacementError::Occupied);
+        assert_eq!(ServerError::from(boxed).code(), ErrorCode::TileOccupied);
+        let boxed: Box<dyn Error + Send + Sync> = Box::new(ServerError::LobbyFull);
+        assert_eq!(ServerError::from(boxed).code(), ErrorCode::LobbyFull);
+        let boxed: Box<dyn Error + Send + Sync> = "invalid word".into();
+        let error = ServerError::from(boxed);
+        assert_eq!(error.code(), ErrorCode::Unknown);
+        assert_eq!(error.to_string(), "invalid word");
+    }
+}
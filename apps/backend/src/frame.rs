@@ -1,66 +1,162 @@
-use std::{io::Cursor, sync::Arc};
+use std::{fmt, io::Cursor, sync::Arc};
 
 use bytes::Buf;
 use prost::Message;
 
 use crate::{
+    model::admin::announce::{AnnounceRequest, AnnounceResponse},
+    model::admin::ban::{BanRequest, BanResponse},
+    model::admin::close_game::{CloseGameRequest, CloseGameResponse},
+    model::admin::handoff::{HandoffRequest, HandoffResponse},
+    model::admin::inspect::{InspectRequest, InspectResponse},
+    model::admin::issue_token::{IssueTokenRequest, IssueTokenResponse},
+    model::admin::kick::{KickRequest, KickResponse},
+    model::admin::list_players::{ListPlayersRequest, ListPlayersResponse},
+    model::admin::maintenance::{MaintenanceRequest, MaintenanceResponse},
+    model::admin::reload::{ReloadRequest, ReloadResponse},
+    model::admin::revoke_token::{RevokeTokenRequest, RevokeTokenResponse},
+    model::chat::{
+        broadcast::ChatBroadcast,
+        quick_chat::{QuickChat, SendQuickChatRequest, SendQuickChatResponse},
+        send::{SendChatRequest, SendChatResponse},
+    },
+    model::control::connect::Capability,
     model::control::connect::ConnectRequest,
     model::control::connect::ConnectResponse,
     model::control::disconnect::DisconnectResponse,
-    model::control::heartbeat::HeartbeatResponse,
-    model::game::broadcast::GameBroadcast,
+    model::control::heartbeat::{HeartbeatRequest, HeartbeatResponse},
+    model::control::notice::Notice,
+    model::control::opcodes::ListSupportedOpcodesResponse,
+    model::control::preferences::{SetPreferencesRequest, SetPreferencesResponse},
+    model::control::server_info::ServerInfoResponse,
+    model::control::time_sync::{TimeSyncRequest, TimeSyncResponse},
+    model::frame::envelope::{RequestEnvelope, ResponseEnvelope, ResponseKind},
+    model::game::analyze::{AnalyzeGameRequest, AnalyzeGameResponse},
+    model::game::bag_info::GetBagInfoResponse,
+    model::game::broadcast::{GameBroadcast, GameEvent},
+    model::game::draw::{AnswerDrawRequest, AnswerDrawResponse, OfferDrawResponse},
+    model::game::exchange::{ExchangeCardsRequest, ExchangeCardsResponse},
     model::game::exit::ExitResponse,
+    model::game::export::{ExportGameRequest, ExportGameResponse},
     model::game::finish_turn::FinishTurnResponse,
     model::game::get_new_card::GetNewCardResponse,
+    model::game::hint::{HintRequest, HintResponse},
+    model::game::pause::PauseResponse,
+    model::game::resign::ResignResponse,
+    model::game::resume::ResumeResponse,
+    model::game::resync::{ResyncFromRequest, ResyncFromResponse},
     model::game::set_tile::SetTileRequest,
     model::game::set_tile::SetTileResponse,
+    model::game::snapshot::BoardSnapshotResponse,
+    model::game::spectate::{SpectateRequest, SpectateResponse},
+    model::game::subscribe::{SubscribeGameRequest, SubscribeGameResponse},
+    model::game::vote_kick::{
+        AnswerVoteKickRequest, AnswerVoteKickResponse, StartVoteKickRequest, StartVoteKickResponse,
+    },
     model::game::{
         cancel::{CancelRequest, CancelResponse},
         start::StartResponse,
     },
-    model::lobby::broadcast::LobbyBroadcast,
+    model::leaderboard::daily::{DailyLeaderboardRequest, DailyLeaderboardResponse},
+    model::leaderboard::season::{SeasonInfoRequest, SeasonInfoResponse},
+    model::lobby::broadcast::{LobbyBroadcast, LobbyEvent},
     model::lobby::create::CreateRequest,
     model::lobby::create::CreateResponse,
+    model::lobby::join::JoinByCodeRequest,
     model::lobby::join::JoinRequest,
     model::lobby::join::JoinResponse,
+    model::lobby::kick::{KickFromLobbyRequest, KickFromLobbyResponse},
     model::lobby::list::ListResponse,
     model::lobby::quit::QuitResponse,
     model::lobby::ready::ReadyResponse,
+    model::lobby::update_settings::{UpdateLobbySettingsRequest, UpdateLobbySettingsResponse},
+    model::lobby::upload_dictionary::{UploadDictionaryRequest, UploadDictionaryResponse},
+    model::player::stats::{GetPlayerStatsRequest, GetPlayerStatsResponse},
+    model::player::{
+        block::{BlockPlayerRequest, BlockPlayerResponse},
+        mute::{MutePlayerRequest, MutePlayerResponse},
+        profile::{UpdateProfileRequest, UpdateProfileResponse},
+        report::{ReportPlayerRequest, ReportPlayerResponse},
+    },
+    model::state::State,
     operation::Operation,
 };
 use std::hash::{Hash, Hasher};
 
+/// Bare protobuf messages behind a hand-rolled header, spoken by clients that
+/// don't announce a version in `ConnectRequest`.
+pub const LEGACY_PROTOCOL_VERSION: u32 = 1;
+/// Every frame is a length prefixed `RequestEnvelope`/`ResponseEnvelope`.
+pub const PROTOCOL_VERSION: u32 = 2;
+/// Set on the length prefix of an envelope frame whose body is LZ4 compressed.
+pub const COMPRESSED_FLAG: u32 = 1 << 31;
+/// Largest request body a client may send, before and after decompression.
+/// Longer lengths are refused before anything is buffered or allocated.
+pub const MAX_FRAME_LEN: usize = 1 << 20;
+
 #[derive(Debug)]
 pub enum Frame {
     Request(Request),
     Response(Response),
+    /// Tells the writer task to shut the connection down.
+    Close,
 }
 
+/// A request sent by the client. `request_id` is generated by the client and
+/// echoed back on the matching `Response`, so clients can pipeline requests.
+/// Ids that collide with a `State` value are reserved for push events.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Request {
-    state: u32,
+    request_id: u32,
     data: Arc<RequestData>,
 }
 
 impl Request {
-    pub fn new(state: u32, data: Arc<RequestData>) -> Self {
-        Self { state, data }
+    pub fn new(request_id: u32, data: Arc<RequestData>) -> Self {
+        Self { request_id, data }
     }
 
-    pub fn get_state(&self) -> u32 {
-        self.state
+    pub fn get_request_id(&self) -> u32 {
+        self.request_id
     }
 
     pub fn get_data(&self) -> Arc<RequestData> {
         self.data.clone()
     }
+
+    /// Encode the request the way a client puts it on the wire. The server
+    /// only decodes requests, this is for tools that talk to it.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = self.data.encode_payload();
+        let op = Operation::try_from(self.data.as_ref())?;
+        let mut bytes = Vec::with_capacity(12 + payload.len());
+        bytes.extend_from_slice(&[u8::from(&op), 0, 0, 0]);
+        bytes.extend_from_slice(&self.request_id.to_be_bytes());
+        bytes.extend_from_slice(&u32::try_from(payload.len())?.to_be_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    /// Encode the request as an envelope frame, used once a connection has
+    /// negotiated `PROTOCOL_VERSION`.
+    pub fn to_envelope_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        let envelope = RequestEnvelope {
+            request_id: self.request_id,
+            operation: u8::from(&Operation::try_from(self.data.as_ref())?) as u32,
+            payload: self.data.encode_payload(),
+        };
+        let mut bytes = Vec::with_capacity(4 + envelope.encoded_len());
+        bytes.extend_from_slice(&u32::try_from(envelope.encoded_len())?.to_be_bytes());
+        envelope.encode(&mut bytes)?;
+        Ok(bytes)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RequestData {
     Connect(ConnectRequest),
     Disconnect,
-    Heartbeat,
+    Heartbeat(HeartbeatRequest),
     CreateLobby(CreateRequest),
     JoinLobby(JoinRequest),
     QuitLobby,
@@ -72,6 +168,53 @@ pub enum RequestData {
     GetNewCard,
     Cancel(CancelRequest),
     Exit,
+    PauseGame,
+    ResumeGame,
+    GetHint(HintRequest),
+    GetPlayerStats(GetPlayerStatsRequest),
+    StartDailyChallenge,
+    GetDailyLeaderboard(DailyLeaderboardRequest),
+    SendChat(SendChatRequest),
+    ReportPlayer(ReportPlayerRequest),
+    MutePlayer(MutePlayerRequest),
+    ListSupportedOpcodes,
+    UploadDictionary(UploadDictionaryRequest),
+    Resign,
+    OfferDraw,
+    AnswerDraw(AnswerDrawRequest),
+    JoinByCode(JoinByCodeRequest),
+    QuickJoin,
+    UpdateProfile(UpdateProfileRequest),
+    BlockPlayer(BlockPlayerRequest),
+    KickFromLobby(KickFromLobbyRequest),
+    ServerInfo,
+    GetSeasonInfo(SeasonInfoRequest),
+    Spectate(SpectateRequest),
+    ExportGame(ExportGameRequest),
+    AnalyzeGame(AnalyzeGameRequest),
+    UpdateLobbySettings(UpdateLobbySettingsRequest),
+    StartSolo,
+    SendQuickChat(SendQuickChatRequest),
+    TimeSync(TimeSyncRequest),
+    SetPreferences(SetPreferencesRequest),
+    BoardSnapshot,
+    ResyncFrom(ResyncFromRequest),
+    GetBagInfo,
+    ExchangeCards(ExchangeCardsRequest),
+    StartVoteKick(StartVoteKickRequest),
+    AnswerVoteKick(AnswerVoteKickRequest),
+    SubscribeGame(SubscribeGameRequest),
+    AdminListPlayers(ListPlayersRequest),
+    AdminKick(KickRequest),
+    AdminBan(BanRequest),
+    AdminAnnounce(AnnounceRequest),
+    AdminMaintenance(MaintenanceRequest),
+    AdminInspect(InspectRequest),
+    AdminCloseGame(CloseGameRequest),
+    AdminReload(ReloadRequest),
+    AdminIssueToken(IssueTokenRequest),
+    AdminRevokeToken(RevokeTokenRequest),
+    AdminHandoff(HandoffRequest),
 }
 
 impl Hash for RequestData {
@@ -79,7 +222,7 @@ impl Hash for RequestData {
         match self {
             RequestData::Connect(_) => 0.hash(state),
             RequestData::Disconnect => 1.hash(state),
-            RequestData::Heartbeat => 2.hash(state),
+            RequestData::Heartbeat(_) => 2.hash(state),
             RequestData::CreateLobby(_) => 3.hash(state),
             RequestData::JoinLobby(_) => 4.hash(state),
             RequestData::QuitLobby => 5.hash(state),
@@ -91,21 +234,145 @@ impl Hash for RequestData {
             RequestData::GetNewCard => 11.hash(state),
             RequestData::Cancel(_) => 12.hash(state),
             RequestData::Exit => 13.hash(state),
+            RequestData::PauseGame => 14.hash(state),
+            RequestData::ResumeGame => 15.hash(state),
+            RequestData::GetHint(_) => 16.hash(state),
+            RequestData::GetPlayerStats(_) => 17.hash(state),
+            RequestData::StartDailyChallenge => 18.hash(state),
+            RequestData::GetDailyLeaderboard(_) => 19.hash(state),
+            RequestData::SendChat(_) => 20.hash(state),
+            RequestData::ReportPlayer(_) => 21.hash(state),
+            RequestData::MutePlayer(_) => 22.hash(state),
+            RequestData::ListSupportedOpcodes => 23.hash(state),
+            RequestData::UploadDictionary(_) => 24.hash(state),
+            RequestData::Resign => 25.hash(state),
+            RequestData::OfferDraw => 26.hash(state),
+            RequestData::AnswerDraw(_) => 27.hash(state),
+            RequestData::JoinByCode(_) => 28.hash(state),
+            RequestData::QuickJoin => 29.hash(state),
+            RequestData::UpdateProfile(_) => 30.hash(state),
+            RequestData::BlockPlayer(_) => 31.hash(state),
+            RequestData::KickFromLobby(_) => 32.hash(state),
+            RequestData::ServerInfo => 33.hash(state),
+            RequestData::GetSeasonInfo(_) => 34.hash(state),
+            RequestData::Spectate(_) => 35.hash(state),
+            RequestData::ExportGame(_) => 36.hash(state),
+            RequestData::AnalyzeGame(_) => 37.hash(state),
+            RequestData::UpdateLobbySettings(_) => 38.hash(state),
+            RequestData::StartSolo => 39.hash(state),
+            RequestData::SendQuickChat(_) => 40.hash(state),
+            RequestData::TimeSync(_) => 41.hash(state),
+            RequestData::SetPreferences(_) => 42.hash(state),
+            RequestData::BoardSnapshot => 43.hash(state),
+            RequestData::ResyncFrom(_) => 44.hash(state),
+            RequestData::GetBagInfo => 45.hash(state),
+            RequestData::ExchangeCards(_) => 46.hash(state),
+            RequestData::StartVoteKick(_) => 47.hash(state),
+            RequestData::AnswerVoteKick(_) => 48.hash(state),
+            RequestData::SubscribeGame(_) => 49.hash(state),
+            RequestData::AdminListPlayers(_) => 128.hash(state),
+            RequestData::AdminKick(_) => 129.hash(state),
+            RequestData::AdminBan(_) => 130.hash(state),
+            RequestData::AdminAnnounce(_) => 131.hash(state),
+            RequestData::AdminMaintenance(_) => 132.hash(state),
+            RequestData::AdminInspect(_) => 133.hash(state),
+            RequestData::AdminCloseGame(_) => 134.hash(state),
+            RequestData::AdminReload(_) => 135.hash(state),
+            RequestData::AdminIssueToken(_) => 136.hash(state),
+            RequestData::AdminRevokeToken(_) => 137.hash(state),
+            RequestData::AdminHandoff(_) => 138.hash(state),
         }
     }
 }
 
+/// Optional pushes a client can turn off with `SetPreferences`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventClass {
+    Chat,
+    Emote,
+    SpectatorJoin,
+}
+
+/// A frame sent to the client. Replies carry the id of the request they
+/// answer, push events carry `None` and are tagged with their `State` instead.
 #[derive(Debug, Clone)]
 pub struct Response {
+    request_id: Option<u32>,
     state: u32,
     data: Arc<ResponseData>,
 }
 
 impl Response {
-    pub fn new(state: u32, data: Arc<ResponseData>) -> Self {
-        Self { state, data }
+    pub fn new(request_id: u32, data: Arc<ResponseData>) -> Self {
+        Self {
+            request_id: Some(request_id),
+            state: request_id,
+            data,
+        }
+    }
+
+    pub fn event(state: State, data: Arc<ResponseData>) -> Self {
+        Self {
+            request_id: None,
+            state: state as u32,
+            data,
+        }
     }
 
+    pub fn get_request_id(&self) -> Option<u32> {
+        self.request_id
+    }
+
+    /// The capability a client has to announce before it is sent this
+    /// event, `None` for events every client understands.
+    pub fn get_capability(&self) -> Option<Capability> {
+        if self.request_id.is_some() {
+            return None;
+        }
+        match self.data.as_ref() {
+            ResponseData::GameBroadcast(broadcast) => match broadcast.event() {
+                GameEvent::YourTurn | GameEvent::TurnChanged => Some(Capability::TurnEvents),
+                GameEvent::Forfeit | GameEvent::Resign => Some(Capability::WithdrawEvents),
+                GameEvent::DrawOffer | GameEvent::DrawDeclined => Some(Capability::DrawEvents),
+                GameEvent::LagWarning => Some(Capability::LatencyEvents),
+                GameEvent::HandUpdate => Some(Capability::HandEvents),
+                GameEvent::PlayerDisconnected => Some(Capability::DisconnectEvents),
+                GameEvent::PowerUp => Some(Capability::PowerUpEvents),
+                GameEvent::SpectatorJoined => Some(Capability::SpectatorEvents),
+                GameEvent::VoteKick | GameEvent::VoteKickFailed => Some(Capability::VoteKickEvents),
+                _ => None,
+            },
+            ResponseData::LobbyBroadcast(broadcast) => match broadcast.event() {
+                LobbyEvent::LagWarning => Some(Capability::LatencyEvents),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// What the event is as far as client preferences go, `None` for
+    /// events no client can turn off.
+    pub fn get_event_class(&self) -> Option<EventClass> {
+        if self.request_id.is_some() {
+            return None;
+        }
+        match self.data.as_ref() {
+            ResponseData::ChatBroadcast(chat) => {
+                match chat.quick_chat.and_then(QuickChat::from_i32) {
+                    Some(phrase) if phrase.is_emote() => Some(EventClass::Emote),
+                    _ => Some(EventClass::Chat),
+                }
+            }
+            ResponseData::GameBroadcast(broadcast) => match broadcast.event() {
+                GameEvent::SpectatorJoined => Some(EventClass::SpectatorJoin),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The value written in the frame header: the echoed request id for
+    /// replies, or the event `State` for pushes.
     pub fn get_state(&self) -> u32 {
         self.state
     }
@@ -113,6 +380,17 @@ impl Response {
     pub fn get_data(&self) -> Arc<ResponseData> {
         self.data.clone()
     }
+
+    /// Stamp a game event with the game's state version, other frames are
+    /// left as they are.
+    pub fn set_state_version(&mut self, version: u64) {
+        if let ResponseData::GameBroadcast(broadcast) = self.data.as_ref() {
+            self.data = Arc::new(ResponseData::GameBroadcast(GameBroadcast {
+                state_version: version,
+                ..broadcast.clone()
+            }));
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -134,132 +412,494 @@ pub enum ResponseData {
     GetNewCard(GetNewCardResponse),
     GameBroadcast(GameBroadcast),
     Exit(ExitResponse),
+    PauseGame(PauseResponse),
+    ResumeGame(ResumeResponse),
+    GetHint(HintResponse),
+    GetPlayerStats(GetPlayerStatsResponse),
+    StartDailyChallenge(StartResponse),
+    GetDailyLeaderboard(DailyLeaderboardResponse),
+    SendChat(SendChatResponse),
+    ReportPlayer(ReportPlayerResponse),
+    MutePlayer(MutePlayerResponse),
+    ListSupportedOpcodes(ListSupportedOpcodesResponse),
+    UploadDictionary(UploadDictionaryResponse),
+    Resign(ResignResponse),
+    OfferDraw(OfferDrawResponse),
+    AnswerDraw(AnswerDrawResponse),
+    JoinByCode(JoinResponse),
+    QuickJoin(JoinResponse),
+    UpdateProfile(UpdateProfileResponse),
+    BlockPlayer(BlockPlayerResponse),
+    KickFromLobby(KickFromLobbyResponse),
+    ServerInfo(ServerInfoResponse),
+    GetSeasonInfo(SeasonInfoResponse),
+    Spectate(SpectateResponse),
+    ExportGame(ExportGameResponse),
+    AnalyzeGame(AnalyzeGameResponse),
+    UpdateLobbySettings(UpdateLobbySettingsResponse),
+    StartSolo(StartResponse),
+    SendQuickChat(SendQuickChatResponse),
+    TimeSync(TimeSyncResponse),
+    SetPreferences(SetPreferencesResponse),
+    BoardSnapshot(BoardSnapshotResponse),
+    ResyncFrom(ResyncFromResponse),
+    GetBagInfo(GetBagInfoResponse),
+    ExchangeCards(ExchangeCardsResponse),
+    StartVoteKick(StartVoteKickResponse),
+    AnswerVoteKick(AnswerVoteKickResponse),
+    SubscribeGame(SubscribeGameResponse),
+    AdminListPlayers(ListPlayersResponse),
+    AdminKick(KickResponse),
+    AdminBan(BanResponse),
+    AdminAnnounce(AnnounceResponse),
+    AdminMaintenance(MaintenanceResponse),
+    AdminInspect(InspectResponse),
+    AdminCloseGame(CloseGameResponse),
+    AdminReload(ReloadResponse),
+    AdminIssueToken(IssueTokenResponse),
+    AdminRevokeToken(RevokeTokenResponse),
+    AdminHandoff(HandoffResponse),
+    Notice(Notice),
+    ChatBroadcast(ChatBroadcast),
+}
+
+impl RequestData {
+    /// Decode the payload of a request with the given operation.
+    pub fn decode(op: &Operation, payload: impl Buf) -> Result<Self, prost::DecodeError> {
+        Ok(match op {
+            Operation::Connect => RequestData::Connect(ConnectRequest::decode(payload)?),
+            Operation::Disconnect => RequestData::Disconnect,
+            Operation::Heartbeat => RequestData::Heartbeat(HeartbeatRequest::decode(payload)?),
+            Operation::CreateLobby => RequestData::CreateLobby(CreateRequest::decode(payload)?),
+            Operation::JoinLobby => RequestData::JoinLobby(JoinRequest::decode(payload)?),
+            Operation::QuitLobby => RequestData::QuitLobby,
+            Operation::ListLobby => RequestData::ListLobby,
+            Operation::Ready => RequestData::Ready,
+            Operation::StartGame => RequestData::StartGame,
+            Operation::SetTile => RequestData::SetTile(SetTileRequest::decode(payload)?),
+            Operation::FinishTurn => RequestData::FinishTurn,
+            Operation::GetNewCard => RequestData::GetNewCard,
+            Operation::Cancel => RequestData::Cancel(CancelRequest::decode(payload)?),
+            Operation::Exit => RequestData::Exit,
+            Operation::PauseGame => RequestData::PauseGame,
+            Operation::ResumeGame => RequestData::ResumeGame,
+            Operation::GetHint => RequestData::GetHint(HintRequest::decode(payload)?),
+            Operation::GetPlayerStats => {
+                RequestData::GetPlayerStats(GetPlayerStatsRequest::decode(payload)?)
+            }
+            Operation::StartDailyChallenge => RequestData::StartDailyChallenge,
+            Operation::GetDailyLeaderboard => {
+                RequestData::GetDailyLeaderboard(DailyLeaderboardRequest::decode(payload)?)
+            }
+            Operation::SendChat => RequestData::SendChat(SendChatRequest::decode(payload)?),
+            Operation::ReportPlayer => {
+                RequestData::ReportPlayer(ReportPlayerRequest::decode(payload)?)
+            }
+            Operation::MutePlayer => RequestData::MutePlayer(MutePlayerRequest::decode(payload)?),
+            Operation::ListSupportedOpcodes => RequestData::ListSupportedOpcodes,
+            Operation::UploadDictionary => {
+                RequestData::UploadDictionary(UploadDictionaryRequest::decode(payload)?)
+            }
+            Operation::Resign => RequestData::Resign,
+            Operation::OfferDraw => RequestData::OfferDraw,
+            Operation::AnswerDraw => RequestData::AnswerDraw(AnswerDrawRequest::decode(payload)?),
+            Operation::JoinByCode => RequestData::JoinByCode(JoinByCodeRequest::decode(payload)?),
+            Operation::QuickJoin => RequestData::QuickJoin,
+            Operation::UpdateProfile => {
+                RequestData::UpdateProfile(UpdateProfileRequest::decode(payload)?)
+            }
+            Operation::BlockPlayer => {
+                RequestData::BlockPlayer(BlockPlayerRequest::decode(payload)?)
+            }
+            Operation::KickFromLobby => {
+                RequestData::KickFromLobby(KickFromLobbyRequest::decode(payload)?)
+            }
+            Operation::ServerInfo => RequestData::ServerInfo,
+            Operation::GetSeasonInfo => {
+                RequestData::GetSeasonInfo(SeasonInfoRequest::decode(payload)?)
+            }
+            Operation::Spectate => RequestData::Spectate(SpectateRequest::decode(payload)?),
+            Operation::ExportGame => RequestData::ExportGame(ExportGameRequest::decode(payload)?),
+            Operation::AnalyzeGame => {
+                RequestData::AnalyzeGame(AnalyzeGameRequest::decode(payload)?)
+            }
+            Operation::UpdateLobbySettings => {
+                RequestData::UpdateLobbySettings(UpdateLobbySettingsRequest::decode(payload)?)
+            }
+            Operation::StartSolo => RequestData::StartSolo,
+            Operation::SendQuickChat => {
+                RequestData::SendQuickChat(SendQuickChatRequest::decode(payload)?)
+            }
+            Operation::TimeSync => RequestData::TimeSync(TimeSyncRequest::decode(payload)?),
+            Operation::SetPreferences => {
+                RequestData::SetPreferences(SetPreferencesRequest::decode(payload)?)
+            }
+            Operation::BoardSnapshot => RequestData::BoardSnapshot,
+            Operation::ResyncFrom => RequestData::ResyncFrom(ResyncFromRequest::decode(payload)?),
+            Operation::GetBagInfo => RequestData::GetBagInfo,
+            Operation::ExchangeCards => {
+                RequestData::ExchangeCards(ExchangeCardsRequest::decode(payload)?)
+            }
+            Operation::StartVoteKick => {
+                RequestData::StartVoteKick(StartVoteKickRequest::decode(payload)?)
+            }
+            Operation::AnswerVoteKick => {
+                RequestData::AnswerVoteKick(AnswerVoteKickRequest::decode(payload)?)
+            }
+            Operation::SubscribeGame => {
+                RequestData::SubscribeGame(SubscribeGameRequest::decode(payload)?)
+            }
+            Operation::AdminListPlayers => {
+                RequestData::AdminListPlayers(ListPlayersRequest::decode(payload)?)
+            }
+            Operation::AdminKick => RequestData::AdminKick(KickRequest::decode(payload)?),
+            Operation::AdminBan => RequestData::AdminBan(BanRequest::decode(payload)?),
+            Operation::AdminAnnounce => {
+                RequestData::AdminAnnounce(AnnounceRequest::decode(payload)?)
+            }
+            Operation::AdminMaintenance => {
+                RequestData::AdminMaintenance(MaintenanceRequest::decode(payload)?)
+            }
+            Operation::AdminInspect => RequestData::AdminInspect(InspectRequest::decode(payload)?),
+            Operation::AdminCloseGame => {
+                RequestData::AdminCloseGame(CloseGameRequest::decode(payload)?)
+            }
+            Operation::AdminReload => RequestData::AdminReload(ReloadRequest::decode(payload)?),
+            Operation::AdminIssueToken => {
+                RequestData::AdminIssueToken(IssueTokenRequest::decode(payload)?)
+            }
+            Operation::AdminRevokeToken => {
+                RequestData::AdminRevokeToken(RevokeTokenRequest::decode(payload)?)
+            }
+            Operation::AdminHandoff => RequestData::AdminHandoff(HandoffRequest::decode(payload)?),
+        })
+    }
+
+    /// Encode the payload of the request, empty for requests without one.
+    pub fn encode_payload(&self) -> Vec<u8> {
+        match self {
+            RequestData::Connect(req) => req.encode_to_vec(),
+            RequestData::Heartbeat(req) => req.encode_to_vec(),
+            RequestData::CreateLobby(req) => req.encode_to_vec(),
+            RequestData::JoinLobby(req) => req.encode_to_vec(),
+            RequestData::SetTile(req) => req.encode_to_vec(),
+            RequestData::Cancel(req) => req.encode_to_vec(),
+            RequestData::GetHint(req) => req.encode_to_vec(),
+            RequestData::GetPlayerStats(req) => req.encode_to_vec(),
+            RequestData::GetDailyLeaderboard(req) => req.encode_to_vec(),
+            RequestData::GetSeasonInfo(req) => req.encode_to_vec(),
+            RequestData::Spectate(req) => req.encode_to_vec(),
+            RequestData::ExportGame(req) => req.encode_to_vec(),
+            RequestData::AnalyzeGame(req) => req.encode_to_vec(),
+            RequestData::UpdateLobbySettings(req) => req.encode_to_vec(),
+            RequestData::SendQuickChat(req) => req.encode_to_vec(),
+            RequestData::TimeSync(req) => req.encode_to_vec(),
+            RequestData::SetPreferences(req) => req.encode_to_vec(),
+            RequestData::ResyncFrom(req) => req.encode_to_vec(),
+            RequestData::ExchangeCards(req) => req.encode_to_vec(),
+            RequestData::StartVoteKick(req) => req.encode_to_vec(),
+            RequestData::AnswerVoteKick(req) => req.encode_to_vec(),
+            RequestData::SubscribeGame(req) => req.encode_to_vec(),
+            RequestData::SendChat(req) => req.encode_to_vec(),
+            RequestData::ReportPlayer(req) => req.encode_to_vec(),
+            RequestData::MutePlayer(req) => req.encode_to_vec(),
+            RequestData::UploadDictionary(req) => req.encode_to_vec(),
+            RequestData::AnswerDraw(req) => req.encode_to_vec(),
+            RequestData::JoinByCode(req) => req.encode_to_vec(),
+            RequestData::UpdateProfile(req) => req.encode_to_vec(),
+            RequestData::BlockPlayer(req) => req.encode_to_vec(),
+            RequestData::KickFromLobby(req) => req.encode_to_vec(),
+            RequestData::AdminListPlayers(req) => req.encode_to_vec(),
+            RequestData::AdminKick(req) => req.encode_to_vec(),
+            RequestData::AdminBan(req) => req.encode_to_vec(),
+            RequestData::AdminAnnounce(req) => req.encode_to_vec(),
+            RequestData::AdminMaintenance(req) => req.encode_to_vec(),
+            RequestData::AdminInspect(req) => req.encode_to_vec(),
+            RequestData::AdminCloseGame(req) => req.encode_to_vec(),
+            RequestData::AdminReload(req) => req.encode_to_vec(),
+            RequestData::AdminIssueToken(req) => req.encode_to_vec(),
+            RequestData::AdminRevokeToken(req) => req.encode_to_vec(),
+            RequestData::AdminHandoff(req) => req.encode_to_vec(),
+            RequestData::Disconnect
+            | RequestData::QuitLobby
+            | RequestData::ListLobby
+            | RequestData::Ready
+            | RequestData::StartGame
+            | RequestData::FinishTurn
+            | RequestData::GetNewCard
+            | RequestData::Exit
+            | RequestData::PauseGame
+            | RequestData::ResumeGame
+            | RequestData::StartDailyChallenge
+            | RequestData::StartSolo
+            | RequestData::ListSupportedOpcodes
+            | RequestData::Resign
+            | RequestData::OfferDraw
+            | RequestData::QuickJoin
+            | RequestData::ServerInfo
+            | RequestData::BoardSnapshot
+            | RequestData::GetBagInfo => Vec::new(),
+        }
+    }
+}
+
+impl ResponseData {
+    pub fn encode_payload(&self) -> Vec<u8> {
+        match self {
+            ResponseData::Error(res) => res.encode_to_vec(),
+            ResponseData::Connect(res) => res.encode_to_vec(),
+            ResponseData::Disconnect(res) => res.encode_to_vec(),
+            ResponseData::Cancel(res) => res.encode_to_vec(),
+            ResponseData::Heartbeat(res) => res.encode_to_vec(),
+            ResponseData::CreateLobby(res) => res.encode_to_vec(),
+            ResponseData::JoinLobby(res) => res.encode_to_vec(),
+            ResponseData::QuitLobby(res) => res.encode_to_vec(),
+            ResponseData::ListLobby(res) => res.encode_to_vec(),
+            ResponseData::Ready(res) => res.encode_to_vec(),
+            ResponseData::StartGame(res) => res.encode_to_vec(),
+            ResponseData::LobbyBroadcast(res) => res.encode_to_vec(),
+            ResponseData::SetTile(res) => res.encode_to_vec(),
+            ResponseData::FinishTurn(res) => res.encode_to_vec(),
+            ResponseData::GetNewCard(res) => res.encode_to_vec(),
+            ResponseData::GameBroadcast(res) => res.encode_to_vec(),
+            ResponseData::Exit(res) => res.encode_to_vec(),
+            ResponseData::PauseGame(res) => res.encode_to_vec(),
+            ResponseData::ResumeGame(res) => res.encode_to_vec(),
+            ResponseData::GetHint(res) => res.encode_to_vec(),
+            ResponseData::GetPlayerStats(res) => res.encode_to_vec(),
+            ResponseData::StartDailyChallenge(res) => res.encode_to_vec(),
+            ResponseData::GetDailyLeaderboard(res) => res.encode_to_vec(),
+            ResponseData::SendChat(res) => res.encode_to_vec(),
+            ResponseData::ReportPlayer(res) => res.encode_to_vec(),
+            ResponseData::MutePlayer(res) => res.encode_to_vec(),
+            ResponseData::ListSupportedOpcodes(res) => res.encode_to_vec(),
+            ResponseData::UploadDictionary(res) => res.encode_to_vec(),
+            ResponseData::Resign(res) => res.encode_to_vec(),
+            ResponseData::OfferDraw(res) => res.encode_to_vec(),
+            ResponseData::AnswerDraw(res) => res.encode_to_vec(),
+            ResponseData::JoinByCode(res) => res.encode_to_vec(),
+            ResponseData::QuickJoin(res) => res.encode_to_vec(),
+            ResponseData::UpdateProfile(res) => res.encode_to_vec(),
+            ResponseData::BlockPlayer(res) => res.encode_to_vec(),
+            ResponseData::KickFromLobby(res) => res.encode_to_vec(),
+            ResponseData::ServerInfo(res) => res.encode_to_vec(),
+            ResponseData::GetSeasonInfo(res) => res.encode_to_vec(),
+            ResponseData::Spectate(res) => res.encode_to_vec(),
+            ResponseData::ExportGame(res) => res.encode_to_vec(),
+            ResponseData::AnalyzeGame(res) => res.encode_to_vec(),
+            ResponseData::UpdateLobbySettings(res) => res.encode_to_vec(),
+            ResponseData::StartSolo(res) => res.encode_to_vec(),
+            ResponseData::SendQuickChat(res) => res.encode_to_vec(),
+            ResponseData::TimeSync(res) => res.encode_to_vec(),
+            ResponseData::SetPreferences(res) => res.encode_to_vec(),
+            ResponseData::BoardSnapshot(res) => res.encode_to_vec(),
+            ResponseData::ResyncFrom(res) => res.encode_to_vec(),
+            ResponseData::GetBagInfo(res) => res.encode_to_vec(),
+            ResponseData::ExchangeCards(res) => res.encode_to_vec(),
+            ResponseData::StartVoteKick(res) => res.encode_to_vec(),
+            ResponseData::AnswerVoteKick(res) => res.encode_to_vec(),
+            ResponseData::SubscribeGame(res) => res.encode_to_vec(),
+            ResponseData::AdminListPlayers(res) => res.encode_to_vec(),
+            ResponseData::AdminKick(res) => res.encode_to_vec(),
+            ResponseData::AdminBan(res) => res.encode_to_vec(),
+            ResponseData::AdminAnnounce(res) => res.encode_to_vec(),
+            ResponseData::AdminMaintenance(res) => res.encode_to_vec(),
+            ResponseData::AdminInspect(res) => res.encode_to_vec(),
+            ResponseData::AdminCloseGame(res) => res.encode_to_vec(),
+            ResponseData::AdminReload(res) => res.encode_to_vec(),
+            ResponseData::AdminIssueToken(res) => res.encode_to_vec(),
+            ResponseData::AdminRevokeToken(res) => res.encode_to_vec(),
+            ResponseData::AdminHandoff(res) => res.encode_to_vec(),
+            ResponseData::Notice(res) => res.encode_to_vec(),
+            ResponseData::ChatBroadcast(res) => res.encode_to_vec(),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum Error {
     Incomplete,
     ProtobufDecodeFailed(prost::DecodeError),
-    Other(Box<dyn std::error::Error + Send + Sync>),
+    /// The opcode doesn't name an operation.
+    UnknownOperation(u32),
+    /// The length prefix, or the size of a compressed body once decompressed,
+    /// is over `MAX_FRAME_LEN`.
+    TooLarge(usize),
+    /// The payload is longer than any valid request of its operation.
+    PayloadTooLarge(Operation, usize),
+    DecompressFailed(lz4_flex::block::DecompressError),
+    /// The client connected with a protocol version this build can't speak.
+    UnsupportedProtocol(u32),
 }
 
-impl Frame {
-    pub fn check(src: &mut Cursor<&[u8]>) -> Result<(), Error> {
-        let op = match Operation::try_from(get_u8(src)?) {
-            Ok(op) => op,
-            Err(e) => {
-                return Err(Error::Other(e));
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Incomplete => write!(f, "incomplete frame"),
+            Error::ProtobufDecodeFailed(e) => write!(f, "invalid payload: {}", e),
+            Error::UnknownOperation(op) => write!(f, "unknown opcode {}", op),
+            Error::TooLarge(len) => write!(
+                f,
+                "frame of {} bytes is over the {} byte limit",
+                len, MAX_FRAME_LEN
+            ),
+            Error::PayloadTooLarge(op, len) => write!(
+                f,
+                "{:?} payload of {} bytes is over the {} byte limit",
+                op,
+                len,
+                op.max_payload_len()
+            ),
+            Error::DecompressFailed(e) => write!(f, "invalid compressed frame: {}", e),
+            Error::UnsupportedProtocol(version) => {
+                write!(f, "protocol version {} not supported", version)
             }
-        };
-        src.set_position(8);
-        let payload_len = get_u32(src)?;
-        let payload = src.take(payload_len as usize);
-        let e = match op {
-            Operation::Connect => ConnectRequest::decode(payload).err(),
-            Operation::Disconnect => return Ok(()),
-            Operation::Heartbeat => return Ok(()),
-            Operation::CreateLobby => CreateRequest::decode(payload).err(),
-            Operation::JoinLobby => JoinRequest::decode(payload).err(),
-            Operation::QuitLobby => return Ok(()),
-            Operation::ListLobby => return Ok(()),
-            Operation::Ready => return Ok(()),
-            Operation::StartGame => return Ok(()),
-            Operation::SetTile => SetTileRequest::decode(payload).err(),
-            Operation::FinishTurn => return Ok(()),
-            Operation::Exit => return Ok(()),
-            Operation::GetNewCard => return Ok(()),
-            Operation::Cancel => CancelRequest::decode(payload).err(),
-        };
-        if e.is_some() {
-            return Err(Error::ProtobufDecodeFailed(e.unwrap()));
         }
-        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Frame {
+    /// Decode the request at the start of `src` in the given protocol
+    /// version, returning it with the number of bytes it took up.
+    /// `Incomplete` means more bytes are needed, any other error means the
+    /// stream can't be read past this point.
+    pub fn decode(src: &[u8], protocol_version: u32) -> Result<(Frame, usize), Error> {
+        let mut buf = Cursor::new(src);
+        let frame = match protocol_version {
+            PROTOCOL_VERSION => Self::parse_envelope(&mut buf)?,
+            _ => Self::parse(&mut buf)?,
+        };
+        Ok((frame, buf.position() as usize))
+    }
+
+    /// Check for a complete legacy frame: `[op, 0, 0, 0]`, the request id and
+    /// the payload length as big endian `u32`, then the payload.
+    pub fn check(src: &mut Cursor<&[u8]>) -> Result<(), Error> {
+        Self::parse(src).map(|_| ())
     }
 
     pub fn parse(src: &mut Cursor<&[u8]>) -> Result<Frame, Error> {
-        let op = match Operation::try_from(get_u8(src)?) {
-            Ok(op) => op,
-            Err(e) => {
-                return Err(Error::Other(e));
+        let op = get_u8(src)?;
+        let op = Operation::try_from(op).map_err(|_| Error::UnknownOperation(op.into()))?;
+        if src.remaining() < 3 {
+            return Err(Error::Incomplete);
+        }
+        src.advance(3);
+        let request_id = get_u32(src)?;
+        let payload_len = get_u32(src)? as usize;
+        if payload_len > MAX_FRAME_LEN {
+            return Err(Error::TooLarge(payload_len));
+        }
+        if payload_len > op.max_payload_len() {
+            return Err(Error::PayloadTooLarge(op, payload_len));
+        }
+        if src.remaining() < payload_len {
+            return Err(Error::Incomplete);
+        }
+        let decoded = RequestData::decode(&op, &src.chunk()[..payload_len]);
+        src.advance(payload_len);
+        match decoded {
+            Ok(data) => Ok(Frame::Request(Request {
+                request_id,
+                data: Arc::new(data),
+            })),
+            Err(e) => Err(Error::ProtobufDecodeFailed(e)),
+        }
+    }
+
+    /// Check for a complete envelope frame: the envelope length as big endian
+    /// `u32`, then a `RequestEnvelope`. The top bit of the length is set when
+    /// the envelope is LZ4 compressed.
+    pub fn check_envelope(src: &mut Cursor<&[u8]>) -> Result<(), Error> {
+        Self::parse_envelope(src).map(|_| ())
+    }
+
+    pub fn parse_envelope(src: &mut Cursor<&[u8]>) -> Result<Frame, Error> {
+        let header = get_u32(src)?;
+        let len = (header & !COMPRESSED_FLAG) as usize;
+        if len > MAX_FRAME_LEN {
+            return Err(Error::TooLarge(len));
+        }
+        if src.remaining() < len {
+            return Err(Error::Incomplete);
+        }
+        let body = &src.chunk()[..len];
+        let decoded = match header & COMPRESSED_FLAG {
+            0 => RequestEnvelope::decode(body),
+            _ => {
+                // The prepended size is trusted by the decompressor, which
+                // allocates it up front
+                let (size, _) =
+                    lz4_flex::block::uncompressed_size(body).map_err(Error::DecompressFailed)?;
+                if size > MAX_FRAME_LEN {
+                    return Err(Error::TooLarge(size));
+                }
+                match lz4_flex::decompress_size_prepended(body) {
+                    Ok(body) => RequestEnvelope::decode(&body[..]),
+                    Err(e) => return Err(Error::DecompressFailed(e)),
+                }
             }
         };
-        src.set_position(4);
-        let state = get_u32(src)?;
-        let payload_len = get_u32(src)?;
-        let payload = src.take(payload_len as usize);
-        match op {
-            Operation::Connect => match ConnectRequest::decode(payload) {
-                Ok(req) => Ok(Frame::Request(Request {
-                    state,
-                    data: Arc::new(RequestData::Connect(req)),
-                })),
-                Err(e) => Err(Error::ProtobufDecodeFailed(e)),
-            },
-            Operation::Cancel => match CancelRequest::decode(payload) {
-                Ok(req) => Ok(Frame::Request(Request {
-                    state,
-                    data: Arc::new(RequestData::Cancel(req)),
-                })),
-                Err(e) => Err(Error::ProtobufDecodeFailed(e)),
-            },
-            Operation::Disconnect => Ok(Frame::Request(Request {
-                state,
-                data: Arc::new(RequestData::Disconnect),
-            })),
-            Operation::Heartbeat => Ok(Frame::Request(Request {
-                state,
-                data: Arc::new(RequestData::Heartbeat),
-            })),
-            Operation::CreateLobby => match CreateRequest::decode(payload) {
-                Ok(req) => Ok(Frame::Request(Request {
-                    state,
-                    data: Arc::new(RequestData::CreateLobby(req)),
-                })),
-                Err(e) => Err(Error::ProtobufDecodeFailed(e)),
-            },
-            Operation::JoinLobby => match JoinRequest::decode(payload) {
-                Ok(req) => Ok(Frame::Request(Request {
-                    state,
-                    data: Arc::new(RequestData::JoinLobby(req)),
-                })),
-                Err(e) => Err(Error::ProtobufDecodeFailed(e)),
-            },
-            Operation::QuitLobby => Ok(Frame::Request(Request {
-                state,
-                data: Arc::new(RequestData::QuitLobby),
-            })),
-            Operation::ListLobby => Ok(Frame::Request(Request {
-                state,
-                data: Arc::new(RequestData::ListLobby),
-            })),
-            Operation::Ready => Ok(Frame::Request(Request {
-                state,
-                data: Arc::new(RequestData::Ready),
-            })),
-            Operation::StartGame => Ok(Frame::Request(Request {
-                state,
-                data: Arc::new(RequestData::StartGame),
-            })),
-            Operation::SetTile => match SetTileRequest::decode(payload) {
-                Ok(req) => Ok(Frame::Request(Request {
-                    state,
-                    data: Arc::new(RequestData::SetTile(req)),
-                })),
-                Err(e) => Err(Error::ProtobufDecodeFailed(e)),
-            },
-            Operation::FinishTurn => Ok(Frame::Request(Request {
-                state,
-                data: Arc::new(RequestData::FinishTurn),
-            })),
-            Operation::GetNewCard => Ok(Frame::Request(Request {
-                state,
-                data: Arc::new(RequestData::GetNewCard),
-            })),
-            Operation::Exit => Ok(Frame::Request(Request {
-                state,
-                data: Arc::new(RequestData::Exit),
+        src.advance(len);
+        let envelope = match decoded {
+            Ok(envelope) => envelope,
+            Err(e) => return Err(Error::ProtobufDecodeFailed(e)),
+        };
+        let op = u8::try_from(envelope.operation)
+            .ok()
+            .and_then(|op| Operation::try_from(op).ok())
+            .ok_or(Error::UnknownOperation(envelope.operation))?;
+        if envelope.payload.len() > op.max_payload_len() {
+            return Err(Error::PayloadTooLarge(op, envelope.payload.len()));
+        }
+        match RequestData::decode(&op, &envelope.payload[..]) {
+            Ok(data) => Ok(Frame::Request(Request {
+                request_id: envelope.request_id,
+                data: Arc::new(data),
             })),
+            Err(e) => Err(Error::ProtobufDecodeFailed(e)),
+        }
+    }
+
+    /// Compress the body of an encoded envelope frame and flag it in the
+    /// length prefix.
+    pub fn compress_envelope(
+        bytes: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        let body = lz4_flex::compress_prepend_size(&bytes[4..]);
+        let len = u32::try_from(body.len())?;
+        if len & COMPRESSED_FLAG != 0 {
+            return Err("frame too large".into());
         }
+        let mut compressed = Vec::with_capacity(4 + body.len());
+        compressed.extend_from_slice(&(len | COMPRESSED_FLAG).to_be_bytes());
+        compressed.extend_from_slice(&body);
+        Ok(compressed)
+    }
+}
+
+impl Response {
+    /// Encode as a legacy frame: the state and the payload length as little
+    /// endian `u32`, then the payload.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        let payload = self.data.encode_payload();
+        let mut bytes = Vec::with_capacity(8 + payload.len());
+        bytes.extend_from_slice(&self.state.to_le_bytes());
+        bytes.extend_from_slice(&u32::try_from(payload.len())?.to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    /// Encode as an envelope frame: the envelope length as big endian `u32`,
+    /// then a `ResponseEnvelope`.
+    pub fn to_envelope_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        let envelope = ResponseEnvelope::from(self);
+        let mut bytes = Vec::with_capacity(4 + envelope.encoded_len());
+        bytes.extend_from_slice(&u32::try_from(envelope.encoded_len())?.to_be_bytes());
+        envelope.encode(&mut bytes)?;
+        Ok(bytes)
     }
 }
 
@@ -278,3 +918,319 @@ fn get_u32(src: &mut Cursor<&[u8]>) -> Result<u32, Error> {
 
     Ok(src.get_u32())
 }
+
+/// The message a reply envelope carries, or the error the server answered
+/// the request with instead, told apart by the envelope's kind.
+pub fn decode_reply<T: Message + Default>(
+    envelope: &ResponseEnvelope,
+) -> Result<Result<T, crate::model::error::error::Error>, prost::DecodeError> {
+    match envelope.kind() {
+        ResponseKind::Error => Ok(Err(crate::model::error::error::Error::decode(
+            &envelope.payload[..],
+        )?)),
+        _ => Ok(Ok(T::decode(&envelope.payload[..])?)),
+    }
+}
+
+/// Like `decode_reply`, for a legacy reply, which doesn't say whether it is
+/// an error. A payload that doesn't decode as `T` is taken for an `Error`.
+/// That only tells the two apart when `T` can't hold an `Error`'s string in
+/// field 1, as with every reply that starts with `bool success = 1`, and is
+/// only for clients that can't negotiate envelopes, such as admin tools that
+/// never connect.
+pub fn decode_legacy_reply<T: Message + Default>(
+    payload: &[u8],
+) -> Result<Result<T, crate::model::error::error::Error>, prost::DecodeError> {
+    match T::decode(payload) {
+        Ok(res) => Ok(Ok(res)),
+        Err(e) => crate::model::error::error::Error::decode(payload)
+            .map(Err)
+            .map_err(|_| e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_with_heartbeat_frame_should_keep_request_id() -> Result<(), Error> {
+        let mut bytes = vec![u8::from(&Operation::Heartbeat), 0, 0, 0];
+        bytes.extend_from_slice(&42u32.to_be_bytes());
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        let frame = Frame::parse(&mut Cursor::new(&bytes[..]))?;
+        match frame {
+            Frame::Request(req) => assert_eq!(req.get_request_id(), 42),
+            _ => panic!("invalid frame"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_with_encoded_admin_request_should_return_same_request(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let req = Request::new(
+            7,
+            Arc::new(RequestData::AdminKick(KickRequest {
+                token: String::from("secret"),
+                player_id: 3,
+            })),
+        );
+        let bytes = req.to_bytes()?;
+        let mut buf = Cursor::new(&bytes[..]);
+        assert!(Frame::check(&mut buf).is_ok());
+        buf.set_position(0);
+        match Frame::parse(&mut buf) {
+            Ok(Frame::Request(parsed)) => assert_eq!(parsed, req),
+            _ => panic!("invalid frame"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_envelope_with_encoded_admin_request_should_return_same_request(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let req = Request::new(
+            7,
+            Arc::new(RequestData::AdminKick(KickRequest {
+                token: String::from("secret"),
+                player_id: 3,
+            })),
+        );
+        let bytes = req.to_envelope_bytes()?;
+        let mut buf = Cursor::new(&bytes[..]);
+        assert!(Frame::check_envelope(&mut buf).is_ok());
+        buf.set_position(0);
+        match Frame::parse_envelope(&mut buf) {
+            Ok(Frame::Request(parsed)) => assert_eq!(parsed, req),
+            _ => panic!("invalid frame"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn check_envelope_with_partial_frame_should_be_incomplete(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let bytes = Request::new(
+            0,
+            Arc::new(RequestData::Heartbeat(HeartbeatRequest::default())),
+        )
+        .to_envelope_bytes()?;
+        let mut buf = Cursor::new(&bytes[..bytes.len() - 1]);
+        assert!(matches!(
+            Frame::check_envelope(&mut buf),
+            Err(Error::Incomplete)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn check_with_partial_payload_should_be_incomplete(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let bytes = Request::new(
+            7,
+            Arc::new(RequestData::AdminKick(KickRequest {
+                token: String::from("secret"),
+                player_id: 3,
+            })),
+        )
+        .to_bytes()?;
+        let mut buf = Cursor::new(&bytes[..bytes.len() - 1]);
+        assert!(matches!(Frame::check(&mut buf), Err(Error::Incomplete)));
+        Ok(())
+    }
+
+    #[test]
+    fn decode_with_back_to_back_frames_should_return_first_frame_length(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let first = Request::new(
+            7,
+            Arc::new(RequestData::Heartbeat(HeartbeatRequest::default())),
+        );
+        let second = Request::new(8, Arc::new(RequestData::Ready));
+        let mut bytes = first.to_bytes()?;
+        let len = bytes.len();
+        bytes.extend_from_slice(&second.to_bytes()?);
+        match Frame::decode(&bytes, LEGACY_PROTOCOL_VERSION)? {
+            (Frame::Request(parsed), parsed_len) => {
+                assert_eq!(parsed, first);
+                assert_eq!(parsed_len, len);
+            }
+            _ => panic!("invalid frame"),
+        }
+        match Frame::decode(&bytes[len..], LEGACY_PROTOCOL_VERSION)? {
+            (Frame::Request(parsed), _) => assert_eq!(parsed, second),
+            _ => panic!("invalid frame"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn decode_with_unknown_opcode_should_fail() {
+        let bytes = [0xff, 0, 0, 0];
+        assert!(matches!(
+            Frame::decode(&bytes, LEGACY_PROTOCOL_VERSION),
+            Err(Error::UnknownOperation(0xff))
+        ));
+        let envelope = RequestEnvelope {
+            request_id: 7,
+            operation: 1 << 16,
+            payload: Vec::new(),
+        };
+        let mut bytes = (envelope.encoded_len() as u32).to_be_bytes().to_vec();
+        envelope.encode(&mut bytes).unwrap();
+        assert!(matches!(
+            Frame::decode(&bytes, PROTOCOL_VERSION),
+            Err(Error::UnknownOperation(op)) if op == 1 << 16
+        ));
+    }
+
+    #[test]
+    fn decode_with_length_over_limit_should_fail_before_buffering() {
+        let mut bytes = vec![u8::from(&Operation::Heartbeat), 0, 0, 0];
+        bytes.extend_from_slice(&7u32.to_be_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            Frame::decode(&bytes, LEGACY_PROTOCOL_VERSION),
+            Err(Error::TooLarge(_))
+        ));
+        let bytes = (MAX_FRAME_LEN as u32 + 1).to_be_bytes();
+        assert!(matches!(
+            Frame::decode(&bytes, PROTOCOL_VERSION),
+            Err(Error::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn decode_with_payload_over_operation_limit_should_fail() {
+        let limit = Operation::SetTile.max_payload_len();
+        let mut bytes = vec![u8::from(&Operation::SetTile), 0, 0, 0];
+        bytes.extend_from_slice(&7u32.to_be_bytes());
+        bytes.extend_from_slice(&(limit as u32 + 1).to_be_bytes());
+        assert!(matches!(
+            Frame::decode(&bytes, LEGACY_PROTOCOL_VERSION),
+            Err(Error::PayloadTooLarge(Operation::SetTile, len)) if len == limit + 1
+        ));
+        let envelope = RequestEnvelope {
+            request_id: 7,
+            operation: u8::from(&Operation::SetTile) as u32,
+            payload: vec![0; limit + 1],
+        };
+        let mut bytes = (envelope.encoded_len() as u32).to_be_bytes().to_vec();
+        envelope.encode(&mut bytes).unwrap();
+        assert!(matches!(
+            Frame::decode(&bytes, PROTOCOL_VERSION),
+            Err(Error::PayloadTooLarge(Operation::SetTile, _))
+        ));
+    }
+
+    #[test]
+    fn decode_with_compressed_size_over_limit_should_fail() {
+        let mut body = (MAX_FRAME_LEN as u32 + 1).to_le_bytes().to_vec();
+        body.push(0);
+        let mut bytes = (body.len() as u32 | COMPRESSED_FLAG).to_be_bytes().to_vec();
+        bytes.extend_from_slice(&body);
+        assert!(matches!(
+            Frame::decode(&bytes, PROTOCOL_VERSION),
+            Err(Error::TooLarge(len)) if len == MAX_FRAME_LEN + 1
+        ));
+    }
+
+    #[test]
+    fn decode_with_invalid_compressed_body_should_fail() {
+        let body = [4, 0, 0, 0, 0xff];
+        let mut bytes = (body.len() as u32 | COMPRESSED_FLAG).to_be_bytes().to_vec();
+        bytes.extend_from_slice(&body);
+        assert!(matches!(
+            Frame::decode(&bytes, PROTOCOL_VERSION),
+            Err(Error::DecompressFailed(_))
+        ));
+    }
+
+    #[test]
+    fn decode_with_empty_compressed_body_should_fail() {
+        // Once panicked inside the LZ4 decompressor
+        let body = [0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0x30];
+        let mut bytes = (body.len() as u32 | COMPRESSED_FLAG).to_be_bytes().to_vec();
+        bytes.extend_from_slice(&body);
+        assert!(Frame::decode(&bytes, PROTOCOL_VERSION).is_err());
+    }
+
+    #[test]
+    fn parse_envelope_with_compressed_frame_should_return_same_request(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let req = Request::new(
+            7,
+            Arc::new(RequestData::AdminAnnounce(AnnounceRequest {
+                token: String::from("secret"),
+                message: "server restarting ".repeat(100),
+                set_motd: false,
+            })),
+        );
+        let bytes = Frame::compress_envelope(&req.to_envelope_bytes()?)?;
+        assert!(bytes.len() < req.to_envelope_bytes()?.len());
+        match Frame::parse_envelope(&mut Cursor::new(&bytes[..])) {
+            Ok(Frame::Request(parsed)) => assert_eq!(parsed, req),
+            _ => panic!("invalid frame"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn new_response_with_request_id_should_echo_request_id() {
+        let res = Response::new(
+            42,
+            Arc::new(ResponseData::Heartbeat(HeartbeatResponse {
+                success: true,
+                ..Default::default()
+            })),
+        );
+        assert_eq!(res.get_request_id(), Some(42));
+        assert_eq!(res.get_state(), 42);
+    }
+
+    #[test]
+    fn event_response_should_not_have_request_id() {
+        let res = Response::event(
+            State::GameBroadcast,
+            Arc::new(ResponseData::GameBroadcast(GameBroadcast::default())),
+        );
+        assert_eq!(res.get_request_id(), None);
+        assert_eq!(res.get_state(), State::GameBroadcast as u32);
+    }
+
+    #[test]
+    fn decode_reply_with_error_kind_should_return_error_even_if_payload_fits(
+    ) -> Result<(), prost::DecodeError> {
+        // A connect request's name is field 1 like an error's message, so the
+        // payload alone decodes either way
+        let envelope = ResponseEnvelope::from(&Response::new(
+            7,
+            Arc::new(ResponseData::Error(crate::model::error::error::Error {
+                message: String::from("Player not found"),
+                code: crate::model::error::error::ErrorCode::Unknown as i32,
+            })),
+        ));
+        let err = decode_reply::<ConnectRequest>(&envelope)?.unwrap_err();
+        assert_eq!(err.message, "Player not found");
+        let envelope = ResponseEnvelope::from(&Response::new(
+            7,
+            Arc::new(ResponseData::AdminKick(KickResponse { success: true })),
+        ));
+        assert!(decode_reply::<KickResponse>(&envelope)?.is_ok_and(|res| res.success));
+        Ok(())
+    }
+
+    #[test]
+    fn decode_legacy_reply_with_error_payload_should_return_error() -> Result<(), prost::DecodeError>
+    {
+        let payload = crate::model::error::error::Error {
+            message: String::from("Player not found"),
+            code: crate::model::error::error::ErrorCode::Unknown as i32,
+        }
+        .encode_to_vec();
+        let err = decode_legacy_reply::<KickResponse>(&payload)?.unwrap_err();
+        assert_eq!(err.to_string(), "Player not found");
+        Ok(())
+    }
+}
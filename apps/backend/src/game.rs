@@ -1,5 +1,14 @@
+pub mod analysis;
 pub mod board;
 pub mod card;
+pub mod event;
 pub mod game;
 pub mod game_player;
+#[cfg(debug_assertions)]
+pub mod invariant;
+pub mod notation;
+pub mod replay;
+pub mod rules;
+pub mod scoring;
+pub mod snapshot;
 pub mod tile;
@@ -0,0 +1,117 @@
+use std::{collections::HashSet, error::Error, sync::Arc};
+
+use crate::solver::{self, Lexicon, Placement};
+
+use super::{event::Event, game::Game, replay::FinishedReplay};
+
+/// How a turn measured up against the best move the hint solver finds for
+/// the hand the player ended it with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnAnalysis {
+    pub turn: u32,
+    pub player_id: u32,
+    pub points: u32,
+    /// `None` when no word could be made.
+    pub best: Option<Placement>,
+    /// What `best` would have scored, words it crosses included.
+    pub best_points: u32,
+}
+
+impl TurnAnalysis {
+    /// Points the turn left on the table.
+    pub fn points_left(&self) -> u32 {
+        self.best_points.saturating_sub(self.points)
+    }
+}
+
+/// Play `record` over `game`, which is set up from `record.replay` with no
+/// events applied yet, and work out the best move of every turn.
+pub fn analyze(
+    game: &Arc<Game>,
+    record: &FinishedReplay,
+    lexicon: &Lexicon,
+    dict: &HashSet<String>,
+) -> Result<Vec<TurnAnalysis>, Box<dyn Error + Send + Sync>> {
+    let mut analyses = Vec::new();
+    for event in &record.events {
+        let best = match event {
+            Event::EndTurn { .. } => {
+                let game_player = game.get_player_in_this_turn();
+                // Tiles placed this turn go back in the hand to be tried again
+                let mut cards = game_player.get_cards();
+                for card in cards.iter_mut() {
+                    card.used = false;
+                }
+                let board = game.get_board_backup();
+                Some(solver::find_best(
+                    game,
+                    &board,
+                    game_player.player.clone(),
+                    &cards,
+                    lexicon,
+                    dict,
+                ))
+            }
+            _ => None,
+        };
+        game.apply(event.clone())?;
+        let best = match best {
+            Some(best) => best,
+            None => continue,
+        };
+        let annotation = match game.get_annotations().pop() {
+            Some(annotation) => annotation,
+            None => return Err("Turn ended without an annotation".into()),
+        };
+        let (best, best_points) = match best {
+            Some((placement, points)) => (Some(placement), points),
+            None => (None, 0),
+        };
+        analyses.push(TurnAnalysis {
+            turn: annotation.turn,
+            player_id: annotation.player_id,
+            points: annotation.points,
+            best,
+            best_points,
+        });
+    }
+    Ok(analyses)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::player::Player;
+
+    use super::*;
+
+    #[test]
+    fn analyze_with_passed_turn_should_count_best_move_as_left(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test {}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(7, players.clone());
+        let first = game.get_player_in_this_turn();
+        // Whatever the first hand is, it spells itself letter by letter
+        let dict = first
+            .get_cards()
+            .iter()
+            .map(|card| card.char.to_string())
+            .collect::<HashSet<_>>();
+        game.apply(Event::EndTurn { keep_tiles: false })?;
+        let record = game.finished_replay();
+
+        let replayed = Arc::new(Game::replay(&record.replay, players, &[])?);
+        let analyses = analyze(&replayed, &record, &Lexicon::new(&dict), &dict)?;
+        assert_eq!(analyses.len(), 1);
+        let analysis = &analyses[0];
+        assert_eq!((analysis.turn, analysis.player_id), (1, first.player.id));
+        assert_eq!(analysis.points, 0);
+        let best = analysis.best.clone().unwrap();
+        assert_eq!(best.tiles.len(), 1);
+        assert!(analysis.best_points > 0);
+        assert_eq!(analysis.points_left(), analysis.best_points);
+        assert_eq!(replayed.get_events(), record.events);
+        Ok(())
+    }
+}
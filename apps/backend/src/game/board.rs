@@ -1,12 +1,92 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fmt,
+    sync::Arc,
+};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use super::{game::Game, tile::Tile};
 
 pub const BOARD_SIZE: usize = 26;
+pub const CLASSIC_BOARD_SIZE: usize = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Premium {
+    DoubleLetter,
+    TripleLetter,
+    DoubleWord,
+    TripleWord,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoardLayout {
+    /// No premium squares, the original LetterLegend board.
+    #[default]
+    Plain,
+    /// The 15x15 crossword board premium squares.
+    Classic,
+    /// Premium squares scattered at random, about as dense as `Classic`.
+    RandomPremium,
+}
+
+/// Why a tile can't go where a player put it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementError {
+    OutOfBoard,
+    Occupied,
+    /// Not next to any tile already on the board.
+    NotConnected,
+    /// The first tile of the game has to go on the centre square.
+    NotOnCenter,
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PlacementError::OutOfBoard => "Tile out of board",
+            PlacementError::Occupied => "Square already has a tile",
+            PlacementError::NotConnected => "Tile must connect to the tiles on the board",
+            PlacementError::NotOnCenter => "First word must cross the center square",
+        })
+    }
+}
+
+impl Error for PlacementError {}
+
+/// Top-left quadrant of the classic board, mirrored onto the other three.
+const CLASSIC_QUADRANT: [(usize, usize, Premium); 18] = [
+    (0, 0, Premium::TripleWord),
+    (7, 0, Premium::TripleWord),
+    (0, 7, Premium::TripleWord),
+    (1, 1, Premium::DoubleWord),
+    (2, 2, Premium::DoubleWord),
+    (3, 3, Premium::DoubleWord),
+    (4, 4, Premium::DoubleWord),
+    (7, 7, Premium::DoubleWord),
+    (5, 1, Premium::TripleLetter),
+    (1, 5, Premium::TripleLetter),
+    (5, 5, Premium::TripleLetter),
+    (3, 0, Premium::DoubleLetter),
+    (0, 3, Premium::DoubleLetter),
+    (6, 2, Premium::DoubleLetter),
+    (2, 6, Premium::DoubleLetter),
+    (6, 6, Premium::DoubleLetter),
+    (7, 3, Premium::DoubleLetter),
+    (3, 7, Premium::DoubleLetter),
+];
 
 #[derive(Debug, Clone)]
 pub struct Board {
     pub tiles: [[Option<Tile>; BOARD_SIZE]; BOARD_SIZE],
+    /// Tiles may only go where both coordinates are below this, the rest of
+    /// the grid stays empty.
+    size: usize,
+    /// Premium squares by `(x, y)` in the coordinates clients place tiles
+    /// with. Never changes during a game, so backups share it.
+    premiums: Arc<BTreeMap<(usize, usize), Premium>>,
 }
 
 impl Board {
@@ -16,6 +96,120 @@ impl Board {
         const ARR: [Option<Tile>; BOARD_SIZE] = [INIT; BOARD_SIZE];
         Self {
             tiles: [ARR; BOARD_SIZE],
+            size: BOARD_SIZE,
+            premiums: Arc::new(BTreeMap::new()),
+        }
+    }
+
+    /// An empty board with the premium squares of `layout` laid out over the
+    /// playable `size` x `size` area.
+    pub fn with_layout(layout: BoardLayout, size: usize, rng: &mut impl Rng) -> Self {
+        let premiums = match layout {
+            BoardLayout::Plain => BTreeMap::new(),
+            BoardLayout::Classic => Self::classic_premiums(),
+            BoardLayout::RandomPremium => Self::random_premiums(size, rng),
+        };
+        Self {
+            size,
+            premiums: Arc::new(premiums),
+            ..Self::new()
+        }
+    }
+
+    /// An empty `size` x `size` board with the given premium squares, as a
+    /// saved game had.
+    pub fn with_premiums(premiums: BTreeMap<(usize, usize), Premium>, size: usize) -> Self {
+        Self {
+            size,
+            premiums: Arc::new(premiums),
+            ..Self::new()
+        }
+    }
+
+    fn classic_premiums() -> BTreeMap<(usize, usize), Premium> {
+        let last = CLASSIC_BOARD_SIZE - 1;
+        let mut premiums = BTreeMap::new();
+        for (x, y, premium) in CLASSIC_QUADRANT {
+            for (x, y) in [(x, y), (last - x, y), (x, last - y), (last - x, last - y)] {
+                premiums.insert((x, y), premium);
+            }
+        }
+        premiums
+    }
+
+    fn random_premiums(size: usize, rng: &mut impl Rng) -> BTreeMap<(usize, usize), Premium> {
+        let mut premiums = BTreeMap::new();
+        for x in 0..size {
+            for y in 0..size {
+                // Same odds as a square on the classic board: 8 triple word,
+                // 17 double word, 12 triple letter and 24 double letter
+                // squares out of 225
+                let premium = match rng.gen_range(0..225) {
+                    0..=7 => Premium::TripleWord,
+                    8..=24 => Premium::DoubleWord,
+                    25..=36 => Premium::TripleLetter,
+                    37..=60 => Premium::DoubleLetter,
+                    _ => continue,
+                };
+                premiums.insert((x, y), premium);
+            }
+        }
+        premiums
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size
+    }
+
+    pub fn get_premium(&self, x: usize, y: usize) -> Option<Premium> {
+        self.premiums.get(&(x, y)).copied()
+    }
+
+    pub fn get_premiums(&self) -> &BTreeMap<(usize, usize), Premium> {
+        &self.premiums
+    }
+
+    fn get_tile(&self, x: usize, y: usize) -> Option<&Tile> {
+        self.tiles[BOARD_SIZE - 1 - y][x].as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.iter().flatten().all(Option::is_none)
+    }
+
+    /// Check a tile may go on `(x, y)`. Tiles go one at a time, so each has
+    /// to touch one already down, starting from the centre square; a word
+    /// crossing the centre or joining up with earlier words is then placed
+    /// from the joining square outwards.
+    pub fn check_placement(&self, x: usize, y: usize) -> Result<(), PlacementError> {
+        if !self.in_bounds(x, y) {
+            return Err(PlacementError::OutOfBoard);
+        }
+        if self.get_tile(x, y).is_some() {
+            return Err(PlacementError::Occupied);
+        }
+        if self.is_empty() {
+            return match (x, y) == (self.size / 2, self.size / 2) {
+                true => Ok(()),
+                false => Err(PlacementError::NotOnCenter),
+            };
+        }
+        let neighbours = [
+            (x + 1, y),
+            (x, y + 1),
+            (x.wrapping_sub(1), y),
+            (x, y.wrapping_sub(1)),
+        ];
+        match neighbours
+            .into_iter()
+            .any(|(x, y)| self.in_bounds(x, y) && self.get_tile(x, y).is_some())
+        {
+            true => Ok(()),
+            false => Err(PlacementError::NotConnected),
         }
     }
 
@@ -326,4 +520,63 @@ mod tests {
         assert!(list.unwrap().len() == 0);
         Ok(())
     }
+
+    #[test]
+    fn with_layout_classic_should_place_classic_premium_squares() {
+        let board = Board::with_layout(
+            BoardLayout::Classic,
+            CLASSIC_BOARD_SIZE,
+            &mut rand::thread_rng(),
+        );
+        let count = |premium| {
+            board
+                .get_premiums()
+                .values()
+                .filter(|&&x| x == premium)
+                .count()
+        };
+        assert_eq!(count(Premium::TripleWord), 8);
+        assert_eq!(count(Premium::DoubleWord), 17);
+        assert_eq!(count(Premium::TripleLetter), 12);
+        assert_eq!(count(Premium::DoubleLetter), 24);
+        assert_eq!(board.get_premium(7, 7), Some(Premium::DoubleWord));
+        assert_eq!(board.get_premium(14, 7), Some(Premium::TripleWord));
+        assert_eq!(board.get_premium(1, 0), None);
+    }
+
+    #[test]
+    fn with_layout_random_premium_should_stay_inside_board_size() {
+        let board = Board::with_layout(BoardLayout::RandomPremium, 10, &mut rand::thread_rng());
+        assert!(board.get_premiums().keys().all(|&(x, y)| x < 10 && y < 10));
+    }
+
+    #[test]
+    fn check_placement_on_empty_smaller_board_should_only_allow_its_centre() {
+        let board = Board::with_layout(
+            BoardLayout::Plain,
+            CLASSIC_BOARD_SIZE,
+            &mut rand::thread_rng(),
+        );
+        assert_eq!(
+            board.check_placement(13, 13),
+            Err(PlacementError::NotOnCenter)
+        );
+        assert_eq!(
+            board.check_placement(15, 7),
+            Err(PlacementError::OutOfBoard)
+        );
+        assert!(board.check_placement(7, 7).is_ok());
+    }
+
+    #[test]
+    fn in_bounds_with_coordinate_on_smaller_board_edge_should_be_false() {
+        let board = Board::with_layout(
+            BoardLayout::Plain,
+            CLASSIC_BOARD_SIZE,
+            &mut rand::thread_rng(),
+        );
+        assert!(board.in_bounds(14, 14));
+        assert!(!board.in_bounds(15, 0));
+        assert!(!board.in_bounds(0, 15));
+    }
 }
@@ -1,12 +1,40 @@
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Card {
     pub char: char,
     pub used: bool,
+    /// Only dealt in games with `GameRules::power_ups` on.
+    pub power_up: Option<PowerUp>,
 }
 
 impl Card {
     pub fn new(char: char) -> Self {
-        Self { char, used: false }
+        Self {
+            char,
+            used: false,
+            power_up: None,
+        }
     }
 }
+
+/// Effect an arcade card has on top of its letter, once its tile stays on
+/// the board at the end of the turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerUp {
+    /// The turn's points are doubled.
+    DoubleWord,
+    /// A random card moves from the next player's hand to the placer's, the
+    /// next player is dealt another in its place.
+    StealLetter,
+    /// The next player's turn only runs half as long.
+    FreezeTimer,
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 3] = [
+        PowerUp::DoubleWord,
+        PowerUp::StealLetter,
+        PowerUp::FreezeTimer,
+    ];
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// A change to a game. Every change goes through `Game::apply`, which keeps
+/// them in order, so applying the log to the game the same seed sets up
+/// gets back to the same state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event {
+    /// A card from the player's hand put on `(x, y)`.
+    PlaceTile {
+        player_id: u32,
+        card_index: usize,
+        x: usize,
+        y: usize,
+    },
+    /// A tile placed this turn taken back into the hand.
+    RemoveTile {
+        player_id: u32,
+        x: usize,
+        y: usize,
+    },
+    /// The player's hand swapped for a fresh one.
+    Shuffle {
+        player_id: u32,
+    },
+    /// The cards at `card_indices` in the player's hand swapped for new
+    /// ones, in place of a whole shuffle.
+    Exchange {
+        player_id: u32,
+        card_indices: Vec<usize>,
+    },
+    UseHint {
+        player_id: u32,
+    },
+    /// The turn ended and the next player's began. `keep_tiles` is false
+    /// when the board didn't validate, so the turn's tiles are taken back.
+    EndTurn {
+        keep_tiles: bool,
+    },
+    Leave {
+        player_id: u32,
+    },
+    /// The player is out for good, by the forfeit policy. Their turn, if it
+    /// is theirs, ends with its tiles taken back.
+    Forfeit {
+        player_id: u32,
+    },
+    /// The player gave up, otherwise the same as `Forfeit`.
+    Resign {
+        player_id: u32,
+    },
+}
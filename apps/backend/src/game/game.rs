@@ -1,21 +1,257 @@
 use std::{
-    collections::{linked_list::LinkedList, HashMap},
-    sync::{Arc, Mutex},
+    collections::{linked_list::LinkedList, HashMap, HashSet},
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, OnceLock, PoisonError,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use super::{board::Board, game_player::GamePlayer};
-use crate::player::Player;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    board::{Board, BOARD_SIZE},
+    card::PowerUp,
+    event::Event,
+    game_player::GamePlayer,
+    replay::{FinishedReplay, MoveAnnotation, Placement, Replay},
+    rules::{GameRules, TurnOrder},
+    scoring,
+    snapshot::{GameSnapshot, TileSnapshot},
+    tile::Tile,
+};
+use crate::{latency::TimedMutex, player::Player, solver::Lexicon};
 pub const END_GAME_TURN: u32 = 16;
-use tokio::task::JoinHandle;
+use tokio::{task::JoinHandle, time::Instant};
+
+/// Where a pause or resume vote stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PauseStatus {
+    pub paused: bool,
+    pub votes: u32,
+    pub votes_needed: u32,
+}
+
+/// Where a draw offer stands. Nothing is on offer while nobody has agreed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawStatus {
+    pub votes: u32,
+    pub votes_needed: u32,
+}
+
+impl DrawStatus {
+    pub fn is_agreed(&self) -> bool {
+        self.votes > 0 && self.votes >= self.votes_needed
+    }
+}
+
+/// Fewer and one of two players could vote the other out on their own.
+pub const MIN_VOTE_KICK_PLAYERS: usize = 3;
+
+/// Where a vote to kick `target` out of the game stands. Everyone else still
+/// in the game gets a vote, and a majority of them is needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KickStatus {
+    pub target: u32,
+    pub votes: u32,
+    pub votes_against: u32,
+    pub votes_needed: u32,
+    /// Players who may vote, all but the target.
+    pub voters: u32,
+    /// When the vote runs out, leaving the target in the game.
+    pub deadline: Instant,
+}
+
+impl KickStatus {
+    pub fn is_passed(&self) -> bool {
+        self.votes >= self.votes_needed
+    }
+
+    /// Whether there aren't enough votes left for it to pass.
+    pub fn is_failed(&self) -> bool {
+        !self.is_passed() && self.voters - self.votes_against < self.votes_needed
+    }
+}
+
+#[derive(Debug)]
+struct KickVote {
+    target: u32,
+    /// Whether each voter wants the target out.
+    votes: HashMap<u32, bool>,
+    deadline: Instant,
+}
+
+/// How a player went out of a game before it ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Withdrawal {
+    /// By the forfeit policy, for going idle or staying away.
+    Forfeited,
+    /// By their own choice.
+    Resigned,
+}
+
+/// A move that breaks the turn rules, as opposed to a tile that just doesn't
+/// fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveViolation {
+    NotYourTurn,
+    /// The turn timer ran out before the move arrived.
+    TurnOver,
+    /// The card index is past the end of the hand.
+    CardNotInHand,
+    /// The card was already put on the board this turn.
+    CardReused,
+}
+
+impl MoveViolation {
+    /// Whether no honest client could have sent the move. An honest client
+    /// can still move out of turn or too late, having raced the turn timer
+    /// over a slow connection.
+    pub fn is_tampered(&self) -> bool {
+        matches!(
+            self,
+            MoveViolation::CardNotInHand | MoveViolation::CardReused
+        )
+    }
+}
+
+impl fmt::Display for MoveViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MoveViolation::NotYourTurn => "Player can't move when not his turn",
+            MoveViolation::TurnOver => "Turn is over",
+            MoveViolation::CardNotInHand => "Card index out of hand",
+            MoveViolation::CardReused => "Card has used",
+        })
+    }
+}
+
+impl Error for MoveViolation {}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Standard,
+    /// Solo game everyone playing on the same day gets the same board and
+    /// tiles for, by days since 1970-01-01 UTC.
+    Daily(u32),
+    /// Solo game against the clock that counts towards no stats or
+    /// leaderboards.
+    Practice,
+}
+
+/// Days since 1970-01-01 UTC, which daily challenges are numbered by.
+pub fn current_day() -> u32 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    (now.as_secs() / (24 * 60 * 60)) as u32
+}
+
+/// `deadline` in unix milliseconds on the server clock, the way clients are
+/// told about it.
+pub fn deadline_millis(deadline: Instant) -> u64 {
+    (SystemTime::now() + deadline.saturating_duration_since(Instant::now()))
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Where a co-op game stands against its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoopStatus {
+    pub score: u32,
+    pub target: u32,
+    pub turns_left: u32,
+}
+
+impl CoopStatus {
+    pub fn is_won(&self) -> bool {
+        self.score >= self.target
+    }
+}
+
+/// What a restored game already had before its log of turns starts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baseline {
+    pub tiles: usize,
+    /// Each seated player's score, by name.
+    pub scores: HashMap<String, u32>,
+}
+
+/// Seed shared by every daily challenge of `day`.
+pub fn daily_seed(day: u32) -> u64 {
+    // Spread consecutive days over the whole seed space
+    (day as u64 ^ 0x4c45_5454_4552_4c47).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
 #[derive(Debug)]
 pub struct Game {
     pub id: u32,
     turn: Mutex<u32>,
-    players: Mutex<HashMap<u32, Arc<GamePlayer>>>,
-    turn_queue: Mutex<LinkedList<Arc<GamePlayer>>>,
-    board: Arc<Mutex<Board>>,
+    /// The turn the game ends after, fixed when it is set up.
+    last_turn: u32,
+    /// Points everyone scored together, which co-op games are played for.
+    shared_score: Mutex<u32>,
+    players: TimedMutex<HashMap<u32, Arc<GamePlayer>>>,
+    /// Player ids in seat order. Starts as `replay.seats`, but a seat
+    /// changes hands when its player comes back to a restored game.
+    seats: Mutex<Vec<u32>>,
+    /// Seats waiting for their players to reconnect, by the id of whoever
+    /// holds them, with when they started waiting. Stand-ins hold the seats
+    /// of a restored game, a player who dropped mid-game holds their own.
+    stand_ins: Mutex<HashMap<u32, Instant>>,
+    /// Players out of the game for good, in the order they went. They keep
+    /// their tally but no longer take turns.
+    withdrawn: Mutex<Vec<(Arc<GamePlayer>, Withdrawal)>>,
+    turn_queue: TimedMutex<LinkedList<Arc<GamePlayer>>>,
+    board: Arc<TimedMutex<Board>>,
     board_backup: Mutex<Board>,
+    /// The board as clients were last told about it, and its version, which
+    /// goes up each time a change is sent out.
+    sent_board: Mutex<(u64, Board)>,
     timeout: Mutex<Option<Arc<JoinHandle<()>>>>,
+    /// When the running turn timer fires.
+    turn_deadline: Mutex<Option<Instant>>,
+    /// Turn time left when the game was paused, `None` while running.
+    paused: Mutex<Option<Duration>>,
+    /// Players who want the game paused, or resumed while it is paused.
+    pause_votes: Mutex<HashSet<u32>>,
+    /// Players who agreed to end the game as a draw.
+    draw_votes: Mutex<HashSet<u32>>,
+    /// The open vote to kick a player out, if any.
+    kick_vote: Mutex<Option<KickVote>>,
+    /// Ids of players watching the game without taking part.
+    spectators: Mutex<HashSet<u32>>,
+    /// Ids of sessions following every event of the game live, such as
+    /// casting overlays.
+    subscribers: Mutex<HashSet<u32>>,
+    rules: GameRules,
+    /// Seeded from `replay.seed`. ChaCha's output is fixed across `rand`
+    /// releases, unlike `StdRng`'s, so old seeds keep replaying the same.
+    rng: Mutex<ChaCha8Rng>,
+    replay: Replay,
+    mode: GameMode,
+    /// Words the lobby uploaded, checked against instead of the language's
+    /// dictionary.
+    custom_wordlist: Option<Arc<HashSet<String>>>,
+    /// `custom_wordlist` for the solver, built the first time it's needed.
+    custom_lexicon: OnceLock<Arc<Lexicon>>,
+    /// Every event applied since the game was set up, or restored from a
+    /// snapshot.
+    log: Mutex<Vec<Event>>,
+    /// One for each `Event::EndTurn` in `log`.
+    annotations: Mutex<Vec<MoveAnnotation>>,
+    /// Tiles and scores `annotations` don't account for, empty unless the
+    /// game was restored.
+    baseline: Baseline,
+    /// Set once the game's logic panicked. Not behind a lock, which the
+    /// panic may have poisoned.
+    errored: AtomicBool,
 }
 
 impl PartialEq for Game {
@@ -26,28 +262,758 @@ impl PartialEq for Game {
 
 impl Game {
     pub fn new(id: u32, players: Vec<Arc<Player>>) -> Self {
+        Self::with_rules(id, players, GameRules::default())
+    }
+
+    pub fn with_rules(id: u32, players: Vec<Arc<Player>>, rules: GameRules) -> Self {
+        Self::with_seed(id, players, rules, rand::random())
+    }
+
+    /// Set up a game whose seating, board and hands all follow from `seed`.
+    /// `players` come in the order they joined, which the seating may follow
+    /// depending on `rules.turn_order`.
+    pub fn with_seed(id: u32, players: Vec<Arc<Player>>, rules: GameRules, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let players = Game::seat(players, rules.turn_order, &mut rng);
+        let board = Board::with_layout(rules.layout, rules.board_size, &mut rng);
+
         let mut map = HashMap::new();
         let mut queue = LinkedList::new();
-        for player in players {
-            map.insert(player.id, Arc::new(GamePlayer::new(player.clone())));
-        }
-
-        for game_player in map.clone() {
-            queue.push_back(game_player.1);
+        for player in players.iter() {
+            let game_player = Arc::new(GamePlayer::new(
+                player.clone(),
+                rules.hand_size,
+                rules.hint_budget,
+                &mut rng,
+            ));
+            if rules.power_ups {
+                game_player.seed_power_ups(0, &mut rng);
+            }
+            map.insert(player.id, game_player.clone());
+            queue.push_back(game_player);
         }
+        let replay = Replay {
+            game_id: id,
+            seed,
+            rules: rules.clone(),
+            seats: players.iter().map(|player| player.id).collect(),
+        };
+        let last_turn = match rules.coop {
+            Some(coop) => coop.rounds * players.len() as u32,
+            None => END_GAME_TURN,
+        };
         Self {
             id,
             turn: Mutex::new(1),
-            players: Mutex::new(map),
-            turn_queue: Mutex::new(queue),
-            board: Arc::new(Mutex::new(Board::new())),
-            board_backup: Mutex::new(Board::new()),
+            last_turn,
+            shared_score: Mutex::new(0),
+            players: TimedMutex::new("game.players", map),
+            seats: Mutex::new(replay.seats.clone()),
+            stand_ins: Mutex::new(HashMap::new()),
+            withdrawn: Mutex::new(Vec::new()),
+            turn_queue: TimedMutex::new("game.turn_queue", queue),
+            board: Arc::new(TimedMutex::new("game.board", board.clone())),
+            sent_board: Mutex::new((0, board.clone())),
+            board_backup: Mutex::new(board),
             timeout: Mutex::new(None),
+            turn_deadline: Mutex::new(None),
+            paused: Mutex::new(None),
+            pause_votes: Mutex::new(HashSet::new()),
+            draw_votes: Mutex::new(HashSet::new()),
+            kick_vote: Mutex::new(None),
+            spectators: Mutex::new(HashSet::new()),
+            subscribers: Mutex::new(HashSet::new()),
+            rules,
+            rng: Mutex::new(rng),
+            replay,
+            mode: GameMode::Standard,
+            custom_wordlist: None,
+            custom_lexicon: OnceLock::new(),
+            log: Mutex::new(Vec::new()),
+            annotations: Mutex::new(Vec::new()),
+            baseline: Baseline::default(),
+            errored: AtomicBool::new(false),
+        }
+    }
+
+    /// Put `players` in the order they will take turns. Seating the order
+    /// this returns again gives the same order back, which is what lets
+    /// `replay` pass in `Replay::seats`.
+    fn seat(
+        mut players: Vec<Arc<Player>>,
+        order: TurnOrder,
+        rng: &mut impl Rng,
+    ) -> Vec<Arc<Player>> {
+        match order {
+            TurnOrder::Random => {
+                // Seat from a fixed order so only the seed decides who goes first
+                players.sort_by_key(|player| player.id);
+                players.shuffle(rng);
+            }
+            TurnOrder::JoinOrder => {}
+            TurnOrder::DrawTiles => {
+                // Drawn in id order, so the seed alone decides who draws what
+                let mut drawing = players.iter().map(|player| player.id).collect::<Vec<_>>();
+                drawing.sort();
+                while drawing.len() > 1 {
+                    let draws = drawing
+                        .iter()
+                        .map(|id| (*id, rng.gen_range('a'..='z')))
+                        .collect::<Vec<_>>();
+                    let lowest = draws.iter().map(|(_, letter)| *letter).min().unwrap();
+                    drawing = draws
+                        .into_iter()
+                        .filter(|(_, letter)| *letter == lowest)
+                        .map(|(id, _)| id)
+                        .collect();
+                }
+                if let Some(first) = drawing
+                    .first()
+                    .and_then(|id| players.iter().position(|player| player.id == *id))
+                {
+                    players.rotate_left(first);
+                }
+            }
+        }
+        players
+    }
+
+    /// `player`'s daily challenge for `day`, set up the same as everyone
+    /// else's.
+    pub fn daily(id: u32, player: Arc<Player>, rules: GameRules, day: u32) -> Self {
+        Self {
+            mode: GameMode::Daily(day),
+            ..Self::with_seed(id, vec![player], rules, daily_seed(day))
+        }
+    }
+
+    /// `player`'s practice game, played with their lobby's rules.
+    pub fn practice(id: u32, player: Arc<Player>, rules: GameRules) -> Self {
+        Self {
+            mode: GameMode::Practice,
+            ..Self::with_rules(id, vec![player], rules)
+        }
+    }
+
+    /// Set a saved game up again. Everyone named in it is played by
+    /// `stand_in`'s player until they reconnect and claim their seat back.
+    pub fn restore(snapshot: &GameSnapshot, mut stand_in: impl FnMut(&str) -> Arc<Player>) -> Self {
+        let mut stand_ins: HashMap<String, Arc<Player>> = HashMap::new();
+        let mut player_named = |name: &str| {
+            stand_ins
+                .entry(name.to_string())
+                .or_insert_with(|| stand_in(name))
+                .clone()
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(snapshot.seed);
+        rng.set_word_pos(snapshot.rng_word_pos);
+        let mut board = Board::with_premiums(
+            snapshot
+                .premiums
+                .iter()
+                .map(|(x, y, premium)| ((*x, *y), *premium))
+                .collect(),
+            snapshot.rules.board_size,
+        );
+        for tile in snapshot.tiles.iter() {
+            board.tiles[tile.row][tile.col] =
+                Some(Tile::new(tile.char, player_named(&tile.owner), tile.turn));
+        }
+        let seated = snapshot
+            .seats
+            .iter()
+            .map(|seat| {
+                Arc::new(GamePlayer::restore(
+                    player_named(&seat.name),
+                    snapshot.rules.hand_size,
+                    seat,
+                ))
+            })
+            .collect::<Vec<_>>();
+        let mut queue = LinkedList::new();
+        for i in 0..seated.len() {
+            queue.push_back(seated[(snapshot.current_seat + i) % seated.len()].clone());
+        }
+        let seats = seated
+            .iter()
+            .map(|game_player| game_player.player.id)
+            .collect::<Vec<_>>();
+        Self {
+            id: snapshot.game_id,
+            turn: Mutex::new(snapshot.turn),
+            last_turn: snapshot.last_turn,
+            shared_score: Mutex::new(snapshot.shared_score),
+            players: TimedMutex::new(
+                "game.players",
+                seated
+                    .iter()
+                    .map(|game_player| (game_player.player.id, game_player.clone()))
+                    .collect(),
+            ),
+            seats: Mutex::new(seats.clone()),
+            stand_ins: Mutex::new(seats.iter().map(|id| (*id, Instant::now())).collect()),
+            withdrawn: Mutex::new(Vec::new()),
+            turn_queue: TimedMutex::new("game.turn_queue", queue),
+            board: Arc::new(TimedMutex::new("game.board", board.clone())),
+            sent_board: Mutex::new((0, board.clone())),
+            board_backup: Mutex::new(board),
+            timeout: Mutex::new(None),
+            turn_deadline: Mutex::new(None),
+            paused: Mutex::new(None),
+            pause_votes: Mutex::new(HashSet::new()),
+            draw_votes: Mutex::new(HashSet::new()),
+            kick_vote: Mutex::new(None),
+            spectators: Mutex::new(HashSet::new()),
+            subscribers: Mutex::new(HashSet::new()),
+            rules: snapshot.rules.clone(),
+            rng: Mutex::new(rng),
+            replay: Replay {
+                game_id: snapshot.game_id,
+                seed: snapshot.seed,
+                rules: snapshot.rules.clone(),
+                seats,
+            },
+            mode: snapshot.mode,
+            custom_wordlist: snapshot.custom_wordlist.clone(),
+            custom_lexicon: OnceLock::new(),
+            log: Mutex::new(Vec::new()),
+            annotations: Mutex::new(Vec::new()),
+            baseline: Baseline {
+                tiles: snapshot.tiles.len(),
+                scores: snapshot
+                    .seats
+                    .iter()
+                    .map(|seat| (seat.name.clone(), seat.tally.score))
+                    .collect(),
+            },
+            errored: AtomicBool::new(false),
+        }
+    }
+
+    /// Set the game `replay` records up again and play `events` over it.
+    /// `players` need the ids the game had, in `replay.seats` order.
+    pub fn replay(
+        replay: &Replay,
+        players: Vec<Arc<Player>>,
+        events: &[Event],
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let game = Self::with_seed(replay.game_id, players, replay.rules.clone(), replay.seed);
+        for event in events {
+            game.apply(event.clone())?;
+        }
+        Ok(game)
+    }
+
+    /// Check `event` can happen now, make the change and log it. The log
+    /// stays locked throughout, so events on one game never interleave.
+    pub fn apply(&self, event: Event) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut log = self.log.lock().unwrap();
+        match event {
+            Event::PlaceTile {
+                player_id,
+                card_index,
+                x,
+                y,
+            } => self.place_tile(player_id, card_index, x, y)?,
+            Event::RemoveTile { player_id, x, y } => self.remove_tile(player_id, x, y)?,
+            Event::Shuffle { player_id } => {
+                let game_player = self.get_turn_player(player_id)?;
+                self.check_can_shuffle(&game_player)?;
+                let mut rng = self.get_rng();
+                game_player.get_new_card(&mut *rng);
+                if self.rules.power_ups {
+                    game_player.seed_power_ups(0, &mut *rng);
+                }
+            }
+            Event::Exchange {
+                player_id,
+                ref card_indices,
+            } => {
+                let game_player = self.get_turn_player(player_id)?;
+                self.check_can_shuffle(&game_player)?;
+                if card_indices.is_empty() {
+                    return Err("No cards to exchange".into());
+                }
+                if card_indices.len() > self.rules.hand_size {
+                    return Err("Can't exchange more cards than a hand holds".into());
+                }
+                let held = game_player.get_cards().len();
+                if card_indices.iter().any(|index| *index >= held) {
+                    return Err("Card not found".into());
+                }
+                if card_indices.iter().collect::<HashSet<_>>().len() != card_indices.len() {
+                    return Err("Card exchanged twice".into());
+                }
+                let mut rng = self.get_rng();
+                game_player.exchange(card_indices, &mut *rng);
+                if self.rules.power_ups {
+                    game_player.seed_power_ups(held - card_indices.len(), &mut *rng);
+                }
+            }
+            Event::UseHint { player_id } => {
+                if self.get_turn_player(player_id)?.use_hint().is_none() {
+                    return Err("No hints left".into());
+                }
+            }
+            Event::EndTurn { keep_tiles } => self.end_turn(keep_tiles),
+            Event::Leave { player_id } => {
+                if self.players.lock().unwrap().remove(&player_id).is_none() {
+                    return Err("Player is not in the game".into());
+                }
+            }
+            Event::Forfeit { player_id } => self.withdraw(player_id, Withdrawal::Forfeited)?,
+            Event::Resign { player_id } => self.withdraw(player_id, Withdrawal::Resigned)?,
+        }
+        log.push(event);
+        Ok(())
+    }
+
+    pub fn get_events(&self) -> Vec<Event> {
+        self.log.lock().unwrap().clone()
+    }
+
+    /// What each turn logged so far came to, in order.
+    pub fn get_annotations(&self) -> Vec<MoveAnnotation> {
+        self.annotations.lock().unwrap().clone()
+    }
+
+    pub fn get_baseline(&self) -> &Baseline {
+        &self.baseline
+    }
+
+    /// Everything needed to write the game down once it's over.
+    pub fn finished_replay(&self) -> FinishedReplay {
+        let names = self
+            .get_players()
+            .into_iter()
+            .chain(
+                self.get_withdrawn()
+                    .into_iter()
+                    .map(|(game_player, _)| game_player),
+            )
+            .map(|game_player| (game_player.player.id, game_player.player.name.clone()))
+            .collect();
+        FinishedReplay {
+            replay: self.replay.clone(),
+            names,
+            events: self.get_events(),
+            annotations: self.get_annotations(),
+        }
+    }
+
+    fn get_turn_player(
+        &self,
+        player_id: u32,
+    ) -> Result<Arc<GamePlayer>, Box<dyn Error + Send + Sync>> {
+        let game_player = match self.get_player(player_id) {
+            Some(game_player) => game_player,
+            None => return Err("Player not found".into()),
+        };
+        if self.get_player_in_this_turn() != game_player {
+            return Err(Box::new(MoveViolation::NotYourTurn));
+        }
+        Ok(game_player)
+    }
+
+    /// A hand can be shuffled or exchanged once a turn, before any of it is
+    /// played.
+    fn check_can_shuffle(
+        &self,
+        game_player: &GamePlayer,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if game_player.get_has_shuffled() {
+            return Err("Player has shuffled in this turn".into());
+        }
+        // New cards would leave the turn's tiles on the board without the
+        // cards they came from
+        if game_player.get_cards().iter().any(|card| card.used) {
+            return Err("Player has placed tiles in this turn".into());
+        }
+        Ok(())
+    }
+
+    fn withdraw(
+        &self,
+        player_id: u32,
+        withdrawal: Withdrawal,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_player = match self.players.lock().unwrap().remove(&player_id) {
+            Some(game_player) => game_player,
+            None => return Err("Player is not in the game".into()),
+        };
+        self.stand_ins.lock().unwrap().remove(&player_id);
+        let mut queue = self.turn_queue.lock().unwrap();
+        // Their turn ends with nothing kept, and goes to the next in line
+        if queue.front() == Some(&game_player) {
+            self.restore_board();
+            if let Some(next) = queue.iter().nth(1) {
+                next.set_has_shuffled(false);
+            }
+        }
+        *queue = queue
+            .iter()
+            .filter(|queued| **queued != game_player)
+            .cloned()
+            .collect();
+        self.withdrawn
+            .lock()
+            .unwrap()
+            .push((game_player, withdrawal));
+        Ok(())
+    }
+
+    fn place_tile(
+        &self,
+        player_id: u32,
+        card_index: usize,
+        x: usize,
+        y: usize,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_player = self.get_turn_player(player_id)?;
+        if self
+            .get_turn_deadline()
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(Box::new(MoveViolation::TurnOver));
+        }
+        if card_index >= game_player.get_cards().len() {
+            return Err(Box::new(MoveViolation::CardNotInHand));
+        }
+        let card = game_player.get_card(card_index);
+        if card.used {
+            return Err(Box::new(MoveViolation::CardReused));
+        }
+        let mut board = self.board.lock().unwrap();
+        board.check_placement(x, y)?;
+        board.tiles[BOARD_SIZE - y - 1][x] = Some(
+            Tile::new(card.char, game_player.player.clone(), self.get_turns())
+                .with_power_up(card.power_up),
+        );
+        game_player.take_card(card_index);
+        Ok(())
+    }
+
+    fn remove_tile(
+        &self,
+        player_id: u32,
+        x: usize,
+        y: usize,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Only the player on turn has tiles placed this turn
+        let game_player = self.get_turn_player(player_id)?;
+        let mut board = self.board.lock().unwrap();
+        if !board.in_bounds(x, y) {
+            return Err("card not in the board".into());
+        }
+        let tile = match board.tiles[BOARD_SIZE - y - 1][x].clone() {
+            Some(tile) => tile,
+            None => return Err("card not in the board".into()),
+        };
+        if tile.turn != self.get_turns() {
+            return Err("card not place in this turn".into());
+        }
+        board.tiles[BOARD_SIZE - y - 1][x] = None;
+        game_player.return_cancel_card(tile.char, tile.power_up);
+        Ok(())
+    }
+
+    /// Score the turn, refill the player's hand and pass the turn on.
+    fn end_turn(&self, keep_tiles: bool) {
+        let game_player = self.get_player_in_this_turn();
+        if !keep_tiles {
+            self.restore_board();
+            game_player.return_cards();
+        }
+        let power_ups = {
+            // Whatever is left of this turn on the board passed validation
+            let board = self.board.lock().unwrap();
+            let turn = self.get_turns();
+            let placed = board
+                .tiles
+                .iter()
+                .flatten()
+                .flatten()
+                .filter(|tile| tile.turn == turn)
+                .collect::<Vec<_>>();
+            let tiles_placed = placed.len() as u32;
+            let power_ups = placed
+                .iter()
+                .filter_map(|tile| tile.power_up)
+                .collect::<Vec<_>>();
+            let words = scoring::score_turn(&board, turn, self.rules.scoring);
+            game_player.record_turn(&words, tiles_placed);
+            let mut score = scoring::score_bonuses(
+                &words,
+                tiles_placed,
+                self.rules.hand_size,
+                game_player.get_tally().streak,
+                self.rules.streak_multipliers,
+            );
+            let doubled = power_ups
+                .iter()
+                .filter(|power_up| **power_up == PowerUp::DoubleWord)
+                .count() as u32;
+            score.points *= 2u32.pow(doubled);
+            score.multiplier *= 2u32.pow(doubled);
+            let word_points = words.iter().map(|(_, score)| score).sum::<u32>();
+            game_player.add_bonus(score.points - word_points);
+            *self.shared_score.lock().unwrap() += score.points;
+            self.annotations.lock().unwrap().push(MoveAnnotation {
+                turn,
+                player_id: game_player.player.id,
+                points: score.points,
+                bingo_bonus: score.bingo_bonus,
+                multiplier: score.multiplier,
+                words,
+                tiles_placed,
+                power_ups: power_ups.clone(),
+                placement: Placement::on(&board, turn),
+                turns_left: self.last_turn.saturating_sub(turn),
+            });
+            power_ups
+        };
+        for power_up in power_ups {
+            if power_up == PowerUp::StealLetter {
+                self.steal_letter(&game_player);
+            }
+        }
+        let kept = game_player
+            .get_cards()
+            .iter()
+            .filter(|card| !card.used)
+            .count();
+        let mut rng = self.get_rng();
+        game_player.refill(&mut *rng);
+        if self.rules.power_ups {
+            game_player.seed_power_ups(kept, &mut *rng);
+        }
+        drop(rng);
+        self.next_turn();
+        self.backup_board();
+    }
+
+    /// Move a random card from the next player's hand to `thief`'s, dealing
+    /// the next player another in its place.
+    fn steal_letter(&self, thief: &GamePlayer) {
+        let victim = match self.get_next_turn_player() {
+            Some(victim) if *victim != *thief => victim,
+            _ => return,
+        };
+        let mut rng = self.get_rng();
+        if let Some(card) = victim.give_up_card(&mut *rng) {
+            let kept = victim.get_cards().len();
+            victim.refill(&mut *rng);
+            victim.seed_power_ups(kept, &mut *rng);
+            thief.add_card(card);
+        }
+    }
+
+    /// The game as of its last finished turn, so tiles placed this turn go
+    /// back in their player's hand.
+    pub fn snapshot(&self) -> GameSnapshot {
+        let seats = self.get_players();
+        // Players who left are still queued, the turn passes over them
+        let current_seat = self
+            .get_turn_queue()
+            .iter()
+            .find_map(|queued| seats.iter().position(|seated| seated == queued))
+            .unwrap_or(0);
+        let board = self.board_backup.lock().unwrap().clone();
+        let mut tiles = Vec::new();
+        for (row, cols) in board.tiles.iter().enumerate() {
+            for (col, tile) in cols.iter().enumerate() {
+                if let Some(tile) = tile {
+                    tiles.push(TileSnapshot {
+                        row,
+                        col,
+                        char: tile.char,
+                        owner: tile.owner.name.clone(),
+                        turn: tile.turn,
+                    });
+                }
+            }
+        }
+        GameSnapshot {
+            game_id: self.id,
+            mode: self.mode,
+            rules: self.rules.clone(),
+            seed: self.replay.seed,
+            rng_word_pos: self.get_rng().get_word_pos(),
+            custom_wordlist: self.custom_wordlist.clone(),
+            turn: self.get_turns(),
+            last_turn: self.last_turn,
+            shared_score: self.get_shared_score(),
+            seats: seats
+                .iter()
+                .map(|game_player| game_player.snapshot())
+                .collect(),
+            current_seat,
+            tiles,
+            premiums: board
+                .get_premiums()
+                .iter()
+                .map(|((x, y), premium)| (*x, *y, *premium))
+                .collect(),
+        }
+    }
+
+    /// Keep `player_id`'s seat for them to claim back when they reconnect.
+    /// Returns false if they aren't in the game.
+    pub fn release_seat(&self, player_id: u32) -> bool {
+        if self.get_player(player_id).is_none() {
+            return false;
+        }
+        self.stand_ins
+            .lock()
+            .unwrap()
+            .entry(player_id)
+            .or_insert_with(Instant::now);
+        true
+    }
+
+    /// Players whose seats are waiting for them, with since when.
+    pub fn get_vacant_seats(&self) -> Vec<(Arc<GamePlayer>, Instant)> {
+        let stand_ins = self.stand_ins.lock().unwrap();
+        self.get_players()
+            .into_iter()
+            .filter_map(|game_player| {
+                stand_ins
+                    .get(&game_player.player.id)
+                    .map(|since| (game_player, *since))
+            })
+            .collect()
+    }
+
+    pub fn get_withdrawn(&self) -> Vec<(Arc<GamePlayer>, Withdrawal)> {
+        self.withdrawn.lock().unwrap().clone()
+    }
+
+    /// Hand `player` the seat a stand-in of the same name holds, if any.
+    pub fn claim_seat(&self, player: Arc<Player>) -> Option<Arc<GamePlayer>> {
+        let stand_in = {
+            let mut stand_ins = self.stand_ins.lock().unwrap();
+            let stand_in = self
+                .players
+                .lock()
+                .unwrap()
+                .values()
+                .find(|game_player| {
+                    stand_ins.contains_key(&game_player.player.id)
+                        && game_player.player.name == player.name
+                })
+                .cloned()?;
+            stand_ins.remove(&stand_in.player.id);
+            stand_in
+        };
+        let game_player = Arc::new(stand_in.reseat(player.clone()));
+        {
+            let mut players = self.players.lock().unwrap();
+            players.remove(&stand_in.player.id);
+            players.insert(player.id, game_player.clone());
+        }
+        for id in self.seats.lock().unwrap().iter_mut() {
+            if *id == stand_in.player.id {
+                *id = player.id;
+            }
+        }
+        for queued in self.turn_queue.lock().unwrap().iter_mut() {
+            if *queued == stand_in {
+                *queued = game_player.clone();
+            }
+        }
+        for annotation in self.annotations.lock().unwrap().iter_mut() {
+            if annotation.player_id == stand_in.player.id {
+                annotation.player_id = player.id;
+            }
+        }
+        for board in [
+            &mut *self.board.lock().unwrap(),
+            &mut *self.board_backup.lock().unwrap(),
+        ] {
+            for tile in board.tiles.iter_mut().flatten().flatten() {
+                if tile.owner == stand_in.player {
+                    tile.owner = player.clone();
+                }
+            }
+        }
+        Some(game_player)
+    }
+
+    pub fn with_custom_wordlist(self, wordlist: Option<Arc<HashSet<String>>>) -> Self {
+        Self {
+            custom_wordlist: wordlist,
+            custom_lexicon: OnceLock::new(),
+            ..self
         }
     }
 
-    pub fn set_timeout_task(&self, task: Arc<JoinHandle<()>>) {
+    pub fn get_custom_wordlist(&self) -> Option<Arc<HashSet<String>>> {
+        self.custom_wordlist.clone()
+    }
+
+    pub fn get_custom_lexicon(&self) -> Option<Arc<Lexicon>> {
+        let wordlist = self.custom_wordlist.as_ref()?;
+        Some(
+            self.custom_lexicon
+                .get_or_init(|| Arc::new(Lexicon::new(wordlist.iter())))
+                .clone(),
+        )
+    }
+
+    pub fn get_mode(&self) -> GameMode {
+        self.mode
+    }
+
+    pub fn get_rules(&self) -> &GameRules {
+        &self.rules
+    }
+
+    pub fn get_replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Every random draw during the game has to come from here to keep it
+    /// replayable.
+    pub fn get_rng(&self) -> MutexGuard<'_, ChaCha8Rng> {
+        self.rng.lock().unwrap()
+    }
+
+    pub fn set_timeout_task(&self, task: Arc<JoinHandle<()>>, deadline: Instant) {
         *self.timeout.lock().unwrap() = Some(task);
+        *self.turn_deadline.lock().unwrap() = Some(deadline);
+    }
+
+    /// When the running turn timer fires, if one was ever started.
+    pub fn get_turn_deadline(&self) -> Option<Instant> {
+        *self.turn_deadline.lock().unwrap()
+    }
+
+    /// Stop the game for good after its logic panicked, returning who was
+    /// playing it. Only the first call returns them. Locks the panic
+    /// poisoned are taken anyway, just to stop the timer and read the seats.
+    pub fn set_errored(&self) -> Option<Vec<Arc<Player>>> {
+        if self.errored.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        if let Some(task) = self
+            .timeout
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            task.abort();
+        }
+        let players = self.players.lock().unwrap_or_else(PoisonError::into_inner);
+        Some(
+            players
+                .values()
+                .map(|game_player| game_player.player.clone())
+                .collect(),
+        )
+    }
+
+    pub fn is_errored(&self) -> bool {
+        self.errored.load(Ordering::SeqCst)
     }
 
     pub fn cancel_timeout_task(&self) -> bool {
@@ -62,45 +1028,306 @@ impl Game {
         }
     }
 
-    pub fn get_board(&self) -> Arc<Mutex<Board>> {
-        self.board.clone()
+    pub fn is_paused(&self) -> bool {
+        self.paused.lock().unwrap().is_some()
+    }
+
+    /// Record `player_id`'s vote to flip the paused state. Votes of players
+    /// who have left don't count.
+    pub fn vote_pause(&self, player_id: u32) -> PauseStatus {
+        self.pause_votes.lock().unwrap().insert(player_id);
+        self.get_pause_status()
+    }
+
+    pub fn get_pause_status(&self) -> PauseStatus {
+        let players = self.players.lock().unwrap();
+        let votes = self
+            .pause_votes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|id| players.contains_key(id))
+            .count();
+        PauseStatus {
+            paused: self.is_paused(),
+            votes: votes as u32,
+            votes_needed: (players.len() / 2 + 1) as u32,
+        }
+    }
+
+    pub fn vote_draw(&self, player_id: u32) -> DrawStatus {
+        self.draw_votes.lock().unwrap().insert(player_id);
+        self.get_draw_status()
+    }
+
+    /// Take the draw offer off the table.
+    pub fn decline_draw(&self) {
+        self.draw_votes.lock().unwrap().clear();
+    }
+
+    /// Open a vote to kick `target` out, with `player_id` voting for it.
+    pub fn start_kick_vote(
+        &self,
+        player_id: u32,
+        target: u32,
+        deadline: Instant,
+    ) -> Result<KickStatus, Box<dyn Error + Send + Sync>> {
+        let players = self.players.lock().unwrap();
+        let mut kick_vote = self.kick_vote.lock().unwrap();
+        if kick_vote.is_some() {
+            return Err("A vote to kick is already open".into());
+        }
+        if player_id == target {
+            return Err("Can't vote to kick yourself".into());
+        }
+        if !players.contains_key(&target) {
+            return Err("Player not found".into());
+        }
+        if players.len() < MIN_VOTE_KICK_PLAYERS {
+            return Err("Not enough players to vote".into());
+        }
+        let vote = kick_vote.insert(KickVote {
+            target,
+            votes: HashMap::from([(player_id, true)]),
+            deadline,
+        });
+        Ok(Game::kick_status(&players, vote))
+    }
+
+    /// Cast or change `player_id`'s vote in the open vote to kick.
+    pub fn vote_kick(
+        &self,
+        player_id: u32,
+        kick: bool,
+    ) -> Result<KickStatus, Box<dyn Error + Send + Sync>> {
+        let players = self.players.lock().unwrap();
+        let mut kick_vote = self.kick_vote.lock().unwrap();
+        let vote = match kick_vote.as_mut() {
+            Some(vote) => vote,
+            None => return Err("No vote to kick open".into()),
+        };
+        if vote.target == player_id {
+            return Err("Can't vote to kick yourself".into());
+        }
+        vote.votes.insert(player_id, kick);
+        Ok(Game::kick_status(&players, vote))
+    }
+
+    pub fn get_kick_status(&self) -> Option<KickStatus> {
+        let players = self.players.lock().unwrap();
+        let kick_vote = self.kick_vote.lock().unwrap();
+        kick_vote
+            .as_ref()
+            .map(|vote| Game::kick_status(&players, vote))
+    }
+
+    /// Close the vote to kick that runs out at `deadline`, returning where
+    /// it stood. `None` if it was already closed, or another vote is open.
+    pub fn end_kick_vote(&self, deadline: Instant) -> Option<KickStatus> {
+        let players = self.players.lock().unwrap();
+        let mut kick_vote = self.kick_vote.lock().unwrap();
+        if kick_vote
+            .as_ref()
+            .is_none_or(|vote| vote.deadline != deadline)
+        {
+            return None;
+        }
+        kick_vote
+            .take()
+            .map(|vote| Game::kick_status(&players, &vote))
+    }
+
+    /// Only players still in the game count, and the target can't vote.
+    fn kick_status(players: &HashMap<u32, Arc<GamePlayer>>, vote: &KickVote) -> KickStatus {
+        let voters = players.keys().filter(|id| **id != vote.target).count() as u32;
+        let count = |kick: bool| {
+            vote.votes
+                .iter()
+                .filter(|(id, vote)| **vote == kick && players.contains_key(id))
+                .count() as u32
+        };
+        KickStatus {
+            target: vote.target,
+            votes: count(true),
+            votes_against: count(false),
+            votes_needed: voters / 2 + 1,
+            voters,
+            deadline: vote.deadline,
+        }
+    }
+
+    pub fn add_spectator(&self, player_id: u32) {
+        self.spectators.lock().unwrap().insert(player_id);
+    }
+
+    pub fn remove_spectator(&self, player_id: u32) {
+        self.spectators.lock().unwrap().remove(&player_id);
+    }
+
+    pub fn get_spectators(&self) -> Vec<u32> {
+        self.spectators.lock().unwrap().iter().copied().collect()
+    }
+
+    pub fn add_subscriber(&self, player_id: u32) {
+        self.subscribers.lock().unwrap().insert(player_id);
+    }
+
+    pub fn remove_subscriber(&self, player_id: u32) {
+        self.subscribers.lock().unwrap().remove(&player_id);
+    }
+
+    pub fn get_subscribers(&self) -> Vec<u32> {
+        self.subscribers.lock().unwrap().iter().copied().collect()
+    }
+
+    /// A draw takes every player still in the game.
+    pub fn get_draw_status(&self) -> DrawStatus {
+        let players = self.players.lock().unwrap();
+        let votes = self
+            .draw_votes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|id| players.contains_key(id))
+            .count();
+        DrawStatus {
+            votes: votes as u32,
+            votes_needed: players.len() as u32,
+        }
+    }
+
+    /// Stop the turn timer and keep the time that was left on it.
+    pub fn pause(&self) {
+        self.cancel_timeout_task();
+        let remaining = match *self.turn_deadline.lock().unwrap() {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => self.rules.turn_duration,
+        };
+        *self.paused.lock().unwrap() = Some(remaining);
+        self.pause_votes.lock().unwrap().clear();
+    }
+
+    /// Unpause, returning the turn time that was left when the game paused.
+    pub fn resume(&self) -> Option<Duration> {
+        self.pause_votes.lock().unwrap().clear();
+        self.paused.lock().unwrap().take()
+    }
+
+    /// Add what the game's rules score at its end on top of the turns,
+    /// once it is over.
+    pub fn finish_scoring(&self) {
+        if !self.rules.territory {
+            return;
+        }
+        let territory = scoring::score_territory(&self.board.lock().unwrap());
+        for game_player in self.get_players() {
+            if let Some(points) = territory.get(&game_player.player.name) {
+                game_player.add_bonus(*points);
+                *self.shared_score.lock().unwrap() += points;
+            }
+        }
+    }
+
+    /// Points everyone has scored together so far.
+    pub fn get_shared_score(&self) -> u32 {
+        *self.shared_score.lock().unwrap()
     }
 
-    pub fn remove_player(&self, player: Arc<Player>) -> Option<Arc<GamePlayer>> {
-        self.players.lock().unwrap().remove(&player.id)
+    /// Where the shared score stands against the target, `None` unless the
+    /// game is co-op.
+    pub fn get_coop_status(&self) -> Option<CoopStatus> {
+        self.rules.coop.map(|coop| CoopStatus {
+            score: self.get_shared_score(),
+            target: coop.target,
+            turns_left: (self.last_turn + 1).saturating_sub(self.get_turns()),
+        })
+    }
+
+    /// The turn the game ends after, fewer or more than `END_GAME_TURN` in
+    /// co-op games depending on the rounds and players.
+    pub fn get_last_turn(&self) -> u32 {
+        self.last_turn
+    }
+
+    /// Whether the last turn is done, or a co-op game already reached its
+    /// target.
+    pub fn is_over(&self) -> bool {
+        self.get_turns() > self.last_turn
+            || self.get_coop_status().is_some_and(|status| status.is_won())
+    }
+
+    pub fn get_board(&self) -> Arc<TimedMutex<Board>> {
+        self.board.clone()
     }
 
     pub fn get_player(&self, id: u32) -> Option<Arc<GamePlayer>> {
         Some(self.players.lock().unwrap().get(&id)?.clone())
     }
 
-    pub fn backup_board(&self) {
+    fn backup_board(&self) {
         *self.board_backup.lock().unwrap() = self.board.lock().unwrap().clone();
     }
 
-    pub fn restore_board(&self) {
+    fn restore_board(&self) {
         *self.board.lock().unwrap() = self.board_backup.lock().unwrap().clone();
     }
 
-    #[cfg(test)]
+    /// The board as it stood when this turn started.
     pub fn get_board_backup(&self) -> Board {
         self.board_backup.lock().unwrap().clone()
     }
 
+    /// Squares that changed since clients were last told, by `(x, y)` in
+    /// the coordinates tiles are set with, and the board version the changes
+    /// bring them to. The version only goes up when something changed.
+    pub fn take_board_changes(&self) -> (u64, Vec<(usize, usize, Option<Tile>)>) {
+        let board = self.board.lock().unwrap();
+        let mut sent = self.sent_board.lock().unwrap();
+        let mut changes = Vec::new();
+        for (row, (tiles, sent_tiles)) in board.tiles.iter().zip(&sent.1.tiles).enumerate() {
+            for (x, (tile, sent_tile)) in tiles.iter().zip(sent_tiles).enumerate() {
+                let unchanged = match (tile, sent_tile) {
+                    (Some(tile), Some(sent_tile)) => tile.looks_like(sent_tile),
+                    (None, None) => true,
+                    _ => false,
+                };
+                if !unchanged {
+                    changes.push((x, BOARD_SIZE - row - 1, tile.clone()));
+                }
+            }
+        }
+        if !changes.is_empty() {
+            sent.0 += 1;
+            sent.1 = board.clone();
+        }
+        (sent.0, changes)
+    }
+
+    /// The board as clients were last told about it, with its version.
+    pub fn get_sent_board(&self) -> (u64, Board) {
+        self.sent_board.lock().unwrap().clone()
+    }
+
+    /// Players still in the game, in seat order.
     pub fn get_players(&self) -> Vec<Arc<GamePlayer>> {
-        self.players
+        let players = self.players.lock().unwrap();
+        self.seats
             .lock()
             .unwrap()
-            .values()
-            .map(|player| player.clone())
+            .iter()
+            .filter_map(|id| players.get(id).cloned())
             .collect()
     }
 
+    pub fn get_turn_queue(&self) -> Vec<Arc<GamePlayer>> {
+        self.turn_queue.lock().unwrap().iter().cloned().collect()
+    }
+
     pub fn get_turns(&self) -> u32 {
         self.turn.lock().unwrap().clone()
     }
 
-    pub fn next_turn(&self) -> u32 {
+    fn next_turn(&self) -> u32 {
         *self.turn.lock().unwrap() += 1;
         let pop_player = self.turn_queue.lock().unwrap().pop_front().unwrap();
         pop_player.set_has_shuffled(false);
@@ -113,7 +1340,7 @@ impl Game {
     }
 
     pub fn get_next_turn_player(&self) -> Option<Arc<GamePlayer>> {
-        if self.get_turns() >= END_GAME_TURN {
+        if self.get_turns() >= self.last_turn {
             return None;
         } else if self.get_players().len() == 1 {
             return Some(self.get_player_in_this_turn());
@@ -140,6 +1367,7 @@ mod tests {
     use tokio::time::{sleep, Duration};
 
     use super::*;
+    use crate::game::rules::Coop;
 
     use std::error::Error;
 
@@ -239,13 +1467,28 @@ mod tests {
         let task = Arc::new(tokio::spawn(async {
             sleep(Duration::from_secs(1)).await;
         }));
-        game.set_timeout_task(task.clone());
+        game.set_timeout_task(task.clone(), Instant::now() + Duration::from_secs(1));
         assert!(game.cancel_timeout_task());
         sleep(Duration::from_millis(10)).await;
         assert!(task.is_finished());
         Ok(())
     }
 
+    #[test]
+    fn with_rules_with_hand_size_should_deal_hand_size_cards() {
+        let game = Game::with_rules(
+            0,
+            vec![Arc::new(Player::new(0, String::from("test")))],
+            GameRules {
+                hand_size: 6,
+                ..GameRules::default()
+            },
+        );
+        let game_player = game.get_player(0).unwrap();
+        assert_eq!(game_player.get_cards().len(), 6);
+        assert_eq!(game_player.get_new_card(&mut *game.get_rng()).len(), 6);
+    }
+
     #[tokio::test]
     async fn cancel_timeout_task_with_none_should_return_false(
     ) -> Result<(), Box<dyn Error + Sync + Send>> {
@@ -254,4 +1497,620 @@ mod tests {
         assert!(!game.cancel_timeout_task());
         Ok(())
     }
+
+    #[test]
+    fn with_seed_with_same_seed_should_set_up_same_game() {
+        let players = (0..4)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let rules = GameRules {
+            layout: crate::game::board::BoardLayout::RandomPremium,
+            ..GameRules::default()
+        };
+        let mut reversed = players.clone();
+        reversed.reverse();
+        let game = Game::with_seed(0, players, rules.clone(), 42);
+        let other = Game::with_seed(1, reversed, rules, 42);
+        assert_eq!(game.get_replay().seats, other.get_replay().seats);
+        assert_eq!(
+            game.get_board().lock().unwrap().get_premiums(),
+            other.get_board().lock().unwrap().get_premiums()
+        );
+        let chars = |cards: Vec<crate::game::card::Card>| {
+            cards.iter().map(|card| card.char).collect::<String>()
+        };
+        for (game_player, other_player) in game.get_players().iter().zip(other.get_players()) {
+            assert_eq!(
+                chars(game_player.get_cards()),
+                chars(other_player.get_cards())
+            );
+            assert_eq!(
+                chars(game_player.get_new_card(&mut *game.get_rng())),
+                chars(other_player.get_new_card(&mut *other.get_rng()))
+            );
+        }
+    }
+
+    #[test]
+    fn with_seed_with_drawn_turn_order_should_follow_join_order_and_replay() {
+        let players = [3, 0, 2, 1]
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .to_vec();
+        let rules = GameRules {
+            turn_order: TurnOrder::DrawTiles,
+            ..GameRules::default()
+        };
+        for seed in 0..8 {
+            let game = Game::with_seed(0, players.clone(), rules.clone(), seed);
+            let seats = game.get_replay().seats.clone();
+            let first = players.iter().position(|player| player.id == seats[0]);
+            let mut joined = vec![3, 0, 2, 1];
+            joined.rotate_left(first.unwrap());
+            assert_eq!(seats, joined);
+            let seated = seats
+                .iter()
+                .map(|id| Arc::new(Player::new(*id, format!("test{}", id))))
+                .collect();
+            let replayed = Game::replay(game.get_replay(), seated, &[]).unwrap();
+            assert_eq!(replayed.get_replay().seats, seats);
+        }
+        let rules = GameRules {
+            turn_order: TurnOrder::JoinOrder,
+            ..GameRules::default()
+        };
+        let game = Game::with_seed(0, players, rules, 42);
+        assert_eq!(game.get_replay().seats, vec![3, 0, 2, 1]);
+    }
+
+    #[test]
+    fn daily_with_same_day_should_deal_same_hand_to_different_players() {
+        let cards = |game: &Game| {
+            game.get_player_in_this_turn()
+                .get_cards()
+                .iter()
+                .map(|card| card.char)
+                .collect::<String>()
+        };
+        let game = Game::daily(
+            0,
+            Arc::new(Player::new(0, String::from("test"))),
+            GameRules::default(),
+            20000,
+        );
+        let other = Game::daily(
+            1,
+            Arc::new(Player::new(7, String::from("test1"))),
+            GameRules::default(),
+            20000,
+        );
+        assert_eq!(game.get_mode(), GameMode::Daily(20000));
+        assert_eq!(cards(&game), cards(&other));
+        assert_eq!(game.get_replay().seed, other.get_replay().seed);
+        assert_ne!(daily_seed(20000), daily_seed(20001));
+    }
+
+    #[tokio::test]
+    async fn pause_with_running_timer_should_keep_remaining_time() {
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        let task = Arc::new(tokio::spawn(async {
+            sleep(Duration::from_secs(10)).await;
+        }));
+        game.set_timeout_task(task, Instant::now() + Duration::from_secs(10));
+        game.pause();
+        assert!(game.is_paused());
+        let remaining = game.resume().unwrap();
+        assert!(remaining > Duration::from_secs(9) && remaining <= Duration::from_secs(10));
+        assert!(!game.is_paused());
+    }
+
+    #[test]
+    fn get_pause_status_should_ignore_votes_of_players_who_left() {
+        let players = (0..3)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players.clone());
+        game.vote_pause(0);
+        assert_eq!(game.vote_pause(1).votes, 2);
+        game.apply(Event::Leave { player_id: 1 }).unwrap();
+        let status = game.get_pause_status();
+        assert_eq!(status.votes, 1);
+        assert_eq!(status.votes_needed, 2);
+    }
+
+    #[test]
+    fn get_draw_status_should_need_every_player_still_in_game() {
+        let players = (0..3)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players.clone());
+        assert!(!game.get_draw_status().is_agreed());
+        game.vote_draw(0);
+        assert!(!game.vote_draw(1).is_agreed());
+        game.apply(Event::Leave { player_id: 2 }).unwrap();
+        assert!(game.get_draw_status().is_agreed());
+        game.decline_draw();
+        assert_eq!(game.get_draw_status().votes, 0);
+    }
+
+    #[test]
+    fn restore_with_snapshot_should_pick_up_where_game_left_off() {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players);
+        let first = game.get_player_in_this_turn();
+        game.get_board().lock().unwrap().tiles[0][0] =
+            Some(Tile::new('a', first.player.clone(), 1));
+        game.backup_board();
+        game.next_turn();
+        // Not committed, so not in the snapshot
+        game.get_board().lock().unwrap().tiles[0][1] =
+            Some(Tile::new('b', first.player.clone(), 2));
+        let snapshot = game.snapshot();
+        let mut next_id = 100;
+        let restored = Game::restore(&snapshot, |name| {
+            next_id += 1;
+            Arc::new(Player::new(next_id, name.to_string()))
+        });
+        assert_eq!(restored.get_turns(), 2);
+        assert_eq!(
+            restored.get_player_in_this_turn().player.name,
+            game.get_player_in_this_turn().player.name
+        );
+        assert!(restored.get_board_backup().tiles[0][1].is_none());
+        let chars = |cards: Vec<crate::game::card::Card>| {
+            cards.iter().map(|card| card.char).collect::<String>()
+        };
+        let restored_first = restored
+            .get_players()
+            .into_iter()
+            .find(|game_player| game_player.player.name == first.player.name)
+            .unwrap();
+        assert_eq!(chars(restored_first.get_cards()), chars(first.get_cards()));
+        assert_eq!(
+            chars(restored_first.get_new_card(&mut *restored.get_rng())),
+            chars(first.get_new_card(&mut *game.get_rng()))
+        );
+
+        let player = Arc::new(Player::new(7, first.player.name.clone()));
+        assert!(restored.claim_seat(player.clone()).is_some());
+        assert!(restored.claim_seat(player.clone()).is_none());
+        assert!(restored.get_player(7).is_some());
+        assert_eq!(restored.get_players().len(), 2);
+        let tile = restored.get_board_backup().tiles[0][0].clone().unwrap();
+        assert_eq!(tile.owner, player);
+    }
+
+    #[test]
+    fn replay_with_logged_events_should_rebuild_same_game(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players.clone());
+        let first = game.get_player_in_this_turn().player.id;
+        let center = game.get_rules().board_size / 2;
+        game.apply(Event::PlaceTile {
+            player_id: first,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        game.apply(Event::EndTurn { keep_tiles: true })?;
+        let second = game.get_player_in_this_turn().player.id;
+        game.apply(Event::Shuffle { player_id: second })?;
+        game.apply(Event::EndTurn { keep_tiles: false })?;
+
+        let replayed = Game::replay(game.get_replay(), players, &game.get_events())?;
+        assert_eq!(replayed.get_turns(), 3);
+        assert_eq!(replayed.get_events(), game.get_events());
+        assert_eq!(replayed.get_annotations(), game.get_annotations());
+        let annotations = game.get_annotations();
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].player_id, first);
+        assert_eq!(annotations[0].tiles_placed, 1);
+        assert_eq!(annotations[0].words.len(), 1);
+        assert_eq!(annotations[0].points, annotations[0].words[0].1);
+        assert_eq!(annotations[0].turns_left, END_GAME_TURN - 1);
+        assert!(annotations[1].words.is_empty());
+        let tile = replayed.get_board_backup().tiles[BOARD_SIZE - center - 1][center]
+            .clone()
+            .unwrap();
+        assert_eq!(tile.owner.id, first);
+        for (game_player, replayed_player) in game.get_players().iter().zip(replayed.get_players())
+        {
+            assert_eq!(game_player.get_tally(), replayed_player.get_tally());
+            let chars = |cards: Vec<crate::game::card::Card>| {
+                cards.iter().map(|card| card.char).collect::<String>()
+            };
+            assert_eq!(
+                chars(game_player.get_cards()),
+                chars(replayed_player.get_cards())
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn apply_with_move_out_of_turn_should_not_log_event() {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players);
+        let waiting = game.get_next_turn_player().unwrap().player.id;
+        assert!(game.apply(Event::Shuffle { player_id: waiting }).is_err());
+        assert!(game
+            .apply(Event::PlaceTile {
+                player_id: waiting,
+                card_index: 0,
+                x: 13,
+                y: 13,
+            })
+            .is_err());
+        assert!(game.get_events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn apply_place_tile_after_turn_deadline_should_be_turn_over(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players);
+        game.set_timeout_task(Arc::new(tokio::spawn(async {})), Instant::now());
+        let center = game.get_rules().board_size / 2;
+        let err = game
+            .apply(Event::PlaceTile {
+                player_id: game.get_player_in_this_turn().player.id,
+                card_index: 0,
+                x: center,
+                y: center,
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MoveViolation>(),
+            Some(&MoveViolation::TurnOver)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn apply_forfeit_on_turn_should_take_back_tiles_and_pass_turn(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let players = (0..3)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players);
+        let first = game.get_player_in_this_turn();
+        let next = game.get_next_turn_player().unwrap();
+        let center = game.get_rules().board_size / 2;
+        game.apply(Event::PlaceTile {
+            player_id: first.player.id,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        game.apply(Event::Forfeit {
+            player_id: first.player.id,
+        })?;
+        assert!(game.get_board().lock().unwrap().tiles[center][center].is_none());
+        assert_eq!(game.get_player_in_this_turn(), next);
+        assert!(!game.get_turn_queue().contains(&first));
+        assert_eq!(game.get_players().len(), 2);
+        assert_eq!(
+            game.get_withdrawn(),
+            vec![(first.clone(), Withdrawal::Forfeited)]
+        );
+        assert!(game
+            .apply(Event::Forfeit {
+                player_id: first.player.id,
+            })
+            .is_err());
+        #[cfg(debug_assertions)]
+        assert!(crate::game::invariant::check(&game).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn apply_shuffle_after_placing_tile_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        let center = game.get_rules().board_size / 2;
+        game.apply(Event::PlaceTile {
+            player_id: 0,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        assert!(game.apply(Event::Shuffle { player_id: 0 }).is_err());
+        #[cfg(debug_assertions)]
+        assert!(crate::game::invariant::check(&game).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn apply_end_turn_with_streak_multipliers_should_multiply_turns_in_a_row(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game = Game::with_rules(
+            0,
+            vec![Arc::new(Player::new(0, String::from("test")))],
+            GameRules {
+                streak_multipliers: true,
+                ..GameRules::default()
+            },
+        );
+        let center = game.get_rules().board_size / 2;
+        for x in [center, center + 1] {
+            game.apply(Event::PlaceTile {
+                player_id: 0,
+                card_index: 0,
+                x,
+                y: center,
+            })?;
+            game.apply(Event::EndTurn { keep_tiles: true })?;
+        }
+        let annotations = game.get_annotations();
+        assert_eq!(annotations[0].multiplier, 100);
+        assert_eq!(annotations[1].multiplier, 150);
+        let words = annotations[1]
+            .words
+            .iter()
+            .map(|(_, score)| score)
+            .sum::<u32>();
+        assert_eq!(annotations[1].points, words * 3 / 2);
+        assert_eq!(
+            game.get_player(0).unwrap().get_tally().score,
+            annotations[0].points + annotations[1].points
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn finish_scoring_with_territory_should_add_squares_covered(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::with_rules(
+            0,
+            players.clone(),
+            GameRules {
+                territory: true,
+                ..GameRules::default()
+            },
+        );
+        {
+            let board = game.get_board();
+            let mut board = board.lock().unwrap();
+            board.tiles[0][0] = Some(Tile::new('a', players[0].clone(), 0));
+            board.tiles[0][1] = Some(Tile::new('b', players[0].clone(), 0));
+            board.tiles[1][0] = Some(Tile::new('c', players[1].clone(), 1));
+        }
+        game.finish_scoring();
+        assert_eq!(
+            game.get_player(0).unwrap().get_tally().score,
+            2 * scoring::TERRITORY_POINTS
+        );
+        assert_eq!(
+            game.get_player(1).unwrap().get_tally().score,
+            scoring::TERRITORY_POINTS
+        );
+        Ok(())
+    }
+
+    /// Place the first card of whoever's turn it is in the middle of the
+    /// board as if it carried `power_up`, and end the turn keeping it.
+    fn play_power_up(game: &Game, power_up: PowerUp) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let center = game.get_rules().board_size / 2;
+        game.apply(Event::PlaceTile {
+            player_id: game.get_player_in_this_turn().player.id,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        {
+            let board = game.get_board();
+            let mut board = board.lock().unwrap();
+            let tile = board.tiles[BOARD_SIZE - center - 1][center]
+                .as_mut()
+                .unwrap();
+            tile.power_up = Some(power_up);
+        }
+        game.apply(Event::EndTurn { keep_tiles: true })?;
+        Ok(())
+    }
+
+    #[test]
+    fn apply_end_turn_with_double_word_should_double_points(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        play_power_up(&game, PowerUp::DoubleWord)?;
+        let annotation = &game.get_annotations()[0];
+        assert_eq!(annotation.power_ups, vec![PowerUp::DoubleWord]);
+        assert_eq!(annotation.multiplier, 200);
+        let words = annotation.words.iter().map(|(_, score)| score).sum::<u32>();
+        assert_eq!(annotation.points, words * 2);
+        Ok(())
+    }
+
+    #[test]
+    fn apply_end_turn_with_steal_letter_should_take_card_from_next_player(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players);
+        let thief = game.get_player_in_this_turn();
+        let victim = game.get_next_turn_player().unwrap();
+        let hand = victim.get_cards();
+        play_power_up(&game, PowerUp::StealLetter)?;
+        let stolen = thief.get_cards().last().unwrap().clone();
+        assert!(hand.contains(&stolen));
+        assert_eq!(victim.get_cards().len(), game.get_rules().hand_size);
+        #[cfg(debug_assertions)]
+        assert!(crate::game::invariant::check(&game).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn apply_end_turn_with_coop_should_share_score_and_end_after_rounds(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::with_rules(
+            0,
+            players,
+            GameRules {
+                coop: Some(Coop {
+                    target: 1000,
+                    rounds: 2,
+                }),
+                ..GameRules::default()
+            },
+        );
+        assert_eq!(game.get_last_turn(), 4);
+        let center = game.get_rules().board_size / 2;
+        game.apply(Event::PlaceTile {
+            player_id: game.get_player_in_this_turn().player.id,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        game.apply(Event::EndTurn { keep_tiles: true })?;
+        let scored = game
+            .get_players()
+            .iter()
+            .map(|game_player| game_player.get_tally().score)
+            .sum::<u32>();
+        assert!(scored > 0);
+        assert_eq!(game.get_shared_score(), scored);
+        for _ in 0..3 {
+            assert!(!game.is_over());
+            game.apply(Event::EndTurn { keep_tiles: false })?;
+        }
+        assert!(game.is_over());
+        let status = game.get_coop_status().unwrap();
+        assert_eq!(status.turns_left, 0);
+        assert!(!status.is_won());
+        Ok(())
+    }
+
+    #[test]
+    fn is_over_with_coop_target_reached_should_be_true() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let game = Game::with_rules(
+            0,
+            vec![Arc::new(Player::new(0, String::from("test")))],
+            GameRules {
+                coop: Some(Coop {
+                    target: 1,
+                    rounds: 5,
+                }),
+                ..GameRules::default()
+            },
+        );
+        let center = game.get_rules().board_size / 2;
+        game.apply(Event::PlaceTile {
+            player_id: 0,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        game.apply(Event::EndTurn { keep_tiles: true })?;
+        assert!(game.get_coop_status().unwrap().is_won());
+        assert!(game.is_over());
+        Ok(())
+    }
+
+    #[test]
+    fn apply_end_turn_should_only_replace_played_cards() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        let game_player = game.get_player(0).unwrap();
+        let chars = |cards: Vec<crate::game::card::Card>| {
+            cards.iter().map(|card| card.char).collect::<String>()
+        };
+        let hand = chars(game_player.get_cards());
+        let center = game.get_rules().board_size / 2;
+        let place = |x| Event::PlaceTile {
+            player_id: 0,
+            card_index: 0,
+            x,
+            y: center,
+        };
+        game.apply(place(center))?;
+        game.apply(Event::EndTurn { keep_tiles: false })?;
+        assert_eq!(chars(game_player.get_cards()), hand);
+        game.apply(place(center))?;
+        game.apply(Event::EndTurn { keep_tiles: true })?;
+        let refilled = chars(game_player.get_cards());
+        assert_eq!(refilled.len(), hand.len());
+        assert!(refilled.starts_with(&hand[1..]));
+        #[cfg(debug_assertions)]
+        assert!(crate::game::invariant::check(&game).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn apply_remove_tile_out_of_turn_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players);
+        let first = game.get_player_in_this_turn().player.id;
+        let waiting = game.get_next_turn_player().unwrap().player.id;
+        let center = game.get_rules().board_size / 2;
+        game.apply(Event::PlaceTile {
+            player_id: first,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        assert!(game
+            .apply(Event::RemoveTile {
+                player_id: waiting,
+                x: center,
+                y: center,
+            })
+            .is_err());
+        #[cfg(debug_assertions)]
+        assert!(crate::game::invariant::check(&game).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn take_board_changes_should_only_bump_version_when_squares_changed(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        let center = game.get_rules().board_size / 2;
+        let (version, changes) = game.take_board_changes();
+        assert_eq!(version, 0);
+        assert!(changes.is_empty());
+        game.apply(Event::PlaceTile {
+            player_id: 0,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        let (version, changes) = game.take_board_changes();
+        assert_eq!(version, 1);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].0, changes[0].1), (center, center));
+        assert!(changes[0].2.is_some());
+        assert_eq!(game.take_board_changes().0, 1);
+        game.apply(Event::RemoveTile {
+            player_id: 0,
+            x: center,
+            y: center,
+        })?;
+        let (version, changes) = game.take_board_changes();
+        assert_eq!(version, 2);
+        assert!(matches!(changes[..], [(x, y, None)] if x == center && y == center));
+        let (version, board) = game.get_sent_board();
+        assert_eq!(version, 2);
+        assert!(board.is_empty());
+        Ok(())
+    }
 }
@@ -2,14 +2,42 @@ use std::sync::Arc;
 
 use std::sync::Mutex;
 
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
 use crate::player::Player;
 
-use super::card::Card;
+use super::{
+    card::{Card, PowerUp},
+    snapshot::SeatSnapshot,
+};
+
+const VOWELS: [char; 5] = ['a', 'e', 'i', 'o', 'u'];
+/// One card in this many dealt in an arcade game carries a power-up.
+const POWER_UP_ODDS: u32 = 12;
+
+/// What a player has done so far in one game.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tally {
+    pub score: u32,
+    pub tiles_placed: u32,
+    /// Highest scoring word, with its score.
+    pub best_word: Option<(String, u32)>,
+    pub longest_word: Option<String>,
+    /// Turns in a row up to the last one that scored a word.
+    pub streak: u32,
+}
 
 #[derive(Debug)]
 pub struct GamePlayer {
     cards: Mutex<Vec<Card>>,
     has_shuffled: Mutex<bool>,
+    /// Hints left out of the game's budget.
+    hints_left: Mutex<u32>,
+    tally: Mutex<Tally>,
+    /// Turn timers that ran out on the player since they last ended a turn.
+    missed_turns: Mutex<u32>,
+    hand_size: usize,
     pub player: Arc<Player>,
 }
 
@@ -20,11 +48,65 @@ impl PartialEq for GamePlayer {
 }
 
 impl GamePlayer {
-    pub fn new(player: Arc<Player>) -> Self {
-        let cards = GamePlayer::generate_new_card();
+    pub fn new(
+        player: Arc<Player>,
+        hand_size: usize,
+        hint_budget: u32,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let cards = GamePlayer::generate_new_card(hand_size, rng);
         Self {
             cards: Mutex::new(cards),
             has_shuffled: Mutex::new(false),
+            hints_left: Mutex::new(hint_budget),
+            tally: Mutex::new(Tally::default()),
+            missed_turns: Mutex::new(0),
+            hand_size,
+            player,
+        }
+    }
+
+    /// Seat `player` with the hand and tally a saved game left them.
+    pub fn restore(player: Arc<Player>, hand_size: usize, seat: &SeatSnapshot) -> Self {
+        Self {
+            cards: Mutex::new(seat.cards.clone()),
+            has_shuffled: Mutex::new(seat.has_shuffled),
+            hints_left: Mutex::new(seat.hints_left),
+            tally: Mutex::new(seat.tally.clone()),
+            missed_turns: Mutex::new(0),
+            hand_size,
+            player,
+        }
+    }
+
+    /// Everything but who holds the seat. Cards placed this turn go back in
+    /// the hand, as the snapshot only keeps the board as of the last turn.
+    pub fn snapshot(&self) -> SeatSnapshot {
+        SeatSnapshot {
+            name: self.player.name.clone(),
+            cards: self
+                .get_cards()
+                .into_iter()
+                .map(|card| Card {
+                    used: false,
+                    ..card
+                })
+                .collect(),
+            has_shuffled: self.get_has_shuffled(),
+            hints_left: self.get_hints_left(),
+            tally: self.get_tally(),
+        }
+    }
+
+    /// The same seat held by `player` instead.
+    pub fn reseat(&self, player: Arc<Player>) -> Self {
+        Self {
+            cards: Mutex::new(self.get_cards()),
+            has_shuffled: Mutex::new(self.get_has_shuffled()),
+            hints_left: Mutex::new(self.get_hints_left()),
+            tally: Mutex::new(self.get_tally()),
+            missed_turns: Mutex::new(self.get_missed_turns()),
+            hand_size: self.hand_size,
             player,
         }
     }
@@ -37,35 +119,194 @@ impl GamePlayer {
         *self.has_shuffled.lock().unwrap()
     }
 
-    pub fn generate_new_card() -> Vec<Card> {
+    pub fn get_tally(&self) -> Tally {
+        self.tally.lock().unwrap().clone()
+    }
+
+    /// Add the words and tiles of a finished turn to the tally.
+    pub fn record_turn(&self, words: &[(String, u32)], tiles_placed: u32) {
+        let mut tally = self.tally.lock().unwrap();
+        tally.tiles_placed += tiles_placed;
+        tally.streak = match words.is_empty() {
+            true => 0,
+            false => tally.streak + 1,
+        };
+        for (word, score) in words {
+            tally.score += score;
+            if tally
+                .best_word
+                .as_ref()
+                .is_none_or(|(_, best)| score > best)
+            {
+                tally.best_word = Some((word.clone(), *score));
+            }
+            if tally
+                .longest_word
+                .as_ref()
+                .is_none_or(|longest| word.len() > longest.len())
+            {
+                tally.longest_word = Some(word.clone());
+            }
+        }
+    }
+
+    /// Add points scored on top of words, for a turn or at the end.
+    pub fn add_bonus(&self, points: u32) {
+        self.tally.lock().unwrap().score += points;
+    }
+
+    pub fn get_missed_turns(&self) -> u32 {
+        *self.missed_turns.lock().unwrap()
+    }
+
+    /// Count a turn timer that ran out on the player, returning how many
+    /// have in a row.
+    pub fn miss_turn(&self) -> u32 {
+        let mut missed_turns = self.missed_turns.lock().unwrap();
+        *missed_turns += 1;
+        *missed_turns
+    }
+
+    pub fn clear_missed_turns(&self) {
+        *self.missed_turns.lock().unwrap() = 0;
+    }
+
+    pub fn get_hints_left(&self) -> u32 {
+        *self.hints_left.lock().unwrap()
+    }
+
+    /// Spend one hint, returning how many are left, or `None` when the
+    /// budget is used up.
+    pub fn use_hint(&self) -> Option<u32> {
+        let mut hints_left = self.hints_left.lock().unwrap();
+        *hints_left = hints_left.checked_sub(1)?;
+        Some(*hints_left)
+    }
+
+    /// Deal a fresh hand, three eighths of it vowels.
+    pub fn generate_new_card(hand_size: usize, rng: &mut impl Rng) -> Vec<Card> {
+        let vowels = GamePlayer::vowel_share(hand_size);
+        GamePlayer::draw(vowels, hand_size - vowels, rng)
+    }
+
+    fn vowel_share(hand_size: usize) -> usize {
+        (hand_size * 3 / 8).max(1)
+    }
+
+    fn consonants() -> Vec<char> {
         let mut consonant = (b'a'..=b'z') // Start as u8
             .map(|c| c as char) // Convert all to chars
             .filter(|c| c.is_alphabetic()) // Filter only alphabetic chars
             .collect::<Vec<_>>();
-        consonant.retain(|consonant| {
-            *consonant != 'a'
-                && *consonant != 'e'
-                && *consonant != 'i'
-                && *consonant != 'o'
-                && *consonant != 'u'
-        });
-        let vowel = vec!['a', 'e', 'i', 'o', 'u'];
+        consonant.retain(|consonant| !VOWELS.contains(consonant));
+        consonant
+    }
+
+    /// Chance in thousandths that a card dealt into a fresh hand of
+    /// `hand_size` is each letter, in alphabetical order. Rounded, so they
+    /// may not add up to exactly a thousand.
+    pub fn deal_odds(hand_size: usize) -> Vec<(char, u32)> {
+        let hand_size = hand_size.max(1);
+        let vowels = GamePlayer::vowel_share(hand_size).min(hand_size);
+        let consonant = GamePlayer::consonants();
+        let odds = |count: usize, letters: usize| {
+            ((count * 1000) as f64 / (hand_size * letters) as f64).round() as u32
+        };
+        let mut letters = VOWELS
+            .iter()
+            .map(|vowel| (*vowel, odds(vowels, VOWELS.len())))
+            .chain(
+                consonant
+                    .iter()
+                    .map(|c| (*c, odds(hand_size - vowels, consonant.len()))),
+            )
+            .collect::<Vec<_>>();
+        letters.sort();
+        letters
+    }
+
+    fn draw(vowels: usize, consonants: usize, rng: &mut impl Rng) -> Vec<Card> {
+        let consonant = GamePlayer::consonants();
         let mut cards: Vec<Card> = Vec::new();
-        for _ in 0..3 {
-            cards.push(Card::new(
-                vowel[rand::random::<u8>() as usize % vowel.len()],
-            ));
+        for _ in 0..vowels {
+            cards.push(Card::new(VOWELS[rng.gen_range(0..VOWELS.len())]));
         }
-        for _ in 3..8 {
-            cards.push(Card::new(
-                consonant[rand::random::<u8>() as usize % consonant.len()],
-            ));
+        for _ in 0..consonants {
+            cards.push(Card::new(consonant[rng.gen_range(0..consonant.len())]));
         }
         cards
     }
 
-    pub fn get_new_card(&self) -> Vec<Card> {
-        *self.cards.lock().unwrap() = GamePlayer::generate_new_card();
+    /// Drop the cards played this turn and draw new ones in their place,
+    /// topping the vowels back up to a fresh hand's share. Returns the hand.
+    pub fn refill(&self, rng: &mut impl Rng) -> Vec<Card> {
+        let mut cards = self.cards.lock().unwrap();
+        cards.retain(|card| !card.used);
+        self.top_up(&mut cards, rng);
+        cards.clone()
+    }
+
+    /// Swap the cards at `indices` for new ones as `refill` would, which
+    /// counts as the turn's shuffle. Returns the hand, the new cards last.
+    pub fn exchange(&self, indices: &[usize], rng: &mut impl Rng) -> Vec<Card> {
+        let mut cards = self.cards.lock().unwrap();
+        let mut index = 0;
+        cards.retain(|_| {
+            index += 1;
+            !indices.contains(&(index - 1))
+        });
+        self.top_up(&mut cards, rng);
+        *self.has_shuffled.lock().unwrap() = true;
+        cards.clone()
+    }
+
+    fn top_up(&self, cards: &mut Vec<Card>, rng: &mut impl Rng) {
+        let missing = self.hand_size.saturating_sub(cards.len());
+        let vowels_held = cards
+            .iter()
+            .filter(|card| VOWELS.contains(&card.char))
+            .count();
+        let vowels = GamePlayer::vowel_share(self.hand_size)
+            .saturating_sub(vowels_held)
+            .min(missing);
+        cards.extend(GamePlayer::draw(vowels, missing - vowels, rng));
+    }
+
+    /// Give some of the cards from `from` on in the hand a power-up.
+    pub fn seed_power_ups(&self, from: usize, rng: &mut impl Rng) {
+        for card in self.cards.lock().unwrap().iter_mut().skip(from) {
+            if rng.gen_ratio(1, POWER_UP_ODDS) {
+                card.power_up = Some(PowerUp::ALL[rng.gen_range(0..PowerUp::ALL.len())]);
+            }
+        }
+    }
+
+    /// Take a random unused card out of the hand, leaving it a card short.
+    pub fn give_up_card(&self, rng: &mut impl Rng) -> Option<Card> {
+        let mut cards = self.cards.lock().unwrap();
+        let unused = (0..cards.len())
+            .filter(|i| !cards[*i].used)
+            .collect::<Vec<_>>();
+        if unused.is_empty() {
+            return None;
+        }
+        Some(cards.remove(unused[rng.gen_range(0..unused.len())]))
+    }
+
+    pub fn add_card(&self, card: Card) {
+        self.cards.lock().unwrap().push(card);
+    }
+
+    /// Take back every card played this turn, e.g. when its tiles come off
+    /// the board.
+    pub fn return_cards(&self) {
+        for card in self.cards.lock().unwrap().iter_mut() {
+            card.used = false;
+        }
+    }
+
+    pub fn get_new_card(&self, rng: &mut impl Rng) -> Vec<Card> {
+        *self.cards.lock().unwrap() = GamePlayer::generate_new_card(self.hand_size, rng);
         *self.has_shuffled.lock().unwrap() = true;
         self.cards.lock().unwrap().clone()
     }
@@ -80,17 +321,18 @@ impl GamePlayer {
 
     pub fn take_card(&self, index: usize) -> Card {
         let mut cards = self.cards.lock().unwrap();
-        let old_card = &cards[index];
-        let mut card = Card::new(old_card.char);
-        card.used = true;
+        let card = Card {
+            used: true,
+            ..cards[index].clone()
+        };
         cards[index] = card.clone();
         card
     }
 
-    pub fn return_cancel_card(&self, char: char) {
+    pub fn return_cancel_card(&self, char: char, power_up: Option<PowerUp>) {
         let mut cards = self.cards.lock().unwrap();
         for i in 0..cards.len() {
-            if cards[i].used && cards[i].char == char {
+            if cards[i].used && cards[i].char == char && cards[i].power_up == power_up {
                 cards[i].used = false;
                 break;
             }
@@ -0,0 +1,234 @@
+//! Consistency checks run after every game mutation in debug builds, so rules
+//! engine bugs show up in development and CI instead of as a desynced client.
+
+use std::fmt::Write;
+
+use super::{board::BOARD_SIZE, game::Game};
+
+/// Panic with a dump of the game if any invariant is violated.
+pub fn assert_invariants(game: &Game) {
+    if let Err(violations) = check(game) {
+        let dump = dump(game);
+        tracing::error!(
+            game_id = game.id,
+            "violated invariants:\n  {}\n{}",
+            violations.join("\n  "),
+            dump
+        );
+        panic!("game {} violated invariants: {:?}", game.id, violations);
+    }
+}
+
+pub fn check(game: &Game) -> Result<(), Vec<String>> {
+    let mut violations = Vec::new();
+    let turn = game.get_turns();
+    let players = game.get_players();
+    let turn_queue = game.get_turn_queue();
+
+    if turn == 0 || turn > game.get_last_turn() {
+        violations.push(format!("turn {} out of range", turn));
+    }
+    for game_player in &players {
+        let queued = turn_queue.iter().filter(|x| *x == game_player).count();
+        if queued != 1 {
+            violations.push(format!(
+                "player {} queued {} times",
+                game_player.player.id, queued
+            ));
+        }
+    }
+
+    let board = game.get_board().lock().unwrap().clone();
+    for game_player in &players {
+        let cards = game_player.get_cards();
+        if cards.len() != game.get_rules().hand_size {
+            violations.push(format!(
+                "player {} holds {} cards",
+                game_player.player.id,
+                cards.len()
+            ));
+        }
+        // Every card used this turn should be sitting on the board
+        let used = cards.iter().filter(|card| card.used).count();
+        let placed = board
+            .tiles
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|tile| tile.owner == game_player.player && tile.turn == turn)
+            .count();
+        if used != placed {
+            violations.push(format!(
+                "player {} used {} cards but placed {} tiles",
+                game_player.player.id, used, placed
+            ));
+        }
+    }
+    for tile in board.tiles.iter().flatten().flatten() {
+        if tile.turn > turn {
+            violations.push(format!(
+                "tile {} placed in future turn {}",
+                tile.char, tile.turn
+            ));
+        }
+    }
+
+    // Cards are drawn from an endless supply rather than a bag, so there are
+    // no dealt cards to count hands against. What can be counted is that
+    // every tile on the board was kept at the end of a logged turn, placed
+    // this turn, or already there when the game was restored.
+    let baseline = game.get_baseline();
+    let annotations = game.get_annotations();
+    let kept = annotations
+        .iter()
+        .map(|annotation| annotation.tiles_placed as usize)
+        .sum::<usize>();
+    let tiles = board.tiles.iter().flatten().flatten().collect::<Vec<_>>();
+    let placed = tiles.iter().filter(|tile| tile.turn == turn).count();
+    if tiles.len() != baseline.tiles + kept + placed {
+        violations.push(format!(
+            "board holds {} tiles but {} were kept and {} placed",
+            tiles.len(),
+            baseline.tiles + kept,
+            placed
+        ));
+    }
+    // Turns are all that score during play. The territory scored once the
+    // game is over isn't logged, but by then the turn is out of range anyway.
+    for game_player in &players {
+        let logged = annotations
+            .iter()
+            .filter(|annotation| annotation.player_id == game_player.player.id)
+            .map(|annotation| annotation.points)
+            .sum::<u32>();
+        let expected = baseline
+            .scores
+            .get(&game_player.player.name)
+            .copied()
+            .unwrap_or(0)
+            + logged;
+        let score = game_player.get_tally().score;
+        if score != expected {
+            violations.push(format!(
+                "player {} scored {} but their turns add up to {}",
+                game_player.player.id, score, expected
+            ));
+        }
+    }
+
+    match violations.is_empty() {
+        true => Ok(()),
+        false => Err(violations),
+    }
+}
+
+fn dump(game: &Game) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "turn: {}", game.get_turns());
+    let _ = writeln!(
+        out,
+        "queue: {:?}",
+        game.get_turn_queue()
+            .iter()
+            .map(|x| x.player.id)
+            .collect::<Vec<_>>()
+    );
+    for game_player in game.get_players() {
+        let hand = game_player
+            .get_cards()
+            .iter()
+            .map(|card| match card.used {
+                true => card.char.to_ascii_uppercase(),
+                false => card.char,
+            })
+            .collect::<String>();
+        let _ = writeln!(out, "player {}: {}", game_player.player.id, hand);
+    }
+    let board = game.get_board().lock().unwrap().clone();
+    for row in 0..BOARD_SIZE {
+        let line = board.tiles[row]
+            .iter()
+            .map(|tile| match tile {
+                Some(tile) => tile.char,
+                None => '.',
+            })
+            .collect::<String>();
+        let _ = writeln!(out, "{}", line);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, sync::Arc};
+
+    use super::*;
+    use crate::{
+        game::{snapshot::TileSnapshot, tile::Tile},
+        player::Player,
+    };
+
+    #[test]
+    fn check_with_new_game_should_be_ok() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game = Game::new(
+            0,
+            vec![
+                Arc::new(Player::new(0, String::from("test"))),
+                Arc::new(Player::new(1, String::from("test1"))),
+            ],
+        );
+        assert!(check(&game).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn check_with_tile_without_used_card_should_return_violation(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let player = Arc::new(Player::new(0, String::from("test")));
+        let game = Game::new(0, vec![player.clone()]);
+        game.get_board().lock().unwrap().tiles[0][0] = Some(Tile::new('a', player, 1));
+        assert_eq!(check(&game).unwrap_err().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn check_with_tile_from_unlogged_turn_should_return_violation(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let player = Arc::new(Player::new(0, String::from("test")));
+        let game = Game::new(0, vec![player.clone()]);
+        game.get_board().lock().unwrap().tiles[0][0] = Some(Tile::new('a', player, 0));
+        assert_eq!(check(&game).unwrap_err().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn check_with_score_off_the_log_should_return_violation(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        game.get_player(0)
+            .unwrap()
+            .record_turn(&[(String::from("a"), 5)], 0);
+        assert_eq!(check(&game).unwrap_err().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn check_with_restored_game_should_count_what_it_had(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        let mut snapshot = game.snapshot();
+        snapshot.tiles.push(TileSnapshot {
+            row: 0,
+            col: 0,
+            char: 'a',
+            owner: String::from("test"),
+            turn: 0,
+        });
+        snapshot.seats[0].tally.score = 5;
+        let restored = Game::restore(&snapshot, |name| {
+            Arc::new(Player::new(100, name.to_string()))
+        });
+        assert!(check(&restored).is_ok());
+        Ok(())
+    }
+}
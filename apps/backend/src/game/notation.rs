@@ -0,0 +1,232 @@
+use std::{collections::HashMap, fmt::Write};
+
+use super::{
+    event::Event,
+    replay::{FinishedReplay, MoveAnnotation, Placement},
+};
+
+/// How a finished game is written down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// A line per turn in plain words, for people to read.
+    Text,
+    /// Lines in the style of GCG, the notation crossword game tools trade
+    /// in. Hands aren't recorded, so moves leave out the rack.
+    Gcg,
+}
+
+/// Write the game `record` holds out turn by turn.
+pub fn write(record: &FinishedReplay, notation: Notation) -> String {
+    let name = |player_id: u32| {
+        record
+            .names
+            .get(&player_id)
+            .cloned()
+            .unwrap_or_else(|| format!("player{}", player_id))
+    };
+    // GCG nicknames end at the first space
+    let nickname = |player_id: u32| name(player_id).replace(char::is_whitespace, "_");
+    let mut out = String::new();
+    match notation {
+        Notation::Text => {
+            let _ = writeln!(out, "Game {}", record.replay.game_id);
+            let players = record.replay.seats.iter().map(|id| name(*id));
+            let _ = writeln!(out, "Players: {}", players.collect::<Vec<_>>().join(", "));
+            out.push('\n');
+        }
+        Notation::Gcg => {
+            let _ = writeln!(out, "#character-encoding UTF-8");
+            for (i, id) in record.replay.seats.iter().enumerate() {
+                let _ = writeln!(out, "#player{} {} {}", i + 1, nickname(*id), name(*id));
+            }
+            let _ = writeln!(out, "#title Game {}", record.replay.game_id);
+        }
+    }
+    let mut totals = HashMap::<u32, u32>::new();
+    let mut annotations = record.annotations.iter();
+    for event in &record.events {
+        let (player_id, withdrawal) = match event {
+            Event::EndTurn { .. } => {
+                let annotation = match annotations.next() {
+                    Some(annotation) => annotation,
+                    None => break,
+                };
+                let total = totals.entry(annotation.player_id).or_default();
+                *total += annotation.points;
+                let line = match notation {
+                    Notation::Text => text_move(&name(annotation.player_id), annotation, *total),
+                    Notation::Gcg => gcg_move(&nickname(annotation.player_id), annotation, *total),
+                };
+                out.push_str(&line);
+                out.push('\n');
+                continue;
+            }
+            Event::Forfeit { player_id } => (*player_id, "forfeited"),
+            Event::Resign { player_id } => (*player_id, "resigned"),
+            _ => continue,
+        };
+        let _ = match notation {
+            Notation::Text => writeln!(out, "{} {}", name(player_id), withdrawal),
+            Notation::Gcg => writeln!(out, "#note {} {}", nickname(player_id), withdrawal),
+        };
+    }
+    if notation == Notation::Text {
+        let scores = record
+            .replay
+            .seats
+            .iter()
+            .map(|id| format!("{} {}", name(*id), totals.get(id).copied().unwrap_or(0)));
+        let _ = write!(
+            out,
+            "\nFinal scores: {}\n",
+            scores.collect::<Vec<_>>().join(", ")
+        );
+    }
+    out
+}
+
+fn text_move(name: &str, annotation: &MoveAnnotation, total: u32) -> String {
+    let placement = match &annotation.placement {
+        Some(placement) => placement,
+        None => return format!("Turn {}: {} passed, {} total", annotation.turn, name, total),
+    };
+    let words = annotation
+        .words
+        .iter()
+        .map(|(word, score)| format!("{} {}", word.to_uppercase(), score))
+        .collect::<Vec<_>>();
+    let mut points = annotation.points.to_string();
+    if annotation.bingo_bonus > 0 {
+        let _ = write!(points, " with a {} point bingo", annotation.bingo_bonus);
+    }
+    if annotation.multiplier != 100 {
+        let _ = write!(points, " on a {}% streak", annotation.multiplier);
+    }
+    format!(
+        "Turn {}: {} played {} at {} ({}) for {}, {} total",
+        annotation.turn,
+        name,
+        placement.word.to_uppercase(),
+        coordinate(placement),
+        words.join(", "),
+        points,
+        total
+    )
+}
+
+fn gcg_move(nickname: &str, annotation: &MoveAnnotation, total: u32) -> String {
+    match &annotation.placement {
+        Some(placement) => format!(
+            ">{}: {} {} +{} {}",
+            nickname,
+            coordinate(placement),
+            placement.word.to_uppercase(),
+            annotation.points,
+            total
+        ),
+        None => format!(">{}: - +0 {}", nickname, total),
+    }
+}
+
+/// Row number then column letter for words across, the other way around for
+/// words down.
+fn coordinate(placement: &Placement) -> String {
+    let col = (b'A' + placement.col as u8) as char;
+    match placement.across {
+        true => format!("{}{}", placement.row + 1, col),
+        false => format!("{}{}", col, placement.row + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, sync::Arc};
+
+    use crate::{
+        game::{board::BOARD_SIZE, game::Game},
+        player::Player,
+    };
+
+    use super::*;
+
+    fn finished_game() -> Result<(Game, u32, u32), Box<dyn Error + Send + Sync>> {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test {}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(7, players);
+        let first = game.get_player_in_this_turn().player.id;
+        let center = game.get_rules().board_size / 2;
+        game.apply(Event::PlaceTile {
+            player_id: first,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        game.apply(Event::EndTurn { keep_tiles: true })?;
+        let second = game.get_player_in_this_turn().player.id;
+        game.apply(Event::EndTurn { keep_tiles: false })?;
+        game.apply(Event::Resign { player_id: second })?;
+        Ok((game, first, second))
+    }
+
+    #[test]
+    fn write_gcg_should_list_moves_and_withdrawals() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (game, first, second) = finished_game()?;
+        let record = game.finished_replay();
+        let annotation = &record.annotations[0];
+        let placement = annotation.placement.clone().unwrap();
+        assert_eq!(
+            placement.row,
+            BOARD_SIZE - game.get_rules().board_size / 2 - 1
+        );
+        let lines = write(&record, Notation::Gcg)
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        assert_eq!(lines[0], "#character-encoding UTF-8");
+        let seats = &record.replay.seats;
+        assert_eq!(
+            lines[1],
+            format!("#player1 test_{} test {}", seats[0], seats[0])
+        );
+        assert_eq!(lines[3], "#title Game 7");
+        assert_eq!(
+            lines[4],
+            format!(
+                ">test_{}: {} {} +{} {}",
+                first,
+                coordinate(&placement),
+                placement.word.to_uppercase(),
+                annotation.points,
+                annotation.points
+            )
+        );
+        assert_eq!(lines[5], format!(">test_{}: - +0 0", second));
+        assert_eq!(lines[6], format!("#note test_{} resigned", second));
+        Ok(())
+    }
+
+    #[test]
+    fn write_text_should_end_with_final_scores() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (game, first, second) = finished_game()?;
+        let record = game.finished_replay();
+        let text = write(&record, Notation::Text);
+        let points = record.annotations[0].points;
+        let seats = &record.replay.seats;
+        assert!(text.starts_with(&format!(
+            "Game 7\nPlayers: test {}, test {}\n\n",
+            seats[0], seats[1]
+        )));
+        assert!(text.contains(&format!("Turn 2: test {} passed, 0 total\n", second)));
+        assert!(text.contains(&format!("test {} resigned\n", second)));
+        let score = |id: u32| if id == first { points } else { 0 };
+        assert!(text.ends_with(&format!(
+            "\nFinal scores: test {} {}, test {} {}\n",
+            seats[0],
+            score(seats[0]),
+            seats[1],
+            score(seats[1])
+        )));
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    board::{Board, BOARD_SIZE},
+    card::PowerUp,
+    event::Event,
+    rules::GameRules,
+};
+
+/// What a game can be played again from. The seed decides the seating, the
+/// board and every hand dealt, so the same record always sets up the same
+/// game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    pub game_id: u32,
+    pub seed: u64,
+    pub rules: GameRules,
+    /// Ids of the players the game started with, in seat order.
+    pub seats: Vec<u32>,
+}
+
+/// What a turn came to, worked out when it ended so whoever shows a replay
+/// doesn't have to score the board again. There is one for every
+/// `Event::EndTurn` in the log, in the same order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveAnnotation {
+    pub turn: u32,
+    pub player_id: u32,
+    /// Every word the turn formed, with its score. Empty when the turn's
+    /// tiles were taken back.
+    pub words: Vec<(String, u32)>,
+    /// The words' scores with the bonuses below added.
+    pub points: u32,
+    /// Part of `points` for playing the whole hand.
+    pub bingo_bonus: u32,
+    /// Percent `points` were multiplied by for a streak and double word
+    /// power-ups, 100 for none.
+    pub multiplier: u32,
+    pub tiles_placed: u32,
+    /// Power-ups of the tiles the turn kept on the board.
+    pub power_ups: Vec<PowerUp>,
+    /// Where the turn's tiles went, `None` when none stayed on the board.
+    pub placement: Option<Placement>,
+    /// Turns still to be played. Hands are dealt fresh rather than drawn
+    /// from a bag, so this is how close the game is to running out.
+    pub turns_left: u32,
+}
+
+/// The word a turn's tiles were laid along, including letters already on the
+/// board it runs through.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Placement {
+    pub word: String,
+    /// Row and column of its first letter, counted from the top left.
+    pub row: usize,
+    pub col: usize,
+    pub across: bool,
+}
+
+impl Placement {
+    /// Find the word the tiles placed on `turn` lie along. A lone tile lies
+    /// across unless it only joins a word running down.
+    pub fn on(board: &Board, turn: u32) -> Option<Placement> {
+        let placed = (0..BOARD_SIZE)
+            .flat_map(|row| (0..BOARD_SIZE).map(move |col| (row, col)))
+            .filter(|(row, col)| {
+                board.tiles[*row][*col]
+                    .as_ref()
+                    .is_some_and(|tile| tile.turn == turn)
+            })
+            .collect::<Vec<_>>();
+        let (row, col) = *placed.first()?;
+        let occupied = |row: usize, col: usize| board.tiles[row][col].is_some();
+        let across = match placed.len() {
+            1 => {
+                let joins_across = (col > 0 && occupied(row, col - 1))
+                    || (col + 1 < BOARD_SIZE && occupied(row, col + 1));
+                let joins_down = (row > 0 && occupied(row - 1, col))
+                    || (row + 1 < BOARD_SIZE && occupied(row + 1, col));
+                joins_across || !joins_down
+            }
+            _ => placed.iter().all(|(other_row, _)| *other_row == row),
+        };
+        let step = |(row, col): (usize, usize), forward: bool| match (across, forward) {
+            (true, true) => (col + 1 < BOARD_SIZE).then(|| (row, col + 1)),
+            (true, false) => col.checked_sub(1).map(|col| (row, col)),
+            (false, true) => (row + 1 < BOARD_SIZE).then(|| (row + 1, col)),
+            (false, false) => row.checked_sub(1).map(|row| (row, col)),
+        };
+        let mut start = (row, col);
+        while let Some(prev) = step(start, false).filter(|(row, col)| occupied(*row, *col)) {
+            start = prev;
+        }
+        let mut word = String::new();
+        let mut cell = Some(start);
+        while let Some((row, col)) = cell {
+            match &board.tiles[row][col] {
+                Some(tile) => word.push(tile.char),
+                None => break,
+            }
+            cell = step((row, col), true);
+        }
+        Some(Placement {
+            word,
+            row: start.0,
+            col: start.1,
+            across,
+        })
+    }
+}
+
+/// A finished game's record, kept once the game itself is gone so it can
+/// still be exported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinishedReplay {
+    pub replay: Replay,
+    /// Name of everyone who played, by player id.
+    pub names: HashMap<u32, String>,
+    pub events: Vec<Event>,
+    pub annotations: Vec<MoveAnnotation>,
+}
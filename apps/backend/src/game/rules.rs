@@ -0,0 +1,183 @@
+use std::{error::Error, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::board::{BoardLayout, BOARD_SIZE, CLASSIC_BOARD_SIZE};
+
+pub const MIN_BOARD_SIZE: usize = 5;
+pub const MIN_HAND_SIZE: usize = 5;
+pub const MAX_HAND_SIZE: usize = 10;
+pub const MIN_TURN_DURATION: Duration = Duration::from_secs(5);
+pub const MAX_TURN_DURATION: Duration = Duration::from_secs(300);
+pub const DEFAULT_LANGUAGE: &str = "en";
+pub const MAX_COOP_ROUNDS: u32 = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scoring {
+    #[default]
+    Classic,
+    WordLength,
+}
+
+/// Who takes the first turn, with everyone else following in seat order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TurnOrder {
+    #[default]
+    Random,
+    /// Seated in the order they joined the lobby, the leader first.
+    JoinOrder,
+    /// Everyone draws a letter, the one closest to 'A' goes first and ties
+    /// draw again. The rest follow in join order.
+    DrawTiles,
+}
+
+/// Everyone plays for one shared score, trying to reach `target` before
+/// `rounds` rounds of turns are up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Coop {
+    pub target: u32,
+    pub rounds: u32,
+}
+
+/// Rules picked by the lobby leader, fixed once the game starts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRules {
+    /// Tiles may only be placed where both coordinates are below this.
+    pub board_size: usize,
+    pub turn_duration: Duration,
+    pub hand_size: usize,
+    /// Key of the dictionary words are checked against.
+    pub language: String,
+    pub scoring: Scoring,
+    /// Where the premium squares are.
+    pub layout: BoardLayout,
+    pub turn_order: TurnOrder,
+    /// Multiply the points of scoring turns in a row, up to double.
+    pub streak_multipliers: bool,
+    /// Add points for the board area each player's tiles cover when the
+    /// game ends.
+    pub territory: bool,
+    /// Arcade mode: some cards dealt carry a power-up.
+    pub power_ups: bool,
+    /// Play together against a target instead of against each other.
+    pub coop: Option<Coop>,
+    /// Turn free text chat off, leaving only quick chat.
+    pub quick_chat_only: bool,
+    /// Tell players the odds each letter is dealt with, not just the turns
+    /// left.
+    pub letter_odds: bool,
+    /// Hints each player may ask for over the whole game.
+    pub hint_budget: u32,
+    /// Turn timers a player can let run out in a row before they forfeit, 0
+    /// for never.
+    pub forfeit_after_missed_turns: u32,
+    /// How long a seat waits for its player to reconnect before they forfeit.
+    pub disconnect_grace: Duration,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            board_size: BOARD_SIZE,
+            turn_duration: Duration::from_secs(30),
+            hand_size: 8,
+            language: String::from(DEFAULT_LANGUAGE),
+            scoring: Scoring::default(),
+            layout: BoardLayout::default(),
+            turn_order: TurnOrder::default(),
+            streak_multipliers: false,
+            territory: false,
+            power_ups: false,
+            coop: None,
+            quick_chat_only: false,
+            letter_odds: false,
+            hint_budget: 3,
+            forfeit_after_missed_turns: 3,
+            disconnect_grace: Duration::from_secs(120),
+        }
+    }
+}
+
+impl GameRules {
+    pub fn validate(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if !(MIN_BOARD_SIZE..=BOARD_SIZE).contains(&self.board_size) {
+            return Err("Invalid board size".into());
+        }
+        if self.layout == BoardLayout::Classic && self.board_size != CLASSIC_BOARD_SIZE {
+            return Err("Classic layout needs a 15x15 board".into());
+        }
+        if !(MIN_TURN_DURATION..=MAX_TURN_DURATION).contains(&self.turn_duration) {
+            return Err("Invalid turn duration".into());
+        }
+        if !(MIN_HAND_SIZE..=MAX_HAND_SIZE).contains(&self.hand_size) {
+            return Err("Invalid hand size".into());
+        }
+        if let Some(coop) = self.coop {
+            if coop.target == 0 {
+                return Err("Invalid co-op target".into());
+            }
+            if !(1..=MAX_COOP_ROUNDS).contains(&coop.rounds) {
+                return Err("Invalid co-op rounds".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_with_default_rules_should_be_ok() {
+        assert!(GameRules::default().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_with_board_larger_than_grid_should_return_error() {
+        let rules = GameRules {
+            board_size: BOARD_SIZE + 1,
+            ..GameRules::default()
+        };
+        assert!(rules.validate().is_err());
+    }
+
+    #[test]
+    fn validate_with_hand_size_out_of_range_should_return_error() {
+        for hand_size in [MIN_HAND_SIZE - 1, MAX_HAND_SIZE + 1] {
+            let rules = GameRules {
+                hand_size,
+                ..GameRules::default()
+            };
+            assert!(rules.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn validate_with_coop_rounds_out_of_range_should_return_error() {
+        for rounds in [0, MAX_COOP_ROUNDS + 1] {
+            let rules = GameRules {
+                coop: Some(Coop {
+                    target: 100,
+                    rounds,
+                }),
+                ..GameRules::default()
+            };
+            assert!(rules.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn validate_with_classic_layout_on_default_board_should_return_error() {
+        let rules = GameRules {
+            layout: BoardLayout::Classic,
+            ..GameRules::default()
+        };
+        assert!(rules.validate().is_err());
+        let rules = GameRules {
+            board_size: CLASSIC_BOARD_SIZE,
+            ..rules
+        };
+        assert!(rules.validate().is_ok());
+    }
+}
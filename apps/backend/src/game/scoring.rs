@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use super::{
+    board::{Board, Premium, BOARD_SIZE},
+    rules::Scoring,
+};
+
+/// Extra points for playing a whole hand in one turn.
+pub const BINGO_BONUS: u32 = 50;
+/// Points for each square a player's tiles cover at the end of a territory
+/// game.
+pub const TERRITORY_POINTS: u32 = 2;
+/// Percent a streak multiplier grows by with each scoring turn in a row.
+const STREAK_STEP: u32 = 50;
+/// Scoring turns in a row past which the multiplier stops growing.
+const MAX_STREAK: u32 = 3;
+
+/// What a turn's words come to once bonuses are added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurnScore {
+    pub points: u32,
+    /// Part of `points` from the bingo bonus.
+    pub bingo_bonus: u32,
+    /// Percent the turn's points were multiplied by, 100 for none.
+    pub multiplier: u32,
+}
+
+/// Total `words` for a turn that placed `tiles_placed` of a `hand_size`
+/// hand, as the `streak`th scoring turn in a row. Streaks only count with
+/// `streak_multipliers` on.
+pub fn score_bonuses(
+    words: &[(String, u32)],
+    tiles_placed: u32,
+    hand_size: usize,
+    streak: u32,
+    streak_multipliers: bool,
+) -> TurnScore {
+    let bingo_bonus = match tiles_placed as usize == hand_size && !words.is_empty() {
+        true => BINGO_BONUS,
+        false => 0,
+    };
+    let multiplier = match streak_multipliers {
+        true => 100 + STREAK_STEP * (streak.clamp(1, MAX_STREAK) - 1),
+        false => 100,
+    };
+    let points = words.iter().map(|(_, score)| score).sum::<u32>() + bingo_bonus;
+    TurnScore {
+        points: points * multiplier / 100,
+        bingo_bonus,
+        multiplier,
+    }
+}
+
+/// Face value of a letter, as on classic crossword tiles.
+pub fn letter_value(char: char) -> u32 {
+    match char.to_ascii_lowercase() {
+        'a' | 'e' | 'i' | 'l' | 'n' | 'o' | 'r' | 's' | 't' | 'u' => 1,
+        'd' | 'g' => 2,
+        'b' | 'c' | 'm' | 'p' => 3,
+        'f' | 'h' | 'v' | 'w' | 'y' => 4,
+        'k' => 5,
+        'j' | 'x' => 8,
+        'q' | 'z' => 10,
+        _ => 0,
+    }
+}
+
+/// Score one word. Each letter comes with the premium under it, which only
+/// counts for tiles placed this turn, so callers pass `None` for the rest.
+pub fn score_word(scoring: Scoring, letters: &[(char, Option<Premium>)]) -> u32 {
+    let mut word_multiplier = 1;
+    let mut score = 0;
+    for (char, premium) in letters {
+        let letter_multiplier = match premium {
+            Some(Premium::DoubleLetter) => 2,
+            Some(Premium::TripleLetter) => 3,
+            _ => 1,
+        };
+        word_multiplier *= match premium {
+            Some(Premium::DoubleWord) => 2,
+            Some(Premium::TripleWord) => 3,
+            _ => 1,
+        };
+        score += match scoring {
+            Scoring::Classic => letter_value(*char) * letter_multiplier,
+            Scoring::WordLength => 1,
+        };
+    }
+    score * word_multiplier
+}
+
+/// Territory points for the squares each player's tiles cover, by name so
+/// tiles placed by a stand-in count for whoever took the seat back.
+pub fn score_territory(board: &Board) -> HashMap<String, u32> {
+    let mut territory = HashMap::new();
+    for tile in board.tiles.iter().flatten().flatten() {
+        *territory.entry(tile.owner.name.clone()).or_default() += TERRITORY_POINTS;
+    }
+    territory
+}
+
+/// Every word holding a tile placed on `turn`, with its score. Words run
+/// across or down and are at least two letters long, except a lone tile
+/// which scores as a word of its own.
+pub fn score_turn(board: &Board, turn: u32, scoring: Scoring) -> Vec<(String, u32)> {
+    let cell = |row: usize, col: usize| {
+        board.tiles[row][col].as_ref().map(|tile| {
+            let premium = match tile.turn == turn {
+                true => board.get_premium(col, BOARD_SIZE - 1 - row),
+                false => None,
+            };
+            (tile.char, premium, tile.turn == turn)
+        })
+    };
+    let mut words = Vec::new();
+    let mut new_tiles = 0;
+    for across in [true, false] {
+        for line in 0..BOARD_SIZE {
+            let mut run = Vec::new();
+            for i in 0..=BOARD_SIZE {
+                let tile = match i < BOARD_SIZE {
+                    true if across => cell(line, i),
+                    true => cell(i, line),
+                    false => None,
+                };
+                match tile {
+                    Some(tile) => {
+                        if across && tile.2 {
+                            new_tiles += 1;
+                        }
+                        run.push(tile);
+                    }
+                    None => {
+                        if run.len() > 1 && run.iter().any(|tile| tile.2) {
+                            words.push(run.clone());
+                        }
+                        run.clear();
+                    }
+                }
+            }
+        }
+    }
+    if words.is_empty() && new_tiles == 1 {
+        for (row, col) in (0..BOARD_SIZE).flat_map(|row| (0..BOARD_SIZE).map(move |col| (row, col)))
+        {
+            if let Some(tile) = cell(row, col).filter(|tile| tile.2) {
+                words.push(vec![tile]);
+            }
+        }
+    }
+    words
+        .into_iter()
+        .map(|run| {
+            let letters = run
+                .iter()
+                .map(|(char, premium, _)| (*char, *premium))
+                .collect::<Vec<_>>();
+            (
+                run.iter().map(|(char, _, _)| char).collect(),
+                score_word(scoring, &letters),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{game::tile::Tile, player::Player};
+
+    #[test]
+    fn score_bonuses_should_add_bingo_then_multiply_streaks() {
+        let words = [(String::from("quiz"), 22), (String::from("it"), 2)];
+        let plain = score_bonuses(&words, 4, 8, 5, false);
+        assert_eq!(
+            (plain.points, plain.bingo_bonus, plain.multiplier),
+            (24, 0, 100)
+        );
+        let bingo = score_bonuses(&words, 8, 8, 1, true);
+        assert_eq!(
+            (bingo.points, bingo.bingo_bonus, bingo.multiplier),
+            (74, 50, 100)
+        );
+        assert_eq!(score_bonuses(&words, 4, 8, 2, true).points, 36);
+        assert_eq!(score_bonuses(&words, 4, 8, 9, true).multiplier, 200);
+        assert_eq!(score_bonuses(&[], 8, 8, 0, true).points, 0);
+    }
+
+    #[test]
+    fn score_word_with_classic_scoring_should_apply_premiums() {
+        let letters = [
+            ('q', Some(Premium::TripleLetter)),
+            ('i', None),
+            ('t', Some(Premium::DoubleWord)),
+        ];
+        assert_eq!(score_word(Scoring::Classic, &letters), (30 + 1 + 1) * 2);
+    }
+
+    #[test]
+    fn score_word_with_word_length_scoring_should_ignore_letter_premiums() {
+        let letters = [
+            ('q', Some(Premium::TripleLetter)),
+            ('i', None),
+            ('t', Some(Premium::DoubleWord)),
+        ];
+        assert_eq!(score_word(Scoring::WordLength, &letters), 6);
+    }
+
+    #[test]
+    fn score_turn_should_only_score_words_with_new_tiles() {
+        let player = Arc::new(Player::new(0, String::from("test")));
+        let mut board = Board::new();
+        for (col, char) in "the".chars().enumerate() {
+            board.tiles[0][col] = Some(Tile::new(char, player.clone(), 1));
+        }
+        board.tiles[1][0] = Some(Tile::new('o', player.clone(), 2));
+        board.tiles[5][5] = Some(Tile::new('x', player, 1));
+        assert_eq!(
+            score_turn(&board, 2, Scoring::Classic),
+            vec![(String::from("to"), 2)]
+        );
+    }
+}
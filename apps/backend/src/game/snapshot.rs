@@ -0,0 +1,52 @@
+use std::{collections::HashSet, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use super::{board::Premium, card::Card, game::GameMode, game_player::Tally, rules::GameRules};
+
+/// An unfinished game as of its last finished turn, enough to carry it over
+/// a restart. Players are kept by name since their ids don't outlive the
+/// connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub game_id: u32,
+    pub mode: GameMode,
+    pub rules: GameRules,
+    pub seed: u64,
+    /// How far into the seed's stream the game has drawn.
+    pub rng_word_pos: u128,
+    pub custom_wordlist: Option<Arc<HashSet<String>>>,
+    pub turn: u32,
+    /// The turn the game ends after.
+    pub last_turn: u32,
+    /// Points scored by everyone together, including players who left.
+    pub shared_score: u32,
+    /// Players still in the game, in seat order.
+    pub seats: Vec<SeatSnapshot>,
+    /// Index into `seats` of the player whose turn it is.
+    pub current_seat: usize,
+    pub tiles: Vec<TileSnapshot>,
+    /// `(x, y, premium)` for every premium square.
+    pub premiums: Vec<(usize, usize, Premium)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeatSnapshot {
+    pub name: String,
+    /// The hand before any of it was placed this turn.
+    pub cards: Vec<Card>,
+    pub has_shuffled: bool,
+    pub hints_left: u32,
+    pub tally: Tally,
+}
+
+/// A tile on the board, by its index in `Board::tiles`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileSnapshot {
+    pub row: usize,
+    pub col: usize,
+    pub char: char,
+    /// Name of the player who placed it.
+    pub owner: String,
+    pub turn: u32,
+}
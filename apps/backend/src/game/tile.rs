@@ -2,15 +2,34 @@ use std::sync::Arc;
 
 use crate::player::Player;
 
+use super::card::PowerUp;
+
 #[derive(Debug, Clone)]
 pub struct Tile {
     pub char: char,
     pub owner: Arc<Player>,
     pub turn: u32,
+    /// The power-up of the card it was placed from.
+    pub power_up: Option<PowerUp>,
 }
 
 impl Tile {
     pub fn new(char: char, owner: Arc<Player>, turn: u32) -> Self {
-        Self { char, owner, turn }
+        Self {
+            char,
+            owner,
+            turn,
+            power_up: None,
+        }
+    }
+
+    pub fn with_power_up(mut self, power_up: Option<PowerUp>) -> Self {
+        self.power_up = power_up;
+        self
+    }
+
+    /// Whether clients would see the two as the same tile.
+    pub fn looks_like(&self, other: &Tile) -> bool {
+        self.char == other.char && self.owner.id == other.owner.id
     }
 }
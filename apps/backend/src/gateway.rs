@@ -0,0 +1,217 @@
+//! Read-only gRPC queries over the services the game protocol uses, for
+//! companion apps and the website. Callers aren't players, so nothing here
+//! needs a connection or changes server state.
+
+use std::{error::Error, sync::Arc};
+
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+use crate::{
+    game::game::current_day,
+    model::{
+        gateway::{
+            gateway_server::{self, GatewayServer},
+            GameSummary, GetGameRequest, GetGameResponse, ListGamesRequest, ListGamesResponse,
+            ListLobbiesRequest,
+        },
+        leaderboard::daily::{DailyLeaderboardRequest, DailyLeaderboardResponse, LeaderboardEntry},
+        lobby::list::{ListResponse, LobbyInfos},
+        player::stats::{GetPlayerStatsRequest, GetPlayerStatsResponse, PlayerStats},
+    },
+    service::{
+        game_service::GameService, lobby_service::LobbyService, player_service::PlayerService,
+    },
+};
+
+#[derive(Debug)]
+pub struct Gateway {
+    player_service: Arc<PlayerService>,
+    lobby_service: Arc<LobbyService>,
+    game_service: Arc<GameService>,
+}
+
+impl Gateway {
+    pub fn new(
+        player_service: Arc<PlayerService>,
+        lobby_service: Arc<LobbyService>,
+        game_service: Arc<GameService>,
+    ) -> Self {
+        Self {
+            player_service,
+            lobby_service,
+            game_service,
+        }
+    }
+
+    /// Answer gRPC calls on `listener` until it fails.
+    pub async fn serve(
+        self: Arc<Self>,
+        listener: TcpListener,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        info!(addr = %listener.local_addr()?, "grpc gateway listening");
+        tonic::transport::Server::builder()
+            .add_service(GatewayServer::from_arc(self))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await?;
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl gateway_server::Gateway for Gateway {
+    async fn list_lobbies(
+        &self,
+        _: Request<ListLobbiesRequest>,
+    ) -> Result<Response<ListResponse>, Status> {
+        Ok(Response::new(ListResponse {
+            success: true,
+            lobby_infos: Some(LobbyInfos::from(self.lobby_service.get_lobbies())),
+        }))
+    }
+
+    async fn list_games(
+        &self,
+        _: Request<ListGamesRequest>,
+    ) -> Result<Response<ListGamesResponse>, Status> {
+        let mut games = self
+            .game_service
+            .get_gamees()
+            .iter()
+            .map(|game| GameSummary::from(game.as_ref()))
+            .collect::<Vec<_>>();
+        games.sort_by_key(|game| game.id);
+        Ok(Response::new(ListGamesResponse { games }))
+    }
+
+    async fn get_game(
+        &self,
+        req: Request<GetGameRequest>,
+    ) -> Result<Response<GetGameResponse>, Status> {
+        match self.game_service.get_game(req.get_ref().game_id) {
+            Some(game) => Ok(Response::new(GetGameResponse::from(game.as_ref()))),
+            None => Err(Status::not_found("Game not found")),
+        }
+    }
+
+    async fn get_daily_leaderboard(
+        &self,
+        req: Request<DailyLeaderboardRequest>,
+    ) -> Result<Response<DailyLeaderboardResponse>, Status> {
+        let day = req.get_ref().day.unwrap_or_else(current_day);
+        let scores = self
+            .game_service
+            .get_leaderboard_service()
+            .get_daily(day)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(DailyLeaderboardResponse {
+            success: true,
+            day,
+            entries: LeaderboardEntry::ranked(&scores),
+        }))
+    }
+
+    async fn get_player_stats(
+        &self,
+        req: Request<GetPlayerStatsRequest>,
+    ) -> Result<Response<GetPlayerStatsResponse>, Status> {
+        // Stats belong to the account, which is the name of a connected
+        // player until players can sign in
+        let player = match self.player_service.get_player(req.get_ref().player_id) {
+            Some(player) => player,
+            None => return Err(Status::not_found("Player not found")),
+        };
+        let stats = self
+            .game_service
+            .get_stats_service()
+            .get_stats(&player.name)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(GetPlayerStatsResponse {
+            success: true,
+            stats: Some(PlayerStats::new(player.name.clone(), &stats)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use tonic::Code;
+
+    use super::*;
+    use crate::{
+        lobby::lobby::Lobby,
+        model::gateway::{gateway_client::GatewayClient, gateway_server::Gateway as _},
+        player::Player,
+    };
+
+    fn new_gateway() -> Gateway {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        Gateway::new(
+            Arc::new(PlayerService::new(
+                lobby_service.clone(),
+                game_service.clone(),
+            )),
+            lobby_service,
+            game_service,
+        )
+    }
+
+    #[tokio::test]
+    async fn get_game_with_running_game_should_return_board_and_scores(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let gateway = new_gateway();
+        let player = Arc::new(Player::new(0, String::from("test")));
+        let lobby = Arc::new(Lobby::new(0, 4, player.clone()));
+        lobby.get_player(0).unwrap().set_ready(true);
+        let game = GameService::start_game(gateway.game_service.clone(), player, lobby)?;
+        let res = gateway
+            .get_game(Request::new(GetGameRequest { game_id: game.id }))
+            .await?
+            .into_inner();
+        assert_eq!(res.id, game.id);
+        assert_eq!(res.turn, 1);
+        assert_eq!(res.scores.len(), 1);
+        assert!(res.board.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_game_with_unknown_id_should_return_not_found() {
+        let err = new_gateway()
+            .get_game(Request::new(GetGameRequest { game_id: 3 }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn get_player_stats_with_unknown_player_should_return_not_found() {
+        let err = new_gateway()
+            .get_player_stats(Request::new(GetPlayerStatsRequest { player_id: 0 }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn serve_should_answer_grpc_client() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let gateway = Arc::new(new_gateway());
+        let leader = gateway.player_service.add_player(0, String::from("test"));
+        gateway.lobby_service.create_lobby(leader, 4)?;
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(gateway.serve(listener));
+        let mut client = GatewayClient::connect(format!("http://{}", addr)).await?;
+        let res = client
+            .list_lobbies(ListLobbiesRequest {})
+            .await?
+            .into_inner();
+        assert_eq!(res.lobby_infos.unwrap().lobby_infos.len(), 1);
+        Ok(())
+    }
+}
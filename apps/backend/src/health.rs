@@ -0,0 +1,167 @@
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+use tracing::{debug, info};
+
+use crate::storage::Storage;
+
+/// State reported by the `/healthz` and `/readyz` probes.
+#[derive(Debug, Default)]
+pub struct Health {
+    listener_bound: AtomicBool,
+    dictionary_loaded: AtomicBool,
+    storage: OnceLock<Arc<dyn Storage>>,
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_listener_bound(&self, value: bool) {
+        self.listener_bound.store(value, Ordering::SeqCst);
+    }
+
+    pub fn set_dictionary_loaded(&self, value: bool) {
+        self.dictionary_loaded.store(value, Ordering::SeqCst);
+    }
+
+    /// Ping `storage` on every readiness probe from now on.
+    pub fn set_storage(&self, storage: Arc<dyn Storage>) {
+        let _ = self.storage.set(storage);
+    }
+
+    fn checks(&self) -> Vec<(&'static str, bool)> {
+        vec![
+            ("listener", self.listener_bound.load(Ordering::SeqCst)),
+            ("dictionary", self.dictionary_loaded.load(Ordering::SeqCst)),
+            (
+                "storage",
+                self.storage
+                    .get()
+                    .is_some_and(|storage| storage.ping().is_ok()),
+            ),
+        ]
+    }
+
+    /// Status line and body for a request path.
+    pub fn respond(&self, path: &str) -> (&'static str, String) {
+        match path {
+            "/healthz" => ("200 OK", String::from("ok\n")),
+            "/readyz" => {
+                let checks = self.checks();
+                let body = checks
+                    .iter()
+                    .map(|(name, ok)| format!("{}: {}\n", name, if *ok { "ok" } else { "fail" }))
+                    .collect::<String>();
+                match checks.iter().all(|(_, ok)| *ok) {
+                    true => ("200 OK", body),
+                    false => ("503 Service Unavailable", body),
+                }
+            }
+            _ => ("404 Not Found", String::from("not found\n")),
+        }
+    }
+
+    /// Answer probes on `listener` until it fails.
+    pub async fn serve(
+        self: Arc<Self>,
+        listener: TcpListener,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        info!(addr = %listener.local_addr()?, "health probe listening");
+        loop {
+            let (socket, _) = listener.accept().await?;
+            let health = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = health.handle(socket).await {
+                    debug!(error = %e, "failed to answer health probe");
+                }
+            });
+        }
+    }
+
+    async fn handle(&self, mut socket: TcpStream) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut buf = [0u8; 1024];
+        let n = timeout(Duration::from_secs(5), socket.read(&mut buf)).await??;
+        // Only the request line matters, e.g. `GET /readyz HTTP/1.1`
+        let request = String::from_utf8_lossy(&buf[..n]);
+        let path = request.split_whitespace().nth(1).unwrap_or("/");
+        let (status, body) = self.respond(path);
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await?;
+        socket.shutdown().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::memory::MemoryStorage;
+
+    #[test]
+    fn respond_with_healthz_should_return_ok() {
+        let health = Health::new();
+        assert_eq!(health.respond("/healthz").0, "200 OK");
+    }
+
+    #[test]
+    fn respond_with_readyz_before_listener_bound_should_return_unavailable() {
+        let health = Health::new();
+        health.set_dictionary_loaded(true);
+        let (status, body) = health.respond("/readyz");
+        assert_eq!(status, "503 Service Unavailable");
+        assert!(body.contains("listener: fail"));
+    }
+
+    #[test]
+    fn respond_with_readyz_when_ready_should_return_ok() {
+        let health = Health::new();
+        health.set_listener_bound(true);
+        health.set_dictionary_loaded(true);
+        health.set_storage(Arc::new(MemoryStorage::new()));
+        assert_eq!(health.respond("/readyz").0, "200 OK");
+    }
+
+    #[test]
+    fn respond_with_readyz_without_storage_should_return_unavailable() {
+        let health = Health::new();
+        health.set_listener_bound(true);
+        health.set_dictionary_loaded(true);
+        let (status, body) = health.respond("/readyz");
+        assert_eq!(status, "503 Service Unavailable");
+        assert!(body.contains("storage: fail"));
+    }
+
+    #[tokio::test]
+    async fn serve_with_readyz_request_should_write_http_response(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(Arc::new(Health::new()).serve(listener));
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"GET /readyz HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable"));
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    cell::Cell,
+    fmt::{self, Debug},
+    sync::{LockResult, Mutex, MutexGuard, TryLockError},
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use tracing::warn;
+
+use crate::operation::Operation;
+
+thread_local! {
+    /// The lock the request being handled on this thread waited on longest,
+    /// and for how long.
+    static LONGEST_WAIT: Cell<Option<(&'static str, Duration)>> = const { Cell::new(None) };
+}
+
+/// A `Mutex` that notes how long taking it had to wait, so a slow request
+/// can say which lock held it up. Only waits are timed, taking a free lock
+/// costs the same as before.
+pub struct TimedMutex<T> {
+    name: &'static str,
+    inner: Mutex<T>,
+}
+
+impl<T> TimedMutex<T> {
+    /// `name` is how slow request logs refer to the lock, such as
+    /// `game.board`.
+    pub fn new(name: &'static str, value: T) -> Self {
+        Self {
+            name,
+            inner: Mutex::new(value),
+        }
+    }
+
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        match self.inner.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::Poisoned(e)) => Err(e),
+            Err(TryLockError::WouldBlock) => {
+                let start = Instant::now();
+                let guard = self.inner.lock();
+                note_wait(self.name, start.elapsed());
+                guard
+            }
+        }
+    }
+}
+
+impl<T: Debug> Debug for TimedMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+fn note_wait(name: &'static str, waited: Duration) {
+    LONGEST_WAIT.with(|longest| {
+        if longest.get().is_none_or(|(_, most)| waited > most) {
+            longest.set(Some((name, waited)));
+        }
+    });
+}
+
+/// Forget the waits of whatever ran on this thread before, ahead of
+/// handling a request.
+pub fn reset_waits() {
+    LONGEST_WAIT.with(|longest| longest.set(None));
+}
+
+/// The lock this thread waited on longest since `reset_waits`, and how long.
+pub fn take_longest_wait() -> Option<(&'static str, Duration)> {
+    LONGEST_WAIT.with(|longest| longest.take())
+}
+
+/// Counts requests that took longer to handle than their budget, by
+/// opcode.
+#[derive(Debug, Default)]
+pub struct SlowRequests {
+    counts: DashMap<u8, u64>,
+}
+
+impl SlowRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count and log the request if handling it took `elapsed`, past
+    /// `budget`. `longest_wait` is the lock it waited on longest, if any.
+    /// Returns whether it was slow.
+    pub fn check(
+        &self,
+        operation: &Operation,
+        elapsed: Duration,
+        budget: Option<Duration>,
+        longest_wait: Option<(&'static str, Duration)>,
+    ) -> bool {
+        if budget.is_none_or(|budget| elapsed <= budget) {
+            return false;
+        }
+        let count = {
+            let mut count = self.counts.entry(u8::from(operation)).or_insert(0);
+            *count += 1;
+            *count
+        };
+        let (lock, lock_wait_us) = match longest_wait {
+            Some((lock, waited)) => (lock, waited.as_micros() as u64),
+            None => ("", 0),
+        };
+        warn!(
+            ?operation,
+            elapsed_ms = elapsed.as_millis() as u64,
+            budget_ms = budget.unwrap_or_default().as_millis() as u64,
+            lock,
+            lock_wait_us,
+            count,
+            "slow request"
+        );
+        true
+    }
+
+    /// How many requests of `operation` went over their budget so far.
+    pub fn get_count(&self, operation: &Operation) -> u64 {
+        self.counts
+            .get(&u8::from(operation))
+            .map_or(0, |count| *count)
+    }
+
+    pub fn get_total(&self) -> u64 {
+        self.counts.iter().map(|count| *count).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{mpsc, Arc},
+        thread,
+    };
+
+    use super::*;
+
+    #[test]
+    fn lock_after_waiting_should_note_longest_wait() {
+        reset_waits();
+        let mutex = Arc::new(TimedMutex::new("test", 0));
+        // Free locks aren't noted
+        *mutex.lock().unwrap() += 1;
+        assert_eq!(take_longest_wait(), None);
+        let (locked, wait_locked) = mpsc::channel();
+        let holder = thread::spawn({
+            let mutex = mutex.clone();
+            move || {
+                let mut guard = mutex.lock().unwrap();
+                locked.send(()).unwrap();
+                thread::sleep(Duration::from_millis(20));
+                *guard += 1;
+            }
+        });
+        wait_locked.recv().unwrap();
+        assert_eq!(*mutex.lock().unwrap(), 2);
+        let (name, waited) = take_longest_wait().unwrap();
+        assert_eq!(name, "test");
+        assert!(waited >= Duration::from_millis(10));
+        assert_eq!(take_longest_wait(), None);
+        holder.join().unwrap();
+    }
+
+    #[test]
+    fn check_past_budget_should_count_request() {
+        let slow_requests = SlowRequests::new();
+        let budget = Some(Duration::from_millis(50));
+        assert!(!slow_requests.check(&Operation::SetTile, Duration::from_millis(50), budget, None));
+        assert!(!slow_requests.check(&Operation::SetTile, Duration::from_secs(1), None, None));
+        assert!(slow_requests.check(
+            &Operation::SetTile,
+            Duration::from_millis(51),
+            budget,
+            Some(("game.board", Duration::from_millis(40)))
+        ));
+        assert_eq!(slow_requests.get_count(&Operation::SetTile), 1);
+        assert_eq!(slow_requests.get_count(&Operation::FinishTurn), 0);
+        assert_eq!(slow_requests.get_total(), 1);
+    }
+}
@@ -1,11 +1,24 @@
+#[cfg(feature = "http")]
+pub mod api;
+pub mod config;
 pub mod connection;
 pub mod controller;
+pub mod error;
 pub mod frame;
 pub mod game;
+#[cfg(feature = "grpc")]
+pub mod gateway;
+pub mod health;
+pub mod latency;
 pub mod lobby;
+pub mod logging;
 pub mod model;
 pub mod operation;
 pub mod player;
+pub mod proxy_protocol;
 pub mod router;
 pub mod server;
 pub mod service;
+pub mod solver;
+pub mod storage;
+pub mod validation;
@@ -1,15 +1,45 @@
-use crate::player::Player;
-use std::error::Error;
+use crate::error::ServerError;
+use crate::model::chat::broadcast::ChatBroadcast;
+use crate::{game::rules::GameRules, latency::TimedMutex, player::Player};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
-use std::{collections::HashMap, sync::Arc};
+use std::time::{Duration, Instant};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+};
 
 use super::lobby_player::LobbyPlayer;
 
+/// What the leader can still change before the game starts.
+#[derive(Debug, Clone)]
+struct Settings {
+    max_players: u32,
+    /// Players needed before the leader can start the game.
+    min_players_to_start: u32,
+    rules: GameRules,
+}
+
 #[derive(Debug, Clone)]
 pub struct Lobby {
     id: u32,
-    max_players: u32,
-    players: Arc<Mutex<HashMap<u32, Arc<LobbyPlayer>>>>,
+    settings: Arc<Mutex<Settings>>,
+    players: Arc<TimedMutex<HashMap<u32, Arc<LobbyPlayer>>>>,
+    /// Players waiting for a free slot, joined in order as players leave.
+    waitlist: Arc<Mutex<VecDeque<Arc<Player>>>>,
+    /// Join code handed out by `LobbyService`, unique among open lobbies.
+    code: Option<String>,
+    /// Names of players the leader kicked for good.
+    banned: Arc<Mutex<HashSet<String>>>,
+    /// When a player last joined or left.
+    last_activity: Arc<Mutex<Instant>>,
+    /// Whether a game was ever started from the lobby.
+    started: Arc<AtomicBool>,
+    /// The latest chat messages sent in the lobby, oldest first, for
+    /// players who join later.
+    chat_history: Arc<Mutex<VecDeque<ChatBroadcast>>>,
+    /// Messages `chat_history` holds at most.
+    chat_history_len: usize,
     pub leader: Arc<Player>,
 }
 
@@ -21,25 +51,60 @@ impl PartialEq for Lobby {
 
 impl Lobby {
     pub fn new(id: u32, max_players: u32, leader: Arc<Player>) -> Self {
-        debug_assert!(max_players >= 4, "max_players must be greater than 4");
-        debug_assert!(max_players <= 8, "max_players must be less than 8");
+        Self::with_settings(id, max_players, 1, leader, GameRules::default())
+    }
+
+    pub fn with_settings(
+        id: u32,
+        max_players: u32,
+        min_players_to_start: u32,
+        leader: Arc<Player>,
+        rules: GameRules,
+    ) -> Self {
+        // The allowed range is configurable, `LobbyService` enforces it
+        debug_assert!(max_players > 0, "max_players must be greater than 0");
+        debug_assert!(
+            (1..=max_players).contains(&min_players_to_start),
+            "min_players_to_start must be between 1 and max_players"
+        );
         Self {
             id,
-            max_players,
-            players: Arc::new(Mutex::new(HashMap::from([(
-                leader.id,
-                Arc::new(LobbyPlayer::new(leader.clone())),
-            )]))),
+            settings: Arc::new(Mutex::new(Settings {
+                max_players,
+                min_players_to_start,
+                rules,
+            })),
+            players: Arc::new(TimedMutex::new(
+                "lobby.players",
+                HashMap::from([(leader.id, Arc::new(LobbyPlayer::new(leader.clone())))]),
+            )),
+            waitlist: Arc::new(Mutex::new(VecDeque::new())),
+            code: None,
+            banned: Arc::new(Mutex::new(HashSet::new())),
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            started: Arc::new(AtomicBool::new(false)),
+            chat_history: Arc::new(Mutex::new(VecDeque::new())),
+            chat_history_len: 0,
             leader,
         }
     }
 
-    pub fn add_player(
-        &self,
-        player: Arc<Player>,
-    ) -> Result<Arc<LobbyPlayer>, Box<dyn Error + Send + Sync>> {
+    pub fn with_code(mut self, code: String) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_chat_history(mut self, len: usize) -> Self {
+        self.chat_history_len = len;
+        self
+    }
+
+    pub fn add_player(&self, player: Arc<Player>) -> Result<Arc<LobbyPlayer>, ServerError> {
         if self.players.lock().unwrap().contains_key(&player.id) {
-            return Err("Player already in lobby".into());
+            return Err(ServerError::AlreadyInLobby);
+        }
+        if self.is_full() {
+            return Err(ServerError::LobbyFull);
         }
 
         let lobby_player = Arc::new(LobbyPlayer::new(player.clone()));
@@ -47,6 +112,7 @@ impl Lobby {
             .lock()
             .unwrap()
             .insert(player.id, lobby_player.clone());
+        self.touch();
         Ok(lobby_player)
     }
 
@@ -58,14 +124,54 @@ impl Lobby {
         self.players.lock().unwrap().values().cloned().collect()
     }
 
-    pub fn remove_player(
-        &self,
-        player: Arc<Player>,
-    ) -> Result<Arc<LobbyPlayer>, Box<dyn Error + Send + Sync>> {
+    /// `get_players`, the longest in the lobby first.
+    pub fn get_players_in_join_order(&self) -> Vec<Arc<LobbyPlayer>> {
+        let mut players = self.get_players();
+        players.sort_by_key(|lobby_player| (lobby_player.get_joined_at(), lobby_player.player.id));
+        players
+    }
+
+    pub fn remove_player(&self, player: Arc<Player>) -> Result<Arc<LobbyPlayer>, ServerError> {
         if !self.players.lock().unwrap().contains_key(&player.id) {
-            return Err("Player is not in the lobby".into());
+            return Err(ServerError::NotInLobby);
         }
-        Ok(self.players.lock().unwrap().remove(&player.id).unwrap())
+        let lobby_player = self.players.lock().unwrap().remove(&player.id).unwrap();
+        self.touch();
+        Ok(lobby_player)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.lock().unwrap().is_empty()
+    }
+
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Time since a player last joined or left.
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.lock().unwrap().elapsed()
+    }
+
+    /// Keep `name` out for as long as the lobby is open.
+    pub fn ban(&self, name: &str) {
+        self.banned.lock().unwrap().insert(name.to_string());
+    }
+
+    pub fn get_banned(&self) -> Vec<String> {
+        self.banned.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn is_banned(&self, name: &str) -> bool {
+        self.banned.lock().unwrap().contains(name)
+    }
+
+    pub fn set_started(&self) {
+        self.started.store(true, Ordering::Relaxed);
+    }
+
+    pub fn has_started(&self) -> bool {
+        self.started.load(Ordering::Relaxed)
     }
 
     pub fn get_id(&self) -> u32 {
@@ -73,7 +179,115 @@ impl Lobby {
     }
 
     pub fn get_max_players(&self) -> u32 {
-        self.max_players
+        self.settings.lock().unwrap().max_players
+    }
+
+    pub fn get_min_players_to_start(&self) -> u32 {
+        self.settings.lock().unwrap().min_players_to_start
+    }
+
+    /// Replace the settings the lobby was created with. The range checks are
+    /// left to `LobbyService`, like on creation.
+    pub fn set_settings(&self, max_players: u32, min_players_to_start: u32, rules: GameRules) {
+        *self.settings.lock().unwrap() = Settings {
+            max_players,
+            min_players_to_start,
+            rules,
+        };
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.players.lock().unwrap().len() >= self.get_max_players() as usize
+    }
+
+    /// Whether the lobby's game has started, which is while any of its
+    /// players is in a game.
+    pub fn is_in_game(&self) -> bool {
+        self.get_players()
+            .iter()
+            .any(|lobby_player| lobby_player.player.get_game().is_some())
+    }
+
+    /// Queue a player for the next free slot, returning their 1-based
+    /// position.
+    pub fn add_to_waitlist(&self, player: Arc<Player>) -> Result<usize, ServerError> {
+        if self.players.lock().unwrap().contains_key(&player.id) {
+            return Err(ServerError::AlreadyInLobby);
+        }
+        let mut waitlist = self.waitlist.lock().unwrap();
+        if waitlist.contains(&player) {
+            return Err("Player already waitlisted".into());
+        }
+        waitlist.push_back(player);
+        Ok(waitlist.len())
+    }
+
+    pub fn remove_from_waitlist(&self, player: &Arc<Player>) -> bool {
+        let mut waitlist = self.waitlist.lock().unwrap();
+        let len = waitlist.len();
+        waitlist.retain(|x| x != player);
+        waitlist.len() != len
+    }
+
+    pub fn pop_waitlist(&self) -> Option<Arc<Player>> {
+        self.waitlist.lock().unwrap().pop_front()
+    }
+
+    pub fn get_waitlist(&self) -> Vec<Arc<Player>> {
+        self.waitlist.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Empty the waitlist, e.g. when the lobby is destroyed.
+    pub fn take_waitlist(&self) -> Vec<Arc<Player>> {
+        self.waitlist.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn get_rules(&self) -> GameRules {
+        self.settings.lock().unwrap().rules.clone()
+    }
+
+    /// Make every player confirm they are ready again.
+    pub fn reset_ready(&self) {
+        for lobby_player in self.get_players() {
+            lobby_player.set_ready(false);
+        }
+    }
+
+    pub fn get_code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Keep `chat` for players who join later, dropping the oldest message
+    /// once the history is full.
+    pub fn record_chat(&self, chat: ChatBroadcast) {
+        if self.chat_history_len == 0 {
+            return;
+        }
+        let mut history = self.chat_history.lock().unwrap();
+        if history.len() == self.chat_history_len {
+            history.pop_front();
+        }
+        history.push_back(chat);
+    }
+
+    /// The chat history, oldest first, without the messages of players
+    /// `reader` blocked.
+    pub fn get_chat_history(&self, reader: &Player) -> Vec<ChatBroadcast> {
+        self.chat_history
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|chat| {
+                chat.player
+                    .as_ref()
+                    .is_none_or(|sender| !reader.has_blocked(&sender.name))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear_chat_history(&self) {
+        self.chat_history.lock().unwrap().clear();
     }
 }
 
@@ -171,4 +385,58 @@ mod tests {
         assert_eq!(lobby.players.lock().unwrap().len(), 0);
         Ok(())
     }
+
+    #[test]
+    fn add_player_with_full_lobby_should_return_error() -> Result<(), Box<dyn std::error::Error>> {
+        let lobby = Lobby::new(0, 2, Arc::new(Player::new(0, "test".to_string())));
+        assert!(lobby
+            .add_player(Arc::new(Player::new(1, "test1".to_string())))
+            .is_ok());
+        assert!(lobby
+            .add_player(Arc::new(Player::new(2, "test2".to_string())))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn add_to_waitlist_with_two_players_should_keep_join_order(
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let lobby = Lobby::new(0, 1, Arc::new(Player::new(0, "test".to_string())));
+        let first = Arc::new(Player::new(1, "test1".to_string()));
+        let second = Arc::new(Player::new(2, "test2".to_string()));
+        assert_eq!(lobby.add_to_waitlist(first.clone())?, 1);
+        assert_eq!(lobby.add_to_waitlist(second.clone())?, 2);
+        assert!(lobby.add_to_waitlist(first.clone()).is_err());
+        assert_eq!(lobby.pop_waitlist(), Some(first));
+        assert!(lobby.remove_from_waitlist(&second));
+        assert!(lobby.get_waitlist().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn record_chat_with_full_history_should_drop_oldest_message() {
+        let leader = Arc::new(Player::new(0, "test".to_string()));
+        let lobby = Lobby::new(0, 4, leader.clone()).with_chat_history(2);
+        for message in ["a", "b", "c"] {
+            lobby.record_chat(ChatBroadcast {
+                player: Some(leader.clone().into()),
+                message: message.to_string(),
+                quick_chat: None,
+            });
+        }
+        let reader = Player::new(1, "test1".to_string());
+        let history = lobby.get_chat_history(&reader);
+        assert_eq!(
+            history
+                .iter()
+                .map(|chat| chat.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "c"]
+        );
+        reader.set_blocked("test", true);
+        assert!(lobby.get_chat_history(&reader).is_empty());
+        lobby.clear_chat_history();
+        reader.set_blocked("test", false);
+        assert!(lobby.get_chat_history(&reader).is_empty());
+    }
 }
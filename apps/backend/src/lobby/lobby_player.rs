@@ -1,12 +1,14 @@
 use std::sync::Arc;
 
 use std::sync::Mutex;
+use std::time::Instant;
 
 use crate::player::Player;
 
 #[derive(Debug)]
 pub struct LobbyPlayer {
     ready: Mutex<bool>,
+    joined_at: Instant,
     pub player: Arc<Player>,
 }
 
@@ -20,6 +22,7 @@ impl LobbyPlayer {
     pub fn new(player: Arc<Player>) -> Self {
         Self {
             ready: Mutex::new(false),
+            joined_at: Instant::now(),
             player,
         }
     }
@@ -31,6 +34,10 @@ impl LobbyPlayer {
     pub fn set_ready(&self, ready: bool) {
         *self.ready.lock().unwrap() = ready;
     }
+
+    pub fn get_joined_at(&self) -> Instant {
+        self.joined_at
+    }
 }
 
 #[cfg(test)]
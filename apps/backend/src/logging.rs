@@ -0,0 +1,26 @@
+use std::{any::Any, env};
+
+use tracing_subscriber::EnvFilter;
+
+/// Install the global subscriber. `LOG_LEVEL` takes an `EnvFilter` directive
+/// and defaults to `info`, `LOG_FORMAT=json` switches to one JSON object per
+/// line for log shipping in production.
+pub fn init() {
+    let filter = EnvFilter::try_from_env("LOG_LEVEL").unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    match env::var("LOG_FORMAT").as_deref() {
+        Ok("json") => builder.json().with_current_span(true).init(),
+        _ => builder.init(),
+    }
+}
+
+/// The message a panic was raised with, for logging a caught panic.
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .unwrap_or("unknown panic"),
+    }
+}
@@ -1,14 +1,16 @@
-use std::error::Error;
+use std::{error::Error, sync::Arc};
 
-use backend::server::Server;
+use backend::{config::ServerConfig, logging, server::Server};
+use tracing::info;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
-    println!("Loading environment variables");
     dotenvy::dotenv().unwrap();
-    println!("Building server");
-    let server = Server::new().await?;
-    println!("Start server");
+    logging::init();
+    info!("Building server");
+    let config = ServerConfig::load()?;
+    let server = Arc::new(Server::new(config).await?);
+    info!("Start server");
     server.run().await?;
     Ok(())
 }
@@ -1,6 +1,12 @@
+pub mod admin;
+pub mod chat;
 pub mod control;
 pub mod error;
+pub mod frame;
 pub mod game;
+#[cfg(feature = "grpc")]
+pub mod gateway;
+pub mod leaderboard;
 pub mod lobby;
 pub mod player;
 pub mod state;
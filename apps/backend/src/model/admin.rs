@@ -0,0 +1,11 @@
+pub mod announce;
+pub mod ban;
+pub mod close_game;
+pub mod handoff;
+pub mod inspect;
+pub mod issue_token;
+pub mod kick;
+pub mod list_players;
+pub mod maintenance;
+pub mod reload;
+pub mod revoke_token;
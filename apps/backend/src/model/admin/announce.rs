@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.announce.rs"));
+
+impl Eq for AnnounceRequest {}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.ban.rs"));
+
+impl Eq for BanRequest {}
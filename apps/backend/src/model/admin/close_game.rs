@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.close_game.rs"));
+
+impl Eq for CloseGameRequest {}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.handoff.rs"));
+
+impl Eq for HandoffRequest {}
@@ -0,0 +1,52 @@
+use crate::model::player::player::Player;
+
+include!(concat!(env!("OUT_DIR"), "/admin.inspect.rs"));
+
+impl Eq for InspectRequest {}
+
+impl From<&crate::lobby::lobby::Lobby> for LobbyDetail {
+    fn from(lobby: &crate::lobby::lobby::Lobby) -> Self {
+        let mut members = lobby
+            .get_players()
+            .iter()
+            .map(|member| LobbyMember {
+                player: Some(Player::from(member.player.clone())),
+                ready: member.get_ready(),
+            })
+            .collect::<Vec<_>>();
+        members.sort_by_key(|member| member.player.as_ref().map(|player| player.id));
+        Self {
+            id: lobby.get_id(),
+            max_players: lobby.get_max_players(),
+            leader: Some(Player::from(lobby.leader.clone())),
+            members,
+        }
+    }
+}
+
+impl From<&crate::game::game::Game> for GameDetail {
+    fn from(game: &crate::game::game::Game) -> Self {
+        let mut players = game
+            .get_players()
+            .into_iter()
+            .map(Player::from)
+            .collect::<Vec<_>>();
+        players.sort_by_key(|player| player.id);
+        let tiles = game
+            .get_board()
+            .lock()
+            .unwrap()
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|tile| tile.is_some())
+            .count() as u32;
+        Self {
+            id: game.id,
+            turn: game.get_turns(),
+            players,
+            current_player: Some(Player::from(game.get_player_in_this_turn())),
+            tiles,
+        }
+    }
+}
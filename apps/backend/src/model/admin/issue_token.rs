@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.issue_token.rs"));
+
+impl Eq for IssueTokenRequest {}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.kick.rs"));
+
+impl Eq for KickRequest {}
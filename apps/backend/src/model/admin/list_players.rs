@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.list_players.rs"));
+
+impl Eq for ListPlayersRequest {}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.maintenance.rs"));
+
+impl Eq for MaintenanceRequest {}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.reload.rs"));
+
+impl Eq for ReloadRequest {}
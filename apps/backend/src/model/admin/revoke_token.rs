@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/admin.revoke_token.rs"));
+
+impl Eq for RevokeTokenRequest {}
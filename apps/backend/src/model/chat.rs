@@ -0,0 +1,3 @@
+pub mod broadcast;
+pub mod quick_chat;
+pub mod send;
@@ -0,0 +1,35 @@
+include!(concat!(env!("OUT_DIR"), "/chat.quick_chat.rs"));
+
+impl Eq for SendQuickChatRequest {}
+
+impl QuickChat {
+    pub fn is_emote(&self) -> bool {
+        matches!(
+            self,
+            QuickChat::EmoteSmile
+                | QuickChat::EmoteLaugh
+                | QuickChat::EmoteSurprised
+                | QuickChat::EmoteSad
+                | QuickChat::EmoteAngry
+        )
+    }
+
+    /// What the phrase reads as, for clients that only show the message.
+    pub fn text(&self) -> &'static str {
+        match self {
+            QuickChat::Hello => "Hello!",
+            QuickChat::GoodLuck => "Good luck!",
+            QuickChat::NiceWord => "Nice word!",
+            QuickChat::WellPlayed => "Well played!",
+            QuickChat::Thanks => "Thanks!",
+            QuickChat::Oops => "Oops!",
+            QuickChat::Thinking => "Hmm, let me think...",
+            QuickChat::GoodGame => "Good game!",
+            QuickChat::EmoteSmile => "\u{1F642}",
+            QuickChat::EmoteLaugh => "\u{1F602}",
+            QuickChat::EmoteSurprised => "\u{1F62E}",
+            QuickChat::EmoteSad => "\u{1F622}",
+            QuickChat::EmoteAngry => "\u{1F620}",
+        }
+    }
+}
@@ -1,3 +1,8 @@
 pub mod connect;
 pub mod disconnect;
 pub mod heartbeat;
+pub mod notice;
+pub mod opcodes;
+pub mod preferences;
+pub mod server_info;
+pub mod time_sync;
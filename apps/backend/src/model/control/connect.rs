@@ -1,3 +1,26 @@
 include!(concat!(env!("OUT_DIR"), "/control.connect.rs"));
 
 impl Eq for ConnectRequest {}
+
+impl Capability {
+    /// Every capability this server knows about.
+    pub const ALL: u64 = Capability::TurnEvents as u64
+        | Capability::WithdrawEvents as u64
+        | Capability::DrawEvents as u64
+        | Capability::LatencyEvents as u64
+        | Capability::HandEvents as u64
+        | Capability::DisconnectEvents as u64
+        | Capability::PowerUpEvents as u64
+        | Capability::SpectatorEvents as u64
+        | Capability::BoardDeltas as u64
+        | Capability::StateVersions as u64
+        | Capability::VoteKickEvents as u64;
+}
+
+impl Scope {
+    /// Every scope this server knows about.
+    pub const ALL: u64 =
+        Scope::Play as u64 | Scope::Spectate as u64 | Scope::Admin as u64 | Scope::Observe as u64;
+    /// What a session opened with a name may do.
+    pub const INTERACTIVE: u64 = Scope::Play as u64 | Scope::Spectate as u64;
+}
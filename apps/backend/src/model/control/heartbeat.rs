@@ -1 +1,3 @@
 include!(concat!(env!("OUT_DIR"), "/control.heartbeat.rs"));
+
+impl Eq for HeartbeatRequest {}
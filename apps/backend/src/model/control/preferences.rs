@@ -0,0 +1,16 @@
+use crate::frame::EventClass;
+
+include!(concat!(env!("OUT_DIR"), "/control.preferences.rs"));
+
+impl Eq for SetPreferencesRequest {}
+
+impl SetPreferencesRequest {
+    /// Whether pushes of `class` are turned off.
+    pub fn mutes(&self, class: EventClass) -> bool {
+        match class {
+            EventClass::Chat => self.mute_chat,
+            EventClass::Emote => self.mute_emotes,
+            EventClass::SpectatorJoin => self.mute_spectators,
+        }
+    }
+}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/control.time_sync.rs"));
+
+impl Eq for TimeSyncRequest {}
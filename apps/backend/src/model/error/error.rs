@@ -1 +1,75 @@
+use std::{error::Error as StdError, fmt};
+
+use crate::{
+    error::ServerError,
+    frame,
+    game::{board::PlacementError, game::MoveViolation},
+};
+
 include!(concat!(env!("OUT_DIR"), "/error.error.rs"));
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl StdError for Error {}
+
+impl From<PlacementError> for ErrorCode {
+    fn from(error: PlacementError) -> Self {
+        match error {
+            PlacementError::OutOfBoard => ErrorCode::TileOutOfBoard,
+            PlacementError::Occupied => ErrorCode::TileOccupied,
+            PlacementError::NotConnected => ErrorCode::TileNotConnected,
+            PlacementError::NotOnCenter => ErrorCode::TileNotOnCenter,
+        }
+    }
+}
+
+impl From<MoveViolation> for ErrorCode {
+    fn from(violation: MoveViolation) -> Self {
+        match violation {
+            MoveViolation::NotYourTurn => ErrorCode::NotYourTurn,
+            MoveViolation::TurnOver => ErrorCode::TurnOver,
+            MoveViolation::CardNotInHand => ErrorCode::CardNotInHand,
+            MoveViolation::CardReused => ErrorCode::CardReused,
+        }
+    }
+}
+
+impl From<&frame::Error> for ErrorCode {
+    fn from(error: &frame::Error) -> Self {
+        match error {
+            frame::Error::UnsupportedProtocol(_) => ErrorCode::UnsupportedProtocol,
+            frame::Error::PayloadTooLarge(..) => ErrorCode::InvalidRequest,
+            _ => ErrorCode::Unknown,
+        }
+    }
+}
+
+impl From<&ServerError> for ErrorCode {
+    fn from(error: &ServerError) -> Self {
+        error.code()
+    }
+}
+
+impl From<&(dyn StdError + Send + Sync + 'static)> for ErrorCode {
+    /// The code for a boxed error, `Unknown` unless it is one of the typed
+    /// errors clients can tell apart.
+    fn from(error: &(dyn StdError + Send + Sync + 'static)) -> Self {
+        if let Some(error) = error.downcast_ref::<ServerError>() {
+            return error.code();
+        }
+        if let Some(error) = error.downcast_ref::<PlacementError>() {
+            return ErrorCode::from(*error);
+        }
+        if let Some(violation) = error.downcast_ref::<MoveViolation>() {
+            return ErrorCode::from(*violation);
+        }
+        match error.downcast_ref::<frame::Error>() {
+            Some(error) => ErrorCode::from(error),
+            None => ErrorCode::Unknown,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    frame::{Response, ResponseData},
+    model::state::State,
+};
+
+include!(concat!(env!("OUT_DIR"), "/frame.envelope.rs"));
+
+impl From<&Response> for ResponseEnvelope {
+    fn from(res: &Response) -> Self {
+        let data = res.get_data();
+        let kind = match (res.get_request_id(), data.as_ref()) {
+            (Some(_), ResponseData::Error(_)) => ResponseKind::Error,
+            (Some(_), _) => ResponseKind::Reply,
+            (None, ResponseData::Error(_)) => ResponseKind::Error,
+            (None, _) if res.get_state() == State::LobbyBroadcast as u32 => {
+                ResponseKind::LobbyEvent
+            }
+            (None, _) if res.get_state() == State::Notice as u32 => ResponseKind::NoticeEvent,
+            (None, _) => ResponseKind::GameEvent,
+        };
+        Self {
+            request_id: res.get_request_id(),
+            kind: kind as i32,
+            payload: data.encode_payload(),
+            state: match res.get_request_id() {
+                Some(_) => None,
+                None => Some(res.get_state()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::model::{control::heartbeat::HeartbeatResponse, game::broadcast::GameBroadcast};
+
+    #[test]
+    fn from_response_with_error_should_be_error_kind() {
+        let envelope = ResponseEnvelope::from(&Response::new(
+            7,
+            Arc::new(ResponseData::Error(crate::model::error::error::Error {
+                message: String::from("Player not found"),
+                code: crate::model::error::error::ErrorCode::Unknown as i32,
+            })),
+        ));
+        assert_eq!(envelope.request_id, Some(7));
+        assert_eq!(envelope.kind(), ResponseKind::Error);
+        assert_eq!(envelope.state, None);
+    }
+
+    #[test]
+    fn from_event_with_error_should_be_error_kind() {
+        let envelope = ResponseEnvelope::from(&Response::event(
+            State::Error,
+            Arc::new(ResponseData::Error(crate::model::error::error::Error {
+                message: String::from("Server is full"),
+                code: crate::model::error::error::ErrorCode::ServerFull as i32,
+            })),
+        ));
+        assert_eq!(envelope.request_id, None);
+        assert_eq!(envelope.kind(), ResponseKind::Error);
+    }
+
+    #[test]
+    fn from_response_with_reply_should_be_reply_kind() {
+        let envelope = ResponseEnvelope::from(&Response::new(
+            7,
+            Arc::new(ResponseData::Heartbeat(HeartbeatResponse {
+                success: true,
+                ..Default::default()
+            })),
+        ));
+        assert_eq!(envelope.kind(), ResponseKind::Reply);
+        assert_eq!(
+            HeartbeatResponse::decode(&envelope.payload[..]).unwrap(),
+            HeartbeatResponse {
+                success: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn from_event_should_not_have_request_id() {
+        let envelope = ResponseEnvelope::from(&Response::event(
+            State::GameBroadcast,
+            Arc::new(ResponseData::GameBroadcast(GameBroadcast::default())),
+        ));
+        assert_eq!(envelope.request_id, None);
+        assert_eq!(envelope.kind(), ResponseKind::GameEvent);
+        assert_eq!(envelope.state, Some(State::GameBroadcast as u32));
+    }
+}
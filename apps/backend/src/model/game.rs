@@ -1,13 +1,28 @@
+pub mod analyze;
+pub mod bag_info;
 pub mod board;
 pub mod broadcast;
 pub mod cancel;
 pub mod card;
 pub mod cards;
+pub mod coop;
+pub mod draw;
+pub mod exchange;
 pub mod exit;
+pub mod export;
 pub mod finish_turn;
 pub mod get_new_card;
 pub mod hand_card;
+pub mod hint;
+pub mod pause;
+pub mod resign;
+pub mod resume;
+pub mod resync;
 pub mod set_tile;
+pub mod snapshot;
+pub mod spectate;
 pub mod start;
+pub mod subscribe;
 pub mod tile;
+pub mod vote_kick;
 pub mod words;
@@ -0,0 +1,22 @@
+use crate::game::analysis;
+
+include!(concat!(env!("OUT_DIR"), "/game.analyze.rs"));
+
+impl Eq for AnalyzeGameRequest {}
+
+impl From<&analysis::TurnAnalysis> for TurnAnalysis {
+    fn from(analysis: &analysis::TurnAnalysis) -> Self {
+        Self {
+            turn: analysis.turn,
+            player_id: analysis.player_id,
+            points: analysis.points,
+            best_word: analysis
+                .best
+                .as_ref()
+                .map_or_else(String::new, |best| best.word.clone()),
+            best_tiles: analysis.best.as_ref().map_or_else(Vec::new, Vec::from),
+            best_points: analysis.best_points,
+            points_left: analysis.points_left(),
+        }
+    }
+}
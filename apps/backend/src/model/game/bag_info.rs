@@ -0,0 +1 @@
+include!(concat!(env!("OUT_DIR"), "/game.bag_info.rs"));
@@ -22,6 +22,53 @@ impl From<&crate::game::board::Board> for Board {
     }
 }
 
+impl BoardDelta {
+    /// The delta for changes taken with `Game::take_board_changes`.
+    pub fn new(version: u64, changes: &[(usize, usize, Option<crate::game::tile::Tile>)]) -> Self {
+        Self {
+            version,
+            squares: changes
+                .iter()
+                .map(|(x, y, tile)| ChangedSquare {
+                    x: *x as u32,
+                    y: *y as u32,
+                    tile: tile.as_ref().map(Tile::from),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<crate::game::board::Premium> for Premium {
+    fn from(premium: crate::game::board::Premium) -> Self {
+        match premium {
+            crate::game::board::Premium::DoubleLetter => Premium::DoubleLetter,
+            crate::game::board::Premium::TripleLetter => Premium::TripleLetter,
+            crate::game::board::Premium::DoubleWord => Premium::DoubleWord,
+            crate::game::board::Premium::TripleWord => Premium::TripleWord,
+        }
+    }
+}
+
+impl From<&crate::game::game::Game> for Layout {
+    fn from(game: &crate::game::game::Game) -> Self {
+        let board = game.get_board();
+        let board = board.lock().unwrap();
+        Self {
+            size: game.get_rules().board_size as u32,
+            premiums: board
+                .get_premiums()
+                .iter()
+                .map(|(&(x, y), &premium)| PremiumSquare {
+                    x: x as u32,
+                    y: y as u32,
+                    premium: Premium::from(premium) as i32,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -62,4 +109,26 @@ mod tests {
             assert_eq!(board.rows[i].columns[25].tile, None);
         }
     }
+
+    #[test]
+    fn from_game_with_classic_layout_should_list_premium_squares() {
+        let player = Arc::new(Player::new(0, String::from("test")));
+        let game = crate::game::game::Game::with_rules(
+            0,
+            vec![player],
+            crate::game::rules::GameRules {
+                board_size: crate::game::board::CLASSIC_BOARD_SIZE,
+                layout: crate::game::board::BoardLayout::Classic,
+                ..crate::game::rules::GameRules::default()
+            },
+        );
+        let layout = Layout::from(&game);
+        assert_eq!(layout.size, 15);
+        assert_eq!(layout.premiums.len(), 61);
+        assert!(layout.premiums.contains(&PremiumSquare {
+            x: 7,
+            y: 7,
+            premium: Premium::DoubleWord as i32,
+        }));
+    }
 }
@@ -4,6 +4,17 @@ impl From<&crate::game::card::Card> for Card {
     fn from(value: &crate::game::card::Card) -> Self {
         Self {
             symbol: String::from(value.char),
+            power_up: value.power_up.map_or(PowerUp::None, PowerUp::from) as i32,
+        }
+    }
+}
+
+impl From<crate::game::card::PowerUp> for PowerUp {
+    fn from(value: crate::game::card::PowerUp) -> Self {
+        match value {
+            crate::game::card::PowerUp::DoubleWord => Self::DoubleWord,
+            crate::game::card::PowerUp::StealLetter => Self::StealLetter,
+            crate::game::card::PowerUp::FreezeTimer => Self::FreezeTimer,
         }
     }
 }
@@ -0,0 +1,14 @@
+use crate::game::game::CoopStatus;
+
+include!(concat!(env!("OUT_DIR"), "/game.coop.rs"));
+
+impl From<CoopStatus> for CoopState {
+    fn from(status: CoopStatus) -> Self {
+        Self {
+            score: status.score,
+            target: status.target,
+            turns_left: status.turns_left,
+            won: status.is_won(),
+        }
+    }
+}
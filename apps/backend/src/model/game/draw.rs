@@ -0,0 +1,14 @@
+use crate::game::game::DrawStatus;
+
+include!(concat!(env!("OUT_DIR"), "/game.draw.rs"));
+
+impl Eq for AnswerDrawRequest {}
+
+impl From<DrawStatus> for DrawState {
+    fn from(status: DrawStatus) -> Self {
+        Self {
+            votes: status.votes,
+            votes_needed: status.votes_needed,
+        }
+    }
+}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/game.exchange.rs"));
+
+impl Eq for ExchangeCardsRequest {}
@@ -0,0 +1,14 @@
+use crate::game::notation::Notation;
+
+include!(concat!(env!("OUT_DIR"), "/game.export.rs"));
+
+impl Eq for ExportGameRequest {}
+
+impl From<ExportFormat> for Notation {
+    fn from(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Text => Notation::Text,
+            ExportFormat::Gcg => Notation::Gcg,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::solver::{self, Placement};
+
+include!(concat!(env!("OUT_DIR"), "/game.hint.rs"));
+
+impl Eq for HintRequest {}
+
+impl From<HintMode> for solver::HintMode {
+    fn from(mode: HintMode) -> Self {
+        match mode {
+            HintMode::Best => solver::HintMode::Best,
+            HintMode::Random => solver::HintMode::Random,
+        }
+    }
+}
+
+impl From<&Placement> for Vec<HintTile> {
+    fn from(placement: &Placement) -> Self {
+        placement
+            .tiles
+            .iter()
+            .map(|&(x, y, card_index)| HintTile {
+                x: x as u32,
+                y: y as u32,
+                card_index: card_index as u32,
+            })
+            .collect()
+    }
+}
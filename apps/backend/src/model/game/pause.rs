@@ -0,0 +1,13 @@
+use crate::game::game::PauseStatus;
+
+include!(concat!(env!("OUT_DIR"), "/game.pause.rs"));
+
+impl From<PauseStatus> for PauseState {
+    fn from(status: PauseStatus) -> Self {
+        Self {
+            paused: status.paused,
+            votes: status.votes,
+            votes_needed: status.votes_needed,
+        }
+    }
+}
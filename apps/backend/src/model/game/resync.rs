@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/game.resync.rs"));
+
+impl Eq for ResyncFromRequest {}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/game.spectate.rs"));
+
+impl Eq for SpectateRequest {}
@@ -1 +1,40 @@
+use std::sync::Arc;
+
+use crate::game::{
+    game::{deadline_millis, Game, GameMode},
+    game_player::GamePlayer,
+};
+
 include!(concat!(env!("OUT_DIR"), "/game.start.rs"));
+
+impl StartResponse {
+    /// What `game_player` needs to draw the game they just joined.
+    pub fn new(game: &Game, game_player: Arc<GamePlayer>) -> Self {
+        // Takes the board lock, so has to be done before locking it below
+        let layout = super::board::Layout::from(game);
+        Self {
+            success: true,
+            board: Some(super::board::Board::from(
+                &*game.get_board().lock().unwrap(),
+            )),
+            cards: Some(super::cards::Cards::from(&game_player.get_cards())),
+            current_player: Some(crate::model::player::player::Player::from(
+                game.get_player_in_this_turn(),
+            )),
+            next_player: game
+                .get_next_turn_player()
+                .map(crate::model::player::player::Player::from),
+            layout: Some(layout),
+            day: match game.get_mode() {
+                GameMode::Daily(day) => Some(day),
+                GameMode::Standard | GameMode::Practice => None,
+            },
+            turn_order: game
+                .get_players()
+                .into_iter()
+                .map(crate::model::player::player::Player::from)
+                .collect(),
+            turn_deadline: game.get_turn_deadline().map(deadline_millis),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/game.subscribe.rs"));
+
+impl Eq for SubscribeGameRequest {}
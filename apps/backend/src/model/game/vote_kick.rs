@@ -0,0 +1,19 @@
+use crate::game::game::{deadline_millis, KickStatus};
+
+include!(concat!(env!("OUT_DIR"), "/game.vote_kick.rs"));
+
+impl Eq for StartVoteKickRequest {}
+
+impl Eq for AnswerVoteKickRequest {}
+
+impl From<KickStatus> for KickVote {
+    fn from(status: KickStatus) -> Self {
+        Self {
+            target_id: status.target,
+            votes: status.votes,
+            votes_against: status.votes_against,
+            votes_needed: status.votes_needed,
+            deadline: deadline_millis(status.deadline),
+        }
+    }
+}
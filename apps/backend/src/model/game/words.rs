@@ -1,9 +1,22 @@
+use crate::game::replay::MoveAnnotation;
+
 include!(concat!(env!("OUT_DIR"), "/game.words.rs"));
 
 impl From<&Vec<String>> for Words {
     fn from(value: &Vec<String>) -> Self {
         Self {
             words: value.iter().map(|word| word.clone()).collect(),
+            definitions: Vec::new(),
+        }
+    }
+}
+
+impl From<&MoveAnnotation> for TurnScore {
+    fn from(annotation: &MoveAnnotation) -> Self {
+        Self {
+            points: annotation.points,
+            bingo_bonus: annotation.bingo_bonus,
+            multiplier: annotation.multiplier,
         }
     }
 }
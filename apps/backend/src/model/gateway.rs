@@ -0,0 +1,49 @@
+use crate::{
+    game::game::Game,
+    model::{
+        game::board::{Board, Layout},
+        player::player::Player,
+    },
+};
+
+include!(concat!(env!("OUT_DIR"), "/gateway.rs"));
+
+impl From<&Game> for GameSummary {
+    fn from(game: &Game) -> Self {
+        let mut players = game
+            .get_players()
+            .into_iter()
+            .map(Player::from)
+            .collect::<Vec<_>>();
+        players.sort_by_key(|player| player.id);
+        Self {
+            id: game.id,
+            turn: game.get_turns(),
+            players,
+        }
+    }
+}
+
+impl From<&Game> for GetGameResponse {
+    fn from(game: &Game) -> Self {
+        let mut scores = game
+            .get_players()
+            .into_iter()
+            .map(|game_player| GameScore {
+                score: game_player.get_tally().score,
+                player: Some(Player::from(game_player)),
+            })
+            .collect::<Vec<_>>();
+        scores.sort_by_key(|score| score.player.as_ref().map(|player| player.id));
+        // `Layout::from` takes the board lock as well
+        let board = Board::from(&*game.get_board().lock().unwrap());
+        Self {
+            id: game.id,
+            turn: game.get_turns(),
+            scores,
+            current_player: Some(Player::from(game.get_player_in_this_turn())),
+            board: Some(board),
+            layout: Some(Layout::from(game)),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+include!(concat!(env!("OUT_DIR"), "/leaderboard.daily.rs"));
+
+impl Eq for DailyLeaderboardRequest {}
+
+impl LeaderboardEntry {
+    /**
+     * Rank sorted scores, starting from 1.
+     */
+    pub fn ranked(scores: &[(String, u32)]) -> Vec<LeaderboardEntry> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(i, (name, score))| LeaderboardEntry {
+                rank: i as u32 + 1,
+                name: name.clone(),
+                score: *score,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,16 @@
+use crate::service::season_service::Reward;
+
+include!(concat!(env!("OUT_DIR"), "/leaderboard.season.rs"));
+
+impl Eq for SeasonInfoRequest {}
+
+impl From<Reward> for SeasonReward {
+    fn from(reward: Reward) -> Self {
+        match reward {
+            Reward::None => SeasonReward::None,
+            Reward::Bronze => SeasonReward::Bronze,
+            Reward::Silver => SeasonReward::Silver,
+            Reward::Gold => SeasonReward::Gold,
+        }
+    }
+}
@@ -1,7 +1,11 @@
 pub mod broadcast;
 pub mod create;
 pub mod join;
+pub mod kick;
 pub mod list;
 pub mod lobby;
 pub mod quit;
 pub mod ready;
+pub mod rules;
+pub mod update_settings;
+pub mod upload_dictionary;
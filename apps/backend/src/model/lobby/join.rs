@@ -1,3 +1,5 @@
 include!(concat!(env!("OUT_DIR"), "/lobby.join.rs"));
 
 impl Eq for JoinRequest {}
+
+impl Eq for JoinByCodeRequest {}
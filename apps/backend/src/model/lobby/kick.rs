@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/lobby.kick.rs"));
+
+impl Eq for KickFromLobbyRequest {}
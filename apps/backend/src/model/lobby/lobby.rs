@@ -19,6 +19,15 @@ impl From<&crate::lobby::lobby::Lobby> for Lobby {
         Self {
             id: lobby.get_id(),
             players,
+            rules: Some(crate::model::lobby::rules::Rules::from(&lobby.get_rules())),
+            max_players: lobby.get_max_players(),
+            min_players_to_start: lobby.get_min_players_to_start(),
+            waitlist: lobby
+                .get_waitlist()
+                .into_iter()
+                .map(crate::model::player::player::Player::from)
+                .collect(),
+            code: lobby.get_code().map(String::from),
         }
     }
 }
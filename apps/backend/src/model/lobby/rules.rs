@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use crate::game::{
+    board::{self, CLASSIC_BOARD_SIZE},
+    rules::GameRules,
+};
+
+include!(concat!(env!("OUT_DIR"), "/lobby.rules.rs"));
+
+impl Eq for Rules {}
+
+impl From<&GameRules> for Rules {
+    fn from(rules: &GameRules) -> Self {
+        Self {
+            board_size: rules.board_size as u32,
+            turn_duration: rules.turn_duration.as_secs() as u32,
+            hand_size: rules.hand_size as u32,
+            language: rules.language.clone(),
+            scoring: Scoring::from(rules.scoring) as i32,
+            layout: BoardLayout::from(rules.layout) as i32,
+            hint_budget: Some(rules.hint_budget),
+            turn_order: TurnOrder::from(rules.turn_order) as i32,
+            streak_multipliers: rules.streak_multipliers,
+            territory: rules.territory,
+            power_ups: rules.power_ups,
+            coop: rules.coop.map(Coop::from),
+            quick_chat_only: rules.quick_chat_only,
+            letter_odds: Some(rules.letter_odds),
+        }
+    }
+}
+
+impl From<crate::game::rules::Coop> for Coop {
+    fn from(coop: crate::game::rules::Coop) -> Self {
+        Self {
+            target: coop.target,
+            rounds: coop.rounds,
+        }
+    }
+}
+
+impl From<&Coop> for crate::game::rules::Coop {
+    fn from(coop: &Coop) -> Self {
+        Self {
+            target: coop.target,
+            rounds: coop.rounds,
+        }
+    }
+}
+
+impl From<crate::game::rules::Scoring> for Scoring {
+    fn from(scoring: crate::game::rules::Scoring) -> Self {
+        match scoring {
+            crate::game::rules::Scoring::Classic => Scoring::Classic,
+            crate::game::rules::Scoring::WordLength => Scoring::WordLength,
+        }
+    }
+}
+
+impl From<Scoring> for crate::game::rules::Scoring {
+    fn from(scoring: Scoring) -> Self {
+        match scoring {
+            Scoring::Classic => crate::game::rules::Scoring::Classic,
+            Scoring::WordLength => crate::game::rules::Scoring::WordLength,
+        }
+    }
+}
+
+impl From<board::BoardLayout> for BoardLayout {
+    fn from(layout: board::BoardLayout) -> Self {
+        match layout {
+            board::BoardLayout::Plain => BoardLayout::Plain,
+            board::BoardLayout::Classic => BoardLayout::Classic,
+            board::BoardLayout::RandomPremium => BoardLayout::RandomPremium,
+        }
+    }
+}
+
+impl From<BoardLayout> for board::BoardLayout {
+    fn from(layout: BoardLayout) -> Self {
+        match layout {
+            BoardLayout::Plain => board::BoardLayout::Plain,
+            BoardLayout::Classic => board::BoardLayout::Classic,
+            BoardLayout::RandomPremium => board::BoardLayout::RandomPremium,
+        }
+    }
+}
+
+impl From<crate::game::rules::TurnOrder> for TurnOrder {
+    fn from(order: crate::game::rules::TurnOrder) -> Self {
+        match order {
+            crate::game::rules::TurnOrder::Random => TurnOrder::Random,
+            crate::game::rules::TurnOrder::JoinOrder => TurnOrder::JoinOrder,
+            crate::game::rules::TurnOrder::DrawTiles => TurnOrder::DrawTiles,
+        }
+    }
+}
+
+impl From<TurnOrder> for crate::game::rules::TurnOrder {
+    fn from(order: TurnOrder) -> Self {
+        match order {
+            TurnOrder::Random => crate::game::rules::TurnOrder::Random,
+            TurnOrder::JoinOrder => crate::game::rules::TurnOrder::JoinOrder,
+            TurnOrder::DrawTiles => crate::game::rules::TurnOrder::DrawTiles,
+        }
+    }
+}
+
+impl Rules {
+    /// Fill the fields the client left unset from `defaults`.
+    pub fn merge_into(&self, defaults: &GameRules) -> GameRules {
+        let layout = self.layout().into();
+        GameRules {
+            board_size: match self.board_size {
+                0 if layout == board::BoardLayout::Classic => CLASSIC_BOARD_SIZE,
+                0 => defaults.board_size,
+                board_size => board_size as usize,
+            },
+            turn_duration: match self.turn_duration {
+                0 => defaults.turn_duration,
+                secs => Duration::from_secs(secs as u64),
+            },
+            hand_size: match self.hand_size {
+                0 => defaults.hand_size,
+                hand_size => hand_size as usize,
+            },
+            language: match self.language.is_empty() {
+                true => defaults.language.clone(),
+                false => self.language.clone(),
+            },
+            scoring: self.scoring().into(),
+            layout,
+            turn_order: self.turn_order().into(),
+            streak_multipliers: self.streak_multipliers,
+            territory: self.territory,
+            power_ups: self.power_ups,
+            coop: self.coop.as_ref().map(crate::game::rules::Coop::from),
+            quick_chat_only: self.quick_chat_only,
+            // Odds help plan ahead, which rated games shouldn't hand out
+            letter_odds: self.letter_odds.unwrap_or(self.coop.is_some()),
+            hint_budget: self.hint_budget.unwrap_or(defaults.hint_budget),
+            // Server policy, not up to the lobby
+            forfeit_after_missed_turns: defaults.forfeit_after_missed_turns,
+            disconnect_grace: defaults.disconnect_grace,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_into_with_unset_fields_should_use_defaults() {
+        let rules = Rules {
+            board_size: 15,
+            ..Rules::default()
+        }
+        .merge_into(&GameRules::default());
+        assert_eq!(rules.board_size, 15);
+        assert_eq!(rules.hand_size, GameRules::default().hand_size);
+        assert_eq!(rules.language, GameRules::default().language);
+    }
+
+    #[test]
+    fn merge_into_with_classic_layout_and_unset_board_size_should_use_classic_size() {
+        let rules = Rules {
+            layout: BoardLayout::Classic as i32,
+            ..Rules::default()
+        }
+        .merge_into(&GameRules::default());
+        assert_eq!(rules.board_size, CLASSIC_BOARD_SIZE);
+        assert!(rules.validate().is_ok());
+    }
+
+    #[test]
+    fn merge_into_with_unset_letter_odds_should_show_them_only_in_coop() {
+        let rules = Rules::default().merge_into(&GameRules::default());
+        assert!(!rules.letter_odds);
+        let rules = Rules {
+            coop: Some(Coop {
+                target: 100,
+                rounds: 5,
+            }),
+            ..Rules::default()
+        }
+        .merge_into(&GameRules::default());
+        assert!(rules.letter_odds);
+        let rules = Rules {
+            letter_odds: Some(true),
+            ..Rules::default()
+        }
+        .merge_into(&GameRules::default());
+        assert!(rules.letter_odds);
+    }
+}
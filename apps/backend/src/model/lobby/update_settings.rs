@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/lobby.update_settings.rs"));
+
+impl Eq for UpdateLobbySettingsRequest {}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/lobby.upload_dictionary.rs"));
+
+impl Eq for UploadDictionaryRequest {}
@@ -1,2 +1,7 @@
+pub mod block;
+pub mod mute;
 pub mod player;
 pub mod players;
+pub mod profile;
+pub mod report;
+pub mod stats;
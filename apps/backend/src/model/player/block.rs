@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/player.block.rs"));
+
+impl Eq for BlockPlayerRequest {}
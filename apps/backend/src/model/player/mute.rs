@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/player.mute.rs"));
+
+impl Eq for MutePlayerRequest {}
@@ -7,6 +7,8 @@ impl From<Arc<crate::player::Player>> for Player {
         Self {
             id: player.id,
             name: player.name.clone(),
+            ping_ms: player.get_average_rtt(),
+            profile: Some(super::profile::Profile::from(&player.get_profile())),
         }
     }
 }
@@ -16,6 +18,8 @@ impl From<Arc<crate::game::game_player::GamePlayer>> for Player {
         Self {
             id: player.player.id,
             name: player.player.name.clone(),
+            ping_ms: player.player.get_average_rtt(),
+            profile: Some(super::profile::Profile::from(&player.player.get_profile())),
         }
     }
 }
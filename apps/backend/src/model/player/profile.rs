@@ -0,0 +1,27 @@
+use crate::service::profile_service;
+
+include!(concat!(env!("OUT_DIR"), "/player.profile.rs"));
+
+impl Eq for UpdateProfileRequest {}
+
+impl From<&profile_service::Profile> for Profile {
+    fn from(profile: &profile_service::Profile) -> Self {
+        Self {
+            avatar_id: profile.avatar_id,
+            tile_color: profile.tile_color as i32,
+            title: profile.title as i32,
+        }
+    }
+}
+
+impl TryFrom<&Profile> for profile_service::Profile {
+    type Error = &'static str;
+
+    fn try_from(profile: &Profile) -> Result<Self, Self::Error> {
+        Ok(Self {
+            avatar_id: profile.avatar_id,
+            tile_color: TileColor::from_i32(profile.tile_color).ok_or("Invalid tile color")?,
+            title: Title::from_i32(profile.title).ok_or("Invalid title")?,
+        })
+    }
+}
@@ -0,0 +1,3 @@
+include!(concat!(env!("OUT_DIR"), "/player.report.rs"));
+
+impl Eq for ReportPlayerRequest {}
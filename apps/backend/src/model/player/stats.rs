@@ -0,0 +1,23 @@
+use crate::service::stats_service;
+
+include!(concat!(env!("OUT_DIR"), "/player.stats.rs"));
+
+impl Eq for GetPlayerStatsRequest {}
+
+impl PlayerStats {
+    pub fn new(name: String, stats: &stats_service::PlayerStats) -> Self {
+        let (best_word, best_word_score) = stats.best_word.clone().unwrap_or_default();
+        Self {
+            name,
+            games_played: stats.games_played,
+            games_won: stats.games_won,
+            win_rate: stats.win_rate(),
+            average_score: stats.average_score(),
+            best_word,
+            best_word_score,
+            tiles_placed: stats.tiles_placed,
+            longest_word: stats.longest_word.clone().unwrap_or_default(),
+            games_resigned: stats.games_resigned,
+        }
+    }
+}
@@ -1,4 +1,25 @@
 pub enum State {
     LobbyBroadcast = 0,
     GameBroadcast = 1,
+    Notice = 2,
+    Error = 3,
+    Chat = 4,
+    /// Sent with a `Notice` before a session is closed for the same name
+    /// connecting again.
+    SessionReplaced = 5,
+    /// Sent with a `Notice` whose `redirect` says where to connect before a
+    /// session is closed for its lobby moving to another instance.
+    Moved = 6,
 }
+
+impl State {
+    /// The highest state a push event is sent under.
+    pub const MAX: u32 = State::Moved as u32;
+}
+
+/// Legacy frames put the request id where push events put their state, so
+/// clients number their requests from here, past every state there is and
+/// room for more.
+pub const FIRST_REQUEST_ID: u32 = 64;
+
+const _: () = assert!(FIRST_REQUEST_ID > State::MAX);
@@ -1,6 +1,7 @@
 use std::error::Error;
 
 use crate::frame::RequestData;
+use crate::model::control::connect::Scope;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum Operation {
@@ -18,6 +19,231 @@ pub enum Operation {
     GetNewCard,
     Cancel,
     Exit,
+    PauseGame,
+    ResumeGame,
+    GetHint,
+    GetPlayerStats,
+    StartDailyChallenge,
+    GetDailyLeaderboard,
+    SendChat,
+    ReportPlayer,
+    MutePlayer,
+    ListSupportedOpcodes,
+    UploadDictionary,
+    Resign,
+    OfferDraw,
+    AnswerDraw,
+    JoinByCode,
+    QuickJoin,
+    UpdateProfile,
+    BlockPlayer,
+    KickFromLobby,
+    ServerInfo,
+    GetSeasonInfo,
+    Spectate,
+    ExportGame,
+    AnalyzeGame,
+    UpdateLobbySettings,
+    StartSolo,
+    SendQuickChat,
+    TimeSync,
+    SetPreferences,
+    BoardSnapshot,
+    ResyncFrom,
+    GetBagInfo,
+    ExchangeCards,
+    StartVoteKick,
+    AnswerVoteKick,
+    SubscribeGame,
+    AdminListPlayers,
+    AdminKick,
+    AdminBan,
+    AdminAnnounce,
+    AdminMaintenance,
+    AdminInspect,
+    AdminCloseGame,
+    AdminReload,
+    AdminIssueToken,
+    AdminRevokeToken,
+    AdminHandoff,
+}
+
+impl Operation {
+    /// The operation named `name`, as it's written in logs and the config,
+    /// such as `FinishTurn`.
+    pub fn from_name(name: &str) -> Option<Operation> {
+        (0..=u8::MAX)
+            .filter_map(|code| Operation::try_from(code).ok())
+            .find(|operation| format!("{:?}", operation) == name)
+    }
+
+    /// Whether the request can change what the server holds, as opposed to
+    /// only reading it. These are the requests the audit log keeps.
+    pub fn is_state_changing(&self) -> bool {
+        !matches!(
+            self,
+            Operation::Heartbeat
+                | Operation::ListLobby
+                | Operation::GetPlayerStats
+                | Operation::GetDailyLeaderboard
+                | Operation::SendChat
+                | Operation::SendQuickChat
+                | Operation::TimeSync
+                | Operation::SetPreferences
+                | Operation::BoardSnapshot
+                | Operation::ResyncFrom
+                | Operation::GetBagInfo
+                | Operation::ListSupportedOpcodes
+                | Operation::ServerInfo
+                | Operation::GetSeasonInfo
+                | Operation::ExportGame
+                | Operation::AnalyzeGame
+                | Operation::AdminListPlayers
+                | Operation::AdminInspect
+        )
+    }
+
+    /// Whether the client has to have connected first. Admin requests carry
+    /// their own token, and server browsers ask for the info without
+    /// joining.
+    pub fn needs_session(&self) -> bool {
+        !matches!(
+            self,
+            Operation::Connect
+                | Operation::ServerInfo
+                | Operation::AdminListPlayers
+                | Operation::AdminKick
+                | Operation::AdminBan
+                | Operation::AdminAnnounce
+                | Operation::AdminMaintenance
+                | Operation::AdminInspect
+                | Operation::AdminCloseGame
+                | Operation::AdminReload
+                | Operation::AdminIssueToken
+                | Operation::AdminRevokeToken
+                | Operation::AdminHandoff
+        )
+    }
+
+    /// Scope bits a session needs for the request. Admin requests from
+    /// clients that never connected are left to their token.
+    pub fn required_scopes(&self) -> u64 {
+        match self {
+            Operation::Connect
+            | Operation::Disconnect
+            | Operation::Heartbeat
+            | Operation::TimeSync
+            | Operation::SetPreferences
+            | Operation::ListSupportedOpcodes
+            | Operation::ServerInfo
+            | Operation::GetSeasonInfo
+            | Operation::GetPlayerStats
+            | Operation::GetDailyLeaderboard
+            | Operation::BoardSnapshot
+            | Operation::ResyncFrom
+            | Operation::GetBagInfo
+            | Operation::ExportGame
+            | Operation::AnalyzeGame => 0,
+            Operation::Spectate => Scope::Spectate as u64,
+            Operation::SubscribeGame => Scope::Observe as u64,
+            Operation::AdminListPlayers
+            | Operation::AdminKick
+            | Operation::AdminBan
+            | Operation::AdminAnnounce
+            | Operation::AdminMaintenance
+            | Operation::AdminInspect
+            | Operation::AdminCloseGame
+            | Operation::AdminReload
+            | Operation::AdminIssueToken
+            | Operation::AdminRevokeToken
+            | Operation::AdminHandoff => Scope::Admin as u64,
+            _ => Scope::Play as u64,
+        }
+    }
+
+    /// Largest payload a request of this operation needs. Longer ones are
+    /// refused before they are decoded.
+    pub fn max_payload_len(&self) -> usize {
+        match self {
+            // A full chunk of words as long as the board, with room to spare
+            // for whitespace the server trims
+            Operation::UploadDictionary => 64 << 10,
+            // Free text, up to a few hundred characters of up to 4 bytes
+            Operation::SendChat
+            | Operation::ReportPlayer
+            | Operation::AdminAnnounce
+            | Operation::AdminMaintenance => 4 << 10,
+            _ => 1 << 10,
+        }
+    }
+}
+
+impl From<&Operation> for u8 {
+    fn from(value: &Operation) -> Self {
+        match value {
+            Operation::Connect => 0,
+            Operation::Disconnect => 1,
+            Operation::Heartbeat => 2,
+            Operation::CreateLobby => 3,
+            Operation::JoinLobby => 4,
+            Operation::QuitLobby => 5,
+            Operation::ListLobby => 6,
+            Operation::Ready => 7,
+            Operation::StartGame => 8,
+            Operation::SetTile => 9,
+            Operation::FinishTurn => 10,
+            Operation::GetNewCard => 11,
+            Operation::Cancel => 12,
+            Operation::Exit => 13,
+            Operation::PauseGame => 14,
+            Operation::ResumeGame => 15,
+            Operation::GetHint => 16,
+            Operation::GetPlayerStats => 17,
+            Operation::StartDailyChallenge => 18,
+            Operation::GetDailyLeaderboard => 19,
+            Operation::SendChat => 20,
+            Operation::ReportPlayer => 21,
+            Operation::MutePlayer => 22,
+            Operation::ListSupportedOpcodes => 23,
+            Operation::UploadDictionary => 24,
+            Operation::Resign => 25,
+            Operation::OfferDraw => 26,
+            Operation::AnswerDraw => 27,
+            Operation::JoinByCode => 28,
+            Operation::QuickJoin => 29,
+            Operation::UpdateProfile => 30,
+            Operation::BlockPlayer => 31,
+            Operation::KickFromLobby => 32,
+            Operation::ServerInfo => 33,
+            Operation::GetSeasonInfo => 34,
+            Operation::Spectate => 35,
+            Operation::ExportGame => 36,
+            Operation::AnalyzeGame => 37,
+            Operation::UpdateLobbySettings => 38,
+            Operation::StartSolo => 39,
+            Operation::SendQuickChat => 40,
+            Operation::TimeSync => 41,
+            Operation::SetPreferences => 42,
+            Operation::BoardSnapshot => 43,
+            Operation::ResyncFrom => 44,
+            Operation::GetBagInfo => 45,
+            Operation::ExchangeCards => 46,
+            Operation::StartVoteKick => 47,
+            Operation::AnswerVoteKick => 48,
+            Operation::SubscribeGame => 49,
+            Operation::AdminListPlayers => 128,
+            Operation::AdminKick => 129,
+            Operation::AdminBan => 130,
+            Operation::AdminAnnounce => 131,
+            Operation::AdminMaintenance => 132,
+            Operation::AdminInspect => 133,
+            Operation::AdminCloseGame => 134,
+            Operation::AdminReload => 135,
+            Operation::AdminIssueToken => 136,
+            Operation::AdminRevokeToken => 137,
+            Operation::AdminHandoff => 138,
+        }
+    }
 }
 
 impl TryFrom<u8> for Operation {
@@ -38,6 +264,53 @@ impl TryFrom<u8> for Operation {
             11 => Ok(Operation::GetNewCard),
             12 => Ok(Operation::Cancel),
             13 => Ok(Operation::Exit),
+            14 => Ok(Operation::PauseGame),
+            15 => Ok(Operation::ResumeGame),
+            16 => Ok(Operation::GetHint),
+            17 => Ok(Operation::GetPlayerStats),
+            18 => Ok(Operation::StartDailyChallenge),
+            19 => Ok(Operation::GetDailyLeaderboard),
+            20 => Ok(Operation::SendChat),
+            21 => Ok(Operation::ReportPlayer),
+            22 => Ok(Operation::MutePlayer),
+            23 => Ok(Operation::ListSupportedOpcodes),
+            24 => Ok(Operation::UploadDictionary),
+            25 => Ok(Operation::Resign),
+            26 => Ok(Operation::OfferDraw),
+            27 => Ok(Operation::AnswerDraw),
+            28 => Ok(Operation::JoinByCode),
+            29 => Ok(Operation::QuickJoin),
+            30 => Ok(Operation::UpdateProfile),
+            31 => Ok(Operation::BlockPlayer),
+            32 => Ok(Operation::KickFromLobby),
+            33 => Ok(Operation::ServerInfo),
+            34 => Ok(Operation::GetSeasonInfo),
+            35 => Ok(Operation::Spectate),
+            36 => Ok(Operation::ExportGame),
+            37 => Ok(Operation::AnalyzeGame),
+            38 => Ok(Operation::UpdateLobbySettings),
+            39 => Ok(Operation::StartSolo),
+            40 => Ok(Operation::SendQuickChat),
+            41 => Ok(Operation::TimeSync),
+            42 => Ok(Operation::SetPreferences),
+            43 => Ok(Operation::BoardSnapshot),
+            44 => Ok(Operation::ResyncFrom),
+            45 => Ok(Operation::GetBagInfo),
+            46 => Ok(Operation::ExchangeCards),
+            47 => Ok(Operation::StartVoteKick),
+            48 => Ok(Operation::AnswerVoteKick),
+            49 => Ok(Operation::SubscribeGame),
+            128 => Ok(Operation::AdminListPlayers),
+            129 => Ok(Operation::AdminKick),
+            130 => Ok(Operation::AdminBan),
+            131 => Ok(Operation::AdminAnnounce),
+            132 => Ok(Operation::AdminMaintenance),
+            133 => Ok(Operation::AdminInspect),
+            134 => Ok(Operation::AdminCloseGame),
+            135 => Ok(Operation::AdminReload),
+            136 => Ok(Operation::AdminIssueToken),
+            137 => Ok(Operation::AdminRevokeToken),
+            138 => Ok(Operation::AdminHandoff),
             _ => Err("invalid operation".into()),
         }
     }
@@ -49,7 +322,7 @@ impl TryFrom<&RequestData> for Operation {
         match value {
             RequestData::Connect(_) => Ok(Operation::Connect),
             RequestData::Disconnect => Ok(Operation::Disconnect),
-            RequestData::Heartbeat => Ok(Operation::Heartbeat),
+            RequestData::Heartbeat(_) => Ok(Operation::Heartbeat),
             RequestData::CreateLobby(_) => Ok(Operation::CreateLobby),
             RequestData::JoinLobby(_) => Ok(Operation::JoinLobby),
             RequestData::QuitLobby => Ok(Operation::QuitLobby),
@@ -61,6 +334,53 @@ impl TryFrom<&RequestData> for Operation {
             RequestData::GetNewCard => Ok(Operation::GetNewCard),
             RequestData::Cancel(_) => Ok(Operation::Cancel),
             RequestData::Exit => Ok(Operation::Exit),
+            RequestData::PauseGame => Ok(Operation::PauseGame),
+            RequestData::ResumeGame => Ok(Operation::ResumeGame),
+            RequestData::GetHint(_) => Ok(Operation::GetHint),
+            RequestData::GetPlayerStats(_) => Ok(Operation::GetPlayerStats),
+            RequestData::StartDailyChallenge => Ok(Operation::StartDailyChallenge),
+            RequestData::GetDailyLeaderboard(_) => Ok(Operation::GetDailyLeaderboard),
+            RequestData::SendChat(_) => Ok(Operation::SendChat),
+            RequestData::ReportPlayer(_) => Ok(Operation::ReportPlayer),
+            RequestData::MutePlayer(_) => Ok(Operation::MutePlayer),
+            RequestData::ListSupportedOpcodes => Ok(Operation::ListSupportedOpcodes),
+            RequestData::UploadDictionary(_) => Ok(Operation::UploadDictionary),
+            RequestData::Resign => Ok(Operation::Resign),
+            RequestData::OfferDraw => Ok(Operation::OfferDraw),
+            RequestData::AnswerDraw(_) => Ok(Operation::AnswerDraw),
+            RequestData::JoinByCode(_) => Ok(Operation::JoinByCode),
+            RequestData::QuickJoin => Ok(Operation::QuickJoin),
+            RequestData::UpdateProfile(_) => Ok(Operation::UpdateProfile),
+            RequestData::BlockPlayer(_) => Ok(Operation::BlockPlayer),
+            RequestData::KickFromLobby(_) => Ok(Operation::KickFromLobby),
+            RequestData::ServerInfo => Ok(Operation::ServerInfo),
+            RequestData::GetSeasonInfo(_) => Ok(Operation::GetSeasonInfo),
+            RequestData::Spectate(_) => Ok(Operation::Spectate),
+            RequestData::ExportGame(_) => Ok(Operation::ExportGame),
+            RequestData::AnalyzeGame(_) => Ok(Operation::AnalyzeGame),
+            RequestData::UpdateLobbySettings(_) => Ok(Operation::UpdateLobbySettings),
+            RequestData::StartSolo => Ok(Operation::StartSolo),
+            RequestData::SendQuickChat(_) => Ok(Operation::SendQuickChat),
+            RequestData::TimeSync(_) => Ok(Operation::TimeSync),
+            RequestData::SetPreferences(_) => Ok(Operation::SetPreferences),
+            RequestData::BoardSnapshot => Ok(Operation::BoardSnapshot),
+            RequestData::ResyncFrom(_) => Ok(Operation::ResyncFrom),
+            RequestData::GetBagInfo => Ok(Operation::GetBagInfo),
+            RequestData::ExchangeCards(_) => Ok(Operation::ExchangeCards),
+            RequestData::StartVoteKick(_) => Ok(Operation::StartVoteKick),
+            RequestData::AnswerVoteKick(_) => Ok(Operation::AnswerVoteKick),
+            RequestData::SubscribeGame(_) => Ok(Operation::SubscribeGame),
+            RequestData::AdminListPlayers(_) => Ok(Operation::AdminListPlayers),
+            RequestData::AdminKick(_) => Ok(Operation::AdminKick),
+            RequestData::AdminBan(_) => Ok(Operation::AdminBan),
+            RequestData::AdminAnnounce(_) => Ok(Operation::AdminAnnounce),
+            RequestData::AdminMaintenance(_) => Ok(Operation::AdminMaintenance),
+            RequestData::AdminInspect(_) => Ok(Operation::AdminInspect),
+            RequestData::AdminCloseGame(_) => Ok(Operation::AdminCloseGame),
+            RequestData::AdminReload(_) => Ok(Operation::AdminReload),
+            RequestData::AdminIssueToken(_) => Ok(Operation::AdminIssueToken),
+            RequestData::AdminRevokeToken(_) => Ok(Operation::AdminRevokeToken),
+            RequestData::AdminHandoff(_) => Ok(Operation::AdminHandoff),
             // _ => Err("invalid request".into()),
         }
     }
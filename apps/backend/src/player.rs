@@ -1,21 +1,40 @@
-#[cfg(not(test))]
-use crate::frame::Frame;
-#[cfg(not(test))]
-use crate::frame::Response;
-use crate::{game::game::Game, lobby::lobby::Lobby};
+use crate::{game::game::Game, lobby::lobby::Lobby, service::profile_service::Profile};
 use core::hash::{Hash, Hasher};
-use std::sync::{Arc, Mutex};
-#[cfg(not(test))]
-use tokio::sync::mpsc::Sender;
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex, Weak},
+};
+
+/// Round trips a player's average latency is taken over.
+const RTT_SAMPLES: usize = 8;
+
+#[derive(Debug, Default)]
+struct Latency {
+    samples: VecDeque<u32>,
+    /// Whether the average was over the warning threshold after the last
+    /// sample, so a slow connection is only reported once.
+    degraded: bool,
+}
 
 #[derive(Debug)]
 pub struct Player {
     pub id: u32,
     pub name: String,
-    #[cfg(not(test))]
-    sender: Sender<Frame>,
     lobby: Mutex<Option<Arc<Lobby>>>,
     game: Mutex<Option<Arc<Game>>>,
+    /// Game the player watches, which they don't keep around once it ends.
+    spectating: Mutex<Weak<Game>>,
+    /// Game the session follows with `SubscribeGame`, held the same way.
+    subscribed: Mutex<Weak<Game>>,
+    /// Ids of players whose chat isn't delivered to this one.
+    muted: Mutex<HashSet<u32>>,
+    /// Names of accounts this one blocked. Their chat isn't delivered to it
+    /// and they can't join lobbies it leads.
+    blocked: Mutex<HashSet<String>>,
+    latency: Mutex<Latency>,
+    /// Invalid moves sent on this connection.
+    violations: Mutex<u32>,
+    profile: Mutex<Profile>,
 }
 
 impl PartialEq for Player {
@@ -33,14 +52,19 @@ impl Hash for Player {
 }
 
 impl Player {
-    pub fn new(id: u32, name: String, #[cfg(not(test))] sender: Sender<Frame>) -> Self {
+    pub fn new(id: u32, name: String) -> Self {
         Player {
             id,
             name,
-            #[cfg(not(test))]
-            sender,
             lobby: Mutex::new(None),
             game: Mutex::new(None),
+            spectating: Mutex::new(Weak::new()),
+            subscribed: Mutex::new(Weak::new()),
+            muted: Mutex::new(HashSet::new()),
+            blocked: Mutex::new(HashSet::new()),
+            latency: Mutex::new(Latency::default()),
+            violations: Mutex::new(0),
+            profile: Mutex::new(Profile::default()),
         }
     }
 
@@ -60,14 +84,87 @@ impl Player {
         *self.game.lock().unwrap() = game;
     }
 
-    #[cfg(not(test))]
-    pub async fn send_message(
-        &self,
-        res: Response,
-    ) -> Result<(), tokio::sync::mpsc::error::SendError<Frame>> {
-        match self.sender.send(Frame::Response(res)).await {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e),
+    pub fn get_spectating(&self) -> Option<Arc<Game>> {
+        self.spectating.lock().unwrap().upgrade()
+    }
+
+    pub fn set_spectating(&self, game: Option<&Arc<Game>>) {
+        *self.spectating.lock().unwrap() = game.map_or_else(Weak::new, Arc::downgrade);
+    }
+
+    pub fn get_subscribed(&self) -> Option<Arc<Game>> {
+        self.subscribed.lock().unwrap().upgrade()
+    }
+
+    pub fn set_subscribed(&self, game: Option<&Arc<Game>>) {
+        *self.subscribed.lock().unwrap() = game.map_or_else(Weak::new, Arc::downgrade);
+    }
+
+    pub fn get_profile(&self) -> Profile {
+        *self.profile.lock().unwrap()
+    }
+
+    pub fn set_profile(&self, profile: Profile) {
+        *self.profile.lock().unwrap() = profile;
+    }
+
+    pub fn set_muted(&self, player_id: u32, muted: bool) {
+        let mut list = self.muted.lock().unwrap();
+        match muted {
+            true => list.insert(player_id),
+            false => list.remove(&player_id),
+        };
+    }
+
+    pub fn has_muted(&self, player_id: u32) -> bool {
+        self.muted.lock().unwrap().contains(&player_id)
+    }
+
+    pub fn set_blocked(&self, name: &str, blocked: bool) {
+        let mut list = self.blocked.lock().unwrap();
+        match blocked {
+            true => list.insert(name.to_string()),
+            false => list.remove(name),
+        };
+    }
+
+    pub fn has_blocked(&self, name: &str) -> bool {
+        self.blocked.lock().unwrap().contains(name)
+    }
+
+    pub fn get_blocked(&self) -> HashSet<String> {
+        self.blocked.lock().unwrap().clone()
+    }
+
+    /// Record a heartbeat round trip the player's client timed. Returns
+    /// whether the average just went over `degraded_ms`, 0 to never report
+    /// it.
+    pub fn record_rtt(&self, rtt_ms: u32, degraded_ms: u32) -> bool {
+        let mut latency = self.latency.lock().unwrap();
+        if latency.samples.len() == RTT_SAMPLES {
+            latency.samples.pop_front();
         }
+        latency.samples.push_back(rtt_ms);
+        let degraded = degraded_ms > 0 && Self::average(&latency.samples) > degraded_ms;
+        let newly_degraded = degraded && !latency.degraded;
+        latency.degraded = degraded;
+        newly_degraded
+    }
+
+    pub fn get_average_rtt(&self) -> Option<u32> {
+        let latency = self.latency.lock().unwrap();
+        Some(Self::average(&latency.samples)).filter(|_| !latency.samples.is_empty())
+    }
+
+    /// Count an invalid move, returning how many the player has sent.
+    pub fn record_violation(&self) -> u32 {
+        let mut violations = self.violations.lock().unwrap();
+        *violations += 1;
+        *violations
+    }
+
+    fn average(samples: &VecDeque<u32>) -> u32 {
+        let sum: u64 = samples.iter().map(|&rtt| rtt as u64).sum();
+        (sum / samples.len().max(1) as u64) as u32
     }
 }
@@ -0,0 +1,169 @@
+use std::{
+    io::{self, ErrorKind},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// How long a load balancer has to send the header once it's connected.
+pub const HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest a version 1 header can be, `\r\n` included.
+const V1_MAX_LEN: usize = 107;
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Read the PROXY protocol header, version 1 or 2, a load balancer sends
+/// before anything from the client, leaving the stream at the client's first
+/// byte. Returns the client's address, or `None` when the balancer connected
+/// on its own behalf (a health check) or doesn't know it.
+pub async fn read_header<R: AsyncRead + Unpin>(stream: &mut R) -> io::Result<Option<SocketAddr>> {
+    let mut start = [0; 5];
+    stream.read_exact(&mut start).await?;
+    if &start == b"PROXY" {
+        read_v1(stream).await
+    } else if start == V2_SIGNATURE[..5] {
+        read_v2(stream).await
+    } else {
+        Err(invalid("connection doesn't start with a PROXY header"))
+    }
+}
+
+/// `PROXY TCP4 <source> <destination> <source port> <destination port>\r\n`,
+/// after the `PROXY` already read. Read a byte at a time so nothing past the
+/// line is taken off the stream.
+async fn read_v1<R: AsyncRead + Unpin>(stream: &mut R) -> io::Result<Option<SocketAddr>> {
+    let mut line = Vec::new();
+    while !line.ends_with(b"\r\n") {
+        if line.len() + 5 >= V1_MAX_LEN {
+            return Err(invalid("PROXY header is too long"));
+        }
+        line.push(stream.read_u8().await?);
+    }
+    let line = std::str::from_utf8(&line[..line.len() - 2])
+        .map_err(|_| invalid("PROXY header isn't text"))?;
+    let fields: Vec<&str> = line.split(' ').collect();
+    match fields[..] {
+        ["", "UNKNOWN", ..] => Ok(None),
+        ["", "TCP4" | "TCP6", source, _, port, _] => {
+            let ip = source
+                .parse::<IpAddr>()
+                .map_err(|_| invalid("PROXY header has a bad source address"))?;
+            let port = port
+                .parse::<u16>()
+                .map_err(|_| invalid("PROXY header has a bad source port"))?;
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        _ => Err(invalid("PROXY header is malformed")),
+    }
+}
+
+/// The binary header, after the first five bytes of its signature. Anything
+/// past the addresses, such as TLVs, is read and ignored.
+async fn read_v2<R: AsyncRead + Unpin>(stream: &mut R) -> io::Result<Option<SocketAddr>> {
+    let mut header = [0; 11];
+    stream.read_exact(&mut header).await?;
+    if header[..7] != V2_SIGNATURE[5..] {
+        return Err(invalid("connection doesn't start with a PROXY header"));
+    }
+    let (version_command, family) = (header[7], header[8]);
+    if version_command >> 4 != 2 {
+        return Err(invalid("unsupported PROXY header version"));
+    }
+    let mut body = vec![0; u16::from_be_bytes([header[9], header[10]]) as usize];
+    stream.read_exact(&mut body).await?;
+    match version_command & 0x0f {
+        // LOCAL
+        0 => return Ok(None),
+        // PROXY
+        1 => {}
+        _ => return Err(invalid("unsupported PROXY header command")),
+    }
+    let source = match family >> 4 {
+        1 if body.len() >= 12 => {
+            let ip = Ipv4Addr::from([body[0], body[1], body[2], body[3]]);
+            SocketAddr::new(ip.into(), u16::from_be_bytes([body[8], body[9]]))
+        }
+        2 if body.len() >= 36 => {
+            let mut ip = [0; 16];
+            ip.copy_from_slice(&body[..16]);
+            SocketAddr::new(
+                Ipv6Addr::from(ip).into(),
+                u16::from_be_bytes([body[32], body[33]]),
+            )
+        }
+        1 | 2 => return Err(invalid("PROXY header addresses are cut short")),
+        // Unspecified or a Unix socket, neither says where the client is
+        _ => return Ok(None),
+    };
+    Ok(Some(source))
+}
+
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    fn v2_header(command: u8, family: u8, body: &[u8]) -> Vec<u8> {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.push(0x20 | command);
+        header.push(family);
+        header.extend((body.len() as u16).to_be_bytes());
+        header.extend(body);
+        header
+    }
+
+    #[tokio::test]
+    async fn read_header_with_v1_should_return_source_and_leave_rest(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut stream = &b"PROXY TCP4 203.0.113.7 10.0.0.1 51234 45678\r\nrest"[..];
+        let source = read_header(&mut stream).await?;
+        assert_eq!(source, Some("203.0.113.7:51234".parse()?));
+        assert_eq!(stream, b"rest");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_header_with_v1_unknown_should_return_none(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut stream = &b"PROXY UNKNOWN\r\n"[..];
+        assert_eq!(read_header(&mut stream).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_header_with_v2_ipv6_should_skip_tlvs() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let mut body = Vec::new();
+        body.extend("2001:db8::7".parse::<Ipv6Addr>()?.octets());
+        body.extend("2001:db8::1".parse::<Ipv6Addr>()?.octets());
+        body.extend(51234u16.to_be_bytes());
+        body.extend(45678u16.to_be_bytes());
+        body.extend([0x04, 0x00, 0x01, 0xff]);
+        let mut bytes = v2_header(1, 0x21, &body);
+        bytes.extend(b"rest");
+        let mut stream = &bytes[..];
+        let source = read_header(&mut stream).await?;
+        assert_eq!(source, Some("[2001:db8::7]:51234".parse()?));
+        assert_eq!(stream, b"rest");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_header_with_v2_local_should_return_none(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let bytes = v2_header(0, 0x00, &[]);
+        assert_eq!(read_header(&mut &bytes[..]).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_header_without_header_should_return_error() {
+        let mut stream = &b"\x01\x00\x00\x00\x04\x00\x00\x00"[..];
+        assert!(read_header(&mut stream).await.is_err());
+    }
+}
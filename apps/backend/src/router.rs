@@ -1,96 +1,406 @@
 use crate::controller::controller::PrintableController;
+use crate::error::ServerError;
 use crate::frame::{Request, ResponseData};
+use crate::model::control::opcodes::{ListSupportedOpcodesResponse, Opcode};
 use crate::operation::Operation;
-use std::collections::HashMap;
+use crate::validation;
 use std::error::Error;
-use std::fmt::Debug;
-use std::sync::{Arc, RwLock};
+use std::fmt::{self, Debug};
 
 #[cfg(not(test))]
 use crate::frame::Frame;
 #[cfg(not(test))]
 use tokio::sync::mpsc::Sender;
 
-#[derive(Debug)]
+/// Controllers are registered once while the server is built and only read
+/// afterwards, so routing takes no lock. They sit in a table indexed by
+/// opcode.
 pub struct Router {
-    controllers: Arc<RwLock<HashMap<Operation, Box<dyn PrintableController>>>>,
+    controllers: Vec<Option<Box<dyn PrintableController>>>,
+}
+
+impl Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Router")
+            .field("operations", &self.get_operations())
+            .finish()
+    }
 }
 
 #[derive(Debug)]
 pub struct RequestContext {
     pub client_id: u32,
+    /// Tells apart the logs of one request, and of the events it fans out,
+    /// from those of any other on any instance. The request's span carries
+    /// it, so services logging while it is handled, and the broadcast tasks
+    /// they spawn, don't need it passed.
+    pub trace_id: u64,
     #[cfg(not(test))]
     pub sender: Sender<Frame>,
 }
 
+impl RequestContext {
+    /// Context for a request just read from the client, with a fresh trace
+    /// id.
+    pub fn new(client_id: u32, #[cfg(not(test))] sender: Sender<Frame>) -> Self {
+        Self {
+            client_id,
+            trace_id: rand::random(),
+            #[cfg(not(test))]
+            sender,
+        }
+    }
+
+    /// The trace id as logs and the audit log write it.
+    pub fn get_trace_id(&self) -> String {
+        format!("{:016x}", self.trace_id)
+    }
+}
+
 impl Router {
     pub fn new() -> Router {
         Router {
-            controllers: Arc::new(RwLock::new(HashMap::new())),
+            controllers: (0..=u8::MAX).map(|_| None).collect(),
         }
     }
 
-    pub fn register_controller(
-        &self,
+    /// Fails if `operation` already has a controller, or is one the router
+    /// answers itself.
+    pub fn register(
+        &mut self,
         operation: Operation,
         controller: Box<dyn PrintableController>,
-    ) -> &Self {
-        self.controllers
-            .write()
-            .unwrap()
-            .insert(operation, controller);
-        self
+    ) -> Result<&mut Self, Box<dyn Error + Send + Sync>> {
+        if operation == Operation::ListSupportedOpcodes {
+            return Err(format!("{:?} is answered by the router", operation).into());
+        }
+        let slot = &mut self.controllers[u8::from(&operation) as usize];
+        if slot.is_some() {
+            return Err(format!("duplicate controller for {:?}", operation).into());
+        }
+        *slot = Some(controller);
+        Ok(self)
+    }
+
+    /// Operations with a controller, plus the ones the router answers itself,
+    /// by opcode.
+    pub fn get_operations(&self) -> Vec<Operation> {
+        let mut operations: Vec<Operation> = self
+            .controllers
+            .iter()
+            .enumerate()
+            .filter(|(_, controller)| controller.is_some())
+            .filter_map(|(code, _)| Operation::try_from(code as u8).ok())
+            .chain([Operation::ListSupportedOpcodes])
+            .collect();
+        operations.sort_by_key(|operation| u8::from(operation));
+        operations
     }
 
+    /// Hand `request` to its controller if the client's session, by its
+    /// scope bits, may make it. Before the client connects there's no
+    /// session, and only requests that don't need one get that far.
     pub fn route(
         &self,
         request: Request,
         context: RequestContext,
+        session: Option<u64>,
     ) -> Result<ResponseData, Box<dyn Error + Sync + Send>> {
-        match self
-            .controllers
-            .read()
-            .unwrap()
-            .get(&Operation::try_from(request.get_data().as_ref())?)
-        {
-            Some(controller) => match controller.handle_request(request, context) {
-                Ok(response) => Ok(response),
-                Err(err) => Ok(ResponseData::Error(crate::model::error::error::Error {
-                    message: err.to_string(),
-                })),
-            },
-            None => Err(format!("no controller for request {:?}", request).into()),
+        let operation = Operation::try_from(request.get_data().as_ref())?;
+        let required = operation.required_scopes();
+        match session {
+            None if operation.needs_session() => {
+                return Ok(error_response(ServerError::Unauthenticated))
+            }
+            Some(scopes) if scopes & required != required => {
+                return Ok(error_response(ServerError::Forbidden))
+            }
+            _ => {}
         }
+        if operation == Operation::ListSupportedOpcodes {
+            return Ok(ResponseData::ListSupportedOpcodes(
+                ListSupportedOpcodesResponse {
+                    success: true,
+                    opcodes: self
+                        .get_operations()
+                        .iter()
+                        .map(|operation| Opcode {
+                            code: u8::from(operation) as u32,
+                            name: format!("{:?}", operation),
+                        })
+                        .collect(),
+                },
+            ));
+        }
+        let controller = match &self.controllers[u8::from(&operation) as usize] {
+            Some(controller) => controller,
+            None => return Ok(error_response(ServerError::UnsupportedOperation)),
+        };
+        // Checked here once, so controllers can trust every field's bounds
+        let result = validation::validate(request.get_data().as_ref())
+            .map_err(ServerError::from)
+            .and_then(|()| controller.handle_request(request, context));
+        Ok(result.unwrap_or_else(error_response))
     }
 }
 
+fn error_response(err: ServerError) -> ResponseData {
+    ResponseData::Error(crate::model::error::error::Error {
+        message: err.to_string(),
+        code: err.code() as i32,
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::{collections::HashSet, sync::Arc, thread};
 
     use crate::{
         controller::control::connect::ConnectController,
+        frame::RequestData,
+        model::{
+            control::connect::{ConnectRequest, Scope},
+            error::error::ErrorCode,
+            game::subscribe::SubscribeGameRequest,
+        },
         service::{
-            game_service::GameService, lobby_service::LobbyService, player_service::PlayerService,
+            admin_service::AdminService, game_service::GameService, lobby_service::LobbyService,
+            player_service::PlayerService,
         },
     };
 
     use super::*;
 
+    fn connect_controller() -> Box<ConnectController> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        Box::new(ConnectController::new(
+            player_service.clone(),
+            Arc::new(AdminService::new(None, player_service)),
+        ))
+    }
+
     #[test]
-    fn register_controller_with_controller_and_operation_controller_should_be_added() {
-        let router = Router::new();
-        router.register_controller(
-            Operation::Connect,
-            Box::new(ConnectController::new(Arc::new(PlayerService::new(
-                Arc::new(LobbyService::new()),
-                Arc::new(GameService::new(HashSet::new())),
-            )))),
+    fn register_with_controller_and_operation_controller_should_be_added(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut router = Router::new();
+        router.register(Operation::Connect, connect_controller())?;
+        assert!(router.controllers[0].is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn new_request_context_should_get_its_own_trace_id() {
+        let first = RequestContext::new(0);
+        let second = RequestContext::new(0);
+        assert_ne!(first.trace_id, second.trace_id);
+        assert_eq!(first.get_trace_id().len(), 16);
+        assert_eq!(
+            u64::from_str_radix(&first.get_trace_id(), 16),
+            Ok(first.trace_id)
         );
+    }
+
+    #[test]
+    fn register_with_duplicate_operation_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut router = Router::new();
+        router.register(Operation::Connect, connect_controller())?;
         assert!(router
-            .controllers
-            .read()
-            .unwrap()
-            .contains_key(&Operation::Connect));
+            .register(Operation::Connect, connect_controller())
+            .is_err());
+        assert!(router
+            .register(Operation::ListSupportedOpcodes, connect_controller())
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn route_with_list_supported_opcodes_should_list_registered_operations(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut router = Router::new();
+        router.register(Operation::Connect, connect_controller())?;
+        let res = router.route(
+            Request::new(0, Arc::new(RequestData::ListSupportedOpcodes)),
+            RequestContext::new(0),
+            Some(Scope::INTERACTIVE),
+        )?;
+        assert_eq!(
+            res,
+            ResponseData::ListSupportedOpcodes(ListSupportedOpcodesResponse {
+                success: true,
+                opcodes: vec![
+                    Opcode {
+                        code: 0,
+                        name: String::from("Connect"),
+                    },
+                    Opcode {
+                        code: 23,
+                        name: String::from("ListSupportedOpcodes"),
+                    },
+                ],
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn route_before_connecting_should_only_accept_connect(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut router = Router::new();
+        router.register(Operation::Connect, connect_controller())?;
+        let res = router.route(
+            Request::new(0, Arc::new(RequestData::ListSupportedOpcodes)),
+            RequestContext::new(0),
+            None,
+        )?;
+        match res {
+            ResponseData::Error(error) => assert_eq!(error.code(), ErrorCode::Unauthenticated),
+            _ => panic!("invalid response"),
+        }
+        let res = router.route(
+            Request::new(
+                0,
+                Arc::new(RequestData::Connect(ConnectRequest {
+                    name: String::from("test"),
+                    ..Default::default()
+                })),
+            ),
+            RequestContext::new(0),
+            None,
+        )?;
+        assert!(matches!(res, ResponseData::Connect(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn route_outside_session_scopes_should_return_forbidden(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let router = Router::new();
+        let res = router.route(
+            Request::new(0, Arc::new(RequestData::Ready)),
+            RequestContext::new(0),
+            Some(Scope::Spectate as u64),
+        )?;
+        match res {
+            ResponseData::Error(error) => assert_eq!(error.code(), ErrorCode::Forbidden),
+            _ => panic!("invalid response"),
+        }
+        // Following games from outside takes a token that grants it
+        let res = router.route(
+            Request::new(
+                0,
+                Arc::new(RequestData::SubscribeGame(SubscribeGameRequest {
+                    game_id: Some(0),
+                })),
+            ),
+            RequestContext::new(0),
+            Some(Scope::INTERACTIVE),
+        )?;
+        match res {
+            ResponseData::Error(error) => assert_eq!(error.code(), ErrorCode::Forbidden),
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn route_without_controller_should_return_unsupported_operation(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let router = Router::new();
+        let res = router.route(
+            Request::new(0, Arc::new(RequestData::Ready)),
+            RequestContext::new(0),
+            Some(Scope::INTERACTIVE),
+        )?;
+        match res {
+            ResponseData::Error(error) => {
+                assert_eq!(error.code(), ErrorCode::UnsupportedOperation)
+            }
+            _ => panic!("invalid response"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn route_with_invalid_field_should_return_error_without_calling_controller(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        let mut router = Router::new();
+        router.register(
+            Operation::Connect,
+            Box::new(ConnectController::new(
+                player_service.clone(),
+                Arc::new(AdminService::new(None, player_service.clone())),
+            )),
+        )?;
+        let res = router.route(
+            Request::new(
+                0,
+                Arc::new(RequestData::Connect(ConnectRequest {
+                    name: String::from("test\n"),
+                    protocol_version: 0,
+                    compression: 0,
+                    capabilities: 0,
+                    token: String::new(),
+                })),
+            ),
+            RequestContext::new(0),
+            Some(Scope::INTERACTIVE),
+        )?;
+        match res {
+            ResponseData::Error(error) => assert_eq!(error.code(), ErrorCode::InvalidRequest),
+            _ => panic!("invalid response"),
+        }
+        assert!(player_service.get_players().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn route_from_multiple_threads_should_handle_every_request() {
+        let mut router = Router::new();
+        let player_service = Arc::new(PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        ));
+        router
+            .register(
+                Operation::Connect,
+                Box::new(ConnectController::new(
+                    player_service.clone(),
+                    Arc::new(AdminService::new(None, player_service.clone())),
+                )),
+            )
+            .unwrap();
+        let router = Arc::new(router);
+        thread::scope(|s| {
+            for client_id in 0..8 {
+                let router = router.clone();
+                s.spawn(move || {
+                    router
+                        .route(
+                            Request::new(
+                                client_id,
+                                Arc::new(RequestData::Connect(ConnectRequest {
+                                    name: format!("test{}", client_id),
+                                    protocol_version: 0,
+                                    compression: 0,
+                                    capabilities: 0,
+                                    token: String::new(),
+                                })),
+                            ),
+                            RequestContext::new(client_id),
+                            None,
+                        )
+                        .unwrap();
+                });
+            }
+        });
+        assert_eq!(player_service.get_players().len(), 8);
     }
 }
@@ -1,39 +1,125 @@
 use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::Arc;
 
-use crate::connection::Connection;
+#[cfg(feature = "http")]
+use crate::api::Api;
+use crate::config::{LiveConfig, ServerConfig};
+use crate::connection::{self, ConnectionReader, ConnectionWriter, Transport, MAX_BATCH_FRAMES};
 #[cfg(not(test))]
 use crate::controller::{
+    admin::{
+        announce::AnnounceController, ban::BanController, close_game::CloseGameController,
+        handoff::HandoffController, inspect::InspectController, issue_token::IssueTokenController,
+        kick::KickController, list_players::ListPlayersController,
+        maintenance::MaintenanceController, reload::ReloadController,
+        revoke_token::RevokeTokenController,
+    },
+    chat::{quick_chat::QuickChatController, send::SendController},
     control::{
         connect::ConnectController, disconnect::DisconnectController,
-        heartbeat::HeartbeatController,
+        heartbeat::HeartbeatController, preferences::PreferencesController,
+        server_info::ServerInfoController, time_sync::TimeSyncController,
     },
     game::{
-        cancel::CancelController, exit::ExitController, finish_turn::FinishTurnController,
-        get_new_card::GetNewCardController, set_tile::SetTileController, start::StartController,
+        analyze::AnalyzeGameController, answer_draw::AnswerDrawController,
+        answer_vote_kick::AnswerVoteKickController, bag_info::GetBagInfoController,
+        cancel::CancelController, daily::DailyController, exchange::ExchangeCardsController,
+        exit::ExitController, export::ExportGameController, finish_turn::FinishTurnController,
+        get_new_card::GetNewCardController, hint::HintController, offer_draw::OfferDrawController,
+        pause::PauseController, resign::ResignController, resume::ResumeController,
+        resync::ResyncController, set_tile::SetTileController, snapshot::BoardSnapshotController,
+        solo::SoloController, spectate::SpectateController, start::StartController,
+        start_vote_kick::StartVoteKickController, subscribe::SubscribeGameController,
     },
+    leaderboard::{daily::DailyLeaderboardController, season::SeasonInfoController},
     lobby::{
-        create::CreateController, join::JoinController, list::ListController, quit::QuitController,
-        ready::ReadyController,
+        create::CreateController, join::JoinController, join_by_code::JoinByCodeController,
+        kick::KickFromLobbyController, list::ListController, quick_join::QuickJoinController,
+        quit::QuitController, ready::ReadyController,
+        update_settings::UpdateLobbySettingsController,
+        upload_dictionary::UploadDictionaryController,
+    },
+    player::{
+        block::BlockController, mute::MuteController, profile::UpdateProfileController,
+        report::ReportController, stats::StatsController,
     },
 };
-use crate::frame::{Frame, Response};
-#[cfg(not(test))]
+use crate::frame::{Frame, Response, ResponseData};
+#[cfg(feature = "grpc")]
+use crate::gateway::Gateway;
+use crate::health::Health;
+use crate::latency::{self, SlowRequests};
+use crate::logging::panic_message;
+use crate::model::{error::error::ErrorCode, state::State};
 use crate::operation::Operation;
+use crate::proxy_protocol::{self, HEADER_TIMEOUT};
 use crate::router::{RequestContext, Router};
+#[cfg(feature = "http")]
+use crate::service::account_service::AccountService;
 use crate::service::player_service::PlayerService;
+use crate::service::{
+    admin_service::AdminService, audit_service::AuditService, game_service::GameService,
+};
 #[cfg(not(test))]
-use crate::service::{game_service::GameService, lobby_service::LobbyService};
-use tokio::net::TcpListener;
+use crate::service::{
+    block_service::BlockService,
+    broadcast_service::BroadcastService,
+    chat_service::ChatService,
+    cluster_service::{ClusterEvent, ClusterService},
+    dictionary_service::DictionaryService,
+    leaderboard_service::LeaderboardService,
+    lobby_service::LobbyService,
+    profile_service::ProfileService,
+    season_service::SeasonService,
+    stats_service::StatsService,
+};
+#[cfg(all(feature = "sql", not(test)))]
+use crate::storage::sql::SqlStorage;
+#[cfg(not(test))]
+use crate::storage::{memory::MemoryStorage, Storage};
+use dashmap::DashMap;
+use socket2::{Domain, SockRef, Socket, TcpKeepalive, Type};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+#[cfg(feature = "simulation")]
+use tokio::io::DuplexStream;
+use tokio::net::{lookup_host, TcpListener, TcpStream};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::mpsc::{channel, Receiver, Sender};
-use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+use tokio::time::{sleep, timeout};
+use tracing::{debug, error, field, info, info_span, warn, Instrument};
+
+/// Bytes an in-memory pipe holds in each direction before writes wait.
+#[cfg(feature = "simulation")]
+const IN_MEMORY_BUFFER_SIZE: usize = 64 * 1024;
 
-#[derive(Debug, Clone)]
+/// Global state shared by every connection task through an `Arc<Server>`.
+/// Per-connection state (client id, socket, outgoing queue) lives in the
+/// tasks spawned by `run` instead.
+#[derive(Debug)]
 pub struct Server {
-    host: String,
-    port: u32,
+    /// Reloading swaps this, clients connecting afterwards get the new
+    /// limits and timeouts.
+    config: Arc<LiveConfig>,
+    connections: AtomicUsize,
+    connections_per_ip: DashMap<IpAddr, usize>,
+    next_client_id: AtomicU32,
+    health: Arc<Health>,
+    #[cfg(feature = "grpc")]
+    gateway: Arc<Gateway>,
+    #[cfg(feature = "http")]
+    api: Arc<Api>,
     player_service: Arc<PlayerService>,
-    router: Arc<Router>,
+    game_service: Arc<GameService>,
+    admin_service: Arc<AdminService>,
+    audit_service: AuditService,
+    slow_requests: SlowRequests,
+    router: Router,
 }
 
 pub struct Context {
@@ -41,214 +127,1297 @@ pub struct Context {
     pub payload: Vec<u8>,
 }
 
-unsafe impl Send for Server {}
-unsafe impl Sync for Server {}
-
 impl Server {
-    pub async fn run(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let listener = TcpListener::bind(format!("{}:{}", self.host, self.port)).await?;
+    pub async fn run(self: Arc<Self>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Addresses are only read here, a reload leaves them to the next
+        // start
+        let config = self.config.get();
+        let mut listeners = Vec::new();
+        for address in config.listen_addresses() {
+            listeners.push(Self::bind(&address).await?);
+            info!(%address, "listening");
+        }
+        self.health.set_listener_bound(true);
+
+        let health_listener =
+            TcpListener::bind(format!("{}:{}", config.host, config.health_port)).await?;
+        let health = self.health.clone();
+        tokio::spawn(async move {
+            if let Err(e) = health.serve(health_listener).await {
+                error!(error = %e, "health probe listener stopped");
+            }
+        });
+
+        if let Some(grpc_port) = config.grpc_port {
+            #[cfg(feature = "grpc")]
+            {
+                let grpc_listener =
+                    TcpListener::bind(format!("{}:{}", config.host, grpc_port)).await?;
+                let gateway = self.gateway.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = gateway.serve(grpc_listener).await {
+                        error!(error = %e, "grpc gateway stopped");
+                    }
+                });
+            }
+            #[cfg(not(feature = "grpc"))]
+            warn!(
+                grpc_port,
+                "built without the grpc feature, not serving the gateway"
+            );
+        }
+
+        if let Some(http_port) = config.http_port {
+            #[cfg(feature = "http")]
+            {
+                let http_listener =
+                    TcpListener::bind(format!("{}:{}", config.host, http_port)).await?;
+                let api = self.api.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = api.serve(http_listener).await {
+                        error!(error = %e, "http api stopped");
+                    }
+                });
+            }
+            #[cfg(not(feature = "http"))]
+            warn!(
+                http_port,
+                "built without the http feature, not serving the api"
+            );
+        }
+
+        self.watch_reloads()?;
 
-        let mut next_client_id = 0;
+        // Every listener feeds the same pipeline, the first one to fail stops
+        // the server
+        let mut accepting = JoinSet::new();
+        for listener in listeners {
+            accepting.spawn(self.clone().accept(listener));
+        }
+        while let Some(result) = accepting.join_next().await {
+            result??;
+        }
+        Ok(())
+    }
+
+    /// Bind a listener for clients on the first address `address` resolves
+    /// to that takes it.
+    async fn bind(address: &str) -> std::io::Result<TcpListener> {
+        let mut last_error = None;
+        for address in lookup_host(address).await? {
+            match Self::bind_to(address) {
+                Ok(listener) => return Ok(listener),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{address} resolves to no address"),
+            )
+        }))
+    }
 
+    /// An IPv6 listener is kept to IPv6 so that `[::]` can share a port with
+    /// `0.0.0.0`.
+    fn bind_to(address: SocketAddr) -> std::io::Result<TcpListener> {
+        let socket = Socket::new(Domain::for_address(address), Type::STREAM, None)?;
+        #[cfg(unix)]
+        socket.set_reuse_address(true)?;
+        if address.is_ipv6() {
+            socket.set_only_v6(true)?;
+        }
+        socket.bind(&address.into())?;
+        socket.listen(1024)?;
+        socket.set_nonblocking(true)?;
+        TcpListener::from_std(socket.into())
+    }
+
+    /// Take clients from one listener until it fails. `run` does this for
+    /// every address it binds, tests can hand it a listener of their own.
+    pub async fn accept(
+        self: Arc<Self>,
+        listener: TcpListener,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
         loop {
-            let (socket, _) = listener.accept().await?;
-            let (tx, rx): (Sender<Frame>, Receiver<Frame>) = channel(128);
-            let shared_rx_bak = Arc::new(Mutex::new(rx));
-            let client_id = next_client_id;
-            next_client_id += 1;
-
-            let connection_bak = Arc::new(Connection::new(socket));
-            // clone the map
-            let connection = connection_bak.clone();
+            let (socket, peer) = listener.accept().await?;
+            let config = self.config.get();
+            if let Err(e) = Self::tune(&socket, &config) {
+                warn!(%peer, error = %e, "failed to set socket options");
+            }
+            if !config.proxy_protocol {
+                self.take_client(socket, peer);
+                continue;
+            }
+            // Waiting on the header here would let one quiet connection hold
+            // up every other one
             let server = self.clone();
-            let shared_rx = shared_rx_bak.clone();
+            tokio::spawn(async move {
+                let mut socket = socket;
+                match timeout(HEADER_TIMEOUT, proxy_protocol::read_header(&mut socket)).await {
+                    Ok(Ok(client)) => server.take_client(socket, client.unwrap_or(peer)),
+                    Ok(Err(e)) => {
+                        warn!(%peer, error = %e, "dropping connection with a bad PROXY header")
+                    }
+                    Err(_) => warn!(%peer, "dropping connection that sent no PROXY header"),
+                }
+            });
+        }
+    }
 
+    /// Serve a client from `peer` if it's within the limits, or tell it why
+    /// not.
+    fn take_client(self: &Arc<Self>, socket: TcpStream, peer: SocketAddr) {
+        if let Err(reason) = self.admit(peer.ip()) {
+            warn!(%peer, reason, "refusing client");
+            tokio::spawn(Self::refuse(socket, reason));
+            return;
+        }
+        self.serve(socket, peer);
+    }
+
+    /// Reload on SIGHUP or an admin's request, one reload at a time.
+    fn watch_reloads(self: &Arc<Self>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        #[cfg(unix)]
+        {
+            let mut hangup = signal(SignalKind::hangup())?;
+            let admin_service = self.admin_service.clone();
             tokio::spawn(async move {
+                while hangup.recv().await.is_some() {
+                    admin_service.request_reload();
+                }
+            });
+        }
+        let server = self.clone();
+        tokio::spawn(async move {
+            loop {
+                server.admin_service.reload_requested().await;
+                if let Err(e) = server.reload().await {
+                    error!(error = %e, "failed to reload, keeping the running config");
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Load the config and dictionaries again and put them in place without
+    /// dropping any client, lobby or game. Settings only a restart applies
+    /// are logged and left as they are.
+    pub async fn reload(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let next = ServerConfig::load()?;
+        #[cfg(not(test))]
+        {
+            let dictionary_service = self.game_service.get_dictionary_service();
+            dictionary_service.reload(&next.game).await?;
+            self.health
+                .set_dictionary_loaded(dictionary_service.is_loaded());
+        }
+        let pending = self.apply_config(next);
+        if !pending.is_empty() {
+            warn!(settings = ?pending, "changed settings take effect on restart");
+        }
+        info!("reloaded config");
+        Ok(())
+    }
+
+    /// Put in place everything in `next` that can change while the server
+    /// runs, returning the names of changed settings that wait for a
+    /// restart.
+    fn apply_config(&self, next: ServerConfig) -> Vec<&'static str> {
+        let current = self.config.get();
+        let (config, pending) = current.reloaded(next);
+        self.game_service.set_default_rules(&config.game);
+        self.player_service.set_lag_warning(config.lag_warning_ms);
+        self.player_service
+            .set_kick_after_violations(config.game.kick_after_violations);
+        self.player_service
+            .set_session_policy(config.duplicate_sessions);
+        // An operator may have replaced the motd at runtime, that one stays
+        // unless the file's changed too
+        if config.motd != current.motd {
+            self.admin_service.set_motd(config.motd.clone());
+        }
+        self.config.set(config);
+        pending
+    }
+
+    /// Serve an admitted client until either side hangs up.
+    fn serve<T: Transport>(self: &Arc<Self>, stream: T, peer: SocketAddr) {
+        let client_id = self.next_client_id.fetch_add(1, Ordering::SeqCst);
+        let config = self.config.get();
+        let (tx, mut rx): (Sender<Frame>, Receiver<Frame>) = channel(config.outbound_queue_size);
+
+        // Each task owns its half of the connection, they only talk through
+        // the outgoing frame channel.
+        let (mut reader, mut writer) =
+            connection::split(stream, config.heartbeat_timeout(), config.write_timeout());
+        let server = self.clone();
+        let connection_span = info_span!("connection", client_id, %peer);
+        let handshake_timeout = config.handshake_timeout();
+
+        tokio::spawn(
+            async move {
+                // Scope bits of the client's session, only a Connect starts
+                // one
+                let mut session = None;
+                let handshake = sleep(handshake_timeout);
+                tokio::pin!(handshake);
                 loop {
-                    let frame = match connection.read_frame().await {
+                    let read = tokio::select! {
+                        read = reader.read_frame() => read,
+                        // The writer gave up on a client that stopped
+                        // reading, nothing queued for it will be sent
+                        _ = tx.closed() => {
+                            info!("writer stopped, closing connection");
+                            break;
+                        }
+                        _ = &mut handshake, if session.is_none() => {
+                            info!("client never connected, closing connection");
+                            break;
+                        }
+                    };
+                    let frame = match read {
                         Ok(Some(frame)) => frame,
                         Ok(None) => {
-                            println!("connection closed by remote peer");
-                            if let Some(player) = server.player_service.get_player(client_id) {
-                                match server.player_service.remove_player(player) {
-                                    Ok(player) => println!(
-                                        "clean up player's resource success. player id: {}, player name: {}", player.id, player.name
-                                    ),
-                                    Err(e) => {
-                                        eprintln!("failed to clean up player's resource, err: {e}")
-                                    }
-                                }
-                            };
-                            shared_rx.lock().await.close();
+                            info!("connection closed by remote peer");
                             break;
                         }
                         Err(e) => {
-                            eprintln!("failed to read frame; err = {:?}", e);
-                            if let Some(player) = server.player_service.get_player(client_id) {
-                                match server.player_service.remove_player(player) {
-                                    Ok(player) => println!(
-                                        "clean up player's resource success. player id: {}, player name: {}", player.id, player.name
-                                    ),
-                                    Err(e) => {
-                                        eprintln!("failed to clean up player's resource, err: {e}")
-                                    }
-                                }
-                            };
-                            shared_rx.lock().await.close();
+                            warn!(error = %e, "failed to read frame");
                             break;
                         }
                     };
                     match frame {
                         Frame::Request(req) => {
-                            println!("received request; request = {:?}", req);
-                            let state = req.get_state();
-                            match server.router.route(
-                                req,
-                                RequestContext {
+                            let request_id = req.get_request_id();
+                            let operation = Operation::try_from(req.get_data().as_ref()).ok();
+                            let context = RequestContext::new(
+                                client_id,
+                                #[cfg(not(test))]
+                                tx.clone(),
+                            );
+                            let trace_id = context.get_trace_id();
+                            let span = info_span!(
+                                "request",
+                                request_id,
+                                trace_id,
+                                opcode = operation.as_ref().map(u8::from),
+                                latency_us = field::Empty,
+                            );
+                            let start = Instant::now();
+                            // Controllers block on locks and storage, keep
+                            // them off the workers driving every connection.
+                            // Waited on here, so a client's requests are
+                            // still handled in order.
+                            let (result, longest_wait) = tokio::task::spawn_blocking({
+                                let server = server.clone();
+                                let span = span.clone();
+                                move || {
+                                    span.in_scope(|| {
+                                        debug!(request = ?req, "received request");
+                                        latency::reset_waits();
+                                        // A bug in one controller must not
+                                        // take the connection, or anyone
+                                        // else's, down with it
+                                        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                                            server.router.route(req, context, session)
+                                        }))
+                                        .unwrap_or_else(|payload| {
+                                            Ok(server.recover_from_panic(
+                                                client_id,
+                                                panic_message(payload.as_ref()),
+                                            ))
+                                        });
+                                        (result, latency::take_longest_wait())
+                                    })
+                                }
+                            })
+                            .await
+                            .unwrap_or_else(|e| (Err(e.into()), None));
+                            let elapsed = start.elapsed();
+                            span.record("latency_us", elapsed.as_micros() as u64);
+                            if let Some(operation) = &operation {
+                                span.in_scope(|| {
+                                    server.slow_requests.check(
+                                        operation,
+                                        elapsed,
+                                        server.config.get().slow_request_budget(operation),
+                                        longest_wait,
+                                    )
+                                });
+                                server.audit_service.record(
                                     client_id,
-                                    #[cfg(not(test))]
-                                    sender: tx.clone(),
-                                },
-                            ) {
+                                    &trace_id,
+                                    peer.ip(),
+                                    operation,
+                                    &result,
+                                );
+                            }
+                            match result {
                                 Ok(res) => {
+                                    span.in_scope(|| info!("handled request"));
+                                    let greet = matches!(res, ResponseData::Connect(_));
+                                    match &res {
+                                        ResponseData::Connect(res) => session = Some(res.scopes),
+                                        ResponseData::Disconnect(_) => {
+                                            session = None;
+                                            handshake.as_mut().reset(
+                                                tokio::time::Instant::now() + handshake_timeout,
+                                            );
+                                        }
+                                        _ => {}
+                                    }
                                     if tx
-                                        .send(Frame::Response(Response::new(state, Arc::new(res))))
+                                        .send(Frame::Response(Response::new(
+                                            request_id,
+                                            Arc::new(res),
+                                        )))
                                         .await
                                         .is_err()
                                     {
-                                        eprintln!("failed to send frame to writer thread");
+                                        error!("failed to send frame to writer task");
                                         break;
                                     }
+                                    // Queued only now, so the greeting can't
+                                    // reach the client ahead of the response
+                                    if let Some(motd) =
+                                        greet.then(|| server.admin_service.motd_notice()).flatten()
+                                    {
+                                        let _ = tx.send(Frame::Response(motd)).await;
+                                    }
                                 }
                                 Err(e) => {
-                                    eprintln!("failed to handle request; err = {:?}", e);
+                                    span.in_scope(|| warn!(error = %e, "failed to handle request"));
                                 }
                             };
                         }
                         _ => {
-                            eprintln!("invalid frame; frame = {:?}", frame)
+                            warn!(?frame, "invalid frame")
                         }
                     };
                 }
-            });
-
-            let connection = connection_bak.clone();
-            let shared_rx = shared_rx_bak.clone();
+                server.clean_up(client_id);
+                // Broadcasts still in flight hold clones of `tx`, so the
+                // writer won't see the channel close on its own
+                let _ = tx.send(Frame::Close).await;
+                server.release(peer.ip());
+            }
+            .instrument(connection_span.clone()),
+        );
 
-            tokio::spawn(async move {
-                loop {
-                    while let Some(frame) = shared_rx.lock().await.recv().await {
-                        match connection.write_frame(&frame).await {
-                            Ok(_) => {
-                                println!("sent frame; frame = {:?}", frame);
-                                continue;
-                            }
+        tokio::spawn(
+            async move {
+                let mut batch = Vec::with_capacity(MAX_BATCH_FRAMES);
+                while let Some(frame) = rx.recv().await {
+                    // Take whatever else is already queued, so a burst of
+                    // broadcasts goes out in one write
+                    let mut close = false;
+                    let mut next = Some(frame);
+                    while let Some(frame) = next.take() {
+                        if let Frame::Close = frame {
+                            close = true;
+                            break;
+                        }
+                        batch.push(frame);
+                        if batch.len() < MAX_BATCH_FRAMES {
+                            next = rx.try_recv().ok();
+                        }
+                    }
+                    if !batch.is_empty() {
+                        match writer.write_frames(&batch).await {
+                            Ok(_) => debug!(frames = ?batch, "sent frames"),
                             Err(e) => {
-                                eprintln!("failed to write frame; err = {:?}", e);
+                                warn!(error = %e, "failed to write frames");
                                 break;
                             }
                         };
+                        batch.clear();
+                    }
+                    if close {
+                        if let Err(e) = writer.shutdown().await {
+                            debug!(error = %e, "failed to close connection");
+                        }
+                        break;
                     }
                 }
-            });
+            }
+            .instrument(connection_span),
+        );
+    }
+
+    /// Connect a client over an in-memory pipe instead of a socket, so tests
+    /// can drive the whole server. It counts against the limits as a client
+    /// from localhost.
+    #[cfg(feature = "simulation")]
+    pub fn connect_in_memory(self: &Arc<Self>) -> Result<DuplexStream, &'static str> {
+        let peer = SocketAddr::from(([127, 0, 0, 1], 0));
+        self.admit(peer.ip())?;
+        let (client, server) = tokio::io::duplex(IN_MEMORY_BUFFER_SIZE);
+        self.serve(server, peer);
+        Ok(client)
+    }
+
+    /// Count a new client against the global and per-address limits.
+    fn admit(&self, ip: IpAddr) -> Result<(), &'static str> {
+        let config = self.config.get();
+        if self.connections.load(Ordering::SeqCst) >= config.max_connections {
+            return Err("Server is full");
         }
+        let mut count = self.connections_per_ip.entry(ip).or_insert(0);
+        if *count >= config.max_connections_per_ip {
+            return Err("Too many connections from your address");
+        }
+        *count += 1;
+        self.connections.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn release(&self, ip: IpAddr) {
+        self.connections.fetch_sub(1, Ordering::SeqCst);
+        self.connections_per_ip.remove_if_mut(&ip, |_, count| {
+            *count -= 1;
+            *count == 0
+        });
+    }
+
+    /// Set the configured TCP options on an accepted socket. A client whose
+    /// socket can't take them is still served on the OS defaults.
+    fn tune(socket: &TcpStream, config: &ServerConfig) -> std::io::Result<()> {
+        let socket = SockRef::from(socket);
+        socket.set_tcp_nodelay(config.tcp_nodelay)?;
+        match config.tcp_keepalive() {
+            Some(time) => socket
+                .set_tcp_keepalive(&TcpKeepalive::new().with_time(time).with_interval(time))?,
+            None => socket.set_keepalive(false)?,
+        }
+        if config.tcp_send_buffer_bytes > 0 {
+            socket.set_send_buffer_size(config.tcp_send_buffer_bytes)?;
+        }
+        if config.tcp_recv_buffer_bytes > 0 {
+            socket.set_recv_buffer_size(config.tcp_recv_buffer_bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Tell a client over the limits why before dropping it. Nothing has been
+    /// negotiated yet, so the frame uses the legacy encoding.
+    async fn refuse(mut socket: TcpStream, reason: &'static str) {
+        let response = Response::event(
+            State::Error,
+            Arc::new(ResponseData::Error(crate::model::error::error::Error {
+                message: String::from(reason),
+                code: ErrorCode::ServerFull as i32,
+            })),
+        );
+        let result = async {
+            let bytes = response.to_bytes()?;
+            timeout(Duration::from_secs(1), socket.write_all(&bytes)).await??;
+            socket.shutdown().await?;
+            Ok::<_, Box<dyn Error + Send + Sync>>(())
+        }
+        .await;
+        if let Err(e) = result {
+            debug!(error = %e, "failed to send server full frame");
+        }
+    }
+
+    /// A controller panicked handling a request from `client_id`. The panic
+    /// may have left the player's game broken, so that game is aborted, and
+    /// the player is told their request failed.
+    fn recover_from_panic(&self, client_id: u32, reason: &str) -> ResponseData {
+        error!(reason, "controller panicked");
+        if let Some(game) = self
+            .player_service
+            .get_player(client_id)
+            .and_then(|player| player.get_game())
+        {
+            self.game_service.abort_game(&game, reason);
+        }
+        ResponseData::Error(crate::model::error::error::Error {
+            message: String::from("Internal server error"),
+            code: ErrorCode::Internal as i32,
+        })
+    }
+
+    fn clean_up(&self, client_id: u32) {
+        if let Some(player) = self.player_service.get_player(client_id) {
+            match self.player_service.remove_player(player) {
+                Ok(player) => info!(
+                    player_id = player.id,
+                    player_name = %player.name,
+                    "clean up player's resource success"
+                ),
+                Err(e) => error!(error = %e, "failed to clean up player's resource"),
+            }
+        };
     }
 
     #[cfg(not(test))]
-    pub async fn new() -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let lobby_service = Arc::new(LobbyService::new());
-        let game_service = Arc::new(GameService::new().await?);
-        let player_service = Arc::new(PlayerService::new(
-            lobby_service.clone(),
-            game_service.clone(),
+    pub async fn new(config: ServerConfig) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let broadcast_service =
+            Arc::new(BroadcastService::new().with_spectator_delay(config.game.spectator_delay()));
+        let lobby_service = Arc::new(
+            LobbyService::with_config(config.lobby.clone(), broadcast_service.clone())
+                .with_node_id(config.node_id),
+        );
+        let dictionary_service = Arc::new(DictionaryService::new(&config.game).await?);
+        let health = Arc::new(Health::new());
+        let audit_service = match &config.audit_log_path {
+            Some(path) => AuditService::open(path, config.audit_log_max_bytes)
+                .map_err(|e| format!("failed to open audit log {}: {}", path, e))?,
+            None => AuditService::disabled(),
+        };
+        health.set_dictionary_loaded(dictionary_service.is_loaded());
+        let storage: Arc<dyn Storage> = match &config.database_url {
+            #[cfg(feature = "sql")]
+            Some(url) => Arc::new(
+                SqlStorage::connect(url).map_err(|e| format!("failed to open database: {}", e))?,
+            ),
+            #[cfg(not(feature = "sql"))]
+            Some(_) => {
+                warn!("built without the sql feature, keeping everything in memory");
+                Arc::new(MemoryStorage::new())
+            }
+            None => Arc::new(MemoryStorage::new()),
+        };
+        health.set_storage(storage.clone());
+        let stats_service = Arc::new(StatsService::new(storage.clone()));
+        let profile_service = Arc::new(ProfileService::new(storage.clone(), stats_service.clone()));
+        let block_service = Arc::new(BlockService::new(storage.clone()));
+        let game_service = Arc::new(GameService::new(
+            &config.game,
+            dictionary_service,
+            stats_service,
+            Arc::new(LeaderboardService::new(storage.clone())),
+            Arc::new(SeasonService::new(&config.season, storage.clone())),
+            broadcast_service.clone(),
+            storage.clone(),
+        ));
+        let restored = GameService::restore_games(game_service.clone())?;
+        if restored > 0 {
+            info!(games = restored, "restored unfinished games");
+        }
+        if let Some(interval) = config.game.snapshot_interval() {
+            GameService::start_snapshots(game_service.clone(), interval);
+        }
+        if let Some(max_idle) = config.lobby.max_idle() {
+            LobbyService::start_reaper(lobby_service.clone(), max_idle);
+        }
+        #[cfg(feature = "http")]
+        let account_service = Arc::new(AccountService::new(storage.clone()));
+        let chat_service = Arc::new(ChatService::new(
+            &config.chat,
+            storage,
+            broadcast_service.clone(),
         ));
-        let router = Arc::new(Router::new());
+        let cluster_service = Arc::new(match &config.redis_url {
+            #[cfg(feature = "redis")]
+            Some(url) => ClusterService::connect(url, config.node_id, config.advertise_address())
+                .await
+                .map_err(|e| format!("failed to connect to redis: {}", e))?,
+            #[cfg(not(feature = "redis"))]
+            Some(_) => {
+                warn!("built without the redis feature, not sharing presence or broadcasts");
+                ClusterService::local(config.node_id)
+            }
+            None => ClusterService::local(config.node_id),
+        });
+        let player_service = Arc::new(
+            PlayerService::new(lobby_service.clone(), game_service.clone())
+                .with_lag_warning(config.lag_warning_ms)
+                .with_kick_after_violations(config.game.kick_after_violations)
+                .with_session_policy(config.duplicate_sessions)
+                .with_profile_service(profile_service.clone())
+                .with_block_service(block_service.clone())
+                .with_cluster_service(cluster_service.clone()),
+        );
+        let admin_service = Arc::new(
+            AdminService::new(config.admin_token.clone(), player_service.clone())
+                .with_motd(config.motd.clone())
+                .with_api_tokens(&config.api_tokens),
+        );
+        if let Some(mut events) = cluster_service.take_events() {
+            let cluster_service = cluster_service.clone();
+            let admin_service = admin_service.clone();
+            let player_service = player_service.clone();
+            let lobby_service = lobby_service.clone();
+            tokio::spawn(async move {
+                while let Some(event) = events.recv().await {
+                    match event {
+                        ClusterEvent::Announce { message } => {
+                            let maintenance = admin_service.get_maintenance().is_some();
+                            admin_service.announce_here(&message, maintenance);
+                        }
+                        ClusterEvent::Handoff { node, lobby } => {
+                            cluster_service.record_move(lobby.lobby_id, node);
+                            if node == cluster_service.get_node_id() {
+                                lobby_service.adopt(lobby, player_service.get_players());
+                            }
+                        }
+                    }
+                }
+            });
+        }
+        let config = Arc::new(LiveConfig::new(config));
+        let mut router = Router::new();
         router
-            .register_controller(
+            .register(
                 Operation::Connect,
-                Box::new(ConnectController::new(player_service.clone())),
-            )
-            .register_controller(
+                Box::new(ConnectController::new(
+                    player_service.clone(),
+                    admin_service.clone(),
+                )),
+            )?
+            .register(
                 Operation::Disconnect,
                 Box::new(DisconnectController::new(player_service.clone())),
-            )
-            .register_controller(Operation::Heartbeat, Box::new(HeartbeatController::new()))
-            .register_controller(
+            )?
+            .register(
+                Operation::Heartbeat,
+                Box::new(HeartbeatController::new(player_service.clone())),
+            )?
+            .register(Operation::TimeSync, Box::new(TimeSyncController::new()))?
+            .register(
+                Operation::SetPreferences,
+                Box::new(PreferencesController::new(
+                    player_service.clone(),
+                    broadcast_service,
+                )),
+            )?
+            .register(
+                Operation::ServerInfo,
+                Box::new(ServerInfoController::new(
+                    config.clone(),
+                    admin_service.clone(),
+                    player_service.clone(),
+                    lobby_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
                 Operation::CreateLobby,
                 Box::new(CreateController::new(
                     player_service.clone(),
                     lobby_service.clone(),
+                    game_service.clone(),
                 )),
-            )
-            .register_controller(
+            )?
+            .register(
                 Operation::JoinLobby,
                 Box::new(JoinController::new(
                     player_service.clone(),
                     lobby_service.clone(),
                 )),
-            )
-            .register_controller(
+            )?
+            .register(
+                Operation::JoinByCode,
+                Box::new(JoinByCodeController::new(
+                    player_service.clone(),
+                    lobby_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::QuickJoin,
+                Box::new(QuickJoinController::new(
+                    player_service.clone(),
+                    lobby_service.clone(),
+                )),
+            )?
+            .register(
                 Operation::ListLobby,
                 Box::new(ListController::new(lobby_service.clone())),
-            )
-            .register_controller(
+            )?
+            .register(
                 Operation::QuitLobby,
                 Box::new(QuitController::new(
                     player_service.clone(),
                     lobby_service.clone(),
                 )),
-            )
-            .register_controller(
+            )?
+            .register(
                 Operation::Ready,
                 Box::new(ReadyController::new(player_service.clone())),
-            )
-            .register_controller(
+            )?
+            .register(
                 Operation::StartGame,
                 Box::new(StartController::new(
                     player_service.clone(),
                     game_service.clone(),
                 )),
-            )
-            .register_controller(
+            )?
+            .register(
                 Operation::SetTile,
                 Box::new(SetTileController::new(
                     player_service.clone(),
                     game_service.clone(),
                 )),
-            )
-            .register_controller(
+            )?
+            .register(
                 Operation::GetNewCard,
                 Box::new(GetNewCardController::new(
                     player_service.clone(),
                     game_service.clone(),
                 )),
-            )
-            .register_controller(
+            )?
+            .register(
+                Operation::ExchangeCards,
+                Box::new(ExchangeCardsController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::StartVoteKick,
+                Box::new(StartVoteKickController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::AnswerVoteKick,
+                Box::new(AnswerVoteKickController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
                 Operation::Cancel,
                 Box::new(CancelController::new(
                     player_service.clone(),
                     game_service.clone(),
                 )),
-            )
-            .register_controller(
+            )?
+            .register(
                 Operation::FinishTurn,
                 Box::new(FinishTurnController::new(
                     player_service.clone(),
                     game_service.clone(),
                 )),
-            )
-            .register_controller(
+            )?
+            .register(
                 Operation::Exit,
                 Box::new(ExitController::new(
                     player_service.clone(),
                     game_service.clone(),
                 )),
-            );
+            )?
+            .register(
+                Operation::PauseGame,
+                Box::new(PauseController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::ResumeGame,
+                Box::new(ResumeController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::Spectate,
+                Box::new(SpectateController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::SubscribeGame,
+                Box::new(SubscribeGameController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::BoardSnapshot,
+                Box::new(BoardSnapshotController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::ResyncFrom,
+                Box::new(ResyncController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::GetBagInfo,
+                Box::new(GetBagInfoController::new(player_service.clone())),
+            )?
+            .register(
+                Operation::ExportGame,
+                Box::new(ExportGameController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::AnalyzeGame,
+                Box::new(AnalyzeGameController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::Resign,
+                Box::new(ResignController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::OfferDraw,
+                Box::new(OfferDrawController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::AnswerDraw,
+                Box::new(AnswerDrawController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::GetHint,
+                Box::new(HintController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::GetPlayerStats,
+                Box::new(StatsController::new(
+                    player_service.clone(),
+                    game_service.get_stats_service(),
+                )),
+            )?
+            .register(
+                Operation::StartDailyChallenge,
+                Box::new(DailyController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::GetDailyLeaderboard,
+                Box::new(DailyLeaderboardController::new(
+                    player_service.clone(),
+                    game_service.get_leaderboard_service(),
+                )),
+            )?
+            .register(
+                Operation::GetSeasonInfo,
+                Box::new(SeasonInfoController::new(
+                    player_service.clone(),
+                    game_service.get_season_service(),
+                )),
+            )?
+            .register(
+                Operation::SendChat,
+                Box::new(SendController::new(
+                    player_service.clone(),
+                    chat_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::SendQuickChat,
+                Box::new(QuickChatController::new(
+                    player_service.clone(),
+                    chat_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::ReportPlayer,
+                Box::new(ReportController::new(
+                    player_service.clone(),
+                    chat_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::MutePlayer,
+                Box::new(MuteController::new(player_service.clone())),
+            )?
+            .register(
+                Operation::UpdateProfile,
+                Box::new(UpdateProfileController::new(
+                    player_service.clone(),
+                    profile_service,
+                )),
+            )?
+            .register(
+                Operation::BlockPlayer,
+                Box::new(BlockController::new(player_service.clone(), block_service)),
+            )?
+            .register(
+                Operation::KickFromLobby,
+                Box::new(KickFromLobbyController::new(
+                    player_service.clone(),
+                    lobby_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::UpdateLobbySettings,
+                Box::new(UpdateLobbySettingsController::new(
+                    player_service.clone(),
+                    lobby_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::StartSolo,
+                Box::new(SoloController::new(
+                    player_service.clone(),
+                    game_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::UploadDictionary,
+                Box::new(UploadDictionaryController::new(
+                    player_service.clone(),
+                    lobby_service.clone(),
+                    game_service.get_dictionary_service(),
+                )),
+            )?
+            .register(
+                Operation::AdminListPlayers,
+                Box::new(ListPlayersController::new(
+                    player_service.clone(),
+                    admin_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::AdminKick,
+                Box::new(KickController::new(admin_service.clone())),
+            )?
+            .register(
+                Operation::AdminBan,
+                Box::new(BanController::new(admin_service.clone())),
+            )?
+            .register(
+                Operation::AdminAnnounce,
+                Box::new(AnnounceController::new(admin_service.clone())),
+            )?
+            .register(
+                Operation::AdminMaintenance,
+                Box::new(MaintenanceController::new(admin_service.clone())),
+            )?
+            .register(
+                Operation::AdminInspect,
+                Box::new(InspectController::new(
+                    lobby_service.clone(),
+                    game_service.clone(),
+                    admin_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::AdminCloseGame,
+                Box::new(CloseGameController::new(
+                    game_service.clone(),
+                    admin_service.clone(),
+                )),
+            )?
+            .register(
+                Operation::AdminReload,
+                Box::new(ReloadController::new(admin_service.clone())),
+            )?
+            .register(
+                Operation::AdminIssueToken,
+                Box::new(IssueTokenController::new(admin_service.clone())),
+            )?
+            .register(
+                Operation::AdminRevokeToken,
+                Box::new(RevokeTokenController::new(admin_service.clone())),
+            )?
+            .register(
+                Operation::AdminHandoff,
+                Box::new(HandoffController::new(
+                    player_service.clone(),
+                    lobby_service.clone(),
+                    admin_service.clone(),
+                )),
+            )?;
         Ok(Self {
-            host: String::from("0.0.0.0"),
-            port: 45678,
+            config,
+            connections: AtomicUsize::new(0),
+            connections_per_ip: DashMap::new(),
+            next_client_id: AtomicU32::new(0),
+            health,
+            #[cfg(feature = "grpc")]
+            gateway: Arc::new(Gateway::new(
+                player_service.clone(),
+                lobby_service,
+                game_service.clone(),
+            )),
+            #[cfg(feature = "http")]
+            api: Arc::new(Api::new(account_service, game_service.clone())),
             player_service,
+            game_service,
+            admin_service,
+            audit_service,
+            slow_requests: SlowRequests::new(),
             router,
         })
     }
 }
+
+// The server and everything it hands to connection tasks must be shareable
+// across the runtime's worker threads without any unsafe impls.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Server>();
+    assert_send_sync::<Router>();
+    assert_send_sync::<ConnectionReader>();
+    assert_send_sync::<ConnectionWriter>();
+    assert_send_sync::<Health>();
+    assert_send_sync::<PlayerService>();
+    assert_send_sync::<crate::player::Player>();
+    assert_send_sync::<crate::service::lobby_service::LobbyService>();
+    assert_send_sync::<crate::service::game_service::GameService>();
+    assert_send_sync::<crate::service::admin_service::AdminService>();
+};
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+    use crate::{
+        error::ServerError,
+        service::{game_service::GameService, lobby_service::LobbyService},
+    };
+
+    fn new_server(config: ServerConfig) -> Server {
+        let lobby_service = Arc::new(LobbyService::new());
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player_service = Arc::new(PlayerService::new(
+            lobby_service.clone(),
+            game_service.clone(),
+        ));
+        Server {
+            config: Arc::new(LiveConfig::new(config)),
+            connections: AtomicUsize::new(0),
+            connections_per_ip: DashMap::new(),
+            next_client_id: AtomicU32::new(0),
+            health: Arc::new(Health::new()),
+            #[cfg(feature = "grpc")]
+            gateway: Arc::new(Gateway::new(
+                player_service.clone(),
+                lobby_service,
+                game_service.clone(),
+            )),
+            #[cfg(feature = "http")]
+            api: Arc::new(Api::new(
+                Arc::new(AccountService::new(Arc::new(
+                    crate::storage::memory::MemoryStorage::new(),
+                ))),
+                game_service.clone(),
+            )),
+            admin_service: Arc::new(AdminService::new(None, player_service.clone())),
+            player_service,
+            game_service,
+            audit_service: AuditService::disabled(),
+            slow_requests: SlowRequests::new(),
+            router: Router::new(),
+        }
+    }
+
+    #[test]
+    fn admit_over_per_ip_limit_should_return_error() {
+        let server = new_server(ServerConfig {
+            max_connections_per_ip: 2,
+            ..ServerConfig::default()
+        });
+        let ip = IpAddr::from([10, 0, 0, 1]);
+        assert!(server.admit(ip).is_ok());
+        assert!(server.admit(ip).is_ok());
+        assert!(server.admit(ip).is_err());
+        assert!(server.admit(IpAddr::from([10, 0, 0, 2])).is_ok());
+    }
+
+    #[test]
+    fn admit_over_max_connections_should_return_error() {
+        let server = new_server(ServerConfig {
+            max_connections: 1,
+            ..ServerConfig::default()
+        });
+        assert!(server.admit(IpAddr::from([10, 0, 0, 1])).is_ok());
+        assert!(server.admit(IpAddr::from([10, 0, 0, 2])).is_err());
+    }
+
+    #[test]
+    fn release_should_free_slot_for_same_ip() {
+        let server = new_server(ServerConfig {
+            max_connections_per_ip: 1,
+            ..ServerConfig::default()
+        });
+        let ip = IpAddr::from([10, 0, 0, 1]);
+        server.admit(ip).unwrap();
+        server.release(ip);
+        assert!(server.connections_per_ip.is_empty());
+        assert!(server.admit(ip).is_ok());
+    }
+
+    #[test]
+    fn apply_config_should_update_limits_rules_and_changed_motd() -> Result<(), ServerError> {
+        let server = new_server(ServerConfig {
+            max_connections: 1,
+            ..ServerConfig::default()
+        });
+        server.admin_service.set_motd(String::from("runtime"));
+        let mut next = ServerConfig {
+            max_connections: 2,
+            port: 1,
+            ..ServerConfig::default()
+        };
+        next.game.turn_duration_secs = 5;
+        assert_eq!(server.apply_config(next.clone()), vec!["port"]);
+        assert!(server.admit(IpAddr::from([10, 0, 0, 1])).is_ok());
+        assert!(server.admit(IpAddr::from([10, 0, 0, 2])).is_ok());
+        assert_eq!(
+            server.game_service.resolve_rules(None)?.turn_duration,
+            Duration::from_secs(5)
+        );
+        assert_eq!(server.config.get().port, ServerConfig::default().port);
+        assert_eq!(
+            server.admin_service.get_motd(),
+            Some(String::from("runtime"))
+        );
+        server.apply_config(ServerConfig {
+            motd: String::from("welcome"),
+            ..next
+        });
+        assert_eq!(
+            server.admin_service.get_motd(),
+            Some(String::from("welcome"))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn recover_from_panic_should_abort_player_game() {
+        let server = new_server(ServerConfig::default());
+        let player = server.player_service.add_player(0, String::from("test"));
+        let game = Arc::new(crate::game::game::Game::new(0, vec![player.clone()]));
+        player.set_game(Some(game.clone()));
+        match server.recover_from_panic(0, "broken game") {
+            ResponseData::Error(error) => assert_eq!(error.code(), ErrorCode::Internal),
+            _ => panic!("wrong response type"),
+        }
+        assert!(game.is_errored());
+        assert!(player.get_game().is_none());
+    }
+
+    #[tokio::test]
+    async fn refuse_should_write_server_full_error_frame(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let mut client = TcpStream::connect(listener.local_addr()?).await?;
+        let (socket, _) = listener.accept().await?;
+        Server::refuse(socket, "Server is full").await;
+        let mut bytes = Vec::new();
+        client.read_to_end(&mut bytes).await?;
+        assert_eq!(bytes[..4], (State::Error as u32).to_le_bytes());
+        let error = <crate::model::error::error::Error as prost::Message>::decode(&bytes[8..])?;
+        assert_eq!(error.code(), ErrorCode::ServerFull);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn bind_with_ipv6_any_should_share_port_with_ipv4_any(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let ipv4 = Server::bind("0.0.0.0:0").await?;
+        let port = ipv4.local_addr()?.port();
+        let ipv6 = Server::bind(&format!("[::]:{port}")).await?;
+        assert_eq!(ipv6.local_addr()?.port(), port);
+        TcpStream::connect(("127.0.0.1", port)).await?;
+        TcpStream::connect(("::1", port)).await?;
+        assert!(ipv4.accept().await?.1.is_ipv4());
+        assert!(ipv6.accept().await?.1.is_ipv6());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn accept_with_proxy_protocol_should_admit_header_address(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let server = Arc::new(new_server(ServerConfig {
+            proxy_protocol: true,
+            ..ServerConfig::default()
+        }));
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(server.clone().accept(listener));
+        let mut client = TcpStream::connect(address).await?;
+        client
+            .write_all(b"PROXY TCP4 203.0.113.7 10.0.0.1 51234 45678\r\n")
+            .await?;
+        let client_ip = IpAddr::from([203, 0, 113, 7]);
+        timeout(Duration::from_secs(1), async {
+            while !server.connections_per_ip.contains_key(&client_ip) {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await?;
+        assert!(!server
+            .connections_per_ip
+            .contains_key(&IpAddr::from([127, 0, 0, 1])));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serve_without_connect_should_close_after_handshake_timeout(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let server = Arc::new(new_server(ServerConfig {
+            handshake_timeout_secs: 1,
+            ..ServerConfig::default()
+        }));
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let mut client = TcpStream::connect(listener.local_addr()?).await?;
+        let (socket, peer) = listener.accept().await?;
+        server.take_client(socket, peer);
+        let mut bytes = Vec::new();
+        timeout(Duration::from_secs(3), client.read_to_end(&mut bytes)).await??;
+        assert!(bytes.is_empty());
+        timeout(Duration::from_secs(1), async {
+            while server.connections.load(Ordering::SeqCst) > 0 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tune_should_apply_configured_socket_options(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let _client = TcpStream::connect(listener.local_addr()?).await?;
+        let (socket, _) = listener.accept().await?;
+        let config = ServerConfig {
+            tcp_nodelay: true,
+            tcp_keepalive_secs: 15,
+            tcp_send_buffer_bytes: 64 * 1024,
+            ..ServerConfig::default()
+        };
+        Server::tune(&socket, &config)?;
+        assert!(SockRef::from(&socket).tcp_nodelay()?);
+        assert!(SockRef::from(&socket).keepalive()?);
+        assert!(SockRef::from(&socket).send_buffer_size()? >= 64 * 1024);
+
+        let config = ServerConfig {
+            tcp_nodelay: false,
+            tcp_keepalive_secs: 0,
+            ..config
+        };
+        Server::tune(&socket, &config)?;
+        assert!(!SockRef::from(&socket).tcp_nodelay()?);
+        assert!(!SockRef::from(&socket).keepalive()?);
+        Ok(())
+    }
+}
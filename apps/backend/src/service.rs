@@ -1,3 +1,15 @@
+pub mod account_service;
+pub mod admin_service;
+pub mod audit_service;
+pub mod block_service;
+pub mod broadcast_service;
+pub mod chat_service;
+pub mod cluster_service;
+pub mod dictionary_service;
 pub mod game_service;
+pub mod leaderboard_service;
 pub mod lobby_service;
 pub mod player_service;
+pub mod profile_service;
+pub mod season_service;
+pub mod stats_service;
@@ -0,0 +1,172 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use tracing::debug;
+
+use crate::storage::Storage;
+
+pub const MAX_NAME_LEN: usize = 32;
+pub const MIN_PASSWORD_LEN: usize = 8;
+/// Tokens stop working this long after login.
+pub const SESSION_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// A registered name, which owns the stats and history kept under it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub name: String,
+    /// PHC string of an Argon2 hash.
+    pub password_hash: String,
+}
+
+#[derive(Debug)]
+struct Session {
+    account: String,
+    expires_at: Instant,
+}
+
+/// Registration and login. Hashing is deliberately slow, so async callers
+/// should run `register` and `login` off the runtime's worker threads.
+#[derive(Debug)]
+pub struct AccountService {
+    storage: Arc<dyn Storage>,
+    /// Held across each lookup and save so two registrations can't both take
+    /// a name.
+    update: Mutex<()>,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl AccountService {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            update: Mutex::new(()),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(&self, name: &str, password: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(format!("Name must be 1 to {} characters", MAX_NAME_LEN).into());
+        }
+        if name.chars().any(char::is_whitespace) {
+            return Err("Name must not contain spaces".into());
+        }
+        if password.chars().count() < MIN_PASSWORD_LEN {
+            return Err(
+                format!("Password must be at least {} characters", MIN_PASSWORD_LEN).into(),
+            );
+        }
+        let salt =
+            SaltString::encode_b64(&rand::random::<[u8; 16]>()).map_err(|e| e.to_string())?;
+        let password_hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| e.to_string())?
+            .to_string();
+        let _update = self.update.lock().unwrap();
+        if self.storage.load_account(name)?.is_some() {
+            return Err("Name already taken".into());
+        }
+        self.storage.save_account(&Account {
+            name: name.to_string(),
+            password_hash,
+        })?;
+        debug!(account = name, "register account");
+        Ok(())
+    }
+
+    /// Check the password and hand out a token for the account.
+    pub fn login(
+        &self,
+        name: &str,
+        password: &str,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let account = match self.storage.load_account(name)? {
+            Some(account) => account,
+            None => return Err("Invalid name or password".into()),
+        };
+        let hash = PasswordHash::new(&account.password_hash).map_err(|e| e.to_string())?;
+        if Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_err()
+        {
+            return Err("Invalid name or password".into());
+        }
+        let token = rand::random::<[u8; 32]>()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions.insert(
+            token.clone(),
+            Session {
+                account: account.name,
+                expires_at: now + SESSION_TTL,
+            },
+        );
+        debug!(account = name, "login");
+        Ok(token)
+    }
+
+    /// The account a token from `login` belongs to, while it hasn't expired.
+    pub fn authenticate(&self, token: &str) -> Option<String> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(token)
+            .filter(|session| session.expires_at > Instant::now())
+            .map(|session| session.account.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::memory::MemoryStorage;
+
+    use super::*;
+
+    #[test]
+    fn login_with_registered_password_should_return_token(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = AccountService::new(Arc::new(MemoryStorage::new()));
+        service.register("test", "password")?;
+        let token = service.login("test", "password")?;
+        assert_eq!(service.authenticate(&token), Some(String::from("test")));
+        assert!(service.authenticate("token").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn login_with_wrong_password_should_return_error() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = AccountService::new(Arc::new(MemoryStorage::new()));
+        service.register("test", "password")?;
+        assert!(service.login("test", "passwort").is_err());
+        assert!(service.login("test1", "password").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn register_with_taken_name_should_return_error() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = AccountService::new(Arc::new(MemoryStorage::new()));
+        service.register("test", "password")?;
+        assert!(service.register("test", "password1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn register_with_short_password_should_return_error() {
+        let service = AccountService::new(Arc::new(MemoryStorage::new()));
+        assert!(service.register("test", "pass").is_err());
+        assert!(service.register("", "password").is_err());
+        assert!(service.register("te st", "password").is_err());
+    }
+}
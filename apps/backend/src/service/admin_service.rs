@@ -0,0 +1,333 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::Notify;
+
+use crate::config::{ApiTokenConfig, TokenScope};
+use crate::frame::{Response, ResponseData};
+use crate::model::{
+    control::{connect::Scope, notice::Notice},
+    state::State,
+};
+use crate::player::Player;
+
+use super::{cluster_service::ClusterEvent, player_service::PlayerService};
+
+/// What a session opened with an API token plays as and may do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiToken {
+    pub name: String,
+    /// Scope bits.
+    pub scopes: u64,
+}
+
+#[derive(Debug)]
+pub struct AdminService {
+    token: Option<String>,
+    player_service: Arc<PlayerService>,
+    /// By token, from the config or issued by an operator.
+    api_tokens: Mutex<HashMap<String, ApiToken>>,
+    banned_names: Mutex<HashSet<String>>,
+    maintenance: Mutex<Option<String>>,
+    motd: Mutex<Option<String>>,
+    /// Woken to have the server load its config and dictionaries again.
+    reload: Notify,
+}
+
+impl AdminService {
+    /// Admin requests are refused entirely when `token` is `None` or empty.
+    pub fn new(token: Option<String>, player_service: Arc<PlayerService>) -> Self {
+        Self {
+            token: token.filter(|token| !token.is_empty()),
+            player_service,
+            api_tokens: Mutex::new(HashMap::new()),
+            banned_names: Mutex::new(HashSet::new()),
+            maintenance: Mutex::new(None),
+            motd: Mutex::new(None),
+            reload: Notify::new(),
+        }
+    }
+
+    /// Greet every player who connects with `motd`, unless it is empty.
+    pub fn with_motd(self, motd: String) -> Self {
+        self.set_motd(motd);
+        self
+    }
+
+    /// Let bots and services connect with the tokens from the config.
+    pub fn with_api_tokens(self, api_tokens: &[ApiTokenConfig]) -> Self {
+        for api_token in api_tokens {
+            let scopes = api_token.scopes.iter().fold(0, |scopes, scope| {
+                scopes
+                    | match scope {
+                        TokenScope::Play => Scope::Play as u64,
+                        TokenScope::Spectate => Scope::Spectate as u64,
+                        TokenScope::Admin => Scope::Admin as u64,
+                        TokenScope::Observe => Scope::Observe as u64,
+                    }
+            });
+            self.api_tokens.lock().unwrap().insert(
+                api_token.token.clone(),
+                ApiToken {
+                    name: api_token.name.clone(),
+                    scopes,
+                },
+            );
+        }
+        self
+    }
+
+    fn notice(message: &str, maintenance: bool) -> Response {
+        Response::event(
+            State::Notice,
+            Arc::new(ResponseData::Notice(Notice {
+                message: message.to_string(),
+                maintenance,
+                redirect: String::new(),
+            })),
+        )
+    }
+
+    /// Accepts the admin token, or an API token with the admin scope.
+    pub fn authorize(&self, token: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self
+            .get_api_token(token)
+            .is_some_and(|api_token| api_token.scopes & Scope::Admin as u64 != 0)
+        {
+            return Ok(());
+        }
+        let expected = match &self.token {
+            Some(expected) => expected,
+            None => return Err("Admin interface disabled".into()),
+        };
+        // Compare every byte so the time taken doesn't leak the prefix length
+        let matched = expected.len() == token.len()
+            && expected
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0;
+        match matched {
+            true => Ok(()),
+            false => Err("Unauthorized".into()),
+        }
+    }
+
+    /// Hand out a new API token for sessions under `name`, keeping the
+    /// `scopes` bits this server knows about.
+    pub fn issue_token(&self, name: String, scopes: u64) -> String {
+        let token = rand::random::<[u8; 32]>()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        self.api_tokens.lock().unwrap().insert(
+            token.clone(),
+            ApiToken {
+                name,
+                scopes: scopes & Scope::ALL,
+            },
+        );
+        token
+    }
+
+    /// Returns whether there was such a token.
+    pub fn revoke_token(&self, token: &str) -> bool {
+        self.api_tokens.lock().unwrap().remove(token).is_some()
+    }
+
+    pub fn get_api_token(&self, token: &str) -> Option<ApiToken> {
+        self.api_tokens.lock().unwrap().get(token).cloned()
+    }
+
+    /// Remove the player from every lobby and game, then close their
+    /// connection.
+    pub fn kick(
+        &self,
+        player_id: u32,
+        reason: &str,
+    ) -> Result<Arc<Player>, Box<dyn Error + Send + Sync>> {
+        let player = match self.player_service.get_player(player_id) {
+            Some(player) => player,
+            None => return Err("Player not found".into()),
+        };
+        Ok(self.player_service.kick(player, reason)?)
+    }
+
+    /// Ban a name and kick everyone currently online under it.
+    pub fn ban(&self, name: String) -> Vec<Arc<Player>> {
+        self.banned_names.lock().unwrap().insert(name.clone());
+        self.player_service
+            .get_players()
+            .into_iter()
+            .filter(|player| player.name == name)
+            .filter_map(|player| self.kick(player.id, "Banned by an operator").ok())
+            .collect()
+    }
+
+    pub fn is_banned(&self, name: &str) -> bool {
+        self.banned_names.lock().unwrap().contains(name)
+    }
+
+    /// Push a notice to every player online here and on the other
+    /// instances, returning how many were reached here.
+    pub fn announce(&self, message: &str, maintenance: bool) -> u32 {
+        self.player_service
+            .get_cluster_service()
+            .publish(ClusterEvent::Announce {
+                message: message.to_string(),
+            });
+        self.announce_here(message, maintenance)
+    }
+
+    /// Push a notice to every player online on this instance, returning how
+    /// many were reached.
+    pub fn announce_here(&self, message: &str, maintenance: bool) -> u32 {
+        let players = self.player_service.get_players();
+        let broadcast_service = self.player_service.get_broadcast_service();
+        for player in players.iter() {
+            broadcast_service.send_to_player(player.id, AdminService::notice(message, maintenance));
+        }
+        players.len() as u32
+    }
+
+    /// While enabled new clients are refused with `message`, players already
+    /// online here are told once and can keep playing.
+    pub fn set_maintenance(&self, enabled: bool, message: String) -> u32 {
+        let recipients = self.announce_here(&message, enabled);
+        *self.maintenance.lock().unwrap() = match enabled {
+            true => Some(message),
+            false => None,
+        };
+        recipients
+    }
+
+    pub fn get_maintenance(&self) -> Option<String> {
+        self.maintenance.lock().unwrap().clone()
+    }
+
+    /// Replace the message of the day, an empty one stops the greeting.
+    pub fn set_motd(&self, motd: String) {
+        *self.motd.lock().unwrap() = Some(motd).filter(|motd| !motd.is_empty());
+    }
+
+    pub fn get_motd(&self) -> Option<String> {
+        self.motd.lock().unwrap().clone()
+    }
+
+    /// Ask the server to reload its config. Requests made while a reload is
+    /// waiting to start are folded into it.
+    pub fn request_reload(&self) {
+        self.reload.notify_one();
+    }
+
+    pub async fn reload_requested(&self) {
+        self.reload.notified().await
+    }
+
+    /// The notice sent to a player right after they connect, if there is a
+    /// message of the day.
+    pub fn motd_notice(&self) -> Option<Response> {
+        self.get_motd()
+            .map(|motd| AdminService::notice(&motd, false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::service::{game_service::GameService, lobby_service::LobbyService};
+
+    fn new_service(token: Option<String>) -> AdminService {
+        AdminService::new(
+            token,
+            Arc::new(PlayerService::new(
+                Arc::new(LobbyService::new()),
+                Arc::new(GameService::new(HashSet::new())),
+            )),
+        )
+    }
+
+    #[test]
+    fn authorize_with_correct_token_should_be_ok() {
+        let service = new_service(Some(String::from("secret")));
+        assert!(service.authorize("secret").is_ok());
+    }
+
+    #[test]
+    fn authorize_with_wrong_token_should_return_error() {
+        let service = new_service(Some(String::from("secret")));
+        assert!(service.authorize("secreT").is_err());
+        assert!(service.authorize("").is_err());
+    }
+
+    #[test]
+    fn authorize_without_configured_token_should_return_error() {
+        let service = new_service(Some(String::new()));
+        assert!(service.authorize("").is_err());
+    }
+
+    #[test]
+    fn authorize_with_admin_scoped_api_token_should_be_ok() {
+        let service = new_service(None);
+        let admin = service.issue_token(String::from("ops"), Scope::Admin as u64);
+        let bot = service.issue_token(String::from("bot"), Scope::INTERACTIVE);
+        assert!(service.authorize(&admin).is_ok());
+        assert!(service.authorize(&bot).is_err());
+        assert!(service.revoke_token(&admin));
+        assert!(service.authorize(&admin).is_err());
+        assert!(!service.revoke_token(&admin));
+    }
+
+    #[test]
+    fn with_api_tokens_should_map_scopes() {
+        let service = new_service(None).with_api_tokens(&[ApiTokenConfig {
+            name: String::from("bot"),
+            token: String::from("secret"),
+            scopes: vec![TokenScope::Play, TokenScope::Spectate],
+        }]);
+        assert_eq!(
+            service.get_api_token("secret"),
+            Some(ApiToken {
+                name: String::from("bot"),
+                scopes: Scope::INTERACTIVE,
+            })
+        );
+        assert!(service.get_api_token("guess").is_none());
+    }
+
+    #[test]
+    fn ban_with_online_player_should_kick_player() {
+        let service = new_service(None);
+        service.player_service.add_player(0, String::from("test"));
+        service.player_service.add_player(1, String::from("test1"));
+        let kicked = service.ban(String::from("test"));
+        assert_eq!(kicked.len(), 1);
+        assert!(service.is_banned("test"));
+        assert!(service.player_service.get_player(0).is_none());
+        assert!(service.player_service.get_player(1).is_some());
+    }
+
+    #[test]
+    fn set_maintenance_with_disabled_should_clear_message() {
+        let service = new_service(None);
+        service.set_maintenance(true, String::from("restarting"));
+        assert_eq!(service.get_maintenance(), Some(String::from("restarting")));
+        service.set_maintenance(false, String::new());
+        assert_eq!(service.get_maintenance(), None);
+    }
+
+    #[test]
+    fn set_motd_with_empty_message_should_stop_greeting() {
+        let service = new_service(None).with_motd(String::from("welcome"));
+        assert_eq!(service.get_motd(), Some(String::from("welcome")));
+        assert!(service.motd_notice().is_some());
+        service.set_motd(String::new());
+        assert_eq!(service.get_motd(), None);
+        assert!(service.motd_notice().is_none());
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{frame::ResponseData, operation::Operation};
+
+/// Full logs kept besides the one being written, as `<path>.1` (the newest)
+/// to `<path>.5`.
+pub const AUDIT_LOG_KEEP: usize = 5;
+
+/// One state-changing request, as a line of JSON in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditRecord {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub client_id: u32,
+    /// The request's trace id, to find what it logged.
+    pub trace_id: String,
+    /// The client's address, as the load balancer reported it when there's
+    /// one in front.
+    pub address: IpAddr,
+    pub opcode: u8,
+    pub operation: String,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum Outcome {
+    Ok,
+    Error { code: i32, message: String },
+}
+
+#[derive(Debug)]
+struct AuditLog {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_bytes: u64,
+}
+
+impl AuditLog {
+    fn open(path: PathBuf, max_bytes: u64) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            len,
+            max_bytes,
+        })
+    }
+
+    fn write(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.len > 0 && self.len + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.len += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift every kept log one number up, dropping the oldest, and start an
+    /// empty one.
+    fn rotate(&mut self) -> io::Result<()> {
+        for i in (1..AUDIT_LOG_KEEP).rev() {
+            match fs::rename(rotated_path(&self.path, i), rotated_path(&self.path, i + 1)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        *self = AuditLog::open(self.path.clone(), self.max_bytes)?;
+        Ok(())
+    }
+}
+
+/// `path` with `.i` appended, where the `i`th newest full log is kept.
+pub fn rotated_path(path: &Path, i: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", i));
+    PathBuf::from(name)
+}
+
+/// Appends a record of every state-changing request to a file that is
+/// rotated once it grows past a size, for looking into abuse and desyncs
+/// after the fact.
+#[derive(Debug, Default)]
+pub struct AuditService {
+    log: Option<Mutex<AuditLog>>,
+}
+
+impl AuditService {
+    /// Keeps no records.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Append to the log at `path`, rotating it once writing a record would
+    /// take it past `max_bytes`.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        Ok(Self {
+            log: Some(Mutex::new(AuditLog::open(path.into(), max_bytes)?)),
+        })
+    }
+
+    /// Record how `client_id`'s request for `operation` turned out, if it is
+    /// one that changes state.
+    pub fn record(
+        &self,
+        client_id: u32,
+        trace_id: &str,
+        address: IpAddr,
+        operation: &Operation,
+        result: &Result<ResponseData, Box<dyn Error + Send + Sync>>,
+    ) {
+        if self.log.is_none() || !operation.is_state_changing() {
+            return;
+        }
+        let outcome = match result {
+            Ok(ResponseData::Error(error)) => Outcome::Error {
+                code: error.code,
+                message: error.message.clone(),
+            },
+            Ok(_) => Outcome::Ok,
+            Err(e) => Outcome::Error {
+                code: 0,
+                message: e.to_string(),
+            },
+        };
+        self.append(&AuditRecord {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            client_id,
+            trace_id: trace_id.to_string(),
+            address,
+            opcode: u8::from(operation),
+            operation: format!("{:?}", operation),
+            outcome,
+        });
+    }
+
+    pub fn append(&self, record: &AuditRecord) {
+        let log = match &self.log {
+            Some(log) => log,
+            None => return,
+        };
+        let result = serde_json::to_vec(record)
+            .map_err(io::Error::from)
+            .and_then(|mut line| {
+                line.push(b'\n');
+                log.lock().unwrap().write(&line)
+            });
+        if let Err(e) = result {
+            warn!(error = %e, "failed to write audit record");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::model::{
+        error::error::{self as proto, ErrorCode},
+        game::set_tile::SetTileResponse,
+    };
+
+    use super::*;
+
+    fn log_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "letterlegend-audit-{}-{}.log",
+            name,
+            rand::random::<u32>()
+        ));
+        for i in 1..=AUDIT_LOG_KEEP {
+            let _ = fs::remove_file(rotated_path(&path, i));
+        }
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn record_should_append_state_changing_requests() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let path = log_path("record");
+        let service = AuditService::open(&path, 1 << 20)?;
+        let address = IpAddr::from([203, 0, 113, 7]);
+        service.record(
+            3,
+            "00000000000000a1",
+            address,
+            &Operation::SetTile,
+            &Ok(ResponseData::SetTile(SetTileResponse { success: true })),
+        );
+        service.record(
+            3,
+            "00000000000000a1",
+            address,
+            &Operation::SetTile,
+            &Ok(ResponseData::Error(proto::Error {
+                message: String::from("Square already has a tile"),
+                code: ErrorCode::TileOccupied as i32,
+            })),
+        );
+        service.record(
+            3,
+            "00000000000000a2",
+            address,
+            &Operation::ListLobby,
+            &Err("ignored".into()),
+        );
+
+        let lines = fs::read_to_string(&path)?
+            .lines()
+            .map(serde_json::from_str::<serde_json::Value>)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["client_id"], 3);
+        assert_eq!(lines[0]["trace_id"], "00000000000000a1");
+        assert_eq!(lines[0]["address"], "203.0.113.7");
+        assert_eq!(lines[0]["opcode"], 9);
+        assert_eq!(lines[0]["operation"], "SetTile");
+        assert_eq!(lines[0]["outcome"]["result"], "ok");
+        assert_eq!(lines[1]["outcome"]["result"], "error");
+        assert_eq!(lines[1]["outcome"]["code"], ErrorCode::TileOccupied as i32);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn append_past_max_bytes_should_rotate_log() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let path = log_path("rotate");
+        let record = AuditRecord {
+            timestamp: 0,
+            client_id: 0,
+            trace_id: String::from("0000000000000000"),
+            address: IpAddr::from([127, 0, 0, 1]),
+            opcode: 0,
+            operation: String::from("Connect"),
+            outcome: Outcome::Ok,
+        };
+        let len = serde_json::to_vec(&record)?.len() as u64 + 1;
+        let service = AuditService::open(&path, len * 2)?;
+        for _ in 0..(AUDIT_LOG_KEEP + 2) * 2 {
+            service.append(&record);
+        }
+        assert_eq!(fs::metadata(&path)?.len(), len * 2);
+        for i in 1..=AUDIT_LOG_KEEP {
+            assert_eq!(fs::metadata(rotated_path(&path, i))?.len(), len * 2);
+            fs::remove_file(rotated_path(&path, i))?;
+        }
+        assert!(!rotated_path(&path, AUDIT_LOG_KEEP + 1).exists());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use std::{collections::HashSet, sync::Arc};
+
+use tracing::debug;
+
+use crate::{error::ServerError, player::Player, storage::Storage};
+
+/// Blocks players keep on others, by account name so they outlast the
+/// blocked player's connection.
+#[derive(Debug)]
+pub struct BlockService {
+    storage: Arc<dyn Storage>,
+}
+
+impl BlockService {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
+    }
+
+    pub fn get_blocked(&self, account: &str) -> Result<HashSet<String>, ServerError> {
+        Ok(self.storage.load_blocked(account)?)
+    }
+
+    /// Block or unblock `name` for `player`, for this connection and the
+    /// ones after.
+    pub fn set_blocked(
+        &self,
+        player: &Player,
+        name: &str,
+        blocked: bool,
+    ) -> Result<(), ServerError> {
+        if name == player.name {
+            return Err("Cannot block yourself".into());
+        }
+        player.set_blocked(name, blocked);
+        self.storage
+            .save_blocked(&player.name, &player.get_blocked())?;
+        debug!(player_id = player.id, name, blocked, "set blocked");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::storage::memory::MemoryStorage;
+
+    use super::*;
+
+    #[test]
+    fn set_blocked_should_keep_block_for_account() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = BlockService::new(Arc::new(MemoryStorage::new()));
+        let player = Player::new(0, String::from("test"));
+        service.set_blocked(&player, "test1", true)?;
+        service.set_blocked(&player, "test2", true)?;
+        service.set_blocked(&player, "test2", false)?;
+        assert!(service.set_blocked(&player, "test", true).is_err());
+        assert!(player.has_blocked("test1"));
+        assert_eq!(
+            service.get_blocked("test")?,
+            HashSet::from([String::from("test1")])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,609 @@
+use std::{collections::VecDeque, sync::OnceLock, time::Duration};
+
+use dashmap::DashMap;
+use tokio::{
+    sync::mpsc::{self, Sender, UnboundedSender},
+    time::{self, Instant},
+};
+use tracing::{warn, Instrument, Span};
+
+use crate::{
+    frame::{Frame, Response},
+    game::game::Game,
+    lobby::lobby::Lobby,
+    model::control::{connect::Capability, preferences::SetPreferencesRequest},
+};
+
+/// Pushes frames to connected players. Each player's outgoing queue is
+/// registered here on connect, so services only need a player id to reach
+/// them. Players without a queue, such as those built in tests, are skipped.
+#[derive(Debug, Default)]
+pub struct BroadcastService {
+    senders: DashMap<u32, Sender<Frame>>,
+    /// Capability bits each player announced on connect, events gated behind
+    /// any other bit are dropped for them.
+    capabilities: DashMap<u32, u64>,
+    /// Optional pushes each player turned off for their session.
+    preferences: DashMap<u32, SetPreferencesRequest>,
+    /// How far behind live spectators see their game.
+    spectator_delay: Duration,
+    /// Spectator events waiting out the delay, drained by a task started
+    /// with the first one.
+    delayed: OnceLock<UnboundedSender<Delayed>>,
+    /// Recent events sent to the whole of each game, by game id, for
+    /// clients that missed some to catch up with.
+    recent: DashMap<u32, RecentEvents>,
+}
+
+/// How many game-wide events each game keeps for `replay`.
+const RECENT_EVENTS: usize = 64;
+
+#[derive(Debug, Default)]
+struct RecentEvents {
+    /// The version the last event brought the game to, 0 before the first.
+    version: u64,
+    /// Oldest first, with the delta sent in place of board updates to
+    /// clients with `Capability::BoardDeltas`.
+    events: VecDeque<(u64, Response, Option<Response>)>,
+}
+
+/// An event held back from a spectator until `due`.
+#[derive(Debug)]
+struct Delayed {
+    due: Instant,
+    player_id: u32,
+    sender: Sender<Frame>,
+    res: Response,
+    /// Span of the request that caused the event.
+    span: Span,
+}
+
+impl BroadcastService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold every event spectators are sent back by `delay`, so watching a
+    /// game can't help anyone playing it.
+    pub fn with_spectator_delay(mut self, delay: Duration) -> Self {
+        self.spectator_delay = delay;
+        self
+    }
+
+    pub fn register(&self, player_id: u32, sender: Sender<Frame>) {
+        self.senders.insert(player_id, sender);
+    }
+
+    pub fn unregister(&self, player_id: u32) {
+        self.senders.remove(&player_id);
+        self.capabilities.remove(&player_id);
+        self.preferences.remove(&player_id);
+    }
+
+    pub fn set_capabilities(&self, player_id: u32, capabilities: u64) {
+        self.capabilities.insert(player_id, capabilities);
+    }
+
+    pub fn has_capability(&self, player_id: u32, capability: Capability) -> bool {
+        self.capabilities
+            .get(&player_id)
+            .is_some_and(|capabilities| *capabilities & capability as u64 != 0)
+    }
+
+    pub fn set_preferences(&self, player_id: u32, preferences: SetPreferencesRequest) {
+        self.preferences.insert(player_id, preferences);
+    }
+
+    /// Whether the player should get `res`, given the capabilities they
+    /// announced and the pushes they turned off.
+    fn wants(&self, player_id: u32, res: &Response) -> bool {
+        if let Some(capability) = res.get_capability() {
+            if !self.has_capability(player_id, capability) {
+                return false;
+            }
+        }
+        match res.get_event_class() {
+            Some(class) => !self
+                .preferences
+                .get(&player_id)
+                .is_some_and(|preferences| preferences.mutes(class)),
+            None => true,
+        }
+    }
+
+    pub fn is_registered(&self, player_id: u32) -> bool {
+        self.senders.contains_key(&player_id)
+    }
+
+    /// Queue `res` for the player without waiting for room, returns whether
+    /// they are connected. Events the player didn't announce the capability
+    /// for or turned off are dropped.
+    pub fn send_to_player(&self, player_id: u32, res: Response) -> bool {
+        let sender = match self.senders.get(&player_id) {
+            Some(sender) => sender.clone(),
+            None => return false,
+        };
+        if !self.wants(player_id, &res) {
+            return true;
+        }
+        // In the span of the request that caused it, so its logs carry the
+        // request's trace id
+        tokio::spawn(
+            async move {
+                if let Err(e) = sender.send(Frame::Response(res)).await {
+                    warn!(player_id, error = %e, "failed to send broadcast");
+                }
+            }
+            .in_current_span(),
+        );
+        true
+    }
+
+    /// Send `res` to everyone in the lobby but `except`.
+    pub fn send_to_lobby(&self, lobby: &Lobby, res: Response, except: Option<u32>) {
+        for lobby_player in lobby.get_players() {
+            if Some(lobby_player.player.id) != except {
+                self.send_to_player(lobby_player.player.id, res.clone());
+            }
+        }
+    }
+
+    pub fn get_spectator_delay(&self) -> Duration {
+        self.spectator_delay
+    }
+
+    /// The version of the game's state as of the last game-wide event.
+    pub fn get_state_version(&self, game_id: u32) -> u64 {
+        self.recent.get(&game_id).map_or(0, |recent| recent.version)
+    }
+
+    /// Move the game to its next state version, stamping `res` and `delta`
+    /// with it and keeping them for `replay`.
+    fn record(&self, game_id: u32, res: &mut Response, delta: Option<&mut Response>) {
+        let mut recent = self.recent.entry(game_id).or_default();
+        recent.version += 1;
+        let version = recent.version;
+        res.set_state_version(version);
+        let delta = delta.map(|delta| {
+            delta.set_state_version(version);
+            delta.clone()
+        });
+        recent.events.push_back((version, res.clone(), delta));
+        if recent.events.len() > RECENT_EVENTS {
+            recent.events.pop_front();
+        }
+    }
+
+    /// Send `res` to one player or spectator of the game, stamped with the
+    /// game's current state version, returns whether they are connected.
+    pub fn send_to_game_player(&self, game: &Game, player_id: u32, mut res: Response) -> bool {
+        res.set_state_version(self.get_state_version(game.id));
+        match game.get_spectators().contains(&player_id) {
+            true => {
+                self.send_to_spectator(player_id, res);
+                self.is_registered(player_id)
+            }
+            false => self.send_to_player(player_id, res),
+        }
+    }
+
+    /// Send the game-wide events after state version `from` to the player
+    /// again, the way spectators get them if they watch. Returns false, and
+    /// sends nothing, when some of them are no longer kept.
+    pub fn replay(&self, game: &Game, player_id: u32, from: u64) -> bool {
+        let events = match self.recent.get(&game.id) {
+            Some(recent) => {
+                let oldest = recent
+                    .events
+                    .front()
+                    .map_or(recent.version + 1, |event| event.0);
+                if from < recent.version && from + 1 < oldest {
+                    return false;
+                }
+                recent
+                    .events
+                    .iter()
+                    .filter(|event| event.0 > from)
+                    .cloned()
+                    .collect::<Vec<_>>()
+            }
+            None => return true,
+        };
+        let spectating = game.get_spectators().contains(&player_id);
+        let wants_delta = self.has_capability(player_id, Capability::BoardDeltas);
+        for (_, res, delta) in events {
+            let res = match delta {
+                Some(delta) if wants_delta => delta,
+                _ => res,
+            };
+            match spectating {
+                true => self.send_to_spectator(player_id, res),
+                false => {
+                    self.send_to_player(player_id, res);
+                }
+            }
+        }
+        true
+    }
+
+    /// Drop the events kept for a game that is over.
+    pub fn forget_game(&self, game_id: u32) {
+        self.recent.remove(&game_id);
+    }
+
+    /// Whether the player is sent the game-wide events about their own
+    /// requests, which `except` leaves out for everyone else. `board` is for
+    /// board updates.
+    pub fn includes_own(&self, player_id: u32, board: bool) -> bool {
+        self.has_capability(player_id, Capability::StateVersions)
+            || (board && self.has_capability(player_id, Capability::BoardDeltas))
+    }
+
+    /// Send `res` to everyone in the game but `except`, after the delay to
+    /// whoever watches it, and right away to its subscribers. It moves the
+    /// game to its next state version.
+    pub fn send_to_game(&self, game: &Game, mut res: Response, except: Option<u32>) {
+        self.record(game.id, &mut res, None);
+        for game_player in game.get_players() {
+            let player_id = game_player.player.id;
+            if Some(player_id) != except || self.includes_own(player_id, false) {
+                self.send_to_player(player_id, res.clone());
+            }
+        }
+        for spectator_id in game.get_spectators() {
+            self.send_to_spectator(spectator_id, res.clone());
+        }
+        for subscriber_id in game.get_subscribers() {
+            self.send_to_player(subscriber_id, res.clone());
+        }
+    }
+
+    /// Send a board update like `send_to_game`, `delta` to those who
+    /// announced `Capability::BoardDeltas` and `full` to everyone else.
+    pub fn send_board_to_game(
+        &self,
+        game: &Game,
+        mut full: Response,
+        mut delta: Response,
+        except: Option<u32>,
+    ) {
+        self.record(game.id, &mut full, Some(&mut delta));
+        let pick = |player_id| match self.has_capability(player_id, Capability::BoardDeltas) {
+            true => delta.clone(),
+            false => full.clone(),
+        };
+        for game_player in game.get_players() {
+            let player_id = game_player.player.id;
+            if Some(player_id) != except || self.includes_own(player_id, true) {
+                self.send_to_player(player_id, pick(player_id));
+            }
+        }
+        for spectator_id in game.get_spectators() {
+            self.send_to_spectator(spectator_id, pick(spectator_id));
+        }
+        for subscriber_id in game.get_subscribers() {
+            self.send_to_player(subscriber_id, pick(subscriber_id));
+        }
+    }
+
+    /// Queue `res` for the spectator once the spectator delay has passed.
+    /// Events are dropped rather than waited on when their queue is full.
+    pub fn send_to_spectator(&self, player_id: u32, res: Response) {
+        if self.spectator_delay.is_zero() {
+            self.send_to_player(player_id, res);
+            return;
+        }
+        let sender = match self.senders.get(&player_id) {
+            Some(sender) => sender.clone(),
+            None => return,
+        };
+        if !self.wants(player_id, &res) {
+            return;
+        }
+        let delayed = Delayed {
+            due: Instant::now() + self.spectator_delay,
+            player_id,
+            sender,
+            res,
+            span: Span::current(),
+        };
+        let _ = self
+            .delayed
+            .get_or_init(BroadcastService::start_delay_queue)
+            .send(delayed);
+    }
+
+    fn start_delay_queue() -> UnboundedSender<Delayed> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Delayed>();
+        tokio::spawn(async move {
+            // Everything waits the same delay, so events come due in the
+            // order they were queued
+            while let Some(delayed) = rx.recv().await {
+                time::sleep_until(delayed.due).await;
+                if let Err(e) = delayed.sender.try_send(Frame::Response(delayed.res)) {
+                    delayed.span.in_scope(|| {
+                        warn!(player_id = delayed.player_id, error = %e, "failed to send delayed broadcast")
+                    });
+                }
+            }
+        });
+        tx
+    }
+
+    /// Send `last`, if any, then ask the writer to close the connection once
+    /// queued frames are out. Nothing more reaches the player afterwards.
+    pub fn close(&self, player_id: u32, last: Option<Response>) {
+        self.capabilities.remove(&player_id);
+        self.preferences.remove(&player_id);
+        let sender = match self.senders.remove(&player_id) {
+            Some((_, sender)) => sender,
+            None => return,
+        };
+        tokio::spawn(
+            async move {
+                if let Some(res) = last {
+                    if let Err(e) = sender.send(Frame::Response(res)).await {
+                        warn!(player_id, error = %e, "failed to send broadcast");
+                    }
+                }
+                if let Err(e) = sender.send(Frame::Close).await {
+                    warn!(player_id, error = %e, "failed to close connection");
+                }
+            }
+            .in_current_span(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, sync::Arc};
+
+    use tokio::sync::mpsc::{self, Receiver};
+
+    use crate::{
+        frame::{EventClass, ResponseData},
+        game::game::Game,
+        model::{
+            chat::{broadcast::ChatBroadcast, quick_chat::QuickChat},
+            control::notice::Notice,
+            game::broadcast::{GameBroadcast, GameEvent},
+            state::State,
+        },
+        player::Player,
+    };
+
+    use super::*;
+
+    fn notice(message: &str) -> Response {
+        Response::event(
+            State::Notice,
+            Arc::new(ResponseData::Notice(Notice {
+                message: String::from(message),
+                maintenance: false,
+                redirect: String::new(),
+            })),
+        )
+    }
+
+    async fn next_notice(rx: &mut Receiver<Frame>) -> Option<String> {
+        match rx.recv().await? {
+            Frame::Response(res) => match res.get_data().as_ref() {
+                ResponseData::Notice(notice) => Some(notice.message.clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_lobby_should_skip_except_and_unregistered_players(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = BroadcastService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        let lobby = Lobby::new(0, 4, leader.clone());
+        let mut receivers = Vec::new();
+        for id in 1..3 {
+            lobby.add_player(Arc::new(Player::new(id, format!("test{}", id))))?;
+            let (tx, rx) = mpsc::channel(4);
+            service.register(id, tx);
+            receivers.push(rx);
+        }
+        let (tx, mut leader_rx) = mpsc::channel(4);
+        service.register(leader.id, tx);
+        service.unregister(2);
+        service.send_to_lobby(&lobby, notice("hello"), Some(leader.id));
+        assert_eq!(
+            next_notice(&mut receivers[0]).await,
+            Some(String::from("hello"))
+        );
+        assert!(receivers[1].recv().await.is_none());
+        assert!(leader_rx.try_recv().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_to_player_with_muted_emotes_should_still_send_chat() {
+        let service = BroadcastService::new();
+        let (tx, mut rx) = mpsc::channel(4);
+        service.register(0, tx);
+        service.set_preferences(
+            0,
+            SetPreferencesRequest {
+                mute_emotes: true,
+                ..Default::default()
+            },
+        );
+        let chat = |quick_chat: Option<QuickChat>| {
+            Response::event(
+                State::Chat,
+                Arc::new(ResponseData::ChatBroadcast(ChatBroadcast {
+                    message: String::from("hello"),
+                    quick_chat: quick_chat.map(|phrase| phrase as i32),
+                    ..Default::default()
+                })),
+            )
+        };
+        assert!(service.send_to_player(0, chat(Some(QuickChat::EmoteSmile))));
+        service.send_to_player(0, chat(Some(QuickChat::GoodGame)));
+        match rx.recv().await {
+            Some(Frame::Response(res)) => {
+                assert_eq!(res.get_event_class(), Some(EventClass::Chat))
+            }
+            _ => panic!("invalid frame"),
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    fn state_version(frame: Option<Frame>) -> u64 {
+        match frame {
+            Some(Frame::Response(res)) => match res.get_data().as_ref() {
+                ResponseData::GameBroadcast(broadcast) => broadcast.state_version,
+                _ => panic!("invalid response"),
+            },
+            _ => panic!("invalid frame"),
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_game_with_state_versions_should_include_except(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = BroadcastService::new();
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players);
+        let mut receivers = Vec::new();
+        for id in 0..2 {
+            let (tx, rx) = mpsc::channel(4);
+            service.register(id, tx);
+            receivers.push(rx);
+        }
+        service.set_capabilities(0, Capability::StateVersions as u64);
+        let shuffle = Response::event(
+            State::GameBroadcast,
+            Arc::new(ResponseData::GameBroadcast(GameBroadcast {
+                event: GameEvent::Shuffle as i32,
+                ..Default::default()
+            })),
+        );
+        service.send_to_game(&game, shuffle.clone(), Some(0));
+        service.send_to_game(&game, shuffle, Some(1));
+        assert_eq!(state_version(receivers[0].recv().await), 1);
+        assert_eq!(state_version(receivers[0].recv().await), 2);
+        assert_eq!(state_version(receivers[1].recv().await), 1);
+        tokio::task::yield_now().await;
+        assert!(receivers[1].try_recv().is_err());
+        assert_eq!(service.get_state_version(game.id), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn replay_should_resend_kept_events_and_refuse_dropped_ones(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = BroadcastService::new();
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        let shuffle = Response::event(
+            State::GameBroadcast,
+            Arc::new(ResponseData::GameBroadcast(GameBroadcast {
+                event: GameEvent::Shuffle as i32,
+                ..Default::default()
+            })),
+        );
+        for _ in 0..RECENT_EVENTS + 2 {
+            service.send_to_game(&game, shuffle.clone(), None);
+        }
+        let (tx, mut rx) = mpsc::channel(4);
+        service.register(0, tx);
+        assert!(!service.replay(&game, 0, 1));
+        assert!(service.replay(&game, 0, RECENT_EVENTS as u64));
+        assert_eq!(state_version(rx.recv().await), RECENT_EVENTS as u64 + 1);
+        assert_eq!(state_version(rx.recv().await), RECENT_EVENTS as u64 + 2);
+        assert!(service.replay(&game, 0, RECENT_EVENTS as u64 + 2));
+        tokio::task::yield_now().await;
+        assert!(rx.try_recv().is_err());
+        service.forget_game(game.id);
+        assert_eq!(service.get_state_version(game.id), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_to_player_should_drop_events_without_announced_capability() {
+        let service = BroadcastService::new();
+        let (tx, mut rx) = mpsc::channel(4);
+        service.register(0, tx);
+        let draw_offer = Response::event(
+            State::GameBroadcast,
+            Arc::new(ResponseData::GameBroadcast(GameBroadcast {
+                event: GameEvent::DrawOffer as i32,
+                ..Default::default()
+            })),
+        );
+        assert!(service.send_to_player(0, draw_offer.clone()));
+        service.send_to_player(0, notice("hello"));
+        assert_eq!(next_notice(&mut rx).await, Some(String::from("hello")));
+        service.set_capabilities(0, Capability::DrawEvents as u64);
+        service.send_to_player(0, draw_offer);
+        match rx.recv().await {
+            Some(Frame::Response(res)) => {
+                assert_eq!(res.get_capability(), Some(Capability::DrawEvents))
+            }
+            _ => panic!("invalid frame"),
+        }
+    }
+
+    #[tokio::test]
+    async fn close_should_send_last_frame_before_close() {
+        let service = BroadcastService::new();
+        let (tx, mut rx) = mpsc::channel(4);
+        service.register(0, tx);
+        service.close(0, Some(notice("bye")));
+        assert_eq!(next_notice(&mut rx).await, Some(String::from("bye")));
+        assert!(matches!(rx.recv().await, Some(Frame::Close)));
+        assert!(!service.send_to_player(0, notice("hello")));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn send_to_game_should_delay_spectators_only() {
+        let service = BroadcastService::new().with_spectator_delay(Duration::from_secs(60));
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        game.add_spectator(1);
+        let (tx, mut player_rx) = mpsc::channel(4);
+        service.register(0, tx);
+        let (tx, mut spectator_rx) = mpsc::channel(4);
+        service.register(1, tx);
+        service.send_to_game(&game, notice("hello"), None);
+        assert_eq!(
+            next_notice(&mut player_rx).await,
+            Some(String::from("hello"))
+        );
+        time::sleep(Duration::from_secs(59)).await;
+        assert!(spectator_rx.try_recv().is_err());
+        time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(
+            next_notice(&mut spectator_rx).await,
+            Some(String::from("hello"))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn send_to_game_should_reach_subscribers_live_whoever_is_left_out() {
+        let service = BroadcastService::new().with_spectator_delay(Duration::from_secs(60));
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        game.add_subscriber(1);
+        let (tx, mut player_rx) = mpsc::channel(4);
+        service.register(0, tx);
+        let (tx, mut subscriber_rx) = mpsc::channel(4);
+        service.register(1, tx);
+        service.send_to_game(&game, notice("hello"), Some(0));
+        assert_eq!(
+            next_notice(&mut subscriber_rx).await,
+            Some(String::from("hello"))
+        );
+        assert!(player_rx.try_recv().is_err());
+        game.remove_subscriber(1);
+        service.send_to_game(&game, notice("bye"), None);
+        assert_eq!(next_notice(&mut player_rx).await, Some(String::from("bye")));
+        assert!(subscriber_rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,389 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    iter,
+    ops::Range,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tracing::debug;
+
+use crate::{
+    config::{ChatConfig, ChatFilter},
+    frame::{Response, ResponseData},
+    model::{
+        chat::{broadcast::ChatBroadcast, quick_chat::QuickChat},
+        state::State,
+    },
+    player::Player,
+    storage::Storage,
+};
+
+use super::broadcast_service::BroadcastService;
+
+/// Longest chat message or report reason accepted, in characters.
+pub const MAX_MESSAGE_LEN: usize = 256;
+
+/// A player reporting another, kept for moderators to review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub reporter: String,
+    pub reported: String,
+    pub reason: String,
+    /// Seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+#[derive(Debug)]
+pub struct WordFilter {
+    mode: ChatFilter,
+    words: HashSet<String>,
+}
+
+impl WordFilter {
+    pub fn new(config: &ChatConfig) -> Self {
+        Self {
+            mode: config.filter,
+            words: config
+                .blocked_words
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Byte ranges of the blocked words in `message`.
+    fn find_blocked(&self, message: &str) -> Vec<Range<usize>> {
+        let mut blocked = Vec::new();
+        let mut start = None;
+        for (i, c) in message
+            .char_indices()
+            .chain(iter::once((message.len(), ' ')))
+        {
+            match (c.is_alphanumeric(), start) {
+                (true, None) => start = Some(i),
+                (false, Some(word_start)) => {
+                    if self.words.contains(&message[word_start..i].to_lowercase()) {
+                        blocked.push(word_start..i);
+                    }
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+        blocked
+    }
+
+    /// The message as it should be delivered, or an error if it mustn't be.
+    pub fn apply(&self, message: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let blocked = self.find_blocked(message);
+        if blocked.is_empty() {
+            return Ok(message.to_string());
+        }
+        if self.mode == ChatFilter::Reject {
+            return Err("Message contains blocked words".into());
+        }
+        let mut masked = String::with_capacity(message.len());
+        let mut last = 0;
+        for range in blocked {
+            masked.push_str(&message[last..range.start]);
+            masked.extend(message[range.clone()].chars().map(|_| '*'));
+            last = range.end;
+        }
+        masked.push_str(&message[last..]);
+        Ok(masked)
+    }
+}
+
+#[derive(Debug)]
+pub struct ChatService {
+    filter: WordFilter,
+    storage: Arc<dyn Storage>,
+    broadcast_service: Arc<BroadcastService>,
+}
+
+impl ChatService {
+    pub fn new(
+        config: &ChatConfig,
+        storage: Arc<dyn Storage>,
+        broadcast_service: Arc<BroadcastService>,
+    ) -> Self {
+        Self {
+            filter: WordFilter::new(config),
+            storage,
+            broadcast_service,
+        }
+    }
+
+    /// Everyone sharing the player's game, or lobby if not playing, who
+    /// hasn't muted or blocked them.
+    pub fn get_recipients(player: &Arc<Player>) -> Vec<Arc<Player>> {
+        let players = match (player.get_game(), player.get_lobby()) {
+            (Some(game), _) => game
+                .get_players()
+                .into_iter()
+                .map(|game_player| game_player.player.clone())
+                .collect(),
+            (None, Some(lobby)) => lobby
+                .get_players()
+                .into_iter()
+                .map(|lobby_player| lobby_player.player.clone())
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+        players
+            .into_iter()
+            .filter(|other| {
+                other.id != player.id
+                    && !other.has_muted(player.id)
+                    && !other.has_blocked(&player.name)
+            })
+            .collect()
+    }
+
+    /// Filter the message and deliver it, returns the message as delivered.
+    pub fn send(
+        &self,
+        player: Arc<Player>,
+        message: &str,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let message = message.trim();
+        if message.is_empty() {
+            return Err("Empty message".into());
+        }
+        if message.chars().count() > MAX_MESSAGE_LEN {
+            return Err("Message too long".into());
+        }
+        let quick_chat_only = match (player.get_game(), player.get_lobby()) {
+            (Some(game), _) => game.get_rules().quick_chat_only,
+            (None, Some(lobby)) => lobby.get_rules().quick_chat_only,
+            (None, None) => return Err("Player not in a lobby or game".into()),
+        };
+        if quick_chat_only {
+            return Err("Only quick chat is allowed here".into());
+        }
+        let message = self.filter.apply(message)?;
+        self.deliver(&player, message.clone(), None);
+        Ok(message)
+    }
+
+    /// Deliver a quick chat phrase, returns its text. Phrases skip the word
+    /// filter and are allowed where free chat is off.
+    pub fn send_quick(
+        &self,
+        player: Arc<Player>,
+        phrase: QuickChat,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        if player.get_game().is_none() && player.get_lobby().is_none() {
+            return Err("Player not in a lobby or game".into());
+        }
+        let message = phrase.text().to_string();
+        self.deliver(&player, message.clone(), Some(phrase));
+        Ok(message)
+    }
+
+    fn deliver(&self, player: &Arc<Player>, message: String, quick_chat: Option<QuickChat>) {
+        let recipients = ChatService::get_recipients(player);
+        debug!(
+            player_id = player.id,
+            recipients = recipients.len(),
+            quick = quick_chat.is_some(),
+            "send chat"
+        );
+        let chat = ChatBroadcast {
+            player: Some(player.clone().into()),
+            message,
+            quick_chat: quick_chat.map(|phrase| phrase as i32),
+        };
+        // Only lobby chat is kept, game chat ends with the game
+        if let (None, Some(lobby)) = (player.get_game(), player.get_lobby()) {
+            lobby.record_chat(chat.clone());
+        }
+        let res = Response::event(State::Chat, Arc::new(ResponseData::ChatBroadcast(chat)));
+        for recipient in recipients {
+            self.broadcast_service
+                .send_to_player(recipient.id, res.clone());
+        }
+    }
+
+    pub fn report(
+        &self,
+        reporter: &Player,
+        reported: &Player,
+        reason: &str,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if reporter.id == reported.id {
+            return Err("Cannot report yourself".into());
+        }
+        if reason.chars().count() > MAX_MESSAGE_LEN {
+            return Err("Reason too long".into());
+        }
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.storage.save_report(&Report {
+            reporter: reporter.name.clone(),
+            reported: reported.name.clone(),
+            reason: reason.trim().to_string(),
+            created_at,
+        })?;
+        debug!(
+            reporter_id = reporter.id,
+            reported_id = reported.id,
+            "report player"
+        );
+        Ok(())
+    }
+
+    pub fn get_reports(&self, account: &str) -> Result<Vec<Report>, Box<dyn Error + Send + Sync>> {
+        self.storage.load_reports(account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        frame::Frame, game::rules::GameRules, service::lobby_service::LobbyService,
+        storage::memory::MemoryStorage,
+    };
+
+    use super::*;
+
+    fn config(filter: ChatFilter) -> ChatConfig {
+        ChatConfig {
+            filter,
+            blocked_words: vec![String::from("Darn")],
+        }
+    }
+
+    #[test]
+    fn apply_with_mask_should_star_out_whole_blocked_words(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let filter = WordFilter::new(&config(ChatFilter::Mask));
+        assert_eq!(
+            filter.apply("DARN it, darned darn!")?,
+            "**** it, darned ****!"
+        );
+        assert_eq!(filter.apply("hello")?, "hello");
+        Ok(())
+    }
+
+    #[test]
+    fn apply_with_reject_should_return_error() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let filter = WordFilter::new(&config(ChatFilter::Reject));
+        assert!(filter.apply("oh darn").is_err());
+        assert_eq!(filter.apply("darned")?, "darned");
+        Ok(())
+    }
+
+    #[test]
+    fn get_recipients_should_skip_sender_and_players_who_muted_them(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = LobbyService::new();
+        let sender = Arc::new(Player::new(0, String::from("test")));
+        let lobby = lobby_service.create_lobby(sender.clone(), 4)?;
+        let listener = Arc::new(Player::new(1, String::from("test1")));
+        let muter = Arc::new(Player::new(2, String::from("test2")));
+        for player in [&listener, &muter] {
+            lobby_service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        }
+        muter.set_muted(sender.id, true);
+        let recipients = ChatService::get_recipients(&sender);
+        assert_eq!(recipients, vec![listener]);
+        Ok(())
+    }
+
+    #[test]
+    fn get_recipients_should_skip_players_who_blocked_sender(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = LobbyService::new();
+        let blocker = Arc::new(Player::new(0, String::from("test")));
+        let lobby = lobby_service.create_lobby(blocker.clone(), 4)?;
+        let sender = Arc::new(Player::new(1, String::from("test1")));
+        lobby_service.add_player_to_lobby(sender.clone(), lobby)?;
+        blocker.set_blocked(&sender.name, true);
+        assert!(ChatService::get_recipients(&sender).is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_with_quick_chat_only_should_only_allow_quick_chat(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let broadcast_service = Arc::new(BroadcastService::new());
+        let service = ChatService::new(
+            &config(ChatFilter::Reject),
+            Arc::new(MemoryStorage::new()),
+            broadcast_service.clone(),
+        );
+        let lobby_service = LobbyService::new();
+        let sender = Arc::new(Player::new(0, String::from("test")));
+        let lobby = lobby_service.create_lobby(sender.clone(), 4)?;
+        let listener = Arc::new(Player::new(1, String::from("test1")));
+        lobby_service.add_player_to_lobby(listener.clone(), lobby.clone())?;
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        broadcast_service.register(listener.id, tx);
+        lobby.set_settings(
+            4,
+            2,
+            GameRules {
+                quick_chat_only: true,
+                ..GameRules::default()
+            },
+        );
+        assert!(service.send(sender.clone(), "hello").is_err());
+        assert_eq!(
+            service.send_quick(sender, QuickChat::GoodLuck)?,
+            "Good luck!"
+        );
+        match rx.recv().await {
+            Some(Frame::Response(res)) => match res.get_data().as_ref() {
+                ResponseData::ChatBroadcast(chat) => {
+                    assert_eq!(chat.message, "Good luck!");
+                    assert_eq!(chat.quick_chat, Some(QuickChat::GoodLuck as i32));
+                }
+                _ => panic!("invalid response"),
+            },
+            _ => panic!("invalid frame"),
+        }
+        assert!(rx.try_recv().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn report_should_save_report_against_account() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = ChatService::new(
+            &config(ChatFilter::Mask),
+            Arc::new(MemoryStorage::new()),
+            Arc::new(BroadcastService::new()),
+        );
+        let reporter = Player::new(0, String::from("test"));
+        let reported = Player::new(1, String::from("test1"));
+        service.report(&reporter, &reported, " spam ")?;
+        assert!(service.report(&reporter, &reporter, "").is_err());
+        let reports = service.get_reports("test1")?;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].reporter, "test");
+        assert_eq!(reports[0].reason, "spam");
+        Ok(())
+    }
+
+    #[test]
+    fn send_in_lobby_should_keep_message_for_players_who_join(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = ChatService::new(
+            &config(ChatFilter::Mask),
+            Arc::new(MemoryStorage::new()),
+            Arc::new(BroadcastService::new()),
+        );
+        let lobby_service = LobbyService::new();
+        let sender = Arc::new(Player::new(0, String::from("test")));
+        let lobby = lobby_service.create_lobby(sender.clone(), 4)?;
+        service.send(sender, "darn hello")?;
+        let history = lobby.get_chat_history(&Player::new(1, String::from("test1")));
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].message, "**** hello");
+        Ok(())
+    }
+}
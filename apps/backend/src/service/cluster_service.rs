@@ -0,0 +1,435 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+};
+#[cfg(feature = "redis")]
+use std::{error::Error, time::Duration};
+
+use dashmap::DashMap;
+#[cfg(feature = "redis")]
+use redis::{
+    aio::{MultiplexedConnection, PubSub},
+    AsyncCommands,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+#[cfg(feature = "redis")]
+use tokio::{sync::mpsc, time};
+#[cfg(feature = "redis")]
+use tokio_stream::StreamExt;
+#[cfg(feature = "redis")]
+use tracing::{error, warn};
+
+use super::lobby_service::LobbyHandoff;
+
+/// Bits at the top of every lobby id that say which instance made it.
+const NODE_BITS: u32 = 8;
+/// How many instances can share a Redis, numbered from 0.
+pub const MAX_NODES: u16 = 1 << NODE_BITS;
+
+/// Start of every key and channel, so the instances can share a Redis with
+/// other applications.
+#[cfg(feature = "redis")]
+const PREFIX: &str = "letterlegend";
+/// How long an instance's presence outlives its last refresh, so players on
+/// one that died stop counting.
+#[cfg(feature = "redis")]
+const PRESENCE_TTL: Duration = Duration::from_secs(60);
+#[cfg(feature = "redis")]
+const REFRESH_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Something every instance should act on, published by the one it
+/// happened on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClusterEvent {
+    /// An operator's notice for every online player.
+    Announce { message: String },
+    /// A lobby moving to `node`, which takes it over. The others only note
+    /// where it went.
+    Handoff { node: u16, lobby: LobbyHandoff },
+}
+
+#[cfg(feature = "redis")]
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    /// Node that published the event, which already acted on it.
+    origin: u16,
+    event: ClusterEvent,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Online(String),
+    Offline(String),
+    Publish(ClusterEvent),
+}
+
+/// Shares who is online and broadcasts between server processes behind one
+/// load balancer, through Redis. Each lobby and game lives on one instance,
+/// a lobby on the one its id names until it is handed off. Calls only queue
+/// work for a background task, so they never wait on Redis.
+#[derive(Debug)]
+pub struct ClusterService {
+    node_id: u16,
+    /// `None` when running alone.
+    commands: Option<UnboundedSender<Command>>,
+    /// Events other instances published, until the server takes them.
+    events: Mutex<Option<UnboundedReceiver<ClusterEvent>>>,
+    /// Players online on every other instance, as of the last refresh.
+    remote_players: Arc<AtomicU32>,
+    /// Where clients reach every other instance, by node id, as of the last
+    /// refresh.
+    nodes: Arc<Mutex<HashMap<u16, String>>>,
+    /// Nodes lobbies were handed off to, by lobby id.
+    moved: DashMap<u32, u16>,
+}
+
+impl ClusterService {
+    /// For a server running alone as `node_id`, shares nothing.
+    pub fn local(node_id: u16) -> Self {
+        Self {
+            node_id,
+            commands: None,
+            events: Mutex::new(None),
+            remote_players: Arc::new(AtomicU32::new(0)),
+            nodes: Arc::new(Mutex::new(HashMap::new())),
+            moved: DashMap::new(),
+        }
+    }
+
+    /// Join the instances using the Redis at `url` as `node_id`, which
+    /// clients reach at `address`. Fails if another instance holds the node
+    /// id.
+    #[cfg(feature = "redis")]
+    pub async fn connect(
+        url: &str,
+        node_id: u16,
+        address: String,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let client = redis::Client::open(url)?;
+        let mut connection = client.get_multiplexed_tokio_connection().await?;
+        let node_key = format!("{}:node:{}", PREFIX, node_id);
+        if let Some(holder) = connection.get::<_, Option<String>>(&node_key).await? {
+            if holder != address {
+                return Err(format!("node {} is already taken by {}", node_id, holder).into());
+            }
+        }
+        connection
+            .set_ex::<_, _, ()>(&node_key, &address, PRESENCE_TTL.as_secs())
+            .await?;
+        let mut pubsub = client.get_async_pubsub().await?;
+        pubsub.subscribe(format!("{}:events", PREFIX)).await?;
+        let (commands, queue) = mpsc::unbounded_channel();
+        let (events, received) = mpsc::unbounded_channel();
+        let service = Self {
+            commands: Some(commands),
+            events: Mutex::new(Some(received)),
+            ..Self::local(node_id)
+        };
+        tokio::spawn(Self::relay(
+            connection,
+            node_id,
+            address,
+            queue,
+            service.remote_players.clone(),
+            service.nodes.clone(),
+        ));
+        tokio::spawn(Self::listen(pubsub, node_id, events));
+        Ok(service)
+    }
+
+    /// Run the queued commands against Redis, and keep this instance's
+    /// presence and address from expiring while reading everyone else's.
+    #[cfg(feature = "redis")]
+    async fn relay(
+        mut connection: MultiplexedConnection,
+        node_id: u16,
+        address: String,
+        mut queue: UnboundedReceiver<Command>,
+        remote_players: Arc<AtomicU32>,
+        nodes: Arc<Mutex<HashMap<u16, String>>>,
+    ) {
+        let key = format!("{}:presence:{}", PREFIX, node_id);
+        let mut refresh = time::interval(REFRESH_INTERVAL);
+        loop {
+            let result = tokio::select! {
+                command = queue.recv() => match command {
+                    Some(command) => Self::run(&mut connection, &key, node_id, command).await,
+                    None => break,
+                },
+                _ = refresh.tick() => {
+                    Self::refresh(&mut connection, node_id, &address, &remote_players, &nodes)
+                        .await
+                }
+            };
+            if let Err(e) = result {
+                warn!(error = %e, "redis command failed");
+            }
+        }
+    }
+
+    #[cfg(feature = "redis")]
+    async fn run(
+        connection: &mut MultiplexedConnection,
+        key: &str,
+        origin: u16,
+        command: Command,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        match command {
+            Command::Online(name) => {
+                redis::pipe()
+                    .sadd(key, name)
+                    .ignore()
+                    .expire(key, PRESENCE_TTL.as_secs() as i64)
+                    .ignore()
+                    .query_async::<_, ()>(connection)
+                    .await?
+            }
+            Command::Offline(name) => connection.srem::<_, _, ()>(key, name).await?,
+            Command::Publish(event) => {
+                let envelope = serde_json::to_string(&Envelope { origin, event })?;
+                connection
+                    .publish::<_, _, ()>(format!("{}:events", PREFIX), envelope)
+                    .await?
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "redis")]
+    async fn refresh(
+        connection: &mut MultiplexedConnection,
+        node_id: u16,
+        address: &str,
+        remote_players: &AtomicU32,
+        nodes: &Mutex<HashMap<u16, String>>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let ttl = PRESENCE_TTL.as_secs();
+        redis::pipe()
+            .set_ex(format!("{}:node:{}", PREFIX, node_id), address, ttl)
+            .ignore()
+            .expire(format!("{}:presence:{}", PREFIX, node_id), ttl as i64)
+            .ignore()
+            .query_async::<_, ()>(connection)
+            .await?;
+        let mut players = 0;
+        let mut addresses = HashMap::new();
+        for other in Self::other_nodes(connection, node_id).await? {
+            players += connection
+                .scard::<_, u32>(format!("{}:presence:{}", PREFIX, other))
+                .await?;
+            let node_key = format!("{}:node:{}", PREFIX, other);
+            if let Some(address) = connection.get::<_, Option<String>>(node_key).await? {
+                addresses.insert(other, address);
+            }
+        }
+        remote_players.store(players, Ordering::Relaxed);
+        *nodes.lock().unwrap() = addresses;
+        Ok(())
+    }
+
+    /// Every node but this one whose address hasn't expired.
+    #[cfg(feature = "redis")]
+    async fn other_nodes(
+        connection: &mut MultiplexedConnection,
+        node_id: u16,
+    ) -> Result<Vec<u16>, Box<dyn Error + Send + Sync>> {
+        let prefix = format!("{}:node:", PREFIX);
+        let mut keys = Vec::new();
+        let mut scan = connection
+            .scan_match::<_, String>(format!("{}*", prefix))
+            .await?;
+        while let Some(key) = scan.next_item().await {
+            keys.push(key);
+        }
+        Ok(keys
+            .iter()
+            .filter_map(|key| key.strip_prefix(&prefix)?.parse().ok())
+            .filter(|other| *other != node_id)
+            .collect())
+    }
+
+    /// Pass on what the other instances publish.
+    #[cfg(feature = "redis")]
+    async fn listen(pubsub: PubSub, node_id: u16, events: UnboundedSender<ClusterEvent>) {
+        let mut messages = pubsub.into_on_message();
+        while let Some(message) = messages.next().await {
+            let payload = match message.get_payload::<String>() {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!(error = %e, "ignoring unreadable cluster event");
+                    continue;
+                }
+            };
+            match serde_json::from_str::<Envelope>(&payload) {
+                Ok(envelope) if envelope.origin == node_id => {}
+                Ok(envelope) => {
+                    if events.send(envelope.event).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => warn!(error = %e, "ignoring malformed cluster event"),
+            }
+        }
+        error!("lost the redis subscription, no longer hearing from other instances");
+    }
+
+    fn send(&self, command: Command) {
+        if let Some(commands) = &self.commands {
+            let _ = commands.send(command);
+        }
+    }
+
+    /// Count `name` as online here until `set_offline`.
+    pub fn set_online(&self, name: &str) {
+        self.send(Command::Online(name.to_string()));
+    }
+
+    pub fn set_offline(&self, name: &str) {
+        self.send(Command::Offline(name.to_string()));
+    }
+
+    /// Have every other instance act on `event`, this one has to itself.
+    pub fn publish(&self, event: ClusterEvent) {
+        self.send(Command::Publish(event));
+    }
+
+    /// Players online on the other instances, 0 when running alone.
+    pub fn get_remote_players(&self) -> u32 {
+        self.remote_players.load(Ordering::Relaxed)
+    }
+
+    pub fn get_node_id(&self) -> u16 {
+        self.node_id
+    }
+
+    /// Where clients reach `node`, `None` if it isn't running.
+    pub fn get_node_address(&self, node: u16) -> Option<String> {
+        self.nodes.lock().unwrap().get(&node).cloned()
+    }
+
+    /// The first lobby id `node` hands out.
+    pub fn first_lobby_id(node: u16) -> u32 {
+        (node as u32) << (u32::BITS - NODE_BITS)
+    }
+
+    /// Note that the lobby now lives on `node`.
+    pub fn record_move(&self, lobby_id: u32, node: u16) {
+        self.moved.insert(lobby_id, node);
+    }
+
+    /// Where clients reach the instance a lobby this one doesn't have
+    /// lives on, `None` if it's this one or nowhere known.
+    pub fn route(&self, lobby_id: u32) -> Option<String> {
+        let node = match self.moved.get(&lobby_id) {
+            Some(node) => *node,
+            None => (lobby_id >> (u32::BITS - NODE_BITS)) as u16,
+        };
+        match node == self.node_id {
+            true => None,
+            false => self.get_node_address(node),
+        }
+    }
+
+    /// Events the other instances published, for the server to act on.
+    /// `None` once taken, or when running alone.
+    pub fn take_events(&self) -> Option<UnboundedReceiver<ClusterEvent>> {
+        self.events.lock().unwrap().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, error::Error};
+
+    use tokio::sync::mpsc;
+
+    use crate::service::{
+        admin_service::AdminService, game_service::GameService, lobby_service::LobbyService,
+        player_service::PlayerService,
+    };
+
+    use super::*;
+
+    fn connected() -> (Arc<ClusterService>, UnboundedReceiver<Command>) {
+        let (commands, queue) = mpsc::unbounded_channel();
+        let cluster_service = ClusterService {
+            commands: Some(commands),
+            ..ClusterService::local(0)
+        };
+        (Arc::new(cluster_service), queue)
+    }
+
+    #[test]
+    fn route_should_follow_moves_to_known_nodes() {
+        let cluster_service = ClusterService::local(1);
+        cluster_service
+            .nodes
+            .lock()
+            .unwrap()
+            .insert(2, String::from("10.0.0.2:45678"));
+        let own = ClusterService::first_lobby_id(1);
+        let remote = ClusterService::first_lobby_id(2) + 5;
+        assert_eq!(cluster_service.route(own), None);
+        assert_eq!(
+            cluster_service.route(remote),
+            Some(String::from("10.0.0.2:45678"))
+        );
+        // Nowhere known
+        assert_eq!(
+            cluster_service.route(ClusterService::first_lobby_id(3)),
+            None
+        );
+        cluster_service.record_move(own, 2);
+        assert_eq!(
+            cluster_service.route(own),
+            Some(String::from("10.0.0.2:45678"))
+        );
+        cluster_service.record_move(remote, 1);
+        assert_eq!(cluster_service.route(remote), None);
+    }
+
+    #[test]
+    fn remove_player_with_other_session_should_stay_online(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (cluster_service, mut queue) = connected();
+        let player_service = PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        )
+        .with_cluster_service(cluster_service);
+        let first = player_service.add_player(0, String::from("test"));
+        let second = player_service.add_player(1, String::from("test"));
+        player_service.remove_player(first)?;
+        assert_eq!(queue.try_recv()?, Command::Online(String::from("test")));
+        assert_eq!(queue.try_recv()?, Command::Online(String::from("test")));
+        assert!(queue.try_recv().is_err());
+        player_service.remove_player(second)?;
+        assert_eq!(queue.try_recv()?, Command::Offline(String::from("test")));
+        Ok(())
+    }
+
+    #[test]
+    fn announce_should_publish_to_other_instances() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (cluster_service, mut queue) = connected();
+        let player_service = Arc::new(
+            PlayerService::new(
+                Arc::new(LobbyService::new()),
+                Arc::new(GameService::new(HashSet::new())),
+            )
+            .with_cluster_service(cluster_service),
+        );
+        let admin_service = AdminService::new(None, player_service);
+        admin_service.announce("restarting soon", false);
+        assert_eq!(
+            queue.try_recv()?,
+            Command::Publish(ClusterEvent::Announce {
+                message: String::from("restarting soon")
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,377 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    sync::{Arc, RwLock},
+};
+
+use dashmap::DashMap;
+
+#[cfg(not(test))]
+use std::{io, iter, path::Path};
+
+#[cfg(not(test))]
+use tokio::{
+    fs::{self, File},
+    io::{AsyncBufReadExt, BufReader},
+};
+#[cfg(not(test))]
+use tracing::{debug, info, warn};
+
+#[cfg(not(test))]
+use crate::config::GameConfig;
+use crate::{
+    game::{board::BOARD_SIZE, rules::DEFAULT_LANGUAGE},
+    model::lobby::upload_dictionary::UploadDictionaryRequest,
+    solver::Lexicon,
+};
+
+/// Most words a lobby's custom list may have.
+pub const MAX_CUSTOM_WORDS: usize = 10_000;
+/// Most words one upload chunk may carry.
+pub const MAX_CHUNK_WORDS: usize = 1_000;
+/// Longest definition sent, in characters. Longer ones are cut short.
+pub const MAX_DEFINITION_LEN: usize = 200;
+/// FNV-1a, which unlike `DefaultHasher` hashes the same on every build, so
+/// compiled lists stay found across upgrades.
+#[cfg(not(test))]
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+#[cfg(not(test))]
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// A custom list still coming in.
+#[derive(Debug)]
+struct Upload {
+    next_chunk: u32,
+    chunk_count: u32,
+    words: HashSet<String>,
+}
+
+/// The configured lists, by language.
+#[derive(Debug)]
+struct Dictionaries {
+    wordlists: HashMap<String, Arc<HashSet<String>>>,
+    /// The same lists compiled for the solver.
+    lexicons: HashMap<String, Arc<Lexicon>>,
+    /// Definitions of words, for the languages that have them.
+    definitions: HashMap<String, Arc<HashMap<String, String>>>,
+}
+
+/// Word lists games are checked against, keyed by the language lobbies pick
+/// in their rules. Lobbies may also upload their own list, which their games
+/// use instead.
+#[derive(Debug)]
+pub struct DictionaryService {
+    /// Swapped whole on a reload. Moves already checked keep the lists they
+    /// were checked against, the next ones get the new lists.
+    dictionaries: RwLock<Dictionaries>,
+    uploads: DashMap<u32, Upload>,
+    /// Finished custom lists, by lobby id.
+    custom_wordlists: DashMap<u32, Arc<HashSet<String>>>,
+}
+
+impl DictionaryService {
+    #[cfg(not(test))]
+    pub async fn new(config: &GameConfig) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self {
+            dictionaries: RwLock::new(DictionaryService::load(config).await?),
+            uploads: DashMap::new(),
+            custom_wordlists: DashMap::new(),
+        })
+    }
+
+    /// Load the lists `config` names and put them in place of the current
+    /// ones. Nothing changes if any of them fails to load.
+    #[cfg(not(test))]
+    pub async fn reload(&self, config: &GameConfig) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let dictionaries = DictionaryService::load(config).await?;
+        info!(
+            languages = dictionaries.wordlists.len(),
+            definitions = dictionaries.definitions.len(),
+            "reload dictionaries"
+        );
+        *self.dictionaries.write().unwrap() = dictionaries;
+        Ok(())
+    }
+
+    #[cfg(not(test))]
+    async fn load(config: &GameConfig) -> Result<Dictionaries, Box<dyn Error + Send + Sync>> {
+        let paths = iter::once((DEFAULT_LANGUAGE, &config.dictionary_path)).chain(
+            config
+                .dictionaries
+                .iter()
+                .map(|(language, path)| (language.as_str(), path)),
+        );
+        let mut wordlists = HashMap::new();
+        let mut lexicons = HashMap::new();
+        for (language, path) in paths {
+            let (wordlist, hash) = DictionaryService::load_wordlist(path).await?;
+            let lexicon =
+                DictionaryService::load_lexicon(&config.dictionary_cache_dir, &wordlist, hash)
+                    .await;
+            wordlists.insert(String::from(language), Arc::new(wordlist));
+            lexicons.insert(String::from(language), Arc::new(lexicon));
+        }
+        let paths = iter::once((DEFAULT_LANGUAGE, &config.definitions_path)).chain(
+            config
+                .definitions
+                .iter()
+                .map(|(language, path)| (language.as_str(), path)),
+        );
+        let mut definitions = HashMap::new();
+        for (language, path) in paths.filter(|(_, path)| !path.is_empty()) {
+            let text = fs::read_to_string(path)
+                .await
+                .map_err(|e| format!("failed to open {}: {}", path, e))?;
+            definitions.insert(
+                String::from(language),
+                Arc::new(DictionaryService::parse_definitions(&text)),
+            );
+        }
+        Ok(Dictionaries {
+            wordlists,
+            lexicons,
+            definitions,
+        })
+    }
+
+    /// Definitions from `word<TAB>definition` lines, by lowercased word.
+    /// Lines without a tab or a definition are skipped.
+    pub fn parse_definitions(text: &str) -> HashMap<String, String> {
+        text.lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(word, definition)| (word.trim(), definition.trim()))
+            .filter(|(word, definition)| !word.is_empty() && !definition.is_empty())
+            .map(|(word, definition)| {
+                (
+                    word.to_lowercase(),
+                    definition.chars().take(MAX_DEFINITION_LEN).collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// The words in the file at `path`, with a hash of its lines for
+    /// finding the list's compiled lexicon.
+    #[cfg(not(test))]
+    async fn load_wordlist(
+        path: &str,
+    ) -> Result<(HashSet<String>, u64), Box<dyn Error + Send + Sync>> {
+        let mut wordlist = HashSet::new();
+        let mut hash = FNV_OFFSET;
+        let file = File::open(path)
+            .await
+            .map_err(|e| format!("failed to open {}: {}", path, e))?;
+        let mut reader = BufReader::new(file).lines();
+        while let Some(line) = reader.next_line().await? {
+            for byte in line.bytes().chain(iter::once(b'\n')) {
+                hash = (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+            }
+            wordlist.insert(line);
+        }
+        Ok((wordlist, hash))
+    }
+
+    /// Compile `wordlist`, or load it from `cache_dir` if a list hashing to
+    /// `hash` was compiled before. A cache that can't be read or written
+    /// only costs compiling the list again.
+    #[cfg(not(test))]
+    async fn load_lexicon(cache_dir: &str, wordlist: &HashSet<String>, hash: u64) -> Lexicon {
+        if cache_dir.is_empty() {
+            return Lexicon::new(wordlist);
+        }
+        let path = Path::new(cache_dir).join(format!("{:016x}.dawg", hash));
+        match fs::read(&path).await {
+            Ok(bytes) => match Lexicon::from_bytes(&bytes) {
+                Ok(lexicon) => {
+                    debug!(path = %path.display(), "load compiled dictionary");
+                    return lexicon;
+                }
+                Err(e) => warn!(path = %path.display(), error = %e, "discard compiled dictionary"),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "failed to read compiled dictionary")
+            }
+        }
+        let lexicon = Lexicon::new(wordlist);
+        match DictionaryService::save_lexicon(&path, &lexicon).await {
+            Ok(()) => info!(path = %path.display(), words = lexicon.len(), "compile dictionary"),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "failed to save compiled dictionary")
+            }
+        }
+        lexicon
+    }
+
+    #[cfg(not(test))]
+    async fn save_lexicon(
+        path: &Path,
+        lexicon: &Lexicon,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        // Written aside and moved into place, so a crash midway can't leave
+        // half a lexicon to load next time
+        let partial = path.with_extension("dawg.partial");
+        fs::write(&partial, lexicon.to_bytes()).await?;
+        fs::rename(&partial, path).await?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub fn new(wordlist: HashSet<String>) -> Self {
+        let lexicon = Lexicon::new(&wordlist);
+        Self {
+            dictionaries: RwLock::new(Dictionaries {
+                wordlists: HashMap::from([(String::from(DEFAULT_LANGUAGE), Arc::new(wordlist))]),
+                lexicons: HashMap::from([(String::from(DEFAULT_LANGUAGE), Arc::new(lexicon))]),
+                definitions: HashMap::new(),
+            }),
+            uploads: DashMap::new(),
+            custom_wordlists: DashMap::new(),
+        }
+    }
+
+    pub fn get_wordlist(&self, language: &str) -> Option<Arc<HashSet<String>>> {
+        self.dictionaries
+            .read()
+            .unwrap()
+            .wordlists
+            .get(language)
+            .cloned()
+    }
+
+    pub fn get_lexicon(&self, language: &str) -> Option<Arc<Lexicon>> {
+        self.dictionaries
+            .read()
+            .unwrap()
+            .lexicons
+            .get(language)
+            .cloned()
+    }
+
+    /// The definitions loaded for `language`, if any were.
+    pub fn get_definitions(&self, language: &str) -> Option<Arc<HashMap<String, String>>> {
+        self.dictionaries
+            .read()
+            .unwrap()
+            .definitions
+            .get(language)
+            .cloned()
+    }
+
+    #[cfg(test)]
+    pub fn set_definitions(&self, language: &str, definitions: HashMap<String, String>) {
+        self.dictionaries
+            .write()
+            .unwrap()
+            .definitions
+            .insert(String::from(language), Arc::new(definitions));
+    }
+
+    pub fn get_custom_wordlist(&self, lobby_id: u32) -> Option<Arc<HashSet<String>>> {
+        self.custom_wordlists
+            .get(&lobby_id)
+            .map(|wordlist| wordlist.clone())
+    }
+
+    /// Drop the lists of lobbies that are gone. Lobbies don't report closing,
+    /// so this runs whenever an upload starts.
+    pub fn retain_lobbies(&self, is_open: impl Fn(u32) -> bool) {
+        self.uploads.retain(|lobby_id, _| is_open(*lobby_id));
+        self.custom_wordlists
+            .retain(|lobby_id, _| is_open(*lobby_id));
+    }
+
+    /// Add a chunk of `lobby_id`'s custom list. Once the last chunk is in,
+    /// the list replaces any the lobby had and the number of distinct words
+    /// uploaded is returned. Words
+    /// are lowercased and must only have the letters cards are dealt from.
+    pub fn upload_chunk(
+        &self,
+        lobby_id: u32,
+        language: &str,
+        req: &UploadDictionaryRequest,
+    ) -> Result<Option<usize>, Box<dyn Error + Send + Sync>> {
+        if req.words.len() > MAX_CHUNK_WORDS {
+            return Err("Too many words in chunk".into());
+        }
+        if req.chunk_count == 0 || req.chunk_index >= req.chunk_count {
+            return Err("Invalid dictionary chunk".into());
+        }
+        let mut words = Vec::with_capacity(req.words.len());
+        for word in req.words.iter() {
+            let word = word.trim().to_lowercase();
+            if word.is_empty()
+                || word.len() > BOARD_SIZE
+                || !word.chars().all(|c| c.is_ascii_lowercase())
+            {
+                return Err(format!("Invalid word: {}", word).into());
+            }
+            words.push(word);
+        }
+        if req.chunk_index == 0 {
+            self.uploads.insert(
+                lobby_id,
+                Upload {
+                    next_chunk: 0,
+                    chunk_count: req.chunk_count,
+                    words: HashSet::new(),
+                },
+            );
+        }
+        let mut upload = match self.uploads.get_mut(&lobby_id) {
+            Some(upload) => upload,
+            None => return Err("No dictionary upload in progress".into()),
+        };
+        if req.chunk_index != upload.next_chunk || req.chunk_count != upload.chunk_count {
+            drop(upload);
+            self.uploads.remove(&lobby_id);
+            return Err("Dictionary chunk out of order".into());
+        }
+        upload.words.extend(words);
+        if upload.words.len() > MAX_CUSTOM_WORDS {
+            drop(upload);
+            self.uploads.remove(&lobby_id);
+            return Err("Dictionary too large".into());
+        }
+        upload.next_chunk += 1;
+        if upload.next_chunk < upload.chunk_count {
+            return Ok(None);
+        }
+        drop(upload);
+        let mut wordlist = match self.uploads.remove(&lobby_id) {
+            Some((_, upload)) => upload.words,
+            None => return Err("No dictionary upload in progress".into()),
+        };
+        if wordlist.is_empty() {
+            return Err("Dictionary is empty".into());
+        }
+        let word_count = wordlist.len();
+        if !req.replace {
+            if let Some(base) = self.get_wordlist(language) {
+                wordlist.extend(base.iter().cloned());
+            }
+        }
+        self.custom_wordlists.insert(lobby_id, Arc::new(wordlist));
+        Ok(Some(word_count))
+    }
+
+    pub fn has_language(&self, language: &str) -> bool {
+        self.dictionaries
+            .read()
+            .unwrap()
+            .wordlists
+            .contains_key(language)
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.dictionaries
+            .read()
+            .unwrap()
+            .wordlists
+            .values()
+            .all(|wordlist| !wordlist.is_empty())
+    }
+}
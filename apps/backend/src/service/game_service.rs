@@ -1,69 +1,182 @@
+use crate::error::ServerError;
 use std::{
     collections::{HashMap, HashSet},
-    error::Error,
-    sync::{Arc, Mutex},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex, PoisonError, Weak},
     time::Duration,
 };
 
-#[cfg(not(test))]
 use tokio::{
-    fs::File,
-    io::{AsyncBufReadExt, BufReader},
+    task,
+    time::{self, sleep, Instant},
 };
-use tokio::{task, time::sleep};
+use tracing::{debug, error};
 
 use crate::{
+    config::GameConfig,
     game::{
-        board::BOARD_SIZE,
-        card::Card,
-        game::{Game, END_GAME_TURN},
+        analysis::{self, TurnAnalysis},
+        card::{Card, PowerUp},
+        event::Event,
+        game::{
+            current_day, deadline_millis, DrawStatus, Game, GameMode, KickStatus, PauseStatus,
+            Withdrawal,
+        },
         game_player::GamePlayer,
-        tile::Tile,
+        notation::{self, Notation},
+        rules::GameRules,
     },
     lobby::lobby::Lobby,
+    logging::panic_message,
+    model::{control::notice::Notice, lobby::rules::Rules},
     player::Player,
+    solver::{self, HintMode, Lexicon, Placement},
+    storage::Storage,
+};
+#[cfg(test)]
+use crate::{config::SeasonConfig, storage::memory::MemoryStorage};
+
+use super::{
+    broadcast_service::BroadcastService, dictionary_service::DictionaryService,
+    leaderboard_service::LeaderboardService, season_service::SeasonService,
+    stats_service::StatsService,
 };
 
-#[cfg(not(test))]
 use crate::frame::{Response, ResponseData};
-#[cfg(not(test))]
+#[cfg(debug_assertions)]
+use crate::game::invariant;
+use crate::latency::TimedMutex;
 use crate::model::game::broadcast::GameEvent;
-#[cfg(not(test))]
 use crate::model::game::cards::Cards;
-#[cfg(not(test))]
+use crate::model::game::coop::CoopState;
+use crate::model::game::words::Words;
 use crate::model::lobby::broadcast::{LobbyBroadcast, LobbyEvent};
-#[cfg(not(test))]
 use crate::model::{game::broadcast::GameBroadcast, state::State};
 
 #[derive(Debug)]
 pub struct GameService {
     next_game_id: Mutex<u32>,
-    games: Mutex<HashMap<u32, Arc<Game>>>,
-    wordlist: HashSet<String>,
+    games: TimedMutex<HashMap<u32, Arc<Game>>>,
+    dictionary_service: Arc<DictionaryService>,
+    stats_service: Arc<StatsService>,
+    leaderboard_service: Arc<LeaderboardService>,
+    season_service: Arc<SeasonService>,
+    broadcast_service: Arc<BroadcastService>,
+    storage: Arc<dyn Storage>,
+    /// Stand-ins for players of restored games count down from the top so
+    /// their ids never clash with client ids.
+    next_stand_in_id: Mutex<u32>,
+    /// Rules lobbies start from, replaced when the config is reloaded.
+    default_rules: Mutex<GameRules>,
+    /// How long a vote to kick stays open, replaced with the rules.
+    vote_kick_timeout: Mutex<Duration>,
 }
 
 impl GameService {
     #[cfg(not(test))]
-    pub async fn new() -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let mut wordlist = HashSet::new();
-        let file = File::open("assets/wordlist.txt").await?;
-        let mut reader = BufReader::new(file).lines();
-        while let Some(line) = reader.next_line().await? {
-            wordlist.insert(line);
-        }
-        Ok(Self {
+    pub fn new(
+        config: &GameConfig,
+        dictionary_service: Arc<DictionaryService>,
+        stats_service: Arc<StatsService>,
+        leaderboard_service: Arc<LeaderboardService>,
+        season_service: Arc<SeasonService>,
+        broadcast_service: Arc<BroadcastService>,
+        storage: Arc<dyn Storage>,
+    ) -> Self {
+        Self {
             next_game_id: Mutex::new(0),
-            games: Mutex::new(HashMap::new()),
-            wordlist,
-        })
+            games: TimedMutex::new("game_service.games", HashMap::new()),
+            dictionary_service,
+            stats_service,
+            leaderboard_service,
+            season_service,
+            broadcast_service,
+            storage,
+            next_stand_in_id: Mutex::new(u32::MAX),
+            default_rules: Mutex::new(GameService::rules_from(config)),
+            vote_kick_timeout: Mutex::new(config.vote_kick_timeout()),
+        }
+    }
+
+    fn rules_from(config: &GameConfig) -> GameRules {
+        GameRules {
+            turn_duration: config.turn_duration(),
+            hint_budget: config.hint_budget,
+            forfeit_after_missed_turns: config.forfeit_after_missed_turns,
+            disconnect_grace: config.disconnect_grace(),
+            ..GameRules::default()
+        }
+    }
+
+    /// Start lobbies from `config`'s rules. Games already running keep the
+    /// rules they started with.
+    pub fn set_default_rules(&self, config: &GameConfig) {
+        *self.default_rules.lock().unwrap() = GameService::rules_from(config);
+        *self.vote_kick_timeout.lock().unwrap() = config.vote_kick_timeout();
     }
 
     #[cfg(test)]
     pub fn new(wordlist: HashSet<String>) -> Self {
+        let storage = Arc::new(MemoryStorage::new());
         Self {
             next_game_id: Mutex::new(0),
-            games: Mutex::new(HashMap::new()),
-            wordlist,
+            games: TimedMutex::new("game_service.games", HashMap::new()),
+            dictionary_service: Arc::new(DictionaryService::new(wordlist)),
+            stats_service: Arc::new(StatsService::new(storage.clone())),
+            leaderboard_service: Arc::new(LeaderboardService::new(storage.clone())),
+            season_service: Arc::new(SeasonService::new(
+                &SeasonConfig::default(),
+                storage.clone(),
+            )),
+            broadcast_service: Arc::new(BroadcastService::new()),
+            storage,
+            next_stand_in_id: Mutex::new(u32::MAX),
+            default_rules: Mutex::new(GameRules {
+                turn_duration: GameConfig::default().turn_duration(),
+                ..GameRules::default()
+            }),
+            vote_kick_timeout: Mutex::new(GameConfig::default().vote_kick_timeout()),
+        }
+    }
+
+    /// Turn the rules a lobby leader asked for into complete, valid rules.
+    pub fn resolve_rules(&self, rules: Option<&Rules>) -> Result<GameRules, ServerError> {
+        let default_rules = self.default_rules.lock().unwrap().clone();
+        let rules = match rules {
+            Some(rules) => rules.merge_into(&default_rules),
+            None => default_rules,
+        };
+        rules.validate()?;
+        if !self.dictionary_service.has_language(&rules.language) {
+            return Err("Unsupported dictionary language".into());
+        }
+        Ok(rules)
+    }
+
+    /// `get_wordlist` for the solver.
+    pub fn get_lexicon(&self, game: &Game) -> Result<Arc<Lexicon>, ServerError> {
+        if let Some(lexicon) = game.get_custom_lexicon() {
+            return Ok(lexicon);
+        }
+        match self
+            .dictionary_service
+            .get_lexicon(&game.get_rules().language)
+        {
+            Some(lexicon) => Ok(lexicon),
+            None => Err("Unsupported dictionary language".into()),
+        }
+    }
+
+    pub fn get_wordlist(&self, game: &Game) -> Result<Arc<HashSet<String>>, ServerError> {
+        if let Some(wordlist) = game.get_custom_wordlist() {
+            return Ok(wordlist);
+        }
+        match self
+            .dictionary_service
+            .get_wordlist(&game.get_rules().language)
+        {
+            Some(wordlist) => Ok(wordlist),
+            None => Err("Unsupported dictionary language".into()),
         }
     }
 
@@ -71,10 +184,13 @@ impl GameService {
         game_service: Arc<GameService>,
         player: Arc<Player>,
         lobby: Arc<Lobby>,
-    ) -> Result<Arc<Game>, Box<dyn Error + Send + Sync>> {
+    ) -> Result<Arc<Game>, ServerError> {
         if player != lobby.leader {
             return Err("Only leader can start game".into());
         }
+        if (lobby.get_players().len() as u32) < lobby.get_min_players_to_start() {
+            return Err("Not enough players".into());
+        }
         let mut check = true;
         for player in lobby.get_players() {
             if !player.get_ready() {
@@ -85,229 +201,1173 @@ impl GameService {
         if !check {
             return Err("Not all players are ready".into());
         }
-        let game = {
-            let mut next_id = game_service.next_game_id.lock().unwrap();
-            let game = Arc::new(Game::new(
-                *next_id,
+        let custom_wordlist = game_service
+            .dictionary_service
+            .get_custom_wordlist(lobby.get_id());
+        let game = game_service.add_game(|id| {
+            Game::with_rules(
+                id,
                 lobby
-                    .get_players()
+                    .get_players_in_join_order()
                     .iter()
                     .map(|x| x.player.clone())
                     .collect(),
-            ));
-            game_service
-                .games
-                .lock()
-                .unwrap()
-                .insert(*next_id, game.clone());
-            *next_id += 1;
-            game
-        };
+                lobby.get_rules(),
+            )
+            .with_custom_wordlist(custom_wordlist)
+        });
+        lobby.set_started();
+        // Enough to set the game up again when chasing a bug report
+        debug!(
+            game_id = game.id,
+            seed = game.get_replay().seed,
+            "start game"
+        );
         for game_player in game.get_players() {
             game_player.player.set_game(Some(game.clone()));
             if game_player.player == player {
                 continue;
             }
-            #[cfg(not(test))]
+            game_service.broadcast_service.send_to_player(
+                game_player.player.id,
+                Response::event(
+                    State::LobbyBroadcast,
+                    Arc::new(ResponseData::LobbyBroadcast(LobbyBroadcast {
+                        event: LobbyEvent::Start as i32,
+                        lobby: None,
+                        cards: Some(Cards::from(&game_player.get_cards())),
+                        current_player: Some(crate::model::player::player::Player::from(
+                            game.get_player_in_this_turn(),
+                        )),
+                        next_player: game
+                            .get_next_turn_player()
+                            .map(crate::model::player::player::Player::from),
+                        layout: Some(crate::model::game::board::Layout::from(game.as_ref())),
+                        player: None,
+                        turn_order: game
+                            .get_players()
+                            .into_iter()
+                            .map(crate::model::player::player::Player::from)
+                            .collect(),
+                        chat_history: Vec::new(),
+                    })),
+                ),
+            );
+        }
+        #[cfg(debug_assertions)]
+        invariant::assert_invariants(&game);
+        let turn_duration = game.get_rules().turn_duration;
+        GameService::start_countdown(game_service.clone(), game.clone(), turn_duration);
+        game_service.broadcast_turn_start(&game);
+        Ok(game)
+    }
+
+    fn add_game(&self, new_game: impl FnOnce(u32) -> Game) -> Arc<Game> {
+        let mut next_id = self.next_game_id.lock().unwrap();
+        let game = Arc::new(new_game(*next_id));
+        self.games.lock().unwrap().insert(*next_id, game.clone());
+        *next_id += 1;
+        game
+    }
+
+    /// Start today's daily challenge for `player`, a solo game on the board
+    /// and tiles everyone gets today.
+    pub fn start_daily_challenge(
+        game_service: Arc<GameService>,
+        player: Arc<Player>,
+    ) -> Result<Arc<Game>, ServerError> {
+        if player.get_game().is_some() {
+            return Err(ServerError::AlreadyInGame);
+        }
+        if player.get_lobby().is_some() {
+            return Err(ServerError::AlreadyInLobby);
+        }
+        let day = current_day();
+        let rules = game_service.default_rules.lock().unwrap().clone();
+        let game = game_service.add_game(|id| Game::daily(id, player.clone(), rules, day));
+        debug!(game_id = game.id, day, "start daily challenge");
+        player.set_game(Some(game.clone()));
+        let turn_duration = game.get_rules().turn_duration;
+        GameService::start_countdown(game_service.clone(), game.clone(), turn_duration);
+        game_service.broadcast_turn_start(&game);
+        Ok(game)
+    }
+
+    /// Start a practice game for the leader of a lobby of one.
+    pub fn start_solo(
+        game_service: Arc<GameService>,
+        player: Arc<Player>,
+        lobby: Arc<Lobby>,
+    ) -> Result<Arc<Game>, ServerError> {
+        if player != lobby.leader {
+            return Err("Only leader can start game".into());
+        }
+        if lobby.get_players().len() != 1 {
+            return Err("Solo games need a lobby of one".into());
+        }
+        if player.get_game().is_some() {
+            return Err(ServerError::AlreadyInGame);
+        }
+        let custom_wordlist = game_service
+            .dictionary_service
+            .get_custom_wordlist(lobby.get_id());
+        let game = game_service.add_game(|id| {
+            Game::practice(id, player.clone(), lobby.get_rules())
+                .with_custom_wordlist(custom_wordlist)
+        });
+        lobby.set_started();
+        debug!(game_id = game.id, "start solo game");
+        player.set_game(Some(game.clone()));
+        let turn_duration = game.get_rules().turn_duration;
+        GameService::start_countdown(game_service.clone(), game.clone(), turn_duration);
+        game_service.broadcast_turn_start(&game);
+        Ok(game)
+    }
+
+    /// Set up again the games saved before a restart, with a full turn on
+    /// the clock. Returns how many there were.
+    pub fn restore_games(game_service: Arc<GameService>) -> Result<usize, ServerError> {
+        let snapshots = game_service.storage.load_snapshots()?;
+        for snapshot in snapshots.iter() {
+            let game = Arc::new(Game::restore(snapshot, |name| {
+                game_service.new_stand_in(name)
+            }));
             {
-                let game = game.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = game_player
-                        .clone()
-                        .player
-                        .send_message(Response::new(
-                            State::LobbyBroadcast as u32,
-                            Arc::new(ResponseData::LobbyBroadcast(LobbyBroadcast {
-                                event: LobbyEvent::Start as i32,
-                                lobby: None,
-                                cards: Some(Cards::from(&game_player.get_cards())),
-                                current_player: Some(crate::model::player::player::Player::from(
-                                    game.get_player_in_this_turn(),
-                                )),
-                                next_player: match game.get_next_turn_player() {
-                                    Some(game_player) => Some(
-                                        crate::model::player::player::Player::from(game_player),
-                                    ),
-                                    None => None,
-                                },
-                            })),
-                        ))
-                        .await
-                    {
-                        eprintln!("Error sending lobby broadcast: {}", e);
+                let mut next_id = game_service.next_game_id.lock().unwrap();
+                *next_id = (*next_id).max(game.id + 1);
+                game_service
+                    .games
+                    .lock()
+                    .unwrap()
+                    .insert(game.id, game.clone());
+            }
+            debug!(game_id = game.id, turn = game.get_turns(), "restore game");
+            let turn_duration = game.get_rules().turn_duration;
+            GameService::start_countdown(game_service.clone(), game, turn_duration);
+        }
+        Ok(snapshots.len())
+    }
+
+    fn new_stand_in(&self, name: &str) -> Arc<Player> {
+        let mut next_id = self.next_stand_in_id.lock().unwrap();
+        let player = Arc::new(Player::new(*next_id, name.to_string()));
+        *next_id -= 1;
+        player
+    }
+
+    /// Save every unfinished game. Holds the games lock throughout, so a game
+    /// ending meanwhile can't have its snapshot saved after it was deleted.
+    pub fn save_snapshots(&self) {
+        let mut broken = Vec::new();
+        {
+            let games = self.games.lock().unwrap();
+            for game in games.values() {
+                let snapshot = match panic::catch_unwind(AssertUnwindSafe(|| game.snapshot())) {
+                    Ok(snapshot) => snapshot,
+                    Err(payload) => {
+                        broken.push((game.clone(), payload));
+                        continue;
                     }
-                });
+                };
+                if let Err(e) = self.storage.save_snapshot(&snapshot) {
+                    error!(game_id = game.id, error = %e, "failed to save game snapshot");
+                }
+            }
+        }
+        // Aborting takes the games lock
+        for (game, payload) in broken {
+            self.abort_game(&game, panic_message(payload.as_ref()));
+        }
+    }
+
+    /// Run `f`, a piece of `game`'s logic, so that a panic in it aborts the
+    /// game instead of unwinding through the caller. Returns `None` when it
+    /// panicked.
+    pub fn supervise<T>(&self, game: &Game, f: impl FnOnce() -> T) -> Option<T> {
+        match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(value) => Some(value),
+            Err(payload) => {
+                self.abort_game(game, panic_message(payload.as_ref()));
+                None
             }
         }
-        GameService::start_countdown(game_service, game.clone());
-        Ok(game)
+    }
+
+    /// End a game whose logic panicked. Its state can't be trusted anymore,
+    /// so nothing of it is kept: no stats, no snapshot. Its players are told
+    /// and sent back to their lobbies.
+    pub fn abort_game(&self, game: &Game, reason: &str) {
+        let players = match game.set_errored() {
+            Some(players) => players,
+            None => return,
+        };
+        error!(game_id = game.id, reason, "game panicked, aborting it");
+        self.games
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&game.id);
+        if let Err(e) = self.storage.delete_snapshot(game.id) {
+            error!(game_id = game.id, error = %e, "failed to delete game snapshot");
+        }
+        for player in players {
+            if player
+                .get_game()
+                .is_some_and(|current| current.id == game.id)
+            {
+                player.set_game(None);
+            }
+            self.broadcast_service.send_to_player(
+                player.id,
+                Response::event(
+                    State::Notice,
+                    Arc::new(ResponseData::Notice(Notice {
+                        message: String::from("The game was ended by a server error"),
+                        maintenance: false,
+                        redirect: String::new(),
+                    })),
+                ),
+            );
+            self.broadcast_service.send_to_game_player(
+                game,
+                player.id,
+                GameService::game_event(GameBroadcast {
+                    event: GameEvent::Destroy as i32,
+                    ..Default::default()
+                }),
+            );
+        }
+        self.broadcast_service.forget_game(game.id);
+    }
+
+    pub fn start_snapshots(game_service: Arc<GameService>, interval: Duration) {
+        task::spawn(async move {
+            let mut ticker = time::interval(interval);
+            loop {
+                ticker.tick().await;
+                game_service.save_snapshots();
+            }
+        });
+    }
+
+    /// Sit `player` back down in the restored game they were playing, if
+    /// any, and let the others there know.
+    pub fn claim_seat(&self, player: Arc<Player>) -> Option<Arc<Game>> {
+        let game = self
+            .get_gamees()
+            .into_iter()
+            .find(|game| game.claim_seat(player.clone()).is_some())?;
+        player.set_game(Some(game.clone()));
+        debug!(game_id = game.id, player_id = player.id, "claim seat");
+        self.broadcast_service.send_to_game(
+            &game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::Rejoin as i32,
+                players: Some(crate::model::player::players::Players::from(
+                    &game.get_players(),
+                )),
+                ..Default::default()
+            }),
+            Some(player.id),
+        );
+        Some(game)
+    }
+
+    /// Hold a dropped player's seat for them. They get it back with
+    /// `claim_seat` if they return within the disconnect grace period.
+    pub fn release_seat(
+        game_service: Arc<GameService>,
+        player: &Player,
+    ) -> Result<(), ServerError> {
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        if !game.release_seat(player.id) {
+            return Err(ServerError::NotInGame);
+        }
+        debug!(game_id = game.id, player_id = player.id, "release seat");
+        game_service.broadcast_service.send_to_game(
+            &game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::PlayerDisconnected as i32,
+                player: game
+                    .get_player(player.id)
+                    .map(crate::model::player::player::Player::from),
+                disconnect_grace: Some(game.get_rules().disconnect_grace.as_secs() as u32),
+                reconnect_deadline: Some(deadline_millis(
+                    Instant::now() + game.get_rules().disconnect_grace,
+                )),
+                ..Default::default()
+            }),
+            Some(player.id),
+        );
+        if game.get_vacant_seats().len() == game.get_players().len() {
+            GameService::watch_abandoned(game_service, &game);
+        }
+        Ok(())
+    }
+
+    /// Forfeit everyone once the disconnect grace runs out, so a game
+    /// nobody is left at ends even while paused, when no turn timer would.
+    /// Only a weak reference is held meanwhile, the game may end before.
+    fn watch_abandoned(game_service: Arc<GameService>, game: &Arc<Game>) {
+        let grace = game.get_rules().disconnect_grace;
+        let game: Weak<Game> = Arc::downgrade(game);
+        task::spawn(async move {
+            sleep(grace).await;
+            let game = match game.upgrade() {
+                Some(game) => game,
+                None => return,
+            };
+            if game_service.get_game(game.id).is_none() {
+                return;
+            }
+            let supervisor = game_service.clone();
+            supervisor.supervise(&game.clone(), move || {
+                if let Err(e) = GameService::enforce_forfeits(game_service, game, None) {
+                    error!(error = %e, "encounter error when forfeit");
+                }
+            });
+        });
+    }
+
+    pub fn get_dictionary_service(&self) -> Arc<DictionaryService> {
+        self.dictionary_service.clone()
+    }
+
+    /// `words` a turn in `game` made, with what they mean if there are
+    /// definitions for the game's language.
+    pub fn get_words(&self, game: &Game, words: &[String]) -> Words {
+        let definitions = self
+            .dictionary_service
+            .get_definitions(&game.get_rules().language);
+        Words {
+            words: words.to_vec(),
+            definitions: match definitions {
+                Some(definitions) => words
+                    .iter()
+                    .map(|word| definitions.get(word).cloned().unwrap_or_default())
+                    .collect(),
+                None => Vec::new(),
+            },
+        }
+    }
+
+    pub fn get_stats_service(&self) -> Arc<StatsService> {
+        self.stats_service.clone()
+    }
+
+    pub fn get_leaderboard_service(&self) -> Arc<LeaderboardService> {
+        self.leaderboard_service.clone()
+    }
+
+    pub fn get_season_service(&self) -> Arc<SeasonService> {
+        self.season_service.clone()
+    }
+
+    pub fn get_broadcast_service(&self) -> Arc<BroadcastService> {
+        self.broadcast_service.clone()
     }
 
     pub fn get_game(&self, id: u32) -> Option<Arc<Game>> {
         self.games.lock().unwrap().get(&id).cloned()
     }
 
+    /// A finished game written down in `notation`.
+    pub fn export_game(&self, game_id: u32, notation: Notation) -> Result<String, ServerError> {
+        match self.storage.load_replay(game_id)? {
+            Some(record) => Ok(notation::write(&record, notation)),
+            None => Err(ServerError::GameNotFound),
+        }
+    }
+
+    /// Replay the finished game with `game_id` and compare every turn with
+    /// the best move the hint solver finds. A custom dictionary isn't kept
+    /// with the replay, so those games are checked against their language's.
+    pub fn analyze_game(&self, game_id: u32) -> Result<Vec<TurnAnalysis>, ServerError> {
+        let record = match self.storage.load_replay(game_id)? {
+            Some(record) => record,
+            None => return Err(ServerError::GameNotFound),
+        };
+        let players = record
+            .replay
+            .seats
+            .iter()
+            .map(|id| {
+                let name = record.names.get(id).cloned().unwrap_or_default();
+                Arc::new(Player::new(*id, name))
+            })
+            .collect();
+        let game = Arc::new(Game::replay(&record.replay, players, &[])?);
+        let analyses = analysis::analyze(
+            &game,
+            &record,
+            &*self.get_lexicon(&game)?,
+            &*self.get_wordlist(&game)?,
+        )?;
+        debug!(game_id, turns = analyses.len(), "analyze game");
+        Ok(analyses)
+    }
+
+    /// Start `player` watching the game with `game_id` instead of whatever
+    /// they watched before, or stop them watching when it's `None`.
+    pub fn spectate(&self, player: &Arc<Player>, game_id: Option<u32>) -> Result<(), ServerError> {
+        let game = match game_id {
+            Some(_) if player.get_game().is_some() => return Err(ServerError::AlreadyInGame),
+            Some(game_id) => Some(self.get_game(game_id).ok_or(ServerError::GameNotFound)?),
+            None => None,
+        };
+        if let Some(watched) = player.get_spectating() {
+            watched.remove_spectator(player.id);
+        }
+        if let Some(game) = &game {
+            game.add_spectator(player.id);
+            let res = GameService::game_event(GameBroadcast {
+                event: GameEvent::SpectatorJoined as i32,
+                player: Some(crate::model::player::player::Player::from(player.clone())),
+                ..Default::default()
+            });
+            for game_player in game.get_players() {
+                self.broadcast_service.send_to_game_player(
+                    game,
+                    game_player.player.id,
+                    res.clone(),
+                );
+            }
+        }
+        player.set_spectating(game.as_ref());
+        Ok(())
+    }
+
+    /// Start the session following every event of the game with `game_id`
+    /// live, instead of whatever it followed before, or stop it following
+    /// when it's `None`. Returns the game's state version as of then.
+    pub fn subscribe(
+        &self,
+        player: &Arc<Player>,
+        game_id: Option<u32>,
+    ) -> Result<u64, ServerError> {
+        let game = match game_id {
+            Some(_) if player.get_game().is_some() => return Err(ServerError::AlreadyInGame),
+            Some(game_id) => Some(self.get_game(game_id).ok_or(ServerError::GameNotFound)?),
+            None => None,
+        };
+        if let Some(followed) = player.get_subscribed() {
+            followed.remove_subscriber(player.id);
+        }
+        if let Some(game) = &game {
+            game.add_subscriber(player.id);
+            debug!(game_id = game.id, player_id = player.id, "subscribe game");
+        }
+        player.set_subscribed(game.as_ref());
+        Ok(game.map_or(0, |game| self.broadcast_service.get_state_version(game.id)))
+    }
+
     /**
-     * Finish turn. Return true if the game is ended.
+     * Finish turn, taking back this turn's tiles unless `keep_tiles`.
+     * Return true if the game is ended.
      */
     fn finish_turn(
         game_service: Arc<GameService>,
         game: Arc<Game>,
-    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
-        let player_in_this_turn = game.get_player_in_this_turn();
-        player_in_this_turn.get_new_card();
+        keep_tiles: bool,
+    ) -> Result<bool, ServerError> {
+        game.apply(Event::EndTurn { keep_tiles })?;
         game.cancel_timeout_task();
-        game.next_turn();
-        game.backup_board();
-        if game.get_turns() > END_GAME_TURN {
-            game_service.clone().remove_game(game.clone())?;
-            #[cfg(not(test))]
-            GameService::boardcast_game_end(game);
+        if game.is_over() {
+            GameService::end_game(game_service, game)?;
             return Ok(true);
         }
+        #[cfg(debug_assertions)]
+        invariant::assert_invariants(&game);
         Ok(false)
     }
 
-    #[cfg(not(test))]
+    /// Record the result, tear the game down and tell everyone it's over.
+    fn end_game(game_service: Arc<GameService>, game: Arc<Game>) -> Result<(), ServerError> {
+        game.finish_scoring();
+        let recorded = match game.get_mode() {
+            // Everyone won or lost together, there's no winner among them
+            // for the stats to count
+            GameMode::Standard if game.get_rules().coop.is_some() => Ok(()),
+            GameMode::Standard => game_service
+                .stats_service
+                .record_game(&game)
+                .and_then(|_| game_service.season_service.record_game(&game)),
+            // Solo games would only pad out win rates, they go on the
+            // day's leaderboard instead
+            GameMode::Daily(day) => game.get_players().iter().try_for_each(|game_player| {
+                game_service
+                    .leaderboard_service
+                    .submit_daily(day, &game_player.player.name, game_player.get_tally().score)
+                    .map(|_| ())
+            }),
+            GameMode::Practice => Ok(()),
+        };
+        if let Err(e) = recorded {
+            error!(game_id = game.id, error = %e, "failed to record game result");
+        }
+        if let Err(e) = game_service.storage.save_replay(&game.finished_replay()) {
+            error!(game_id = game.id, error = %e, "failed to save replay");
+        }
+        game_service.clone().remove_game(game.clone())?;
+        game_service.broadcast_game_end(&game);
+        Ok(())
+    }
+
+    fn game_event(broadcast: GameBroadcast) -> Response {
+        Response::event(
+            State::GameBroadcast,
+            Arc::new(ResponseData::GameBroadcast(broadcast)),
+        )
+    }
+
+    /// How long the turn that just started runs, halved for each freeze
+    /// timer the turn before set off.
+    fn next_turn_duration(game: &Game) -> Duration {
+        let frozen = game.get_annotations().last().map_or(0, |annotation| {
+            annotation
+                .power_ups
+                .iter()
+                .filter(|power_up| **power_up == PowerUp::FreezeTimer)
+                .count() as u32
+        });
+        game.get_rules().turn_duration / 2u32.pow(frozen)
+    }
+
     fn send_finish_turn_broadcast(
+        &self,
         game: Arc<Game>,
-        words: &Vec<String>,
+        words: &[String],
         origin_player: Arc<GamePlayer>,
         send_to_origin_player: bool,
     ) {
-        for game_player in game.get_players() {
-            if game_player == origin_player && !send_to_origin_player {
-                continue;
+        let power_ups = game
+            .get_annotations()
+            .last()
+            .map(|annotation| annotation.power_ups.clone())
+            .unwrap_or_default();
+        let (version, changes) = game.take_board_changes();
+        let broadcast = GameBroadcast {
+            event: GameEvent::FinishTurn as i32,
+            current_player: Some(crate::model::player::player::Player::from(
+                game.get_player_in_this_turn(),
+            )),
+            next_player: game
+                .get_next_turn_player()
+                .map(crate::model::player::player::Player::from),
+            words: Some(self.get_words(&game, words)),
+            score: game
+                .get_annotations()
+                .last()
+                .map(crate::model::game::words::TurnScore::from),
+            coop: game.get_coop_status().map(CoopState::from),
+            ..Default::default()
+        };
+        let full = GameBroadcast {
+            board: Some(crate::model::game::board::Board::from(
+                &*game.get_board().lock().unwrap(),
+            )),
+            ..broadcast.clone()
+        };
+        let delta = GameBroadcast {
+            board_delta: (!changes.is_empty())
+                .then(|| crate::model::game::board::BoardDelta::new(version, &changes)),
+            ..broadcast
+        };
+        self.broadcast_service.send_board_to_game(
+            &game,
+            GameService::game_event(full.clone()),
+            GameService::game_event(delta),
+            Some(origin_player.player.id),
+        );
+        // Only the player whose turn ended gets to see their new cards. Those
+        // sent the turn with everyone else get them with the hand update
+        if send_to_origin_player
+            && !self
+                .broadcast_service
+                .includes_own(origin_player.player.id, true)
+        {
+            self.broadcast_service.send_to_game_player(
+                &game,
+                origin_player.player.id,
+                GameService::game_event(GameBroadcast {
+                    cards: Some(Cards::from(&origin_player.get_cards())),
+                    ..full
+                }),
+            );
+        }
+        self.broadcast_hand_update(&game, &origin_player);
+        self.broadcast_power_ups(&game, &origin_player, &power_ups);
+        if !game.is_over() {
+            self.broadcast_turn_start(&game);
+        }
+    }
+
+    /// Tell everyone what went off at the end of `origin_player`'s turn. Steal
+    /// letter and freeze timer go off on the player whose turn is next.
+    fn broadcast_power_ups(&self, game: &Game, origin_player: &GamePlayer, power_ups: &[PowerUp]) {
+        let next_player = game.get_player_in_this_turn();
+        let target = (next_player.player.id != origin_player.player.id).then_some(next_player);
+        for power_up in power_ups {
+            let target = match power_up {
+                PowerUp::DoubleWord => None,
+                PowerUp::StealLetter | PowerUp::FreezeTimer => target.clone(),
+            };
+            self.broadcast_service.send_to_game(
+                game,
+                GameService::game_event(GameBroadcast {
+                    event: GameEvent::PowerUp as i32,
+                    player: Some(crate::model::player::player::Player::from(
+                        origin_player.player.clone(),
+                    )),
+                    power_up: Some(crate::model::game::card::PowerUp::from(*power_up) as i32),
+                    target: target.map(crate::model::player::player::Player::from),
+                    ..Default::default()
+                }),
+                None,
+            );
+        }
+        if let Some(victim) = target {
+            if power_ups.contains(&PowerUp::StealLetter) {
+                self.broadcast_hand_update(game, &victim);
             }
-            let game = game.clone();
-            let board = Some(crate::model::game::board::Board::from({
-                &game.get_board().lock().unwrap().clone()
-            }));
-            let words = Some(crate::model::game::words::Words::from(words));
-            let origin_player = origin_player.clone();
-            tokio::spawn(async move {
-                if let Err(e) = game_player
-                    .player
-                    .send_message(Response::new(
-                        State::GameBroadcast as u32,
-                        Arc::new(ResponseData::GameBroadcast(GameBroadcast {
-                            event: GameEvent::FinishTurn as i32,
-                            board,
-                            players: None,
-                            current_player: Some(crate::model::player::player::Player::from(
-                                game.get_player_in_this_turn(),
-                            )),
-                            next_player: match game.get_next_turn_player() {
-                                Some(game_player) => {
-                                    Some(crate::model::player::player::Player::from(game_player))
-                                }
-                                None => None,
-                            },
-                            words,
-                            cards: match game_player == origin_player {
-                                true => Some(crate::model::game::cards::Cards::from(
-                                    &origin_player.get_cards(),
-                                )),
-                                false => None,
-                            },
-                        })),
-                    ))
-                    .await
-                {
-                    eprintln!("Error sending game broadcast: {}", e);
-                }
-            });
         }
     }
 
-    fn start_countdown(game_service: Arc<GameService>, game: Arc<Game>) {
+    /// Show `game_player` the hand they were dealt and everyone else only
+    /// how many cards it has.
+    fn broadcast_hand_update(&self, game: &Game, game_player: &GamePlayer) {
+        let cards = game_player.get_cards();
+        let broadcast = GameBroadcast {
+            event: GameEvent::HandUpdate as i32,
+            player: Some(crate::model::player::player::Player::from(
+                game_player.player.clone(),
+            )),
+            hand_count: Some(cards.len() as u32),
+            ..Default::default()
+        };
+        self.broadcast_service.send_to_game(
+            game,
+            GameService::game_event(broadcast.clone()),
+            Some(game_player.player.id),
+        );
+        self.broadcast_service.send_to_game_player(
+            game,
+            game_player.player.id,
+            GameService::game_event(GameBroadcast {
+                cards: Some(Cards::from(&cards)),
+                ..broadcast
+            }),
+        );
+    }
+
+    /// Tell the player on turn it is theirs and everyone else whose it is,
+    /// both with the time the turn runs out.
+    fn broadcast_turn_start(&self, game: &Game) {
+        let deadline = match game.get_turn_deadline() {
+            Some(deadline) => deadline,
+            None => return,
+        };
+        let current_player = game.get_player_in_this_turn();
+        let broadcast = GameBroadcast {
+            event: GameEvent::TurnChanged as i32,
+            current_player: Some(crate::model::player::player::Player::from(
+                current_player.clone(),
+            )),
+            next_player: game
+                .get_next_turn_player()
+                .map(crate::model::player::player::Player::from),
+            turn_deadline: Some(deadline_millis(deadline)),
+            ..Default::default()
+        };
+        self.broadcast_service.send_to_game(
+            game,
+            GameService::game_event(broadcast.clone()),
+            Some(current_player.player.id),
+        );
+        self.broadcast_service.send_to_game_player(
+            game,
+            current_player.player.id,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::YourTurn as i32,
+                ..broadcast
+            }),
+        );
+    }
+
+    fn start_countdown(game_service: Arc<GameService>, game: Arc<Game>, turn_duration: Duration) {
         let game_bak = game.clone();
+        let deadline = Instant::now() + turn_duration;
         let task = Arc::new(task::spawn(async move {
-            sleep(Duration::from_secs(30)).await;
-            let _origin_player = game.get_player_in_this_turn();
-            match GameService::timeout_finish_turn(game_service, game.clone()) {
-                Ok(_words) => {
-                    #[cfg(not(test))]
-                    GameService::send_finish_turn_broadcast(
-                        game.clone(),
-                        &_words,
-                        _origin_player,
-                        true,
-                    );
+            sleep(turn_duration).await;
+            let supervisor = game_service.clone();
+            supervisor.supervise(&game.clone(), move || {
+                // Pausing right as the timer fired must not end the turn
+                if game.is_paused() {
+                    return;
                 }
-                Err(e) => eprintln!("encounter error when finish turn: {}", e),
-            }
+                let origin_player = game.get_player_in_this_turn();
+                match GameService::timeout_finish_turn(game_service.clone(), game.clone()) {
+                    Ok(words) => {
+                        game_service.send_finish_turn_broadcast(
+                            game.clone(),
+                            &words,
+                            origin_player.clone(),
+                            true,
+                        );
+                    }
+                    Err(e) => error!(error = %e, "encounter error when finish turn"),
+                }
+                if !game.is_over() {
+                    if let Err(e) =
+                        GameService::enforce_forfeits(game_service, game, Some(origin_player))
+                    {
+                        error!(error = %e, "encounter error when forfeit");
+                    }
+                }
+            });
         }));
-        game_bak.set_timeout_task(task);
+        game_bak.set_timeout_task(task, deadline);
     }
 
     pub fn timeout_finish_turn(
         game_service: Arc<GameService>,
         game: Arc<Game>,
-    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    ) -> Result<Vec<String>, ServerError> {
         let words = {
             game.clone()
                 .get_board()
                 .lock()
                 .unwrap()
-                .validate(&game_service.wordlist, game.clone())
+                .validate(&*game_service.get_wordlist(&game)?, game.clone())
         };
-        let words = match words {
-            Some(words) => words,
-            None => {
-                game.restore_board();
-                Vec::new()
+        let keep_tiles = words.is_some();
+        if !GameService::finish_turn(game_service.clone(), game.clone(), keep_tiles)? {
+            let turn_duration = GameService::next_turn_duration(&game);
+            GameService::start_countdown(game_service, game.clone(), turn_duration);
+        }
+        Ok(words.unwrap_or_default())
+    }
+
+    /// Find a word `game_player` could place with their unused cards. Spends
+    /// nothing, the caller takes the hint off the budget.
+    pub fn find_hint(
+        &self,
+        game: Arc<Game>,
+        game_player: &GamePlayer,
+        mode: HintMode,
+    ) -> Result<Placement, ServerError> {
+        let board = game.get_board().lock().unwrap().clone();
+        let placement = solver::find_hint(
+            &game,
+            &board,
+            game_player.player.clone(),
+            &game_player.get_cards(),
+            &*self.get_lexicon(&game)?,
+            &*self.get_wordlist(&game)?,
+            mode,
+        );
+        debug!(
+            game_id = game.id,
+            player_id = game_player.player.id,
+            found = placement.is_some(),
+            "find hint"
+        );
+        placement.ok_or_else(|| "No valid placement found".into())
+    }
+
+    /// Count `player`'s vote to pause the game, or to resume it with
+    /// `pause` false. Once a majority agrees the turn timer is frozen, or
+    /// restarted with the time that was left.
+    pub fn vote_pause(
+        game_service: Arc<GameService>,
+        game: Arc<Game>,
+        player: Arc<Player>,
+        pause: bool,
+    ) -> Result<PauseStatus, ServerError> {
+        if game.get_player(player.id).is_none() {
+            return Err(ServerError::NotInGame);
+        }
+        match (pause, game.is_paused()) {
+            (true, true) => return Err("Game is already paused".into()),
+            (false, false) => return Err("Game is not paused".into()),
+            _ => (),
+        }
+        let status = game.vote_pause(player.id);
+        if status.votes >= status.votes_needed {
+            match pause {
+                true => game.pause(),
+                false => {
+                    let turn_duration = game.resume().unwrap_or(game.get_rules().turn_duration);
+                    GameService::start_countdown(game_service.clone(), game.clone(), turn_duration);
+                }
             }
+            debug!(game_id = game.id, paused = pause, "pause vote passed");
+        }
+        let status = game.get_pause_status();
+        game_service.broadcast_service.send_to_game(
+            &game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::Pause as i32,
+                pause: Some(crate::model::game::pause::PauseState::from(status)),
+                ..Default::default()
+            }),
+            Some(player.id),
+        );
+        // The turn picks up again with a new deadline
+        if !pause && !game.is_paused() {
+            game_service.broadcast_turn_start(&game);
+        }
+        Ok(status)
+    }
+
+    /// Offer everyone else in the game a draw. `player`'s own vote counts
+    /// towards it.
+    pub fn offer_draw(
+        game_service: Arc<GameService>,
+        game: Arc<Game>,
+        player: Arc<Player>,
+    ) -> Result<DrawStatus, ServerError> {
+        if game.get_player(player.id).is_none() {
+            return Err(ServerError::NotInGame);
+        }
+        if game.get_draw_status().votes > 0 {
+            return Err("A draw is already on offer".into());
+        }
+        game.vote_draw(player.id);
+        Ok(GameService::settle_draw(game_service, game, player))
+    }
+
+    /// Accept the draw on offer, or with `accept` false call it off.
+    pub fn answer_draw(
+        game_service: Arc<GameService>,
+        game: Arc<Game>,
+        player: Arc<Player>,
+        accept: bool,
+    ) -> Result<DrawStatus, ServerError> {
+        if game.get_player(player.id).is_none() {
+            return Err(ServerError::NotInGame);
+        }
+        if game.get_draw_status().votes == 0 {
+            return Err("No draw on offer".into());
+        }
+        if accept {
+            game.vote_draw(player.id);
+            return Ok(GameService::settle_draw(game_service, game, player));
+        }
+        game.decline_draw();
+        debug!(game_id = game.id, player_id = player.id, "draw declined");
+        let status = game.get_draw_status();
+        game_service.broadcast_service.send_to_game(
+            &game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::DrawDeclined as i32,
+                draw: Some(crate::model::game::draw::DrawState::from(status)),
+                player: Some(crate::model::player::player::Player::from(player.clone())),
+                ..Default::default()
+            }),
+            Some(player.id),
+        );
+        Ok(status)
+    }
+
+    /// Tell the others where the draw stands after `player` voted for it,
+    /// and end the game once everyone has.
+    fn settle_draw(
+        game_service: Arc<GameService>,
+        game: Arc<Game>,
+        player: Arc<Player>,
+    ) -> DrawStatus {
+        let status = game.get_draw_status();
+        debug!(
+            game_id = game.id,
+            player_id = player.id,
+            votes = status.votes,
+            "draw vote"
+        );
+        game_service.broadcast_service.send_to_game(
+            &game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::DrawOffer as i32,
+                draw: Some(crate::model::game::draw::DrawState::from(status)),
+                player: Some(crate::model::player::player::Player::from(player.clone())),
+                ..Default::default()
+            }),
+            Some(player.id),
+        );
+        if status.is_agreed() {
+            game.cancel_timeout_task();
+            if let Err(e) = GameService::end_game(game_service, game.clone()) {
+                error!(game_id = game.id, error = %e, "encounter error when end game");
+            }
+        }
+        status
+    }
+
+    /// Open a vote to kick `target_id` out of `game`, with `player`'s own
+    /// vote for it. The target stays if the vote hasn't passed by the time
+    /// it runs out.
+    pub fn start_vote_kick(
+        game_service: Arc<GameService>,
+        game: Arc<Game>,
+        player: Arc<Player>,
+        target_id: u32,
+    ) -> Result<KickStatus, ServerError> {
+        if game.get_player(player.id).is_none() {
+            return Err(ServerError::NotInGame);
+        }
+        let timeout = *game_service.vote_kick_timeout.lock().unwrap();
+        let deadline = Instant::now() + timeout;
+        let status = game.start_kick_vote(player.id, target_id, deadline)?;
+        task::spawn({
+            let game_service = game_service.clone();
+            // The vote doesn't keep a game that's gone alive
+            let game = Arc::downgrade(&game);
+            async move {
+                sleep(timeout).await;
+                if let Some(game) = game.upgrade() {
+                    game_service
+                        .supervise(&game, || game_service.expire_vote_kick(&game, deadline));
+                }
+            }
+        });
+        Ok(GameService::settle_vote_kick(
+            game_service,
+            game,
+            player,
+            status,
+        ))
+    }
+
+    /// Vote in the open vote to kick, for the kick with `kick` true.
+    pub fn answer_vote_kick(
+        game_service: Arc<GameService>,
+        game: Arc<Game>,
+        player: Arc<Player>,
+        kick: bool,
+    ) -> Result<KickStatus, ServerError> {
+        if game.get_player(player.id).is_none() {
+            return Err(ServerError::NotInGame);
+        }
+        let status = game.vote_kick(player.id, kick)?;
+        Ok(GameService::settle_vote_kick(
+            game_service,
+            game,
+            player,
+            status,
+        ))
+    }
+
+    /// Tell the others where the vote to kick stands after `player` voted,
+    /// and close it once it's decided, taking the target out if it passed.
+    fn settle_vote_kick(
+        game_service: Arc<GameService>,
+        game: Arc<Game>,
+        player: Arc<Player>,
+        status: KickStatus,
+    ) -> KickStatus {
+        debug!(
+            game_id = game.id,
+            player_id = player.id,
+            target = status.target,
+            votes = status.votes,
+            votes_against = status.votes_against,
+            "vote kick"
+        );
+        let decided = status.is_passed() || status.is_failed();
+        // Whoever's vote closed it acts on the outcome, once
+        if decided && game.end_kick_vote(status.deadline).is_none() {
+            return status;
+        }
+        let event = match status.is_failed() {
+            true => GameEvent::VoteKickFailed,
+            false => GameEvent::VoteKick,
         };
-        let _origin_player = game.get_player_in_this_turn();
-        game.get_player_in_this_turn().set_has_shuffled(false);
-        if !GameService::finish_turn(game_service.clone(), game.clone())? {
-            GameService::start_countdown(game_service, game.clone());
+        game_service.broadcast_service.send_to_game(
+            &game,
+            GameService::game_event(GameBroadcast {
+                event: event as i32,
+                kick_vote: Some(crate::model::game::vote_kick::KickVote::from(status)),
+                player: Some(crate::model::player::player::Player::from(player.clone())),
+                ..Default::default()
+            }),
+            Some(player.id),
+        );
+        if status.is_passed() {
+            if let Some(target) = game.get_player(status.target) {
+                if let Err(e) =
+                    GameService::withdraw(game_service, game.clone(), target, Withdrawal::Forfeited)
+                {
+                    error!(game_id = game.id, error = %e, "encounter error when kick");
+                }
+            }
         }
-        Ok(words)
+        status
+    }
+
+    /// Close the vote to kick running out at `deadline` if it's still open,
+    /// leaving its target in the game.
+    fn expire_vote_kick(&self, game: &Game, deadline: Instant) {
+        if game.is_over() {
+            return;
+        }
+        let status = match game.end_kick_vote(deadline) {
+            Some(status) => status,
+            None => return,
+        };
+        debug!(
+            game_id = game.id,
+            target = status.target,
+            "vote kick ran out"
+        );
+        self.broadcast_service.send_to_game(
+            game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::VoteKickFailed as i32,
+                kick_vote: Some(crate::model::game::vote_kick::KickVote::from(status)),
+                ..Default::default()
+            }),
+            None,
+        );
+    }
+
+    /// Forfeit whoever the game's rules say is out: `timed_out` once their
+    /// turn timer has run out too many times in a row, and anyone whose seat
+    /// has been held past the disconnect grace period.
+    fn enforce_forfeits(
+        game_service: Arc<GameService>,
+        game: Arc<Game>,
+        timed_out: Option<Arc<GamePlayer>>,
+    ) -> Result<(), ServerError> {
+        let rules = game.get_rules();
+        let mut forfeits = Vec::new();
+        // Look the player up again, their seat may have changed hands
+        if let Some(game_player) =
+            timed_out.and_then(|timed_out| game.get_player(timed_out.player.id))
+        {
+            let missed_turns = game_player.miss_turn();
+            if rules.forfeit_after_missed_turns > 0
+                && missed_turns >= rules.forfeit_after_missed_turns
+            {
+                forfeits.push(game_player);
+            }
+        }
+        for (game_player, since) in game.get_vacant_seats() {
+            if since.elapsed() >= rules.disconnect_grace && !forfeits.contains(&game_player) {
+                forfeits.push(game_player);
+            }
+        }
+        for game_player in forfeits {
+            if GameService::withdraw(
+                game_service.clone(),
+                game.clone(),
+                game_player,
+                Withdrawal::Forfeited,
+            )? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take `player` out of their game at their own request.
+    pub fn resign(game_service: Arc<GameService>, player: Arc<Player>) -> Result<(), ServerError> {
+        let game = match player.get_game() {
+            Some(game) => game,
+            None => return Err(ServerError::NotInGame),
+        };
+        let game_player = match game.get_player(player.id) {
+            Some(game_player) => game_player,
+            None => return Err(ServerError::NotInGame),
+        };
+        GameService::withdraw(game_service, game, game_player, Withdrawal::Resigned)?;
+        Ok(())
+    }
+
+    /// Take `game_player` out of the game for good, with nothing of theirs
+    /// handed to anyone. Return true if that leaves too few to play on and
+    /// the game is ended.
+    fn withdraw(
+        game_service: Arc<GameService>,
+        game: Arc<Game>,
+        game_player: Arc<GamePlayer>,
+        withdrawal: Withdrawal,
+    ) -> Result<bool, ServerError> {
+        let player = game_player.player.clone();
+        let was_on_turn = game.get_player_in_this_turn().player.id == player.id;
+        let player_id = player.id;
+        game.apply(match withdrawal {
+            Withdrawal::Forfeited => Event::Forfeit { player_id },
+            Withdrawal::Resigned => Event::Resign { player_id },
+        })?;
+        debug!(game_id = game.id, player_id, ?withdrawal, "withdraw");
+        let withdrawn = Some(crate::model::player::player::Player::from(game_player));
+        let (event, forfeited, resigned) = match withdrawal {
+            Withdrawal::Forfeited => (GameEvent::Forfeit, withdrawn, None),
+            Withdrawal::Resigned => (GameEvent::Resign, None, withdrawn),
+        };
+        let broadcast = GameService::game_event(GameBroadcast {
+            event: event as i32,
+            players: Some(crate::model::player::players::Players::from(
+                &game.get_players(),
+            )),
+            forfeited,
+            resigned,
+            ..Default::default()
+        });
+        game_service
+            .broadcast_service
+            .send_to_game(&game, broadcast.clone(), None);
+        game_service
+            .broadcast_service
+            .send_to_game_player(&game, player.id, broadcast);
+        player.set_game(None);
+        let players_left = game.get_players().len();
+        // A game that started alone is played out alone
+        if players_left == 0 || (players_left == 1 && game.get_replay().seats.len() > 1) {
+            game.cancel_timeout_task();
+            GameService::end_game(game_service, game)?;
+            return Ok(true);
+        }
+        if was_on_turn && !game.is_paused() {
+            game.cancel_timeout_task();
+            let turn_duration = game.get_rules().turn_duration;
+            GameService::start_countdown(game_service.clone(), game.clone(), turn_duration);
+            game_service.broadcast_turn_start(&game);
+        }
+        #[cfg(debug_assertions)]
+        invariant::assert_invariants(&game);
+        Ok(false)
     }
 
     pub fn remove_player_from_game(
         &self,
         player: Arc<Player>,
-    ) -> Result<Arc<GamePlayer>, Box<dyn Error + Send + Sync>> {
+    ) -> Result<Arc<GamePlayer>, ServerError> {
         let game = match player.clone().get_game() {
             Some(game) => game,
-            None => return Err("Player is not in a game".into()),
+            None => return Err(ServerError::NotInGame),
         };
-        let game_player = match game.remove_player(player.clone()) {
-            Some(game) => game,
-            None => return Err("Player is not in the game".into()),
+        let game_player = match game.get_player(player.id) {
+            Some(game_player) => game_player,
+            None => return Err(ServerError::NotInGame),
         };
+        game.apply(Event::Leave {
+            player_id: player.id,
+        })?;
         let is_game_destroy = game.get_players().len() == 0;
-        #[cfg(not(test))]
-        {
-            for game_player in game.get_players() {
-                let game = game.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = game_player
-                        .player
-                        .send_message(Response::new(
-                            State::GameBroadcast as u32,
-                            Arc::new(ResponseData::GameBroadcast(GameBroadcast {
-                                event: GameEvent::Leave as i32,
-                                board: None,
-                                players: Some(crate::model::player::players::Players::from(
-                                    &game.get_players(),
-                                )),
-                                current_player: None,
-                                next_player: None,
-                                words: None,
-                                cards: None,
-                            })),
-                        ))
-                        .await
-                    {
-                        eprintln!("Error sending game broadcast: {}", e);
-                    }
-                });
-            }
-        }
+        self.broadcast_service.send_to_game(
+            &game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::Leave as i32,
+                players: Some(crate::model::player::players::Players::from(
+                    &game.get_players(),
+                )),
+                ..Default::default()
+            }),
+            None,
+        );
         player.set_game(None);
         if is_game_destroy {
             self.remove_game(game)?;
@@ -315,207 +1375,289 @@ impl GameService {
         Ok(game_player)
     }
 
-    pub fn remove_game(&self, game: Arc<Game>) -> Result<Arc<Game>, Box<dyn Error + Send + Sync>> {
-        match self.games.lock().unwrap().remove(&game.id) {
+    /// Drop `game` from the map and stop its turn timer, which holds it,
+    /// and detach its players from it.
+    pub fn remove_game(&self, game: Arc<Game>) -> Result<Arc<Game>, ServerError> {
+        let removed = self.games.lock().unwrap().remove(&game.id);
+        match removed {
             Some(game) => {
+                game.cancel_timeout_task();
                 for game_player in game.get_players() {
-                    game_player.player.set_game(None);
+                    // They may have moved on to another game already
+                    if game_player
+                        .player
+                        .get_game()
+                        .is_some_and(|current| current.id == game.id)
+                    {
+                        game_player.player.set_game(None);
+                    }
+                }
+                if let Err(e) = self.storage.delete_snapshot(game.id) {
+                    error!(game_id = game.id, error = %e, "failed to delete game snapshot");
                 }
+                self.broadcast_service.forget_game(game.id);
                 Ok(game)
             }
-            None => Err("Game not found".into()),
+            None => Err(ServerError::GameNotFound),
         }
     }
 
+    /// End a game without waiting for its last turn, e.g. when it got stuck.
+    pub fn close_game(&self, id: u32) -> Result<Arc<Game>, ServerError> {
+        let game = match self.get_game(id) {
+            Some(game) => game,
+            None => return Err(ServerError::GameNotFound),
+        };
+        game.cancel_timeout_task();
+        self.broadcast_game_end(&game);
+        self.remove_game(game)
+    }
+
     pub fn get_gamees(&self) -> Vec<Arc<Game>> {
         self.games.lock().unwrap().values().cloned().collect()
     }
 
-    pub fn place_tile_on_board(&self, game: Arc<Game>, tile: Tile, x: usize, y: usize) {
-        let t = game.get_board();
-        let mut board = t.lock().unwrap();
-        println!("x: {x}, y: {y}");
-        board.tiles[BOARD_SIZE - y - 1][x] = Some(tile);
-        for i in 0..26 {
-            for j in 0..26 {
-                if board.tiles[i][j].is_some() {
-                    println!(
-                        "row: {i}, col: {j}, {:?}",
-                        board.tiles[i][j].clone().unwrap().char
-                    );
-                }
-            }
-        }
-        #[cfg(not(test))]
-        {
-            for game_player in game.get_players() {
-                if game_player == game.get_player_in_this_turn() {
-                    continue;
-                }
+    /// Put the player's card at `card_index` on `(x, y)` and show the others.
+    pub fn place_tile(
+        &self,
+        game: Arc<Game>,
+        player: &Player,
+        card_index: usize,
+        x: usize,
+        y: usize,
+    ) -> Result<(), ServerError> {
+        game.apply(Event::PlaceTile {
+            player_id: player.id,
+            card_index,
+            x,
+            y,
+        })?;
+        debug!(game_id = game.id, x, y, "place tile");
+        #[cfg(debug_assertions)]
+        invariant::assert_invariants(&game);
+        self.broadcast_board(&game, player.id);
+        Ok(())
+    }
 
-                let board = game.get_board().clone();
-                tokio::spawn(async move {
-                    let t = Some(crate::model::game::board::Board::from(
-                        &*board.lock().unwrap(),
-                    ));
-                    if let Err(e) = game_player
-                        .player
-                        .send_message(Response::new(
-                            State::GameBroadcast as u32,
-                            Arc::new(ResponseData::GameBroadcast(GameBroadcast {
-                                event: GameEvent::PlaceTile as i32,
-                                board: t,
-                                players: None,
-                                current_player: None,
-                                next_player: None,
-                                words: None,
-                                cards: None,
-                            })),
-                        ))
-                        .await
-                    {
-                        eprintln!("Error sending game broadcast: {}", e);
-                    }
-                });
+    fn broadcast_board(&self, game: &Game, except: u32) {
+        let (version, changes) = game.take_board_changes();
+        let board = crate::model::game::board::Board::from(&*game.get_board().lock().unwrap());
+        let delta = GameService::game_event(GameBroadcast {
+            event: GameEvent::PlaceTile as i32,
+            board_delta: Some(crate::model::game::board::BoardDelta::new(
+                version, &changes,
+            )),
+            ..Default::default()
+        });
+        self.broadcast_service.send_board_to_game(
+            game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::PlaceTile as i32,
+                board: Some(board),
+                ..Default::default()
+            }),
+            delta,
+            Some(except),
+        );
+    }
+
+    /// The game the player plays in, or else the one they watch or
+    /// subscribed to.
+    fn get_followed_game(player: &Player) -> Result<Arc<Game>, ServerError> {
+        player
+            .get_game()
+            .or_else(|| player.get_spectating())
+            .or_else(|| player.get_subscribed())
+            .ok_or(ServerError::NotInGame)
+    }
+
+    /// Push the whole board at its current version to a player or spectator
+    /// of the game, who missed a delta.
+    pub fn send_board_snapshot(&self, player: &Player) -> Result<(), ServerError> {
+        let game = GameService::get_followed_game(player)?;
+        let (version, board) = game.get_sent_board();
+        let res = GameService::game_event(GameBroadcast {
+            event: GameEvent::BoardSnapshot as i32,
+            board: Some(crate::model::game::board::Board::from(&board)),
+            board_version: Some(version),
+            ..Default::default()
+        });
+        self.broadcast_service
+            .send_to_game_player(&game, player.id, res);
+        Ok(())
+    }
+
+    /// Catch the player or spectator up on the game from state version
+    /// `from`, returns whether the events they missed were replayed rather
+    /// than summed up in a RESYNC event.
+    pub fn resync(&self, player: &Player, from: u64) -> Result<bool, ServerError> {
+        let game = GameService::get_followed_game(player)?;
+        let game_player = game.get_player(player.id);
+        if self.broadcast_service.replay(&game, player.id, from) {
+            // Replayed events are the ones everybody got, without the hand
+            if let Some(game_player) = game_player {
+                let cards = game_player.get_cards();
+                self.broadcast_service.send_to_game_player(
+                    &game,
+                    player.id,
+                    GameService::game_event(GameBroadcast {
+                        event: GameEvent::HandUpdate as i32,
+                        player: Some(crate::model::player::player::Player::from(
+                            game_player.player.clone(),
+                        )),
+                        hand_count: Some(cards.len() as u32),
+                        cards: Some(Cards::from(&cards)),
+                        ..Default::default()
+                    }),
+                );
             }
+            return Ok(true);
         }
+        let (board_version, board) = game.get_sent_board();
+        self.broadcast_service.send_to_game_player(
+            &game,
+            player.id,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::Resync as i32,
+                board: Some(crate::model::game::board::Board::from(&board)),
+                board_version: Some(board_version),
+                players: Some(crate::model::player::players::Players::from(
+                    &game.get_players(),
+                )),
+                current_player: Some(crate::model::player::player::Player::from(
+                    game.get_player_in_this_turn(),
+                )),
+                turn_deadline: game.get_turn_deadline().map(deadline_millis),
+                cards: game_player.map(|game_player| Cards::from(&game_player.get_cards())),
+                ..Default::default()
+            }),
+        );
+        Ok(false)
     }
 
-    #[cfg(not(test))]
-    fn boardcast_game_end(game: Arc<Game>) {
-        for game_player in game.get_players() {
-            tokio::spawn(async move {
-                if let Err(e) = game_player
-                    .player
-                    .send_message(Response::new(
-                        State::GameBroadcast as u32,
-                        Arc::new(ResponseData::GameBroadcast(GameBroadcast {
-                            event: GameEvent::Destroy as i32,
-                            board: None,
-                            players: None,
-                            current_player: None,
-                            next_player: None,
-                            words: None,
-                            cards: None,
-                        })),
-                    ))
-                    .await
-                {
-                    eprintln!("Error sending game broadcast: {}", e);
-                }
-            });
-        }
+    fn broadcast_game_end(&self, game: &Game) {
+        self.broadcast_service.send_to_game(
+            game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::Destroy as i32,
+                coop: game.get_coop_status().map(CoopState::from),
+                ..Default::default()
+            }),
+            None,
+        );
+        // Nobody catches up on a game that is over
+        self.broadcast_service.forget_game(game.id);
     }
 
     pub fn validate_board_and_finish_turn(
         game_service: Arc<GameService>,
         game: Arc<Game>,
-    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    ) -> Result<Vec<String>, ServerError> {
         let words = match game
             .get_board()
             .lock()
             .unwrap()
-            .validate(&game_service.wordlist, game.clone())
+            .validate(&*game_service.get_wordlist(&game)?, game.clone())
         {
             Some(words) => words,
             None => return Err("invalid word".into()),
         };
-        let _origin_player = game.get_player_in_this_turn();
-        if !GameService::finish_turn(game_service.clone(), game.clone())? {
-            GameService::start_countdown(game_service, game.clone());
+        let origin_player = game.get_player_in_this_turn();
+        origin_player.clear_missed_turns();
+        let is_game_end = GameService::finish_turn(game_service.clone(), game.clone(), true)?;
+        if !is_game_end {
+            let turn_duration = GameService::next_turn_duration(&game);
+            GameService::start_countdown(game_service.clone(), game.clone(), turn_duration);
+        }
+        game_service.send_finish_turn_broadcast(game.clone(), &words, origin_player, false);
+        // The turn went through either way
+        if !is_game_end {
+            if let Err(e) = GameService::enforce_forfeits(game_service, game, None) {
+                error!(error = %e, "encounter error when forfeit");
+            }
         }
-        #[cfg(not(test))]
-        GameService::send_finish_turn_broadcast(game.clone(), &words, _origin_player, false);
         Ok(words)
     }
 
-    pub fn remove_selected_tile(&self, x: u32, y: u32, game: Arc<Game>) {
-        {
-            game.get_board().lock().unwrap().tiles[(BOARD_SIZE - y as usize - 1) as usize]
-                [x as usize] = None;
-            println!("x: {x}, y: {y}");
-            let row = BOARD_SIZE - y as usize - 1;
-            println!("row: {row}, col: {x}");
-        }
-        #[cfg(not(test))]
-        {
-            let board = game.get_board().clone();
-            for game_player in game.get_players() {
-                if game_player == game.get_player_in_this_turn() {
-                    continue;
-                }
-                let _game = game.clone();
-                let t = Some(crate::model::game::board::Board::from(
-                    &*board.lock().unwrap(),
-                ));
-                tokio::spawn(async move {
-                    if let Err(e) = game_player
-                        .player
-                        .send_message(Response::new(
-                            State::GameBroadcast as u32,
-                            Arc::new(ResponseData::GameBroadcast(GameBroadcast {
-                                event: GameEvent::PlaceTile as i32,
-                                board: t,
-                                players: None,
-                                current_player: None,
-                                next_player: None,
-                                words: None,
-                                cards: None,
-                            })),
-                        ))
-                        .await
-                    {
-                        eprintln!("Error sending game broadcast: {}", e);
-                    }
-                });
-            }
-        }
+    /// Take a tile the player placed this turn back into their hand.
+    pub fn remove_tile(
+        &self,
+        game: Arc<Game>,
+        player: &Player,
+        x: usize,
+        y: usize,
+    ) -> Result<(), ServerError> {
+        game.apply(Event::RemoveTile {
+            player_id: player.id,
+            x,
+            y,
+        })?;
+        debug!(game_id = game.id, x, y, "remove tile");
+        #[cfg(debug_assertions)]
+        invariant::assert_invariants(&game);
+        self.broadcast_board(&game, player.id);
+        Ok(())
     }
 
     pub fn shuffle(
         &self,
-        #[cfg(not(test))] game: Arc<Game>,
+        game: Arc<Game>,
         game_player: Arc<GamePlayer>,
-    ) -> Result<Vec<Card>, Box<dyn Error + Send + Sync>> {
-        if game_player.get_has_shuffled() {
-            return Err("Player has shuffled in this turn".into());
-        }
-        let cards = game_player.get_new_card();
-        #[cfg(not(test))]
-        {
-            for game_player in game.get_players() {
-                if game_player == game.get_player_in_this_turn() {
-                    continue;
-                }
+    ) -> Result<Vec<Card>, ServerError> {
+        let event = Event::Shuffle {
+            player_id: game_player.player.id,
+        };
+        self.swap_cards(game, game_player, event)
+    }
 
-                tokio::spawn(async move {
-                    if let Err(e) = game_player
-                        .player
-                        .send_message(Response::new(
-                            State::GameBroadcast as u32,
-                            Arc::new(ResponseData::GameBroadcast(GameBroadcast {
-                                event: GameEvent::Shuffle as i32,
-                                board: None,
-                                players: None,
-                                current_player: None,
-                                next_player: None,
-                                words: None,
-                                cards: None,
-                            })),
-                        ))
-                        .await
-                    {
-                        eprintln!("Error sending game broadcast: {}", e);
-                    }
-                });
-            }
-        }
+    /// Swap the cards at `card_indices` for new ones. The others in the game
+    /// see it as a shuffle.
+    pub fn exchange(
+        &self,
+        game: Arc<Game>,
+        game_player: Arc<GamePlayer>,
+        card_indices: Vec<usize>,
+    ) -> Result<Vec<Card>, ServerError> {
+        let event = Event::Exchange {
+            player_id: game_player.player.id,
+            card_indices,
+        };
+        self.swap_cards(game, game_player, event)
+    }
+
+    fn swap_cards(
+        &self,
+        game: Arc<Game>,
+        game_player: Arc<GamePlayer>,
+        event: Event,
+    ) -> Result<Vec<Card>, ServerError> {
+        game.apply(event)?;
+        let cards = game_player.get_cards();
+        #[cfg(debug_assertions)]
+        invariant::assert_invariants(&game);
+        self.broadcast_service.send_to_game(
+            &game,
+            GameService::game_event(GameBroadcast {
+                event: GameEvent::Shuffle as i32,
+                ..Default::default()
+            }),
+            Some(game.get_player_in_this_turn().player.id),
+        );
         Ok(cards)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        error::Error,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use crate::game::rules::DEFAULT_LANGUAGE;
+    use crate::game::{board::BOARD_SIZE, game::END_GAME_TURN, tile::Tile};
+    use crate::model::control::connect::Capability;
+    use crate::service::stats_service::HistoryFilter;
 
     use super::*;
 
@@ -530,6 +1672,26 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn get_words_with_definitions_should_define_known_words() {
+        let game_service = GameService::new(HashSet::new());
+        let game = Game::new(0, vec![Arc::new(Player::new(0, String::from("test")))]);
+        let words = vec![String::from("cat"), String::from("zax")];
+        assert!(game_service.get_words(&game, &words).definitions.is_empty());
+        game_service.dictionary_service.set_definitions(
+            DEFAULT_LANGUAGE,
+            DictionaryService::parse_definitions(
+                "CAT\tA small domesticated feline.\nno definition\ndog\t\n",
+            ),
+        );
+        let res = game_service.get_words(&game, &words);
+        assert_eq!(res.words, words);
+        assert_eq!(
+            res.definitions,
+            vec![String::from("A small domesticated feline."), String::new()]
+        );
+    }
+
     #[tokio::test]
     async fn start_game_with_test_player_not_in_lobby_should_return_error(
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -555,6 +1717,23 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn start_game_with_fewer_players_than_min_to_start_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let leader = Arc::new(Player::new(0, "test".to_string()));
+        let lobby = Arc::new(Lobby::with_settings(
+            0,
+            4,
+            2,
+            leader.clone(),
+            GameRules::default(),
+        ));
+        lobby.get_player(leader.id).unwrap().set_ready(true);
+        assert!(GameService::start_game(game_service, leader, lobby).is_err());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn start_game_with_players_not_ready_should_return_error(
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -631,6 +1810,92 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn start_game_should_push_turn_start_with_deadline(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player = Arc::new(Player::new(0, String::from("test1")));
+        let player1 = Arc::new(Player::new(1, String::from("test2")));
+        let lobby = Arc::new(Lobby::new(0, 4, player.clone()));
+        lobby.add_player(player1)?;
+        lobby.get_player(0).unwrap().set_ready(true);
+        lobby.get_player(1).unwrap().set_ready(true);
+        let mut receivers = Vec::new();
+        for id in 0..2 {
+            let (tx, rx) = tokio::sync::mpsc::channel(4);
+            let broadcast_service = game_service.get_broadcast_service();
+            broadcast_service.register(id, tx);
+            broadcast_service.set_capabilities(id, Capability::TurnEvents as u64);
+            receivers.push(rx);
+        }
+        let before = SystemTime::now();
+        let game = GameService::start_game(game_service, player, lobby)?;
+        let current = game.get_player_in_this_turn().player.id;
+        for (id, rx) in receivers.iter_mut().enumerate() {
+            let broadcast = loop {
+                match rx.recv().await {
+                    Some(crate::frame::Frame::Response(res)) => {
+                        if let ResponseData::GameBroadcast(broadcast) = res.get_data().as_ref() {
+                            break broadcast.clone();
+                        }
+                    }
+                    _ => panic!("no turn start"),
+                }
+            };
+            let expected = match id as u32 == current {
+                true => GameEvent::YourTurn,
+                false => GameEvent::TurnChanged,
+            };
+            assert_eq!(broadcast.event, expected as i32);
+            assert_eq!(broadcast.current_player.unwrap().id, current);
+            let deadline = UNIX_EPOCH + Duration::from_millis(broadcast.turn_deadline.unwrap());
+            assert!(deadline >= before + game.get_rules().turn_duration - Duration::from_secs(1));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn validate_board_and_finish_turn_should_show_new_hand_only_to_mover(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player = Arc::new(Player::new(0, String::from("test1")));
+        let player1 = Arc::new(Player::new(1, String::from("test2")));
+        let lobby = Arc::new(Lobby::new(0, 4, player.clone()));
+        lobby.add_player(player1)?;
+        lobby.get_player(0).unwrap().set_ready(true);
+        lobby.get_player(1).unwrap().set_ready(true);
+        let game = GameService::start_game(game_service.clone(), player, lobby)?;
+        let mover = game.get_player_in_this_turn().player.id;
+        let mut receivers = Vec::new();
+        for id in 0..2 {
+            let (tx, rx) = tokio::sync::mpsc::channel(8);
+            let broadcast_service = game_service.get_broadcast_service();
+            broadcast_service.register(id, tx);
+            broadcast_service.set_capabilities(id, Capability::HandEvents as u64);
+            receivers.push(rx);
+        }
+        GameService::validate_board_and_finish_turn(game_service, game)?;
+        for (id, rx) in receivers.iter_mut().enumerate() {
+            let broadcast = loop {
+                match rx.recv().await {
+                    Some(crate::frame::Frame::Response(res)) => match res.get_data().as_ref() {
+                        ResponseData::GameBroadcast(broadcast)
+                            if broadcast.event() == GameEvent::HandUpdate =>
+                        {
+                            break broadcast.clone()
+                        }
+                        _ => continue,
+                    },
+                    _ => panic!("no hand update"),
+                }
+            };
+            assert_eq!(broadcast.player.unwrap().id, mover);
+            assert_eq!(broadcast.hand_count, Some(8));
+            assert_eq!(broadcast.cards.is_some(), id as u32 == mover);
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn timeout_finish_turn_when_times_up_should_success(
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -643,7 +1908,7 @@ mod tests {
         lobby.get_player(1).unwrap().set_ready(true);
         let game = GameService::start_game(game_service.clone(), player, lobby)?;
         let turn_before = game.clone().get_turns();
-        GameService::finish_turn(game_service, game.clone())?;
+        GameService::finish_turn(game_service, game.clone(), true)?;
         assert!(turn_before + 1 == game.get_turns());
         Ok(())
     }
@@ -753,8 +2018,427 @@ mod tests {
         player.set_game(Some(game.clone()));
         game_service.games.lock().unwrap().insert(0, game.clone());
         game.get_board().lock().unwrap().tiles[0][0] = Some(Tile::new('a', player, 1));
-        GameService::finish_turn(game_service, game.clone())?;
+        GameService::finish_turn(game_service, game.clone(), true)?;
         assert!(game.get_board_backup().tiles[0][0].is_some());
         Ok(())
     }
+
+    #[tokio::test]
+    async fn restore_games_with_saved_game_should_let_player_claim_seat(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player = Arc::new(Player::new(0, String::from("test1")));
+        let game = Arc::new(Game::new(3, vec![player.clone()]));
+        game_service.games.lock().unwrap().insert(3, game.clone());
+        game_service.save_snapshots();
+        // As if the server restarted
+        game_service.games.lock().unwrap().clear();
+        assert_eq!(GameService::restore_games(game_service.clone())?, 1);
+        let restored = game_service.get_game(3).unwrap();
+        assert_ne!(restored.get_players()[0].player, player);
+        assert_eq!(*game_service.next_game_id.lock().unwrap(), 4);
+
+        let player = Arc::new(Player::new(0, String::from("test1")));
+        assert!(game_service.claim_seat(player.clone()).is_some());
+        assert_eq!(player.get_game(), Some(restored.clone()));
+        assert_eq!(restored.get_players()[0].player, player);
+        assert!(game_service
+            .claim_seat(Arc::new(Player::new(1, String::from("test2"))))
+            .is_none());
+        restored.cancel_timeout_task();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn supervise_with_panic_should_abort_only_that_game(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = GameService::new(HashSet::new());
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let games = (0..2)
+            .map(|id| Arc::new(Game::new(id, vec![players[id as usize].clone()])))
+            .collect::<Vec<_>>();
+        for (player, game) in players.iter().zip(&games) {
+            player.set_game(Some(game.clone()));
+            game_service
+                .games
+                .lock()
+                .unwrap()
+                .insert(game.id, game.clone());
+        }
+        game_service.save_snapshots();
+        let result = game_service.supervise(&games[0], || {
+            // Poison the board on the way out
+            let board = games[0].get_board();
+            let _board = board.lock().unwrap();
+            panic!("broken game");
+        });
+        assert!(result.is_none());
+        assert!(games[0].is_errored());
+        assert!(game_service.get_game(0).is_none());
+        assert!(players[0].get_game().is_none());
+        assert_eq!(game_service.storage.load_snapshots()?.len(), 1);
+
+        assert_eq!(game_service.supervise(&games[1], || 1), Some(1));
+        assert!(!games[1].is_errored());
+        assert!(game_service.get_game(1).is_some());
+        assert!(players[1].get_game().is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_game_with_saved_game_should_delete_snapshot(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = GameService::new(HashSet::new());
+        let game = Arc::new(Game::new(
+            0,
+            vec![Arc::new(Player::new(0, String::from("test")))],
+        ));
+        game_service.games.lock().unwrap().insert(0, game.clone());
+        game_service.save_snapshots();
+        assert_eq!(game_service.storage.load_snapshots()?.len(), 1);
+        game_service.remove_game(game)?;
+        assert!(game_service.storage.load_snapshots()?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn enforce_forfeits_with_too_many_missed_turns_should_forfeit_player(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let players = (0..3)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Arc::new(Game::new(0, players.clone()));
+        for player in &players {
+            player.set_game(Some(game.clone()));
+        }
+        game_service.games.lock().unwrap().insert(0, game.clone());
+        let idle = game.get_next_turn_player().unwrap();
+        for _ in 1..game.get_rules().forfeit_after_missed_turns {
+            GameService::enforce_forfeits(game_service.clone(), game.clone(), Some(idle.clone()))?;
+        }
+        assert!(game.get_player(idle.player.id).is_some());
+        GameService::enforce_forfeits(game_service.clone(), game.clone(), Some(idle.clone()))?;
+        assert!(game.get_player(idle.player.id).is_none());
+        assert!(idle.player.get_game().is_none());
+        assert!(game_service.get_game(0).is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn enforce_forfeits_with_seat_held_past_grace_should_end_game(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Arc::new(Game::with_rules(
+            0,
+            players.clone(),
+            GameRules {
+                disconnect_grace: Duration::ZERO,
+                ..GameRules::default()
+            },
+        ));
+        for player in &players {
+            player.set_game(Some(game.clone()));
+        }
+        game_service.games.lock().unwrap().insert(0, game.clone());
+        GameService::release_seat(game_service.clone(), &players[0])?;
+        GameService::enforce_forfeits(game_service.clone(), game.clone(), None)?;
+        assert!(game_service.get_game(0).is_none());
+        let stats = game_service.get_stats_service();
+        assert_eq!(stats.get_stats("test0")?.games_won, 0);
+        assert_eq!(stats.get_stats("test1")?.games_won, 1);
+        let history = stats.get_history("test0", &HistoryFilter::default(), 1)?;
+        assert_eq!(history[0].forfeited, vec![(String::from("test0"), 0)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_game_should_cancel_timer_and_drop_every_reference(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let player = Arc::new(Player::new(0, String::from("test1")));
+        let player1 = Arc::new(Player::new(1, String::from("test2")));
+        let lobby = Arc::new(Lobby::new(0, 4, player.clone()));
+        lobby.add_player(player1.clone())?;
+        lobby.get_player(0).unwrap().set_ready(true);
+        lobby.get_player(1).unwrap().set_ready(true);
+        let game = GameService::start_game(game_service.clone(), player.clone(), lobby)?;
+        let weak = Arc::downgrade(&game);
+        game_service.remove_game(game)?;
+        // Let the runtime drop the aborted timer
+        for _ in 0..10 {
+            task::yield_now().await;
+        }
+        assert!(player.get_game().is_none());
+        assert!(player1.get_game().is_none());
+        assert!(weak.upgrade().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn release_seat_should_tell_others_how_long_the_seat_is_held(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Arc::new(Game::new(0, players.clone()));
+        for player in &players {
+            player.set_game(Some(game.clone()));
+        }
+        game_service.games.lock().unwrap().insert(0, game.clone());
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        game_service.broadcast_service.register(players[1].id, tx);
+        game_service
+            .broadcast_service
+            .set_capabilities(players[1].id, Capability::DisconnectEvents as u64);
+        GameService::release_seat(game_service.clone(), &players[0])?;
+        match rx.recv().await {
+            Some(crate::frame::Frame::Response(res)) => match res.get_data().as_ref() {
+                ResponseData::GameBroadcast(broadcast) => {
+                    assert_eq!(broadcast.event(), GameEvent::PlayerDisconnected);
+                    assert_eq!(broadcast.player.as_ref().unwrap().id, players[0].id);
+                    assert_eq!(
+                        broadcast.disconnect_grace,
+                        Some(game.get_rules().disconnect_grace.as_secs() as u32)
+                    );
+                    assert!(
+                        broadcast.reconnect_deadline.unwrap() > deadline_millis(Instant::now())
+                    );
+                }
+                _ => panic!("invalid response"),
+            },
+            _ => panic!("invalid frame"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn place_tile_should_send_delta_only_to_clients_with_board_deltas(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Arc::new(Game::new(0, players.clone()));
+        let mover = game.get_player_in_this_turn().player.clone();
+        let other = game.get_next_turn_player().unwrap().player.clone();
+        let mut receivers = Vec::new();
+        for player in [&mover, &other] {
+            let (tx, rx) = tokio::sync::mpsc::channel(4);
+            game_service.broadcast_service.register(player.id, tx);
+            receivers.push(rx);
+        }
+        game_service
+            .broadcast_service
+            .set_capabilities(mover.id, Capability::BoardDeltas as u64);
+        let center = game.get_rules().board_size / 2;
+        game_service.place_tile(game.clone(), &mover, 0, center, center)?;
+        let mut broadcasts = Vec::new();
+        for rx in &mut receivers {
+            match rx.recv().await {
+                Some(crate::frame::Frame::Response(res)) => match res.get_data().as_ref() {
+                    ResponseData::GameBroadcast(broadcast) => broadcasts.push(broadcast.clone()),
+                    _ => panic!("invalid response"),
+                },
+                _ => panic!("invalid frame"),
+            }
+        }
+        assert!(broadcasts[0].board.is_none());
+        let delta = broadcasts[0].board_delta.as_ref().unwrap();
+        assert_eq!(delta.version, 1);
+        assert_eq!(delta.squares.len(), 1);
+        assert!(broadcasts[1].board.is_some());
+        assert!(broadcasts[1].board_delta.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn spectate_should_tell_players_with_spectator_events_who_is_watching(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Arc::new(Game::new(0, players.clone()));
+        game_service.games.lock().unwrap().insert(0, game.clone());
+        let mut receivers = Vec::new();
+        for player in &players {
+            let (tx, rx) = tokio::sync::mpsc::channel(4);
+            game_service.broadcast_service.register(player.id, tx);
+            receivers.push(rx);
+        }
+        game_service
+            .broadcast_service
+            .set_capabilities(players[0].id, Capability::SpectatorEvents as u64);
+        let spectator = Arc::new(Player::new(2, String::from("test2")));
+        game_service.spectate(&spectator, Some(game.id))?;
+        match receivers[0].recv().await {
+            Some(crate::frame::Frame::Response(res)) => match res.get_data().as_ref() {
+                ResponseData::GameBroadcast(broadcast) => {
+                    assert_eq!(broadcast.event(), GameEvent::SpectatorJoined);
+                    assert_eq!(broadcast.player.as_ref().unwrap().id, spectator.id);
+                }
+                _ => panic!("invalid response"),
+            },
+            _ => panic!("invalid frame"),
+        }
+        tokio::task::yield_now().await;
+        assert!(receivers[1].try_recv().is_err());
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn release_seat_with_every_seat_vacant_should_end_game_after_grace(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Arc::new(Game::new(0, players.clone()));
+        for player in &players {
+            player.set_game(Some(game.clone()));
+        }
+        game_service.games.lock().unwrap().insert(0, game.clone());
+        game.pause();
+        let grace = game.get_rules().disconnect_grace;
+        let weak = Arc::downgrade(&game);
+        drop(game);
+        GameService::release_seat(game_service.clone(), &players[0])?;
+        GameService::release_seat(game_service.clone(), &players[1])?;
+        sleep(grace / 2).await;
+        assert!(game_service.get_game(0).is_some());
+        sleep(grace).await;
+        assert!(game_service.get_game(0).is_none());
+        assert!(players.iter().all(|player| player.get_game().is_none()));
+        assert!(weak.upgrade().is_none());
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn start_vote_kick_without_majority_should_run_out_and_keep_target(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let players = (0..3)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Arc::new(Game::new(0, players.clone()));
+        for player in &players {
+            player.set_game(Some(game.clone()));
+        }
+        game_service.games.lock().unwrap().insert(0, game.clone());
+        let broadcast_service = game_service.get_broadcast_service();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        broadcast_service.register(1, tx);
+        broadcast_service.set_capabilities(1, Capability::VoteKickEvents as u64);
+        let status = GameService::start_vote_kick(
+            game_service.clone(),
+            game.clone(),
+            players[0].clone(),
+            2,
+        )?;
+        assert!(!status.is_passed() && !status.is_failed());
+        let event = |frame: Option<crate::frame::Frame>| match frame {
+            Some(crate::frame::Frame::Response(res)) => match res.get_data().as_ref() {
+                ResponseData::GameBroadcast(broadcast) => broadcast.clone(),
+                _ => panic!("invalid response"),
+            },
+            _ => panic!("invalid frame"),
+        };
+        let broadcast = event(rx.recv().await);
+        assert_eq!(broadcast.event(), GameEvent::VoteKick);
+        assert_eq!(broadcast.kick_vote.unwrap().votes, 1);
+        sleep(GameConfig::default().vote_kick_timeout()).await;
+        let broadcast = event(rx.recv().await);
+        assert_eq!(broadcast.event(), GameEvent::VoteKickFailed);
+        assert!(broadcast.player.is_none());
+        assert!(game.get_kick_status().is_none());
+        assert!(game.get_player(2).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn next_turn_duration_after_freeze_timer_should_halve_turn(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Game::new(0, players.clone());
+        let turn_duration = game.get_rules().turn_duration;
+        assert_eq!(GameService::next_turn_duration(&game), turn_duration);
+        let center = game.get_rules().board_size / 2;
+        game.apply(Event::PlaceTile {
+            player_id: game.get_player_in_this_turn().player.id,
+            card_index: 0,
+            x: center,
+            y: center,
+        })?;
+        {
+            let board = game.get_board();
+            let mut board = board.lock().unwrap();
+            let tile = board.tiles[BOARD_SIZE - center - 1][center]
+                .as_mut()
+                .unwrap();
+            tile.power_up = Some(PowerUp::FreezeTimer);
+        }
+        game.apply(Event::EndTurn { keep_tiles: true })?;
+        assert_eq!(GameService::next_turn_duration(&game), turn_duration / 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn end_game_with_coop_should_send_shared_result_and_record_no_stats(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_service = Arc::new(GameService::new(HashSet::new()));
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let game = Arc::new(Game::with_rules(
+            0,
+            players.clone(),
+            GameRules {
+                coop: Some(crate::game::rules::Coop {
+                    target: 1000,
+                    rounds: 1,
+                }),
+                ..GameRules::default()
+            },
+        ));
+        for player in &players {
+            player.set_game(Some(game.clone()));
+        }
+        game_service.games.lock().unwrap().insert(0, game.clone());
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        game_service.broadcast_service.register(players[1].id, tx);
+        assert!(!GameService::finish_turn(
+            game_service.clone(),
+            game.clone(),
+            false
+        )?);
+        assert!(GameService::finish_turn(
+            game_service.clone(),
+            game.clone(),
+            false
+        )?);
+        match rx.recv().await {
+            Some(crate::frame::Frame::Response(res)) => match res.get_data().as_ref() {
+                ResponseData::GameBroadcast(broadcast) => {
+                    assert_eq!(broadcast.event(), GameEvent::Destroy);
+                    let coop = broadcast.coop.as_ref().unwrap();
+                    assert_eq!(coop.target, 1000);
+                    assert_eq!(coop.turns_left, 0);
+                    assert!(!coop.won);
+                }
+                _ => panic!("invalid response"),
+            },
+            _ => panic!("invalid frame"),
+        }
+        assert!(game_service.storage.load_stats("test0")?.is_none());
+        Ok(())
+    }
 }
@@ -0,0 +1,87 @@
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use tracing::debug;
+
+use crate::storage::Storage;
+
+#[derive(Debug)]
+pub struct LeaderboardService {
+    storage: Arc<dyn Storage>,
+    /// Held across each load and save so two scores submitted together
+    /// can't drop one another.
+    update: Mutex<()>,
+}
+
+impl LeaderboardService {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            update: Mutex::new(()),
+        }
+    }
+
+    fn daily_board(day: u32) -> String {
+        format!("daily-{}", day)
+    }
+
+    /// Enter a daily challenge score. Only an account's best score of the
+    /// day is kept, returns whether this one beat it.
+    pub fn submit_daily(
+        &self,
+        day: u32,
+        account: &str,
+        score: u32,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let board = LeaderboardService::daily_board(day);
+        let _update = self.update.lock().unwrap();
+        let best = self
+            .storage
+            .load_leaderboard(&board)?
+            .into_iter()
+            .find(|(name, _)| name == account)
+            .map(|(_, score)| score);
+        if best.is_some_and(|best| best >= score) {
+            return Ok(false);
+        }
+        self.storage
+            .save_leaderboard_score(&board, account, score)?;
+        debug!(day, account, score, "submit daily score");
+        Ok(true)
+    }
+
+    /// Scores of `day`, best first.
+    pub fn get_daily(&self, day: u32) -> Result<Vec<(String, u32)>, Box<dyn Error + Send + Sync>> {
+        let mut scores = self
+            .storage
+            .load_leaderboard(&LeaderboardService::daily_board(day))?;
+        scores.sort_by(|(a_name, a_score), (b_name, b_score)| {
+            b_score.cmp(a_score).then_with(|| a_name.cmp(b_name))
+        });
+        Ok(scores)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::memory::MemoryStorage;
+
+    use super::*;
+
+    #[test]
+    fn submit_daily_should_keep_best_score_per_day() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LeaderboardService::new(Arc::new(MemoryStorage::new()));
+        assert!(service.submit_daily(1, "test", 10)?);
+        assert!(!service.submit_daily(1, "test", 5)?);
+        assert!(service.submit_daily(1, "test1", 12)?);
+        assert!(service.submit_daily(2, "test", 3)?);
+        assert_eq!(
+            service.get_daily(1)?,
+            vec![(String::from("test1"), 12), (String::from("test"), 10)]
+        );
+        assert_eq!(service.get_daily(2)?, vec![(String::from("test"), 3)]);
+        Ok(())
+    }
+}
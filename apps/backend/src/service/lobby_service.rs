@@ -1,160 +1,689 @@
+use crate::error::ServerError;
 use std::{
-    collections::HashMap,
-    error::Error,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
+use dashmap::{mapref::entry::Entry, DashMap};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::{task, time};
+use tracing::{debug, info};
+
 use crate::{
+    config::LobbyConfig,
+    frame::{Response, ResponseData},
+    game::rules::GameRules,
     lobby::{lobby::Lobby, lobby_player::LobbyPlayer},
+    model::{
+        lobby::broadcast::{LobbyBroadcast, LobbyEvent},
+        state::State,
+    },
     player::Player,
 };
 
-#[cfg(not(test))]
-use crate::frame::{Response, ResponseData};
-#[cfg(not(test))]
-use crate::model::lobby::broadcast::{LobbyBroadcast, LobbyEvent};
-#[cfg(not(test))]
-use crate::model::state::State;
+use super::{broadcast_service::BroadcastService, cluster_service::ClusterService};
+
+pub const JOIN_CODE_LEN: usize = 6;
+/// Letters and digits that can't be mistaken for each other when read out.
+const JOIN_CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+/// How often `start_reaper` looks for idle lobbies.
+pub const REAP_INTERVAL: Duration = Duration::from_secs(30);
+/// How long a lobby handed over by another instance waits for its players
+/// to connect here.
+pub const HANDOFF_GRACE: Duration = Duration::from_secs(60);
+
+/// A lobby on its way to another instance. Players are kept by name since
+/// their ids don't outlive the connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LobbyHandoff {
+    pub lobby_id: u32,
+    pub max_players: u32,
+    pub min_players_to_start: u32,
+    pub rules: GameRules,
+    pub leader: String,
+    /// Everyone but the leader, in join order, with whether they were ready.
+    pub players: Vec<(String, bool)>,
+    pub banned: Vec<String>,
+}
+
+/// A handed over lobby waiting for its leader to connect.
+#[derive(Debug)]
+struct PendingHandoff {
+    handoff: LobbyHandoff,
+    received_at: Instant,
+    /// Players who connected before the leader, with whether they were
+    /// ready.
+    arrived: Vec<(Arc<Player>, bool)>,
+}
+
+/// A player of a handed over lobby who hasn't connected yet.
+#[derive(Debug)]
+struct ExpectedPlayer {
+    lobby_id: u32,
+    ready: bool,
+    received_at: Instant,
+}
 
 #[derive(Debug)]
 pub struct LobbyService {
-    next_lobby_id: Mutex<u32>,
-    lobbies: Mutex<HashMap<u32, Arc<Lobby>>>,
+    next_lobby_id: AtomicU32,
+    lobbies: DashMap<u32, Arc<Lobby>>,
+    /// Join codes of open lobbies, by code.
+    codes: DashMap<String, u32>,
+    /// Lobbies closed for sitting idle since the server started.
+    reaped: AtomicU64,
+    config: LobbyConfig,
+    broadcast_service: Arc<BroadcastService>,
+    /// Lobbies other instances handed over, by id, until their leader
+    /// connects.
+    handoffs: DashMap<u32, PendingHandoff>,
+    /// Players of handed over lobbies, by name, until they connect.
+    expected: DashMap<String, ExpectedPlayer>,
 }
 
 impl LobbyService {
     pub fn new() -> Self {
+        Self::with_config(LobbyConfig::default(), Arc::new(BroadcastService::new()))
+    }
+
+    pub fn with_config(config: LobbyConfig, broadcast_service: Arc<BroadcastService>) -> Self {
         Self {
-            next_lobby_id: Mutex::new(0),
-            lobbies: Mutex::new(HashMap::new()),
+            next_lobby_id: AtomicU32::new(0),
+            lobbies: DashMap::new(),
+            codes: DashMap::new(),
+            reaped: AtomicU64::new(0),
+            config,
+            broadcast_service,
+            handoffs: DashMap::new(),
+            expected: DashMap::new(),
         }
     }
 
+    /// Hand out lobby ids from the block of the instance `node_id` names,
+    /// so every instance can tell where a lobby lives.
+    pub fn with_node_id(self, node_id: u16) -> Self {
+        self.next_lobby_id
+            .store(ClusterService::first_lobby_id(node_id), Ordering::Relaxed);
+        self
+    }
+
+    fn lobby_event(event: LobbyEvent, lobby: Arc<Lobby>) -> Response {
+        Response::event(
+            State::LobbyBroadcast,
+            Arc::new(ResponseData::LobbyBroadcast(LobbyBroadcast {
+                event: event as i32,
+                lobby: Some(crate::model::lobby::lobby::Lobby::from(lobby)),
+                cards: None,
+                current_player: None,
+                next_player: None,
+                layout: None,
+                player: None,
+                turn_order: Vec::new(),
+                chat_history: Vec::new(),
+            })),
+        )
+    }
+
     pub fn create_lobby(
         &self,
         leader: Arc<Player>,
         max_players: u32,
-    ) -> Result<Arc<Lobby>, Box<dyn Error + Send + Sync + Send + Sync>> {
-        if max_players < 4 || max_players > 8 {
+    ) -> Result<Arc<Lobby>, ServerError> {
+        self.create_lobby_with_settings(leader, max_players, None, GameRules::default())
+    }
+
+    /// `rules` should already be validated by `GameService::resolve_rules`.
+    /// Without `min_players_to_start` the configured default is used.
+    pub fn create_lobby_with_settings(
+        &self,
+        leader: Arc<Player>,
+        max_players: u32,
+        min_players_to_start: Option<u32>,
+        rules: GameRules,
+    ) -> Result<Arc<Lobby>, ServerError> {
+        if max_players < self.config.min_players || max_players > self.config.max_players {
             return Err("Invalid max players".into());
         }
-        let mut next_lobby_id = self.next_lobby_id.lock().unwrap();
-        let lobby = Arc::new(Lobby::new(*next_lobby_id, max_players, leader.clone()));
-        self.lobbies
-            .lock()
-            .unwrap()
-            .insert(*next_lobby_id, lobby.clone());
-        *next_lobby_id += 1;
+        let min_players_to_start = min_players_to_start.unwrap_or(self.config.min_players_to_start);
+        if min_players_to_start == 0 || min_players_to_start > max_players {
+            return Err("Invalid min players to start".into());
+        }
+        self.leave_waitlist(&leader);
+        let id = self.next_lobby_id.fetch_add(1, Ordering::Relaxed);
+        let lobby = Arc::new(
+            Lobby::with_settings(id, max_players, min_players_to_start, leader.clone(), rules)
+                .with_code(self.reserve_code(id))
+                .with_chat_history(self.config.chat_history_len),
+        );
+        self.lobbies.insert(id, lobby.clone());
         leader.set_lobby(Some(lobby.clone()));
         Ok(lobby)
     }
 
-    pub fn add_player_to_lobby(
+    /// Pick a join code no open lobby is using and hold it for lobby `id`.
+    fn reserve_code(&self, id: u32) -> String {
+        let mut rng = rand::thread_rng();
+        loop {
+            let code: String = (0..JOIN_CODE_LEN)
+                .map(|_| JOIN_CODE_CHARS[rng.gen_range(0..JOIN_CODE_CHARS.len())] as char)
+                .collect();
+            if let Entry::Vacant(entry) = self.codes.entry(code.clone()) {
+                entry.insert(id);
+                return code;
+            }
+        }
+    }
+
+    /// Join `lobby`, or with `waitlist` queue for it when it is full. Returns
+    /// the waitlist position when the player was queued.
+    pub fn join_lobby(
         &self,
         player: Arc<Player>,
         lobby: Arc<Lobby>,
-    ) -> Result<Arc<LobbyPlayer>, Box<dyn Error + Send + Sync>> {
+        waitlist: bool,
+    ) -> Result<Option<usize>, ServerError> {
+        if waitlist && lobby.is_full() {
+            return Ok(Some(self.add_player_to_waitlist(player, lobby)?));
+        }
+        self.add_player_to_lobby(player, lobby)?;
+        Ok(None)
+    }
+
+    /// Put `player` in the fullest lobby that still has room and hasn't
+    /// started, or in a new lobby they lead when there is none.
+    pub fn quick_join(&self, player: Arc<Player>) -> Result<Arc<Lobby>, ServerError> {
         if player.get_lobby().is_some() {
-            return Err("player already in a lobby".into());
+            return Err(ServerError::AlreadyInLobby);
         }
-        let lobby_player = lobby.add_player(player.clone())?;
-        #[cfg(not(test))]
-        {
-            for lobby_player in lobby.get_players() {
-                if lobby_player.player == player {
-                    continue;
-                }
-                let lobby = lobby.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = lobby_player
-                        .player
-                        .send_message(Response::new(
-                            State::LobbyBroadcast as u32,
-                            Arc::new(ResponseData::LobbyBroadcast(LobbyBroadcast {
-                                event: LobbyEvent::Join as i32,
-                                lobby: Some(crate::model::lobby::lobby::Lobby::from(lobby.clone())),
-                                cards: None,
-                                current_player: None,
-                                next_player: None,
-                            })),
-                        ))
-                        .await
-                    {
-                        eprintln!("Error sending lobby broadcast: {}", e);
-                    }
-                });
+        let mut open: Vec<_> = self
+            .get_lobbies()
+            .into_iter()
+            .filter(|lobby| !lobby.is_full() && !lobby.is_in_game())
+            .collect();
+        open.sort_by_key(|lobby| (std::cmp::Reverse(lobby.get_players().len()), lobby.get_id()));
+        for lobby in open {
+            // Another player may have taken the last slot in the meantime
+            if self
+                .add_player_to_lobby(player.clone(), lobby.clone())
+                .is_ok()
+            {
+                return Ok(lobby);
             }
         }
+        self.create_lobby(player, self.config.min_players)
+    }
+
+    pub fn add_player_to_lobby(
+        &self,
+        player: Arc<Player>,
+        lobby: Arc<Lobby>,
+    ) -> Result<Arc<LobbyPlayer>, ServerError> {
+        if player.get_lobby().is_some() {
+            return Err(ServerError::AlreadyInLobby);
+        }
+        LobbyService::check_welcome(&lobby, &player)?;
+        let lobby_player = lobby.add_player(player.clone())?;
+        self.leave_waitlist(&player);
+        self.broadcast_service.send_to_lobby(
+            &lobby,
+            LobbyService::lobby_event(LobbyEvent::Join, lobby.clone()),
+            Some(player.id),
+        );
         player.set_lobby(Some(lobby));
         Ok(lobby_player)
     }
 
+    /// Queue `player` for the next free slot in `lobby`, returning their
+    /// 1-based position. A player waits on at most one lobby at a time.
+    pub fn add_player_to_waitlist(
+        &self,
+        player: Arc<Player>,
+        lobby: Arc<Lobby>,
+    ) -> Result<usize, ServerError> {
+        if player.get_lobby().is_some() {
+            return Err(ServerError::AlreadyInLobby);
+        }
+        LobbyService::check_welcome(&lobby, &player)?;
+        if !lobby.get_waitlist().contains(&player) {
+            self.leave_waitlist(&player);
+        }
+        lobby.add_to_waitlist(player)
+    }
+
+    /// Keep out players the lobby's leader banned or blocked.
+    fn check_welcome(lobby: &Lobby, player: &Player) -> Result<(), ServerError> {
+        if lobby.is_banned(&player.name) || lobby.leader.has_blocked(&player.name) {
+            return Err(ServerError::BannedFromLobby);
+        }
+        Ok(())
+    }
+
+    /// Let `leader` remove `player_id` from their lobby before its game
+    /// starts, and with `ban` keep them out while it is open. Returns the
+    /// kicked player.
+    pub fn kick_player(
+        &self,
+        leader: Arc<Player>,
+        player_id: u32,
+        ban: bool,
+    ) -> Result<Arc<Player>, ServerError> {
+        let lobby = match leader.get_lobby() {
+            Some(lobby) => lobby,
+            None => return Err(ServerError::NotInLobby),
+        };
+        if leader != lobby.leader {
+            return Err("Only leader can kick players".into());
+        }
+        if player_id == leader.id {
+            return Err("Cannot kick yourself".into());
+        }
+        if lobby.is_in_game() {
+            return Err(ServerError::AlreadyInGame);
+        }
+        let player = match lobby.get_player(player_id) {
+            Some(lobby_player) => lobby_player.player.clone(),
+            None => return Err(ServerError::PlayerNotFound),
+        };
+        if ban {
+            lobby.ban(&player.name);
+        }
+        self.remove_player_from_lobby(player.clone())?;
+        self.broadcast_service.send_to_player(
+            player.id,
+            LobbyService::lobby_event(LobbyEvent::Kicked, lobby.clone()),
+        );
+        tracing::debug!(lobby_id = lobby.get_id(), player_id, ban, "kick player");
+        Ok(player)
+    }
+
+    /// Change the settings of `leader`'s lobby before its game starts. Every
+    /// player has to be ready again, so nobody starts on settings they never
+    /// saw. `rules` should already be validated by
+    /// `GameService::resolve_rules`.
+    pub fn update_settings(
+        &self,
+        leader: Arc<Player>,
+        max_players: u32,
+        min_players_to_start: u32,
+        rules: GameRules,
+    ) -> Result<Arc<Lobby>, ServerError> {
+        let lobby = match leader.get_lobby() {
+            Some(lobby) => lobby,
+            None => return Err(ServerError::NotInLobby),
+        };
+        if leader != lobby.leader {
+            return Err("Only leader can change settings".into());
+        }
+        if lobby.is_in_game() {
+            return Err(ServerError::AlreadyInGame);
+        }
+        if max_players < self.config.min_players || max_players > self.config.max_players {
+            return Err("Invalid max players".into());
+        }
+        if (max_players as usize) < lobby.get_players().len() {
+            return Err("Max players is below the players in the lobby".into());
+        }
+        if min_players_to_start == 0 || min_players_to_start > max_players {
+            return Err("Invalid min players to start".into());
+        }
+        lobby.set_settings(max_players, min_players_to_start, rules);
+        lobby.reset_ready();
+        self.promote_waitlist(lobby.clone());
+        self.broadcast_service.send_to_lobby(
+            &lobby,
+            LobbyService::lobby_event(LobbyEvent::SettingsChanged, lobby.clone()),
+            Some(leader.id),
+        );
+        tracing::debug!(
+            lobby_id = lobby.get_id(),
+            max_players,
+            "update lobby settings"
+        );
+        Ok(lobby)
+    }
+
+    /// Take `player` off whichever waitlist they are on, returning whether
+    /// they were on one.
+    pub fn leave_waitlist(&self, player: &Arc<Player>) -> bool {
+        self.lobbies
+            .iter()
+            .any(|lobby| lobby.value().remove_from_waitlist(player))
+    }
+
+    /// Move waitlisted players into `lobby` until it is full or nobody is
+    /// left waiting.
+    fn promote_waitlist(&self, lobby: Arc<Lobby>) {
+        while !lobby.is_full() {
+            let player = match lobby.pop_waitlist() {
+                Some(player) => player,
+                None => return,
+            };
+            if player.get_game().is_some() {
+                continue;
+            }
+            if let Err(e) = self.add_player_to_lobby(player.clone(), lobby.clone()) {
+                tracing::debug!(error = %e, "skipping waitlisted player");
+                continue;
+            }
+            // They missed what was said while they waited
+            self.broadcast_service.send_to_player(
+                player.id,
+                Response::event(
+                    State::LobbyBroadcast,
+                    Arc::new(ResponseData::LobbyBroadcast(LobbyBroadcast {
+                        event: LobbyEvent::Promoted as i32,
+                        lobby: Some(crate::model::lobby::lobby::Lobby::from(lobby.clone())),
+                        chat_history: lobby.get_chat_history(&player),
+                        ..Default::default()
+                    })),
+                ),
+            );
+        }
+    }
+
     pub fn get_lobbies(&self) -> Vec<Arc<Lobby>> {
-        self.lobbies.lock().unwrap().values().cloned().collect()
+        self.lobbies.iter().map(|x| x.value().clone()).collect()
     }
 
     pub fn get_lobby(&self, id: u32) -> Option<Arc<Lobby>> {
-        Some(self.lobbies.lock().unwrap().get(&id)?.clone())
+        Some(self.lobbies.get(&id)?.clone())
+    }
+
+    pub fn get_lobby_by_code(&self, code: &str) -> Option<Arc<Lobby>> {
+        let id = *self.codes.get(&code.trim().to_uppercase())?;
+        self.get_lobby(id)
     }
 
     pub fn remove_player_from_lobby(
         &self,
         player: Arc<Player>,
-    ) -> Result<Arc<LobbyPlayer>, Box<dyn Error + Send + Sync>> {
+    ) -> Result<Arc<LobbyPlayer>, ServerError> {
         let lobby = match player.clone().get_lobby() {
             Some(lobby) => lobby,
-            None => return Err("Player is not in a lobby".into()),
+            None => return Err(ServerError::NotInLobby),
         };
         let lobby_player = lobby.remove_player(player.clone())?;
-        let is_lobby_destroy = player == lobby.leader;
-        #[cfg(not(test))]
-        {
-            for lobby_player in lobby.get_players() {
-                let lobby = lobby.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = lobby_player
-                        .player
-                        .send_message(Response::new(
-                            State::LobbyBroadcast as u32,
-                            Arc::new(ResponseData::LobbyBroadcast(LobbyBroadcast {
-                                event: match is_lobby_destroy {
-                                    true => LobbyEvent::Destroy as i32,
-                                    false => LobbyEvent::Leave as i32,
-                                },
-                                lobby: Some(crate::model::lobby::lobby::Lobby::from(lobby)),
-                                cards: None,
-                                current_player: None,
-                                next_player: None,
-                            })),
-                        ))
-                        .await
-                    {
-                        eprintln!("Error sending lobby broadcast: {}", e);
-                    }
-                });
-            }
+        let is_lobby_destroy = player == lobby.leader || lobby.is_empty();
+        // Nobody is promoted into a lobby that is going away
+        let waitlist = match is_lobby_destroy {
+            true => lobby.take_waitlist(),
+            false => Vec::new(),
+        };
+        let res = LobbyService::lobby_event(
+            match is_lobby_destroy {
+                true => LobbyEvent::Destroy,
+                false => LobbyEvent::Leave,
+            },
+            lobby.clone(),
+        );
+        self.broadcast_service
+            .send_to_lobby(&lobby, res.clone(), None);
+        for waiting in waitlist {
+            self.broadcast_service
+                .send_to_player(waiting.id, res.clone());
         }
         player.set_lobby(None);
-        if is_lobby_destroy {
-            self.remove_lobby(lobby)?;
+        match is_lobby_destroy {
+            true => {
+                // Whoever is left goes with it, as when it is reaped
+                for lobby_player in lobby.get_players() {
+                    lobby_player.player.set_lobby(None);
+                }
+                self.remove_lobby(lobby)?;
+            }
+            false => self.promote_waitlist(lobby),
         }
         Ok(lobby_player)
     }
 
-    pub fn remove_lobby(
-        &self,
-        lobby: Arc<Lobby>,
-    ) -> Result<Arc<Lobby>, Box<dyn Error + Send + Sync>> {
-        let mut lobbies = self.lobbies.lock().unwrap();
-        if !lobbies.contains_key(&lobby.get_id()) {
-            return Err("Lobby does not exist".into());
+    /// Close every lobby that is empty, or that never started a game and
+    /// nobody joined or left for `max_idle`. Everyone in or waiting on a
+    /// closed lobby is sent a `Destroy` event. Returns the closed lobbies.
+    pub fn reap_idle_lobbies(&self, max_idle: Duration) -> Vec<Arc<Lobby>> {
+        let idle: Vec<_> = self
+            .get_lobbies()
+            .into_iter()
+            .filter(|lobby| {
+                lobby.is_empty()
+                    || (!lobby.has_started() && !lobby.is_in_game() && lobby.idle_for() >= max_idle)
+            })
+            .collect();
+        let mut reaped = Vec::new();
+        for lobby in idle {
+            // Someone may have closed it in the meantime
+            if self.remove_lobby(lobby.clone()).is_err() {
+                continue;
+            }
+            let res = LobbyService::lobby_event(LobbyEvent::Destroy, lobby.clone());
+            self.broadcast_service
+                .send_to_lobby(&lobby, res.clone(), None);
+            for waiting in lobby.take_waitlist() {
+                self.broadcast_service
+                    .send_to_player(waiting.id, res.clone());
+            }
+            for lobby_player in lobby.get_players() {
+                lobby_player.player.set_lobby(None);
+            }
+            lobby.clear_chat_history();
+            self.reaped.fetch_add(1, Ordering::Relaxed);
+            info!(
+                lobby_id = lobby.get_id(),
+                players = lobby.get_players().len(),
+                idle_secs = lobby.idle_for().as_secs(),
+                "reaped idle lobby"
+            );
+            reaped.push(lobby);
+        }
+        reaped
+    }
+
+    /// Lobbies `reap_idle_lobbies` closed since the server started.
+    pub fn reaped_count(&self) -> u64 {
+        self.reaped.load(Ordering::Relaxed)
+    }
+
+    pub fn start_reaper(lobby_service: Arc<LobbyService>, max_idle: Duration) {
+        task::spawn(async move {
+            let mut ticker = time::interval(REAP_INTERVAL.min(max_idle));
+            loop {
+                ticker.tick().await;
+                lobby_service.reap_idle_lobbies(max_idle);
+            }
+        });
+    }
+
+    /// Close `lobby` here so another instance can open it, returning what
+    /// that takes. Its players are let go for the caller to send on, anyone
+    /// waiting for a slot is told it closed. Lobbies with a game running
+    /// stay.
+    pub fn hand_off(&self, lobby: Arc<Lobby>) -> Result<LobbyHandoff, ServerError> {
+        if lobby.is_in_game() {
+            return Err(ServerError::AlreadyInGame);
+        }
+        self.remove_lobby(lobby.clone())?;
+        let res = LobbyService::lobby_event(LobbyEvent::Destroy, lobby.clone());
+        for waiting in lobby.take_waitlist() {
+            self.broadcast_service
+                .send_to_player(waiting.id, res.clone());
+        }
+        for lobby_player in lobby.get_players() {
+            lobby_player.player.set_lobby(None);
+        }
+        debug!(lobby_id = lobby.get_id(), "hand off lobby");
+        Ok(LobbyHandoff {
+            lobby_id: lobby.get_id(),
+            max_players: lobby.get_max_players(),
+            min_players_to_start: lobby.get_min_players_to_start(),
+            rules: lobby.get_rules(),
+            leader: lobby.leader.name.clone(),
+            players: lobby
+                .get_players_in_join_order()
+                .into_iter()
+                .filter(|lobby_player| lobby_player.player.id != lobby.leader.id)
+                .map(|lobby_player| (lobby_player.player.name.clone(), lobby_player.get_ready()))
+                .collect(),
+            banned: lobby.get_banned(),
+        })
+    }
+
+    /// Hold a lobby another instance handed over until its leader connects
+    /// here, when it opens again under the same id. Its players among
+    /// `online`, who got here first, are put back in it straight away.
+    pub fn adopt(&self, handoff: LobbyHandoff, online: Vec<Arc<Player>>) {
+        self.handoffs
+            .retain(|_, pending| pending.received_at.elapsed() < HANDOFF_GRACE);
+        self.expected
+            .retain(|_, expected| expected.received_at.elapsed() < HANDOFF_GRACE);
+        let received_at = Instant::now();
+        for (name, ready) in handoff.players.iter() {
+            self.expected.insert(
+                name.clone(),
+                ExpectedPlayer {
+                    lobby_id: handoff.lobby_id,
+                    ready: *ready,
+                    received_at,
+                },
+            );
+        }
+        debug!(lobby_id = handoff.lobby_id, "adopt lobby");
+        let names = handoff
+            .players
+            .iter()
+            .map(|(name, _)| name)
+            .chain([&handoff.leader])
+            .cloned()
+            .collect::<Vec<_>>();
+        self.handoffs.insert(
+            handoff.lobby_id,
+            PendingHandoff {
+                handoff,
+                received_at,
+                arrived: Vec::new(),
+            },
+        );
+        for player in online {
+            if names.contains(&player.name) {
+                self.claim(player, true);
+            }
+        }
+    }
+
+    /// Put `player` back in the handed over lobby they were in, if any. The
+    /// leader opens it, whoever connects before them is let in then.
+    pub fn claim_handoff(&self, player: Arc<Player>) -> Option<Arc<Lobby>> {
+        self.claim(player, false)
+    }
+
+    /// With `notify` the player is sent the lobby once they are in it,
+    /// otherwise the caller tells them.
+    fn claim(&self, player: Arc<Player>, notify: bool) -> Option<Arc<Lobby>> {
+        if player.get_lobby().is_some() {
+            return None;
+        }
+        let led = self
+            .handoffs
+            .iter()
+            .find(|pending| pending.handoff.leader == player.name)
+            .map(|pending| *pending.key());
+        if let Some(lobby_id) = led {
+            let (_, pending) = self.handoffs.remove(&lobby_id)?;
+            if pending.received_at.elapsed() >= HANDOFF_GRACE {
+                return None;
+            }
+            let handoff = pending.handoff;
+            let lobby = Arc::new(
+                Lobby::with_settings(
+                    lobby_id,
+                    handoff.max_players,
+                    handoff.min_players_to_start,
+                    player.clone(),
+                    handoff.rules,
+                )
+                .with_code(self.reserve_code(lobby_id))
+                .with_chat_history(self.config.chat_history_len),
+            );
+            for name in handoff.banned.iter() {
+                lobby.ban(name);
+            }
+            self.lobbies.insert(lobby_id, lobby.clone());
+            player.set_lobby(Some(lobby.clone()));
+            if notify {
+                self.broadcast_service.send_to_player(
+                    player.id,
+                    LobbyService::lobby_event(LobbyEvent::Join, lobby.clone()),
+                );
+            }
+            for (arrived, ready) in pending.arrived {
+                // They connected without a lobby, so they hear of it now
+                if self.rejoin(arrived.clone(), &lobby, ready) {
+                    self.broadcast_service.send_to_player(
+                        arrived.id,
+                        LobbyService::lobby_event(LobbyEvent::Join, lobby.clone()),
+                    );
+                }
+            }
+            return Some(lobby);
+        }
+        let (_, expected) = self.expected.remove(&player.name)?;
+        if expected.received_at.elapsed() >= HANDOFF_GRACE {
+            return None;
+        }
+        match self.get_lobby(expected.lobby_id) {
+            Some(lobby) => {
+                if !self.rejoin(player.clone(), &lobby, expected.ready) {
+                    return None;
+                }
+                if notify {
+                    self.broadcast_service.send_to_player(
+                        player.id,
+                        LobbyService::lobby_event(LobbyEvent::Join, lobby.clone()),
+                    );
+                }
+                Some(lobby)
+            }
+            None => {
+                let mut pending = self.handoffs.get_mut(&expected.lobby_id)?;
+                pending.arrived.push((player, expected.ready));
+                None
+            }
+        }
+    }
+
+    /// Returns whether `player` got back in.
+    fn rejoin(&self, player: Arc<Player>, lobby: &Arc<Lobby>, ready: bool) -> bool {
+        match self.add_player_to_lobby(player.clone(), lobby.clone()) {
+            Ok(lobby_player) => {
+                lobby_player.set_ready(ready);
+                true
+            }
+            Err(e) => {
+                debug!(lobby_id = lobby.get_id(), player_id = player.id, error = %e, "rejoin handed over lobby");
+                false
+            }
+        }
+    }
+
+    pub fn remove_lobby(&self, lobby: Arc<Lobby>) -> Result<Arc<Lobby>, ServerError> {
+        match self.lobbies.remove(&lobby.get_id()) {
+            Some((_, lobby)) => {
+                if let Some(code) = lobby.get_code() {
+                    self.codes.remove(code);
+                }
+                Ok(lobby)
+            }
+            None => Err(ServerError::LobbyNotFound),
         }
-        Ok(lobbies.remove(&lobby.get_id()).unwrap())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
+
     use super::*;
 
     #[tokio::test]
@@ -162,7 +691,7 @@ mod tests {
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         let service = LobbyService::new();
         service.create_lobby(Arc::new(Player::new(0, String::from("test"))), 4)?;
-        assert!(service.lobbies.lock().unwrap().get(&0).is_some());
+        assert!(service.lobbies.get(&0).is_some());
         Ok(())
     }
 
@@ -182,14 +711,7 @@ mod tests {
         let service = LobbyService::new();
         let leader = Arc::new(Player::new(0, String::from("test")));
         service.create_lobby(leader, 4)?;
-        assert!(service
-            .lobbies
-            .lock()
-            .unwrap()
-            .get(&0)
-            .unwrap()
-            .get_player(0)
-            .is_some());
+        assert!(service.lobbies.get(&0).unwrap().get_player(0).is_some());
         Ok(())
     }
 
@@ -199,16 +721,9 @@ mod tests {
         let service = LobbyService::new();
         let leader = Arc::new(Player::new(0, String::from("test")));
         let lobby = Arc::new(Lobby::new(0, 4, leader.clone()));
-        service.lobbies.lock().unwrap().insert(0, lobby.clone());
+        service.lobbies.insert(0, lobby.clone());
         service.add_player_to_lobby(Arc::new(Player::new(1, String::from("test2"))), lobby)?;
-        assert!(service
-            .lobbies
-            .lock()
-            .unwrap()
-            .get(&0)
-            .unwrap()
-            .get_player(1)
-            .is_some());
+        assert!(service.lobbies.get(&0).unwrap().get_player(1).is_some());
         Ok(())
     }
 
@@ -218,7 +733,7 @@ mod tests {
         let service = LobbyService::new();
         let leader = Arc::new(Player::new(0, String::from("test")));
         let lobby = Arc::new(Lobby::new(0, 4, leader.clone()));
-        service.lobbies.lock().unwrap().insert(0, lobby.clone());
+        service.lobbies.insert(0, lobby.clone());
         let player = Arc::new(Player::new(1, String::from("test2")));
         service.add_player_to_lobby(player.clone(), lobby.clone())?;
         assert_eq!(player.get_lobby().unwrap(), lobby);
@@ -234,14 +749,7 @@ mod tests {
         let other_player = Arc::new(Player::new(1, String::from("test1")));
         service.add_player_to_lobby(other_player.clone(), lobby)?;
         service.remove_player_from_lobby(other_player)?;
-        assert!(service
-            .lobbies
-            .lock()
-            .unwrap()
-            .get(&0)
-            .unwrap()
-            .get_player(1)
-            .is_none());
+        assert!(service.lobbies.get(&0).unwrap().get_player(1).is_none());
         Ok(())
     }
 
@@ -252,7 +760,7 @@ mod tests {
         let leader = Arc::new(Player::new(0, String::from("test")));
         service.create_lobby(leader.clone(), 4)?;
         service.remove_player_from_lobby(leader)?;
-        assert!(service.lobbies.lock().unwrap().get(&0).is_none());
+        assert!(service.lobbies.get(&0).is_none());
         Ok(())
     }
 
@@ -263,7 +771,22 @@ mod tests {
         let leader = Arc::new(Player::new(0, String::from("test")));
         service.create_lobby(leader.clone(), 4)?;
         service.remove_player_from_lobby(leader)?;
-        assert!(service.lobbies.lock().unwrap().is_empty());
+        assert!(service.lobbies.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_player_from_lobby_with_leader_should_let_others_leave_and_join_again(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LobbyService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        let player = Arc::new(Player::new(1, String::from("test1")));
+        let lobby = service.create_lobby(leader.clone(), 4)?;
+        service.add_player_to_lobby(player.clone(), lobby)?;
+        service.remove_player_from_lobby(leader)?;
+        assert!(player.get_lobby().is_none());
+        assert!(service.remove_player_from_lobby(player.clone()).is_err());
+        service.create_lobby(player, 4)?;
         Ok(())
     }
 
@@ -271,7 +794,7 @@ mod tests {
     async fn get_lobbies_with_test_looby_should_return_test_lobby(
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         let service = LobbyService::new();
-        service.lobbies.lock().unwrap().insert(
+        service.lobbies.insert(
             0,
             Arc::new(Lobby::new(
                 0,
@@ -293,9 +816,221 @@ mod tests {
             4,
             Arc::new(Player::new(0, String::from("test"))),
         ));
-        service.lobbies.lock().unwrap().insert(0, lobby.clone());
+        service.lobbies.insert(0, lobby.clone());
         service.remove_lobby(lobby)?;
-        assert_eq!(service.lobbies.lock().unwrap().len(), 0);
+        assert_eq!(service.lobbies.len(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn create_lobby_with_min_players_to_start_over_max_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LobbyService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        assert!(service
+            .create_lobby_with_settings(leader.clone(), 4, Some(5), GameRules::default())
+            .is_err());
+        assert!(service
+            .create_lobby_with_settings(leader, 4, Some(0), GameRules::default())
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_player_from_full_lobby_should_promote_first_waitlisted_player(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LobbyService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        let lobby = service.create_lobby(leader, 4)?;
+        let players: Vec<_> = (1..4)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect();
+        for player in players.iter() {
+            service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        }
+        let first = Arc::new(Player::new(4, String::from("test4")));
+        let second = Arc::new(Player::new(5, String::from("test5")));
+        assert!(service
+            .add_player_to_lobby(first.clone(), lobby.clone())
+            .is_err());
+        assert_eq!(
+            service.add_player_to_waitlist(first.clone(), lobby.clone())?,
+            1
+        );
+        assert_eq!(
+            service.add_player_to_waitlist(second.clone(), lobby.clone())?,
+            2
+        );
+        service.remove_player_from_lobby(players[0].clone())?;
+        assert_eq!(first.get_lobby().unwrap(), lobby);
+        assert!(second.get_lobby().is_none());
+        assert_eq!(lobby.get_waitlist(), vec![second]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_lobby_by_code_should_find_lobby_until_it_closes(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LobbyService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        let lobby = service.create_lobby(leader.clone(), 4)?;
+        let other = service.create_lobby(Arc::new(Player::new(1, String::from("test1"))), 4)?;
+        let code = lobby.get_code().unwrap().to_string();
+        assert_eq!(code.len(), JOIN_CODE_LEN);
+        assert_ne!(other.get_code(), Some(code.as_str()));
+        assert_eq!(service.get_lobby_by_code(&code), Some(lobby.clone()));
+        assert_eq!(service.get_lobby_by_code(&code.to_lowercase()), Some(lobby));
+        service.remove_player_from_lobby(leader)?;
+        assert!(service.get_lobby_by_code(&code).is_none());
+        assert!(service.codes.get(&code).is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quick_join_should_pick_fullest_open_lobby_or_create_one(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LobbyService::new();
+        let empty = service.create_lobby(Arc::new(Player::new(0, String::from("test"))), 4)?;
+        let busy = service.create_lobby(Arc::new(Player::new(1, String::from("test1"))), 4)?;
+        service.add_player_to_lobby(
+            Arc::new(Player::new(2, String::from("test2"))),
+            busy.clone(),
+        )?;
+        let player = Arc::new(Player::new(3, String::from("test3")));
+        assert_eq!(service.quick_join(player.clone())?, busy);
+        assert!(service.quick_join(player).is_err());
+        service.add_player_to_lobby(Arc::new(Player::new(4, String::from("test4"))), busy)?;
+        assert_eq!(
+            service.quick_join(Arc::new(Player::new(5, String::from("test5"))))?,
+            empty
+        );
+        service.remove_player_from_lobby(empty.leader.clone())?;
+        let player = Arc::new(Player::new(6, String::from("test6")));
+        let created = service.quick_join(player.clone())?;
+        assert_eq!(created.leader, player);
+        assert_eq!(service.get_lobbies().len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_player_from_lobby_with_leader_should_clear_waitlist(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LobbyService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        let lobby = service.create_lobby(leader.clone(), 4)?;
+        let player = Arc::new(Player::new(1, String::from("test1")));
+        service.add_player_to_waitlist(player.clone(), lobby.clone())?;
+        service.remove_player_from_lobby(leader)?;
+        assert!(lobby.get_waitlist().is_empty());
+        assert!(player.get_lobby().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reap_idle_lobbies_should_close_only_idle_lobbies_that_never_started(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LobbyService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        let idle = service.create_lobby(leader.clone(), 4)?;
+        let waiting = Arc::new(Player::new(1, String::from("test1")));
+        service.add_player_to_waitlist(waiting, idle.clone())?;
+        let started = service.create_lobby(Arc::new(Player::new(2, String::from("test2"))), 4)?;
+        started.set_started();
+        assert!(service
+            .reap_idle_lobbies(Duration::from_secs(3600))
+            .is_empty());
+        assert_eq!(
+            service.reap_idle_lobbies(Duration::ZERO),
+            vec![idle.clone()]
+        );
+        assert_eq!(service.get_lobbies(), vec![started]);
+        assert!(leader.get_lobby().is_none());
+        assert!(idle.get_waitlist().is_empty());
+        assert!(service
+            .get_lobby_by_code(idle.get_code().unwrap())
+            .is_none());
+        assert_eq!(service.reaped_count(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn kick_player_with_ban_should_keep_player_out(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LobbyService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        let lobby = service.create_lobby(leader.clone(), 4)?;
+        let kicked = Arc::new(Player::new(1, String::from("test1")));
+        let banned = Arc::new(Player::new(2, String::from("test2")));
+        for player in [&kicked, &banned] {
+            service.add_player_to_lobby(player.clone(), lobby.clone())?;
+        }
+        assert!(service.kick_player(kicked.clone(), 2, false).is_err());
+        service.kick_player(leader.clone(), 1, false)?;
+        service.kick_player(leader, 2, true)?;
+        assert!(kicked.get_lobby().is_none());
+        assert!(banned.get_lobby().is_none());
+        service.add_player_to_lobby(kicked, lobby.clone())?;
+        // Banned by name, so coming back on another connection doesn't help
+        let rejoined = Arc::new(Player::new(3, String::from("test2")));
+        assert!(matches!(
+            service.add_player_to_lobby(rejoined.clone(), lobby.clone()),
+            Err(ServerError::BannedFromLobby)
+        ));
+        assert!(service.add_player_to_waitlist(rejoined, lobby).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn add_player_to_lobby_blocked_by_leader_should_return_error(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = LobbyService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        let lobby = service.create_lobby(leader.clone(), 4)?;
+        leader.set_blocked("test1", true);
+        let player = Arc::new(Player::new(1, String::from("test1")));
+        assert!(matches!(
+            service.add_player_to_lobby(player.clone(), lobby.clone()),
+            Err(ServerError::BannedFromLobby)
+        ));
+        // Quick join looks elsewhere
+        assert_ne!(service.quick_join(player)?, lobby);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn hand_off_then_adopt_should_reopen_lobby_as_players_connect(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let source = LobbyService::new();
+        let leader = Arc::new(Player::new(0, String::from("test")));
+        let lobby = source.create_lobby(leader.clone(), 4)?;
+        for (id, name) in [(1, "test1"), (2, "test2"), (3, "test3")] {
+            source.add_player_to_lobby(
+                Arc::new(Player::new(id, String::from(name))),
+                lobby.clone(),
+            )?;
+        }
+        lobby.get_player(1).unwrap().set_ready(true);
+        source.kick_player(leader.clone(), 3, true)?;
+        let handoff = source.hand_off(lobby.clone())?;
+        assert!(source.get_lobby(lobby.get_id()).is_none());
+        assert!(leader.get_lobby().is_none());
+
+        let target = LobbyService::new().with_node_id(1);
+        target.adopt(handoff, Vec::new());
+        // Before the leader there's no lobby to put them in yet
+        let early = Arc::new(Player::new(10, String::from("test1")));
+        assert!(target.claim_handoff(early.clone()).is_none());
+        let leader = Arc::new(Player::new(11, String::from("test")));
+        let adopted = target.claim_handoff(leader).unwrap();
+        assert_eq!(adopted.get_id(), lobby.get_id());
+        assert!(adopted.get_player(early.id).unwrap().get_ready());
+        let late = Arc::new(Player::new(12, String::from("test2")));
+        assert_eq!(target.claim_handoff(late.clone()), Some(adopted.clone()));
+        assert!(!adopted.get_player(late.id).unwrap().get_ready());
+        assert!(adopted.is_banned("test3"));
+        assert!(target
+            .claim_handoff(Arc::new(Player::new(13, String::from("test3"))))
+            .is_none());
         Ok(())
     }
 }
@@ -1,88 +1,362 @@
 #[cfg(not(test))]
 use crate::frame::Frame;
-use crate::player::Player;
+use crate::{config::SessionPolicy, error::ServerError};
+use crate::{
+    frame::{Response, ResponseData},
+    game::game::MoveViolation,
+    model::{
+        control::notice::Notice,
+        game::broadcast::{GameBroadcast, GameEvent},
+        lobby::broadcast::{LobbyBroadcast, LobbyEvent},
+        state::State,
+    },
+    player::Player,
+};
 
-use std::{
-    collections::HashMap,
-    error::Error,
-    sync::{Arc, Mutex},
+use dashmap::DashMap;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
 };
 #[cfg(not(test))]
 use tokio::sync::mpsc::Sender;
 
-use super::{game_service::GameService, lobby_service::LobbyService};
+use super::{
+    block_service::BlockService, broadcast_service::BroadcastService,
+    cluster_service::ClusterService, game_service::GameService, lobby_service::LobbyService,
+    profile_service::ProfileService,
+};
 
-type ClientMap = Arc<Mutex<HashMap<u32, Arc<Player>>>>;
+/// Sharded by client id so unrelated connections don't contend on one lock.
+type ClientMap = Arc<DashMap<u32, Arc<Player>>>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct PlayerService {
     online_player_map: ClientMap,
     lobby_service: Arc<LobbyService>,
     game_service: Arc<GameService>,
+    broadcast_service: Arc<BroadcastService>,
+    /// Average round trip a player's lobby or game is warned about, 0 to
+    /// never warn.
+    lag_warning_ms: AtomicU32,
+    /// Invalid moves a player is kicked after, 0 to never kick.
+    kick_after_violations: AtomicU32,
+    session_policy: Mutex<SessionPolicy>,
+    /// Held from checking a name for open sessions until the new one is
+    /// in, so two sessions connecting at once can't both miss each other.
+    opening: Mutex<()>,
+    /// Where players' profiles are loaded from when they connect, if
+    /// anywhere.
+    profile_service: Option<Arc<ProfileService>>,
+    /// Where the accounts players blocked are loaded from when they
+    /// connect, if anywhere.
+    block_service: Option<Arc<BlockService>>,
+    /// Told who comes online and goes offline, for the other instances.
+    cluster_service: Arc<ClusterService>,
 }
 
 impl PlayerService {
     pub fn new(lobby_service: Arc<LobbyService>, game_service: Arc<GameService>) -> Self {
         Self {
-            online_player_map: Arc::new(Mutex::new(HashMap::new())),
+            online_player_map: Arc::new(DashMap::new()),
             lobby_service,
+            broadcast_service: game_service.get_broadcast_service(),
             game_service,
+            lag_warning_ms: AtomicU32::new(0),
+            kick_after_violations: AtomicU32::new(0),
+            session_policy: Mutex::new(SessionPolicy::Allow),
+            opening: Mutex::new(()),
+            profile_service: None,
+            block_service: None,
+            cluster_service: Arc::new(ClusterService::local(0)),
         }
     }
 
-    pub fn get_player(&self, client_id: u32) -> Option<Arc<Player>> {
-        match self.online_player_map.lock().unwrap().get(&client_id) {
-            Some(player) => Some(player.clone()),
-            None => None,
+    pub fn with_lag_warning(self, lag_warning_ms: u32) -> Self {
+        self.set_lag_warning(lag_warning_ms);
+        self
+    }
+
+    pub fn with_kick_after_violations(self, kick_after_violations: u32) -> Self {
+        self.set_kick_after_violations(kick_after_violations);
+        self
+    }
+
+    pub fn with_session_policy(self, session_policy: SessionPolicy) -> Self {
+        self.set_session_policy(session_policy);
+        self
+    }
+
+    pub fn set_lag_warning(&self, lag_warning_ms: u32) {
+        self.lag_warning_ms.store(lag_warning_ms, Ordering::Relaxed);
+    }
+
+    pub fn set_kick_after_violations(&self, kick_after_violations: u32) {
+        self.kick_after_violations
+            .store(kick_after_violations, Ordering::Relaxed);
+    }
+
+    pub fn set_session_policy(&self, session_policy: SessionPolicy) {
+        *self.session_policy.lock().unwrap() = session_policy;
+    }
+
+    pub fn with_profile_service(mut self, profile_service: Arc<ProfileService>) -> Self {
+        self.profile_service = Some(profile_service);
+        self
+    }
+
+    pub fn with_block_service(mut self, block_service: Arc<BlockService>) -> Self {
+        self.block_service = Some(block_service);
+        self
+    }
+
+    pub fn with_cluster_service(mut self, cluster_service: Arc<ClusterService>) -> Self {
+        self.cluster_service = cluster_service;
+        self
+    }
+
+    /// Log an invalid move `player` sent, and kick them once they have sent
+    /// too many.
+    pub fn record_violation(&self, player: Arc<Player>, violation: MoveViolation) {
+        let violations = player.record_violation();
+        tracing::warn!(
+            player_id = player.id,
+            name = player.name,
+            %violation,
+            violations,
+            "invalid move"
+        );
+        let kick_after_violations = self.kick_after_violations.load(Ordering::Relaxed);
+        if kick_after_violations == 0 || violations < kick_after_violations {
+            return;
+        }
+        if let Err(e) = self.kick(player, "Kicked for sending invalid moves") {
+            tracing::error!(error = %e, "failed to kick player");
         }
     }
 
-    pub fn add_player(
+    /// Close the player's connection with `reason` as the last thing they
+    /// are sent, and clean up after them.
+    pub fn kick(&self, player: Arc<Player>, reason: &str) -> Result<Arc<Player>, ServerError> {
+        self.close(
+            player,
+            State::Notice,
+            Notice {
+                message: reason.to_string(),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Close the player's connection after telling them to connect to
+    /// `address` instead, where their lobby went.
+    pub fn redirect(
+        &self,
+        player: Arc<Player>,
+        address: String,
+    ) -> Result<Arc<Player>, ServerError> {
+        self.close(
+            player,
+            State::Moved,
+            Notice {
+                message: String::from("Your lobby moved to another server"),
+                maintenance: false,
+                redirect: address,
+            },
+        )
+    }
+
+    fn close(
+        &self,
+        player: Arc<Player>,
+        state: State,
+        notice: Notice,
+    ) -> Result<Arc<Player>, ServerError> {
+        // Closing takes the player off the broadcast list first, so nothing
+        // sent while they are cleaned up can land after the close
+        self.broadcast_service.close(
+            player.id,
+            Some(Response::event(
+                state,
+                Arc::new(ResponseData::Notice(notice)),
+            )),
+        );
+        self.remove_player(player)
+    }
+
+    /// Bring `name` online as `client_id`, after settling any session
+    /// already online under the name as the session policy says.
+    pub fn open_session(
         &self,
         client_id: u32,
         name: String,
         #[cfg(not(test))] sender: Sender<Frame>,
-    ) -> Arc<Player> {
-        let player = Arc::new(Player::new(
+    ) -> Result<Arc<Player>, ServerError> {
+        let session_policy = *self.session_policy.lock().unwrap();
+        let _opening = match session_policy {
+            SessionPolicy::Allow => None,
+            _ => Some(self.opening.lock().unwrap()),
+        };
+        let existing = self
+            .get_players()
+            .into_iter()
+            .filter(|player| player.name == name);
+        match session_policy {
+            SessionPolicy::Reject => {
+                if existing.count() > 0 {
+                    return Err(ServerError::AlreadyConnected);
+                }
+            }
+            SessionPolicy::Replace => {
+                for player in existing {
+                    tracing::info!(player_id = player.id, name, "replace session");
+                    self.close(
+                        player,
+                        State::SessionReplaced,
+                        Notice {
+                            message: String::from("Connected from another session"),
+                            ..Default::default()
+                        },
+                    )?;
+                }
+            }
+            SessionPolicy::Allow => (),
+        }
+        Ok(self.add_player(
             client_id,
             name,
             #[cfg(not(test))]
             sender,
-        ));
+        ))
+    }
+
+    /// Record a heartbeat round trip `player` timed, and warn whoever shares
+    /// their game or lobby once their connection gets slow.
+    pub fn record_rtt(&self, player: &Arc<Player>, rtt_ms: u32) {
+        if !player.record_rtt(rtt_ms, self.lag_warning_ms.load(Ordering::Relaxed)) {
+            return;
+        }
+        tracing::debug!(
+            player_id = player.id,
+            average_rtt_ms = player.get_average_rtt(),
+            "connection degraded"
+        );
+        let slow_player = crate::model::player::player::Player::from(player.clone());
+        if let Some(game) = player.get_game() {
+            self.broadcast_service.send_to_game(
+                &game,
+                Response::event(
+                    State::GameBroadcast,
+                    Arc::new(ResponseData::GameBroadcast(GameBroadcast {
+                        event: GameEvent::LagWarning as i32,
+                        player: Some(slow_player),
+                        ..Default::default()
+                    })),
+                ),
+                None,
+            );
+        } else if let Some(lobby) = player.get_lobby() {
+            self.broadcast_service.send_to_lobby(
+                &lobby,
+                Response::event(
+                    State::LobbyBroadcast,
+                    Arc::new(ResponseData::LobbyBroadcast(LobbyBroadcast {
+                        event: LobbyEvent::LagWarning as i32,
+                        lobby: Some(crate::model::lobby::lobby::Lobby::from(lobby.clone())),
+                        player: Some(slow_player),
+                        ..Default::default()
+                    })),
+                ),
+                None,
+            );
+        }
+    }
+
+    pub fn get_player(&self, client_id: u32) -> Option<Arc<Player>> {
         self.online_player_map
-            .lock()
-            .unwrap()
-            .insert(client_id, player.clone());
+            .get(&client_id)
+            .map(|player| player.clone())
+    }
+
+    pub fn add_player(
+        &self,
+        client_id: u32,
+        name: String,
+        #[cfg(not(test))] sender: Sender<Frame>,
+    ) -> Arc<Player> {
+        let player = Arc::new(Player::new(client_id, name));
+        if let Some(profile_service) = &self.profile_service {
+            match profile_service.get_profile(&player.name) {
+                Ok(profile) => player.set_profile(profile),
+                Err(e) => tracing::warn!(error = %e, "failed to load profile"),
+            }
+        }
+        if let Some(block_service) = &self.block_service {
+            match block_service.get_blocked(&player.name) {
+                Ok(blocked) => blocked
+                    .iter()
+                    .for_each(|name| player.set_blocked(name, true)),
+                Err(e) => tracing::warn!(error = %e, "failed to load blocked players"),
+            }
+        }
+        #[cfg(not(test))]
+        self.broadcast_service.register(client_id, sender);
+        self.online_player_map.insert(client_id, player.clone());
+        self.cluster_service.set_online(&player.name);
+        self.game_service.claim_seat(player.clone());
+        self.lobby_service.claim_handoff(player.clone());
         player
     }
 
+    pub fn get_broadcast_service(&self) -> Arc<BroadcastService> {
+        self.broadcast_service.clone()
+    }
+
+    pub fn get_cluster_service(&self) -> Arc<ClusterService> {
+        self.cluster_service.clone()
+    }
+
     pub fn get_players(&self) -> Vec<Arc<Player>> {
         self.online_player_map
-            .lock()
-            .unwrap()
             .iter()
-            .map(|x| x.1.clone())
+            .map(|x| x.value().clone())
             .collect()
     }
 
-    pub fn remove_player(
-        &self,
-        player: Arc<Player>,
-    ) -> Result<Arc<Player>, Box<dyn Error + Send + Sync>> {
-        match self.online_player_map.lock().unwrap().remove(&player.id) {
-            Some(player) => {
+    pub fn remove_player(&self, player: Arc<Player>) -> Result<Arc<Player>, ServerError> {
+        // The shard guard is dropped by `remove`, so the lobby and game
+        // cleanup below never runs while holding it
+        match self.online_player_map.remove(&player.id) {
+            Some((_, player)) => {
+                self.broadcast_service.unregister(player.id);
+                if !self
+                    .online_player_map
+                    .iter()
+                    .any(|other| other.name == player.name)
+                {
+                    self.cluster_service.set_offline(&player.name);
+                }
+                self.lobby_service.leave_waitlist(&player);
+                if let Some(game) = player.get_spectating() {
+                    game.remove_spectator(player.id);
+                }
+                if let Some(game) = player.get_subscribed() {
+                    game.remove_subscriber(player.id);
+                }
                 if player.clone().get_lobby().is_some() {
-                    println!("cleaning: remove player from lobby");
+                    tracing::debug!("cleaning: remove player from lobby");
                     self.lobby_service
                         .remove_player_from_lobby(player.clone())?;
                 };
+                // The seat waits for them to come back, until the game's
+                // disconnect grace runs out
                 if player.clone().get_game().is_some() {
-                    println!("cleaning: remove player from game");
-                    self.game_service.remove_player_from_game(player.clone())?;
+                    tracing::debug!("cleaning: release player's seat in game");
+                    GameService::release_seat(self.game_service.clone(), &player)?;
                 };
                 Ok(player)
             }
-            None => Err("Player not found".into()),
+            None => Err(ServerError::PlayerNotFound),
         }
     }
 }
@@ -92,6 +366,7 @@ mod tests {
     use std::{collections::HashSet, error::Error};
 
     use super::*;
+    use crate::{frame::Frame, model::control::connect::Capability};
 
     #[tokio::test]
     async fn add_player_with_test_user_online_player_map_should_include_test_user(
@@ -101,7 +376,7 @@ mod tests {
             Arc::new(GameService::new(HashSet::new())),
         );
         service.add_player(0, String::from("test"));
-        assert!(service.online_player_map.lock().unwrap().get(&0).is_some());
+        assert!(service.online_player_map.get(&0).is_some());
         Ok(())
     }
 
@@ -114,13 +389,86 @@ mod tests {
         );
         service
             .online_player_map
-            .lock()
-            .unwrap()
             .insert(0, Arc::new(Player::new(0, String::from("test"))));
         assert_eq!(service.get_players().len(), 1);
         Ok(())
     }
 
+    #[tokio::test]
+    async fn open_session_with_reject_policy_should_refuse_online_name() {
+        let service = PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        )
+        .with_session_policy(SessionPolicy::Reject);
+        assert!(service.open_session(0, String::from("test")).is_ok());
+        assert!(matches!(
+            service.open_session(1, String::from("test")),
+            Err(ServerError::AlreadyConnected)
+        ));
+        assert!(service.open_session(2, String::from("test1")).is_ok());
+        service.set_session_policy(SessionPolicy::Allow);
+        assert!(service.open_session(3, String::from("test")).is_ok());
+        assert_eq!(service.get_players().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn open_session_with_replace_policy_should_close_old_session(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = PlayerService::new(
+            Arc::new(LobbyService::new()),
+            Arc::new(GameService::new(HashSet::new())),
+        )
+        .with_session_policy(SessionPolicy::Replace);
+        let old = service.open_session(0, String::from("test"))?;
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        service.broadcast_service.register(old.id, tx);
+        service.open_session(1, String::from("test"))?;
+        match rx.recv().await {
+            Some(Frame::Response(res)) => {
+                assert_eq!(res.get_state(), State::SessionReplaced as u32)
+            }
+            _ => panic!("invalid frame"),
+        }
+        assert!(matches!(rx.recv().await, Some(Frame::Close)));
+        assert!(service.get_player(0).is_none());
+        assert!(service.get_player(1).is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn record_rtt_over_lag_warning_should_warn_lobby_once(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lobby_service = Arc::new(LobbyService::new());
+        let service = PlayerService::new(
+            lobby_service.clone(),
+            Arc::new(GameService::new(HashSet::new())),
+        )
+        .with_lag_warning(200);
+        let leader = service.add_player(0, String::from("test"));
+        lobby_service.create_lobby(leader.clone(), 4)?;
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        service.broadcast_service.register(leader.id, tx);
+        service
+            .broadcast_service
+            .set_capabilities(leader.id, Capability::LatencyEvents as u64);
+        service.record_rtt(&leader, 100);
+        service.record_rtt(&leader, 400);
+        service.record_rtt(&leader, 400);
+        match rx.recv().await {
+            Some(Frame::Response(res)) => match res.get_data().as_ref() {
+                ResponseData::LobbyBroadcast(broadcast) => {
+                    assert_eq!(broadcast.event(), LobbyEvent::LagWarning);
+                    assert_eq!(broadcast.player.as_ref().unwrap().ping_ms, Some(250));
+                }
+                _ => panic!("invalid response"),
+            },
+            _ => panic!("invalid frame"),
+        }
+        assert!(rx.try_recv().is_err());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn remove_player_with_a_player_in_lobby_should_remove_player_from_lobby(
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -138,7 +486,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn remove_player_with_a_player_in_game_should_remove_player_from_game(
+    async fn remove_player_with_a_player_in_game_should_hold_their_seat(
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         let game_service = Arc::new(GameService::new(HashSet::new()));
         let lobby_service = Arc::new(LobbyService::new());
@@ -148,8 +496,13 @@ mod tests {
         lobby.get_player(player.id).unwrap().set_ready(true);
         let game = GameService::start_game(game_service, player.clone(), lobby)?;
         service.remove_player(player.clone())?;
-        assert_eq!(game.get_players().len(), 0);
-        assert!(player.get_game().is_none());
+        assert_eq!(game.get_players().len(), 1);
+        assert_eq!(game.get_vacant_seats().len(), 1);
+
+        let player = service.add_player(1, String::from("test"));
+        assert_eq!(player.get_game(), Some(game.clone()));
+        assert!(game.get_vacant_seats().is_empty());
+        game.cancel_timeout_task();
         Ok(())
     }
 
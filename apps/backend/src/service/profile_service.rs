@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use tracing::debug;
+
+use crate::{
+    error::ServerError,
+    model::player::profile::{TileColor, Title},
+    storage::Storage,
+};
+
+use super::stats_service::{PlayerStats, StatsService};
+
+/// Avatars clients ship, numbered from 0.
+pub const AVATAR_COUNT: u32 = 32;
+
+/// How a player shows up to others, kept with their account.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub avatar_id: u32,
+    pub tile_color: TileColor,
+    pub title: Title,
+}
+
+/// Titles `stats` has earned, `NoTitle` first since anyone may pick it.
+pub fn earned_titles(stats: &PlayerStats) -> Vec<Title> {
+    [
+        (Title::NoTitle, true),
+        (Title::Winner, stats.games_won >= 1),
+        (Title::Veteran, stats.games_played >= 50),
+        (Title::Champion, stats.games_won >= 25),
+        (
+            Title::Wordsmith,
+            stats
+                .best_word
+                .as_ref()
+                .is_some_and(|(_, score)| *score >= 50),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, earned)| *earned)
+    .map(|(title, _)| title)
+    .collect()
+}
+
+#[derive(Debug)]
+pub struct ProfileService {
+    storage: Arc<dyn Storage>,
+    stats_service: Arc<StatsService>,
+}
+
+impl ProfileService {
+    pub fn new(storage: Arc<dyn Storage>, stats_service: Arc<StatsService>) -> Self {
+        Self {
+            storage,
+            stats_service,
+        }
+    }
+
+    pub fn get_profile(&self, account: &str) -> Result<Profile, ServerError> {
+        Ok(self.storage.load_profile(account)?.unwrap_or_default())
+    }
+
+    /// Replace `account`'s profile, as long as the avatar exists and the
+    /// title was earned.
+    pub fn update_profile(&self, account: &str, profile: Profile) -> Result<Profile, ServerError> {
+        if profile.avatar_id >= AVATAR_COUNT {
+            return Err("Invalid avatar".into());
+        }
+        let stats = self.stats_service.get_stats(account)?;
+        if !earned_titles(&stats).contains(&profile.title) {
+            return Err("Title not earned".into());
+        }
+        self.storage.save_profile(account, &profile)?;
+        debug!(account, ?profile, "update profile");
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::storage::memory::MemoryStorage;
+
+    use super::*;
+
+    #[test]
+    fn update_profile_should_only_accept_earned_titles() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let storage = Arc::new(MemoryStorage::new());
+        let service = ProfileService::new(
+            storage.clone(),
+            Arc::new(StatsService::new(storage.clone())),
+        );
+        let profile = Profile {
+            avatar_id: 3,
+            tile_color: TileColor::Ocean,
+            title: Title::Winner,
+        };
+        assert!(service.update_profile("test", profile).is_err());
+        assert_eq!(service.get_profile("test")?, Profile::default());
+        storage.save_stats(
+            "test",
+            &PlayerStats {
+                games_played: 1,
+                games_won: 1,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(service.update_profile("test", profile)?, profile);
+        assert_eq!(service.get_profile("test")?, profile);
+        assert!(service
+            .update_profile(
+                "test",
+                Profile {
+                    avatar_id: AVATAR_COUNT,
+                    ..profile
+                }
+            )
+            .is_err());
+        Ok(())
+    }
+}
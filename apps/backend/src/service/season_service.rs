@@ -0,0 +1,313 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use tracing::debug;
+
+use crate::{
+    config::SeasonConfig,
+    game::game::{current_day, Game},
+    storage::Storage,
+};
+
+/// Rating an account starts its first season on.
+pub const BASE_RATING: u32 = 1200;
+/// Most a rating moves in one game, split between every opponent.
+const K_FACTOR: f64 = 32.0;
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// One ranked season, numbered from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Season {
+    pub number: u32,
+    /// Days since 1970-01-01 the season runs from, and the day after its
+    /// last.
+    pub start_day: u32,
+    pub end_day: u32,
+}
+
+impl Season {
+    pub fn starts_at(&self) -> u64 {
+        self.start_day as u64 * SECS_PER_DAY
+    }
+
+    pub fn ends_at(&self) -> u64 {
+        self.end_day as u64 * SECS_PER_DAY
+    }
+}
+
+/// What an account earned by where its rating ended a season.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reward {
+    None,
+    /// For playing a rated game in the season at all.
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Reward {
+    fn for_rating(rating: u32) -> Reward {
+        match rating {
+            1500.. => Reward::Gold,
+            1350.. => Reward::Silver,
+            _ => Reward::Bronze,
+        }
+    }
+}
+
+/// Rates finished standard games and keeps each season's ratings on a
+/// leaderboard of its own. An account's first game of a season starts it off
+/// from its last season's rating, pulled back towards `BASE_RATING`.
+#[derive(Debug)]
+pub struct SeasonService {
+    start_day: u32,
+    length_days: u32,
+    carry_over_percent: u32,
+    storage: Arc<dyn Storage>,
+    /// Held across each load and save so two games ending together can't
+    /// drop one another's rating changes.
+    update: Mutex<()>,
+}
+
+impl SeasonService {
+    /// `config` is expected to have been validated.
+    pub fn new(config: &SeasonConfig, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            start_day: config.start_day().unwrap_or_default(),
+            length_days: config.length_days.max(1),
+            carry_over_percent: config.carry_over_percent.min(100),
+            storage,
+            update: Mutex::new(()),
+        }
+    }
+
+    fn board(season: u32) -> String {
+        format!("season-{}", season)
+    }
+
+    pub fn get_season(&self, number: u32) -> Season {
+        let start_day = self.start_day + (number.max(1) - 1) * self.length_days;
+        Season {
+            number: number.max(1),
+            start_day,
+            end_day: start_day + self.length_days,
+        }
+    }
+
+    /// The season running on `day`, `None` before the first one starts.
+    pub fn season_on(&self, day: u32) -> Option<Season> {
+        let days_in = day.checked_sub(self.start_day)?;
+        Some(self.get_season(days_in / self.length_days + 1))
+    }
+
+    pub fn current_season(&self) -> Option<Season> {
+        self.season_on(current_day())
+    }
+
+    /// Where `account` stands in `season`, or would start from if it hasn't
+    /// played in it yet.
+    pub fn get_rating(
+        &self,
+        season: u32,
+        account: &str,
+    ) -> Result<u32, Box<dyn Error + Send + Sync>> {
+        if let Some(rating) = self.find_rating(season, account)? {
+            return Ok(rating);
+        }
+        let previous = match season {
+            0 | 1 => None,
+            season => self.find_rating(season - 1, account)?,
+        };
+        Ok(match previous {
+            // Soft reset, keep only part of the distance from the base
+            Some(rating) => {
+                let distance = rating as i64 - BASE_RATING as i64;
+                (BASE_RATING as i64 + distance * self.carry_over_percent as i64 / 100) as u32
+            }
+            None => BASE_RATING,
+        })
+    }
+
+    fn find_rating(
+        &self,
+        season: u32,
+        account: &str,
+    ) -> Result<Option<u32>, Box<dyn Error + Send + Sync>> {
+        Ok(self
+            .storage
+            .load_leaderboard(&SeasonService::board(season))?
+            .into_iter()
+            .find(|(name, _)| name == account)
+            .map(|(_, rating)| rating))
+    }
+
+    /// Ratings of everyone who played a rated game in `season`, best first.
+    pub fn get_standings(
+        &self,
+        season: u32,
+    ) -> Result<Vec<(String, u32)>, Box<dyn Error + Send + Sync>> {
+        let mut ratings = self
+            .storage
+            .load_leaderboard(&SeasonService::board(season))?;
+        ratings.sort_by(|(a_name, a_rating), (b_name, b_rating)| {
+            b_rating.cmp(a_rating).then_with(|| a_name.cmp(b_name))
+        });
+        Ok(ratings)
+    }
+
+    pub fn get_reward(
+        &self,
+        season: u32,
+        account: &str,
+    ) -> Result<Reward, Box<dyn Error + Send + Sync>> {
+        Ok(self
+            .find_rating(season, account)?
+            .map_or(Reward::None, Reward::for_rating))
+    }
+
+    /// Rate a finished game in the current season. Players still in it are
+    /// placed by score, ahead of everyone who withdrew.
+    pub fn record_game(&self, game: &Game) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let season = match self.current_season() {
+            Some(season) => season.number,
+            None => return Ok(()),
+        };
+        let drawn = game.get_draw_status().is_agreed();
+        let mut placings = game
+            .get_players()
+            .into_iter()
+            .map(|game_player| {
+                let score = match drawn {
+                    true => 0,
+                    false => game_player.get_tally().score,
+                };
+                (game_player.player.name.clone(), Some(score))
+            })
+            .collect::<Vec<_>>();
+        placings.extend(
+            game.get_withdrawn()
+                .into_iter()
+                .map(|(game_player, _)| (game_player.player.name.clone(), None)),
+        );
+        if placings.len() < 2 {
+            return Ok(());
+        }
+        let _update = self.update.lock().unwrap();
+        let entries = placings
+            .into_iter()
+            .map(|(account, score)| Ok((self.get_rating(season, &account)?, score, account)))
+            .collect::<Result<Vec<_>, Box<dyn Error + Send + Sync>>>()?;
+        let ratings = rate(
+            &entries
+                .iter()
+                .map(|(rating, score, _)| (*rating, *score))
+                .collect::<Vec<_>>(),
+        );
+        let board = SeasonService::board(season);
+        for ((_, _, account), rating) in entries.iter().zip(ratings) {
+            self.storage
+                .save_leaderboard_score(&board, account, rating)?;
+        }
+        debug!(game_id = game.id, season, "rate game");
+        Ok(())
+    }
+}
+
+/// New ratings after a game, for players given as their rating and score,
+/// `None` for those who withdrew. Every pair of players counts as a game of
+/// its own, worth a share of `K_FACTOR`.
+fn rate(players: &[(u32, Option<u32>)]) -> Vec<u32> {
+    let weight = K_FACTOR / (players.len().max(2) - 1) as f64;
+    players
+        .iter()
+        .enumerate()
+        .map(|(i, (rating, score))| {
+            let change = players
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, (other_rating, other_score))| {
+                    let expected =
+                        1.0 / (1.0 + 10f64.powf((*other_rating as f64 - *rating as f64) / 400.0));
+                    let actual = match score.cmp(other_score) {
+                        Ordering::Greater => 1.0,
+                        Ordering::Equal => 0.5,
+                        Ordering::Less => 0.0,
+                    };
+                    weight * (actual - expected)
+                })
+                .sum::<f64>();
+            (*rating as f64 + change).round().max(0.0) as u32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::memory::MemoryStorage;
+
+    use super::*;
+
+    fn new_service() -> SeasonService {
+        SeasonService::new(
+            &SeasonConfig {
+                start: String::from("2024-01-01"),
+                length_days: 10,
+                carry_over_percent: 50,
+            },
+            Arc::new(MemoryStorage::new()),
+        )
+    }
+
+    #[test]
+    fn season_on_should_roll_over_after_length() {
+        let service = new_service();
+        assert_eq!(service.season_on(19_722), None);
+        let first = service.season_on(19_723).unwrap();
+        assert_eq!(
+            (first.number, first.start_day, first.end_day),
+            (1, 19_723, 19_733)
+        );
+        assert_eq!(service.season_on(19_732).unwrap().number, 1);
+        let second = service.season_on(19_733).unwrap();
+        assert_eq!(
+            (second.number, second.starts_at()),
+            (2, 19_733 * SECS_PER_DAY)
+        );
+    }
+
+    #[test]
+    fn rate_should_move_winner_up_and_withdrawn_down() {
+        assert_eq!(
+            rate(&[(1200, Some(30)), (1200, Some(10))]),
+            vec![1216, 1184]
+        );
+        assert_eq!(rate(&[(1200, Some(0)), (1200, Some(0))]), vec![1200, 1200]);
+        let ratings = rate(&[(1200, Some(5)), (1200, Some(20)), (1300, None)]);
+        assert!(ratings[1] > ratings[0] && ratings[0] > 1200);
+        assert!(ratings[2] < 1300);
+    }
+
+    #[test]
+    fn get_rating_in_new_season_should_soft_reset() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = new_service();
+        service
+            .storage
+            .save_leaderboard_score(&SeasonService::board(1), "test", 1400)?;
+        service
+            .storage
+            .save_leaderboard_score(&SeasonService::board(1), "test1", 1100)?;
+        assert_eq!(service.get_rating(1, "test")?, 1400);
+        assert_eq!(service.get_rating(2, "test")?, 1300);
+        assert_eq!(service.get_rating(2, "test1")?, 1150);
+        assert_eq!(service.get_rating(3, "test")?, BASE_RATING);
+        assert!(service.get_standings(2)?.is_empty());
+        assert_eq!(service.get_reward(1, "test")?, Reward::Silver);
+        assert_eq!(service.get_reward(1, "test1")?, Reward::Bronze);
+        assert_eq!(service.get_reward(2, "test")?, Reward::None);
+        Ok(())
+    }
+}
@@ -0,0 +1,388 @@
+use std::{
+    error::Error,
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{
+    game::{
+        game::{Game, Withdrawal},
+        game_player::Tally,
+    },
+    storage::Storage,
+};
+
+/// Totals over every game an account has finished.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerStats {
+    pub games_played: u32,
+    pub games_won: u32,
+    /// Games the account gave up, counted among those played and lost.
+    pub games_resigned: u32,
+    pub total_score: u64,
+    pub tiles_placed: u64,
+    /// Highest scoring word, with its score.
+    pub best_word: Option<(String, u32)>,
+    pub longest_word: Option<String>,
+}
+
+impl PlayerStats {
+    pub fn win_rate(&self) -> f32 {
+        match self.games_played {
+            0 => 0.0,
+            played => self.games_won as f32 / played as f32,
+        }
+    }
+
+    pub fn average_score(&self) -> f32 {
+        match self.games_played {
+            0 => 0.0,
+            played => self.total_score as f32 / played as f32,
+        }
+    }
+
+    fn add_game(&mut self, tally: &Tally, won: bool) {
+        self.games_played += 1;
+        self.games_won += won as u32;
+        self.total_score += tally.score as u64;
+        self.tiles_placed += tally.tiles_placed as u64;
+        if let Some((word, score)) = &tally.best_word {
+            if self.best_word.as_ref().is_none_or(|(_, best)| score > best) {
+                self.best_word = Some((word.clone(), *score));
+            }
+        }
+        if let Some(word) = &tally.longest_word {
+            if self
+                .longest_word
+                .as_ref()
+                .is_none_or(|longest| word.len() > longest.len())
+            {
+                self.longest_word = Some(word.clone());
+            }
+        }
+    }
+}
+
+/// A finished game as it shows up in the history of everyone who played it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchRecord {
+    pub game_id: u32,
+    /// Seconds since the Unix epoch.
+    pub finished_at: u64,
+    /// Final score of each account still in the game, best first.
+    pub scores: Vec<(String, u32)>,
+    /// Score each account that forfeited had when it did, in the order they
+    /// went out. They never win.
+    pub forfeited: Vec<(String, u32)>,
+    /// Same as `forfeited`, for accounts that resigned.
+    pub resigned: Vec<(String, u32)>,
+    /// Ended early by agreement, which nobody wins.
+    pub drawn: bool,
+}
+
+impl MatchRecord {
+    pub fn played_by(&self, account: &str) -> bool {
+        self.scores
+            .iter()
+            .chain(&self.forfeited)
+            .chain(&self.resigned)
+            .any(|(name, _)| name == account)
+    }
+
+    pub fn winners(&self) -> impl Iterator<Item = &str> {
+        let high_score = match self.drawn {
+            true => None,
+            false => self.scores.first().map(|(_, score)| *score),
+        };
+        self.scores
+            .iter()
+            .take_while(move |(_, score)| Some(*score) == high_score)
+            .map(|(account, _)| account.as_str())
+    }
+}
+
+/// Where a page of match history ends, so the next page starts with the game
+/// finished right before it. Clients get it as an opaque string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MatchCursor {
+    finished_at: u64,
+    game_id: u32,
+}
+
+impl MatchCursor {
+    pub fn of(record: &MatchRecord) -> Self {
+        Self {
+            finished_at: record.finished_at,
+            game_id: record.game_id,
+        }
+    }
+}
+
+impl fmt::Display for MatchCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.finished_at, self.game_id)
+    }
+}
+
+impl FromStr for MatchCursor {
+    type Err = Box<dyn Error + Send + Sync>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (finished_at, game_id) = s.split_once('.').ok_or("Invalid cursor")?;
+        Ok(Self {
+            finished_at: finished_at.parse().map_err(|_| "Invalid cursor")?,
+            game_id: game_id.parse().map_err(|_| "Invalid cursor")?,
+        })
+    }
+}
+
+/// Which of an account's games `get_history` returns, all of them by
+/// default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryFilter {
+    /// Only games older than the last one of the previous page.
+    pub before: Option<MatchCursor>,
+    /// Only games finished at or after this, in seconds since the Unix epoch.
+    pub since: Option<u64>,
+    /// Only games finished before this, in seconds since the Unix epoch.
+    pub until: Option<u64>,
+    /// Only games this account played in too.
+    pub opponent: Option<String>,
+}
+
+impl HistoryFilter {
+    fn matches(&self, record: &MatchRecord) -> bool {
+        self.before
+            .is_none_or(|before| MatchCursor::of(record) < before)
+            && self.since.is_none_or(|since| record.finished_at >= since)
+            && self.until.is_none_or(|until| record.finished_at < until)
+            && self
+                .opponent
+                .as_ref()
+                .is_none_or(|opponent| record.played_by(opponent))
+    }
+}
+
+#[derive(Debug)]
+pub struct StatsService {
+    storage: Arc<dyn Storage>,
+    /// Held across each load and save so two games ending together can't
+    /// drop one another's update.
+    update: Mutex<()>,
+}
+
+impl StatsService {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            update: Mutex::new(()),
+        }
+    }
+
+    /// Add a finished game to the stats of everyone who played it. Ties for
+    /// the highest score among those still in it all count as wins, unless
+    /// the game was drawn.
+    pub fn record_game(&self, game: &Game) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let tallies = game
+            .get_players()
+            .into_iter()
+            .map(|game_player| (game_player.player.name.clone(), game_player.get_tally()))
+            .collect::<Vec<_>>();
+        let drawn = game.get_draw_status().is_agreed();
+        let high_score = match drawn {
+            true => None,
+            false => tallies.iter().map(|(_, tally)| tally.score).max(),
+        };
+        let mut scores = tallies
+            .iter()
+            .map(|(account, tally)| (account.clone(), tally.score))
+            .collect::<Vec<_>>();
+        scores.sort_by(|(a_name, a_score), (b_name, b_score)| {
+            b_score.cmp(a_score).then_with(|| a_name.cmp(b_name))
+        });
+        let withdrawals = game
+            .get_withdrawn()
+            .into_iter()
+            .map(|(game_player, withdrawal)| {
+                (
+                    game_player.player.name.clone(),
+                    game_player.get_tally(),
+                    withdrawal,
+                )
+            })
+            .collect::<Vec<_>>();
+        let withdrawn = |kind: Withdrawal| {
+            withdrawals
+                .iter()
+                .filter(|(_, _, withdrawal)| *withdrawal == kind)
+                .map(|(account, tally, _)| (account.clone(), tally.score))
+                .collect::<Vec<_>>()
+        };
+        let forfeited = withdrawn(Withdrawal::Forfeited);
+        let resigned = withdrawn(Withdrawal::Resigned);
+        let _update = self.update.lock().unwrap();
+        for (account, tally) in tallies {
+            let mut stats = self.storage.load_stats(&account)?.unwrap_or_default();
+            stats.add_game(&tally, Some(tally.score) == high_score);
+            self.storage.save_stats(&account, &stats)?;
+        }
+        for (account, tally, withdrawal) in withdrawals {
+            let mut stats = self.storage.load_stats(&account)?.unwrap_or_default();
+            stats.add_game(&tally, false);
+            stats.games_resigned += (withdrawal == Withdrawal::Resigned) as u32;
+            self.storage.save_stats(&account, &stats)?;
+        }
+        self.storage.save_match(&MatchRecord {
+            game_id: game.id,
+            finished_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            scores,
+            forfeited,
+            resigned,
+            drawn,
+        })?;
+        debug!(game_id = game.id, "record game stats");
+        Ok(())
+    }
+
+    pub fn get_stats(&self, account: &str) -> Result<PlayerStats, Box<dyn Error + Send + Sync>> {
+        Ok(self.storage.load_stats(account)?.unwrap_or_default())
+    }
+
+    /// The last `limit` games `account` finished that pass `filter`, newest
+    /// first. Games finished in the same second go by id.
+    pub fn get_history(
+        &self,
+        account: &str,
+        filter: &HistoryFilter,
+        limit: usize,
+    ) -> Result<Vec<MatchRecord>, Box<dyn Error + Send + Sync>> {
+        let mut matches = self.storage.load_matches(account)?;
+        matches.retain(|record| filter.matches(record));
+        matches.sort_by_key(|record| std::cmp::Reverse(MatchCursor::of(record)));
+        matches.truncate(limit);
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{player::Player, storage::memory::MemoryStorage};
+
+    use super::*;
+
+    #[test]
+    fn record_game_should_count_highest_score_as_win() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = StatsService::new(Arc::new(MemoryStorage::new()));
+        let game = Game::new(
+            0,
+            vec![
+                Arc::new(Player::new(0, String::from("test"))),
+                Arc::new(Player::new(1, String::from("test1"))),
+            ],
+        );
+        game.get_player(0)
+            .unwrap()
+            .record_turn(&[(String::from("quiz"), 22), (String::from("it"), 2)], 4);
+        game.get_player(1)
+            .unwrap()
+            .record_turn(&[(String::from("the"), 6)], 3);
+        service.record_game(&game)?;
+        service.record_game(&game)?;
+        let stats = service.get_stats("test")?;
+        assert_eq!(stats.games_played, 2);
+        assert_eq!(stats.win_rate(), 1.0);
+        assert_eq!(stats.average_score(), 24.0);
+        assert_eq!(stats.tiles_placed, 8);
+        assert_eq!(stats.best_word, Some((String::from("quiz"), 22)));
+        assert_eq!(service.get_stats("test1")?.win_rate(), 0.0);
+        assert_eq!(service.get_stats("nobody")?, PlayerStats::default());
+        Ok(())
+    }
+
+    #[test]
+    fn get_history_should_return_newest_games_first() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let service = StatsService::new(Arc::new(MemoryStorage::new()));
+        for id in 0..3 {
+            let game = Game::new(
+                id,
+                vec![
+                    Arc::new(Player::new(0, String::from("test"))),
+                    Arc::new(Player::new(1, String::from("test1"))),
+                ],
+            );
+            game.get_player(1)
+                .unwrap()
+                .record_turn(&[(String::from("the"), 6)], 3);
+            service.record_game(&game)?;
+        }
+        let history = service.get_history("test", &HistoryFilter::default(), 2)?;
+        assert_eq!(
+            history
+                .iter()
+                .map(|record| record.game_id)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(
+            history[0].scores,
+            vec![(String::from("test1"), 6), (String::from("test"), 0)]
+        );
+        assert_eq!(history[0].winners().collect::<Vec<_>>(), vec!["test1"]);
+        assert!(service
+            .get_history("nobody", &HistoryFilter::default(), 2)?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn get_history_with_filter_should_page_through_matching_games(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let storage = Arc::new(MemoryStorage::new());
+        let service = StatsService::new(storage.clone());
+        for (game_id, finished_at, opponent) in [
+            (0, 100, "test1"),
+            (1, 200, "test2"),
+            (2, 200, "test1"),
+            (3, 300, "test1"),
+        ] {
+            storage.save_match(&MatchRecord {
+                game_id,
+                finished_at,
+                scores: vec![(String::from("test"), 1), (String::from(opponent), 0)],
+                forfeited: Vec::new(),
+                resigned: Vec::new(),
+                drawn: false,
+            })?;
+        }
+        let ids = |records: &[MatchRecord]| {
+            records
+                .iter()
+                .map(|record| record.game_id)
+                .collect::<Vec<_>>()
+        };
+        let mut filter = HistoryFilter {
+            opponent: Some(String::from("test1")),
+            ..Default::default()
+        };
+        let page = service.get_history("test", &filter, 2)?;
+        assert_eq!(ids(&page), vec![3, 2]);
+        filter.before = Some(MatchCursor::of(&page[1]).to_string().parse()?);
+        assert_eq!(ids(&service.get_history("test", &filter, 2)?), vec![0]);
+        let filter = HistoryFilter {
+            since: Some(200),
+            until: Some(300),
+            ..Default::default()
+        };
+        assert_eq!(ids(&service.get_history("test", &filter, 10)?), vec![2, 1]);
+        assert!("200".parse::<MatchCursor>().is_err());
+        Ok(())
+    }
+}
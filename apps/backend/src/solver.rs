@@ -0,0 +1,502 @@
+//! Move generation: every placement a hand can make on a board, and the
+//! best of them. Hints and game analysis are built on it, and nothing here
+//! needs a running game apart from `find_hint` and `find_best`, which check
+//! candidates the way a finished turn is checked.
+//!
+//! Words come from a `Lexicon`, a trie of the word list, so only letters
+//! some word goes on with are ever tried.
+
+pub mod lexicon;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use rand::seq::SliceRandom;
+
+use crate::{
+    game::{
+        board::{Board, BOARD_SIZE},
+        card::Card,
+        game::Game,
+        rules::GameRules,
+        scoring,
+        tile::Tile,
+    },
+    player::Player,
+};
+
+pub use lexicon::Lexicon;
+use lexicon::NodeId;
+
+/// Candidates checked against the whole board before giving up, so a hint
+/// on a crowded board stays cheap.
+const MAX_CHECKS: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintMode {
+    Best,
+    Random,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub word: String,
+    /// `(x, y, card_index)` of every tile to place, in the coordinates
+    /// clients place tiles with and in an order `check_placement` accepts.
+    pub tiles: Vec<(usize, usize, usize)>,
+    pub score: u32,
+}
+
+fn get_char(board: &Board, x: usize, y: usize) -> Option<char> {
+    board.tiles[BOARD_SIZE - 1 - y][x]
+        .as_ref()
+        .map(|tile| tile.char)
+}
+
+/// One row or column being searched for words.
+struct Line<'a> {
+    board: &'a Board,
+    rules: &'a GameRules,
+    lexicon: &'a Lexicon,
+    hand: &'a HashMap<char, Vec<usize>>,
+    empty: bool,
+    /// `(x, y)` of each cell along the line, in reading order.
+    cells: Vec<(usize, usize)>,
+    /// Whether a word covering the cell joins up with the board, as
+    /// `try_place` requires.
+    anchors: Vec<bool>,
+    across: bool,
+}
+
+impl Line<'_> {
+    /// Follow the lexicon from `node` through the cell at `pos`, keeping
+    /// every word past `anchor` that ends against an empty cell or the edge.
+    #[allow(clippy::too_many_arguments)]
+    fn extend(
+        &self,
+        start: usize,
+        anchor: usize,
+        pos: usize,
+        node: NodeId,
+        letters: &mut Vec<char>,
+        taken: &mut HashMap<char, usize>,
+        found: &mut Vec<Placement>,
+    ) {
+        let next = self
+            .cells
+            .get(pos)
+            .map(|&(x, y)| get_char(self.board, x, y));
+        if pos > anchor && self.lexicon.is_word(node) && !matches!(next, Some(Some(_))) {
+            // A single letter reads the same both ways, so it's only kept
+            // across
+            if letters.len() > 1 || self.across {
+                let placement = try_place(
+                    self.board,
+                    self.rules,
+                    letters,
+                    &self.cells[start..pos],
+                    self.hand,
+                    self.empty,
+                );
+                found.extend(placement);
+            }
+        }
+        match next {
+            None => (),
+            Some(Some(char)) => {
+                if let Some(child) = self.lexicon.child(node, char) {
+                    letters.push(char);
+                    self.extend(start, anchor, pos + 1, child, letters, taken, found);
+                    letters.pop();
+                }
+            }
+            Some(None) => {
+                for (char, child) in self.lexicon.children(node) {
+                    let count = self.hand.get(&char).map_or(0, |indices| indices.len());
+                    if taken.get(&char).copied().unwrap_or(0) >= count {
+                        continue;
+                    }
+                    *taken.entry(char).or_default() += 1;
+                    letters.push(char);
+                    self.extend(start, anchor, pos + 1, child, letters, taken, found);
+                    letters.pop();
+                    *taken.entry(char).or_default() -= 1;
+                }
+            }
+        }
+    }
+}
+
+/// Every placement of a word in `lexicon` the unused cards can make along
+/// one row or column, best scoring first. Only the word itself is scored,
+/// words it crosses are left to `score_placement` or `Board::validate`.
+pub fn find_placements(
+    board: &Board,
+    rules: &GameRules,
+    cards: &[Card],
+    lexicon: &Lexicon,
+) -> Vec<Placement> {
+    let size = rules.board_size;
+    let mut hand: HashMap<char, Vec<usize>> = HashMap::new();
+    for (index, card) in cards.iter().enumerate() {
+        if !card.used {
+            hand.entry(card.char).or_default().push(index);
+        }
+    }
+    let tiles_in_hand = cards.iter().filter(|card| !card.used).count();
+    let occupied = |x: usize, y: usize| x < size && y < size && get_char(board, x, y).is_some();
+    let empty = (0..size).all(|x| (0..size).all(|y| !occupied(x, y)));
+    let is_anchor = |(x, y): (usize, usize)| {
+        occupied(x, y)
+            || occupied(x + 1, y)
+            || occupied(x, y + 1)
+            || (x > 0 && occupied(x - 1, y))
+            || (y > 0 && occupied(x, y - 1))
+            || (empty && (x, y) == (size / 2, size / 2))
+    };
+
+    // Kept with where they were found, so placements scoring the same come
+    // out in the same order whatever order the lexicon is searched in
+    let mut placements = Vec::new();
+    for line in 0..size {
+        for across in [true, false] {
+            // Across runs left to right, down runs top to bottom, which is
+            // towards smaller y
+            let cells = (0..size)
+                .map(|i| match across {
+                    true => (i, line),
+                    false => (line, size - 1 - i),
+                })
+                .collect::<Vec<_>>();
+            let search = Line {
+                board,
+                rules,
+                lexicon,
+                hand: &hand,
+                empty,
+                anchors: cells.iter().copied().map(is_anchor).collect(),
+                cells,
+                across,
+            };
+            for start in 0..size {
+                // Words start where the run before them ends
+                if start > 0 {
+                    let (x, y) = search.cells[start - 1];
+                    if occupied(x, y) {
+                        continue;
+                    }
+                }
+                // and have to reach an anchor with the tiles in hand. Cells
+                // before the first anchor are all empty
+                let anchor = match (start..size).find(|i| search.anchors[*i]) {
+                    Some(anchor) => anchor,
+                    None => continue,
+                };
+                let (x, y) = search.cells[anchor];
+                if anchor - start + usize::from(!occupied(x, y)) > tiles_in_hand {
+                    continue;
+                }
+                let mut found = Vec::new();
+                search.extend(
+                    start,
+                    anchor,
+                    start,
+                    Lexicon::ROOT,
+                    &mut Vec::new(),
+                    &mut HashMap::new(),
+                    &mut found,
+                );
+                placements.extend(
+                    found
+                        .into_iter()
+                        .map(|placement| ((line, start, !across), placement)),
+                );
+            }
+        }
+    }
+    placements.sort_by(|(a_at, a), (b_at, b)| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.word.cmp(&b.word))
+            .then_with(|| a_at.cmp(b_at))
+    });
+    placements
+        .into_iter()
+        .map(|(_, placement)| placement)
+        .collect()
+}
+
+/// What `placement` scores with the words it crosses counted too, or `None`
+/// if any word it makes isn't in `lexicon`.
+pub fn score_placement(
+    board: &Board,
+    rules: &GameRules,
+    placement: &Placement,
+    cards: &[Card],
+    lexicon: &Lexicon,
+) -> Option<u32> {
+    // The tiles need an owner and a turn of their own, neither is scored
+    let owner = Arc::new(Player::new(0, String::new()));
+    let turn = board
+        .tiles
+        .iter()
+        .flatten()
+        .flatten()
+        .map(|tile| tile.turn + 1)
+        .max()
+        .unwrap_or(1);
+    let played = play(board, placement, owner, cards, turn);
+    let mut total = 0;
+    for (word, score) in scoring::score_turn(&played, turn, rules.scoring) {
+        if !lexicon.contains(&word) {
+            return None;
+        }
+        total += score;
+    }
+    Some(total)
+}
+
+fn try_place(
+    board: &Board,
+    rules: &GameRules,
+    letters: &[char],
+    cells: &[(usize, usize)],
+    hand: &HashMap<char, Vec<usize>>,
+    empty: bool,
+) -> Option<Placement> {
+    let size = rules.board_size;
+    let (first, last) = (cells[0], cells[cells.len() - 1]);
+    let (dx, dy) = match cells.len() {
+        1 => (1, 0),
+        _ => (
+            cells[1].0 as isize - first.0 as isize,
+            cells[1].1 as isize - first.1 as isize,
+        ),
+    };
+    // The word has to end where the run of tiles does
+    let before = (first.0 as isize - dx, first.1 as isize - dy);
+    let after = (last.0 as isize + dx, last.1 as isize + dy);
+    for (x, y) in [before, after] {
+        if (0..size as isize).contains(&x)
+            && (0..size as isize).contains(&y)
+            && get_char(board, x as usize, y as usize).is_some()
+        {
+            return None;
+        }
+    }
+
+    let occupied = |x: usize, y: usize| x < size && y < size && get_char(board, x, y).is_some();
+    let mut taken: HashMap<char, usize> = HashMap::new();
+    let mut tiles = Vec::new();
+    let mut letters_scored = Vec::new();
+    // Positions along the word a tile can go next to straight away
+    let mut anchors = Vec::new();
+    for (i, (&char, &(x, y))) in letters.iter().zip(cells).enumerate() {
+        match get_char(board, x, y) {
+            Some(existing) if existing == char => {
+                anchors.push(i);
+                letters_scored.push((char, None));
+            }
+            Some(_) => return None,
+            None => {
+                let count = taken.entry(char).or_default();
+                let index = *hand.get(&char)?.get(*count)?;
+                *count += 1;
+                tiles.push((i, (x, y, index)));
+                if occupied(x + 1, y)
+                    || occupied(x, y + 1)
+                    || (x > 0 && occupied(x - 1, y))
+                    || (y > 0 && occupied(x, y - 1))
+                    || (empty && (x, y) == (size / 2, size / 2))
+                {
+                    anchors.push(i);
+                }
+                letters_scored.push((char, board.get_premium(x, y)));
+            }
+        }
+    }
+    // The first word goes through the centre, every later one joins up
+    // with what is already there
+    if tiles.is_empty() || anchors.is_empty() {
+        return None;
+    }
+    // Tiles go down one at a time, each next to one already there, so list
+    // them outwards from the anchors
+    tiles.sort_by_key(|(i, _)| anchors.iter().map(|anchor| anchor.abs_diff(*i)).min());
+    let tiles = tiles.into_iter().map(|(_, tile)| tile).collect();
+    Some(Placement {
+        word: letters.iter().collect(),
+        tiles,
+        score: scoring::score_word(rules.scoring, &letters_scored),
+    })
+}
+
+/// Pick a placement that leaves the whole board valid, the best scoring one
+/// or any at random. `dict` holds the same words as `lexicon`.
+pub fn find_hint(
+    game: &Arc<Game>,
+    board: &Board,
+    owner: Arc<Player>,
+    cards: &[Card],
+    lexicon: &Lexicon,
+    dict: &HashSet<String>,
+    mode: HintMode,
+) -> Option<Placement> {
+    let mut placements = find_placements(board, game.get_rules(), cards, lexicon);
+    if mode == HintMode::Random {
+        // Not the game's rng, asking for hints must not change the replay
+        placements.shuffle(&mut rand::thread_rng());
+    }
+    placements.into_iter().take(MAX_CHECKS).find(|placement| {
+        play(board, placement, owner.clone(), cards, game.get_turns())
+            .validate(dict, game.clone())
+            .is_some()
+    })
+}
+
+/// The placement scoring the most once the words it crosses count too, with
+/// what it scores that way.
+pub fn find_best(
+    game: &Arc<Game>,
+    board: &Board,
+    owner: Arc<Player>,
+    cards: &[Card],
+    lexicon: &Lexicon,
+    dict: &HashSet<String>,
+) -> Option<(Placement, u32)> {
+    let turn = game.get_turns();
+    find_placements(board, game.get_rules(), cards, lexicon)
+        .into_iter()
+        .take(MAX_CHECKS)
+        .filter_map(|placement| {
+            let played = play(board, &placement, owner.clone(), cards, turn);
+            played.validate(dict, game.clone())?;
+            let score = scoring::score_turn(&played, turn, game.get_rules().scoring)
+                .iter()
+                .map(|(_, score)| score)
+                .sum();
+            Some((placement, score))
+        })
+        // Ties go to the earlier placement, as `find_hint` would pick it
+        .rev()
+        .max_by_key(|(_, score)| *score)
+}
+
+fn play(
+    board: &Board,
+    placement: &Placement,
+    owner: Arc<Player>,
+    cards: &[Card],
+    turn: u32,
+) -> Board {
+    let mut board = board.clone();
+    for &(x, y, index) in &placement.tiles {
+        board.tiles[BOARD_SIZE - 1 - y][x] =
+            Some(Tile::new(cards[index].char, owner.clone(), turn));
+    }
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(chars: &str) -> Vec<Card> {
+        chars.chars().map(Card::new).collect()
+    }
+
+    fn place(board: &mut Board, owner: Arc<Player>, word: &str, x: usize, y: usize) {
+        for (i, char) in word.chars().enumerate() {
+            board.tiles[BOARD_SIZE - 1 - y][x + i] = Some(Tile::new(char, owner.clone(), 1));
+        }
+    }
+
+    #[test]
+    fn find_placements_on_empty_board_should_cover_centre() {
+        let dict = HashSet::from([String::from("the")]);
+        let rules = GameRules::default();
+        let centre = (rules.board_size / 2, rules.board_size / 2);
+        let placements =
+            find_placements(&Board::new(), &rules, &cards("xeht"), &Lexicon::new(&dict));
+        assert!(!placements.is_empty());
+        for placement in placements {
+            assert_eq!(placement.word, "the");
+            assert_eq!(placement.score, 6);
+            assert!(placement.tiles.iter().any(|&(x, y, _)| (x, y) == centre));
+        }
+    }
+
+    #[test]
+    fn find_hint_with_word_on_board_should_extend_it() {
+        let dict = HashSet::from([String::from("the"), String::from("there")]);
+        let player = Arc::new(Player::new(0, String::from("test")));
+        let game = Arc::new(Game::new(0, vec![player.clone()]));
+        let mut board = Board::new();
+        place(&mut board, player.clone(), "the", 12, 13);
+        let placement = find_hint(
+            &game,
+            &board,
+            player,
+            &cards("re"),
+            &Lexicon::new(&dict),
+            &dict,
+            HintMode::Best,
+        )
+        .unwrap();
+        assert_eq!(placement.word, "there");
+        assert_eq!(placement.tiles, vec![(15, 13, 0), (16, 13, 1)]);
+    }
+
+    #[test]
+    fn find_hint_with_used_cards_only_should_return_none() {
+        let dict = HashSet::from([String::from("the")]);
+        let player = Arc::new(Player::new(0, String::from("test")));
+        let game = Arc::new(Game::new(0, vec![player.clone()]));
+        let mut hand = cards("the");
+        for card in hand.iter_mut() {
+            card.used = true;
+        }
+        let lexicon = Lexicon::new(&dict);
+        assert!(find_hint(
+            &game,
+            &Board::new(),
+            player,
+            &hand,
+            &lexicon,
+            &dict,
+            HintMode::Random
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn score_placement_should_count_crossed_words() {
+        let dict = HashSet::from([String::from("the"), String::from("he"), String::from("eh")]);
+        let lexicon = Lexicon::new(&dict);
+        let player = Arc::new(Player::new(0, String::from("test")));
+        let rules = GameRules::default();
+        let mut board = Board::new();
+        place(&mut board, player, "the", 12, 13);
+        let placements = find_placements(&board, &rules, &cards("h"), &lexicon);
+        let below_e = placements
+            .iter()
+            .find(|placement| placement.word == "eh")
+            .unwrap();
+        assert_eq!(below_e.tiles, vec![(14, 12, 0)]);
+        assert_eq!(
+            score_placement(&board, &rules, below_e, &cards("h"), &lexicon),
+            Some(below_e.score)
+        );
+        let beside_e = Placement {
+            word: String::from("h"),
+            tiles: vec![(15, 12, 0)],
+            score: 0,
+        };
+        assert_eq!(
+            score_placement(&board, &rules, &beside_e, &cards("h"), &lexicon),
+            None
+        );
+    }
+}
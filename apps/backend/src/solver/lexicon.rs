@@ -0,0 +1,286 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+/// Start of every compiled lexicon, with the layout's version last.
+const MAGIC: &[u8; 8] = b"LLDAWG\0\x01";
+
+/// A node of a `Lexicon`, standing for the prefix spelled on the way to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(u32);
+
+/// A trie node while the lexicon is being built.
+#[derive(Debug, Default)]
+struct TrieNode {
+    /// Sorted by letter.
+    children: Vec<(char, usize)>,
+    terminal: bool,
+}
+
+/// A word list as a DAWG, a trie with every identical tail stored once, so
+/// move generation only follows letters some word goes on with instead of
+/// trying every word in every spot. Building one walks the whole list, so
+/// it's built once per list, shared, and can be kept on disk with
+/// `to_bytes`.
+pub struct Lexicon {
+    /// Where each node's edges start in `edges`, with one more entry for
+    /// where the last node's end.
+    starts: Vec<u32>,
+    terminal: Vec<bool>,
+    /// Sorted by letter within each node.
+    edges: Vec<(char, NodeId)>,
+    words: usize,
+}
+
+/// Just the size, a whole dictionary's nodes would swamp any log.
+impl fmt::Debug for Lexicon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lexicon")
+            .field("words", &self.words)
+            .field("nodes", &self.terminal.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Lexicon {
+    pub const ROOT: NodeId = NodeId(0);
+
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut trie = vec![TrieNode::default()];
+        let mut count = 0;
+        for word in words {
+            let word = word.as_ref();
+            if word.is_empty() {
+                continue;
+            }
+            let mut node = 0;
+            for char in word.chars() {
+                let children = &trie[node].children;
+                node = match children.binary_search_by_key(&char, |(other, _)| *other) {
+                    Ok(i) => children[i].1,
+                    Err(at) => {
+                        let child = trie.len();
+                        trie.push(TrieNode::default());
+                        trie[node].children.insert(at, (char, child));
+                        child
+                    }
+                };
+            }
+            if !trie[node].terminal {
+                trie[node].terminal = true;
+                count += 1;
+            }
+        }
+        Lexicon::minimize(&trie, count)
+    }
+
+    /// Merge every set of trie nodes with the same tail into one. Nodes are
+    /// numbered as their tails finish, so the root comes last and is then
+    /// moved to the front.
+    fn minimize(trie: &[TrieNode], words: usize) -> Self {
+        let mut ids = HashMap::<(bool, Vec<(char, NodeId)>), NodeId>::new();
+        let mut merged = vec![NodeId(0); trie.len()];
+        let mut nodes = Vec::<(bool, Vec<(char, NodeId)>)>::new();
+        // Children always come after their parent, so going backwards sees
+        // every tail before the nodes leading into it
+        for (i, node) in trie.iter().enumerate().rev() {
+            let key = (
+                node.terminal,
+                node.children
+                    .iter()
+                    .map(|(char, child)| (*char, merged[*child]))
+                    .collect::<Vec<_>>(),
+            );
+            merged[i] = *ids.entry(key.clone()).or_insert_with(|| {
+                nodes.push(key);
+                NodeId(nodes.len() as u32 - 1)
+            });
+        }
+        let last = nodes.len() as u32 - 1;
+        let flip = |NodeId(id): NodeId| NodeId(last - id);
+        let mut lexicon = Lexicon {
+            starts: Vec::with_capacity(nodes.len() + 1),
+            terminal: Vec::with_capacity(nodes.len()),
+            edges: Vec::new(),
+            words,
+        };
+        for (terminal, children) in nodes.into_iter().rev() {
+            lexicon.starts.push(lexicon.edges.len() as u32);
+            lexicon.terminal.push(terminal);
+            lexicon.edges.extend(
+                children
+                    .into_iter()
+                    .map(|(char, child)| (char, flip(child))),
+            );
+        }
+        lexicon.starts.push(lexicon.edges.len() as u32);
+        lexicon
+    }
+
+    fn edges(&self, NodeId(node): NodeId) -> &[(char, NodeId)] {
+        let node = node as usize;
+        &self.edges[self.starts[node] as usize..self.starts[node + 1] as usize]
+    }
+
+    /// Where `char` leads from `node`, if any word goes on that way.
+    pub fn child(&self, node: NodeId, char: char) -> Option<NodeId> {
+        let edges = self.edges(node);
+        edges
+            .binary_search_by_key(&char, |(other, _)| *other)
+            .ok()
+            .map(|i| edges[i].1)
+    }
+
+    /// Every letter some word goes on with from `node`, in order.
+    pub fn children(&self, node: NodeId) -> impl Iterator<Item = (char, NodeId)> + '_ {
+        self.edges(node).iter().copied()
+    }
+
+    /// Whether the prefix `node` stands for is a word of its own.
+    pub fn is_word(&self, NodeId(node): NodeId) -> bool {
+        self.terminal[node as usize]
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        let mut node = Lexicon::ROOT;
+        for char in word.chars() {
+            node = match self.child(node, char) {
+                Some(child) => child,
+                None => return false,
+            };
+        }
+        !word.is_empty() && self.is_word(node)
+    }
+
+    /// Number of distinct words.
+    pub fn len(&self) -> usize {
+        self.words
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words == 0
+    }
+
+    /// The compiled lexicon, for `from_bytes` to load again without going
+    /// through the word list.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let nodes = self.terminal.len();
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 12 + nodes * 5 + self.edges.len() * 8);
+        bytes.extend_from_slice(MAGIC);
+        for count in [self.words, nodes, self.edges.len()] {
+            bytes.extend_from_slice(&(count as u32).to_le_bytes());
+        }
+        for (start, terminal) in self.starts.iter().zip(&self.terminal) {
+            bytes.extend_from_slice(&start.to_le_bytes());
+            bytes.push(u8::from(*terminal));
+        }
+        for (char, NodeId(child)) in &self.edges {
+            bytes.extend_from_slice(&u32::from(*char).to_le_bytes());
+            bytes.extend_from_slice(&child.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Load a lexicon `to_bytes` wrote, checking it hangs together so a
+    /// damaged file can't make lookups go out of bounds.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut rest = bytes
+            .strip_prefix(MAGIC)
+            .ok_or("Not a compiled lexicon of this version")?;
+        let words = read_u32(&mut rest)? as usize;
+        let nodes = read_u32(&mut rest)? as usize;
+        let edge_count = read_u32(&mut rest)?;
+        // Checked before anything is allocated for them
+        if nodes == 0 || rest.len() != nodes * 5 + edge_count as usize * 8 {
+            return Err("Compiled lexicon has the wrong length".into());
+        }
+        let mut lexicon = Lexicon {
+            starts: Vec::with_capacity(nodes + 1),
+            terminal: Vec::with_capacity(nodes),
+            edges: Vec::with_capacity(edge_count as usize),
+            words,
+        };
+        for _ in 0..nodes {
+            let start = read_u32(&mut rest)?;
+            if lexicon.starts.last().is_some_and(|last| start < *last) || start > edge_count {
+                return Err("Compiled lexicon has edges out of order".into());
+            }
+            lexicon.starts.push(start);
+            let (terminal, tail) = rest.split_first().ok_or("Compiled lexicon is truncated")?;
+            rest = tail;
+            lexicon.terminal.push(*terminal != 0);
+        }
+        lexicon.starts.push(edge_count);
+        for _ in 0..edge_count {
+            let char =
+                char::from_u32(read_u32(&mut rest)?).ok_or("Compiled lexicon has a bad letter")?;
+            let child = read_u32(&mut rest)?;
+            if child as usize >= nodes {
+                return Err("Compiled lexicon has an edge to nowhere".into());
+            }
+            lexicon.edges.push((char, NodeId(child)));
+        }
+        Ok(lexicon)
+    }
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32, Box<dyn Error + Send + Sync>> {
+    let (head, tail) = bytes
+        .split_first_chunk::<4>()
+        .ok_or("Compiled lexicon is truncated")?;
+    *bytes = tail;
+    Ok(u32::from_le_bytes(*head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_should_keep_words_and_share_prefixes() {
+        let lexicon = Lexicon::new(["the", "there", "then", "the", "a", ""]);
+        assert_eq!(lexicon.len(), 4);
+        assert!(lexicon.contains("the") && lexicon.contains("there") && lexicon.contains("a"));
+        assert!(!lexicon.contains("th") && !lexicon.contains("thens") && !lexicon.contains(""));
+        let the = "the"
+            .chars()
+            .try_fold(Lexicon::ROOT, |node, char| lexicon.child(node, char))
+            .unwrap();
+        assert!(lexicon.is_word(the));
+        assert_eq!(
+            lexicon
+                .children(the)
+                .map(|(char, _)| char)
+                .collect::<Vec<_>>(),
+            vec!['n', 'r']
+        );
+    }
+
+    #[test]
+    fn new_should_share_tails() {
+        let lexicon = Lexicon::new(["tap", "taps", "top", "tops"]);
+        let ta = lexicon.child(lexicon.child(Lexicon::ROOT, 't').unwrap(), 'a');
+        let to = lexicon.child(lexicon.child(Lexicon::ROOT, 't').unwrap(), 'o');
+        assert_eq!(ta, to);
+        // Root, t, the shared a/o node, p and s
+        assert_eq!(lexicon.terminal.len(), 5);
+    }
+
+    #[test]
+    fn from_bytes_should_load_what_to_bytes_wrote() {
+        let words = ["the", "there", "then", "a", "ab"];
+        let lexicon = Lexicon::new(words);
+        let bytes = lexicon.to_bytes();
+        let loaded = Lexicon::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.len(), lexicon.len());
+        assert!(words.iter().all(|word| loaded.contains(word)));
+        assert!(!loaded.contains("th"));
+        assert!(Lexicon::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut bad_edge = bytes.clone();
+        let last = bad_edge.len() - 4;
+        bad_edge[last..].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Lexicon::from_bytes(&bad_edge).is_err());
+    }
+}
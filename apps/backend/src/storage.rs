@@ -0,0 +1,97 @@
+use std::{collections::HashSet, error::Error, fmt::Debug};
+
+use crate::{
+    game::{replay::FinishedReplay, snapshot::GameSnapshot},
+    service::{
+        account_service::Account,
+        chat_service::Report,
+        profile_service::Profile,
+        stats_service::{MatchRecord, PlayerStats},
+    },
+};
+
+pub mod memory;
+#[cfg(feature = "sql")]
+pub mod sql;
+
+/// Where state that outlives a connection is kept. Accounts are keyed by
+/// player name until players can sign in.
+pub trait Storage: Debug + Send + Sync {
+    fn load_stats(
+        &self,
+        account: &str,
+    ) -> Result<Option<PlayerStats>, Box<dyn Error + Send + Sync>>;
+
+    fn save_stats(
+        &self,
+        account: &str,
+        stats: &PlayerStats,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    fn load_account(&self, name: &str) -> Result<Option<Account>, Box<dyn Error + Send + Sync>>;
+
+    /// Replace whatever was saved under the account's name.
+    fn save_account(&self, account: &Account) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    fn load_profile(&self, account: &str) -> Result<Option<Profile>, Box<dyn Error + Send + Sync>>;
+
+    fn save_profile(
+        &self,
+        account: &str,
+        profile: &Profile,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Names `account` blocked, empty if none.
+    fn load_blocked(&self, account: &str) -> Result<HashSet<String>, Box<dyn Error + Send + Sync>>;
+
+    /// Replace the names `account` blocked.
+    fn save_blocked(
+        &self,
+        account: &str,
+        blocked: &HashSet<String>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    fn save_match(&self, record: &MatchRecord) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Finished games `account` played in, oldest first.
+    fn load_matches(&self, account: &str)
+        -> Result<Vec<MatchRecord>, Box<dyn Error + Send + Sync>>;
+
+    /// Every score on the named leaderboard, in no particular order.
+    fn load_leaderboard(
+        &self,
+        board: &str,
+    ) -> Result<Vec<(String, u32)>, Box<dyn Error + Send + Sync>>;
+
+    /// Set `account`'s score on the named leaderboard.
+    fn save_leaderboard_score(
+        &self,
+        board: &str,
+        account: &str,
+        score: u32,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Replace whatever was saved for the replay's game.
+    fn save_replay(&self, replay: &FinishedReplay) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    fn load_replay(
+        &self,
+        game_id: u32,
+    ) -> Result<Option<FinishedReplay>, Box<dyn Error + Send + Sync>>;
+
+    fn save_report(&self, report: &Report) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Reports filed against `account`, oldest first.
+    fn load_reports(&self, account: &str) -> Result<Vec<Report>, Box<dyn Error + Send + Sync>>;
+
+    /// Replace whatever was saved for the snapshot's game.
+    fn save_snapshot(&self, snapshot: &GameSnapshot) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    fn delete_snapshot(&self, game_id: u32) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Every game saved and not deleted since, in no particular order.
+    fn load_snapshots(&self) -> Result<Vec<GameSnapshot>, Box<dyn Error + Send + Sync>>;
+
+    /// Check the backend can still be reached, for the readiness probe.
+    fn ping(&self) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
@@ -0,0 +1,212 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    sync::Mutex,
+};
+
+use crate::{
+    game::{replay::FinishedReplay, snapshot::GameSnapshot},
+    service::{
+        account_service::Account,
+        chat_service::Report,
+        profile_service::Profile,
+        stats_service::{MatchRecord, PlayerStats},
+    },
+};
+
+use super::Storage;
+
+/// Keeps everything in the process, so it is lost on restart.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    stats: Mutex<HashMap<String, PlayerStats>>,
+    accounts: Mutex<HashMap<String, Account>>,
+    profiles: Mutex<HashMap<String, Profile>>,
+    blocked: Mutex<HashMap<String, HashSet<String>>>,
+    matches: Mutex<Vec<MatchRecord>>,
+    leaderboards: Mutex<HashMap<String, HashMap<String, u32>>>,
+    replays: Mutex<HashMap<u32, FinishedReplay>>,
+    reports: Mutex<Vec<Report>>,
+    snapshots: Mutex<HashMap<u32, GameSnapshot>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load_stats(
+        &self,
+        account: &str,
+    ) -> Result<Option<PlayerStats>, Box<dyn Error + Send + Sync>> {
+        Ok(self.stats.lock().unwrap().get(account).cloned())
+    }
+
+    fn save_stats(
+        &self,
+        account: &str,
+        stats: &PlayerStats,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.stats
+            .lock()
+            .unwrap()
+            .insert(account.to_string(), stats.clone());
+        Ok(())
+    }
+
+    fn load_account(&self, name: &str) -> Result<Option<Account>, Box<dyn Error + Send + Sync>> {
+        Ok(self.accounts.lock().unwrap().get(name).cloned())
+    }
+
+    fn save_account(&self, account: &Account) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.accounts
+            .lock()
+            .unwrap()
+            .insert(account.name.clone(), account.clone());
+        Ok(())
+    }
+
+    fn load_profile(&self, account: &str) -> Result<Option<Profile>, Box<dyn Error + Send + Sync>> {
+        Ok(self.profiles.lock().unwrap().get(account).copied())
+    }
+
+    fn save_profile(
+        &self,
+        account: &str,
+        profile: &Profile,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.profiles
+            .lock()
+            .unwrap()
+            .insert(account.to_string(), *profile);
+        Ok(())
+    }
+
+    fn load_blocked(&self, account: &str) -> Result<HashSet<String>, Box<dyn Error + Send + Sync>> {
+        Ok(self
+            .blocked
+            .lock()
+            .unwrap()
+            .get(account)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn save_blocked(
+        &self,
+        account: &str,
+        blocked: &HashSet<String>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.blocked
+            .lock()
+            .unwrap()
+            .insert(account.to_string(), blocked.clone());
+        Ok(())
+    }
+
+    fn save_match(&self, record: &MatchRecord) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.matches.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+
+    fn load_matches(
+        &self,
+        account: &str,
+    ) -> Result<Vec<MatchRecord>, Box<dyn Error + Send + Sync>> {
+        Ok(self
+            .matches
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|record| record.played_by(account))
+            .cloned()
+            .collect())
+    }
+
+    fn load_leaderboard(
+        &self,
+        board: &str,
+    ) -> Result<Vec<(String, u32)>, Box<dyn Error + Send + Sync>> {
+        Ok(self
+            .leaderboards
+            .lock()
+            .unwrap()
+            .get(board)
+            .map(|scores| {
+                scores
+                    .iter()
+                    .map(|(account, score)| (account.clone(), *score))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn save_leaderboard_score(
+        &self,
+        board: &str,
+        account: &str,
+        score: u32,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.leaderboards
+            .lock()
+            .unwrap()
+            .entry(board.to_string())
+            .or_default()
+            .insert(account.to_string(), score);
+        Ok(())
+    }
+
+    fn save_replay(&self, replay: &FinishedReplay) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.replays
+            .lock()
+            .unwrap()
+            .insert(replay.replay.game_id, replay.clone());
+        Ok(())
+    }
+
+    fn load_replay(
+        &self,
+        game_id: u32,
+    ) -> Result<Option<FinishedReplay>, Box<dyn Error + Send + Sync>> {
+        Ok(self.replays.lock().unwrap().get(&game_id).cloned())
+    }
+
+    fn save_report(&self, report: &Report) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.reports.lock().unwrap().push(report.clone());
+        Ok(())
+    }
+
+    fn load_reports(&self, account: &str) -> Result<Vec<Report>, Box<dyn Error + Send + Sync>> {
+        Ok(self
+            .reports
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|report| report.reported == account)
+            .cloned()
+            .collect())
+    }
+
+    fn save_snapshot(&self, snapshot: &GameSnapshot) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.snapshots
+            .lock()
+            .unwrap()
+            .insert(snapshot.game_id, snapshot.clone());
+        Ok(())
+    }
+
+    fn delete_snapshot(&self, game_id: u32) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.snapshots.lock().unwrap().remove(&game_id);
+        Ok(())
+    }
+
+    fn load_snapshots(&self) -> Result<Vec<GameSnapshot>, Box<dyn Error + Send + Sync>> {
+        Ok(self.snapshots.lock().unwrap().values().cloned().collect())
+    }
+
+    fn ping(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Ok(())
+    }
+}
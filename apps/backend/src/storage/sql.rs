@@ -0,0 +1,672 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    future::Future,
+    pin::Pin,
+    sync::mpsc::{self as std_mpsc, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use sqlx::{any::AnyPoolOptions, AnyPool, Row};
+use tokio::{
+    runtime::{self, Handle, RuntimeFlavor},
+    sync::mpsc,
+    task,
+};
+
+use crate::{
+    game::{replay::FinishedReplay, snapshot::GameSnapshot},
+    model::player::profile::{TileColor, Title},
+    service::{
+        account_service::Account,
+        chat_service::Report,
+        profile_service::Profile,
+        stats_service::{MatchRecord, PlayerStats},
+    },
+};
+
+use super::Storage;
+
+/// How long a caller waits for its query before giving up on it.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Job = Box<dyn FnOnce(AnyPool) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// Tables are created on connect if they're missing, so a fresh database
+/// needs no setup. Games and replays are kept whole as JSON since nothing
+/// looks inside them but the game that wrote them.
+const SCHEMA: [&str; 10] = [
+    "CREATE TABLE IF NOT EXISTS stats (
+        account TEXT PRIMARY KEY,
+        games_played BIGINT NOT NULL,
+        games_won BIGINT NOT NULL,
+        games_resigned BIGINT NOT NULL,
+        total_score BIGINT NOT NULL,
+        tiles_placed BIGINT NOT NULL,
+        best_word TEXT NOT NULL,
+        best_word_score BIGINT NOT NULL,
+        longest_word TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS accounts (
+        name TEXT PRIMARY KEY,
+        password_hash TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS profiles (
+        account TEXT PRIMARY KEY,
+        avatar_id BIGINT NOT NULL,
+        tile_color BIGINT NOT NULL,
+        title BIGINT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS blocked (
+        account TEXT NOT NULL,
+        blocked TEXT NOT NULL,
+        PRIMARY KEY (account, blocked)
+    )",
+    "CREATE TABLE IF NOT EXISTS matches (
+        game_id BIGINT NOT NULL,
+        finished_at BIGINT NOT NULL,
+        record TEXT NOT NULL,
+        PRIMARY KEY (game_id, finished_at)
+    )",
+    "CREATE TABLE IF NOT EXISTS match_players (
+        game_id BIGINT NOT NULL,
+        finished_at BIGINT NOT NULL,
+        account TEXT NOT NULL,
+        PRIMARY KEY (account, game_id, finished_at)
+    )",
+    "CREATE TABLE IF NOT EXISTS leaderboards (
+        board TEXT NOT NULL,
+        account TEXT NOT NULL,
+        score BIGINT NOT NULL,
+        PRIMARY KEY (board, account)
+    )",
+    "CREATE TABLE IF NOT EXISTS replays (
+        game_id BIGINT PRIMARY KEY,
+        replay TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS reports (
+        reporter TEXT NOT NULL,
+        reported TEXT NOT NULL,
+        reason TEXT NOT NULL,
+        created_at BIGINT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS snapshots (
+        game_id BIGINT PRIMARY KEY,
+        snapshot TEXT NOT NULL
+    )",
+];
+
+/// Keeps everything in a SQLite or Postgres database, picked by the URL's
+/// scheme. `Storage` is called from sync code, some of it on the server's
+/// runtime, so queries run on a runtime of their own on a separate thread
+/// and callers block until theirs is done, or for `QUERY_TIMEOUT` at most.
+#[derive(Debug)]
+pub struct SqlStorage {
+    jobs: mpsc::UnboundedSender<Job>,
+}
+
+impl SqlStorage {
+    /// Connect to `url` and create any tables that are missing.
+    pub fn connect(url: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        sqlx::any::install_default_drivers();
+        let runtime = runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let (jobs, mut queue) = mpsc::unbounded_channel::<Job>();
+        let (connected, connecting) = std_mpsc::sync_channel(1);
+        let url = url.to_string();
+        thread::Builder::new()
+            .name("storage".to_string())
+            .spawn(move || {
+                runtime.block_on(async move {
+                    let pool = match Self::open(&url).await {
+                        Ok(pool) => {
+                            let _ = connected.send(Ok(()));
+                            pool
+                        }
+                        Err(e) => {
+                            let _ = connected.send(Err(e));
+                            return;
+                        }
+                    };
+                    while let Some(job) = queue.recv().await {
+                        tokio::spawn(job(pool.clone()));
+                    }
+                    pool.close().await;
+                })
+            })?;
+        connecting.recv()??;
+        Ok(Self { jobs })
+    }
+
+    async fn open(url: &str) -> Result<AnyPool, Box<dyn Error + Send + Sync>> {
+        let mut options = AnyPoolOptions::new();
+        if url.contains(":memory:") {
+            // Every SQLite connection to memory gets a database of its own,
+            // so keep the one connection open for good.
+            options = options
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None);
+        }
+        let pool = options.connect(url).await?;
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        Ok(pool)
+    }
+
+    /// Run `job` on the storage thread and wait for what it returns.
+    fn run<T, F, Fut>(&self, job: F) -> Result<T, Box<dyn Error + Send + Sync>>
+    where
+        T: Send + 'static,
+        F: FnOnce(AnyPool) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, Box<dyn Error + Send + Sync>>> + Send + 'static,
+    {
+        let (reply, result) = std_mpsc::sync_channel(1);
+        self.jobs
+            .send(Box::new(move |pool| {
+                Box::pin(async move {
+                    let _ = reply.send(job(pool).await);
+                })
+            }))
+            .map_err(|_| "storage thread stopped")?;
+        let wait = || match result.recv_timeout(QUERY_TIMEOUT) {
+            Ok(res) => res,
+            Err(RecvTimeoutError::Timeout) => Err("storage query timed out".into()),
+            Err(RecvTimeoutError::Disconnected) => Err("storage thread stopped".into()),
+        };
+        // On a worker of the server's runtime, hand its other tasks to
+        // another worker while this one waits
+        match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                task::block_in_place(wait)
+            }
+            _ => wait(),
+        }
+    }
+}
+
+impl Storage for SqlStorage {
+    fn load_stats(
+        &self,
+        account: &str,
+    ) -> Result<Option<PlayerStats>, Box<dyn Error + Send + Sync>> {
+        let account = account.to_string();
+        self.run(move |pool| async move {
+            let row = sqlx::query(
+                "SELECT games_played, games_won, games_resigned, total_score, tiles_placed,
+                    best_word, best_word_score, longest_word
+                FROM stats WHERE account = $1",
+            )
+            .bind(account)
+            .fetch_optional(&pool)
+            .await?;
+            let Some(row) = row else {
+                return Ok(None);
+            };
+            // The Any driver can't decode NULL, so no word is kept as an
+            // empty one.
+            let best_word: String = row.try_get("best_word")?;
+            let best_word_score = row.try_get::<i64, _>("best_word_score")? as u32;
+            let longest_word: String = row.try_get("longest_word")?;
+            Ok(Some(PlayerStats {
+                games_played: row.try_get::<i64, _>("games_played")? as u32,
+                games_won: row.try_get::<i64, _>("games_won")? as u32,
+                games_resigned: row.try_get::<i64, _>("games_resigned")? as u32,
+                total_score: row.try_get::<i64, _>("total_score")? as u64,
+                tiles_placed: row.try_get::<i64, _>("tiles_placed")? as u64,
+                best_word: Some(best_word)
+                    .filter(|word| !word.is_empty())
+                    .map(|word| (word, best_word_score)),
+                longest_word: Some(longest_word).filter(|word| !word.is_empty()),
+            }))
+        })
+    }
+
+    fn save_stats(
+        &self,
+        account: &str,
+        stats: &PlayerStats,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let account = account.to_string();
+        let stats = stats.clone();
+        self.run(move |pool| async move {
+            let (best_word, best_word_score) = stats.best_word.unzip();
+            sqlx::query(
+                "INSERT INTO stats (account, games_played, games_won, games_resigned,
+                    total_score, tiles_placed, best_word, best_word_score, longest_word)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (account) DO UPDATE SET
+                    games_played = excluded.games_played,
+                    games_won = excluded.games_won,
+                    games_resigned = excluded.games_resigned,
+                    total_score = excluded.total_score,
+                    tiles_placed = excluded.tiles_placed,
+                    best_word = excluded.best_word,
+                    best_word_score = excluded.best_word_score,
+                    longest_word = excluded.longest_word",
+            )
+            .bind(account)
+            .bind(stats.games_played as i64)
+            .bind(stats.games_won as i64)
+            .bind(stats.games_resigned as i64)
+            .bind(stats.total_score as i64)
+            .bind(stats.tiles_placed as i64)
+            .bind(best_word.unwrap_or_default())
+            .bind(best_word_score.unwrap_or_default() as i64)
+            .bind(stats.longest_word.unwrap_or_default())
+            .execute(&pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn load_account(&self, name: &str) -> Result<Option<Account>, Box<dyn Error + Send + Sync>> {
+        let name = name.to_string();
+        self.run(move |pool| async move {
+            let row = sqlx::query("SELECT password_hash FROM accounts WHERE name = $1")
+                .bind(name.clone())
+                .fetch_optional(&pool)
+                .await?;
+            Ok(match row {
+                Some(row) => Some(Account {
+                    name,
+                    password_hash: row.try_get("password_hash")?,
+                }),
+                None => None,
+            })
+        })
+    }
+
+    fn save_account(&self, account: &Account) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let account = account.clone();
+        self.run(move |pool| async move {
+            sqlx::query(
+                "INSERT INTO accounts (name, password_hash) VALUES ($1, $2)
+                ON CONFLICT (name) DO UPDATE SET password_hash = excluded.password_hash",
+            )
+            .bind(account.name)
+            .bind(account.password_hash)
+            .execute(&pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn load_profile(&self, account: &str) -> Result<Option<Profile>, Box<dyn Error + Send + Sync>> {
+        let account = account.to_string();
+        self.run(move |pool| async move {
+            let row =
+                sqlx::query("SELECT avatar_id, tile_color, title FROM profiles WHERE account = $1")
+                    .bind(account)
+                    .fetch_optional(&pool)
+                    .await?;
+            let Some(row) = row else {
+                return Ok(None);
+            };
+            Ok(Some(Profile {
+                avatar_id: row.try_get::<i64, _>("avatar_id")? as u32,
+                tile_color: TileColor::from_i32(row.try_get::<i64, _>("tile_color")? as i32)
+                    .ok_or("invalid tile color")?,
+                title: Title::from_i32(row.try_get::<i64, _>("title")? as i32)
+                    .ok_or("invalid title")?,
+            }))
+        })
+    }
+
+    fn save_profile(
+        &self,
+        account: &str,
+        profile: &Profile,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let account = account.to_string();
+        let profile = *profile;
+        self.run(move |pool| async move {
+            sqlx::query(
+                "INSERT INTO profiles (account, avatar_id, tile_color, title) VALUES ($1, $2, $3, $4)
+                ON CONFLICT (account) DO UPDATE SET
+                    avatar_id = excluded.avatar_id,
+                    tile_color = excluded.tile_color,
+                    title = excluded.title",
+            )
+            .bind(account)
+            .bind(profile.avatar_id as i64)
+            .bind(profile.tile_color as i64)
+            .bind(profile.title as i64)
+            .execute(&pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn load_blocked(&self, account: &str) -> Result<HashSet<String>, Box<dyn Error + Send + Sync>> {
+        let account = account.to_string();
+        self.run(move |pool| async move {
+            let rows = sqlx::query("SELECT blocked FROM blocked WHERE account = $1")
+                .bind(account)
+                .fetch_all(&pool)
+                .await?;
+            Ok(rows
+                .iter()
+                .map(|row| row.try_get("blocked"))
+                .collect::<Result<_, _>>()?)
+        })
+    }
+
+    fn save_blocked(
+        &self,
+        account: &str,
+        blocked: &HashSet<String>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let account = account.to_string();
+        let blocked = blocked.clone();
+        self.run(move |pool| async move {
+            let mut transaction = pool.begin().await?;
+            sqlx::query("DELETE FROM blocked WHERE account = $1")
+                .bind(account.clone())
+                .execute(&mut *transaction)
+                .await?;
+            for name in blocked {
+                sqlx::query("INSERT INTO blocked (account, blocked) VALUES ($1, $2)")
+                    .bind(account.clone())
+                    .bind(name)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+
+    fn save_match(&self, record: &MatchRecord) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let record = record.clone();
+        self.run(move |pool| async move {
+            let mut transaction = pool.begin().await?;
+            sqlx::query("INSERT INTO matches (game_id, finished_at, record) VALUES ($1, $2, $3)")
+                .bind(record.game_id as i64)
+                .bind(record.finished_at as i64)
+                .bind(serde_json::to_string(&record)?)
+                .execute(&mut *transaction)
+                .await?;
+            let accounts = record
+                .scores
+                .iter()
+                .chain(&record.forfeited)
+                .chain(&record.resigned)
+                .map(|(name, _)| name)
+                .collect::<HashSet<_>>();
+            for account in accounts {
+                sqlx::query(
+                    "INSERT INTO match_players (game_id, finished_at, account) VALUES ($1, $2, $3)",
+                )
+                .bind(record.game_id as i64)
+                .bind(record.finished_at as i64)
+                .bind(account.clone())
+                .execute(&mut *transaction)
+                .await?;
+            }
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+
+    fn load_matches(
+        &self,
+        account: &str,
+    ) -> Result<Vec<MatchRecord>, Box<dyn Error + Send + Sync>> {
+        let account = account.to_string();
+        self.run(move |pool| async move {
+            let rows = sqlx::query(
+                "SELECT matches.record FROM matches
+                JOIN match_players ON match_players.game_id = matches.game_id
+                    AND match_players.finished_at = matches.finished_at
+                WHERE match_players.account = $1
+                ORDER BY matches.finished_at, matches.game_id",
+            )
+            .bind(account)
+            .fetch_all(&pool)
+            .await?;
+            let mut records = Vec::with_capacity(rows.len());
+            for row in rows {
+                records.push(serde_json::from_str(row.try_get("record")?)?);
+            }
+            Ok(records)
+        })
+    }
+
+    fn load_leaderboard(
+        &self,
+        board: &str,
+    ) -> Result<Vec<(String, u32)>, Box<dyn Error + Send + Sync>> {
+        let board = board.to_string();
+        self.run(move |pool| async move {
+            let rows = sqlx::query("SELECT account, score FROM leaderboards WHERE board = $1")
+                .bind(board)
+                .fetch_all(&pool)
+                .await?;
+            let mut scores = Vec::with_capacity(rows.len());
+            for row in rows {
+                scores.push((
+                    row.try_get("account")?,
+                    row.try_get::<i64, _>("score")? as u32,
+                ));
+            }
+            Ok(scores)
+        })
+    }
+
+    fn save_leaderboard_score(
+        &self,
+        board: &str,
+        account: &str,
+        score: u32,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let board = board.to_string();
+        let account = account.to_string();
+        self.run(move |pool| async move {
+            sqlx::query(
+                "INSERT INTO leaderboards (board, account, score) VALUES ($1, $2, $3)
+                ON CONFLICT (board, account) DO UPDATE SET score = excluded.score",
+            )
+            .bind(board)
+            .bind(account)
+            .bind(score as i64)
+            .execute(&pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn save_replay(&self, replay: &FinishedReplay) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_id = replay.replay.game_id;
+        let replay = serde_json::to_string(replay)?;
+        self.run(move |pool| async move {
+            sqlx::query(
+                "INSERT INTO replays (game_id, replay) VALUES ($1, $2)
+                ON CONFLICT (game_id) DO UPDATE SET replay = excluded.replay",
+            )
+            .bind(game_id as i64)
+            .bind(replay)
+            .execute(&pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn load_replay(
+        &self,
+        game_id: u32,
+    ) -> Result<Option<FinishedReplay>, Box<dyn Error + Send + Sync>> {
+        self.run(move |pool| async move {
+            let row = sqlx::query("SELECT replay FROM replays WHERE game_id = $1")
+                .bind(game_id as i64)
+                .fetch_optional(&pool)
+                .await?;
+            Ok(match row {
+                Some(row) => Some(serde_json::from_str(row.try_get("replay")?)?),
+                None => None,
+            })
+        })
+    }
+
+    fn save_report(&self, report: &Report) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let report = report.clone();
+        self.run(move |pool| async move {
+            sqlx::query(
+                "INSERT INTO reports (reporter, reported, reason, created_at)
+                VALUES ($1, $2, $3, $4)",
+            )
+            .bind(report.reporter)
+            .bind(report.reported)
+            .bind(report.reason)
+            .bind(report.created_at as i64)
+            .execute(&pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn load_reports(&self, account: &str) -> Result<Vec<Report>, Box<dyn Error + Send + Sync>> {
+        let account = account.to_string();
+        self.run(move |pool| async move {
+            let rows = sqlx::query(
+                "SELECT reporter, reason, created_at FROM reports WHERE reported = $1
+                ORDER BY created_at",
+            )
+            .bind(account.clone())
+            .fetch_all(&pool)
+            .await?;
+            let mut reports = Vec::with_capacity(rows.len());
+            for row in rows {
+                reports.push(Report {
+                    reporter: row.try_get("reporter")?,
+                    reported: account.clone(),
+                    reason: row.try_get("reason")?,
+                    created_at: row.try_get::<i64, _>("created_at")? as u64,
+                });
+            }
+            Ok(reports)
+        })
+    }
+
+    fn save_snapshot(&self, snapshot: &GameSnapshot) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let game_id = snapshot.game_id;
+        let snapshot = serde_json::to_string(snapshot)?;
+        self.run(move |pool| async move {
+            sqlx::query(
+                "INSERT INTO snapshots (game_id, snapshot) VALUES ($1, $2)
+                ON CONFLICT (game_id) DO UPDATE SET snapshot = excluded.snapshot",
+            )
+            .bind(game_id as i64)
+            .bind(snapshot)
+            .execute(&pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn delete_snapshot(&self, game_id: u32) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.run(move |pool| async move {
+            sqlx::query("DELETE FROM snapshots WHERE game_id = $1")
+                .bind(game_id as i64)
+                .execute(&pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn load_snapshots(&self) -> Result<Vec<GameSnapshot>, Box<dyn Error + Send + Sync>> {
+        self.run(move |pool| async move {
+            let rows = sqlx::query("SELECT snapshot FROM snapshots")
+                .fetch_all(&pool)
+                .await?;
+            let mut snapshots = Vec::with_capacity(rows.len());
+            for row in rows {
+                snapshots.push(serde_json::from_str(row.try_get("snapshot")?)?);
+            }
+            Ok(snapshots)
+        })
+    }
+
+    fn ping(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.run(move |pool| async move {
+            sqlx::query("SELECT 1").execute(&pool).await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{game::game::Game, player::Player};
+
+    use super::*;
+
+    #[test]
+    fn load_with_saved_records_should_return_them() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let storage = SqlStorage::connect("sqlite::memory:")?;
+        let stats = PlayerStats {
+            games_played: 3,
+            games_won: 1,
+            best_word: Some(("quiz".to_string(), 22)),
+            ..Default::default()
+        };
+        storage.save_stats("alice", &stats)?;
+        assert_eq!(storage.load_stats("alice")?, Some(stats));
+        assert_eq!(storage.load_stats("bob")?, None);
+
+        storage.save_blocked("alice", &HashSet::from(["bob".to_string()]))?;
+        storage.save_blocked("alice", &HashSet::from(["carol".to_string()]))?;
+        assert_eq!(
+            storage.load_blocked("alice")?,
+            HashSet::from(["carol".to_string()])
+        );
+
+        let record = MatchRecord {
+            game_id: 7,
+            finished_at: 1_700_000_000,
+            scores: vec![("alice".to_string(), 40), ("bob".to_string(), 30)],
+            forfeited: Vec::new(),
+            resigned: Vec::new(),
+            drawn: false,
+        };
+        storage.save_match(&record)?;
+        assert_eq!(storage.load_matches("bob")?, vec![record]);
+        assert!(storage.load_matches("carol")?.is_empty());
+
+        storage.save_leaderboard_score("weekly", "alice", 10)?;
+        storage.save_leaderboard_score("weekly", "alice", 25)?;
+        assert_eq!(
+            storage.load_leaderboard("weekly")?,
+            vec![("alice".to_string(), 25)]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn load_snapshots_from_runtime_should_return_saved_games(
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let storage = SqlStorage::connect("sqlite::memory:")?;
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect::<Vec<_>>();
+        let snapshot = Game::new(3, players).snapshot();
+        storage.save_snapshot(&snapshot)?;
+        assert_eq!(storage.load_snapshots()?, vec![snapshot]);
+        storage.delete_snapshot(3)?;
+        assert!(storage.load_snapshots()?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ping_from_server_runtime_should_return_ok() -> Result<(), Box<dyn Error + Send + Sync>>
+    {
+        let storage = SqlStorage::connect("sqlite::memory:")?;
+        storage.ping()?;
+        Ok(())
+    }
+}
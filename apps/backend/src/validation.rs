@@ -0,0 +1,156 @@
+use crate::{
+    frame::RequestData,
+    game::{board::BOARD_SIZE, rules::MAX_HAND_SIZE},
+    service::{
+        account_service::MAX_NAME_LEN, chat_service::MAX_MESSAGE_LEN,
+        dictionary_service::MAX_CHUNK_WORDS, lobby_service::JOIN_CODE_LEN,
+    },
+};
+
+/// Longest language key a lobby's rules may name.
+const MAX_LANGUAGE_LEN: usize = 16;
+
+/// Why a request was refused before reaching its controller. Strings are
+/// already known to be UTF-8, decoding refuses any that aren't.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidRequest {
+    #[error("{field} must be below {limit}")]
+    OutOfRange { field: &'static str, limit: usize },
+    #[error("{field} must be at most {limit} characters")]
+    TooLong { field: &'static str, limit: usize },
+    #[error("{field} must have at most {limit} entries")]
+    TooMany { field: &'static str, limit: usize },
+    #[error("{field} must not have control characters")]
+    ControlCharacter { field: &'static str },
+}
+
+/// Check the fields of `data` against the bounds no valid request goes past,
+/// whatever state the server is in. Limits that depend on a game, such as
+/// its board size, are left to the game.
+pub fn validate(data: &RequestData) -> Result<(), InvalidRequest> {
+    match data {
+        RequestData::Connect(req) => text("name", &req.name, MAX_NAME_LEN),
+        RequestData::CreateLobby(req) => match &req.rules {
+            Some(rules) => text("language", &rules.language, MAX_LANGUAGE_LEN),
+            None => Ok(()),
+        },
+        RequestData::UpdateLobbySettings(req) => match &req.rules {
+            Some(rules) => text("language", &rules.language, MAX_LANGUAGE_LEN),
+            None => Ok(()),
+        },
+        RequestData::SetTile(req) => {
+            below("x", req.x, BOARD_SIZE)?;
+            below("y", req.y, BOARD_SIZE)?;
+            below("cardIndex", req.card_index, MAX_HAND_SIZE)
+        }
+        RequestData::ExchangeCards(req) => {
+            if req.card_indices.len() > MAX_HAND_SIZE {
+                return Err(InvalidRequest::TooMany {
+                    field: "cardIndices",
+                    limit: MAX_HAND_SIZE,
+                });
+            }
+            for card_index in &req.card_indices {
+                below("cardIndices", *card_index, MAX_HAND_SIZE)?;
+            }
+            Ok(())
+        }
+        RequestData::Cancel(req) => {
+            below("x", req.x, BOARD_SIZE)?;
+            below("y", req.y, BOARD_SIZE)
+        }
+        RequestData::SendChat(req) => text("message", &req.message, MAX_MESSAGE_LEN),
+        RequestData::ReportPlayer(req) => text("reason", &req.reason, MAX_MESSAGE_LEN),
+        RequestData::UploadDictionary(req) => {
+            if req.words.len() > MAX_CHUNK_WORDS {
+                return Err(InvalidRequest::TooLong {
+                    field: "words",
+                    limit: MAX_CHUNK_WORDS,
+                });
+            }
+            Ok(())
+        }
+        RequestData::JoinByCode(req) => text("code", &req.code, JOIN_CODE_LEN),
+        RequestData::BlockPlayer(req) => text("name", &req.name, MAX_NAME_LEN),
+        _ => Ok(()),
+    }
+}
+
+fn below(field: &'static str, value: u32, limit: usize) -> Result<(), InvalidRequest> {
+    match (value as usize) < limit {
+        true => Ok(()),
+        false => Err(InvalidRequest::OutOfRange { field, limit }),
+    }
+}
+
+fn text(field: &'static str, value: &str, limit: usize) -> Result<(), InvalidRequest> {
+    if value.chars().count() > limit {
+        return Err(InvalidRequest::TooLong { field, limit });
+    }
+    if value.chars().any(char::is_control) {
+        return Err(InvalidRequest::ControlCharacter { field });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{
+        chat::send::SendChatRequest,
+        game::{exchange::ExchangeCardsRequest, set_tile::SetTileRequest},
+    };
+
+    use super::*;
+
+    #[test]
+    fn validate_with_coordinates_off_every_board_should_return_error() {
+        let set_tile = |x, card_index| {
+            validate(&RequestData::SetTile(SetTileRequest {
+                x,
+                y: 0,
+                card_index,
+            }))
+        };
+        assert!(set_tile(BOARD_SIZE as u32 - 1, 0).is_ok());
+        assert_eq!(
+            set_tile(BOARD_SIZE as u32, 0),
+            Err(InvalidRequest::OutOfRange {
+                field: "x",
+                limit: BOARD_SIZE,
+            })
+        );
+        assert!(set_tile(0, MAX_HAND_SIZE as u32).is_err());
+    }
+
+    #[test]
+    fn validate_with_more_cards_than_any_hand_should_return_error() {
+        let exchange = |card_indices: Vec<u32>| {
+            validate(&RequestData::ExchangeCards(ExchangeCardsRequest {
+                card_indices,
+            }))
+        };
+        assert!(exchange((0..MAX_HAND_SIZE as u32).collect()).is_ok());
+        assert_eq!(
+            exchange(vec![0; MAX_HAND_SIZE + 1]),
+            Err(InvalidRequest::TooMany {
+                field: "cardIndices",
+                limit: MAX_HAND_SIZE,
+            })
+        );
+        assert!(exchange(vec![MAX_HAND_SIZE as u32]).is_err());
+    }
+
+    #[test]
+    fn validate_with_long_or_control_text_should_return_error() {
+        let send = |message: String| validate(&RequestData::SendChat(SendChatRequest { message }));
+        assert!(send("é".repeat(MAX_MESSAGE_LEN)).is_ok());
+        assert!(matches!(
+            send("a".repeat(MAX_MESSAGE_LEN + 1)),
+            Err(InvalidRequest::TooLong { .. })
+        ));
+        assert_eq!(
+            send(String::from("hi\u{1b}[2J")),
+            Err(InvalidRequest::ControlCharacter { field: "message" })
+        );
+    }
+}
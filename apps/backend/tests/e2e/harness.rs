@@ -0,0 +1,210 @@
+use std::{collections::VecDeque, error::Error, net::SocketAddr, sync::Arc, time::Duration};
+
+use backend::{
+    config::{GameConfig, ServerConfig},
+    frame::{Request, RequestData},
+    model::{
+        chat::broadcast::ChatBroadcast,
+        control::{
+            connect::{ConnectRequest, ConnectResponse},
+            notice::Notice,
+        },
+        error::error::Error as ErrorResponse,
+        game::broadcast::GameBroadcast,
+        lobby::broadcast::LobbyBroadcast,
+        state::{State, FIRST_REQUEST_ID},
+    },
+    server::Server,
+};
+use prost::Message;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+/// Longest a test waits on any one frame before calling the server stuck.
+const FRAME_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long `assert_quiet` listens for frames nobody expected.
+const QUIET_PERIOD: Duration = Duration::from_millis(200);
+
+/// Every single letter is in the fixture word list, so one tile makes a
+/// valid move. Games aren't saved and nobody is forfeited for idling.
+pub fn config() -> ServerConfig {
+    ServerConfig {
+        host: String::from("127.0.0.1"),
+        port: 0,
+        game: GameConfig {
+            dictionary_path: format!("{}/tests/fixtures/wordlist.txt", env!("CARGO_MANIFEST_DIR")),
+            snapshot_interval_secs: 0,
+            forfeit_after_missed_turns: 0,
+            ..GameConfig::default()
+        },
+        ..ServerConfig::default()
+    }
+}
+
+/// Start the server on an ephemeral port of the loopback interface, and
+/// return where clients reach it.
+pub async fn boot(config: ServerConfig) -> Result<SocketAddr> {
+    let server = Arc::new(Server::new(config).await?);
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    tokio::spawn(server.accept(listener));
+    Ok(address)
+}
+
+/// An event as it came off the socket, decoded by its state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Lobby(Box<LobbyBroadcast>),
+    Game(Box<GameBroadcast>),
+    /// A notice under the state it came with, `Notice` or one sent right
+    /// before the server closes the connection.
+    Notice(u32, Notice),
+    Error(ErrorResponse),
+    Chat(ChatBroadcast),
+    Other(u32, Vec<u8>),
+}
+
+impl Event {
+    fn decode(state: u32, payload: Vec<u8>) -> Result<Self> {
+        let slice = payload.as_slice();
+        Ok(match state {
+            s if s == State::LobbyBroadcast as u32 => {
+                Event::Lobby(Box::new(LobbyBroadcast::decode(slice)?))
+            }
+            s if s == State::GameBroadcast as u32 => {
+                Event::Game(Box::new(GameBroadcast::decode(slice)?))
+            }
+            s if s == State::Notice as u32
+                || s == State::SessionReplaced as u32
+                || s == State::Moved as u32 =>
+            {
+                Event::Notice(state, Notice::decode(slice)?)
+            }
+            s if s == State::Error as u32 => Event::Error(ErrorResponse::decode(slice)?),
+            s if s == State::Chat as u32 => Event::Chat(ChatBroadcast::decode(slice)?),
+            _ => Event::Other(state, payload),
+        })
+    }
+}
+
+/// A client on a real socket speaking the original framing: requests out
+/// with a big-endian header, responses and events back with a
+/// little-endian one.
+pub struct Client {
+    stream: TcpStream,
+    next_request_id: u32,
+    /// Events that arrived while waiting for a response, in order.
+    events: VecDeque<Event>,
+}
+
+impl Client {
+    pub async fn open(address: SocketAddr) -> Result<Self> {
+        let stream = TcpStream::connect(address).await?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            next_request_id: FIRST_REQUEST_ID,
+            events: VecDeque::new(),
+        })
+    }
+
+    pub async fn connect(
+        address: SocketAddr,
+        name: &str,
+        capabilities: u64,
+    ) -> Result<(Self, ConnectResponse)> {
+        let mut client = Self::open(address).await?;
+        let res = client
+            .call(RequestData::Connect(ConnectRequest {
+                name: String::from(name),
+                capabilities,
+                ..Default::default()
+            }))
+            .await?;
+        Ok((client, res))
+    }
+
+    /// Send `data` and wait for its response, keeping any event that comes
+    /// first for `next_event`.
+    pub async fn call<T: Message + Default>(&mut self, data: RequestData) -> Result<T> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let bytes = Request::new(request_id, Arc::new(data)).to_bytes()?;
+        self.stream.write_all(&bytes).await?;
+        loop {
+            let (state, payload) = self.read_frame().await?.ok_or("connection closed")?;
+            if state == request_id {
+                return Ok(T::decode(payload.as_slice())?);
+            }
+            self.events.push_back(Event::decode(state, payload)?);
+        }
+    }
+
+    /// `None` once the server has closed the connection.
+    async fn read_frame(&mut self) -> Result<Option<(u32, Vec<u8>)>> {
+        let state = match timeout(FRAME_TIMEOUT, self.stream.read_u32_le()).await? {
+            Ok(state) => state,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let len = self.stream.read_u32_le().await?;
+        let mut payload = vec![0; len as usize];
+        self.stream.read_exact(&mut payload).await?;
+        Ok(Some((state, payload)))
+    }
+
+    pub async fn next_event(&mut self) -> Result<Event> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(event);
+        }
+        let (state, payload) = self.read_frame().await?.ok_or("connection closed")?;
+        Event::decode(state, payload)
+    }
+
+    pub async fn next_lobby_event(&mut self) -> Result<LobbyBroadcast> {
+        match self.next_event().await? {
+            Event::Lobby(event) => Ok(*event),
+            event => Err(format!("expected a lobby event, got {:?}", event).into()),
+        }
+    }
+
+    pub async fn next_game_event(&mut self) -> Result<GameBroadcast> {
+        match self.next_event().await? {
+            Event::Game(event) => Ok(*event),
+            event => Err(format!("expected a game event, got {:?}", event).into()),
+        }
+    }
+
+    /// Fails if anything arrives within a moment.
+    pub async fn assert_quiet(&mut self) -> Result<()> {
+        if let Some(event) = self.events.pop_front() {
+            return Err(format!("expected nothing, got {:?}", event).into());
+        }
+        match timeout(QUIET_PERIOD, self.stream.read_u32_le()).await {
+            Err(_) => Ok(()),
+            Ok(Ok(state)) => Err(format!("expected nothing, got a frame for {}", state).into()),
+            Ok(Err(e)) => Err(e.into()),
+        }
+    }
+
+    /// Wait for the server to close the connection, failing on any frame
+    /// before it does.
+    pub async fn assert_closed(&mut self) -> Result<()> {
+        if let Some(event) = self.events.pop_front() {
+            return Err(format!("expected the connection to close, got {:?}", event).into());
+        }
+        match self.read_frame().await? {
+            None => Ok(()),
+            Some((state, _)) => Err(format!(
+                "expected the connection to close, got a frame for {}",
+                state
+            )
+            .into()),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! Whole-server flows over real TCP sockets on the loopback interface, with
+//! each client asserting on every frame it gets, in order.
+
+mod harness;
+
+use std::net::SocketAddr;
+
+use backend::{
+    config::SessionPolicy,
+    frame::RequestData,
+    model::{
+        control::{
+            connect::{Capability, ConnectRequest, ConnectResponse},
+            disconnect::DisconnectResponse,
+        },
+        error::error::{Error as ErrorResponse, ErrorCode},
+        game::{
+            broadcast::{GameBroadcast, GameEvent},
+            finish_turn::FinishTurnResponse,
+            resign::ResignResponse,
+            set_tile::{SetTileRequest, SetTileResponse},
+            start::StartResponse,
+        },
+        lobby::{
+            broadcast::LobbyEvent,
+            create::{CreateRequest, CreateResponse},
+            join::{JoinRequest, JoinResponse},
+            ready::ReadyResponse,
+            rules::{Rules, TurnOrder},
+        },
+        state::State,
+    },
+};
+use harness::{boot, config, Client, Event, Result};
+
+/// Two clients in a lobby `alice` leads, seated as they joined so she goes
+/// first.
+struct Table {
+    alice: Client,
+    bob: Client,
+    alice_id: u32,
+    bob_id: u32,
+    /// Where the first tile alice holds is in her hand.
+    card_index: u32,
+}
+
+async fn connect(address: SocketAddr, name: &str, capabilities: u64) -> Result<(Client, u32)> {
+    let (client, res) = Client::connect(address, name, capabilities).await?;
+    assert!(res.success);
+    assert_eq!(res.capabilities, capabilities);
+    Ok((client, res.player.unwrap().id))
+}
+
+/// Connect both, get them into a lobby and start the game, checking every
+/// frame up to alice's first turn that isn't about turns.
+async fn start_game(address: SocketAddr, capabilities: u64) -> Result<Table> {
+    let (mut alice, alice_id) = connect(address, "alice", capabilities).await?;
+    let (mut bob, bob_id) = connect(address, "bob", capabilities).await?;
+    let create: CreateResponse = alice
+        .call(RequestData::CreateLobby(CreateRequest {
+            max_players: 4,
+            rules: Some(Rules {
+                turn_order: TurnOrder::JoinOrder as i32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+        .await?;
+    assert!(create.success);
+    let lobby_id = create.lobby.unwrap().id;
+    let join: JoinResponse = bob
+        .call(RequestData::JoinLobby(JoinRequest {
+            lobby_id,
+            waitlist: false,
+        }))
+        .await?;
+    assert!(join.success);
+    assert_eq!(join.lobby.unwrap().players.len(), 2);
+    let event = alice.next_lobby_event().await?;
+    assert_eq!(event.event, LobbyEvent::Join as i32);
+    let mut players = event
+        .lobby
+        .unwrap()
+        .players
+        .iter()
+        .map(|player| player.id)
+        .collect::<Vec<_>>();
+    players.sort();
+    assert_eq!(players, vec![alice_id.min(bob_id), alice_id.max(bob_id)]);
+    for client in [&mut alice, &mut bob] {
+        let ready: ReadyResponse = client.call(RequestData::Ready).await?;
+        assert!(ready.success);
+    }
+    let start: StartResponse = alice.call(RequestData::StartGame).await?;
+    assert!(start.success);
+    assert_eq!(start.current_player.unwrap().id, alice_id);
+    let card_index = start
+        .cards
+        .unwrap()
+        .cards
+        .iter()
+        .position(|hand_card| hand_card.card.is_some())
+        .unwrap() as u32;
+    let event = bob.next_lobby_event().await?;
+    assert_eq!(event.event, LobbyEvent::Start as i32);
+    assert_eq!(event.current_player.unwrap().id, alice_id);
+    assert_eq!(
+        event
+            .turn_order
+            .iter()
+            .map(|player| player.id)
+            .collect::<Vec<_>>(),
+        vec![alice_id, bob_id]
+    );
+    assert!(event.cards.is_some());
+    Ok(Table {
+        alice,
+        bob,
+        alice_id,
+        bob_id,
+        card_index,
+    })
+}
+
+/// Alice lays one tile and ends her turn.
+async fn play_first_turn(table: &mut Table) -> Result<()> {
+    let set: SetTileResponse = table
+        .alice
+        .call(RequestData::SetTile(SetTileRequest {
+            x: 13,
+            y: 13,
+            card_index: table.card_index,
+        }))
+        .await?;
+    assert!(set.success);
+    let finish: FinishTurnResponse = table.alice.call(RequestData::FinishTurn).await?;
+    assert!(finish.success);
+    assert_eq!(finish.current_player.unwrap().id, table.bob_id);
+    assert!(!finish.words.unwrap().words.is_empty());
+    Ok(())
+}
+
+async fn game_events(client: &mut Client, count: usize) -> Result<Vec<GameBroadcast>> {
+    let mut events = Vec::new();
+    for _ in 0..count {
+        events.push(client.next_game_event().await?);
+    }
+    Ok(events)
+}
+
+async fn disconnect(mut client: Client) -> Result<()> {
+    let res: DisconnectResponse = client.call(RequestData::Disconnect).await?;
+    assert!(res.success);
+    client.assert_quiet().await
+}
+
+#[tokio::test]
+async fn legacy_clients_should_play_from_connect_to_disconnect() -> Result<()> {
+    let address = boot(config()).await?;
+    let mut table = start_game(address, 0).await?;
+    table.alice.assert_quiet().await?;
+    play_first_turn(&mut table).await?;
+    // Alice made the move, only bob hears of it
+    let event = table.bob.next_game_event().await?;
+    assert_eq!(event.event, GameEvent::PlaceTile as i32);
+    let board = event.board.unwrap();
+    let tiles = board
+        .rows
+        .iter()
+        .flat_map(|row| &row.columns)
+        .filter(|column| column.tile.is_some())
+        .count();
+    assert_eq!(tiles, 1);
+    let event = table.bob.next_game_event().await?;
+    assert_eq!(event.event, GameEvent::FinishTurn as i32);
+    assert_eq!(event.current_player.unwrap().id, table.bob_id);
+    assert!(!event.words.unwrap().words.is_empty());
+    table.alice.assert_quiet().await?;
+
+    let resign: ResignResponse = table.bob.call(RequestData::Resign).await?;
+    assert!(resign.success);
+    // Nobody is left to play against, legacy clients aren't told who
+    // resigned
+    let event = table.alice.next_game_event().await?;
+    assert_eq!(event.event, GameEvent::Destroy as i32);
+    table.bob.assert_quiet().await?;
+
+    // Both are back in the lobby, which closes as its leader leaves
+    disconnect(table.alice).await?;
+    let event = table.bob.next_lobby_event().await?;
+    assert_eq!(event.event, LobbyEvent::Destroy as i32);
+    disconnect(table.bob).await
+}
+
+#[tokio::test]
+async fn clients_with_every_capability_should_get_every_event_in_order() -> Result<()> {
+    let address = boot(config()).await?;
+    let mut table = start_game(address, Capability::ALL).await?;
+    let (alice_id, bob_id) = (table.alice_id, table.bob_id);
+
+    // The first turn is announced to everyone, and to alice as hers
+    let events = game_events(&mut table.alice, 2).await?;
+    assert_eq!(events[0].event, GameEvent::TurnChanged as i32);
+    assert_eq!(events[1].event, GameEvent::YourTurn as i32);
+    assert!(events[1].turn_deadline.is_some());
+    let events = game_events(&mut table.bob, 1).await?;
+    assert_eq!(events[0].event, GameEvent::TurnChanged as i32);
+    assert_eq!(events[0].current_player.as_ref().unwrap().id, alice_id);
+
+    play_first_turn(&mut table).await?;
+    // Alice hears of her own move too, her refilled hand comes both as
+    // hers and as everyone else sees it
+    let events = game_events(&mut table.alice, 5).await?;
+    let kinds = events.iter().map(|event| event.event).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            GameEvent::PlaceTile,
+            GameEvent::FinishTurn,
+            GameEvent::HandUpdate,
+            GameEvent::HandUpdate,
+            GameEvent::TurnChanged,
+        ]
+        .map(|event| event as i32)
+    );
+    assert!(events[0].board.is_none() && events[0].board_delta.is_some());
+    assert_eq!(
+        events
+            .iter()
+            .map(|event| event.state_version)
+            .collect::<Vec<_>>(),
+        [2, 3, 4, 4, 5]
+    );
+    assert!(events[2..4].iter().any(|event| event.cards.is_some()));
+    let events = game_events(&mut table.bob, 5).await?;
+    let kinds = events.iter().map(|event| event.event).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            GameEvent::PlaceTile,
+            GameEvent::FinishTurn,
+            GameEvent::HandUpdate,
+            GameEvent::TurnChanged,
+            GameEvent::YourTurn,
+        ]
+        .map(|event| event as i32)
+    );
+    // Bob only sees how many tiles alice holds
+    assert_eq!(events[2].player.as_ref().unwrap().id, alice_id);
+    assert!(events[2].cards.is_none() && events[2].hand_count.is_some());
+    assert_eq!(events[4].current_player.as_ref().unwrap().id, bob_id);
+
+    let resign: ResignResponse = table.bob.call(RequestData::Resign).await?;
+    assert!(resign.success);
+    let event = table.bob.next_game_event().await?;
+    assert_eq!(event.event, GameEvent::Resign as i32);
+    assert_eq!(event.resigned.unwrap().id, bob_id);
+    let events = game_events(&mut table.alice, 2).await?;
+    assert_eq!(events[0].event, GameEvent::Resign as i32);
+    assert_eq!(events[1].event, GameEvent::Destroy as i32);
+    table.bob.assert_quiet().await?;
+
+    disconnect(table.alice).await?;
+    let event = table.bob.next_lobby_event().await?;
+    assert_eq!(event.event, LobbyEvent::Destroy as i32);
+    disconnect(table.bob).await
+}
+
+#[tokio::test]
+async fn request_before_connect_should_be_refused() -> Result<()> {
+    let address = boot(config()).await?;
+    let mut client = Client::open(address).await?;
+    let res: ErrorResponse = client
+        .call(RequestData::CreateLobby(CreateRequest {
+            max_players: 4,
+            ..Default::default()
+        }))
+        .await?;
+    assert_eq!(res.code(), ErrorCode::Unauthenticated);
+    // The connection stays up for the client to connect after all
+    let res: ConnectResponse = client
+        .call(RequestData::Connect(ConnectRequest {
+            name: String::from("alice"),
+            ..Default::default()
+        }))
+        .await?;
+    assert!(res.success);
+    client.assert_quiet().await
+}
+
+#[tokio::test]
+async fn connecting_again_with_replace_policy_should_close_old_session() -> Result<()> {
+    let address = boot(backend::config::ServerConfig {
+        duplicate_sessions: SessionPolicy::Replace,
+        ..config()
+    })
+    .await?;
+    let (mut old, _) = connect(address, "alice", 0).await?;
+    let (mut new, _) = connect(address, "alice", 0).await?;
+    match old.next_event().await? {
+        Event::Notice(state, notice) => {
+            assert_eq!(state, State::SessionReplaced as u32);
+            assert_eq!(notice.message, "Connected from another session");
+        }
+        event => return Err(format!("expected a notice, got {:?}", event).into()),
+    }
+    old.assert_closed().await?;
+    new.assert_quiet().await
+}
@@ -0,0 +1,956 @@
+//! The bytes every response and event puts on the wire, in both framings,
+//! checked against `golden/responses.txt`. A change there is a change to the
+//! protocol clients see, so it should be made on purpose: rerun with
+//! `UPDATE_GOLDEN=1` to rewrite the file, and review the diff.
+
+use std::{env, fs, path::PathBuf, sync::Arc};
+
+use backend::{
+    frame::{Response, ResponseData},
+    model::{
+        admin::{
+            announce::AnnounceResponse,
+            ban::BanResponse,
+            close_game::CloseGameResponse,
+            handoff::HandoffResponse,
+            inspect::{GameDetail, InspectResponse, LobbyDetail, LobbyMember},
+            issue_token::IssueTokenResponse,
+            kick::KickResponse,
+            list_players::{ListPlayersResponse, OnlinePlayer},
+            maintenance::MaintenanceResponse,
+            reload::ReloadResponse,
+            revoke_token::RevokeTokenResponse,
+        },
+        chat::{
+            broadcast::ChatBroadcast,
+            quick_chat::{QuickChat, SendQuickChatResponse},
+            send::SendChatResponse,
+        },
+        control::{
+            connect::{Capability, Compression, ConnectResponse, Scope},
+            disconnect::DisconnectResponse,
+            heartbeat::HeartbeatResponse,
+            notice::Notice,
+            opcodes::{ListSupportedOpcodesResponse, Opcode},
+            preferences::SetPreferencesResponse,
+            server_info::ServerInfoResponse,
+            time_sync::TimeSyncResponse,
+        },
+        error::error::{Error, ErrorCode},
+        game::{
+            analyze::{AnalyzeGameResponse, TurnAnalysis},
+            bag_info::{GetBagInfoResponse, LetterOdds},
+            board::{
+                Board, BoardDelta, ChangedSquare, Column, Layout, Premium, PremiumSquare, Row,
+            },
+            broadcast::{GameBroadcast, GameEvent},
+            cancel::CancelResponse,
+            card::{Card, PowerUp},
+            cards::Cards,
+            coop::CoopState,
+            draw::{AnswerDrawResponse, DrawState, OfferDrawResponse},
+            exchange::ExchangeCardsResponse,
+            exit::ExitResponse,
+            export::ExportGameResponse,
+            finish_turn::FinishTurnResponse,
+            get_new_card::GetNewCardResponse,
+            hand_card::HandCard,
+            hint::{HintResponse, HintTile},
+            pause::{PauseResponse, PauseState},
+            resign::ResignResponse,
+            resume::ResumeResponse,
+            resync::ResyncFromResponse,
+            set_tile::SetTileResponse,
+            snapshot::BoardSnapshotResponse,
+            spectate::SpectateResponse,
+            start::StartResponse,
+            subscribe::SubscribeGameResponse,
+            tile::Tile,
+            vote_kick::{AnswerVoteKickResponse, KickVote, StartVoteKickResponse},
+            words::{TurnScore, Words},
+        },
+        leaderboard::{
+            daily::{DailyLeaderboardResponse, LeaderboardEntry},
+            season::{SeasonInfoResponse, SeasonReward},
+        },
+        lobby::{
+            broadcast::{LobbyBroadcast, LobbyEvent},
+            create::CreateResponse,
+            join::JoinResponse,
+            kick::KickFromLobbyResponse,
+            list::{ListResponse, LobbyInfo, LobbyInfos},
+            lobby::Lobby,
+            quit::QuitResponse,
+            ready::ReadyResponse,
+            rules::{BoardLayout, Coop, Rules, Scoring, TurnOrder},
+            update_settings::UpdateLobbySettingsResponse,
+            upload_dictionary::UploadDictionaryResponse,
+        },
+        player::{
+            block::BlockPlayerResponse,
+            mute::MutePlayerResponse,
+            player::Player,
+            players::Players,
+            profile::{Profile, TileColor, Title, UpdateProfileResponse},
+            report::ReportPlayerResponse,
+            stats::{GetPlayerStatsResponse, PlayerStats},
+        },
+        state::State,
+    },
+};
+
+/// Request id every sample reply answers.
+const REQUEST_ID: u32 = 7;
+
+/// The variant a sample is filed under. Adding a variant fails to compile
+/// here, so it gets a sample in `samples` too.
+fn variant(data: &ResponseData) -> &'static str {
+    match data {
+        ResponseData::Error(_) => "error",
+        ResponseData::Connect(_) => "connect",
+        ResponseData::Disconnect(_) => "disconnect",
+        ResponseData::Cancel(_) => "cancel",
+        ResponseData::Heartbeat(_) => "heartbeat",
+        ResponseData::CreateLobby(_) => "create_lobby",
+        ResponseData::JoinLobby(_) => "join_lobby",
+        ResponseData::QuitLobby(_) => "quit_lobby",
+        ResponseData::ListLobby(_) => "list_lobby",
+        ResponseData::Ready(_) => "ready",
+        ResponseData::StartGame(_) => "start_game",
+        ResponseData::LobbyBroadcast(_) => "lobby_broadcast",
+        ResponseData::SetTile(_) => "set_tile",
+        ResponseData::FinishTurn(_) => "finish_turn",
+        ResponseData::GetNewCard(_) => "get_new_card",
+        ResponseData::GameBroadcast(_) => "game_broadcast",
+        ResponseData::Exit(_) => "exit",
+        ResponseData::PauseGame(_) => "pause_game",
+        ResponseData::ResumeGame(_) => "resume_game",
+        ResponseData::GetHint(_) => "get_hint",
+        ResponseData::GetPlayerStats(_) => "get_player_stats",
+        ResponseData::StartDailyChallenge(_) => "start_daily_challenge",
+        ResponseData::GetDailyLeaderboard(_) => "get_daily_leaderboard",
+        ResponseData::SendChat(_) => "send_chat",
+        ResponseData::ReportPlayer(_) => "report_player",
+        ResponseData::MutePlayer(_) => "mute_player",
+        ResponseData::ListSupportedOpcodes(_) => "list_supported_opcodes",
+        ResponseData::UploadDictionary(_) => "upload_dictionary",
+        ResponseData::Resign(_) => "resign",
+        ResponseData::OfferDraw(_) => "offer_draw",
+        ResponseData::AnswerDraw(_) => "answer_draw",
+        ResponseData::JoinByCode(_) => "join_by_code",
+        ResponseData::QuickJoin(_) => "quick_join",
+        ResponseData::UpdateProfile(_) => "update_profile",
+        ResponseData::BlockPlayer(_) => "block_player",
+        ResponseData::KickFromLobby(_) => "kick_from_lobby",
+        ResponseData::ServerInfo(_) => "server_info",
+        ResponseData::GetSeasonInfo(_) => "get_season_info",
+        ResponseData::Spectate(_) => "spectate",
+        ResponseData::ExportGame(_) => "export_game",
+        ResponseData::AnalyzeGame(_) => "analyze_game",
+        ResponseData::UpdateLobbySettings(_) => "update_lobby_settings",
+        ResponseData::StartSolo(_) => "start_solo",
+        ResponseData::SendQuickChat(_) => "send_quick_chat",
+        ResponseData::TimeSync(_) => "time_sync",
+        ResponseData::SetPreferences(_) => "set_preferences",
+        ResponseData::BoardSnapshot(_) => "board_snapshot",
+        ResponseData::ResyncFrom(_) => "resync_from",
+        ResponseData::GetBagInfo(_) => "get_bag_info",
+        ResponseData::ExchangeCards(_) => "exchange_cards",
+        ResponseData::StartVoteKick(_) => "start_vote_kick",
+        ResponseData::AnswerVoteKick(_) => "answer_vote_kick",
+        ResponseData::SubscribeGame(_) => "subscribe_game",
+        ResponseData::AdminListPlayers(_) => "admin_list_players",
+        ResponseData::AdminKick(_) => "admin_kick",
+        ResponseData::AdminBan(_) => "admin_ban",
+        ResponseData::AdminAnnounce(_) => "admin_announce",
+        ResponseData::AdminMaintenance(_) => "admin_maintenance",
+        ResponseData::AdminInspect(_) => "admin_inspect",
+        ResponseData::AdminCloseGame(_) => "admin_close_game",
+        ResponseData::AdminReload(_) => "admin_reload",
+        ResponseData::AdminIssueToken(_) => "admin_issue_token",
+        ResponseData::AdminRevokeToken(_) => "admin_revoke_token",
+        ResponseData::AdminHandoff(_) => "admin_handoff",
+        ResponseData::Notice(_) => "notice",
+        ResponseData::ChatBroadcast(_) => "chat_broadcast",
+    }
+}
+
+fn player(id: u32, name: &str) -> Player {
+    Player {
+        id,
+        name: String::from(name),
+        ping_ms: Some(40 + id),
+        profile: Some(Profile {
+            avatar_id: id + 1,
+            tile_color: TileColor::Ocean as i32,
+            title: Title::Champion as i32,
+        }),
+    }
+}
+
+fn players() -> Vec<Player> {
+    vec![player(1, "alice"), player(2, "bob")]
+}
+
+fn rules() -> Rules {
+    Rules {
+        board_size: 15,
+        turn_duration: 60,
+        hand_size: 7,
+        language: String::from("en"),
+        scoring: Scoring::WordLength as i32,
+        layout: BoardLayout::Classic as i32,
+        hint_budget: Some(3),
+        turn_order: TurnOrder::JoinOrder as i32,
+        streak_multipliers: true,
+        quick_chat_only: true,
+        letter_odds: Some(true),
+        territory: true,
+        power_ups: true,
+        coop: Some(Coop {
+            target: 100,
+            rounds: 10,
+        }),
+    }
+}
+
+fn lobby() -> Lobby {
+    Lobby {
+        id: 3,
+        players: players(),
+        rules: Some(rules()),
+        max_players: 4,
+        min_players_to_start: 2,
+        waitlist: vec![player(5, "carol")],
+        code: Some(String::from("ABCD")),
+    }
+}
+
+fn tile(owner: u32, char: &str) -> Tile {
+    Tile {
+        owner,
+        char: String::from(char),
+    }
+}
+
+/// A 2x2 board with one tile, the full 26x26 one only adds rows.
+fn board() -> Board {
+    Board {
+        rows: vec![
+            Row {
+                columns: vec![
+                    Column {
+                        tile: Some(tile(1, "a")),
+                    },
+                    Column { tile: None },
+                ],
+            },
+            Row {
+                columns: vec![Column { tile: None }, Column { tile: None }],
+            },
+        ],
+    }
+}
+
+fn layout() -> Layout {
+    Layout {
+        size: 15,
+        premiums: vec![PremiumSquare {
+            x: 7,
+            y: 7,
+            premium: Premium::DoubleWord as i32,
+        }],
+    }
+}
+
+/// One played slot and one still empty.
+fn cards() -> Cards {
+    Cards {
+        cards: vec![
+            HandCard {
+                card: Some(Card {
+                    symbol: String::from("q"),
+                    power_up: PowerUp::StealLetter as i32,
+                }),
+            },
+            HandCard { card: None },
+        ],
+    }
+}
+
+fn words() -> Words {
+    Words {
+        words: vec![String::from("at"), String::from("cat")],
+        definitions: vec![String::new(), String::from("A small feline")],
+    }
+}
+
+fn score() -> TurnScore {
+    TurnScore {
+        points: 12,
+        bingo_bonus: 50,
+        multiplier: 2,
+    }
+}
+
+fn pause() -> PauseState {
+    PauseState {
+        paused: true,
+        votes: 1,
+        votes_needed: 2,
+    }
+}
+
+fn draw() -> DrawState {
+    DrawState {
+        votes: 1,
+        votes_needed: 2,
+    }
+}
+
+fn kick_vote() -> KickVote {
+    KickVote {
+        target_id: 3,
+        votes: 1,
+        votes_against: 1,
+        votes_needed: 2,
+        deadline: 1_700_000_030_000,
+    }
+}
+
+fn entry(rank: u32, name: &str, score: u32) -> LeaderboardEntry {
+    LeaderboardEntry {
+        rank,
+        name: String::from(name),
+        score,
+    }
+}
+
+fn chat() -> ChatBroadcast {
+    ChatBroadcast {
+        player: Some(player(1, "alice")),
+        message: String::from("hi"),
+        quick_chat: None,
+    }
+}
+
+fn start() -> StartResponse {
+    StartResponse {
+        success: true,
+        board: Some(board()),
+        cards: Some(cards()),
+        current_player: Some(player(1, "alice")),
+        next_player: Some(player(2, "bob")),
+        layout: Some(layout()),
+        day: Some(20000),
+        turn_order: players(),
+        turn_deadline: Some(1_700_000_060_000),
+    }
+}
+
+fn join() -> JoinResponse {
+    JoinResponse {
+        success: true,
+        lobby: Some(lobby()),
+        waitlist_position: Some(1),
+        chat_history: vec![chat()],
+    }
+}
+
+fn lobby_event(event: LobbyEvent) -> LobbyBroadcast {
+    LobbyBroadcast {
+        event: event as i32,
+        lobby: Some(lobby()),
+        ..Default::default()
+    }
+}
+
+fn game_event(event: GameEvent) -> GameBroadcast {
+    GameBroadcast {
+        event: event as i32,
+        state_version: 9,
+        ..Default::default()
+    }
+}
+
+fn reply(data: ResponseData) -> (&'static str, Response) {
+    ("", Response::new(REQUEST_ID, Arc::new(data)))
+}
+
+fn event(name: &'static str, state: State, data: ResponseData) -> (&'static str, Response) {
+    (name, Response::event(state, Arc::new(data)))
+}
+
+/// Every response with each of its fields set, and each kind of event
+/// with the fields it's sent with. Replies are named after their variant,
+/// events after it and the event.
+fn samples() -> Vec<(&'static str, Response)> {
+    vec![
+        reply(ResponseData::Error(Error {
+            message: String::from("Not your turn"),
+            code: ErrorCode::NotYourTurn as i32,
+        })),
+        reply(ResponseData::Connect(ConnectResponse {
+            success: true,
+            player: Some(player(1, "alice")),
+            protocol_version: 2,
+            compression: Compression::Lz4 as i32,
+            game: Some(start()),
+            capabilities: Capability::TurnEvents as u64 | Capability::BoardDeltas as u64,
+            scopes: Scope::Play as u64 | Scope::Spectate as u64,
+            lobby: Some(lobby()),
+        })),
+        reply(ResponseData::Disconnect(DisconnectResponse {
+            success: true,
+        })),
+        reply(ResponseData::Cancel(CancelResponse {
+            success: true,
+            cards: Some(cards()),
+        })),
+        reply(ResponseData::Heartbeat(HeartbeatResponse {
+            success: true,
+            sent_at: 1_700_000_000_000,
+            server_time: 1_700_000_000_020,
+            average_rtt_ms: Some(40),
+        })),
+        reply(ResponseData::CreateLobby(CreateResponse {
+            success: true,
+            lobby: Some(lobby()),
+        })),
+        reply(ResponseData::JoinLobby(join())),
+        reply(ResponseData::QuitLobby(QuitResponse { success: true })),
+        reply(ResponseData::ListLobby(ListResponse {
+            success: true,
+            lobby_infos: Some(LobbyInfos {
+                lobby_infos: vec![LobbyInfo {
+                    id: 3,
+                    max_players: 4,
+                    current_players: 2,
+                }],
+            }),
+        })),
+        reply(ResponseData::Ready(ReadyResponse { success: true })),
+        reply(ResponseData::StartGame(start())),
+        reply(ResponseData::SetTile(SetTileResponse { success: true })),
+        reply(ResponseData::FinishTurn(FinishTurnResponse {
+            success: true,
+            current_player: Some(player(2, "bob")),
+            next_player: Some(player(1, "alice")),
+            cards: Some(cards()),
+            words: Some(words()),
+            score: Some(score()),
+        })),
+        reply(ResponseData::GetNewCard(GetNewCardResponse {
+            success: true,
+            cards: Some(cards()),
+        })),
+        reply(ResponseData::Exit(ExitResponse { success: true })),
+        reply(ResponseData::PauseGame(PauseResponse {
+            success: true,
+            pause: Some(pause()),
+        })),
+        reply(ResponseData::ResumeGame(ResumeResponse {
+            success: true,
+            pause: Some(PauseState {
+                paused: false,
+                ..pause()
+            }),
+        })),
+        reply(ResponseData::GetHint(HintResponse {
+            success: true,
+            word: String::from("cat"),
+            tiles: vec![HintTile {
+                x: 7,
+                y: 8,
+                card_index: 1,
+            }],
+            score: 5,
+            hints_left: 2,
+        })),
+        reply(ResponseData::GetPlayerStats(GetPlayerStatsResponse {
+            success: true,
+            stats: Some(PlayerStats {
+                name: String::from("alice"),
+                games_played: 10,
+                games_won: 4,
+                win_rate: 0.4,
+                average_score: 120.5,
+                best_word: String::from("quartz"),
+                best_word_score: 48,
+                tiles_placed: 300,
+                longest_word: String::from("alphabet"),
+                games_resigned: 1,
+            }),
+        })),
+        reply(ResponseData::StartDailyChallenge(start())),
+        reply(ResponseData::GetDailyLeaderboard(
+            DailyLeaderboardResponse {
+                success: true,
+                day: 20000,
+                entries: vec![entry(1, "alice", 90), entry(2, "bob", 80)],
+            },
+        )),
+        reply(ResponseData::SendChat(SendChatResponse {
+            success: false,
+            message: String::from("Muted"),
+        })),
+        reply(ResponseData::ReportPlayer(ReportPlayerResponse {
+            success: true,
+        })),
+        reply(ResponseData::MutePlayer(MutePlayerResponse {
+            success: true,
+        })),
+        reply(ResponseData::ListSupportedOpcodes(
+            ListSupportedOpcodesResponse {
+                success: true,
+                opcodes: vec![Opcode {
+                    code: 0,
+                    name: String::from("Connect"),
+                }],
+            },
+        )),
+        reply(ResponseData::UploadDictionary(UploadDictionaryResponse {
+            success: true,
+            complete: true,
+            word_count: 2,
+        })),
+        reply(ResponseData::Resign(ResignResponse { success: true })),
+        reply(ResponseData::OfferDraw(OfferDrawResponse {
+            success: true,
+            draw: Some(draw()),
+        })),
+        reply(ResponseData::AnswerDraw(AnswerDrawResponse {
+            success: true,
+            draw: Some(draw()),
+        })),
+        reply(ResponseData::JoinByCode(join())),
+        reply(ResponseData::QuickJoin(join())),
+        reply(ResponseData::UpdateProfile(UpdateProfileResponse {
+            profile: player(1, "alice").profile,
+        })),
+        reply(ResponseData::BlockPlayer(BlockPlayerResponse {
+            success: true,
+        })),
+        reply(ResponseData::KickFromLobby(KickFromLobbyResponse {
+            success: true,
+        })),
+        reply(ResponseData::ServerInfo(ServerInfoResponse {
+            success: true,
+            name: String::from("letter-legend"),
+            region: String::from("eu"),
+            version: String::from("1.0.0"),
+            players: 2,
+            max_players: 100,
+            lobbies: 1,
+            games: 1,
+            motd: String::from("Welcome"),
+            cluster_players: 5,
+        })),
+        reply(ResponseData::GetSeasonInfo(SeasonInfoResponse {
+            success: true,
+            season: 4,
+            starts_at: 1_700_000_000,
+            ends_at: 1_702_592_000,
+            rating: 1516,
+            rank: 2,
+            standings: vec![entry(1, "bob", 1620), entry(2, "alice", 1516)],
+            previous_reward: SeasonReward::Gold as i32,
+        })),
+        reply(ResponseData::Spectate(SpectateResponse {
+            success: true,
+            delay_secs: 30,
+        })),
+        reply(ResponseData::ExportGame(ExportGameResponse {
+            success: true,
+            notation: String::from("#player1 alice alice\n"),
+        })),
+        reply(ResponseData::AnalyzeGame(AnalyzeGameResponse {
+            success: true,
+            turns: vec![TurnAnalysis {
+                turn: 1,
+                player_id: 1,
+                points: 5,
+                best_word: String::from("cat"),
+                best_tiles: vec![HintTile {
+                    x: 7,
+                    y: 7,
+                    card_index: 0,
+                }],
+                best_points: 10,
+                points_left: 5,
+            }],
+        })),
+        reply(ResponseData::UpdateLobbySettings(
+            UpdateLobbySettingsResponse {
+                success: true,
+                lobby: Some(lobby()),
+            },
+        )),
+        reply(ResponseData::StartSolo(start())),
+        reply(ResponseData::SendQuickChat(SendQuickChatResponse {
+            success: false,
+            message: String::from("Too fast"),
+        })),
+        reply(ResponseData::TimeSync(TimeSyncResponse {
+            success: true,
+            client_sent_at: 1_700_000_000_000,
+            server_received_at: 1_700_000_000_010,
+            server_sent_at: 1_700_000_000_011,
+        })),
+        reply(ResponseData::SetPreferences(SetPreferencesResponse {
+            success: true,
+        })),
+        reply(ResponseData::BoardSnapshot(BoardSnapshotResponse {
+            success: true,
+        })),
+        reply(ResponseData::ResyncFrom(ResyncFromResponse {
+            success: true,
+            replayed: true,
+        })),
+        reply(ResponseData::GetBagInfo(GetBagInfoResponse {
+            success: true,
+            turns_left: 12,
+            language: String::from("en"),
+            letters: vec![
+                LetterOdds {
+                    letter: String::from("a"),
+                    per_mille: 75,
+                },
+                LetterOdds {
+                    letter: String::from("b"),
+                    per_mille: 30,
+                },
+            ],
+        })),
+        reply(ResponseData::ExchangeCards(ExchangeCardsResponse {
+            success: true,
+            cards: Some(cards()),
+        })),
+        reply(ResponseData::StartVoteKick(StartVoteKickResponse {
+            success: true,
+            vote: Some(kick_vote()),
+        })),
+        reply(ResponseData::AnswerVoteKick(AnswerVoteKickResponse {
+            success: true,
+            vote: Some(kick_vote()),
+        })),
+        reply(ResponseData::SubscribeGame(SubscribeGameResponse {
+            success: true,
+            state_version: 41,
+        })),
+        reply(ResponseData::AdminListPlayers(ListPlayersResponse {
+            success: true,
+            players: vec![OnlinePlayer {
+                id: 1,
+                name: String::from("alice"),
+                lobby_id: Some(3),
+                game_id: Some(4),
+            }],
+        })),
+        reply(ResponseData::AdminKick(KickResponse { success: true })),
+        reply(ResponseData::AdminBan(BanResponse { success: true })),
+        reply(ResponseData::AdminAnnounce(AnnounceResponse {
+            success: true,
+            recipients: 2,
+        })),
+        reply(ResponseData::AdminMaintenance(MaintenanceResponse {
+            success: true,
+            enabled: true,
+        })),
+        reply(ResponseData::AdminInspect(InspectResponse {
+            success: true,
+            lobby: Some(LobbyDetail {
+                id: 3,
+                max_players: 4,
+                leader: Some(player(1, "alice")),
+                members: vec![LobbyMember {
+                    player: Some(player(2, "bob")),
+                    ready: true,
+                }],
+            }),
+            game: Some(GameDetail {
+                id: 4,
+                turn: 2,
+                players: players(),
+                current_player: Some(player(2, "bob")),
+                tiles: 5,
+            }),
+        })),
+        reply(ResponseData::AdminCloseGame(CloseGameResponse {
+            success: true,
+        })),
+        reply(ResponseData::AdminReload(ReloadResponse { success: true })),
+        reply(ResponseData::AdminIssueToken(IssueTokenResponse {
+            success: true,
+            api_token: String::from("token"),
+        })),
+        reply(ResponseData::AdminRevokeToken(RevokeTokenResponse {
+            success: true,
+            revoked: true,
+        })),
+        reply(ResponseData::AdminHandoff(HandoffResponse {
+            success: true,
+            lobbies: 1,
+            players: 2,
+        })),
+        event(
+            "join",
+            State::LobbyBroadcast,
+            ResponseData::LobbyBroadcast(lobby_event(LobbyEvent::Join)),
+        ),
+        event(
+            "start",
+            State::LobbyBroadcast,
+            ResponseData::LobbyBroadcast(LobbyBroadcast {
+                cards: Some(cards()),
+                current_player: Some(player(1, "alice")),
+                next_player: Some(player(2, "bob")),
+                layout: Some(layout()),
+                turn_order: players(),
+                ..lobby_event(LobbyEvent::Start)
+            }),
+        ),
+        event(
+            "kicked",
+            State::LobbyBroadcast,
+            ResponseData::LobbyBroadcast(LobbyBroadcast {
+                player: Some(player(2, "bob")),
+                chat_history: vec![chat()],
+                ..lobby_event(LobbyEvent::Kicked)
+            }),
+        ),
+        event(
+            "place_tile",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                board: Some(board()),
+                board_version: Some(3),
+                ..game_event(GameEvent::PlaceTile)
+            }),
+        ),
+        event(
+            "place_tile_delta",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                board_delta: Some(BoardDelta {
+                    version: 3,
+                    squares: vec![
+                        ChangedSquare {
+                            x: 7,
+                            y: 7,
+                            tile: Some(tile(1, "a")),
+                        },
+                        ChangedSquare {
+                            x: 8,
+                            y: 7,
+                            tile: None,
+                        },
+                    ],
+                }),
+                board_version: Some(3),
+                ..game_event(GameEvent::PlaceTile)
+            }),
+        ),
+        event(
+            "finish_turn",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                players: Some(Players { players: players() }),
+                current_player: Some(player(2, "bob")),
+                next_player: Some(player(1, "alice")),
+                words: Some(words()),
+                score: Some(score()),
+                coop: Some(CoopState {
+                    score: 12,
+                    target: 100,
+                    turns_left: 9,
+                    won: false,
+                }),
+                ..game_event(GameEvent::FinishTurn)
+            }),
+        ),
+        event(
+            "your_turn",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                current_player: Some(player(2, "bob")),
+                turn_deadline: Some(1_700_000_060_000),
+                ..game_event(GameEvent::YourTurn)
+            }),
+        ),
+        event(
+            "hand_update",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                player: Some(player(1, "alice")),
+                hand_count: Some(7),
+                cards: Some(cards()),
+                ..game_event(GameEvent::HandUpdate)
+            }),
+        ),
+        event(
+            "pause",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                pause: Some(pause()),
+                ..game_event(GameEvent::Pause)
+            }),
+        ),
+        event(
+            "forfeit",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                forfeited: Some(player(2, "bob")),
+                ..game_event(GameEvent::Forfeit)
+            }),
+        ),
+        event(
+            "resign",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                resigned: Some(player(2, "bob")),
+                ..game_event(GameEvent::Resign)
+            }),
+        ),
+        event(
+            "draw_offer",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                draw: Some(draw()),
+                ..game_event(GameEvent::DrawOffer)
+            }),
+        ),
+        event(
+            "vote_kick",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                kick_vote: Some(kick_vote()),
+                player: Some(player(1, "alice")),
+                ..game_event(GameEvent::VoteKick)
+            }),
+        ),
+        event(
+            "player_disconnected",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                player: Some(player(2, "bob")),
+                disconnect_grace: Some(60),
+                reconnect_deadline: Some(1_700_000_060_000),
+                ..game_event(GameEvent::PlayerDisconnected)
+            }),
+        ),
+        event(
+            "power_up",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(GameBroadcast {
+                player: Some(player(1, "alice")),
+                power_up: Some(PowerUp::FreezeTimer as i32),
+                target: Some(player(2, "bob")),
+                ..game_event(GameEvent::PowerUp)
+            }),
+        ),
+        event(
+            "destroy",
+            State::GameBroadcast,
+            ResponseData::GameBroadcast(game_event(GameEvent::Destroy)),
+        ),
+        event(
+            "",
+            State::Notice,
+            ResponseData::Notice(Notice {
+                message: String::from("Back in five"),
+                maintenance: true,
+                redirect: String::new(),
+            }),
+        ),
+        event(
+            "session_replaced",
+            State::SessionReplaced,
+            ResponseData::Notice(Notice {
+                message: String::from("Connected from another session"),
+                ..Default::default()
+            }),
+        ),
+        event(
+            "moved",
+            State::Moved,
+            ResponseData::Notice(Notice {
+                message: String::from("Lobby moved"),
+                maintenance: false,
+                redirect: String::from("10.0.0.2:8080"),
+            }),
+        ),
+        event("", State::Chat, ResponseData::ChatBroadcast(chat())),
+        event(
+            "quick_chat",
+            State::Chat,
+            ResponseData::ChatBroadcast(ChatBroadcast {
+                message: String::from("Good luck!"),
+                quick_chat: Some(QuickChat::GoodLuck as i32),
+                ..chat()
+            }),
+        ),
+        event(
+            "error",
+            State::Error,
+            ResponseData::Error(Error {
+                message: String::from("Server is full"),
+                code: ErrorCode::ServerFull as i32,
+            }),
+        ),
+    ]
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// One line per framing of every sample, as `name framing bytes`.
+fn render() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let mut out = String::new();
+    for (event, res) in samples() {
+        let name = match event {
+            "" => String::from(variant(&res.get_data())),
+            event => format!("{}.{}", variant(&res.get_data()), event),
+        };
+        out += &format!("{} legacy {}\n", name, hex(&res.to_bytes()?));
+        out += &format!("{} envelope {}\n", name, hex(&res.to_envelope_bytes()?));
+    }
+    Ok(out)
+}
+
+#[test]
+fn encoded_responses_should_match_golden_file(
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/responses.txt");
+    let actual = render()?;
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual)?;
+        return Ok(());
+    }
+    let expected = fs::read_to_string(&path)?;
+    let changed: Vec<_> = expected
+        .lines()
+        .zip(actual.lines())
+        .filter(|(expected, actual)| expected != actual)
+        .map(|(expected, actual)| format!("- {}\n+ {}", expected, actual))
+        .collect();
+    assert!(
+        changed.is_empty() && expected.lines().count() == actual.lines().count(),
+        "wire format changed, rerun with UPDATE_GOLDEN=1 if that's intended:\n{}",
+        changed.join("\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn samples_should_have_unique_names() {
+    let mut names: Vec<_> = samples()
+        .into_iter()
+        .map(|(event, res)| (variant(&res.get_data()), event))
+        .collect();
+    let count = names.len();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), count);
+}
@@ -0,0 +1,259 @@
+//! Invariants of the rules engine over random games. Each case deals a game
+//! from a random seed and rules, then plays random moves at it the way
+//! `GameService` would, checking the game after every one.
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, OnceLock},
+};
+
+use backend::{
+    game::{
+        board::{Board, BoardLayout, Premium, CLASSIC_BOARD_SIZE},
+        event::Event,
+        game::{Game, END_GAME_TURN},
+        invariant,
+        rules::{GameRules, Scoring, MAX_HAND_SIZE, MIN_HAND_SIZE},
+        scoring::{letter_value, score_turn, score_word},
+    },
+    player::Player,
+};
+use proptest::prelude::*;
+
+fn dictionary() -> &'static HashSet<String> {
+    static DICTIONARY: OnceLock<HashSet<String>> = OnceLock::new();
+    DICTIONARY.get_or_init(|| {
+        include_str!("fixtures/wordlist.txt")
+            .lines()
+            .map(String::from)
+            .collect()
+    })
+}
+
+#[derive(Debug, Clone)]
+enum Move {
+    /// Offsets are from the centre square, to keep most tiles in play.
+    Place {
+        card_index: usize,
+        dx: i8,
+        dy: i8,
+    },
+    Remove {
+        dx: i8,
+        dy: i8,
+    },
+    Shuffle,
+    /// Indices may be out of the hand or repeat, which must be refused.
+    Exchange(Vec<usize>),
+    Finish,
+    /// The same move by whoever isn't on turn, which must be refused.
+    OutOfTurn(Box<Move>),
+}
+
+fn a_move() -> impl Strategy<Value = Move> {
+    let own = prop_oneof![
+        6 => (0..MAX_HAND_SIZE, -3i8..=3, -3i8..=3)
+            .prop_map(|(card_index, dx, dy)| Move::Place { card_index, dx, dy }),
+        2 => (-3i8..=3, -3i8..=3).prop_map(|(dx, dy)| Move::Remove { dx, dy }),
+        1 => Just(Move::Shuffle),
+        1 => prop::collection::vec(0..=MAX_HAND_SIZE, 0..=MAX_HAND_SIZE + 1).prop_map(Move::Exchange),
+        2 => Just(Move::Finish),
+    ];
+    prop_oneof![
+        9 => own.clone(),
+        1 => own.prop_map(|own| Move::OutOfTurn(Box::new(own))),
+    ]
+}
+
+fn rules() -> impl Strategy<Value = GameRules> {
+    (
+        MIN_HAND_SIZE..=MAX_HAND_SIZE,
+        prop_oneof![Just(Scoring::Classic), Just(Scoring::WordLength)],
+        prop_oneof![
+            Just(BoardLayout::Plain),
+            Just(BoardLayout::Classic),
+            Just(BoardLayout::RandomPremium)
+        ],
+    )
+        .prop_map(|(hand_size, scoring, layout)| GameRules {
+            hand_size,
+            scoring,
+            layout,
+            board_size: match layout {
+                BoardLayout::Classic => CLASSIC_BOARD_SIZE,
+                _ => GameRules::default().board_size,
+            },
+            ..GameRules::default()
+        })
+}
+
+fn premium() -> impl Strategy<Value = Option<Premium>> {
+    prop_oneof![
+        Just(None),
+        Just(Some(Premium::DoubleLetter)),
+        Just(Some(Premium::TripleLetter)),
+        Just(Some(Premium::DoubleWord)),
+        Just(Some(Premium::TripleWord)),
+    ]
+}
+
+/// Board tiles, each player's hand and the turn.
+type State = (Vec<String>, Vec<Vec<(char, bool)>>, u32);
+
+/// What the game looks like to compare before and after a refused move.
+fn state(game: &Game) -> State {
+    let board = game.get_board().lock().unwrap().clone();
+    let tiles = board
+        .tiles
+        .iter()
+        .flatten()
+        .map(|tile| format!("{:?}", tile.as_ref().map(|tile| (tile.char, tile.turn))))
+        .collect();
+    let hands = game
+        .get_players()
+        .iter()
+        .map(|game_player| {
+            game_player
+                .get_cards()
+                .iter()
+                .map(|card| (card.char, card.used))
+                .collect()
+        })
+        .collect();
+    (tiles, hands, game.get_turns())
+}
+
+fn to_event(game: &Arc<Game>, player_id: u32, centre: usize, mv: &Move) -> Event {
+    let square = |dx: i8, dy: i8| {
+        (
+            centre.wrapping_add_signed(dx.into()),
+            centre.wrapping_add_signed(dy.into()),
+        )
+    };
+    match *mv {
+        Move::Place { card_index, dx, dy } => {
+            let (x, y) = square(dx, dy);
+            Event::PlaceTile {
+                player_id,
+                card_index,
+                x,
+                y,
+            }
+        }
+        Move::Remove { dx, dy } => {
+            let (x, y) = square(dx, dy);
+            Event::RemoveTile { player_id, x, y }
+        }
+        Move::Shuffle => Event::Shuffle { player_id },
+        Move::Exchange(ref card_indices) => Event::Exchange {
+            player_id,
+            card_indices: card_indices.clone(),
+        },
+        // As `GameService::timeout_finish_turn` does, the turn's tiles only
+        // stay when the board validates
+        Move::Finish => Event::EndTurn {
+            keep_tiles: game
+                .get_board()
+                .lock()
+                .unwrap()
+                .validate(dictionary(), game.clone())
+                .is_some(),
+        },
+        Move::OutOfTurn(_) => unreachable!("out of turn moves are unwrapped by the caller"),
+    }
+}
+
+proptest! {
+    #[test]
+    fn score_word_should_be_at_least_face_value(
+        letters in prop::collection::vec(("[a-z]", premium()), 1..=MAX_HAND_SIZE),
+    ) {
+        let letters: Vec<(char, Option<Premium>)> = letters
+            .into_iter()
+            .map(|(char, premium)| (char.chars().next().unwrap(), premium))
+            .collect();
+        let face_value: u32 = letters.iter().map(|(char, _)| letter_value(*char)).sum();
+        prop_assert!(score_word(Scoring::Classic, &letters) >= face_value);
+        prop_assert!(score_word(Scoring::WordLength, &letters) >= letters.len() as u32);
+        let plain: Vec<(char, Option<Premium>)> =
+            letters.iter().map(|(char, _)| (*char, None)).collect();
+        prop_assert_eq!(score_word(Scoring::Classic, &plain), face_value);
+        prop_assert_eq!(score_word(Scoring::WordLength, &plain), letters.len() as u32);
+    }
+
+    #[test]
+    fn random_moves_should_keep_game_consistent(
+        seed in any::<u64>(),
+        rules in rules(),
+        moves in prop::collection::vec(a_move(), 1..120),
+    ) {
+        let players = (0..2)
+            .map(|id| Arc::new(Player::new(id, format!("test{}", id))))
+            .collect();
+        let hand_size = rules.hand_size;
+        let centre = rules.board_size / 2;
+        let game = Arc::new(Game::with_seed(0, players, rules, seed));
+        for mv in &moves {
+            let current = game.get_player_in_this_turn();
+            let (player_id, mv) = match mv {
+                Move::OutOfTurn(mv) => {
+                    let other = game
+                        .get_players()
+                        .into_iter()
+                        .find(|game_player| *game_player != current)
+                        .unwrap();
+                    (other.player.id, mv.as_ref())
+                }
+                mv => (current.player.id, mv),
+            };
+            if game.get_turns() == END_GAME_TURN && matches!(mv, Move::Finish) {
+                // The service ends the game here rather than starting a turn
+                break;
+            }
+            let before = state(&game);
+            let score_before = current.get_tally().score;
+            let board_before = game.get_board().lock().unwrap().clone();
+            let event = to_event(&game, player_id, centre, mv);
+            let applied = game.apply(event.clone());
+
+            if applied.is_err() {
+                prop_assert_eq!(&state(&game), &before, "refused {:?} changed the game", event);
+                continue;
+            }
+            prop_assert!(
+                matches!(mv, Move::Finish) || player_id == current.player.id,
+                "{:?} accepted out of turn",
+                event
+            );
+            // Cards are conserved: every card used this turn is on the board
+            // and hands stay full
+            if let Err(violations) = invariant::check(&game) {
+                prop_assert!(false, "after {:?}: {:?}", event, violations);
+            }
+            for game_player in game.get_players() {
+                prop_assert_eq!(game_player.get_cards().len(), hand_size);
+            }
+            if let Event::EndTurn { keep_tiles } = event {
+                let committed: Board = game.get_board().lock().unwrap().clone();
+                prop_assert!(
+                    committed.validate(dictionary(), game.clone()).is_some(),
+                    "committed board doesn't validate"
+                );
+                let turn_score: u32 = match keep_tiles {
+                    true => score_turn(&board_before, before.2, game.get_rules().scoring)
+                        .iter()
+                        .map(|(_, score)| score)
+                        .sum(),
+                    false => 0,
+                };
+                prop_assert_eq!(current.get_tally().score, score_before + turn_score);
+                prop_assert!(committed
+                    .tiles
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .all(|tile| tile.turn < game.get_turns()));
+            }
+        }
+    }
+}
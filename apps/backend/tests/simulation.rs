@@ -0,0 +1,335 @@
+//! Whole-server flows over in-memory connections. Time is paused, so it only
+//! moves when every task is waiting on a timer and heartbeat and turn
+//! timeouts fire at exactly their deadline. Every single letter is in the
+//! fixture word list, so one tile makes a valid move.
+
+use std::{collections::VecDeque, error::Error, sync::Arc, time::Duration};
+
+use backend::{
+    config::{GameConfig, ServerConfig},
+    frame::{Request, RequestData},
+    model::{
+        control::{
+            connect::{Capability, ConnectRequest, ConnectResponse},
+            heartbeat::{HeartbeatRequest, HeartbeatResponse},
+        },
+        game::{
+            broadcast::{GameBroadcast, GameEvent},
+            cards::Cards,
+            finish_turn::FinishTurnResponse,
+            set_tile::{SetTileRequest, SetTileResponse},
+            start::StartResponse,
+        },
+        lobby::{
+            broadcast::{LobbyBroadcast, LobbyEvent},
+            create::{CreateRequest, CreateResponse},
+            join::{JoinRequest, JoinResponse},
+            ready::ReadyResponse,
+        },
+        state::{State, FIRST_REQUEST_ID},
+    },
+    server::Server,
+};
+use prost::Message;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, DuplexStream},
+    time::{self, Instant},
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+const TURN_DURATION: Duration = Duration::from_secs(30);
+
+/// Players are never forfeited unless a test asks for it.
+fn game_config() -> GameConfig {
+    GameConfig {
+        dictionary_path: format!("{}/tests/fixtures/wordlist.txt", env!("CARGO_MANIFEST_DIR")),
+        turn_duration_secs: TURN_DURATION.as_secs(),
+        snapshot_interval_secs: 0,
+        forfeit_after_missed_turns: 0,
+        ..GameConfig::default()
+    }
+}
+
+async fn new_server(heartbeat_timeout_secs: u64) -> Result<Arc<Server>> {
+    new_server_with_game(heartbeat_timeout_secs, game_config()).await
+}
+
+async fn new_server_with_game(
+    heartbeat_timeout_secs: u64,
+    game: GameConfig,
+) -> Result<Arc<Server>> {
+    let server = Server::new(ServerConfig {
+        heartbeat_timeout_secs,
+        game,
+        ..ServerConfig::default()
+    })
+    .await?;
+    // Everything from here on runs on the virtual clock
+    time::pause();
+    Ok(Arc::new(server))
+}
+
+struct Client {
+    stream: DuplexStream,
+    next_request_id: u32,
+    /// Events that arrived while waiting for a response.
+    events: VecDeque<(u32, Vec<u8>)>,
+}
+
+impl Client {
+    async fn connect(server: &Arc<Server>, name: &str) -> Result<(Self, ConnectResponse)> {
+        let mut client = Self {
+            stream: server.connect_in_memory()?,
+            next_request_id: FIRST_REQUEST_ID,
+            events: VecDeque::new(),
+        };
+        let res = client
+            .call(RequestData::Connect(ConnectRequest {
+                name: String::from(name),
+                capabilities: Capability::ALL,
+                ..Default::default()
+            }))
+            .await?;
+        Ok((client, res))
+    }
+
+    async fn call<T: Message + Default>(&mut self, data: RequestData) -> Result<T> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let bytes = Request::new(request_id, Arc::new(data)).to_bytes()?;
+        self.stream.write_all(&bytes).await?;
+        loop {
+            let (state, payload) = self.read_frame().await?.ok_or("connection closed")?;
+            if state == request_id {
+                return Ok(T::decode(payload.as_slice())?);
+            }
+            self.events.push_back((state, payload));
+        }
+    }
+
+    /// `None` once the server has closed the connection.
+    async fn read_frame(&mut self) -> Result<Option<(u32, Vec<u8>)>> {
+        let state = match self.stream.read_u32_le().await {
+            Ok(state) => state,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let len = self.stream.read_u32_le().await?;
+        let mut payload = vec![0; len as usize];
+        self.stream.read_exact(&mut payload).await?;
+        Ok(Some((state, payload)))
+    }
+
+    async fn next_event(&mut self, state: State) -> Result<Vec<u8>> {
+        let state = state as u32;
+        loop {
+            let (event_state, payload) = match self.events.pop_front() {
+                Some(event) => event,
+                None => self.read_frame().await?.ok_or("connection closed")?,
+            };
+            if event_state == state {
+                return Ok(payload);
+            }
+        }
+    }
+
+    async fn next_game_event(&mut self) -> Result<GameBroadcast> {
+        Ok(GameBroadcast::decode(
+            self.next_event(State::GameBroadcast).await?.as_slice(),
+        )?)
+    }
+}
+
+/// A started game between two players, `players[0]` leading the lobby.
+struct Table {
+    players: [Client; 2],
+    ids: [u32; 2],
+    hands: [Cards; 2],
+    first: usize,
+}
+
+async fn start_game(server: &Arc<Server>) -> Result<Table> {
+    let (mut alice, alice_res) = Client::connect(server, "alice").await?;
+    let (mut bob, bob_res) = Client::connect(server, "bob").await?;
+    let ids = [alice_res.player.unwrap().id, bob_res.player.unwrap().id];
+    let lobby: CreateResponse = alice
+        .call(RequestData::CreateLobby(CreateRequest {
+            max_players: 4,
+            ..Default::default()
+        }))
+        .await?;
+    let join: JoinResponse = bob
+        .call(RequestData::JoinLobby(JoinRequest {
+            lobby_id: lobby.lobby.unwrap().id,
+            waitlist: false,
+        }))
+        .await?;
+    assert!(join.success);
+    for client in [&mut alice, &mut bob] {
+        assert!(
+            client
+                .call::<ReadyResponse>(RequestData::Ready)
+                .await?
+                .success
+        );
+    }
+    let start: StartResponse = alice.call(RequestData::StartGame).await?;
+    assert!(start.success);
+    let started = loop {
+        let event =
+            LobbyBroadcast::decode(bob.next_event(State::LobbyBroadcast).await?.as_slice())?;
+        if event.event == LobbyEvent::Start as i32 {
+            break event;
+        }
+    };
+    let first = match start.current_player.unwrap().id == ids[0] {
+        true => 0,
+        false => 1,
+    };
+    Ok(Table {
+        players: [alice, bob],
+        ids,
+        hands: [start.cards.unwrap(), started.cards.unwrap()],
+        first,
+    })
+}
+
+#[tokio::test]
+async fn game_should_run_from_lobby_to_game_end() -> Result<()> {
+    let server = new_server(3600).await?;
+    let Table {
+        mut players,
+        ids,
+        hands,
+        first,
+    } = start_game(&server).await?;
+    let second = 1 - first;
+    let card_index = hands[first]
+        .cards
+        .iter()
+        .position(|hand_card| hand_card.card.is_some())
+        .unwrap();
+    let set: SetTileResponse = players[first]
+        .call(RequestData::SetTile(SetTileRequest {
+            x: 13,
+            y: 13,
+            card_index: card_index as u32,
+        }))
+        .await?;
+    assert!(set.success);
+    let finish: FinishTurnResponse = players[first].call(RequestData::FinishTurn).await?;
+    assert!(finish.success);
+    assert_eq!(finish.current_player.unwrap().id, ids[second]);
+    // Everyone announced board deltas, so the tile comes as a change
+    let mut placed = false;
+    loop {
+        let event = players[second].next_game_event().await?;
+        if event.event == GameEvent::PlaceTile as i32 {
+            let delta = event.board_delta.unwrap();
+            assert_eq!(delta.version, 1);
+            placed = delta
+                .squares
+                .iter()
+                .any(|square| (square.x, square.y) == (13, 13) && square.tile.is_some());
+        }
+        if event.event == GameEvent::FinishTurn as i32 {
+            assert_eq!(event.current_player.unwrap().id, ids[second]);
+            assert!(placed);
+            break;
+        }
+    }
+    // Everyone announced state versions, so the first player is sent their
+    // own turn as well
+    while players[first].next_game_event().await?.event != GameEvent::FinishTurn as i32 {}
+    // Nobody moves again, so every later turn runs out its timer
+    let idle_since = Instant::now();
+    let mut timed_out = 0;
+    loop {
+        let event = players[first].next_game_event().await?;
+        match GameEvent::from_i32(event.event) {
+            Some(GameEvent::FinishTurn) => timed_out += 1,
+            Some(GameEvent::Destroy) => break,
+            _ => (),
+        }
+    }
+    assert!(timed_out > 0);
+    // Timers fire on the next millisecond, whole seconds are exact
+    assert_eq!(
+        idle_since.elapsed().as_secs(),
+        TURN_DURATION.as_secs() * (timed_out + 1)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn turn_timeout_should_pass_turn_to_next_player() -> Result<()> {
+    let server = new_server(3600).await?;
+    let mut table = start_game(&server).await?;
+    let second = 1 - table.first;
+    let started_at = Instant::now();
+    let event = table.players[second].next_game_event().await?;
+    assert_eq!(event.event, GameEvent::TurnChanged as i32);
+    assert_eq!(event.current_player.unwrap().id, table.ids[table.first]);
+    let event = table.players[second].next_game_event().await?;
+    assert_eq!(event.event, GameEvent::FinishTurn as i32);
+    assert_eq!(event.current_player.unwrap().id, table.ids[second]);
+    assert_eq!(started_at.elapsed().as_secs(), TURN_DURATION.as_secs());
+    // The first player's refilled hand, of which only the size is shown
+    let event = table.players[second].next_game_event().await?;
+    assert_eq!(event.event, GameEvent::HandUpdate as i32);
+    assert_eq!(event.player.unwrap().id, table.ids[table.first]);
+    assert!(event.cards.is_none() && event.hand_count.is_some());
+    // Everyone is told whose turn it is, the player on turn included as they
+    // announced state versions, before they are told it is theirs
+    let event = table.players[second].next_game_event().await?;
+    assert_eq!(event.event, GameEvent::TurnChanged as i32);
+    assert_eq!(event.current_player.unwrap().id, table.ids[second]);
+    let event = table.players[second].next_game_event().await?;
+    assert_eq!(event.event, GameEvent::YourTurn as i32);
+    assert!(event.turn_deadline.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn idle_player_should_forfeit_after_missed_turns() -> Result<()> {
+    let server = new_server_with_game(
+        3600,
+        GameConfig {
+            forfeit_after_missed_turns: 2,
+            ..game_config()
+        },
+    )
+    .await?;
+    let mut table = start_game(&server).await?;
+    let second = 1 - table.first;
+    let started_at = Instant::now();
+    // The first player runs out of time twice before the second does
+    let event = loop {
+        let event = table.players[second].next_game_event().await?;
+        if event.event == GameEvent::Forfeit as i32 {
+            break event;
+        }
+    };
+    assert_eq!(event.forfeited.unwrap().id, table.ids[table.first]);
+    assert_eq!(started_at.elapsed().as_secs(), TURN_DURATION.as_secs() * 3);
+    // Nobody is left to play against
+    let event = table.players[second].next_game_event().await?;
+    assert_eq!(event.event, GameEvent::Destroy as i32);
+    Ok(())
+}
+
+#[tokio::test]
+async fn silent_client_should_be_disconnected_after_heartbeat_timeout() -> Result<()> {
+    let server = new_server(30).await?;
+    let (mut client, _) = Client::connect(&server, "alice").await?;
+    time::advance(Duration::from_secs(29)).await;
+    let res: HeartbeatResponse = client
+        .call(RequestData::Heartbeat(HeartbeatRequest::default()))
+        .await?;
+    assert!(res.success);
+    let last_heard = Instant::now();
+    assert!(client.read_frame().await?.is_none());
+    assert_eq!(last_heard.elapsed().as_secs(), 30);
+    Ok(())
+}
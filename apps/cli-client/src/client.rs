@@ -0,0 +1,126 @@
+use std::{collections::HashMap, error::Error, sync::Arc};
+
+use backend::{
+    frame::{decode_legacy_reply, Request, RequestData, PROTOCOL_VERSION},
+    model::{
+        control::connect::{ConnectRequest, ConnectResponse},
+        frame::envelope::ResponseEnvelope,
+        state::FIRST_REQUEST_ID,
+    },
+};
+use prost::Message;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream,
+    },
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+/// A frame from the server, either the answer to one of our requests or an
+/// event pushed at us, with the state it's tagged with.
+#[derive(Debug)]
+pub enum Incoming {
+    Response(Arc<RequestData>, ResponseEnvelope),
+    Event(u32, Vec<u8>),
+}
+
+pub struct GameClient {
+    writer: OwnedWriteHalf,
+    next_request_id: u32,
+    pending: HashMap<u32, Arc<RequestData>>,
+}
+
+impl GameClient {
+    /// Connect with `req`, asking for the envelope protocol, and start
+    /// reading frames in the background. They come out of the returned
+    /// receiver until the server hangs up.
+    pub async fn connect(
+        host: &str,
+        port: u16,
+        req: ConnectRequest,
+    ) -> Result<(Self, ConnectResponse, Receiver<ResponseEnvelope>), Box<dyn Error + Send + Sync>>
+    {
+        let mut stream = TcpStream::connect((host, port)).await?;
+        let req = ConnectRequest {
+            protocol_version: PROTOCOL_VERSION,
+            ..req
+        };
+        let bytes =
+            Request::new(FIRST_REQUEST_ID, Arc::new(RequestData::Connect(req))).to_bytes()?;
+        stream.write_all(&bytes).await?;
+        // The connect response is the last frame in the legacy encoding
+        let payload = loop {
+            let state = stream.read_u32_le().await?;
+            let len = stream.read_u32_le().await?;
+            let mut payload = vec![0; len as usize];
+            stream.read_exact(&mut payload).await?;
+            if state == FIRST_REQUEST_ID {
+                break payload;
+            }
+        };
+        let res: ConnectResponse = decode_legacy_reply(&payload)??;
+        if res.protocol_version != PROTOCOL_VERSION {
+            return Err("server doesn't speak the envelope protocol".into());
+        }
+        let (reader, writer) = stream.into_split();
+        let (tx, rx) = mpsc::channel(32);
+        tokio::spawn(read_frames(reader, tx));
+        Ok((
+            Self {
+                writer,
+                next_request_id: FIRST_REQUEST_ID + 1,
+                pending: HashMap::new(),
+            },
+            res,
+            rx,
+        ))
+    }
+
+    /// Send a request without waiting for the response, which comes back
+    /// through `resolve`.
+    pub async fn send(&mut self, data: RequestData) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let data = Arc::new(data);
+        let bytes = Request::new(request_id, data.clone()).to_envelope_bytes()?;
+        self.writer.write_all(&bytes).await?;
+        self.pending.insert(request_id, data);
+        Ok(())
+    }
+
+    /// Match a frame read off the connection with the request it answers.
+    /// Responses to requests we don't know about are dropped.
+    pub fn resolve(&mut self, envelope: ResponseEnvelope) -> Option<Incoming> {
+        let request_id = match envelope.request_id {
+            Some(request_id) => request_id,
+            None => {
+                let state = envelope.state.unwrap_or_default();
+                return Some(Incoming::Event(state, envelope.payload));
+            }
+        };
+        let req = self.pending.remove(&request_id)?;
+        Some(Incoming::Response(req, envelope))
+    }
+}
+
+async fn read_frames(mut reader: OwnedReadHalf, tx: Sender<ResponseEnvelope>) {
+    loop {
+        // Compression is never asked for, so the length has no flag set
+        let frame = async {
+            let len = reader.read_u32().await?;
+            let mut body = vec![0; len as usize];
+            reader.read_exact(&mut body).await?;
+            ResponseEnvelope::decode(&body[..]).map_err(std::io::Error::other)
+        };
+        match frame.await {
+            Ok(envelope) => {
+                if tx.send(envelope).await.is_err() {
+                    return;
+                }
+            }
+            Err(_) => return,
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use backend::{
+    frame::RequestData,
+    model::{
+        chat::{quick_chat::SendQuickChatRequest, send::SendChatRequest},
+        control::preferences::SetPreferencesRequest,
+        game::{
+            cancel::CancelRequest,
+            draw::AnswerDrawRequest,
+            exchange::ExchangeCardsRequest,
+            set_tile::SetTileRequest,
+            subscribe::SubscribeGameRequest,
+            vote_kick::{AnswerVoteKickRequest, StartVoteKickRequest},
+        },
+        lobby::{
+            create::CreateRequest,
+            join::{JoinByCodeRequest, JoinRequest},
+        },
+    },
+};
+
+pub const HELP: &str = "\
+lobbies                 list open lobbies
+create [max players]    create a lobby, 4 players by default
+join <lobby id>         join a lobby
+joincode <code>         join a lobby by its join code
+quickjoin               join any open lobby, or create one
+leave                   leave the lobby
+ready                   toggle ready
+start                   start the game, leader only
+solo                    start a practice game alone in your lobby
+board                   show the board and your hand
+place <card> <x> <y>    put a card from your hand on the board
+take <x> <y>            take back a tile placed this turn
+finish                  end your turn
+shuffle                 swap your hand for a new one
+exchange <card>...      swap just those cards for new ones
+bag                     show the turns left and how likely each letter is
+exit                    leave the game
+resign                  give up the game
+draw                    offer the others a draw
+accept                  accept the draw on offer
+decline                 turn the draw on offer down
+votekick <player id>    start a vote to kick a player out of the game
+kick                    vote for the open vote to kick
+keep                    vote against the open vote to kick
+follow [game id]        follow every event of a game live, with an observe
+                        token, stop following when empty
+say <message>           chat with your lobby or game
+quick <phrase>          send a quick chat phrase or emote by id
+mute [chat|emotes|spectators]...
+                        stop those pushes, unmute everything when empty
+quit                    disconnect
+";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    Board,
+    Quit,
+    /// Anything that is just a request to the server.
+    Send(RequestData),
+}
+
+/// Parse a line typed by the user, `None` for a blank line.
+pub fn parse(line: &str) -> Result<Option<Command>, String> {
+    let line = line.trim();
+    let (name, rest) = match line.split_once(char::is_whitespace) {
+        Some((name, rest)) => (name, rest.trim()),
+        None => (line, ""),
+    };
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    let command = match name {
+        "" => return Ok(None),
+        "help" => Command::Help,
+        "board" => Command::Board,
+        "quit" => Command::Quit,
+        "lobbies" => Command::Send(RequestData::ListLobby),
+        "create" => Command::Send(RequestData::CreateLobby(CreateRequest {
+            max_players: match args.first() {
+                Some(arg) => number(arg)?,
+                None => 4,
+            },
+            ..Default::default()
+        })),
+        "join" => Command::Send(RequestData::JoinLobby(JoinRequest {
+            lobby_id: number(expect(&args, 1)?[0])?,
+            waitlist: false,
+        })),
+        "joincode" => Command::Send(RequestData::JoinByCode(JoinByCodeRequest {
+            code: expect(&args, 1)?[0].to_string(),
+            waitlist: false,
+        })),
+        "quickjoin" => Command::Send(RequestData::QuickJoin),
+        "leave" => Command::Send(RequestData::QuitLobby),
+        "ready" => Command::Send(RequestData::Ready),
+        "start" => Command::Send(RequestData::StartGame),
+        "solo" => Command::Send(RequestData::StartSolo),
+        "place" => {
+            let args = expect(&args, 3)?;
+            Command::Send(RequestData::SetTile(SetTileRequest {
+                card_index: number(args[0])?,
+                x: number(args[1])?,
+                y: number(args[2])?,
+            }))
+        }
+        "take" => {
+            let args = expect(&args, 2)?;
+            Command::Send(RequestData::Cancel(CancelRequest {
+                x: number(args[0])?,
+                y: number(args[1])?,
+            }))
+        }
+        "finish" => Command::Send(RequestData::FinishTurn),
+        "shuffle" => Command::Send(RequestData::GetNewCard),
+        "exchange" if !args.is_empty() => {
+            Command::Send(RequestData::ExchangeCards(ExchangeCardsRequest {
+                card_indices: args
+                    .iter()
+                    .map(|arg| number(arg))
+                    .collect::<Result<_, _>>()?,
+            }))
+        }
+        "exchange" => return Err(String::from("no cards to exchange")),
+        "bag" => Command::Send(RequestData::GetBagInfo),
+        "exit" => Command::Send(RequestData::Exit),
+        "resign" => Command::Send(RequestData::Resign),
+        "draw" => Command::Send(RequestData::OfferDraw),
+        "accept" => Command::Send(RequestData::AnswerDraw(AnswerDrawRequest { accept: true })),
+        "decline" => Command::Send(RequestData::AnswerDraw(AnswerDrawRequest { accept: false })),
+        "votekick" => Command::Send(RequestData::StartVoteKick(StartVoteKickRequest {
+            target_id: number(expect(&args, 1)?[0])?,
+        })),
+        "kick" => Command::Send(RequestData::AnswerVoteKick(AnswerVoteKickRequest {
+            kick: true,
+        })),
+        "keep" => Command::Send(RequestData::AnswerVoteKick(AnswerVoteKickRequest {
+            kick: false,
+        })),
+        "follow" => Command::Send(RequestData::SubscribeGame(SubscribeGameRequest {
+            game_id: args.first().map(|arg| number(arg)).transpose()?,
+        })),
+        "say" if !rest.is_empty() => Command::Send(RequestData::SendChat(SendChatRequest {
+            message: rest.to_string(),
+        })),
+        "say" => return Err(String::from("nothing to say")),
+        "quick" => Command::Send(RequestData::SendQuickChat(SendQuickChatRequest {
+            phrase: number(expect(&args, 1)?[0])? as i32,
+        })),
+        "mute" => {
+            let mut preferences = SetPreferencesRequest::default();
+            for arg in args {
+                match arg {
+                    "chat" => preferences.mute_chat = true,
+                    "emotes" => preferences.mute_emotes = true,
+                    "spectators" => preferences.mute_spectators = true,
+                    _ => return Err(format!("cannot mute `{}`", arg)),
+                }
+            }
+            Command::Send(RequestData::SetPreferences(preferences))
+        }
+        _ => return Err(format!("unknown command `{}`, try `help`", name)),
+    };
+    Ok(Some(command))
+}
+
+fn expect<'a>(args: &'a [&'a str], count: usize) -> Result<&'a [&'a str], String> {
+    match args.len() == count {
+        true => Ok(args),
+        false => Err(format!("expected {} arguments, got {}", count, args.len())),
+    }
+}
+
+fn number(arg: &str) -> Result<u32, String> {
+    arg.parse()
+        .map_err(|_| format!("`{}` is not a number", arg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_with_place_should_return_set_tile_request() {
+        assert_eq!(
+            parse("place 2 13 13"),
+            Ok(Some(Command::Send(RequestData::SetTile(SetTileRequest {
+                card_index: 2,
+                x: 13,
+                y: 13,
+            }))))
+        );
+    }
+
+    #[test]
+    fn parse_with_say_should_keep_whole_message() {
+        assert_eq!(
+            parse("say  hello there "),
+            Ok(Some(Command::Send(RequestData::SendChat(
+                SendChatRequest {
+                    message: String::from("hello there"),
+                }
+            ))))
+        );
+    }
+
+    #[test]
+    fn parse_with_bad_arguments_should_return_error() {
+        assert!(parse("place 1 2").is_err());
+        assert!(parse("join x").is_err());
+        assert!(parse("joincode").is_err());
+        assert!(parse("exchange").is_err());
+        assert!(parse("exchange 1 x").is_err());
+        assert!(parse("votekick").is_err());
+        assert!(parse("follow x").is_err());
+        assert!(parse("dance").is_err());
+        assert!(parse("mute lobby").is_err());
+        assert_eq!(parse("   "), Ok(None));
+    }
+}
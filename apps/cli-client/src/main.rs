@@ -0,0 +1,682 @@
+mod client;
+mod command;
+mod render;
+
+use std::{
+    error::Error,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use backend::{
+    frame::{decode_reply, RequestData},
+    game::board::BOARD_SIZE,
+    model::{
+        chat::{
+            broadcast::ChatBroadcast, quick_chat::SendQuickChatResponse, send::SendChatResponse,
+        },
+        control::{
+            connect::{Capability, ConnectRequest, ConnectResponse},
+            heartbeat::{HeartbeatRequest, HeartbeatResponse},
+            notice::Notice,
+            time_sync::{TimeSyncRequest, TimeSyncResponse},
+        },
+        frame::envelope::ResponseEnvelope,
+        game::{
+            bag_info::GetBagInfoResponse,
+            board::{Board, BoardDelta, Column, Layout, Row},
+            broadcast::{GameBroadcast, GameEvent},
+            cancel::CancelResponse,
+            card::PowerUp,
+            cards::Cards,
+            coop::CoopState,
+            draw::{AnswerDrawResponse, DrawState, OfferDrawResponse},
+            exchange::ExchangeCardsResponse,
+            exit::ExitResponse,
+            finish_turn::FinishTurnResponse,
+            get_new_card::GetNewCardResponse,
+            resign::ResignResponse,
+            resync::{ResyncFromRequest, ResyncFromResponse},
+            set_tile::SetTileResponse,
+            start::StartResponse,
+            subscribe::SubscribeGameResponse,
+            tile::Tile,
+            vote_kick::{AnswerVoteKickResponse, KickVote, StartVoteKickResponse},
+            words::Words,
+        },
+        lobby::{
+            broadcast::{LobbyBroadcast, LobbyEvent},
+            create::CreateResponse,
+            join::JoinResponse,
+            list::ListResponse,
+            lobby::Lobby,
+            quit::QuitResponse,
+            ready::ReadyResponse,
+        },
+        player::player::Player,
+        state::State,
+    },
+};
+use clap::Parser;
+use prost::Message;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    time::interval,
+};
+
+use client::{GameClient, Incoming};
+use command::Command;
+
+/// Well inside the server's default heartbeat timeout.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Parser)]
+#[command(name = "cli-client", about = "Play Letter Legend from a terminal")]
+struct Cli {
+    #[arg(long, env = "HOST", default_value = "127.0.0.1")]
+    host: String,
+    #[arg(long, env = "PORT", default_value_t = 45678)]
+    port: u16,
+    #[arg(long, env = "NAME", required_unless_present = "token")]
+    name: Option<String>,
+    /// API token to play as a bot account instead of under a name
+    #[arg(long, env = "TOKEN", hide_env_values = true)]
+    token: Option<String>,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+    let (mut client, res, mut frames) = GameClient::connect(
+        &cli.host,
+        cli.port,
+        ConnectRequest {
+            name: cli.name.unwrap_or_default(),
+            token: cli.token.unwrap_or_default(),
+            capabilities: Capability::ALL,
+            ..Default::default()
+        },
+    )
+    .await?;
+    let mut session = Session::default();
+    session.connected(res);
+    client
+        .send(RequestData::TimeSync(TimeSyncRequest {
+            client_sent_at: unix_millis(),
+        }))
+        .await?;
+    println!("type `help` for commands");
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut heartbeat = interval(HEARTBEAT_INTERVAL);
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = match line? {
+                    Some(line) => line,
+                    None => break,
+                };
+                match command::parse(&line) {
+                    Ok(Some(Command::Help)) => print!("{}", command::HELP),
+                    Ok(Some(Command::Board)) => session.print_board(),
+                    Ok(Some(Command::Quit)) => break,
+                    Ok(Some(Command::Send(data))) => client.send(data).await?,
+                    Ok(None) => (),
+                    Err(e) => println!("{}", e),
+                }
+            }
+            frame = frames.recv() => {
+                let envelope = match frame {
+                    Some(frame) => frame,
+                    None => {
+                        println!("connection closed by server");
+                        return Ok(());
+                    }
+                };
+                let res = match client.resolve(envelope) {
+                    Some(Incoming::Response(req, envelope)) => session.handle_response(&req, &envelope),
+                    Some(Incoming::Event(state, payload)) => session.handle_event(state, &payload),
+                    None => Ok(()),
+                };
+                if let Err(e) = res {
+                    println!("error: {}", e);
+                }
+                if std::mem::take(&mut session.request_snapshot) {
+                    client.send(RequestData::BoardSnapshot).await?
+                }
+                if let Some(version) = session.resync_from.take() {
+                    client.send(RequestData::ResyncFrom(ResyncFromRequest { version })).await?
+                }
+            }
+            _ = heartbeat.tick() => {
+                let req = HeartbeatRequest {
+                    sent_at: unix_millis(),
+                    rtt_ms: session.rtt_ms,
+                };
+                client.send(RequestData::Heartbeat(req)).await?
+            }
+        }
+    }
+    client.send(RequestData::Disconnect).await?;
+    Ok(())
+}
+
+/// What the client knows about its game, kept up to date from responses and
+/// pushed events so the board can be drawn at any time.
+#[derive(Debug, Default)]
+struct Session {
+    player: Option<Player>,
+    board: Option<Board>,
+    /// Version `board` is at, deltas further ahead mean one was missed.
+    board_version: u64,
+    /// A delta was missed and the whole board is to be asked for.
+    request_snapshot: bool,
+    /// The whole board was asked for and hasn't come yet.
+    awaiting_snapshot: bool,
+    /// Last game state version seen, 0 until the first event of a game.
+    state_version: u64,
+    /// Game events were missed after this version and are to be asked for.
+    resync_from: Option<u64>,
+    size: usize,
+    cards: Option<Cards>,
+    /// Round trip of the last heartbeat, reported with the next one.
+    rtt_ms: u32,
+    /// How far the server clock is ahead of ours, which deadlines are on.
+    clock_offset_ms: i64,
+}
+
+impl Session {
+    fn connected(&mut self, res: ConnectResponse) {
+        if let Some(player) = &res.player {
+            println!("connected as {} ({})", player.name, player.id);
+        }
+        self.player = res.player;
+        if let Some(game) = res.game {
+            println!("back in your game");
+            self.start(game.layout, game.board, game.cards, game.current_player);
+        }
+    }
+
+    fn handle_response(
+        &mut self,
+        req: &RequestData,
+        reply: &ResponseEnvelope,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        match req {
+            RequestData::Heartbeat(_) => {
+                let res: HeartbeatResponse = decode_reply(reply)??;
+                self.rtt_ms = unix_millis().saturating_sub(res.sent_at) as u32;
+            }
+            RequestData::TimeSync(_) => {
+                let res: TimeSyncResponse = decode_reply(reply)??;
+                let received_at = unix_millis() as i64;
+                self.clock_offset_ms = ((res.server_received_at as i64
+                    - res.client_sent_at as i64)
+                    + (res.server_sent_at as i64 - received_at))
+                    / 2;
+            }
+            RequestData::ListLobby => {
+                let res: ListResponse = decode_reply(reply)??;
+                let lobbies = res.lobby_infos.unwrap_or_default().lobby_infos;
+                if lobbies.is_empty() {
+                    println!("no open lobbies");
+                }
+                for lobby in lobbies {
+                    println!(
+                        "lobby {}: {}/{} players",
+                        lobby.id, lobby.current_players, lobby.max_players
+                    );
+                }
+            }
+            RequestData::CreateLobby(_) => {
+                let res: CreateResponse = decode_reply(reply)??;
+                if let Some(lobby) = res.lobby {
+                    print_lobby("created", &lobby);
+                }
+            }
+            RequestData::JoinLobby(_) | RequestData::JoinByCode(_) | RequestData::QuickJoin => {
+                let res: JoinResponse = decode_reply(reply)??;
+                match (res.waitlist_position, res.lobby) {
+                    (Some(position), _) => println!("waitlisted, position {}", position),
+                    (None, Some(lobby)) => print_lobby("joined", &lobby),
+                    (None, None) => (),
+                }
+                res.chat_history.into_iter().for_each(print_chat);
+            }
+            RequestData::QuitLobby => {
+                decode_reply::<QuitResponse>(reply)??;
+                println!("left the lobby");
+            }
+            RequestData::Ready => {
+                decode_reply::<ReadyResponse>(reply)??;
+                println!("ready toggled");
+            }
+            RequestData::StartGame | RequestData::StartSolo => {
+                let res: StartResponse = decode_reply(reply)??;
+                self.start(res.layout, res.board, res.cards, res.current_player);
+            }
+            RequestData::SetTile(req) => {
+                decode_reply::<SetTileResponse>(reply)??;
+                // The placer isn't sent the board, so put the tile down here
+                let card = self
+                    .cards
+                    .as_mut()
+                    .and_then(|cards| cards.cards.get_mut(req.card_index as usize))
+                    .and_then(|hand_card| hand_card.card.take());
+                if let Some(card) = card {
+                    self.set_tile(
+                        req.x,
+                        req.y,
+                        Some(Tile {
+                            owner: self.player.as_ref().map_or(0, |player| player.id),
+                            char: card.symbol,
+                        }),
+                    );
+                }
+                self.print_board();
+            }
+            RequestData::Cancel(req) => {
+                let res: CancelResponse = decode_reply(reply)??;
+                self.set_tile(req.x, req.y, None);
+                self.set_cards(res.cards);
+            }
+            RequestData::FinishTurn => {
+                let res: FinishTurnResponse = decode_reply(reply)??;
+                print_words(res.words);
+                self.set_cards(res.cards);
+                print_turn(res.current_player);
+            }
+            RequestData::GetNewCard => {
+                let res: GetNewCardResponse = decode_reply(reply)??;
+                self.set_cards(res.cards);
+            }
+            RequestData::ExchangeCards(_) => {
+                let res: ExchangeCardsResponse = decode_reply(reply)??;
+                self.set_cards(res.cards);
+            }
+            RequestData::GetBagInfo => {
+                let res: GetBagInfoResponse = decode_reply(reply)??;
+                println!("{} turns left ({})", res.turns_left, res.language);
+                // The game's rules may keep the odds to themselves
+                if !res.letters.is_empty() {
+                    let odds = res
+                        .letters
+                        .iter()
+                        .map(|odds| format!("{} {:.1}%", odds.letter, odds.per_mille as f64 / 10.0))
+                        .collect::<Vec<_>>();
+                    println!("{}", odds.join("  "));
+                }
+            }
+            RequestData::Exit => {
+                decode_reply::<ExitResponse>(reply)??;
+                println!("left the game");
+                self.board = None;
+                self.cards = None;
+            }
+            RequestData::Resign => {
+                decode_reply::<ResignResponse>(reply)??;
+                println!("resigned");
+                self.board = None;
+                self.cards = None;
+            }
+            RequestData::OfferDraw => {
+                let res: OfferDrawResponse = decode_reply(reply)??;
+                print_draw(res.draw);
+            }
+            RequestData::AnswerDraw(_) => {
+                let res: AnswerDrawResponse = decode_reply(reply)??;
+                print_draw(res.draw);
+            }
+            RequestData::StartVoteKick(_) => {
+                let res: StartVoteKickResponse = decode_reply(reply)??;
+                print_kick_vote(res.vote);
+            }
+            RequestData::AnswerVoteKick(_) => {
+                let res: AnswerVoteKickResponse = decode_reply(reply)??;
+                print_kick_vote(res.vote);
+            }
+            RequestData::SubscribeGame(req) => {
+                let res: SubscribeGameResponse = decode_reply(reply)??;
+                match req.game_id {
+                    Some(game_id) => println!(
+                        "following game {} from state version {}",
+                        game_id, res.state_version
+                    ),
+                    None => println!("stopped following"),
+                }
+            }
+            RequestData::SendChat(_) => {
+                let res: SendChatResponse = decode_reply(reply)??;
+                println!("you: {}", res.message);
+            }
+            RequestData::ResyncFrom(_) => {
+                let res: ResyncFromResponse = decode_reply(reply)??;
+                if res.replayed {
+                    println!("catching up on missed events");
+                }
+            }
+            RequestData::SendQuickChat(_) => {
+                let res: SendQuickChatResponse = decode_reply(reply)??;
+                println!("you: {}", res.message);
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn handle_event(
+        &mut self,
+        state: u32,
+        payload: &[u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        match state {
+            x if x == State::LobbyBroadcast as u32 => {
+                let event = LobbyBroadcast::decode(payload)?;
+                match LobbyEvent::from_i32(event.event) {
+                    Some(LobbyEvent::Start) => {
+                        self.start(event.layout, None, event.cards, event.current_player)
+                    }
+                    Some(LobbyEvent::Destroy) => println!("the lobby was closed"),
+                    Some(LobbyEvent::Promoted) => {
+                        println!("moved off the waitlist");
+                        event.chat_history.into_iter().for_each(print_chat);
+                    }
+                    Some(LobbyEvent::LagWarning) => print_lag_warning(event.player),
+                    _ => {
+                        if let Some(lobby) = event.lobby {
+                            print_lobby("lobby changed", &lobby);
+                        }
+                    }
+                }
+            }
+            x if x == State::GameBroadcast as u32 => {
+                let event = GameBroadcast::decode(payload)?;
+                if self.state_version != 0 && event.state_version > self.state_version + 1 {
+                    self.resync_from = Some(self.state_version);
+                }
+                self.state_version = self.state_version.max(event.state_version);
+                if let Some(board) = event.board {
+                    self.board = Some(board);
+                }
+                if let Some(delta) = event.board_delta {
+                    self.apply_delta(delta);
+                }
+                match GameEvent::from_i32(event.event) {
+                    Some(GameEvent::PlaceTile) => self.print_board(),
+                    Some(GameEvent::Resync) => {
+                        self.board_version = event.board_version.unwrap_or_default();
+                        self.awaiting_snapshot = false;
+                        self.set_cards(event.cards);
+                        self.print_board();
+                        print_turn(event.current_player);
+                    }
+                    Some(GameEvent::BoardSnapshot) => {
+                        self.board_version = event.board_version.unwrap_or_default();
+                        self.awaiting_snapshot = false;
+                        self.print_board();
+                    }
+                    Some(GameEvent::FinishTurn) => {
+                        print_words(event.words);
+                        print_coop(event.coop);
+                        self.set_cards(event.cards);
+                        print_turn(event.current_player);
+                    }
+                    Some(GameEvent::Leave) => println!("a player left the game"),
+                    Some(GameEvent::Destroy) => {
+                        println!("game over");
+                        print_coop(event.coop);
+                        self.board = None;
+                        self.cards = None;
+                    }
+                    Some(GameEvent::Pause) => println!("pause vote changed"),
+                    Some(GameEvent::Rejoin) => println!("a player took their seat back"),
+                    Some(GameEvent::YourTurn) => {
+                        println!(
+                            "your turn, {}s left",
+                            self.seconds_left(event.turn_deadline)
+                        )
+                    }
+                    Some(GameEvent::TurnChanged) => print_turn(event.current_player),
+                    Some(GameEvent::Forfeit) => {
+                        if let Some(player) = event.forfeited {
+                            println!("{} forfeited", player.name);
+                        }
+                    }
+                    Some(GameEvent::Resign) => {
+                        if let Some(player) = event.resigned {
+                            println!("{} resigned", player.name);
+                        }
+                    }
+                    Some(GameEvent::DrawOffer) => {
+                        if let Some(player) = event.player {
+                            println!("{} wants a draw", player.name);
+                        }
+                        print_draw(event.draw);
+                    }
+                    Some(GameEvent::DrawDeclined) => {
+                        if let Some(player) = event.player {
+                            println!("{} declined the draw", player.name);
+                        }
+                    }
+                    Some(GameEvent::VoteKick) => {
+                        if let Some(player) = event.player {
+                            println!("{} voted to kick", player.name);
+                        }
+                        print_kick_vote(event.kick_vote);
+                    }
+                    Some(GameEvent::VoteKickFailed) => {
+                        if let Some(vote) = event.kick_vote {
+                            println!("player {} stays, the vote to kick failed", vote.target_id);
+                        }
+                    }
+                    Some(GameEvent::LagWarning) => print_lag_warning(event.player),
+                    // Others' hands only come with a count, nothing to show
+                    Some(GameEvent::HandUpdate) => self.set_cards(event.cards),
+                    Some(GameEvent::PlayerDisconnected) => {
+                        if let Some(player) = event.player {
+                            println!(
+                                "{} disconnected, waiting {}s for them to reconnect",
+                                player.name,
+                                event.disconnect_grace.unwrap_or_default()
+                            );
+                        }
+                    }
+                    Some(GameEvent::PowerUp) => {
+                        if let Some(player) = event.player {
+                            let power_up = event
+                                .power_up
+                                .and_then(PowerUp::from_i32)
+                                .unwrap_or(PowerUp::None);
+                            match event.target {
+                                Some(target) => println!(
+                                    "{} set off {:?} on {}",
+                                    player.name, power_up, target.name
+                                ),
+                                None => println!("{} set off {:?}", player.name, power_up),
+                            }
+                        }
+                    }
+                    Some(GameEvent::SpectatorJoined) => {
+                        if let Some(player) = event.player {
+                            println!("{} is watching", player.name);
+                        }
+                    }
+                    Some(GameEvent::Shuffle) | None => (),
+                }
+            }
+            x if x == State::Notice as u32 => {
+                println!("notice: {}", Notice::decode(payload)?.message);
+            }
+            x if x == State::Error as u32 => {
+                let err = backend::model::error::error::Error::decode(payload)?;
+                println!("error: {}", err.message);
+            }
+            x if x == State::Chat as u32 => {
+                print_chat(ChatBroadcast::decode(payload)?);
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn start(
+        &mut self,
+        layout: Option<Layout>,
+        board: Option<Board>,
+        cards: Option<Cards>,
+        current_player: Option<Player>,
+    ) {
+        println!("game started");
+        self.size = layout.map_or(BOARD_SIZE, |layout| layout.size as usize);
+        self.board = Some(board.unwrap_or_else(empty_board));
+        self.board_version = 0;
+        self.awaiting_snapshot = false;
+        self.state_version = 0;
+        self.cards = cards;
+        self.print_board();
+        print_turn(current_player);
+    }
+
+    fn set_tile(&mut self, x: u32, y: u32, tile: Option<Tile>) {
+        let column = self.board.as_mut().and_then(|board| {
+            let row = board.rows.len().checked_sub(y as usize + 1)?;
+            board.rows[row].columns.get_mut(x as usize)
+        });
+        if let Some(column) = column {
+            column.tile = tile;
+        }
+    }
+
+    /// Put the changed squares down, or ask for the whole board when an
+    /// earlier delta never arrived.
+    fn apply_delta(&mut self, delta: BoardDelta) {
+        if delta.version <= self.board_version || self.awaiting_snapshot {
+            return;
+        }
+        if delta.version > self.board_version + 1 {
+            self.request_snapshot = true;
+            self.awaiting_snapshot = true;
+            return;
+        }
+        for square in delta.squares {
+            self.set_tile(square.x, square.y, square.tile);
+        }
+        self.board_version = delta.version;
+    }
+
+    fn set_cards(&mut self, cards: Option<Cards>) {
+        if let Some(cards) = cards {
+            println!("hand: {}", render::render_hand(&cards));
+            self.cards = Some(cards);
+        }
+    }
+
+    /// Whole seconds until `deadline`, a time on the server clock.
+    fn seconds_left(&self, deadline: Option<u64>) -> u64 {
+        let now = unix_millis() as i64 + self.clock_offset_ms;
+        (deadline.unwrap_or_default() as i64 - now).max(0) as u64 / 1000
+    }
+
+    fn print_board(&self) {
+        match &self.board {
+            Some(board) => print!("{}", render::render_board(board, self.size)),
+            None => println!("not in a game"),
+        }
+        if let Some(cards) = &self.cards {
+            println!("hand: {}", render::render_hand(cards));
+        }
+    }
+}
+
+fn empty_board() -> Board {
+    Board {
+        rows: vec![
+            Row {
+                columns: vec![Column { tile: None }; BOARD_SIZE]
+            };
+            BOARD_SIZE
+        ],
+    }
+}
+
+fn print_lobby(what: &str, lobby: &Lobby) {
+    let names: Vec<String> = lobby
+        .players
+        .iter()
+        .map(|player| match player.ping_ms {
+            Some(ping_ms) => format!("{} ({} ms)", player.name, ping_ms),
+            None => player.name.clone(),
+        })
+        .collect();
+    println!(
+        "{} lobby {} ({}/{}): {}",
+        what,
+        lobby.id,
+        names.len(),
+        lobby.max_players,
+        names.join(", ")
+    );
+    if let Some(code) = &lobby.code {
+        println!("join code: {}", code);
+    }
+}
+
+fn print_lag_warning(player: Option<Player>) {
+    if let Some(player) = player {
+        println!(
+            "{} has a slow connection ({} ms)",
+            player.name,
+            player.ping_ms.unwrap_or_default()
+        );
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn print_words(words: Option<Words>) {
+    if let Some(words) = words.filter(|words| !words.words.is_empty()) {
+        println!("words: {}", words.words.join(", "));
+    }
+}
+
+fn print_draw(draw: Option<DrawState>) {
+    if let Some(draw) = draw {
+        println!("draw: {}/{} agreed", draw.votes, draw.votes_needed);
+    }
+}
+
+fn print_kick_vote(vote: Option<KickVote>) {
+    if let Some(vote) = vote {
+        println!(
+            "vote to kick player {}: {}/{} for, {} against",
+            vote.target_id, vote.votes, vote.votes_needed, vote.votes_against
+        );
+    }
+}
+
+fn print_chat(chat: ChatBroadcast) {
+    let name = chat.player.map(|player| player.name).unwrap_or_default();
+    println!("{}: {}", name, chat.message);
+}
+
+fn print_coop(coop: Option<CoopState>) {
+    if let Some(coop) = coop {
+        match coop.won {
+            true => println!("team reached {}/{}", coop.score, coop.target),
+            false => println!(
+                "team at {}/{}, {} turns left",
+                coop.score, coop.target, coop.turns_left
+            ),
+        }
+    }
+}
+
+fn print_turn(current_player: Option<Player>) {
+    if let Some(player) = current_player {
+        println!("{}'s turn", player.name);
+    }
+}
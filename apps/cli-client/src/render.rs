@@ -0,0 +1,109 @@
+use backend::model::game::{board::Board, card::PowerUp, cards::Cards};
+
+/// Draw the bottom-left `size` squares of the board, the playable area.
+/// `y` counts up from the bottom row and `x` from the left, the same
+/// coordinates tiles are placed with.
+pub fn render_board(board: &Board, size: usize) -> String {
+    let width = size.saturating_sub(1).to_string().len();
+    let mut out = String::new();
+    for y in (0..size).rev() {
+        let row = board.rows.len().checked_sub(y + 1).map(|i| &board.rows[i]);
+        let cells: Vec<String> = (0..size)
+            .map(|x| {
+                let tile = row
+                    .and_then(|row| row.columns.get(x))
+                    .and_then(|column| column.tile.as_ref());
+                let cell = match tile {
+                    Some(tile) => tile.char.to_uppercase(),
+                    None => String::from("."),
+                };
+                format!("{:>width$}", cell, width = width)
+            })
+            .collect();
+        out.push_str(&format!(
+            "{:>width$} | {}\n",
+            y,
+            cells.join(" "),
+            width = width
+        ));
+    }
+    let labels: Vec<String> = (0..size)
+        .map(|x| format!("{:>width$}", x, width = width))
+        .collect();
+    out.push_str(&format!(
+        "{} +-{}\n",
+        " ".repeat(width),
+        "-".repeat(labels.join(" ").len())
+    ));
+    out.push_str(&format!("{}   {}\n", " ".repeat(width), labels.join(" ")));
+    out
+}
+
+/// The hand as `index:letter` pairs, `-` for cards already on the board.
+pub fn render_hand(cards: &Cards) -> String {
+    cards
+        .cards
+        .iter()
+        .enumerate()
+        .map(|(i, hand_card)| match &hand_card.card {
+            Some(card) if card.power_up() != PowerUp::None => {
+                format!("{}:{}*", i, card.symbol.to_uppercase())
+            }
+            Some(card) => format!("{}:{}", i, card.symbol.to_uppercase()),
+            None => format!("{}:-", i),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use backend::model::game::{
+        board::{Column, Row},
+        card::{Card, PowerUp},
+        hand_card::HandCard,
+        tile::Tile,
+    };
+
+    use super::*;
+
+    #[test]
+    fn render_board_should_put_y_zero_at_bottom() {
+        let mut rows = vec![
+            Row {
+                columns: vec![Column { tile: None }; 3]
+            };
+            3
+        ];
+        rows[2].columns[1].tile = Some(Tile {
+            owner: 0,
+            char: String::from("a"),
+        });
+        assert_eq!(
+            render_board(&Board { rows }, 3),
+            "2 | . . .\n1 | . . .\n0 | . A .\n  +------\n    0 1 2\n"
+        );
+    }
+
+    #[test]
+    fn render_hand_should_mark_used_cards() {
+        let cards = Cards {
+            cards: vec![
+                HandCard {
+                    card: Some(Card {
+                        symbol: String::from("q"),
+                        power_up: PowerUp::None as i32,
+                    }),
+                },
+                HandCard { card: None },
+                HandCard {
+                    card: Some(Card {
+                        symbol: String::from("z"),
+                        power_up: PowerUp::StealLetter as i32,
+                    }),
+                },
+            ],
+        };
+        assert_eq!(render_hand(&cards), "0:Q 1:- 2:Z*");
+    }
+}
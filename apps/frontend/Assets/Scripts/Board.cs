@@ -72,7 +72,16 @@ public void Update()
             case GameEvent.Leave:
                 //Debug.Log(res.Players);
                 break;
+            case GameEvent.Forfeit:
+            case GameEvent.Resign:
+                if (Equals(res.Forfeited ?? res.Resigned, GameManager.Instance.GetMainPlayer()))
+                {
+                    SceneManager.LoadScene(0);
+                }
+                break;
             case GameEvent.Shuffle:
+            case GameEvent.DrawOffer:
+            case GameEvent.DrawDeclined:
                 break;
             case GameEvent.PlaceTile:
                 SetBoard(res.Board);
@@ -88,6 +97,11 @@ public void Update()
                 dict.AddWord(res.Words.Words_.ToList());
                 timer.ResetCurrentTime();
                 break;
+            case GameEvent.YourTurn:
+            case GameEvent.TurnChanged:
+                playerShowText.SetPlayerName(res.CurrentPlayer, res.NextPlayer);
+                GameManager.Instance.SetPlayers(res.CurrentPlayer, res.NextPlayer);
+                break;
             default:
                 throw new ArgumentOutOfRangeException();
         }
@@ -24,6 +24,7 @@ public class GameTcpClient
         private readonly System.Random _random;
         private Task _receiveLoop;
         private readonly CancellationTokenSource _cancellationTokenSource;
+        private uint _rttMs;
         public RoomPanel RoomPanel { get; set; }
         public Board Board { get; set; }
 
@@ -115,7 +116,9 @@ public async Task<Player> ConnectAsync(string name)
             Loop();
             var req = new ConnectRequest()
             {
-                Name = name
+                Name = name,
+                Capabilities = (ulong)Capability.TurnEvents | (ulong)Capability.WithdrawEvents |
+                               (ulong)Capability.DrawEvents
             };
             var stream = new MemoryStream();
             req.WriteTo(stream);
@@ -176,6 +179,35 @@ public async Task<Lobby> JoinLobby(uint lobbyId)
             return res.Lobby;
         }
 
+        public async Task<Lobby> JoinByCode(string code)
+        {
+            var req = new JoinByCodeRequest()
+            {
+                Code = code
+            };
+
+            var stream = new MemoryStream();
+            req.WriteTo(stream);
+            var res = JoinResponse.Parser.ParseFrom(await Rpc(Operation.JoinByCode, stream.ToArray()));
+            if (!res.Success)
+            {
+                throw new Exception("join room failed");
+            }
+
+            return res.Lobby;
+        }
+
+        public async Task<Lobby> QuickJoin()
+        {
+            var res = JoinResponse.Parser.ParseFrom(await Rpc(Operation.QuickJoin));
+            if (!res.Success)
+            {
+                throw new Exception("quick join failed");
+            }
+
+            return res.Lobby;
+        }
+
         public async Task QuitLobby()
         {
             var res = QuitResponse.Parser.ParseFrom(await Rpc(Operation.QuitLobby));
@@ -252,11 +284,21 @@ public async Task<List<HandCard>> GetNewCard()
 
         public async Task HeartBeat()
         {
-            var res = HeartbeatResponse.Parser.ParseFrom(await Rpc(Operation.Heartbeat));
+            var req = new HeartbeatRequest()
+            {
+                SentAt = (ulong)DateTimeOffset.UtcNow.ToUnixTimeMilliseconds(),
+                RttMs = _rttMs
+            };
+            var stream = new MemoryStream();
+            req.WriteTo(stream);
+
+            var res = HeartbeatResponse.Parser.ParseFrom(await Rpc(Operation.Heartbeat, stream.ToArray()));
             if (!res.Success)
             {
                 throw new Exception("heart beat failed");
             }
+
+            _rttMs = (uint)((ulong)DateTimeOffset.UtcNow.ToUnixTimeMilliseconds() - res.SentAt);
         }
 
         public async Task<List<HandCard>> Cancel(uint x, uint y)
@@ -289,6 +331,63 @@ public async Task Exit()
             }
         }
 
+        public async Task Resign()
+        {
+            var res = ResignResponse.Parser.ParseFrom(await Rpc(Operation.Resign));
+
+            if (!res.Success)
+            {
+                throw new Exception("resign failed");
+            }
+        }
+
+        public async Task<DrawState> OfferDraw()
+        {
+            var res = OfferDrawResponse.Parser.ParseFrom(await Rpc(Operation.OfferDraw));
+            if (!res.Success)
+            {
+                throw new Exception("offer draw failed");
+            }
+
+            return res.Draw;
+        }
+
+        public async Task<DrawState> AnswerDraw(bool accept)
+        {
+            var req = new AnswerDrawRequest
+            {
+                Accept = accept
+            };
+            var stream = new MemoryStream();
+            req.WriteTo(stream);
+
+            var res = AnswerDrawResponse.Parser.ParseFrom(await Rpc(Operation.AnswerDraw, stream.ToArray()));
+            if (!res.Success)
+            {
+                throw new Exception("answer draw failed");
+            }
+
+            return res.Draw;
+        }
+
+        public async Task<Profile> UpdateProfile(uint avatarId, TileColor tileColor, Title title)
+        {
+            var req = new UpdateProfileRequest
+            {
+                Profile = new Profile
+                {
+                    AvatarId = avatarId,
+                    TileColor = tileColor,
+                    Title = title
+                }
+            };
+            var stream = new MemoryStream();
+            req.WriteTo(stream);
+
+            var res = UpdateProfileResponse.Parser.ParseFrom(await Rpc(Operation.UpdateProfile, stream.ToArray()));
+            return res.Profile;
+        }
+
         public Task Reconnect()
         {
             throw new NotImplementedException();
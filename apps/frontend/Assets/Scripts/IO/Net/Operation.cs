@@ -15,6 +15,42 @@ public enum Operation
         FinishTurn,
         GetNewCard,
         Cancel,
-        Exit
+        Exit,
+        PauseGame,
+        ResumeGame,
+        GetHint,
+        GetPlayerStats,
+        StartDailyChallenge,
+        GetDailyLeaderboard,
+        SendChat,
+        ReportPlayer,
+        MutePlayer,
+        ListSupportedOpcodes,
+        UploadDictionary,
+        Resign,
+        OfferDraw,
+        AnswerDraw,
+        JoinByCode,
+        QuickJoin,
+        UpdateProfile,
+        BlockPlayer,
+        KickFromLobby,
+        ServerInfo,
+        GetSeasonInfo,
+        Spectate,
+        ExportGame,
+        AnalyzeGame,
+        UpdateLobbySettings,
+        StartSolo,
+        SendQuickChat,
+        TimeSync,
+        SetPreferences,
+        BoardSnapshot,
+        ResyncFrom,
+        GetBagInfo,
+        ExchangeCards,
+        StartVoteKick,
+        AnswerVoteKick,
+        SubscribeGame
     }
 }
\ No newline at end of file
@@ -0,0 +1,162 @@
+use std::{collections::VecDeque, error::Error, sync::Arc, time::Duration};
+
+use backend::{
+    frame::{decode_legacy_reply, decode_reply, Request, RequestData, PROTOCOL_VERSION},
+    model::{
+        control::connect::{ConnectRequest, ConnectResponse},
+        frame::envelope::ResponseEnvelope,
+        state::FIRST_REQUEST_ID,
+    },
+    operation::Operation,
+};
+use prost::Message;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream,
+    },
+    sync::mpsc::{self, Receiver, Sender},
+    time::{timeout, Instant},
+};
+
+use crate::stats::Stats;
+
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the server made of a request: the response, or the message of the
+/// error it answered with.
+pub type Reply<T> = Result<T, String>;
+
+/// One connection to the server, timing every request it sends.
+pub struct SimClient {
+    writer: OwnedWriteHalf,
+    frames: Receiver<ResponseEnvelope>,
+    /// Events pushed while waiting for a response, not yet handled.
+    events: VecDeque<(u32, Vec<u8>)>,
+    next_request_id: u32,
+    pub stats: Stats,
+}
+
+impl SimClient {
+    /// Connect with `req`, asking for the envelope protocol every later
+    /// frame is sent in, and time it like any other request.
+    pub async fn connect(
+        host: &str,
+        port: u16,
+        req: ConnectRequest,
+    ) -> Result<(Self, Reply<ConnectResponse>), Box<dyn Error + Send + Sync>> {
+        let mut stream = TcpStream::connect((host, port)).await?;
+        let req = ConnectRequest {
+            protocol_version: PROTOCOL_VERSION,
+            ..req
+        };
+        let bytes =
+            Request::new(FIRST_REQUEST_ID, Arc::new(RequestData::Connect(req))).to_bytes()?;
+        let sent_at = Instant::now();
+        stream.write_all(&bytes).await?;
+        // The connect response is the last frame in the legacy encoding
+        let read = async {
+            loop {
+                let state = stream.read_u32_le().await?;
+                let len = stream.read_u32_le().await?;
+                let mut payload = vec![0; len as usize];
+                stream.read_exact(&mut payload).await?;
+                if state == FIRST_REQUEST_ID {
+                    return Ok::<_, std::io::Error>(payload);
+                }
+            }
+        };
+        let payload = match timeout(RESPONSE_TIMEOUT, read).await {
+            Ok(payload) => payload?,
+            Err(_) => return Err(format!("no response to {:?}", Operation::Connect).into()),
+        };
+        let reply = decode_legacy_reply::<ConnectResponse>(&payload)?.map_err(|e| e.message);
+        let mut stats = Stats::default();
+        stats.record(&Operation::Connect, sent_at.elapsed(), reply.is_ok());
+        if matches!(&reply, Ok(res) if res.protocol_version != PROTOCOL_VERSION) {
+            return Err("server doesn't speak the envelope protocol".into());
+        }
+        let (reader, writer) = stream.into_split();
+        let (tx, rx) = mpsc::channel(32);
+        tokio::spawn(read_frames(reader, tx));
+        let client = Self {
+            writer,
+            frames: rx,
+            events: VecDeque::new(),
+            next_request_id: FIRST_REQUEST_ID + 1,
+            stats,
+        };
+        Ok((client, reply))
+    }
+
+    /// Send a request and wait for its response. Errors are reserved for a
+    /// broken connection, a request the server refused is an `Err` reply.
+    pub async fn call<T: Message + Default>(
+        &mut self,
+        data: RequestData,
+    ) -> Result<Reply<T>, Box<dyn Error + Send + Sync>> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let op = Operation::try_from(&data)?;
+        let bytes = Request::new(request_id, Arc::new(data)).to_envelope_bytes()?;
+        let sent_at = Instant::now();
+        self.writer.write_all(&bytes).await?;
+        let envelope = loop {
+            let envelope = match timeout(RESPONSE_TIMEOUT, self.frames.recv()).await {
+                Ok(Some(envelope)) => envelope,
+                Ok(None) => return Err("connection closed by server".into()),
+                Err(_) => return Err(format!("no response to {:?}", op).into()),
+            };
+            match envelope.request_id {
+                Some(x) if x == request_id => break envelope,
+                Some(_) => (),
+                None => self.events.push_back(event(envelope)),
+            }
+        };
+        let reply = decode_reply(&envelope)?.map_err(|e| e.message);
+        self.stats.record(&op, sent_at.elapsed(), reply.is_ok());
+        Ok(reply)
+    }
+
+    /// The next pushed event, waiting up to `wait` for one to arrive.
+    pub async fn next_event(
+        &mut self,
+        wait: Duration,
+    ) -> Result<Option<(u32, Vec<u8>)>, Box<dyn Error + Send + Sync>> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(Some(event));
+        }
+        match timeout(wait, self.frames.recv()).await {
+            Ok(Some(envelope)) if envelope.request_id.is_none() => Ok(Some(event(envelope))),
+            Ok(Some(_)) => Ok(None),
+            Ok(None) => Err("connection closed by server".into()),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// A pushed event as the state it's tagged with and its payload.
+fn event(envelope: ResponseEnvelope) -> (u32, Vec<u8>) {
+    (envelope.state.unwrap_or_default(), envelope.payload)
+}
+
+async fn read_frames(mut reader: OwnedReadHalf, tx: Sender<ResponseEnvelope>) {
+    loop {
+        // Compression is never asked for, so the length has no flag set
+        let frame = async {
+            let len = reader.read_u32().await?;
+            let mut body = vec![0; len as usize];
+            reader.read_exact(&mut body).await?;
+            ResponseEnvelope::decode(&body[..]).map_err(std::io::Error::other)
+        };
+        match frame.await {
+            Ok(envelope) => {
+                if tx.send(envelope).await.is_err() {
+                    return;
+                }
+            }
+            Err(_) => return,
+        }
+    }
+}
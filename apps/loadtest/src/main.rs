@@ -0,0 +1,100 @@
+mod client;
+mod sim;
+mod stats;
+
+use std::{error::Error, sync::Arc, time::Duration};
+
+use clap::Parser;
+use tokio::{
+    sync::{oneshot, Barrier},
+    time::Instant,
+};
+
+use sim::{Role, Settings};
+use stats::Stats;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "loadtest",
+    about = "Drive a running Letter Legend server with simulated players"
+)]
+struct Cli {
+    #[arg(long, env = "HOST", default_value = "127.0.0.1")]
+    host: String,
+    #[arg(long, env = "PORT", default_value_t = 45678)]
+    port: u16,
+    /// Simulated players, all from this address, so the server's
+    /// MAX_CONNECTIONS_PER_IP has to allow them
+    #[arg(long, default_value_t = 16)]
+    clients: usize,
+    /// Players per lobby, must be within the server's lobby size range
+    #[arg(long, default_value_t = 4)]
+    lobby_size: usize,
+    /// How long to run, in seconds
+    #[arg(long, default_value_t = 30)]
+    duration: u64,
+    /// Seconds between heartbeats
+    #[arg(long, default_value_t = 10)]
+    heartbeat: u64,
+    /// Milliseconds a player waits before moving
+    #[arg(long, default_value_t = 200)]
+    think: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+    if cli.lobby_size == 0 {
+        return Err("lobby size must be at least 1".into());
+    }
+    let started_at = Instant::now();
+    let settings = Arc::new(Settings {
+        host: cli.host,
+        port: cli.port,
+        deadline: started_at + Duration::from_secs(cli.duration),
+        heartbeat: Duration::from_secs(cli.heartbeat),
+        think: Duration::from_millis(cli.think),
+    });
+    let ids: Vec<usize> = (0..cli.clients).collect();
+    let mut tasks = Vec::new();
+    for group in ids.chunks(cli.lobby_size) {
+        let ready = Arc::new(Barrier::new(group.len()));
+        let (members, lobbies): (Vec<_>, Vec<_>) =
+            group[1..].iter().map(|_| oneshot::channel()).unzip();
+        let roles = [Role::Leader {
+            max_players: cli.lobby_size as u32,
+            members,
+        }]
+        .into_iter()
+        .chain(lobbies.into_iter().map(Role::Member));
+        for (&id, role) in group.iter().zip(roles) {
+            tasks.push(tokio::spawn(sim::simulate(
+                id,
+                role,
+                ready.clone(),
+                settings.clone(),
+            )));
+        }
+    }
+    println!(
+        "{} clients running for {}s",
+        tasks.len(),
+        settings.deadline.duration_since(started_at).as_secs()
+    );
+    let mut stats = Stats::default();
+    let mut failed = 0;
+    for (id, task) in tasks.into_iter().enumerate() {
+        let (client_stats, res) = task.await?;
+        stats.merge(client_stats);
+        if let Err(e) = res {
+            failed += 1;
+            eprintln!("client {} stopped: {}", id, e);
+        }
+    }
+    print!("{}", stats.report(started_at.elapsed()));
+    if failed > 0 {
+        println!("{} clients stopped early", failed);
+    }
+    Ok(())
+}
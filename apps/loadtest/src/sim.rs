@@ -0,0 +1,372 @@
+use std::{collections::HashSet, error::Error, sync::Arc, time::Duration};
+
+use backend::{
+    frame::RequestData,
+    game::board::BOARD_SIZE,
+    model::{
+        control::{
+            connect::{ConnectRequest, ConnectResponse},
+            disconnect::DisconnectResponse,
+            heartbeat::{HeartbeatRequest, HeartbeatResponse},
+        },
+        game::{
+            board::{Board, Layout},
+            broadcast::{GameBroadcast, GameEvent},
+            cancel::{CancelRequest, CancelResponse},
+            cards::Cards,
+            finish_turn::FinishTurnResponse,
+            set_tile::{SetTileRequest, SetTileResponse},
+            start::StartResponse,
+        },
+        lobby::{
+            broadcast::{LobbyBroadcast, LobbyEvent},
+            create::{CreateRequest, CreateResponse},
+            join::{JoinRequest, JoinResponse},
+            ready::ReadyResponse,
+        },
+        player::player::Player,
+        state::State,
+    },
+};
+use prost::Message;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use tokio::{
+    sync::{oneshot, Barrier},
+    time::{sleep, timeout_at, Instant},
+};
+
+use crate::{
+    client::{Reply, SimClient},
+    stats::Stats,
+};
+
+#[derive(Debug)]
+pub struct Settings {
+    pub host: String,
+    pub port: u16,
+    pub deadline: Instant,
+    pub heartbeat: Duration,
+    /// Pause before each move, so turns look a little like a person's.
+    pub think: Duration,
+}
+
+/// Whether the client opens its group's lobby or joins it.
+pub enum Role {
+    Leader {
+        max_players: u32,
+        members: Vec<oneshot::Sender<u32>>,
+    },
+    Member(oneshot::Receiver<u32>),
+}
+
+/// Play as one client until the deadline. The stats cover every request
+/// sent, even when the client gave up early.
+pub async fn simulate(
+    id: usize,
+    role: Role,
+    ready: Arc<Barrier>,
+    settings: Arc<Settings>,
+) -> (Stats, Result<(), Box<dyn Error + Send + Sync>>) {
+    let req = ConnectRequest {
+        name: format!("load-{}", id),
+        ..Default::default()
+    };
+    let (client, res) = match SimClient::connect(&settings.host, settings.port, req).await {
+        Ok(connected) => connected,
+        Err(e) => return (Stats::default(), Err(e)),
+    };
+    let mut sim = Sim {
+        client,
+        table: Table::default(),
+        rng: StdRng::seed_from_u64(id as u64),
+        settings,
+        last_heartbeat: Instant::now(),
+    };
+    let res = match res {
+        Ok(res) => sim.run(res, role, ready).await,
+        Err(e) => Err(e.into()),
+    };
+    (sim.client.stats, res)
+}
+
+struct Sim {
+    client: SimClient,
+    table: Table,
+    rng: StdRng,
+    settings: Arc<Settings>,
+    last_heartbeat: Instant,
+}
+
+impl Sim {
+    async fn run(
+        &mut self,
+        res: ConnectResponse,
+        role: Role,
+        ready: Arc<Barrier>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.table.player_id = res.player.map(|player| player.id);
+        let is_leader = matches!(role, Role::Leader { .. });
+        match role {
+            Role::Leader {
+                max_players,
+                members,
+            } => {
+                let res: CreateResponse = self
+                    .client
+                    .call(RequestData::CreateLobby(CreateRequest {
+                        max_players,
+                        ..Default::default()
+                    }))
+                    .await??;
+                let lobby_id = res.lobby.ok_or("created lobby missing")?.id;
+                for member in members {
+                    member.send(lobby_id).ok();
+                }
+            }
+            Role::Member(lobby) => {
+                let lobby_id = timeout_at(self.settings.deadline, lobby).await??;
+                self.client
+                    .call::<JoinResponse>(RequestData::JoinLobby(JoinRequest {
+                        lobby_id,
+                        waitlist: false,
+                    }))
+                    .await??;
+            }
+        }
+        self.client
+            .call::<ReadyResponse>(RequestData::Ready)
+            .await??;
+        timeout_at(self.settings.deadline, ready.wait()).await?;
+        if is_leader {
+            if let Ok(res) = self
+                .client
+                .call::<StartResponse>(RequestData::StartGame)
+                .await?
+            {
+                self.table
+                    .start(res.layout, res.board, res.cards, res.current_player);
+            }
+        }
+        while Instant::now() < self.settings.deadline {
+            if self.table.is_my_turn() {
+                sleep(self.settings.think).await;
+                self.play_turn().await?;
+            }
+            let wait = self
+                .settings
+                .heartbeat
+                .saturating_sub(self.last_heartbeat.elapsed())
+                .min(
+                    self.settings
+                        .deadline
+                        .saturating_duration_since(Instant::now()),
+                );
+            if let Some((state, payload)) = self.client.next_event(wait).await? {
+                self.table.handle_event(state, &payload)?;
+            }
+            if self.last_heartbeat.elapsed() >= self.settings.heartbeat {
+                self.client
+                    .call::<HeartbeatResponse>(RequestData::Heartbeat(HeartbeatRequest::default()))
+                    .await??;
+                self.last_heartbeat = Instant::now();
+            }
+        }
+        self.client
+            .call::<DisconnectResponse>(RequestData::Disconnect)
+            .await??;
+        Ok(())
+    }
+
+    /// Put a random card on a random square it may go on and finish the
+    /// turn. The word is almost never valid, in which case the tile is
+    /// taken back and the turn passed empty-handed.
+    async fn play_turn(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut placed = None;
+        if let (Some(card_index), Some((x, y))) = (
+            self.table.pick_card(&mut self.rng),
+            self.table.pick_square(&mut self.rng),
+        ) {
+            let res = self
+                .client
+                .call::<SetTileResponse>(RequestData::SetTile(SetTileRequest {
+                    x: x as u32,
+                    y: y as u32,
+                    card_index: card_index as u32,
+                }))
+                .await?;
+            if res.is_ok() {
+                self.table.tiles.insert((x, y));
+                self.table.hand[card_index] = false;
+                placed = Some((x, y));
+            }
+        }
+        let mut res = self
+            .client
+            .call::<FinishTurnResponse>(RequestData::FinishTurn)
+            .await?;
+        let finished = |res: &Reply<FinishTurnResponse>| matches!(res, Ok(res) if res.success);
+        if let (false, Some((x, y))) = (finished(&res), placed) {
+            let cancel = self
+                .client
+                .call::<CancelResponse>(RequestData::Cancel(CancelRequest {
+                    x: x as u32,
+                    y: y as u32,
+                }))
+                .await?;
+            if let Ok(cancel) = cancel {
+                self.table.tiles.remove(&(x, y));
+                self.table.set_cards(cancel.cards);
+            }
+            res = self
+                .client
+                .call::<FinishTurnResponse>(RequestData::FinishTurn)
+                .await?;
+        }
+        match res {
+            Ok(res) if res.success => {
+                self.table.set_cards(res.cards);
+                self.table.current_player = res.current_player.map(|player| player.id);
+            }
+            // Leave it to the turn timer, the broadcast says who is next
+            _ => self.table.current_player = None,
+        }
+        Ok(())
+    }
+}
+
+/// The client's view of its game, enough to pick legal moves.
+#[derive(Debug, Default)]
+struct Table {
+    player_id: Option<u32>,
+    current_player: Option<u32>,
+    size: usize,
+    /// `(x, y)` of every tile on the board.
+    tiles: HashSet<(usize, usize)>,
+    /// Whether each card in the hand is still there to be placed.
+    hand: Vec<bool>,
+}
+
+impl Table {
+    fn start(
+        &mut self,
+        layout: Option<Layout>,
+        board: Option<Board>,
+        cards: Option<Cards>,
+        current_player: Option<Player>,
+    ) {
+        self.size = layout.map_or(BOARD_SIZE, |layout| layout.size as usize);
+        if let Some(board) = board {
+            self.set_board(&board);
+        }
+        self.set_cards(cards);
+        self.current_player = current_player.map(|player| player.id);
+    }
+
+    fn is_my_turn(&self) -> bool {
+        self.current_player.is_some() && self.current_player == self.player_id
+    }
+
+    fn handle_event(&mut self, state: u32, payload: &[u8]) -> Result<(), prost::DecodeError> {
+        match state {
+            x if x == State::LobbyBroadcast as u32 => {
+                let event = LobbyBroadcast::decode(payload)?;
+                if event.event == LobbyEvent::Start as i32 {
+                    self.start(event.layout, None, event.cards, event.current_player);
+                }
+            }
+            x if x == State::GameBroadcast as u32 => {
+                let event = GameBroadcast::decode(payload)?;
+                if let Some(board) = &event.board {
+                    self.set_board(board);
+                }
+                match GameEvent::from_i32(event.event) {
+                    Some(GameEvent::FinishTurn) => {
+                        self.current_player = event.current_player.map(|player| player.id);
+                    }
+                    Some(GameEvent::Destroy) => self.current_player = None,
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn set_board(&mut self, board: &Board) {
+        self.tiles.clear();
+        for (i, row) in board.rows.iter().enumerate() {
+            for (x, column) in row.columns.iter().enumerate() {
+                if column.tile.is_some() {
+                    self.tiles.insert((x, board.rows.len() - i - 1));
+                }
+            }
+        }
+    }
+
+    fn set_cards(&mut self, cards: Option<Cards>) {
+        if let Some(cards) = cards {
+            self.hand = cards.cards.iter().map(|card| card.card.is_some()).collect();
+        }
+    }
+
+    fn pick_card(&self, rng: &mut StdRng) -> Option<usize> {
+        let cards: Vec<usize> = (0..self.hand.len()).filter(|&i| self.hand[i]).collect();
+        cards.choose(rng).copied()
+    }
+
+    /// A free square a tile may go on: the centre of an empty board,
+    /// otherwise one next to a tile.
+    fn pick_square(&self, rng: &mut StdRng) -> Option<(usize, usize)> {
+        if self.tiles.is_empty() {
+            return Some((self.size / 2, self.size / 2));
+        }
+        let mut squares: Vec<(usize, usize)> = self
+            .tiles
+            .iter()
+            .flat_map(|&(x, y)| {
+                [
+                    (x + 1, y),
+                    (x, y + 1),
+                    (x.wrapping_sub(1), y),
+                    (x, y.wrapping_sub(1)),
+                ]
+            })
+            .filter(|&(x, y)| x < self.size && y < self.size && !self.tiles.contains(&(x, y)))
+            .collect();
+        // The set's order isn't stable, sort so a seed gives the same moves
+        squares.sort();
+        squares.dedup();
+        squares.choose(rng).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_square_on_empty_board_should_return_centre() {
+        let table = Table {
+            size: 15,
+            ..Default::default()
+        };
+        assert_eq!(
+            table.pick_square(&mut StdRng::seed_from_u64(0)),
+            Some((7, 7))
+        );
+    }
+
+    #[test]
+    fn pick_square_should_return_free_square_next_to_tile() {
+        let table = Table {
+            size: 3,
+            tiles: HashSet::from([(0, 0), (1, 0), (0, 1)]),
+            ..Default::default()
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10 {
+            let square = table.pick_square(&mut rng).unwrap();
+            assert!([(2, 0), (1, 1), (0, 2)].contains(&square));
+        }
+    }
+}
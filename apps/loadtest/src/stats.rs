@@ -0,0 +1,102 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use backend::operation::Operation;
+
+/// Latencies and failures of one or more simulated clients, per request
+/// type.
+#[derive(Debug, Default)]
+pub struct Stats {
+    latencies: BTreeMap<String, Vec<Duration>>,
+    errors: BTreeMap<String, u32>,
+}
+
+impl Stats {
+    pub fn record(&mut self, op: &Operation, latency: Duration, ok: bool) {
+        let op = format!("{:?}", op);
+        if !ok {
+            *self.errors.entry(op.clone()).or_default() += 1;
+        }
+        self.latencies.entry(op).or_default().push(latency);
+    }
+
+    pub fn merge(&mut self, other: Stats) {
+        for (op, latencies) in other.latencies {
+            self.latencies.entry(op).or_default().extend(latencies);
+        }
+        for (op, errors) in other.errors {
+            *self.errors.entry(op).or_default() += errors;
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.latencies.values().map(Vec::len).sum()
+    }
+
+    /// One line per request type with its count, errors and latency
+    /// percentiles in milliseconds, then the overall throughput.
+    pub fn report(&mut self, elapsed: Duration) -> String {
+        let mut out = format!(
+            "{:<16} {:>8} {:>6} {:>8} {:>8} {:>8} {:>8}\n",
+            "REQUEST", "COUNT", "ERRORS", "P50", "P90", "P99", "MAX"
+        );
+        for (op, latencies) in &mut self.latencies {
+            latencies.sort();
+            out.push_str(&format!(
+                "{:<16} {:>8} {:>6} {:>8.2} {:>8.2} {:>8.2} {:>8.2}\n",
+                op,
+                latencies.len(),
+                self.errors.get(op).copied().unwrap_or_default(),
+                millis(percentile(latencies, 50.0)),
+                millis(percentile(latencies, 90.0)),
+                millis(percentile(latencies, 99.0)),
+                millis(latencies.last().copied().unwrap_or_default()),
+            ));
+        }
+        out.push_str(&format!(
+            "{} requests in {:.1}s, {:.1} req/s\n",
+            self.total(),
+            elapsed.as_secs_f64(),
+            self.total() as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+        ));
+        out
+    }
+}
+
+/// Nearest-rank percentile of already sorted samples.
+pub fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_should_use_nearest_rank() {
+        let samples: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 50.0), Duration::from_millis(5));
+        assert_eq!(percentile(&samples, 99.0), Duration::from_millis(10));
+        assert_eq!(percentile(&samples, 0.0), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn merge_should_add_up_samples_and_errors() {
+        let mut stats = Stats::default();
+        stats.record(&Operation::SetTile, Duration::from_millis(1), true);
+        let mut other = Stats::default();
+        other.record(&Operation::SetTile, Duration::from_millis(2), false);
+        other.record(&Operation::Heartbeat, Duration::from_millis(1), true);
+        stats.merge(other);
+        assert_eq!(stats.total(), 3);
+        assert_eq!(stats.errors.get("SetTile"), Some(&1));
+    }
+}